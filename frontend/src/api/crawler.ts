@@ -1,5 +1,25 @@
 import { invoke } from '@tauri-apps/api/core';
-import { CrawlRequest, CrawlStatus, WebCrawlerConfig, CrawlerFormConfig } from '../types/crawler';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import {
+  CrawlRequest,
+  CrawlStatus,
+  WebCrawlerConfig,
+  CrawlerFormConfig,
+  QueueSnapshot,
+  ConfigPresetInfo,
+  ConfigProfileInfo,
+  ExportFormat,
+  ExportSummary,
+  ConvertSummary,
+  ExportProgressEvent,
+  ResultFilter,
+  ResultPage,
+  CrawlEvent,
+  LogLevel,
+  LogLine,
+  ValidationReport,
+  PolicyDecision,
+} from '../types/crawler';
 
 // Debug function to test Tauri environment
 export const debugTauriEnvironment = () => {
@@ -156,11 +176,61 @@ export class CrawlerAPI {
     }
   }
 
-  // Validate crawler configuration
-  static async validateConfig(request: CrawlRequest): Promise<string> {
+  // List available configuration presets
+  static async listConfigPresets(): Promise<ConfigPresetInfo[]> {
+    try {
+      return await safeInvoke<ConfigPresetInfo[]>('list_config_presets');
+    } catch (error) {
+      console.error('Failed to list config presets:', error);
+      throw new Error(`Failed to list config presets: ${error}`);
+    }
+  }
+
+  // Get configuration for a named preset
+  static async getConfigPreset(name: string): Promise<WebCrawlerConfig> {
+    try {
+      return await safeInvoke<WebCrawlerConfig>('get_config_preset', { name });
+    } catch (error) {
+      console.error('Failed to get config preset:', error);
+      throw new Error(`Failed to get config preset: ${error}`);
+    }
+  }
+
+  // Save the form configuration as a named profile
+  static async saveConfigProfile(name: string, config: CrawlRequest): Promise<ConfigProfileInfo> {
+    try {
+      return await safeInvoke<ConfigProfileInfo>('save_config_profile', { name, config });
+    } catch (error) {
+      console.error('Failed to save config profile:', error);
+      throw new Error(`Failed to save config profile: ${error}`);
+    }
+  }
+
+  // List saved configuration profiles
+  static async listConfigProfiles(): Promise<ConfigProfileInfo[]> {
+    try {
+      return await safeInvoke<ConfigProfileInfo[]>('list_config_profiles');
+    } catch (error) {
+      console.error('Failed to list config profiles:', error);
+      throw new Error(`Failed to list config profiles: ${error}`);
+    }
+  }
+
+  // Load a saved configuration profile
+  static async loadConfigProfile(name: string): Promise<CrawlRequest> {
+    try {
+      return await safeInvoke<CrawlRequest>('load_config_profile', { name });
+    } catch (error) {
+      console.error('Failed to load config profile:', error);
+      throw new Error(`Failed to load config profile: ${error}`);
+    }
+  }
+
+  // Validate crawler configuration; the report lists every invalid field
+  static async validateConfig(request: CrawlRequest): Promise<ValidationReport> {
     try {
       console.log('🔍 Calling Tauri invoke: validate_config with request:', request);
-      const result = await safeInvoke<string>('validate_config', { request });
+      const result = await safeInvoke<ValidationReport>('validate_config', { request });
       console.log('✅ validate_config returned:', result);
       return result;
     } catch (error) {
@@ -169,6 +239,16 @@ export class CrawlerAPI {
     }
   }
 
+  // Explain whether a crawl with this configuration would crawl a URL
+  static async evaluateUrl(request: CrawlRequest, url: string): Promise<PolicyDecision> {
+    try {
+      return await safeInvoke<PolicyDecision>('evaluate_url', { request, url });
+    } catch (error) {
+      console.error('Failed to evaluate URL:', error);
+      throw new Error(`Failed to evaluate URL: ${error}`);
+    }
+  }
+
   // Initialize a new crawl session
   static async startCrawl(request: CrawlRequest): Promise<string> {
     try {
@@ -195,6 +275,16 @@ export class CrawlerAPI {
     }
   }
 
+  // Get queue snapshot (per-domain counts and next tasks to be dequeued)
+  static async getQueueSnapshot(sessionId: string, nextN?: number): Promise<QueueSnapshot> {
+    try {
+      return await safeInvoke<QueueSnapshot>('get_queue_snapshot', { sessionId, nextN });
+    } catch (error) {
+      console.error('Failed to get queue snapshot:', error);
+      throw new Error(`Failed to get queue snapshot: ${error}`);
+    }
+  }
+
   // Stop crawl session
   static async stopCrawl(sessionId: string): Promise<string> {
     try {
@@ -205,10 +295,95 @@ export class CrawlerAPI {
     }
   }
 
+  // Export stored session results to a file
+  static async exportResults(
+    sessionId: string,
+    format: ExportFormat,
+    path: string
+  ): Promise<ExportSummary> {
+    try {
+      return await safeInvoke<ExportSummary>('export_results', { sessionId, format, path });
+    } catch (error) {
+      console.error('Failed to export results:', error);
+      throw new Error(`Failed to export results: ${error}`);
+    }
+  }
+
+  // Convert stored results between formats; the output is written next to the source
+  static async convertResults(
+    sessionDir: string,
+    from: ExportFormat,
+    to: ExportFormat
+  ): Promise<ConvertSummary> {
+    try {
+      return await safeInvoke<ConvertSummary>('convert_results', { sessionDir, from, to });
+    } catch (error) {
+      console.error('Failed to convert results:', error);
+      throw new Error(`Failed to convert results: ${error}`);
+    }
+  }
+
+  // Subscribe to export progress events
+  static async onExportProgress(
+    handler: (progress: ExportProgressEvent) => void
+  ): Promise<UnlistenFn> {
+    return listen<ExportProgressEvent>('export-progress', (event) => handler(event.payload));
+  }
+
+  // Read one page of a stored session's results
+  static async getSessionResults(
+    sessionId: string,
+    offset: number,
+    limit: number,
+    filter?: ResultFilter
+  ): Promise<ResultPage> {
+    try {
+      return await safeInvoke<ResultPage>('get_session_results', {
+        sessionId,
+        offset,
+        limit,
+        filter,
+      });
+    } catch (error) {
+      console.error('Failed to load session results:', error);
+      throw new Error(`Failed to load session results: ${error}`);
+    }
+  }
+
+  // Reload the event timeline recorded for a session, oldest event first
+  static async getSessionEvents(sessionId: string): Promise<CrawlEvent[]> {
+    try {
+      return await safeInvoke<CrawlEvent[]>('get_session_events', { sessionId });
+    } catch (error) {
+      console.error('Failed to load session events:', error);
+      throw new Error(`Failed to load session events: ${error}`);
+    }
+  }
+
+  // Recent backend log lines at the given level or more severe, oldest first
+  static async getRecentLogs(level?: LogLevel, limit?: number): Promise<LogLine[]> {
+    try {
+      return await safeInvoke<LogLine[]>('get_recent_logs', { level, limit });
+    } catch (error) {
+      console.error('Failed to load logs:', error);
+      throw new Error(`Failed to load logs: ${error}`);
+    }
+  }
+
+  // Empty the backend log buffer
+  static async clearLogs(): Promise<void> {
+    try {
+      await safeInvoke<void>('clear_logs');
+    } catch (error) {
+      console.error('Failed to clear logs:', error);
+      throw new Error(`Failed to clear logs: ${error}`);
+    }
+  }
+
   // Convenience method to start crawl session
   static async startAndExecuteCrawl(formConfig: CrawlerFormConfig): Promise<{
     sessionId: string;
-    validationResult: string;
+    validationResult: ValidationReport;
     startResult: string;
   }> {
     console.log('🔄 CrawlerAPI.startAndExecuteCrawl called with formConfig:', formConfig);
@@ -221,6 +396,12 @@ export class CrawlerAPI {
       console.log('🔍 Validating configuration...');
       const validationResult = await this.validateConfig(request);
       console.log('✅ Configuration validation result:', validationResult);
+      if (!validationResult.valid) {
+        const messages = validationResult.errors.map((error) =>
+          error.field ? `${error.field}: ${error.message}` : error.message
+        );
+        throw new Error(`Invalid configuration: ${messages.join('; ')}`);
+      }
 
       // Start the crawl session
       console.log('🚀 Starting crawl session...');
@@ -13,6 +13,23 @@ export interface CrawlRequest {
   enable_language_filtering: boolean;
   latin_word_filter: boolean;
   match_strategy: 'any' | 'all';
+  proxy_pool?: string[];
+  requests_per_second?: number;
+  burst?: number;
+  retry_base_delay_ms?: number;
+  retry_max_delay_ms?: number;
+}
+
+export interface ValidationError {
+  message: string;
+  // Path of the offending field, e.g. 'proxy_pool[1]'
+  field?: string;
+  suggested_range?: string;
+}
+
+export interface ValidationReport {
+  valid: boolean;
+  errors: ValidationError[];
 }
 
 export interface CrawlStatus {
@@ -26,6 +43,193 @@ export interface CrawlStatus {
   results: CrawlResultSummary[];
 }
 
+export interface ConfigPresetInfo {
+  name: string;
+  description: string;
+}
+
+export interface ConfigProfileInfo {
+  name: string;
+  saved_at: { secs_since_epoch: number; nanos_since_epoch: number };
+}
+
+export interface DomainQueueSnapshot {
+  domain: string;
+  pending: number;
+  in_progress: number;
+  retrying: number;
+  dead: number;
+}
+
+export interface QueuedTaskSummary {
+  id: string;
+  url: string;
+  priority: 'Low' | 'Normal' | 'Medium' | 'High' | 'Critical';
+  status: 'Pending' | 'InProgress' | 'Completed' | 'Failed' | 'Retrying' | 'Dead';
+  attempt_count: number;
+  depth: number;
+  error_message?: string;
+}
+
+export interface QueueSnapshot {
+  domains: DomainQueueSnapshot[];
+  next_tasks: QueuedTaskSummary[];
+  waiting_retries: number;
+  stats: Record<string, number>;
+  timestamp: { secs_since_epoch: number; nanos_since_epoch: number };
+}
+
+export type ExportFormat = 'json' | 'jsonl' | 'csv' | 'parquet';
+
+export interface ExportSummary {
+  path: string;
+  format: 'Json' | 'Jsonl' | 'Csv' | 'Parquet';
+  records: number;
+}
+
+export interface ConvertSummary {
+  path: string;
+  from: ExportSummary['format'];
+  to: ExportSummary['format'];
+  files_read: number;
+  records: number;
+  skipped: number;
+}
+
+export interface ExportProgressEvent {
+  session_id: string;
+  exported: number;
+  total: number;
+}
+
+export type PolicyCheck =
+  | 'Scheme'
+  | 'Scope'
+  | 'Extension'
+  | 'LinkFilter'
+  | 'Depth'
+  | 'Budget'
+  | 'Robots';
+
+export interface CheckOutcome {
+  check: PolicyCheck;
+  passed: boolean;
+  reason: string;
+}
+
+export interface PolicyDecision {
+  url: string;
+  checks: CheckOutcome[];
+}
+
+export interface ResultFilter {
+  domain?: string;
+  language?: string;
+  success?: boolean;
+}
+
+export interface ExtractedTable {
+  caption?: string;
+  headers: string[];
+  rows: string[][];
+}
+
+export interface ExtractedList {
+  ordered: boolean;
+  items: string[];
+}
+
+export interface StructuredContent {
+  tables: ExtractedTable[];
+  lists: ExtractedList[];
+}
+
+export interface ExtractedKeyword {
+  term: string;
+  score: number;
+}
+
+export interface PaginationChain {
+  id: string;
+  page: number;
+}
+
+export interface ScreenshotRef {
+  hash: string;
+  format: 'png' | 'jpeg';
+  width: number;
+  height: number;
+}
+
+export interface StoredCrawlResult {
+  schema_version: number;
+  url: string;
+  title?: string;
+  content?: string;
+  word_count: number;
+  language?: string;
+  declared_language?: string;
+  detected_language?: string;
+  raw_html_hash?: string;
+  screenshot?: ScreenshotRef;
+  sanitized_html?: string;
+  pii_scrubbed?: Record<string, number>;
+  keywords?: ExtractedKeyword[];
+  links_found: string[];
+  relevance_score?: number;
+  structured?: StructuredContent;
+  metadata: {
+    status_code?: number;
+    content_type?: string;
+    content_encoding?: string;
+    response_time_ms: number;
+    depth: number;
+    parent_url?: string;
+    crawl_session_id: string;
+    redirect_chain: string[];
+    custom?: Record<string, string>;
+    pagination?: PaginationChain;
+    sniffed_type?: string;
+  };
+  timestamp: { secs_since_epoch: number; nanos_since_epoch: number };
+}
+
+export type CrawlEventType =
+  | 'Started'
+  | 'Completed'
+  | 'Failed'
+  | 'Retrying'
+  | 'Blocked'
+  | 'RobotsOverridden'
+  | 'RateLimited'
+  | 'Cached'
+  | 'Redirected'
+  | 'Timeout'
+  | 'Skipped';
+
+export interface CrawlEvent {
+  url: string;
+  event_type: CrawlEventType;
+  timestamp: { secs_since_epoch: number; nanos_since_epoch: number };
+  duration_ms?: number;
+  status_code?: number;
+  content_length?: number;
+  word_count?: number;
+  language?: string;
+  depth?: number;
+  retry_count?: number;
+  user_agent?: string;
+  proxy_used?: string;
+  error_message?: string;
+  session_id?: string;
+}
+
+export interface ResultPage {
+  results: StoredCrawlResult[];
+  offset: number;
+  next_offset?: number;
+}
+
 export interface CrawlResultSummary {
   url: string;
   title?: string;
@@ -60,3 +264,12 @@ export interface CrawlerFormConfig {
   latinWordFilter: boolean;
   matchStrategy: 'any' | 'all';
 }
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export interface LogLine {
+  timestamp_ms: number;
+  level: string;
+  target: string;
+  message: string;
+}
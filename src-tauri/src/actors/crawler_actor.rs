@@ -5,17 +5,28 @@
 // environment and the WebCrawler's non-Send types (ThreadRng).
 //
 // Architecture:
-// - CrawlerActor: Owns the WebCrawler in a dedicated thread
+// - CrawlerActor: Owns the running crawl sessions in a dedicated thread
 // - CrawlerBridge: Send-safe interface for Tauri commands
 // - Message Queue: Communication channel between bridge and actor
+//
+// Crawls run as local tasks next to the message loop, so status and queue
+// snapshots are answered while a crawl is in progress.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
+use std::time::SystemTime;
 use tokio::sync::{mpsc, oneshot};
+use tokio::task::LocalSet;
 
 use crate::core::{CrawlRequest, CrawlResultSummary, CrawlStatus};
-use rust_web_crawler::config::WebCrawlerConfig;
-use rust_web_crawler::crawler::WebCrawler;
+use rust_web_crawler::logging::session_events_path;
+use rust_web_crawler::queue::QueueSnapshot;
+use rust_web_crawler::storage::{
+    CrawlMetadata, DataStorage, OutputFormat, StoredCrawlResult, SCHEMA_VERSION,
+};
+use rust_web_crawler::{CrawlResultData, CrawlSession, CrawlSessionConfig, SessionResult};
 
 /// Messages sent to the crawler actor
 #[derive(Debug)]
@@ -31,11 +42,24 @@ pub enum ActorMessage {
         session_id: String,
         response: oneshot::Sender<Option<CrawlStatus>>,
     },
+    /// Get a queue snapshot of a crawl session
+    GetQueueSnapshot {
+        session_id: String,
+        next_n: usize,
+        response: oneshot::Sender<Option<QueueSnapshot>>,
+    },
     /// Stop a crawl session
     StopCrawl {
         session_id: String,
         response: oneshot::Sender<Result<String, String>>,
     },
+    /// A crawl started by `StartCrawl` ended; sent by the actor to itself
+    CrawlFinished {
+        session_id: String,
+        request: CrawlRequest,
+        result: Result<SessionResult, String>,
+        response: oneshot::Sender<Result<String, String>>,
+    },
     /// Shutdown the actor
     Shutdown,
 }
@@ -47,13 +71,15 @@ pub struct CrawlerBridge {
 }
 
 impl CrawlerBridge {
-    /// Create a new crawler bridge and spawn the actor thread
-    pub fn new() -> Self {
+    /// Create a new crawler bridge and spawn the actor thread; crawled pages are
+    /// stored in `results_dir`
+    pub fn new(results_dir: PathBuf) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
 
         // Spawn the actor in a dedicated thread (not tokio::spawn)
+        let actor_sender = sender.clone();
         thread::spawn(move || {
-            let actor = CrawlerActor::new(receiver);
+            let actor = CrawlerActor::new(receiver, actor_sender, results_dir);
             actor.run();
         });
 
@@ -94,6 +120,27 @@ impl CrawlerBridge {
             .map_err(|_| "Actor response failed".to_string())
     }
 
+    /// Get queue snapshot of a crawl session (async, Send-safe)
+    pub async fn get_queue_snapshot(
+        &self,
+        session_id: String,
+        next_n: usize,
+    ) -> Result<Option<QueueSnapshot>, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.sender
+            .send(ActorMessage::GetQueueSnapshot {
+                session_id,
+                next_n,
+                response: response_tx,
+            })
+            .map_err(|_| "Actor is not running".to_string())?;
+
+        response_rx
+            .await
+            .map_err(|_| "Actor response failed".to_string())
+    }
+
     /// Stop a crawl session (async, Send-safe)
     pub async fn stop_crawl(&self, session_id: String) -> Result<String, String> {
         let (response_tx, response_rx) = oneshot::channel();
@@ -114,15 +161,26 @@ impl CrawlerBridge {
 /// The actual crawler actor that owns non-Send types
 struct CrawlerActor {
     receiver: mpsc::UnboundedReceiver<ActorMessage>,
+    /// Sender for reporting finished crawls back to the message loop
+    sender: mpsc::UnboundedSender<ActorMessage>,
     sessions: HashMap<String, CrawlStatus>,
-    // Note: We'll store crawlers here when we support multiple concurrent sessions
+    /// Sessions whose crawl is still running, for queue snapshots
+    live: HashMap<String, Arc<CrawlSession>>,
+    results_dir: PathBuf,
 }
 
 impl CrawlerActor {
-    fn new(receiver: mpsc::UnboundedReceiver<ActorMessage>) -> Self {
+    fn new(
+        receiver: mpsc::UnboundedReceiver<ActorMessage>,
+        sender: mpsc::UnboundedSender<ActorMessage>,
+        results_dir: PathBuf,
+    ) -> Self {
         Self {
             receiver,
+            sender,
             sessions: HashMap::new(),
+            live: HashMap::new(),
+            results_dir,
         }
     }
 
@@ -132,8 +190,9 @@ impl CrawlerActor {
 
         // Create a simple tokio runtime for this thread
         let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+        let local = LocalSet::new();
 
-        rt.block_on(async {
+        local.block_on(&rt, async {
             while let Some(message) = self.receiver.recv().await {
                 match message {
                     ActorMessage::StartCrawl {
@@ -141,8 +200,7 @@ impl CrawlerActor {
                         request,
                         response,
                     } => {
-                        let result = self.handle_start_crawl(session_id, request).await;
-                        let _ = response.send(result);
+                        self.handle_start_crawl(session_id, request, response).await;
                     }
                     ActorMessage::GetStatus {
                         session_id,
@@ -151,6 +209,22 @@ impl CrawlerActor {
                         let status = self.sessions.get(&session_id).cloned();
                         let _ = response.send(status);
                     }
+                    ActorMessage::GetQueueSnapshot {
+                        session_id,
+                        next_n,
+                        response,
+                    } => {
+                        // Answer from a local task so a busy queue lock does not
+                        // hold up the message loop
+                        let session = self.live.get(&session_id).cloned();
+                        tokio::task::spawn_local(async move {
+                            let snapshot = match session {
+                                Some(session) => Some(session.queue_snapshot(next_n).await),
+                                None => None,
+                            };
+                            let _ = response.send(snapshot);
+                        });
+                    }
                     ActorMessage::StopCrawl {
                         session_id,
                         response,
@@ -158,6 +232,15 @@ impl CrawlerActor {
                         let result = self.handle_stop_crawl(session_id);
                         let _ = response.send(result);
                     }
+                    ActorMessage::CrawlFinished {
+                        session_id,
+                        request,
+                        result,
+                        response,
+                    } => {
+                        let result = self.handle_crawl_finished(&session_id, &request, result);
+                        let _ = response.send(result);
+                    }
                     ActorMessage::Shutdown => {
                         println!("🎭 CrawlerActor shutting down");
                         break;
@@ -168,11 +251,15 @@ impl CrawlerActor {
     }
 
     /// Handle start crawl request
+    ///
+    /// The crawl runs as a local task; `response` is answered by
+    /// `handle_crawl_finished` once it ends.
     async fn handle_start_crawl(
         &mut self,
         session_id: String,
         request: CrawlRequest,
-    ) -> Result<String, String> {
+        response: oneshot::Sender<Result<String, String>>,
+    ) {
         println!("🎭 Actor starting crawl for session: {}", session_id);
 
         // Initialize session status
@@ -187,71 +274,182 @@ impl CrawlerActor {
             Err(e) => {
                 let error_msg = format!("Invalid URL: {}", e);
                 self.set_session_error(&session_id, error_msg.clone());
-                return Err(error_msg);
+                let _ = response.send(Err(error_msg));
+                return;
             }
         };
 
-        // Create crawler config
-        let config = WebCrawlerConfig {
-            base_url: vec![request.base_url.clone()],
-            max_crawl_depth: request.max_crawl_depth as usize,
-            max_total_urls: request.max_total_urls as usize,
-            target_words: request.target_words,
-            enable_extension_crawling: request.enable_discovery_crawling,
-            enable_keyword_filtering: request.enable_keyword_filtering,
-            avoid_url_extensions: request.avoid_url_extensions,
-            user_agent: "Tauri WebCrawler".to_string(),
-            ..WebCrawlerConfig::default()
+        let config = CrawlSessionConfig {
+            crawler_config: request.crawler_config(),
+            max_depth: request.max_crawl_depth as usize,
+            // Pages are stored in the results directory as they are reported
+            enable_storage: false,
+            storage_path: None,
+            // The event timeline is kept there too, for `get_session_events`
+            event_timeline: Some(session_events_path(&self.results_dir, &session_id)),
+            ..CrawlSessionConfig::default()
+        };
+        let session = match CrawlSession::new(config).await {
+            Ok(session) => Arc::new(session),
+            Err(e) => {
+                let error_msg = format!("Failed to create crawler: {}", e);
+                self.set_session_error(&session_id, error_msg.clone());
+                let _ = response.send(Err(error_msg));
+                return;
+            }
         };
+        self.live.insert(session_id.clone(), Arc::clone(&session));
 
-        // Create and run crawler (this can use non-Send types safely in this thread)
-        match WebCrawler::new(config, 5, 3) {
-            Ok(crawler) => {
-                match crawler.init_crawling(url).await {
-                    Ok(content_opt) => {
-                        println!("✅ Actor crawl completed for session: {}", session_id);
-
-                        // Check if we have content and create results
-                        let has_content = content_opt.is_some();
-                        let crawl_results = if let Some(content) = content_opt {
-                            vec![CrawlResultSummary {
-                                url: request.base_url.clone(),
-                                title: Some("Crawled Successfully".to_string()),
-                                word_count: content.len(),
-                                target_words_found: vec![], // TODO: Implement target word detection
-                                language: Some("unknown".to_string()),
-                                status_code: Some(200),
-                            }]
-                        } else {
-                            vec![]
-                        };
-
-                        // Update session to completed
-                        if let Some(status) = self.sessions.get_mut(&session_id) {
-                            status.status = "completed".to_string();
-                            status.total_urls_processed = 1;
-                            status.successful_crawls = if has_content { 1 } else { 0 };
-                            status.failed_crawls = if has_content { 0 } else { 1 };
-                            status.results = crawl_results;
-                        }
-
-                        Ok(format!("Crawl completed for session: {}", session_id))
-                    }
-                    Err(e) => {
-                        let error_msg = format!("Crawl failed: {}", e);
-                        self.set_session_error(&session_id, error_msg.clone());
-                        Err(error_msg)
-                    }
+        let sender = self.sender.clone();
+        let results_dir = self.results_dir.clone();
+        tokio::task::spawn_local(async move {
+            let result = session
+                .execute_crawl(vec![url])
+                .await
+                .map_err(|e| e.to_string());
+            if let Ok(session_result) = &result {
+                for page in &session_result.results {
+                    Self::store_page(&results_dir, &session_id, page).await;
                 }
             }
+            // The actor is gone only at shutdown, when nobody waits for the answer
+            let _ = sender.send(ActorMessage::CrawlFinished {
+                session_id,
+                request,
+                result,
+                response,
+            });
+        });
+    }
+
+    /// Record the outcome of a finished crawl and forget its session
+    fn handle_crawl_finished(
+        &mut self,
+        session_id: &str,
+        request: &CrawlRequest,
+        result: Result<SessionResult, String>,
+    ) -> Result<String, String> {
+        self.live.remove(session_id);
+
+        let session_result = match result {
+            Ok(session_result) => session_result,
             Err(e) => {
-                let error_msg = format!("Failed to create crawler: {}", e);
-                self.set_session_error(&session_id, error_msg.clone());
-                Err(error_msg)
+                let error_msg = format!("Crawl failed: {}", e);
+                self.set_session_error(session_id, error_msg.clone());
+                return Err(error_msg);
+            }
+        };
+        println!("✅ Actor crawl completed for session: {}", session_id);
+
+        let crawl_results = session_result
+            .results
+            .iter()
+            .filter_map(|page| {
+                let content = page.content.as_ref()?;
+                Some(CrawlResultSummary {
+                    url: page.url.to_string(),
+                    title: None,
+                    word_count: content.word_count,
+                    target_words_found: request
+                        .target_words
+                        .iter()
+                        .filter(|word| content.content.contains(word.as_str()))
+                        .cloned()
+                        .collect(),
+                    language: page
+                        .language
+                        .language
+                        .as_ref()
+                        .map(|language| language.to_http_code().to_string()),
+                    status_code: page.status_code,
+                })
+            })
+            .collect();
+
+        if let Some(status) = self.sessions.get_mut(session_id) {
+            // A stopped session keeps its status
+            if status.status == "running" {
+                status.status = "completed".to_string();
             }
+            status.current_url = None;
+            status.total_urls_processed = session_result.total_urls_processed;
+            status.successful_crawls = session_result.successful_crawls;
+            status.failed_crawls = session_result.failed_crawls;
+            status.results = crawl_results;
+        }
+
+        Ok(format!("Crawl completed for session: {}", session_id))
+    }
+
+    /// Store a crawled page in the results directory, logging failures
+    async fn store_page(results_dir: &Path, session_id: &str, page: &CrawlResultData) {
+        let Some(content) = &page.content else {
+            return;
+        };
+        if let Err(e) = Self::store_result(
+            results_dir,
+            session_id,
+            page.url.as_str(),
+            &content.content,
+            page.duration.as_millis() as u64,
+        )
+        .await
+        {
+            println!("⚠️ Failed to store result for {}: {}", session_id, e);
         }
     }
 
+    /// Append a crawled page to the results directory
+    async fn store_result(
+        results_dir: &Path,
+        session_id: &str,
+        url: &str,
+        content: &str,
+        response_time_ms: u64,
+    ) -> Result<(), String> {
+        let storage =
+            DataStorage::new(results_dir, OutputFormat::Jsonl).map_err(|e| e.to_string())?;
+        let result = StoredCrawlResult {
+            schema_version: SCHEMA_VERSION,
+            url: url.to_string(),
+            title: None,
+            content: Some(content.to_string()),
+            word_count: content.split_whitespace().count(),
+            language: None,
+            links_found: vec![],
+            relevance_score: None,
+            alternates: vec![],
+            structured: None,
+            declared_language: None,
+            detected_language: None,
+            raw_html_hash: None,
+            screenshot: None,
+            sanitized_html: None,
+            pii_scrubbed: Default::default(),
+            keywords: Vec::new(),
+            metadata: CrawlMetadata {
+                status_code: Some(200),
+                content_type: Some("text/html".to_string()),
+                content_length: Some(content.len() as u64),
+                content_encoding: None,
+                response_time_ms,
+                depth: 0,
+                parent_url: None,
+                crawl_session_id: session_id.to_string(),
+                user_agent: Some("Tauri WebCrawler".to_string()),
+                redirect_chain: vec![],
+                custom: Default::default(),
+                pagination: None,
+                sniffed_type: None,
+            },
+            timestamp: SystemTime::now(),
+        };
+        storage
+            .store_result(&result)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     /// Handle stop crawl request
     fn handle_stop_crawl(&mut self, session_id: String) -> Result<String, String> {
         if let Some(status) = self.sessions.get_mut(&session_id) {
@@ -270,3 +468,59 @@ impl CrawlerActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(session_id: &str, base_url: &str) -> CrawlRequest {
+        serde_json::from_value(serde_json::json!({
+            "session_id": session_id,
+            "base_url": base_url,
+            "max_total_urls": 1,
+            "max_crawl_depth": 0,
+            "enable_discovery_crawling": false,
+            "enable_keyword_filtering": false,
+            "target_words": [],
+            "enable_content_filtering": false,
+            "avoid_url_extensions": [],
+            "enable_language_filtering": false,
+            "latin_word_filter": false,
+            "match_strategy": "any",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_forgotten_when_their_crawl_ends() {
+        // No page is crawled, so nothing is stored
+        let bridge = CrawlerBridge::new(std::env::temp_dir().join("crawler_actor_test"));
+
+        let error = bridge
+            .start_crawl(request("invalid", "not a url"))
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Invalid URL"));
+        let status = bridge.get_status("invalid".to_string()).await.unwrap();
+        assert_eq!(status.unwrap().status, "error");
+
+        // The SSRF guard refuses the loopback seed, so the crawl ends without a page
+        bridge
+            .start_crawl(request("refused", "http://127.0.0.1:9/"))
+            .await
+            .unwrap();
+        let status = bridge
+            .get_status("refused".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.status, "completed");
+        assert_eq!(status.successful_crawls, 0);
+        assert!(status.results.is_empty());
+        assert!(bridge
+            .get_queue_snapshot("refused".to_string(), 5)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,8 +1,23 @@
-use rust_web_crawler::config::WebCrawlerConfig;
+use rust_web_crawler::config::{config_presets, WebCrawlerConfig};
+use rust_web_crawler::logging::CrawlEvent;
+use rust_web_crawler::queue::QueueSnapshot;
+use rust_web_crawler::session::{CrawlPolicy, Decision};
+use rust_web_crawler::storage::{
+    convert, ConvertSummary, DataStorage, ExportSummary, OutputFormat, ResultFilter, ResultPage,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tauri::{Emitter, Manager};
 
 use crate::actors::CrawlerBridge;
-use crate::core::{CrawlRequest, CrawlStatus};
-use crate::utils::validate_crawl_request;
+use crate::core::{
+    ConfigPresetInfo, ConfigProfile, ConfigProfileInfo, CrawlRequest, CrawlStatus,
+    ExportProgressEvent, LogLine, ResultsDir,
+};
+use crate::utils::log_buffer::LogBuffer;
+use crate::utils::profiles::{self, PROFILES_DIR};
+use crate::utils::{validate_crawl_request, validate_crawl_request_fields, ValidationReport};
 
 /// Get default crawler configuration
 #[tauri::command]
@@ -11,24 +26,122 @@ pub async fn get_default_config() -> Result<WebCrawlerConfig, String> {
     Ok(WebCrawlerConfig::default())
 }
 
+/// List available configuration presets
+#[tauri::command]
+pub async fn list_config_presets() -> Result<Vec<ConfigPresetInfo>, String> {
+    println!("🔧 list_config_presets called");
+    Ok(config_presets()
+        .iter()
+        .map(|preset| ConfigPresetInfo {
+            name: preset.name.to_string(),
+            description: preset.description.to_string(),
+        })
+        .collect())
+}
+
+/// Get the crawler configuration for a named preset
+#[tauri::command]
+pub async fn get_config_preset(name: String) -> Result<WebCrawlerConfig, String> {
+    println!("🔧 get_config_preset called for preset: {}", name);
+    WebCrawlerConfig::preset(&name).map_err(|e| e.to_string())
+}
+
 /// Validate crawler configuration
+///
+/// Returns every problem found, each with the path of the offending field, so the
+/// form can highlight all invalid inputs at once.
 #[tauri::command]
-pub async fn validate_config(request: CrawlRequest) -> Result<String, String> {
+pub async fn validate_config(request: CrawlRequest) -> Result<ValidationReport, String> {
     println!(
         "🔍 validate_config called for session: {}",
         request.session_id
     );
 
-    match validate_crawl_request(&request) {
-        Ok(()) => {
-            println!("✅ Configuration validation passed");
-            Ok("Configuration is valid".to_string())
-        }
-        Err(e) => {
-            println!("❌ Validation failed: {}", e);
-            Err(e.to_string())
-        }
+    let report = ValidationReport::new(validate_crawl_request_fields(&request));
+    if report.valid {
+        println!("✅ Configuration validation passed");
+    } else {
+        println!("❌ Validation failed: {}", report);
+    }
+    Ok(report)
+}
+
+/// Directory of the saved configuration profiles
+fn profiles_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PROFILES_DIR))
+        .map_err(|e| format!("App data directory unavailable: {}", e))
+}
+
+/// Save a configuration under `name`, replacing an existing profile of that name
+#[tauri::command]
+pub async fn save_config_profile(
+    app: tauri::AppHandle,
+    name: String,
+    config: CrawlRequest,
+) -> Result<ConfigProfileInfo, String> {
+    println!("💾 save_config_profile called for profile: {}", name);
+
+    let report = ValidationReport::new(validate_crawl_request_fields(&config));
+    if !report.valid {
+        println!("❌ Validation failed: {}", report);
+        return Err(report.to_string());
+    }
+
+    let profile = ConfigProfile {
+        name: name.trim().to_string(),
+        saved_at: SystemTime::now(),
+        request: config,
+    };
+    let path = profiles::save_profile(&profiles_dir(&app)?, &profile)?;
+    println!("✅ Profile saved to {}", path.display());
+
+    Ok(ConfigProfileInfo {
+        name: profile.name,
+        saved_at: profile.saved_at,
+    })
+}
+
+/// List the saved configuration profiles
+#[tauri::command]
+pub async fn list_config_profiles(app: tauri::AppHandle) -> Result<Vec<ConfigProfileInfo>, String> {
+    println!("🔧 list_config_profiles called");
+    profiles::list_profiles(&profiles_dir(&app)?)
+}
+
+/// Load a saved configuration profile
+///
+/// The configuration is validated again, since profile files can be edited by hand.
+#[tauri::command]
+pub async fn load_config_profile(
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<CrawlRequest, String> {
+    println!("📂 load_config_profile called for profile: {}", name);
+
+    let profile = profiles::load_profile(&profiles_dir(&app)?, &name)?;
+    let report = ValidationReport::new(validate_crawl_request_fields(&profile.request));
+    if !report.valid {
+        println!("❌ Saved profile '{}' is invalid: {}", name, report);
+        return Err(format!("Saved profile '{}' is invalid: {}", name, report));
     }
+    Ok(profile.request)
+}
+
+/// Explain whether a crawl with this configuration would crawl `url`, without fetching it
+#[tauri::command]
+pub async fn evaluate_url(request: CrawlRequest, url: String) -> Result<Decision, String> {
+    println!("🧪 evaluate_url called for: {}", url);
+
+    let url = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let policy = CrawlPolicy::new(&request.crawler_config()).map_err(|e| e.to_string())?;
+    let decision = policy.evaluate(&url).await;
+    match decision.skipped_by() {
+        None => println!("✅ {} would be crawled", url),
+        Some(outcome) => println!("⛔ {} would be skipped: {}", url, outcome.reason),
+    }
+    Ok(decision)
 }
 
 /// Initialize a new crawl session
@@ -75,6 +188,31 @@ pub async fn get_crawl_status(
     }
 }
 
+/// Inspect the task queue of a crawl session
+#[tauri::command]
+pub async fn get_queue_snapshot(
+    session_id: String,
+    next_n: Option<usize>,
+    bridge: tauri::State<'_, CrawlerBridge>,
+) -> Result<QueueSnapshot, String> {
+    println!("🔎 get_queue_snapshot called for session: {}", session_id);
+
+    match bridge
+        .get_queue_snapshot(session_id.clone(), next_n.unwrap_or(10))
+        .await
+    {
+        Ok(Some(snapshot)) => Ok(snapshot),
+        Ok(None) => Err(format!("Session {} not found", session_id)),
+        Err(e) => {
+            println!(
+                "❌ Error getting queue snapshot for session {}: {}",
+                session_id, e
+            );
+            Err(e)
+        }
+    }
+}
+
 /// Stop and clean up a crawl session
 #[tauri::command]
 pub async fn stop_crawl(
@@ -85,3 +223,286 @@ pub async fn stop_crawl(
 
     bridge.stop_crawl(session_id).await
 }
+
+/// Export the stored results of a session to a CSV, JSON or JSONL file
+///
+/// Emits `export-progress` events while records are written.
+#[tauri::command]
+pub async fn export_results(
+    app: tauri::AppHandle,
+    session_id: String,
+    format: String,
+    path: String,
+    results_dir: tauri::State<'_, ResultsDir>,
+) -> Result<ExportSummary, String> {
+    println!(
+        "📦 export_results called for session: {} ({} -> {})",
+        session_id, format, path
+    );
+
+    let format: OutputFormat = format.parse().map_err(|e| e.to_string())?;
+    let storage = DataStorage::new(&results_dir.0, format).map_err(|e| e.to_string())?;
+
+    let event_session_id = session_id.clone();
+    let result = storage
+        .export_results(
+            &session_id,
+            format,
+            &PathBuf::from(&path),
+            move |progress| {
+                let _ = app.emit(
+                    "export-progress",
+                    ExportProgressEvent {
+                        session_id: event_session_id.clone(),
+                        exported: progress.exported,
+                        total: progress.total,
+                    },
+                );
+            },
+        )
+        .await;
+
+    match result {
+        Ok(summary) => {
+            println!("✅ Exported {} records to {}", summary.records, path);
+            Ok(summary)
+        }
+        Err(e) => {
+            println!("❌ Export failed for session {}: {}", session_id, e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Convert the stored results in a session directory (or file) to another format
+///
+/// The converted file is written next to the source, e.g. `session_1` -> `session_1.csv`.
+#[tauri::command]
+pub async fn convert_results(
+    session_dir: String,
+    from: String,
+    to: String,
+) -> Result<ConvertSummary, String> {
+    println!(
+        "🔁 convert_results called for {} ({} -> {})",
+        session_dir, from, to
+    );
+
+    let from: OutputFormat = from.parse().map_err(|e| e.to_string())?;
+    let to: OutputFormat = to.parse().map_err(|e| e.to_string())?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        convert(&PathBuf::from(session_dir), from, to)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match result {
+        Ok(summary) => {
+            println!(
+                "✅ Converted {} records to {}",
+                summary.records,
+                summary.path.display()
+            );
+            Ok(summary)
+        }
+        Err(e) => {
+            println!("❌ Conversion failed: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Read one page of a stored session's results
+///
+/// Only the requested page is loaded, so large sessions can be browsed page by page.
+#[tauri::command]
+pub async fn get_session_results(
+    session_id: String,
+    offset: usize,
+    limit: usize,
+    filter: Option<ResultFilter>,
+    results_dir: tauri::State<'_, ResultsDir>,
+) -> Result<ResultPage, String> {
+    println!(
+        "📄 get_session_results called for session: {} (offset {}, limit {})",
+        session_id, offset, limit
+    );
+
+    if limit == 0 {
+        return Err("limit must be at least 1".to_string());
+    }
+
+    let storage =
+        DataStorage::new(&results_dir.0, OutputFormat::Jsonl).map_err(|e| e.to_string())?;
+    storage
+        .read_session_page(&session_id, filter.unwrap_or_default(), offset, limit)
+        .await
+        .map_err(|e| {
+            println!("❌ Failed to read session {}: {}", session_id, e);
+            e.to_string()
+        })
+}
+
+/// Reload the event timeline recorded for a session, oldest event first
+#[tauri::command]
+pub async fn get_session_events(
+    session_id: String,
+    results_dir: tauri::State<'_, ResultsDir>,
+) -> Result<Vec<CrawlEvent>, String> {
+    println!("🕒 get_session_events called for session: {}", session_id);
+
+    let storage =
+        DataStorage::new(&results_dir.0, OutputFormat::Jsonl).map_err(|e| e.to_string())?;
+    storage.load_session_events(&session_id).map_err(|e| {
+        println!("❌ Failed to load events of session {}: {}", session_id, e);
+        e.to_string()
+    })
+}
+
+/// Recent backend log lines at `level` or more severe, oldest first
+///
+/// `level` defaults to "info" and `limit` to 200 lines. A level more verbose than
+/// the log filter lets through (`RUST_LOG`) is an error rather than an empty list.
+#[tauri::command]
+pub async fn get_recent_logs(
+    level: Option<String>,
+    limit: Option<usize>,
+    logs: tauri::State<'_, Arc<LogBuffer>>,
+) -> Result<Vec<LogLine>, String> {
+    let level = match level {
+        Some(level) => level
+            .trim()
+            .parse::<tracing::Level>()
+            .map_err(|_| format!("Unknown log level '{}'", level))?,
+        None => tracing::Level::INFO,
+    };
+    let captured = tracing::level_filters::LevelFilter::current();
+    if level > captured {
+        return Err(format!(
+            "{} lines are not captured (the log filter keeps {} and above); set RUST_LOG to capture them",
+            level, captured
+        ));
+    }
+    Ok(logs.recent(level, limit.unwrap_or(200)))
+}
+
+/// Empty the log viewer buffer
+#[tauri::command]
+pub async fn clear_logs(logs: tauri::State<'_, Arc<LogBuffer>>) -> Result<(), String> {
+    println!("🧹 clear_logs called");
+    logs.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> CrawlRequest {
+        serde_json::from_value(serde_json::json!({
+            "session_id": "validation",
+            "base_url": "https://example.com/",
+            "max_total_urls": 10,
+            "max_crawl_depth": 2,
+            "enable_discovery_crawling": false,
+            "enable_keyword_filtering": false,
+            "target_words": [],
+            "enable_content_filtering": false,
+            "avoid_url_extensions": [".pdf"],
+            "enable_language_filtering": false,
+            "latin_word_filter": false,
+            "match_strategy": "any",
+        }))
+        .unwrap()
+    }
+
+    /// Fields and suggested ranges of the report's errors, in order
+    async fn invalid_fields(request: CrawlRequest) -> Vec<(String, Option<String>)> {
+        let report = validate_config(request).await.unwrap();
+        assert_eq!(report.valid, report.errors.is_empty());
+        report
+            .errors
+            .into_iter()
+            .map(|error| (error.field.unwrap(), error.suggested_range))
+            .collect()
+    }
+
+    fn field(name: &str, range: Option<&str>) -> (String, Option<String>) {
+        (name.to_string(), range.map(str::to_string))
+    }
+
+    #[tokio::test]
+    async fn test_every_invalid_field_is_reported() {
+        assert!(invalid_fields(request()).await.is_empty());
+
+        let every_field = CrawlRequest {
+            base_url: "not a url".to_string(),
+            max_total_urls: 0,
+            max_crawl_depth: 0,
+            avoid_url_extensions: vec![".pdf".to_string(), String::new()],
+            proxy_pool: vec![
+                "http://proxy.example:8080".to_string(),
+                "not a proxy".to_string(),
+            ],
+            match_strategy: "most".to_string(),
+            requests_per_second: Some(0),
+            retry_base_delay_ms: Some(0),
+            ..request()
+        };
+        assert_eq!(
+            invalid_fields(every_field).await,
+            vec![
+                field("base_url", Some("an absolute http(s) URL")),
+                field("max_total_urls", Some(">= 1")),
+                field("max_crawl_depth", Some(">= 1")),
+                field("avoid_url_extensions[1]", None),
+                field("proxy_pool[1]", None),
+                field("match_strategy", Some("'any' or 'all'")),
+                field("requests_per_second", Some(">= 1")),
+                field("retry_base_delay_ms", Some(">= 1")),
+            ]
+        );
+
+        let metadata_address = CrawlRequest {
+            base_url: "http://169.254.169.254/latest/meta-data/".to_string(),
+            ..request()
+        };
+        assert_eq!(
+            invalid_fields(metadata_address).await,
+            vec![field("base_url", Some("a URL on a public host"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cross_field_checks_blame_the_field_the_user_set() {
+        // A burst below the rate, and a maximum delay below the base delay
+        let below = CrawlRequest {
+            requests_per_second: Some(5),
+            burst: Some(2),
+            retry_base_delay_ms: Some(5_000),
+            retry_max_delay_ms: Some(1_000),
+            ..request()
+        };
+        assert_eq!(
+            invalid_fields(below).await,
+            vec![
+                field("burst", Some(">= 5")),
+                field("retry_max_delay_ms", Some("> 5000")),
+            ]
+        );
+
+        // A burst without a rate, and a base delay above the default maximum
+        let unset = CrawlRequest {
+            burst: Some(3),
+            retry_base_delay_ms: Some(60_000),
+            ..request()
+        };
+        assert_eq!(
+            invalid_fields(unset).await,
+            vec![
+                field("burst", None),
+                field("retry_base_delay_ms", Some("< 30000")),
+            ]
+        );
+    }
+}
@@ -19,7 +19,17 @@ pub mod utils;
 // Re-exports for convenience
 use crate::actors::CrawlerBridge;
 use crate::api::*;
+use crate::core::ResultsDir;
+use crate::utils::log_buffer::{LogBuffer, LogBufferLayer, LOG_BUFFER_CAPACITY};
 use log::LevelFilter;
+use std::sync::Arc;
+use tauri::Manager;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::EnvFilter;
+
+/// Log filter when `RUST_LOG` is unset: debug for the crawler and this app,
+/// info for the HTTP stack and everything else
+const DEFAULT_LOG_FILTER: &str = "info,rust_web_crawler=debug,app_lib=debug";
 
 /// Application metadata
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -31,19 +41,50 @@ pub fn run() {
     // Initialize the application
     println!("🚀 Starting {} v{}", NAME, VERSION);
 
+    // Keep recent crawler tracing events for the log viewer
+    let log_buffer = Arc::new(LogBuffer::new(LOG_BUFFER_CAPACITY));
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(LogBufferLayer::new(log_buffer.clone()));
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        println!("⚠️ Tracing subscriber already set; log viewer disabled");
+    }
+
     tauri::Builder::default()
-        // Register crawler bridge
-        .manage(CrawlerBridge::new())
+        // Register the log viewer buffer
+        .manage(log_buffer)
         // Register Tauri commands (API endpoints)
         .invoke_handler(tauri::generate_handler![
             get_default_config,
+            list_config_presets,
+            get_config_preset,
             validate_config,
+            save_config_profile,
+            list_config_profiles,
+            load_config_profile,
+            evaluate_url,
             start_crawl,
             get_crawl_status,
+            get_queue_snapshot,
             stop_crawl,
+            export_results,
+            convert_results,
+            get_session_results,
+            get_session_events,
+            get_recent_logs,
+            clear_logs,
         ])
         // Setup application
         .setup(|app| {
+            // Crawl results live in the app data directory, whatever the working directory
+            let results_dir = app.path().app_data_dir()?.join("crawl_data");
+            println!("📁 Storing crawl results in {}", results_dir.display());
+            app.manage(CrawlerBridge::new(results_dir.clone()));
+            app.manage(ResultsDir(results_dir));
+
             // Initialize logging in debug mode
             if cfg!(debug_assertions) {
                 app.handle().plugin(
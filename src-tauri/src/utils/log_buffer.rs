@@ -0,0 +1,166 @@
+// =============================================================================
+// LOG BUFFER - Recent Backend Log Lines
+// =============================================================================
+// This module keeps the most recent tracing events in a bounded in-memory ring
+// buffer, so the desktop UI can show live logs without reading log files.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use crate::core::LogLine;
+
+/// Log lines kept before the oldest are dropped
+pub const LOG_BUFFER_CAPACITY: usize = 2000;
+
+/// Bounded buffer of the most recent log lines
+pub struct LogBuffer {
+    lines: Mutex<VecDeque<(Level, LogLine)>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Append a line, dropping the oldest one when the buffer is full
+    pub fn push(&self, level: Level, line: LogLine) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() >= self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back((level, line));
+    }
+
+    /// The last `limit` lines at `min_level` or more severe, oldest first
+    pub fn recent(&self, min_level: Level, limit: usize) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        // Levels compare by verbosity: ERROR is the smallest
+        let mut recent: Vec<LogLine> = lines
+            .iter()
+            .rev()
+            .filter(|(level, _)| *level <= min_level)
+            .take(limit)
+            .map(|(_, line)| line.clone())
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    pub fn clear(&self) {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Tracing layer copying every event into a `LogBuffer`
+pub struct LogBufferLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl LogBufferLayer {
+    pub fn new(buffer: Arc<LogBuffer>) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.buffer.push(
+            *metadata.level(),
+            LogLine {
+                timestamp_ms,
+                level: metadata.level().to_string(),
+                target: metadata.target().to_string(),
+                message: visitor.message + &visitor.fields,
+            },
+        );
+    }
+}
+
+/// Collects the `message` field and the other fields as ` key=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(message: &str) -> LogLine {
+        LogLine {
+            timestamp_ms: 0,
+            level: String::new(),
+            target: "app_lib".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn messages(lines: Vec<LogLine>) -> Vec<String> {
+        lines.into_iter().map(|line| line.message).collect()
+    }
+
+    #[test]
+    fn test_full_buffers_drop_their_oldest_lines() {
+        let buffer = LogBuffer::new(3);
+        for message in ["a", "b", "c", "d"] {
+            buffer.push(Level::INFO, line(message));
+        }
+        assert_eq!(messages(buffer.recent(Level::TRACE, 10)), ["b", "c", "d"]);
+
+        buffer.clear();
+        assert!(buffer.recent(Level::TRACE, 10).is_empty());
+    }
+
+    #[test]
+    fn test_recent_lines_are_filtered_by_level_and_limit() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(Level::ERROR, line("failed"));
+        buffer.push(Level::DEBUG, line("detail"));
+        buffer.push(Level::WARN, line("slow"));
+        buffer.push(Level::INFO, line("done"));
+
+        assert_eq!(messages(buffer.recent(Level::WARN, 10)), ["failed", "slow"]);
+        assert_eq!(
+            messages(buffer.recent(Level::TRACE, 10)),
+            ["failed", "detail", "slow", "done"]
+        );
+        // The limit keeps the newest lines, still oldest first
+        assert_eq!(messages(buffer.recent(Level::INFO, 2)), ["slow", "done"]);
+    }
+}
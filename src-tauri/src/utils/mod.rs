@@ -5,6 +5,8 @@
 // functions used throughout the application.
 
 pub mod helpers;
+pub mod log_buffer;
+pub mod profiles;
 pub mod validation;
 
 // Re-export public utilities
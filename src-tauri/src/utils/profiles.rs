@@ -0,0 +1,162 @@
+// =============================================================================
+// PROFILES - Named Configuration Profiles
+// =============================================================================
+// This module persists crawl form configurations as one JSON file per profile,
+// so the desktop app can restore them between launches.
+
+use rust_web_crawler::storage::durable::write_atomic;
+use rust_web_crawler::storage::naming::next_free_path;
+use rust_web_crawler::storage::{path_safe_component, FsyncPolicy};
+use std::path::{Path, PathBuf};
+
+use crate::core::{ConfigProfile, ConfigProfileInfo};
+
+/// Subdirectory of the app data dir holding the profiles
+pub const PROFILES_DIR: &str = "profiles";
+
+fn profile_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    Ok(name)
+}
+
+/// Profile files in `dir` with their contents; unreadable files are skipped
+fn saved_profiles(dir: &Path) -> Result<Vec<(PathBuf, ConfigProfile)>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let profile = serde_json::from_str::<ConfigProfile>(&content).ok()?;
+            Some((path, profile))
+        })
+        .collect())
+}
+
+/// File of the profile saved as `name`
+///
+/// Names that sanitize to the same file name ("a/b" and "a_b") get files of their
+/// own, so the file is found by the name stored in it.
+fn find_profile(dir: &Path, name: &str) -> Result<Option<(PathBuf, ConfigProfile)>, String> {
+    Ok(saved_profiles(dir)?
+        .into_iter()
+        .find(|(_, profile)| profile.name.trim() == name))
+}
+
+/// Write `profile`, replacing a profile of the same name
+pub fn save_profile(dir: &Path, profile: &ConfigProfile) -> Result<PathBuf, String> {
+    let name = profile_name(&profile.name)?;
+    let path = match find_profile(dir, name)? {
+        Some((path, _)) => path,
+        None => next_free_path(&dir.join(format!("{}.json", path_safe_component(name)))),
+    };
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let content = serde_json::to_vec_pretty(profile).map_err(|e| e.to_string())?;
+    write_atomic(&path, &content, FsyncPolicy::AtomicWritesOnly)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Read the profile saved as `name`
+pub fn load_profile(dir: &Path, name: &str) -> Result<ConfigProfile, String> {
+    let name = profile_name(name)?;
+    find_profile(dir, name)?
+        .map(|(_, profile)| profile)
+        .ok_or_else(|| format!("Profile '{}' not found", name))
+}
+
+/// All readable profiles, sorted by name
+///
+/// Files that do not parse as a profile are skipped.
+pub fn list_profiles(dir: &Path) -> Result<Vec<ConfigProfileInfo>, String> {
+    let mut profiles: Vec<ConfigProfileInfo> = saved_profiles(dir)?
+        .into_iter()
+        .map(|(_, profile)| ConfigProfileInfo {
+            name: profile.name,
+            saved_at: profile.saved_at,
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CrawlRequest;
+    use std::time::SystemTime;
+
+    fn profile(name: &str, base_url: &str) -> ConfigProfile {
+        let request: CrawlRequest = serde_json::from_value(serde_json::json!({
+            "session_id": "profile",
+            "base_url": base_url,
+            "max_total_urls": 1,
+            "max_crawl_depth": 0,
+            "enable_discovery_crawling": false,
+            "enable_keyword_filtering": false,
+            "target_words": [],
+            "enable_content_filtering": false,
+            "avoid_url_extensions": [],
+            "enable_language_filtering": false,
+            "latin_word_filter": false,
+            "match_strategy": "any",
+        }))
+        .unwrap();
+        ConfigProfile {
+            name: name.to_string(),
+            saved_at: SystemTime::now(),
+            request,
+        }
+    }
+
+    fn profiles_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("profiles_test_{}", test));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_names_sanitized_alike_keep_separate_profiles() {
+        let dir = profiles_dir("collisions");
+        let slash = save_profile(&dir, &profile("news/daily", "https://a.example/")).unwrap();
+        let underscore = save_profile(&dir, &profile("news_daily", "https://b.example/")).unwrap();
+        assert_ne!(slash, underscore);
+
+        let load = |name: &str| load_profile(&dir, name).unwrap().request.base_url;
+        assert_eq!(load("news/daily"), "https://a.example/");
+        assert_eq!(load("news_daily"), "https://b.example/");
+        let names: Vec<String> = list_profiles(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|info| info.name)
+            .collect();
+        assert_eq!(names, ["news/daily", "news_daily"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_saving_a_name_again_replaces_its_profile() {
+        let dir = profiles_dir("replace");
+        let first = save_profile(&dir, &profile("weekly", "https://a.example/")).unwrap();
+        let second = save_profile(&dir, &profile(" weekly ", "https://b.example/")).unwrap();
+        assert_eq!(first, second);
+
+        assert_eq!(list_profiles(&dir).unwrap().len(), 1);
+        assert_eq!(
+            load_profile(&dir, "weekly").unwrap().request.base_url,
+            "https://b.example/"
+        );
+        assert!(load_profile(&dir, "monthly").is_err());
+        assert!(load_profile(&dir, "  ").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,18 +2,27 @@
 // VALIDATION - Input Validation and Error Handling
 // =============================================================================
 // This module contains validation logic for request data and error handling.
+// Every problem is reported with the path of the offending field, so the
+// frontend can highlight the input it came from.
 
 use crate::core::CrawlRequest;
+use rust_web_crawler::config::{ProxyConfig, SsrfProtectionConfig};
+use rust_web_crawler::network::SsrfGuard;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 /// Result type for validation operations
 pub type ValidationResult<T> = Result<T, ValidationError>;
 
 /// Custom error type for validation failures
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
     pub message: String,
+    /// Path of the offending field, e.g. `proxy_pool[1]`
     pub field: Option<String>,
+    /// Values that would be accepted, as a hint for the input
+    #[serde(default)]
+    pub suggested_range: Option<String>,
 }
 
 impl ValidationError {
@@ -21,71 +30,199 @@ impl ValidationError {
         Self {
             message: message.to_string(),
             field: Some(field.to_string()),
+            suggested_range: None,
         }
     }
+
+    pub fn with_range(mut self, range: &str) -> Self {
+        self.suggested_range = Some(range.to_string());
+        self
+    }
 }
 
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.field {
-            Some(field) => write!(f, "{}: {}", field, self.message),
-            None => write!(f, "{}", self.message),
+            Some(field) => write!(f, "{}: {}", field, self.message)?,
+            None => write!(f, "{}", self.message)?,
+        }
+        if let Some(range) = &self.suggested_range {
+            write!(f, " (expected {})", range)?;
         }
+        Ok(())
     }
 }
 
 impl std::error::Error for ValidationError {}
 
-/// Validate crawler configuration
+/// Outcome of validating a request, with every problem found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn new(errors: Vec<ValidationError>) -> Self {
+        Self {
+            valid: errors.is_empty(),
+            errors,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+/// Validate crawler configuration, stopping at the first problem
 pub fn validate_crawl_request(request: &CrawlRequest) -> ValidationResult<()> {
+    match validate_crawl_request_fields(request).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Validate crawler configuration, collecting a problem for every offending field
+pub fn validate_crawl_request_fields(request: &CrawlRequest) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
     // Validate base URL
     if request.base_url.is_empty() {
-        return Err(ValidationError::with_field(
+        errors.push(ValidationError::with_field(
             "Base URL is required",
             "base_url",
         ));
-    }
-
-    // Validate URL format
-    if Url::parse(&request.base_url).is_err() {
-        return Err(ValidationError::with_field(
-            "Invalid URL format",
-            "base_url",
-        ));
+    } else {
+        match Url::parse(&request.base_url) {
+            Err(_) => errors.push(
+                ValidationError::with_field("Invalid URL format", "base_url")
+                    .with_range("an absolute http(s) URL"),
+            ),
+            Ok(url) => {
+                // Host names are resolved and checked again when the crawl starts
+                if let Err(violation) = SsrfGuard::new(SsrfProtectionConfig::default()).check(&url)
+                {
+                    errors.push(
+                        ValidationError::with_field(
+                            &format!("Address not allowed: {}", violation),
+                            "base_url",
+                        )
+                        .with_range("a URL on a public host"),
+                    );
+                }
+            }
+        }
     }
 
     // Validate numeric fields
     if request.max_total_urls == 0 {
-        return Err(ValidationError::with_field(
-            "Max total URLs must be greater than 0",
-            "max_total_urls",
-        ));
+        errors.push(
+            ValidationError::with_field("Max total URLs must be greater than 0", "max_total_urls")
+                .with_range(">= 1"),
+        );
     }
 
     if request.max_crawl_depth == 0 {
-        return Err(ValidationError::with_field(
-            "Max crawl depth must be greater than 0",
-            "max_crawl_depth",
-        ));
+        errors.push(
+            ValidationError::with_field(
+                "Max crawl depth must be greater than 0",
+                "max_crawl_depth",
+            )
+            .with_range(">= 1"),
+        );
     }
 
     // Validate URL extensions format
-    for ext in &request.avoid_url_extensions {
+    for (index, ext) in request.avoid_url_extensions.iter().enumerate() {
         if ext.is_empty() {
-            return Err(ValidationError::with_field(
+            errors.push(ValidationError::with_field(
                 "URL extensions cannot be empty",
-                "avoid_url_extensions",
+                &format!("avoid_url_extensions[{}]", index),
+            ));
+        }
+    }
+
+    // Validate proxies with the same parser the crawler uses
+    for (index, proxy_url) in request.proxy_pool.iter().enumerate() {
+        if let Err(e) = ProxyConfig::parse(proxy_url) {
+            errors.push(ValidationError::with_field(
+                &e.to_string(),
+                &format!("proxy_pool[{}]", index),
             ));
         }
     }
 
     // Validate match strategy
     if !["any", "all"].contains(&request.match_strategy.as_str()) {
-        return Err(ValidationError::with_field(
-            "Match strategy must be 'any' or 'all'",
-            "match_strategy",
-        ));
+        errors.push(
+            ValidationError::with_field("Match strategy must be 'any' or 'all'", "match_strategy")
+                .with_range("'any' or 'all'"),
+        );
+    }
+
+    // Validate rate limiting; a burst below the rate would shrink the window under a second
+    if request.requests_per_second == Some(0) {
+        errors.push(
+            ValidationError::with_field(
+                "Requests per second must be greater than 0",
+                "requests_per_second",
+            )
+            .with_range(">= 1"),
+        );
+    }
+    if let Some(burst) = request.burst {
+        match request.requests_per_second {
+            None => errors.push(ValidationError::with_field(
+                "Burst requires requests per second to be set",
+                "burst",
+            )),
+            Some(rate) if burst < rate => errors.push(
+                ValidationError::with_field(
+                    "Burst must be at least the requests per second",
+                    "burst",
+                )
+                .with_range(&format!(">= {}", rate)),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    // Validate retry delays against each other
+    let retry = request.crawler_config().retry_config.unwrap_or_default();
+    if retry.timing.base_delay_ms == 0 {
+        errors.push(
+            ValidationError::with_field(
+                "Retry base delay must be greater than 0",
+                "retry_base_delay_ms",
+            )
+            .with_range(">= 1"),
+        );
+    }
+    if retry.timing.base_delay_ms >= retry.timing.max_delay_ms {
+        // Blame the maximum only when the user set it
+        let (field, range) = if request.retry_max_delay_ms.is_some() {
+            (
+                "retry_max_delay_ms",
+                format!("> {}", retry.timing.base_delay_ms),
+            )
+        } else {
+            (
+                "retry_base_delay_ms",
+                format!("< {}", retry.timing.max_delay_ms),
+            )
+        };
+        errors.push(
+            ValidationError::with_field(
+                "Retry base delay must be less than the maximum delay",
+                field,
+            )
+            .with_range(&range),
+        );
     }
 
-    Ok(())
+    errors
 }
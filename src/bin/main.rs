@@ -2,34 +2,285 @@
 ///
 /// This demonstrates how to use the refactored architecture with minimal boilerplate
 use anyhow::Error;
+use futures::StreamExt;
 use rust_web_crawler::{
-    config::presets::create_production_session_config, logging::init_logging, session::CrawlSession,
+    CrawlPolicy, WebCrawler,
+    config::{PersistentVisitedConfig, presets::create_production_session_config},
+    crawler::AssetManifest,
+    logging::{ProgressDisplay, init_logging, init_logging_with_level},
+    processing::{ContentPipeline, reprocess_session},
+    session::{CheckpointConfig, CrawlSession, SeedHistoryConfig, SessionResult},
+    storage::{
+        DataStorage, FeedConfig, FsyncPolicy, HeadlessChrome, OutputFormat, SCHEMA_VERSION,
+        ScreenshotConfig, convert, migrate_dir,
+    },
 };
+use std::time::Duration;
 use tracing::info;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
+    // `--progress` shows a live table instead of the info log, so only warnings are logged
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let progress = args.iter().any(|arg| arg == "--progress");
+
     // Initialize logging
-    init_logging()?;
+    if progress {
+        init_logging_with_level("warn")?;
+    } else {
+        init_logging()?;
+    }
 
     info!("🚀 Rust Web Crawler - Refactored Production Mode");
     info!("==================================================");
 
     // Create session configuration using preset
-    let session_config = create_production_session_config();
+    let mut session_config = create_production_session_config();
     info!("⚙️ Loaded production session configuration");
 
+    // `--check <url>...` explains what the crawl would do with each URL, without crawling
+    if args.first().map(String::as_str) == Some("--check") {
+        return check_urls(&session_config.crawler_config, &args[1..]).await;
+    }
+
+    // `--check-links <url>...` prints the HTTP status of each URL as CSV
+    if args.first().map(String::as_str) == Some("--check-links") {
+        let crawler = WebCrawler::new(
+            session_config.crawler_config.clone(),
+            session_config.max_concurrent_requests,
+            session_config.max_depth,
+        )?;
+        let urls = args[1..]
+            .iter()
+            .map(|url| Url::parse(url))
+            .collect::<Result<Vec<_>, _>>()?;
+        let report = crawler.check_links(&urls).await;
+        info!(
+            "🔗 Checked {} links: {} broken or unreachable",
+            report.checks.len(),
+            report.failures().count()
+        );
+        return report.write_csv(std::io::stdout());
+    }
+
+    // `--download <dir> <url>...` fetches files into dir, resuming interrupted downloads
+    if args.first().map(String::as_str) == Some("--download") {
+        let Some((dir, urls)) = args[1..].split_first() else {
+            return Err(anyhow::anyhow!("Usage: --download <dir> <url>..."));
+        };
+        let crawler = WebCrawler::new(
+            session_config.crawler_config.clone(),
+            session_config.max_concurrent_requests,
+            session_config.max_depth,
+        )?;
+        let mut manifest = AssetManifest::open(std::path::Path::new(dir))?;
+        let mut failed = 0;
+        for url in urls {
+            match crawler
+                .download_asset(&Url::parse(url)?, &mut manifest)
+                .await
+            {
+                Ok(entry) => info!(
+                    "📥 {} -> {} ({} bytes)",
+                    url,
+                    manifest.path_of(&entry.file).display(),
+                    entry.bytes_received
+                ),
+                Err(e) => {
+                    failed += 1;
+                    tracing::warn!("Failed to download {}: {}", url, e);
+                }
+            }
+        }
+        info!(
+            "📥 Downloaded {} of {} files",
+            urls.len() - failed,
+            urls.len()
+        );
+        return Ok(());
+    }
+
+    // `--migrate [dir]` upgrades stored results to the current output schema
+    if args.first().map(String::as_str) == Some("--migrate") {
+        let dir = args.get(1).map_or("./crawl_data", String::as_str);
+        let report = migrate_dir(std::path::Path::new(dir), FsyncPolicy::default())?;
+        info!(
+            "📦 Migrated {} records in {} files to schema v{} ({} already current)",
+            report.records_migrated,
+            report.files_migrated.len(),
+            SCHEMA_VERSION,
+            report.records_current
+        );
+        for (path, error) in &report.errors {
+            tracing::warn!("⚠️ {}: {}", path.display(), error);
+        }
+        return Ok(());
+    }
+
+    // `--convert <session_dir> <from> <to>` rewrites stored results in another format
+    if args.first().map(String::as_str) == Some("--convert") {
+        let [session_dir, from, to] = &args[1..] else {
+            return Err(anyhow::anyhow!(
+                "Usage: --convert <session_dir> <json|jsonl|csv> <json|jsonl|csv>"
+            ));
+        };
+        let summary = convert(
+            std::path::Path::new(session_dir),
+            from.parse()?,
+            to.parse()?,
+        )?;
+        info!(
+            "🔁 Converted {} records from {} files to {} ({} skipped)",
+            summary.records,
+            summary.files_read,
+            summary.path.display(),
+            summary.skipped
+        );
+        return Ok(());
+    }
+
+    // `--reprocess <session_id> [dir]` re-extracts a stored session into a new one
+    if args.first().map(String::as_str) == Some("--reprocess") {
+        let session_id = args
+            .get(1)
+            .ok_or_else(|| anyhow::anyhow!("Usage: --reprocess <session_id> [dir]"))?;
+        let dir = args.get(2).map_or("./crawl_data", String::as_str);
+        let storage = DataStorage::new(dir, OutputFormat::Json)?
+            .with_raw_html(&session_config.raw_html.clone().unwrap_or_default());
+        let pipeline = ContentPipeline::from_config(&session_config.crawler_config)?;
+        let report = reprocess_session(&storage, session_id, &pipeline, &storage).await?;
+        info!(
+            "♻️ Reprocessed {} results into session {} ({} from raw HTML, {} from text, {} rejected)",
+            report.stored,
+            report.session_id,
+            report.from_raw_html,
+            report.from_text,
+            report.rejected.len()
+        );
+        for (url, error) in &report.errors {
+            tracing::warn!("⚠️ {}: {}", url, error);
+        }
+        return Ok(());
+    }
+
+    // `--diff-frontier <base_session> <session> [dir]` prints the coverage change as JSON
+    if args.first().map(String::as_str) == Some("--diff-frontier") {
+        let (Some(base), Some(compare)) = (args.get(1), args.get(2)) else {
+            return Err(anyhow::anyhow!(
+                "Usage: --diff-frontier <base_session> <session> [dir]"
+            ));
+        };
+        let dir = args.get(3).map_or("./crawl_data", String::as_str);
+        let diff = DataStorage::new(dir, OutputFormat::Json)?.diff_frontiers(base, compare)?;
+        info!(
+            "🧭 {} -> {}: {} -> {} URLs discovered ({} new, {} lost)",
+            base,
+            compare,
+            diff.base_discovered,
+            diff.compare_discovered,
+            diff.new_urls.len(),
+            diff.lost_urls.len()
+        );
+        for section in diff.lost_sections() {
+            tracing::warn!(
+                "⚠️ Section no longer reached: {} ({} URLs before)",
+                section.section,
+                section.base_urls
+            );
+        }
+        for section in diff.new_sections() {
+            info!(
+                "➕ New section: {} ({} URLs)",
+                section.section, section.compare_urls
+            );
+        }
+        serde_json::to_writer_pretty(std::io::stdout(), &diff)?;
+        return Ok(());
+    }
+
+    let flag_value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    // `--visited-store <path>` skips URLs stored by recent sessions; `--force`
+    // crawls them anyway, still recording the visits
+    if let Some(path) = flag_value("--visited-store") {
+        session_config.crawler_config.persistent_visited = Some(PersistentVisitedConfig {
+            store_path: std::path::PathBuf::from(path),
+            ..PersistentVisitedConfig::default()
+        });
+    }
+    if args.iter().any(|arg| arg == "--force") {
+        match session_config.crawler_config.persistent_visited.as_mut() {
+            Some(persistent) => {
+                persistent.force = true;
+                info!("🔁 Forced re-crawl: previous visits are ignored");
+            }
+            None => tracing::warn!("--force has no effect without --visited-store"),
+        }
+    }
+
+    // `--checkpoint <path>` saves the queue there; `--resume <path>` continues from it,
+    // refusing a changed configuration unless `--allow-config-change` is given
+    let resume_from = flag_value("--resume").cloned();
+    if let Some(path) = flag_value("--checkpoint").or(resume_from.as_ref()) {
+        session_config.checkpoint = Some(CheckpointConfig::new(path));
+    }
+
+    // `--seed-history <path>` shares seed outcomes between scheduled runs, skipping
+    // seeds that failed several runs in a row
+    if let Some(path) = flag_value("--seed-history") {
+        session_config.seed_history = Some(SeedHistoryConfig::new(path));
+    }
+
+    // `--feed` writes an Atom feed of new and changed pages to the session directory;
+    // `--feed-series <dir>` also keeps one feed across scheduled runs there
+    let feed_series = flag_value("--feed-series");
+    if args.iter().any(|arg| arg == "--feed") || feed_series.is_some() {
+        session_config.feed = Some(FeedConfig {
+            series_dir: feed_series.map(std::path::PathBuf::from),
+            ..FeedConfig::default()
+        });
+    }
+
+    // `--skip-report` lists every URL that was not crawled, and why, in skipped.jsonl
+    if args.iter().any(|arg| arg == "--skip-report") {
+        session_config.skip_report = true;
+    }
+
+    // `--screenshots <chrome>` captures every stored page with a headless Chrome
+    if let Some(binary) = flag_value("--screenshots") {
+        session_config.screenshots = Some(ScreenshotConfig::default());
+        session_config.screenshot_capture = Some(std::sync::Arc::new(HeadlessChrome::new(binary)));
+    }
+
     // Create crawl session
-    let session = CrawlSession::new(session_config).await?;
+    let (session, target_urls) = match &resume_from {
+        Some(path) => {
+            let allow_config_change = args.iter().any(|arg| arg == "--allow-config-change");
+            let session = CrawlSession::resume(
+                session_config,
+                std::path::Path::new(path),
+                allow_config_change,
+            )
+            .await?;
+            info!("⏯️ Resuming session {} from {}", session.session_id(), path);
+            (session, Vec::new())
+        }
+        None => (CrawlSession::new(session_config).await?, get_target_urls()?),
+    };
     info!("📝 Session ID: {}", session.session_id());
-
-    // Define target URLs
-    let target_urls = get_target_urls()?;
     info!("🎯 Target URLs: {}", target_urls.len());
 
     // Execute the crawl session
-    let session_result = session.execute_crawl(target_urls).await?;
+    let session_result = if progress {
+        crawl_with_progress(&session, target_urls).await?
+    } else {
+        session.execute_crawl(target_urls).await?
+    };
 
     // Log final statistics
     info!("=== Crawl Session Summary ===");
@@ -39,6 +290,7 @@ async fn main() -> Result<(), Error> {
     );
     info!("Successful crawls: {}", session_result.successful_crawls);
     info!("Failed crawls: {}", session_result.failed_crawls);
+    info!("Skipped URLs: {}", session_result.skipped_crawls);
     if session_result.total_urls_processed > 0 {
         let success_rate = (session_result.successful_crawls as f64
             / session_result.total_urls_processed as f64)
@@ -49,6 +301,29 @@ async fn main() -> Result<(), Error> {
         "Session duration: {:.2}s",
         session_result.total_duration.as_secs_f64()
     );
+    if let Some(remaining) = &session_result.remaining_queue {
+        info!(
+            "⏱️ Time box reached: {} pending and {} retry tasks left in the queue",
+            remaining.pending, remaining.retries
+        );
+    }
+
+    if let Some(blacklist) = &session_result.seed_blacklist
+        && !blacklist.blacklisted.is_empty()
+    {
+        info!(
+            "🚫 {} seeds blacklisted after repeated failures ({} skipped this run)",
+            blacklist.blacklisted.len(),
+            blacklist.skipped.len()
+        );
+    }
+
+    if let Some(reason) = &session_result.stop_reason {
+        info!("🛑 Stopped early: {}", reason);
+    }
+    if let Some(page) = &session_result.matched_page {
+        info!("🎯 Matching page: {}", page.url);
+    }
 
     info!("✅ Crawl session completed successfully!");
     info!("📊 Results stored in configured storage location");
@@ -56,6 +331,45 @@ async fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Run the crawl while redrawing the progress table every second
+async fn crawl_with_progress(
+    session: &CrawlSession,
+    urls: Vec<Url>,
+) -> Result<SessionResult, Error> {
+    let display = ProgressDisplay::new(10);
+    let crawl = session.execute_crawl(urls);
+    let updates = session.progress_updates(Duration::from_secs(1));
+    tokio::pin!(crawl, updates);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut crawl => break result,
+            Some(progress) = updates.next() => display.update(&progress),
+        }
+    };
+    display.finish();
+    result
+}
+
+/// Print the policy decision for each URL
+async fn check_urls(
+    config: &rust_web_crawler::config::WebCrawlerConfig,
+    urls: &[String],
+) -> Result<(), Error> {
+    let policy = CrawlPolicy::new(config)?;
+    for url in urls {
+        let decision = policy.evaluate(&Url::parse(url)?).await;
+        match decision.skipped_by() {
+            None => info!("✅ {} would be crawled", decision.url),
+            Some(outcome) => info!(
+                "⛔ {} would be skipped ({:?}): {}",
+                decision.url, outcome.check, outcome.reason
+            ),
+        }
+    }
+    Ok(())
+}
+
 /// Get target URLs for crawling
 fn get_target_urls() -> Result<Vec<Url>, Error> {
     Ok(vec![
@@ -1,4 +1,4 @@
-use crate::config::WebCrawlerConfig;
+use crate::config::{ForwardProxyConfig, WebCrawlerConfig};
 use crate::core::{DomainRateLimit, types::RateConfig};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -103,6 +103,10 @@ impl EnvironmentConfig {
         {
             self.monitoring.metrics_port = port;
         }
+
+        if let Some(forward_proxy) = ForwardProxyConfig::from_env() {
+            self.crawler.forward_proxy = Some(forward_proxy);
+        }
     }
 
     /// Get configuration optimized for the current environment
@@ -5,6 +5,20 @@ pub mod environment;
 pub mod presets;
 
 // Re-export common configuration types
-pub use crawler::{HttpClientFactory, LatinWordFilter, LoggingConfig, WebCrawlerConfig, defaults};
+#[allow(deprecated)]
+pub use crawler::HttpClientFactory;
+pub use crawler::{
+    AdaptiveConcurrencyConfig, AutoKeywordConfig, AutoKeywordMethod, CassetteConfig, CassetteMode,
+    ChangeDetectionConfig, ContactConfig, DnsPrefetchConfig, ForwardProxyConfig, FragmentConfig,
+    FragmentPolicy, GeoFallback, GeoProxyConfig, GreetingConfig, HeaderTemplate,
+    HeaderTemplateConfig, HttpTraceConfig, IdentityConfig, IdentityProfile, InternalNetworkConfig,
+    InterstitialConfig, InterstitialHandling, LanguagePriorConfig, LatinWordFilter, LoggingConfig,
+    MetaNavigationConfig, NavigationPolicy, PaginationConfig, PersistentVisitedConfig, PiiConfig,
+    ProtocolFallbackConfig, ProxyAuth, ProxyConfig, ProxyProvider, ProxySourceConfig,
+    PublicSuffixConfig, RetryEscalation, RetryEscalationConfig, RobotsOverrideConfig,
+    SeedPrecheckConfig, SsrfProtectionConfig, StructuredExtractionConfig, TimeoutConfig,
+    TlsCertificateConfig, TlsVersionFloor, UaRotation, VisitedSetConfig, WebCrawlerConfig,
+    defaults,
+};
 pub use environment::EnvironmentConfig;
 pub use presets::*;
@@ -1,4 +1,5 @@
-use crate::config::{LatinWordFilter, WebCrawlerConfig};
+use crate::config::{IdentityConfig, IdentityProfile, LatinWordFilter, WebCrawlerConfig};
+use crate::core::error::CrawlError;
 use crate::core::types::RateConfig;
 use crate::core::{DomainRateLimit, LangType, RetryConfig};
 use crate::session::CrawlSessionConfig;
@@ -8,6 +9,73 @@ use crate::session::CrawlSessionConfig;
 /// reducing the need to manually configure all options in main.rs files.
 use std::collections::HashMap;
 
+/// A named, documented configuration preset
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    build: fn() -> WebCrawlerConfig,
+}
+
+impl ConfigPreset {
+    /// Build a fresh configuration from this preset
+    pub fn build(&self) -> WebCrawlerConfig {
+        (self.build)()
+    }
+}
+
+/// All registered presets, looked up by name via `WebCrawlerConfig::preset`
+const CONFIG_PRESETS: &[ConfigPreset] = &[
+    ConfigPreset {
+        name: "production",
+        description: "Conservative per-domain rate limits and retries for unattended runs",
+        build: create_production_config,
+    },
+    ConfigPreset {
+        name: "development",
+        description: "Relaxed limits and a small crawl budget against test sites",
+        build: create_development_config,
+    },
+    ConfigPreset {
+        name: "demo",
+        description: "Tiny multilingual crawl with keyword filtering for demonstrations",
+        build: create_demo_config,
+    },
+    ConfigPreset {
+        name: "polite-news",
+        description: "One request per second, shallow link following, English news articles",
+        build: create_polite_news_config,
+    },
+    ConfigPreset {
+        name: "aggressive-intranet",
+        description: "High request rates and deep crawls for internal sites you control",
+        build: create_aggressive_intranet_config,
+    },
+    ConfigPreset {
+        name: "docs-mirror",
+        description: "Deep, exhaustive crawl of documentation sites, any language",
+        build: create_docs_mirror_config,
+    },
+    ConfigPreset {
+        name: "focused-topic",
+        description: "Keeps only pages matching the target words set with `with_target_words`",
+        build: create_focused_topic_config,
+    },
+];
+
+/// List all registered configuration presets
+pub fn config_presets() -> &'static [ConfigPreset] {
+    CONFIG_PRESETS
+}
+
+/// Find a preset by name (case-insensitive)
+pub fn find_preset(name: &str) -> Result<&'static ConfigPreset, CrawlError> {
+    CONFIG_PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| CrawlError::PresetNotFound(name.to_string()))
+}
+
 /// Create a production-ready configuration with conservative rate limits
 pub fn create_production_config() -> WebCrawlerConfig {
     let mut domain_rate_limits = HashMap::new();
@@ -103,6 +171,39 @@ pub fn create_production_config() -> WebCrawlerConfig {
         proxy_pool: vec![],
         avoid_url_extensions: vec![],
         logging_config: None,
+        robots_override: None,
+        http_trace: None,
+        forward_proxy: None,
+        identity: None,
+        visited_set: None,
+        timeouts: None,
+        meta_navigation: None,
+        internal_network: None,
+        change_detection: None,
+        structured_extraction: None,
+        adaptive_concurrency: None,
+        persistent_visited: None,
+        language_priors: None,
+        retry_escalation: None,
+        interstitials: None,
+        header_templates: None,
+        tls_certificates: None,
+        cassette: None,
+        contact: None,
+        pii: None,
+        auto_keywords: None,
+        protocol_fallback: None,
+        max_page_bytes: None,
+        language_probe_bytes: None,
+        pagination: None,
+        proxy_sources: None,
+        fragments: None,
+        public_suffixes: None,
+        ssrf_protection: None,
+        geo_proxies: None,
+        dns_prefetch: None,
+        greeting: None,
+        domain_shards: None,
         enable_extension_crawling: false,
         max_crawl_depth: 2,
         max_total_urls: 100,
@@ -163,6 +264,39 @@ pub fn create_development_config() -> WebCrawlerConfig {
         proxy_pool: vec![],
         avoid_url_extensions: vec![],
         logging_config: None,
+        robots_override: None,
+        http_trace: None,
+        forward_proxy: None,
+        identity: None,
+        visited_set: None,
+        timeouts: None,
+        meta_navigation: None,
+        internal_network: None,
+        change_detection: None,
+        structured_extraction: None,
+        adaptive_concurrency: None,
+        persistent_visited: None,
+        language_priors: None,
+        retry_escalation: None,
+        interstitials: None,
+        header_templates: None,
+        tls_certificates: None,
+        cassette: None,
+        contact: None,
+        pii: None,
+        auto_keywords: None,
+        protocol_fallback: None,
+        max_page_bytes: None,
+        language_probe_bytes: None,
+        pagination: None,
+        proxy_sources: None,
+        fragments: None,
+        public_suffixes: None,
+        ssrf_protection: None,
+        geo_proxies: None,
+        dns_prefetch: None,
+        greeting: None,
+        domain_shards: None,
         enable_extension_crawling: true,
         max_crawl_depth: 1,
         max_total_urls: 20,
@@ -227,6 +361,39 @@ pub fn create_demo_config() -> WebCrawlerConfig {
         proxy_pool: vec![],
         avoid_url_extensions: vec![],
         logging_config: None,
+        robots_override: None,
+        http_trace: None,
+        forward_proxy: None,
+        identity: None,
+        visited_set: None,
+        timeouts: None,
+        meta_navigation: None,
+        internal_network: None,
+        change_detection: None,
+        structured_extraction: None,
+        adaptive_concurrency: None,
+        persistent_visited: None,
+        language_priors: None,
+        retry_escalation: None,
+        interstitials: None,
+        header_templates: None,
+        tls_certificates: None,
+        cassette: None,
+        contact: None,
+        pii: None,
+        auto_keywords: None,
+        protocol_fallback: None,
+        max_page_bytes: None,
+        language_probe_bytes: None,
+        pagination: None,
+        proxy_sources: None,
+        fragments: None,
+        public_suffixes: None,
+        ssrf_protection: None,
+        geo_proxies: None,
+        dns_prefetch: None,
+        greeting: None,
+        domain_shards: None,
         enable_extension_crawling: false,
         max_crawl_depth: 1,
         max_total_urls: 10,
@@ -235,6 +402,120 @@ pub fn create_demo_config() -> WebCrawlerConfig {
     }
 }
 
+/// Polite news crawling: one request per second, shallow link following
+pub fn create_polite_news_config() -> WebCrawlerConfig {
+    WebCrawlerConfig {
+        accepted_languages: vec![LangType::Eng],
+        target_words: vec![],
+        min_word_length: 150,
+        default_rate_limit: Some(DomainRateLimit {
+            rate: RateConfig {
+                max_requests_per_second: 1,
+                window_size_ms: 1000,
+            },
+        }),
+        retry_config: Some(RetryConfig {
+            max_retries: 3,
+            timing: crate::core::types::TimingConfig {
+                base_delay_ms: 2000,
+                max_delay_ms: 60000,
+                backoff_multiplier: 2.0,
+            },
+            jitter_factor: 0.3,
+        }),
+        avoid_url_extensions: media_extensions(),
+        enable_extension_crawling: true,
+        max_crawl_depth: 2,
+        max_total_urls: 500,
+        enable_keyword_filtering: false,
+        latin_word_filter: create_enhanced_latin_filter(),
+        // Identify honestly as a crawler
+        identity: Some(IdentityConfig {
+            default_profile: IdentityProfile::Fixed,
+            ..IdentityConfig::default()
+        }),
+        ..WebCrawlerConfig::default()
+    }
+}
+
+/// Aggressive intranet crawling: high request rate, deep crawl, minimal filtering
+pub fn create_aggressive_intranet_config() -> WebCrawlerConfig {
+    WebCrawlerConfig {
+        accepted_languages: vec![],
+        target_words: vec![],
+        min_word_length: 10,
+        user_agent: "Rust-Web-Crawler/1.0 (Intranet)".to_string(),
+        default_rate_limit: Some(DomainRateLimit {
+            rate: RateConfig {
+                max_requests_per_second: 20,
+                window_size_ms: 1000,
+            },
+        }),
+        retry_config: Some(RetryConfig {
+            max_retries: 1,
+            timing: crate::core::types::TimingConfig {
+                base_delay_ms: 200,
+                max_delay_ms: 1000,
+                backoff_multiplier: 1.5,
+            },
+            jitter_factor: 0.1,
+        }),
+        avoid_url_extensions: media_extensions(),
+        enable_extension_crawling: true,
+        max_crawl_depth: 6,
+        max_total_urls: 20000,
+        enable_keyword_filtering: false,
+        latin_word_filter: create_basic_latin_filter(),
+        ..WebCrawlerConfig::default()
+    }
+}
+
+/// Documentation mirroring: deep and exhaustive, keeps short reference pages
+pub fn create_docs_mirror_config() -> WebCrawlerConfig {
+    WebCrawlerConfig {
+        accepted_languages: vec![],
+        target_words: vec![],
+        min_word_length: 20,
+        user_agent: "Rust-Web-Crawler/1.0 (Docs Mirror)".to_string(),
+        default_rate_limit: Some(DomainRateLimit {
+            rate: RateConfig {
+                max_requests_per_second: 4,
+                window_size_ms: 1000,
+            },
+        }),
+        avoid_url_extensions: media_extensions(),
+        enable_extension_crawling: true,
+        max_crawl_depth: 10,
+        max_total_urls: 5000,
+        enable_keyword_filtering: false,
+        latin_word_filter: create_basic_latin_filter(),
+        ..WebCrawlerConfig::default()
+    }
+}
+
+/// Focused topic crawling: `with_target_words` sets the topic and turns on keyword filtering
+pub fn create_focused_topic_config() -> WebCrawlerConfig {
+    WebCrawlerConfig {
+        accepted_languages: vec![LangType::Eng],
+        target_words: vec![],
+        min_word_length: 100,
+        default_rate_limit: Some(DomainRateLimit {
+            rate: RateConfig {
+                max_requests_per_second: 2,
+                window_size_ms: 1000,
+            },
+        }),
+        avoid_url_extensions: media_extensions(),
+        enable_extension_crawling: true,
+        max_crawl_depth: 3,
+        max_total_urls: 1000,
+        // Nothing to filter by until target words are set
+        enable_keyword_filtering: false,
+        latin_word_filter: create_enhanced_latin_filter(),
+        ..WebCrawlerConfig::default()
+    }
+}
+
 /// Create session config for production crawling
 pub fn create_production_session_config() -> CrawlSessionConfig {
     CrawlSessionConfig {
@@ -245,6 +526,35 @@ pub fn create_production_session_config() -> CrawlSessionConfig {
         session_timeout: Some(std::time::Duration::from_secs(600)), // 10 minutes
         enable_storage: true,
         storage_path: Some("./crawl_data".to_string()),
+        output_naming: None,
+        content_pipeline: None,
+        enqueue_hreflang_alternates: false,
+        link_importance: false,
+        max_urls_per_domain: None,
+        sampling: None,
+        link_categorization: None,
+        retention: None,
+        object_storage: None,
+        raw_html: None,
+        search_index_path: None,
+        max_session_duration: None,
+        seed_precheck: None,
+        webhooks: None,
+        stop_conditions: None,
+        domain_metadata: None,
+        frontier_file: None,
+        frontier_scopes: Vec::new(),
+        metrics_log: None,
+        integrity_manifest: None,
+        short_pages_report: false,
+        skip_report: false,
+        event_timeline: None,
+        screenshots: None,
+        screenshot_capture: None,
+        sanitized_html: None,
+        checkpoint: None,
+        seed_history: None,
+        feed: None,
     }
 }
 
@@ -258,6 +568,35 @@ pub fn create_development_session_config() -> CrawlSessionConfig {
         session_timeout: Some(std::time::Duration::from_secs(300)), // 5 minutes
         enable_storage: true,
         storage_path: Some("./dev_crawl_data".to_string()),
+        output_naming: None,
+        content_pipeline: None,
+        enqueue_hreflang_alternates: false,
+        link_importance: false,
+        max_urls_per_domain: None,
+        sampling: None,
+        link_categorization: None,
+        retention: None,
+        object_storage: None,
+        raw_html: None,
+        search_index_path: None,
+        max_session_duration: None,
+        seed_precheck: None,
+        webhooks: None,
+        stop_conditions: None,
+        domain_metadata: None,
+        frontier_file: None,
+        frontier_scopes: Vec::new(),
+        metrics_log: None,
+        integrity_manifest: None,
+        short_pages_report: false,
+        skip_report: false,
+        event_timeline: None,
+        screenshots: None,
+        screenshot_capture: None,
+        sanitized_html: None,
+        checkpoint: None,
+        seed_history: None,
+        feed: None,
     }
 }
 
@@ -271,9 +610,48 @@ pub fn create_demo_session_config() -> CrawlSessionConfig {
         session_timeout: Some(std::time::Duration::from_secs(120)), // 2 minutes
         enable_storage: true,
         storage_path: Some("./demo_crawl_data".to_string()),
+        output_naming: None,
+        content_pipeline: None,
+        enqueue_hreflang_alternates: false,
+        link_importance: false,
+        max_urls_per_domain: None,
+        sampling: None,
+        link_categorization: None,
+        retention: None,
+        object_storage: None,
+        raw_html: None,
+        search_index_path: None,
+        max_session_duration: None,
+        seed_precheck: None,
+        webhooks: None,
+        stop_conditions: None,
+        domain_metadata: None,
+        frontier_file: None,
+        frontier_scopes: Vec::new(),
+        metrics_log: None,
+        integrity_manifest: None,
+        short_pages_report: false,
+        skip_report: false,
+        event_timeline: None,
+        screenshots: None,
+        screenshot_capture: None,
+        sanitized_html: None,
+        checkpoint: None,
+        seed_history: None,
+        feed: None,
     }
 }
 
+/// File extensions skipped by presets that only want HTML pages
+fn media_extensions() -> Vec<String> {
+    [
+        ".pdf", ".jpg", ".jpeg", ".png", ".gif", ".svg", ".webp", ".mp3", ".mp4", ".zip", ".exe",
+    ]
+    .iter()
+    .map(|ext| ext.to_string())
+    .collect()
+}
+
 /// Create enhanced Latin word filter with comprehensive exclusions
 fn create_enhanced_latin_filter() -> LatinWordFilter {
     LatinWordFilter {
@@ -357,3 +735,42 @@ fn create_basic_latin_filter() -> LatinWordFilter {
         min_word_length: 3,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_found_by_name() {
+        assert_eq!(find_preset(" Polite-News ").unwrap().name, "polite-news");
+        assert!(matches!(
+            find_preset("unknown"),
+            Err(CrawlError::PresetNotFound(_))
+        ));
+
+        for preset in config_presets() {
+            assert_eq!(find_preset(preset.name).unwrap().name, preset.name);
+        }
+    }
+
+    #[test]
+    fn test_no_preset_filters_by_keyword_without_target_words() {
+        for preset in config_presets() {
+            let config = preset.build();
+            assert!(
+                !config.enable_keyword_filtering || !config.target_words.is_empty(),
+                "{} filters by keyword without target words",
+                preset.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_target_words_turn_on_focused_filtering() {
+        let config = WebCrawlerConfig::preset("focused-topic")
+            .unwrap()
+            .with_target_words(vec!["rust".to_string()]);
+        assert!(config.enable_keyword_filtering);
+        assert_eq!(config.target_words, vec!["rust".to_string()]);
+    }
+}
@@ -1,12 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::types::TimeoutStage;
+
 /// Enhanced crawl error types with more specific categorization
 #[derive(Debug, Clone)]
 pub enum CrawlError {
     // Network-related errors
     NetworkError(String),
     NetworkTimeout,
+    /// A request stage exceeded its configured timeout (limit in milliseconds)
+    StageTimeout(TimeoutStage, u64),
     ConnectionRefused,
     DnsResolutionFailed(String),
     ProxyError,
+    /// The server's TLS certificate was rejected
+    CertificateError(String),
 
     // HTTP-related errors
     HttpError(u16),
@@ -25,11 +33,16 @@ pub enum CrawlError {
     ExtensiveConfigError(String),
     CleaningConfigError(String),
     CleaningRuleError(String),
+    PresetNotFound(String),
 
     // Policy-related errors
     RobotsBlocked,
     RateLimited,
     Forbidden,
+    /// The response is a bot-detection page (the matched marker and retry history)
+    BotDetected(String),
+    /// The URL points to a private, link-local or cloud metadata address
+    AddressBlocked(String),
 
     // System errors
     UnknownError(String),
@@ -40,11 +53,15 @@ impl std::fmt::Display for CrawlError {
         match self {
             CrawlError::NetworkError(msg) => write!(f, "Network error: {}", msg),
             CrawlError::NetworkTimeout => write!(f, "Network timeout"),
+            CrawlError::StageTimeout(stage, limit_ms) => {
+                write!(f, "Timeout in {} stage after {} ms", stage, limit_ms)
+            }
             CrawlError::ConnectionRefused => write!(f, "Connection refused"),
             CrawlError::DnsResolutionFailed(domain) => {
                 write!(f, "DNS resolution failed for {}", domain)
             }
             CrawlError::ProxyError => write!(f, "Proxy connection error"),
+            CrawlError::CertificateError(detail) => write!(f, "TLS certificate error: {}", detail),
             CrawlError::HttpError(code) => write!(f, "HTTP error: {}", code),
             CrawlError::RedirectLoop => write!(f, "Redirect loop detected"),
             CrawlError::InvalidUrl(url) => write!(f, "Invalid URL: {}", url),
@@ -63,9 +80,12 @@ impl std::fmt::Display for CrawlError {
                 write!(f, "Text cleaning configuration error: {}", msg)
             }
             CrawlError::CleaningRuleError(msg) => write!(f, "Text cleaning rule error: {}", msg),
+            CrawlError::PresetNotFound(name) => write!(f, "Unknown configuration preset: {}", name),
             CrawlError::RobotsBlocked => write!(f, "Blocked by robots.txt"),
             CrawlError::RateLimited => write!(f, "Rate limited"),
             CrawlError::Forbidden => write!(f, "Access forbidden"),
+            CrawlError::BotDetected(detail) => write!(f, "Bot detection page: {}", detail),
+            CrawlError::AddressBlocked(detail) => write!(f, "Blocked network address: {}", detail),
             CrawlError::UnknownError(msg) => write!(f, "Unknown error: {}", msg),
         }
     }
@@ -76,16 +96,36 @@ impl std::error::Error for CrawlError {}
 impl CrawlError {
     /// Check if this error type should trigger a retry
     pub fn is_retryable(&self) -> bool {
-        matches!(
-            self,
-            CrawlError::NetworkError(_)
-                | CrawlError::NetworkTimeout
-                | CrawlError::ConnectionRefused
-                | CrawlError::DnsResolutionFailed(_)
-                | CrawlError::HttpError(500..=599)
-                | CrawlError::ProxyError
-                | CrawlError::RateLimited
-        )
+        self.permanent_failure().is_none()
+            && matches!(
+                self,
+                CrawlError::NetworkError(_)
+                    | CrawlError::NetworkTimeout
+                    | CrawlError::StageTimeout(
+                        TimeoutStage::Connect | TimeoutStage::FirstByte | TimeoutStage::Download,
+                        _
+                    )
+                    | CrawlError::ConnectionRefused
+                    | CrawlError::DnsResolutionFailed(_)
+                    | CrawlError::HttpError(500..=599)
+                    | CrawlError::ProxyError
+                    | CrawlError::RateLimited
+            )
+    }
+
+    /// The permanent failure this error stands for, if it cannot go away on retry
+    pub fn permanent_failure(&self) -> Option<PermanentFailure> {
+        match self {
+            CrawlError::HttpError(410) => Some(PermanentFailure::Gone),
+            CrawlError::HttpError(451) => Some(PermanentFailure::UnavailableForLegalReasons),
+            CrawlError::DnsResolutionFailed(detail) if contains_any(detail, NXDOMAIN_MARKERS) => {
+                Some(PermanentFailure::NxDomain)
+            }
+            CrawlError::CertificateError(detail) if contains_any(detail, NAME_MISMATCH_MARKERS) => {
+                Some(PermanentFailure::CertificateNameMismatch)
+            }
+            _ => None,
+        }
     }
 
     /// Get the severity level of the error
@@ -97,6 +137,8 @@ impl CrawlError {
             | CrawlError::NetworkTimeout
             | CrawlError::ConnectionRefused
             | CrawlError::ProxyError => ErrorSeverity::High,
+            CrawlError::CertificateError(_) => ErrorSeverity::Medium,
+            CrawlError::StageTimeout(..) => ErrorSeverity::Medium,
             CrawlError::HttpError(500..=599) | CrawlError::DnsResolutionFailed(_) => {
                 ErrorSeverity::Medium
             }
@@ -106,24 +148,51 @@ impl CrawlError {
             CrawlError::ParsingError | CrawlError::EncodingError => ErrorSeverity::Medium,
             CrawlError::KeywordConfigError(_)
             | CrawlError::ExtensiveConfigError(_)
-            | CrawlError::CleaningConfigError(_) => ErrorSeverity::High,
+            | CrawlError::CleaningConfigError(_)
+            | CrawlError::PresetNotFound(_) => ErrorSeverity::High,
             CrawlError::KeywordNotFound => ErrorSeverity::Low,
             CrawlError::CleaningRuleError(_) => ErrorSeverity::Medium,
-            CrawlError::RobotsBlocked | CrawlError::Forbidden => ErrorSeverity::Low,
+            CrawlError::RobotsBlocked
+            | CrawlError::Forbidden
+            | CrawlError::BotDetected(_)
+            | CrawlError::AddressBlocked(_) => ErrorSeverity::Low,
             CrawlError::HttpError(_) => ErrorSeverity::Medium,
             CrawlError::UnknownError(_) => ErrorSeverity::Critical,
         }
     }
 
+    /// The error for an unsuccessful HTTP status; request and gateway timeouts count as timeouts
+    pub fn from_status(code: u16) -> Self {
+        match code {
+            408 | 504 => CrawlError::NetworkTimeout,
+            code => CrawlError::HttpError(code),
+        }
+    }
+
     /// Categorize a generic error into CrawlError
     pub fn from_anyhow_error(error: &anyhow::Error) -> Self {
-        let error_str = error.to_string().to_lowercase(); // Scoped variable for DRY
+        if let Some(crawl_error) = error.downcast_ref::<CrawlError>() {
+            return match crawl_error {
+                CrawlError::HttpError(code) => Self::from_status(*code),
+                other => other.clone(),
+            };
+        }
+
+        // The alternate form includes the source chain, where resolver and TLS errors sit
+        let error_str = format!("{:#}", error).to_lowercase(); // Scoped variable for DRY
         let error_msg = error.to_string(); // Scoped variable for original message
 
         match error_str.as_str() {
+            s if s.starts_with("http error: ") => s["http error: ".len()..]
+                .split_whitespace()
+                .next()
+                .and_then(|code| code.parse().ok())
+                .map(CrawlError::from_status)
+                .unwrap_or(CrawlError::UnknownError(error_msg)),
             s if s.contains("timeout") => CrawlError::NetworkTimeout,
             s if s.contains("connection refused") => CrawlError::ConnectionRefused,
             s if s.contains("dns") => CrawlError::DnsResolutionFailed(error_msg),
+            s if s.contains("certificate") => CrawlError::CertificateError(error_msg),
             s if s.contains("proxy") => CrawlError::ProxyError,
             s if s.contains("redirect") => CrawlError::RedirectLoop,
             s if s.contains("url") || s.contains("parse") => CrawlError::InvalidUrl(error_msg),
@@ -131,3 +200,88 @@ impl CrawlError {
         }
     }
 }
+
+/// Resolver messages for a name that does not exist (glibc, macOS, Windows)
+const NXDOMAIN_MARKERS: &[&str] = &[
+    "nxdomain",
+    "name or service not known",
+    "nodename nor servname provided",
+    "no such host is known",
+];
+
+/// TLS messages for a certificate issued to another name (OpenSSL, rustls, Schannel, macOS)
+const NAME_MISMATCH_MARKERS: &[&str] = &[
+    "hostname mismatch",
+    "notvalidforname",
+    "principal name is incorrect",
+    "not valid for",
+];
+
+fn contains_any(detail: &str, markers: &[&str]) -> bool {
+    let detail = detail.to_lowercase();
+    markers.iter().any(|marker| detail.contains(marker))
+}
+
+/// A failure that retrying, now or in a later crawl, will not fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermanentFailure {
+    /// HTTP 410
+    Gone,
+    /// HTTP 451
+    UnavailableForLegalReasons,
+    /// The domain does not exist
+    NxDomain,
+    /// The certificate is valid, but for another host name
+    CertificateNameMismatch,
+}
+
+impl std::fmt::Display for PermanentFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermanentFailure::Gone => write!(f, "HTTP 410 Gone"),
+            PermanentFailure::UnavailableForLegalReasons => {
+                write!(f, "HTTP 451 Unavailable For Legal Reasons")
+            }
+            PermanentFailure::NxDomain => write!(f, "domain does not exist"),
+            PermanentFailure::CertificateNameMismatch => {
+                write!(f, "certificate does not match the host name")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_statuses_classify_as_timeouts() {
+        for status in [408, 504] {
+            let error = anyhow::anyhow!("HTTP error: {} Gateway Timeout", status);
+            assert!(matches!(
+                CrawlError::from_anyhow_error(&error),
+                CrawlError::NetworkTimeout
+            ));
+            let error = anyhow::Error::from(CrawlError::HttpError(status));
+            assert!(matches!(
+                CrawlError::from_anyhow_error(&error),
+                CrawlError::NetworkTimeout
+            ));
+        }
+    }
+
+    #[test]
+    fn test_other_statuses_keep_their_code() {
+        let error = anyhow::anyhow!("HTTP error: 404 Not Found");
+        assert!(matches!(
+            CrawlError::from_anyhow_error(&error),
+            CrawlError::HttpError(404)
+        ));
+        let error = anyhow::Error::from(CrawlError::HttpError(503));
+        assert!(matches!(
+            CrawlError::from_anyhow_error(&error),
+            CrawlError::HttpError(503)
+        ));
+    }
+}
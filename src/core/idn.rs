@@ -0,0 +1,72 @@
+/// Internationalized domain names
+///
+/// `Url` keeps hosts in their ASCII (punycode) form, but domains written in the
+/// configuration, filters or seed lists may be in Unicode, in mixed case or with a
+/// trailing dot. Hosts are compared, rate limited and deduplicated in the form
+/// `host_key` returns, and shown to people in the form `display_host` returns.
+use url::{Position, Url};
+
+/// Lowercase ASCII form of a host, without a trailing dot
+///
+/// "Bücher.Example." and "xn--bcher-kva.example" both become
+/// "xn--bcher-kva.example". Hosts that are not valid domain names are only
+/// lowercased.
+pub fn host_key(host: &str) -> String {
+    let host = host.trim().trim_end_matches('.');
+    idna::domain_to_ascii(host).unwrap_or_else(|_| host.to_lowercase())
+}
+
+/// Unicode form of a host for reports and logs
+pub fn display_host(host: &str) -> String {
+    let (unicode, result) = idna::domain_to_unicode(&host_key(host));
+    match result {
+        Ok(()) => unicode,
+        Err(_) => host.to_lowercase(),
+    }
+}
+
+/// `url` with its host in Unicode, for display only
+pub fn display_url(url: &Url) -> String {
+    match url.host_str() {
+        Some(host) => format!(
+            "{}{}{}",
+            &url[..Position::BeforeHost],
+            display_host(host),
+            &url[Position::AfterHost..]
+        ),
+        None => url.to_string(),
+    }
+}
+
+/// Drop the trailing dot of a fully qualified host so both spellings are one URL
+pub fn normalize_host(url: &mut Url) {
+    if let Some(host) = url.host_str()
+        && host.len() > 1
+        && host.ends_with('.')
+    {
+        let host = host.trim_end_matches('.').to_string();
+        let _ = url.set_host(Some(&host));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_and_punycode_hosts_are_one_key() {
+        assert_eq!(host_key("Bücher.Example."), "xn--bcher-kva.example");
+        assert_eq!(host_key("xn--bcher-kva.example"), "xn--bcher-kva.example");
+        assert_eq!(host_key("例え.テスト"), host_key("xn--r8jz45g.xn--zckzah"));
+        assert_eq!(display_host("XN--BCHER-KVA.example"), "bücher.example");
+        assert_eq!(display_host("127.0.0.1"), "127.0.0.1");
+
+        let unicode = Url::parse("https://Bücher.example./katalog?q=1").unwrap();
+        let mut ascii = Url::parse("https://xn--bcher-kva.example/katalog?q=1").unwrap();
+        assert_eq!(display_url(&ascii), "https://bücher.example/katalog?q=1");
+        let mut normalized = unicode.clone();
+        normalize_host(&mut normalized);
+        normalize_host(&mut ascii);
+        assert_eq!(normalized, ascii);
+    }
+}
@@ -1,18 +1,23 @@
 // Core types, traits, and error handling
 
 pub mod error;
+pub mod idn;
+pub mod psl;
 pub mod traits;
 pub mod types;
 pub mod utils;
 
 // Re-export common items for convenience
-pub use error::CrawlError;
+pub use error::{CrawlError, PermanentFailure};
+pub use idn::{display_host, display_url, host_key};
+pub use psl::{PublicSuffixes, registrable_domain};
 pub use traits::{
     Categorizable, ContentProcessor, DnsResolver, ErrorHandler, HttpClientManager, Normalizable,
     RateLimiter, Retryable, RobotsChecker, TimestampedTask, Validatable,
 };
 pub use types::{
-    CrawlResult, CrawlTask, DomainRateLimit, ErrorSeverity, LangType, OptionInstant, QueueStats,
-    RetryConfig, SkipReason, TaskPriority, TaskResult, TaskStatus, TaskTiming,
+    CrawlResult, CrawlTask, DomainRateLimit, ErrorSeverity, GroupQueueStats, LangType,
+    OptionInstant, PaginationChain, QueueStats, Region, RetryConfig, SeedScope, SkipReason,
+    TaskPriority, TaskProvenance, TaskResult, TaskStatus, TaskTiming, TimeoutStage, UrlOutcome,
 };
 pub use utils::ErrorUtils;
@@ -0,0 +1,184 @@
+/// Registrable domains from the public suffix list
+///
+/// Hosts belong to the same site when they share a registrable domain: the public
+/// suffix they end in ("com", "co.uk", "github.io") plus one more label, so
+/// "www.example.co.uk" and "shop.example.co.uk" are one site while "a.github.io"
+/// and "b.github.io" are two. `PublicSuffixes` holds the suffix rules, the list
+/// bundled with the crate unless another copy is loaded from a file, together with
+/// site overrides: domains treated as sites of their own whatever the list says.
+/// Each crawler builds its own from its config; `registrable_domain` answers with
+/// the bundled list.
+use anyhow::{Context, Result, anyhow};
+use publicsuffix::{List, Psl};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::idn::host_key;
+
+/// Public suffix list used when no list file is configured
+const BUILTIN_LIST: &str = include_str!("public_suffix_list.dat");
+
+/// Marker of the private section of the list ("github.io", "herokuapp.com", ...)
+const PRIVATE_SECTION: &str = "===BEGIN PRIVATE DOMAINS===";
+
+/// The bundled list, parsed once
+static BUILTIN: LazyLock<PublicSuffixes> = LazyLock::new(|| {
+    PublicSuffixes::parse(BUILTIN_LIST, true).expect("built-in public suffix list is valid")
+});
+
+/// Public suffix rules and site overrides
+#[derive(Clone)]
+pub struct PublicSuffixes {
+    list: List,
+    /// Domains that are sites of their own, as host keys
+    overrides: Vec<String>,
+}
+
+impl std::fmt::Debug for PublicSuffixes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublicSuffixes")
+            .field("overrides", &self.overrides)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PublicSuffixes {
+    /// Bundled list, private suffixes included
+    pub fn builtin() -> Self {
+        BUILTIN.clone()
+    }
+
+    /// Bundled list without the private suffixes
+    pub fn builtin_icann() -> Self {
+        Self::parse(BUILTIN_LIST, false).expect("built-in public suffix list is valid")
+    }
+
+    /// Load a list in the publicsuffix.org format, e.g. `public_suffix_list.dat`
+    pub fn load(path: &Path, include_private: bool) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read public suffix list {}", path.display()))?;
+        Self::parse(&text, include_private)
+    }
+
+    /// Parse list text; without `include_private` only the ICANN section is used
+    pub fn parse(text: &str, include_private: bool) -> Result<Self> {
+        let text = match text.find(PRIVATE_SECTION) {
+            Some(index) if !include_private => &text[..index],
+            _ => text,
+        };
+        let list = text
+            .parse::<List>()
+            .map_err(|e| anyhow!("Invalid public suffix list: {}", e))?;
+        Ok(Self {
+            list,
+            overrides: Vec::new(),
+        })
+    }
+
+    /// Treat each domain, with everything under it, as one site of its own
+    pub fn with_overrides<I, S>(mut self, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.overrides
+            .extend(domains.into_iter().map(|domain| host_key(domain.as_ref())));
+        self
+    }
+
+    /// Registrable domain of `host`, in host key form
+    ///
+    /// The most specific site override containing the host wins. IP addresses,
+    /// public suffixes and single-label hosts are their own domain.
+    pub fn registrable_domain(&self, host: &str) -> String {
+        let host = host_key(host);
+        if let Some(site) = self
+            .overrides
+            .iter()
+            .filter(|site| host == **site || host.ends_with(&format!(".{}", site)))
+            .max_by_key(|site| site.len())
+        {
+            return site.clone();
+        }
+        if host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok()
+        {
+            return host;
+        }
+        match self.list.domain(host.as_bytes()) {
+            Some(domain) => String::from_utf8_lossy(domain.as_bytes()).into_owned(),
+            None => host,
+        }
+    }
+
+    /// Whether two hosts belong to the same registrable domain
+    pub fn same_site(&self, a: &str, b: &str) -> bool {
+        self.registrable_domain(a) == self.registrable_domain(b)
+    }
+}
+
+impl Default for PublicSuffixes {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Registrable domain of `host` under the bundled list, without site overrides
+pub fn registrable_domain(host: &str) -> String {
+    BUILTIN.registrable_domain(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registrable_domains_follow_the_suffix_list() {
+        let suffixes = PublicSuffixes::builtin();
+        assert_eq!(
+            suffixes.registrable_domain("WWW.Example.com."),
+            "example.com"
+        );
+        assert_eq!(
+            suffixes.registrable_domain("shop.example.co.uk"),
+            "example.co.uk"
+        );
+        assert_eq!(
+            suffixes.registrable_domain("a.b.bücher.example"),
+            "xn--bcher-kva.example"
+        );
+        assert_eq!(suffixes.registrable_domain("co.uk"), "co.uk");
+        assert_eq!(suffixes.registrable_domain("shop.site.ck"), "shop.site.ck");
+        assert_eq!(suffixes.registrable_domain("a.www.ck"), "www.ck");
+        assert_eq!(suffixes.registrable_domain("localhost"), "localhost");
+        assert_eq!(suffixes.registrable_domain("192.168.0.1"), "192.168.0.1");
+        assert_eq!(suffixes.registrable_domain("[::1]"), "[::1]");
+        assert!(suffixes.same_site("www.example.co.uk", "example.co.uk"));
+        assert!(!suffixes.same_site("alice.github.io", "bob.github.io"));
+
+        let icann = PublicSuffixes::builtin_icann();
+        assert!(icann.same_site("alice.github.io", "bob.github.io"));
+
+        let overridden = PublicSuffixes::builtin().with_overrides(["Teams.Example.com"]);
+        assert_eq!(
+            overridden.registrable_domain("wiki.teams.example.com"),
+            "teams.example.com"
+        );
+        assert!(!overridden.same_site("teams.example.com", "www.example.com"));
+        assert!(PublicSuffixes::parse("// no rules\n", true).is_err());
+
+        // Multi-label suffixes beyond the common ones
+        assert_eq!(
+            suffixes.registrable_domain("shop.example.com.vn"),
+            "example.com.vn"
+        );
+        assert_eq!(
+            suffixes.registrable_domain("www.sklep.com.pl"),
+            "sklep.com.pl"
+        );
+        assert_eq!(suffixes.registrable_domain("news.toko.co.id"), "toko.co.id");
+        assert!(!suffixes.same_site("alpha.s3.amazonaws.com", "beta.s3.amazonaws.com"));
+    }
+}
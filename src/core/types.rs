@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Instant;
 use url::Url;
 use whatlang::Lang;
 
+use super::idn::host_key;
+
 /// Type aliases for optional types - building blocks for common patterns
 pub type OptionInstant = Option<Instant>;
 pub type OptionString = Option<String>;
@@ -76,6 +79,28 @@ pub mod url_serde {
         let s = String::deserialize(deserializer)?;
         Url::parse(&s).map_err(serde::de::Error::custom)
     }
+
+    /// Same for `Option<Url>`
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use url::Url;
+
+        pub fn serialize<S>(url: &Option<Url>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            url.as_ref().map(Url::as_str).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Url>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<String>::deserialize(deserializer)?
+                .map(|s| Url::parse(&s).map_err(serde::de::Error::custom))
+                .transpose()
+        }
+    }
 }
 
 /// Enhanced language type with additional utility methods
@@ -114,6 +139,20 @@ impl LangType {
         }
     }
 
+    /// Convert a BCP 47 language tag such as "en-GB" or "zh_Hant", by primary subtag
+    pub fn from_language_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(LangType::Eng),
+            "zh" => Some(LangType::Cmn),
+            "fr" => Some(LangType::Fra),
+            "de" => Some(LangType::Deu),
+            "ja" => Some(LangType::Jpn),
+            "ko" => Some(LangType::Kor),
+            _ => None,
+        }
+    }
+
     /// Convert from whatlang::Lang to LangType
     pub fn from_detected_lang(lang: Lang) -> Option<Self> {
         match lang {
@@ -225,16 +264,196 @@ pub enum CrawlResult {
     },
 }
 
+/// How a processed URL ended, for error rates and failure streaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlOutcome {
+    Crawled,
+    /// Left out on purpose (visited, filtered, unchanged); neither a success nor
+    /// an error
+    Skipped,
+    Failed,
+}
+
 /// Reasons why a URL might be skipped
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    remote = "Self",
+    tag = "kind",
+    content = "detail",
+    rename_all = "snake_case"
+)]
 pub enum SkipReason {
     AlreadyVisited,
+    /// Fetched by a previous session within the re-crawl window
+    VisitedPreviously,
+    /// Failed permanently in a previous session, with that failure
+    FailedPreviously(String),
     RobotsBlocked,
+    /// Rejected by the live target-word filter
     ContentFiltered,
-    LanguageNotAccepted,
+    /// Rejected by a content pipeline stage
+    PipelineRejected {
+        stage: String,
+        reason: String,
+    },
+    /// The text was detected (ISO 639-3 code) in a language outside the accepted list
+    ///
+    /// Reports written before the language was recorded hold the bare
+    /// `{"kind":"language_not_accepted"}`, read as `und` with no confidence.
+    LanguageNotAccepted {
+        language: String,
+        confidence: f64,
+    },
     NoContent,
+    /// Body or text identical to the previous crawl's
+    Unchanged,
+    TooShort {
+        word_count: usize,
+        min_words: usize,
+    },
+    /// A consent wall or JavaScript-required page instead of content
+    Interstitial(String),
+    /// The body is a binary format (sniffed MIME type) whatever its declared type;
+    /// the URL may serve a document in a later crawl, so only this session skips it
+    BinaryContent(String),
     ExtensionBlocked(String),
     DomainBlocked(String),
+    /// The host is, or resolves to, an address SSRF protection refuses
+    AddressBlocked(String),
+    /// Excluded by the link filter, with the link processor's reason
+    LinkFiltered(String),
+    /// Outside the scope of its seed or of the navigation policy
+    OutOfScope(String),
+    /// The path already used its query-parameter variant budget
+    ParamBudgetExceeded(String),
+    /// The pagination chain already reached its page budget
+    PaginationBudgetExceeded(String),
+    /// The registrable domain already queued its URL budget
+    DomainBudgetExceeded(String),
+    /// The crawler already fetched its total URL budget (`max_total_urls`)
+    UrlBudgetExceeded(usize),
+    QueueFull,
+    /// The link fell outside the random sample of its registrable domain
+    SampledOut {
+        domain: String,
+        percent: f64,
+    },
+    /// The seed failed several scheduled runs in a row
+    SeedBlacklisted,
+    /// The seed was dead, unreachable or blocked in the precheck
+    SeedFailedPrecheck(String),
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::AlreadyVisited => write!(f, "already visited"),
+            SkipReason::VisitedPreviously => {
+                write!(
+                    f,
+                    "visited by a previous session within the re-crawl window"
+                )
+            }
+            SkipReason::FailedPreviously(failure) => {
+                write!(f, "failed permanently in a previous session: {}", failure)
+            }
+            SkipReason::RobotsBlocked => write!(f, "blocked by robots.txt"),
+            SkipReason::ContentFiltered => write!(f, "content filtered"),
+            SkipReason::PipelineRejected { stage, reason } => {
+                write!(f, "rejected by {} stage: {}", stage, reason)
+            }
+            SkipReason::LanguageNotAccepted {
+                language,
+                confidence,
+            } => write!(
+                f,
+                "language {} not accepted (confidence {:.2})",
+                language, confidence
+            ),
+            SkipReason::NoContent => write!(f, "no content"),
+            SkipReason::Unchanged => write!(f, "content unchanged since previous crawl"),
+            SkipReason::TooShort {
+                word_count,
+                min_words,
+            } => write!(f, "{} words < {} minimum", word_count, min_words),
+            SkipReason::Interstitial(interstitial) => {
+                write!(f, "interstitial page: {}", interstitial)
+            }
+            SkipReason::BinaryContent(mime) => write!(f, "binary content sniffed as {}", mime),
+            SkipReason::ExtensionBlocked(extension) => {
+                write!(f, "extension {} blocked", extension)
+            }
+            SkipReason::DomainBlocked(domain) => write!(f, "domain {} blocked", domain),
+            SkipReason::LinkFiltered(reason) => write!(f, "link filtered: {}", reason),
+            SkipReason::OutOfScope(scope) => write!(f, "outside {}", scope),
+            SkipReason::ParamBudgetExceeded(path) => {
+                write!(f, "query-parameter budget of {} exceeded", path)
+            }
+            SkipReason::PaginationBudgetExceeded(chain) => {
+                write!(f, "page budget of pagination chain {} exceeded", chain)
+            }
+            SkipReason::DomainBudgetExceeded(domain) => {
+                write!(f, "URL budget of {} exceeded", domain)
+            }
+            SkipReason::UrlBudgetExceeded(max) => {
+                write!(f, "total URL budget of {} exceeded", max)
+            }
+            SkipReason::AddressBlocked(violation) => {
+                write!(f, "refused by SSRF protection: {}", violation)
+            }
+            SkipReason::QueueFull => write!(f, "queue full"),
+            SkipReason::SampledOut { domain, percent } => {
+                write!(f, "outside the {}% sample of {}", percent, domain)
+            }
+            SkipReason::SeedBlacklisted => write!(f, "seed blacklisted after repeated failures"),
+            SkipReason::SeedFailedPrecheck(status) => write!(f, "seed failed precheck: {}", status),
+        }
+    }
+}
+
+impl Serialize for SkipReason {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SkipReason::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SkipReason {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        // `LanguageNotAccepted` was a unit variant before it carried the language
+        if value.get("kind").and_then(|kind| kind.as_str()) == Some("language_not_accepted")
+            && let Some(fields) = value.as_object_mut()
+        {
+            fields
+                .entry("detail")
+                .or_insert_with(|| serde_json::json!({ "language": "und", "confidence": 0.0 }));
+        }
+        SkipReason::deserialize(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Request stage that ran past its timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeoutStage {
+    /// Establishing the TCP/TLS connection
+    Connect,
+    /// Waiting for response headers after the request was sent
+    FirstByte,
+    /// The whole request including the body download
+    Download,
+    /// Text extraction and the content pipeline
+    ContentProcessing,
+}
+
+impl std::fmt::Display for TimeoutStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeoutStage::Connect => write!(f, "connect"),
+            TimeoutStage::FirstByte => write!(f, "first byte"),
+            TimeoutStage::Download => write!(f, "total download"),
+            TimeoutStage::ContentProcessing => write!(f, "content processing"),
+        }
+    }
 }
 
 /// Error severity levels for better error handling
@@ -327,6 +546,160 @@ pub struct TaskState {
     pub error_message: Option<String>,
 }
 
+/// Where a task's URL was discovered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskProvenance {
+    #[serde(with = "url_serde")]
+    pub parent_url: Url,
+    /// Relevance score of the parent page, if one was computed
+    pub parent_relevance: Option<f32>,
+    pub discovered_at: std::time::SystemTime,
+    /// How the link was found, e.g. a link category or "hreflang"
+    pub link_category: Option<String>,
+}
+
+/// Depth and domain limits a seed sets for itself and every link crawled from it
+///
+/// A scope only narrows the crawl-wide limits of `ExtensiveConfig`; leave those open
+/// (e.g. `CrawlDepth::Unlimited`) to let each seed decide how far it goes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedScope {
+    /// Host of the seed the links are judged against
+    pub domain: String,
+    /// Links at most this many hops from the seed are followed; `None` for no limit
+    pub max_depth: Option<usize>,
+    /// Also follow links to subdomains of `domain`
+    pub include_subdomains: bool,
+}
+
+impl SeedScope {
+    /// Scope limited to the seed's own host, without a depth limit
+    pub fn for_seed(seed: &Url) -> Self {
+        Self {
+            domain: host_key(seed.host_str().unwrap_or_default()),
+            max_depth: None,
+            include_subdomains: false,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_subdomains(mut self) -> Self {
+        self.include_subdomains = true;
+        self
+    }
+
+    /// Whether a link at `depth` (seeds are at depth 0) is within the scope
+    pub fn allows_depth(&self, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max_depth| depth <= max_depth)
+    }
+
+    /// Whether `url` is on the seed's host, or one of its subdomains if included
+    pub fn allows_url(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(host_key) else {
+            return false;
+        };
+        host == self.domain
+            || (self.include_subdomains
+                && host
+                    .strip_suffix(self.domain.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.')))
+    }
+}
+
+/// Country a URL is meant to be fetched from, as an ISO 3166-1 alpha-2 code ("KR")
+///
+/// Geo-restricted sites serve different content, or none, depending on where the
+/// request comes from; a task's region picks the proxies it is fetched through.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Region(String);
+
+/// Country-code TLDs used as generic domains, which say nothing about the audience
+const GENERIC_CCTLDS: &[&str] = &["ai", "cc", "co", "fm", "io", "me", "tv", "ws"];
+
+impl Region {
+    /// Region of a two-letter country code, case-insensitively
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim();
+        (code.len() == 2 && code.bytes().all(|byte| byte.is_ascii_alphabetic()))
+            .then(|| Self(code.to_ascii_uppercase()))
+    }
+
+    /// Region subtag of a language tag, e.g. "KR" in `ko-KR`; the first tag of a
+    /// `Content-Language` list counts
+    pub fn from_language_tag(tag: &str) -> Option<Self> {
+        let tag = tag.split(',').next()?.trim();
+        tag.split(['-', '_']).skip(1).find_map(Self::parse)
+    }
+
+    /// Region of the host's country-code TLD (`.kr`; `.uk` is GB)
+    pub fn from_host(host: &str) -> Option<Self> {
+        let host = host_key(host);
+        let tld = host.rsplit('.').next()?;
+        if host == tld || GENERIC_CCTLDS.contains(&tld) {
+            return None;
+        }
+        match tld {
+            "uk" => Self::parse("gb"),
+            tld => Self::parse(tld),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Region {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        Self::parse(&code).ok_or_else(|| format!("'{}' is not a two-letter country code", code))
+    }
+}
+
+impl From<Region> for String {
+    fn from(region: Region) -> Self {
+        region.0
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Position of a page in a chain of paginated pages (`rel="next"`, `?page=2`, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaginationChain {
+    /// URL of the page the chain was first followed from
+    pub id: String,
+    /// 1 for that first page, 2 for the page after it, ...
+    pub page: usize,
+}
+
+impl PaginationChain {
+    /// Chain position of the page after `current`, starting a chain at `current` if
+    /// it is not part of one yet
+    pub fn following(current: &CrawlTask) -> Self {
+        match &current.pagination {
+            Some(chain) => Self {
+                id: chain.id.clone(),
+                page: chain.page + 1,
+            },
+            None => Self {
+                id: current.url.to_string(),
+                page: 2,
+            },
+        }
+    }
+}
+
 /// A crawl task in the message queue - composed of building blocks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlTask {
@@ -340,7 +713,37 @@ pub struct CrawlTask {
     pub retry_count: u32, // Added for backward compatibility
     pub depth: usize,
     pub error_message: OptionString,
+    /// Errors of all failed attempts, oldest first
+    #[serde(default)]
+    pub error_history: Vec<String>,
     pub user_agent: String, // Added for backward compatibility
+    /// Parent page context; `None` for seeds
+    #[serde(default)]
+    pub provenance: Option<TaskProvenance>,
+    /// Seed group the task belongs to, inherited by discovered links
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Caller-supplied key/value pairs copied into the task's results, inherited by discovered links
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Depth and domain limits of the task's seed, inherited by discovered links
+    #[serde(default)]
+    pub scope: Option<SeedScope>,
+    /// Pagination chain the task's page belongs to, set for pagination links
+    #[serde(default)]
+    pub pagination: Option<PaginationChain>,
+    /// Region to fetch the task from, inherited by discovered links; `None` lets
+    /// the geo proxy selector infer one
+    #[serde(default)]
+    pub region: Option<Region>,
+    /// Link-graph importance when the queue scores links, in `[0, 1)`; orders
+    /// tasks of equal priority
+    #[serde(default)]
+    pub importance: f64,
+    /// Relevance score of the task's page once crawled, when a relevance stage
+    /// ran; recorded in the provenance of the links found on it
+    #[serde(default)]
+    pub relevance: Option<f32>,
 
     // Building blocks for composition - timing is handled by TaskTiming
     #[serde(skip)]
@@ -363,8 +766,17 @@ impl CrawlTask {
             retry_count: 0, // Initialize to 0
             depth: 0,       // Default depth
             error_message: None,
+            error_history: Vec::new(),
             user_agent: "rust-web-crawler/1.0".to_string(), // Default user agent
-            timing: TaskTiming::new(),                      // Use building block
+            provenance: None,
+            group: None,
+            metadata: BTreeMap::new(),
+            scope: None,
+            pagination: None,
+            region: None,
+            importance: 0.0,
+            relevance: None,
+            timing: TaskTiming::new(), // Use building block
         }
     }
 
@@ -386,11 +798,41 @@ impl CrawlTask {
             retry_count: 0, // Initialize to 0
             depth,
             error_message: None,
+            error_history: Vec::new(),
             user_agent: "rust-web-crawler/1.0".to_string(), // Default user agent
-            timing: TaskTiming::new(),                      // Use building block
+            provenance: None,
+            group: None,
+            metadata: BTreeMap::new(),
+            scope: None,
+            pagination: None,
+            region: None,
+            importance: 0.0,
+            relevance: None,
+            timing: TaskTiming::new(), // Use building block
         }
     }
 
+    /// Create a task for a link discovered on `parent`'s page, one level deeper
+    pub fn discovered_from(
+        parent: &CrawlTask,
+        url: Url,
+        priority: TaskPriority,
+        link_category: Option<String>,
+    ) -> Self {
+        let mut task = Self::new_with_depth(url, priority, parent.max_retries, parent.depth + 1);
+        task.provenance = Some(TaskProvenance {
+            parent_url: parent.url.clone(),
+            parent_relevance: parent.relevance,
+            discovered_at: std::time::SystemTime::now(),
+            link_category,
+        });
+        task.group = parent.group.clone();
+        task.metadata = parent.metadata.clone();
+        task.scope = parent.scope.clone();
+        task.region = parent.region.clone();
+        task
+    }
+
     pub fn can_retry(&self) -> bool {
         self.attempt_count < self.max_retries && self.status != TaskStatus::Dead
     }
@@ -399,9 +841,19 @@ impl CrawlTask {
         self.timing.is_ready_for_retry()
     }
 
+    /// Record a failure that rules out retries
+    pub fn mark_permanently_failed(&mut self, error: String) {
+        self.attempt_count += 1;
+        self.timing.mark_attempt();
+        self.error_history.push(error.clone());
+        self.error_message = Some(error);
+        self.status = TaskStatus::Dead;
+    }
+
     pub fn mark_failed(&mut self, error: String, retry_delay: Option<std::time::Duration>) {
         self.attempt_count += 1;
         self.timing.mark_attempt();
+        self.error_history.push(error.clone());
         self.error_message = Some(error);
 
         if self.can_retry() {
@@ -459,4 +911,16 @@ pub struct QueueStats {
     pub counts: TaskCounts,
     #[serde(flatten)]
     pub performance: PerformanceMetrics,
+    /// Task counts per seed group, by group name
+    #[serde(default)]
+    pub groups: Vec<GroupQueueStats>,
+}
+
+/// Queue statistics of one seed group
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GroupQueueStats {
+    pub group: String,
+    pub weight: u32,
+    #[serde(flatten)]
+    pub counts: TaskCounts,
 }
@@ -46,8 +46,9 @@ impl ErrorUtils {
     /// Normalize URL for deduplication
     pub fn normalize_url(url: &str) -> String {
         if let Ok(mut parsed) = url::Url::parse(url) {
-            // Remove fragment
+            // Remove fragment and the trailing dot of the host
             parsed.set_fragment(None);
+            super::idn::normalize_host(&mut parsed);
 
             // Sort query parameters for consistent hashing
             if let Some(query) = parsed.query() {
@@ -0,0 +1,54 @@
+/// URL admission checks of the crawler
+///
+/// The avoided-extension filter and the total URL budget decide whether a URL is
+/// fetched at all. `WebCrawler` applies them before fetching and `CrawlPolicy`
+/// reports them in a dry run, so both decide the same way.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use url::Url;
+
+/// The entry of `extensions` (`avoid_url_extensions`) the last path segment of
+/// `url` ends with
+pub(crate) fn avoided_extension<'a>(url: &Url, extensions: &'a [String]) -> Option<&'a str> {
+    let last_segment = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or("");
+    extensions
+        .iter()
+        .find(|extension| last_segment.ends_with(extension.as_str()))
+        .map(String::as_str)
+}
+
+/// Total number of URLs a crawler fetches (`max_total_urls`)
+#[derive(Debug)]
+pub(crate) struct UrlBudget {
+    max: usize,
+    used: AtomicUsize,
+}
+
+impl UrlBudget {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            max,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Whether another URL fits, without using it up
+    pub(crate) fn has_room(&self) -> bool {
+        self.used.load(Ordering::Relaxed) < self.max
+    }
+
+    /// Use up one URL of the budget; false once it is spent
+    pub(crate) fn admit(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                (used < self.max).then_some(used + 1)
+            })
+            .is_ok()
+    }
+}
@@ -0,0 +1,176 @@
+/// Crawling through paginated JSON APIs
+///
+/// Many sites list their content through a JSON endpoint rather than HTML listing
+/// pages. An `ApiSeed` names such an endpoint with two JSONPath expressions: one
+/// selecting the URLs of the listed items, one selecting the next page (either a
+/// URL or a cursor passed back in a query parameter). `WebCrawler::walk_api`
+/// follows the pages and returns the item URLs, which are then crawled as seeds.
+use anyhow::{Error, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use url::Url;
+
+use crate::core::types::url_serde;
+
+/// A JSON endpoint whose pages list the URLs to crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSeed {
+    /// First page of the listing
+    #[serde(with = "url_serde")]
+    pub endpoint: Url,
+    /// JSONPath of the item URLs on a page, e.g. `$.data[*].url`
+    pub item_url_path: String,
+    /// JSONPath of the next page's URL, or of the cursor when `cursor_param` is set;
+    /// `None` reads the first page only
+    pub next_path: Option<String>,
+    /// Query parameter of the endpoint the cursor is sent in, e.g. `cursor`
+    pub cursor_param: Option<String>,
+    /// Pages fetched at most
+    pub max_pages: usize,
+}
+
+impl ApiSeed {
+    pub fn new(endpoint: Url, item_url_path: impl Into<String>) -> Self {
+        Self {
+            endpoint,
+            item_url_path: item_url_path.into(),
+            next_path: None,
+            cursor_param: None,
+            max_pages: 100,
+        }
+    }
+
+    /// Follow the page URL found at `next_path`
+    pub fn with_next_page(mut self, next_path: impl Into<String>) -> Self {
+        self.next_path = Some(next_path.into());
+        self
+    }
+
+    /// Send the cursor found at `next_path` back in the `param` query parameter
+    pub fn with_cursor(mut self, next_path: impl Into<String>, param: impl Into<String>) -> Self {
+        self.next_path = Some(next_path.into());
+        self.cursor_param = Some(param.into());
+        self
+    }
+
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Check that both JSONPath expressions parse
+    pub fn validate(&self) -> Result<(), Error> {
+        parse_path(&self.item_url_path)?;
+        if let Some(next_path) = &self.next_path {
+            parse_path(next_path)?;
+        }
+        Ok(())
+    }
+
+    /// Item URLs listed on `page`, resolved against the page's URL
+    ///
+    /// Values that are not strings or not URLs are skipped.
+    pub fn item_urls(&self, page: &Value, page_url: &Url) -> Result<Vec<Url>, Error> {
+        Ok(parse_path(&self.item_url_path)?
+            .query(page)
+            .iter()
+            .filter_map(|value| value.as_str())
+            .filter_map(|href| page_url.join(href.trim()).ok())
+            .collect())
+    }
+
+    /// URL of the page after `page`, `None` at the end of the listing
+    ///
+    /// A missing, null or empty next value ends the listing.
+    pub fn next_page(&self, page: &Value, page_url: &Url) -> Result<Option<Url>, Error> {
+        let Some(next_path) = &self.next_path else {
+            return Ok(None);
+        };
+        let next = match parse_path(next_path)?.query(page).first() {
+            Some(Value::String(next)) if !next.trim().is_empty() => next.trim().to_string(),
+            Some(Value::Number(next)) => next.to_string(),
+            _ => return Ok(None),
+        };
+        match &self.cursor_param {
+            Some(param) => {
+                let mut url = self.endpoint.clone();
+                let query: Vec<(String, String)> = self
+                    .endpoint
+                    .query_pairs()
+                    .filter(|(key, _)| key != param)
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                url.query_pairs_mut()
+                    .clear()
+                    .extend_pairs(query)
+                    .append_pair(param, &next);
+                Ok(Some(url))
+            }
+            None => Ok(Some(page_url.join(&next)?)),
+        }
+    }
+}
+
+/// What walking an API listing found
+#[derive(Debug, Clone, Default)]
+pub struct ApiWalk {
+    pub pages: usize,
+    /// Item URLs in listing order, without duplicates
+    pub item_urls: Vec<Url>,
+    /// Why the walk stopped before the end of the listing, if it did
+    pub truncated: Option<String>,
+}
+
+fn parse_path(path: &str) -> Result<JsonPath, Error> {
+    JsonPath::parse(path).map_err(|e| anyhow!("Invalid JSONPath '{}': {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cursor_and_next_url_pagination() {
+        let endpoint = Url::parse("https://api.example/v1/posts?limit=2&cursor=old").unwrap();
+        let seed = ApiSeed::new(endpoint.clone(), "$.data[*].links.html")
+            .with_cursor("$.meta.next_cursor", "cursor");
+        seed.validate().unwrap();
+
+        let page = json!({
+            "data": [
+                {"links": {"html": "/posts/1"}},
+                {"links": {"html": "https://blog.example/posts/2"}},
+                {"links": {"html": 3}}
+            ],
+            "meta": {"next_cursor": "abc 123"}
+        });
+        let items = seed.item_urls(&page, &endpoint).unwrap();
+        assert_eq!(
+            items.iter().map(Url::as_str).collect::<Vec<_>>(),
+            [
+                "https://api.example/posts/1",
+                "https://blog.example/posts/2"
+            ]
+        );
+        assert_eq!(
+            seed.next_page(&page, &endpoint).unwrap().unwrap().as_str(),
+            "https://api.example/v1/posts?limit=2&cursor=abc+123"
+        );
+        let last = json!({"data": [], "meta": {"next_cursor": null}});
+        assert_eq!(seed.next_page(&last, &endpoint).unwrap(), None);
+
+        let linked = ApiSeed::new(endpoint.clone(), "$.items[*]").with_next_page("$.next");
+        let page = json!({"items": [], "next": "/v1/posts?page=2"});
+        assert_eq!(
+            linked
+                .next_page(&page, &endpoint)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "https://api.example/v1/posts?page=2"
+        );
+        assert!(ApiSeed::new(endpoint, "$.data[").validate().is_err());
+    }
+}
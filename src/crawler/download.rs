@@ -0,0 +1,282 @@
+/// Resumable asset and document downloads
+///
+/// Large files (PDFs, archives, media) are streamed to `<name>.part` next to their
+/// final path and renamed once complete. `AssetManifest` keeps one `AssetEntry` per
+/// URL in `assets.json` of the download directory, recording how many bytes have
+/// been written and the validators the server sent. When a download is interrupted
+/// and the server advertised `Accept-Ranges: bytes`, the next attempt asks for the
+/// rest of the file with `Range` and `If-Range`; a server answering with the full
+/// body instead (the file changed, or ranges are not honoured) restarts the file.
+/// The manifest is written on the blocking pool, and only synced to disk once a
+/// download ends; progress saves along the way leave flushing to the OS.
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use url::Url;
+
+use crate::storage::durable::{FsyncPolicy, write_atomic};
+use crate::storage::url_to_filename;
+
+/// File name of the manifest inside a download directory
+pub const ASSET_MANIFEST_FILE: &str = "assets.json";
+
+/// Suffix of files still being downloaded
+pub const PARTIAL_SUFFIX: &str = "part";
+
+/// Bytes streamed between manifest saves while downloading
+pub(crate) const MANIFEST_SAVE_INTERVAL_BYTES: u64 = 1 << 20;
+
+/// Bookkeeping for one downloaded or partially downloaded URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetEntry {
+    pub url: String,
+    /// Final path of the file, relative to the download directory
+    pub file: PathBuf,
+    /// Bytes written to the partial file, or the file size once complete
+    pub bytes_received: u64,
+    /// Full size of the file when the server announced it
+    pub total_bytes: Option<u64>,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// The server advertised `Accept-Ranges: bytes`
+    pub accepts_ranges: bool,
+    /// Number of requests that continued the file instead of starting over
+    pub resumed: u32,
+    pub complete: bool,
+    pub updated_at: SystemTime,
+}
+
+impl AssetEntry {
+    pub fn new(url: &Url) -> Self {
+        Self {
+            url: url.to_string(),
+            file: PathBuf::from(asset_file_name(url)),
+            bytes_received: 0,
+            total_bytes: None,
+            content_type: None,
+            etag: None,
+            last_modified: None,
+            accepts_ranges: false,
+            resumed: 0,
+            complete: false,
+            updated_at: SystemTime::now(),
+        }
+    }
+
+    /// Path of the partial file, relative to the download directory
+    pub fn partial_file(&self) -> PathBuf {
+        let mut name = self.file.clone().into_os_string();
+        name.push(".");
+        name.push(PARTIAL_SUFFIX);
+        PathBuf::from(name)
+    }
+
+    /// Offset to request the rest of the file from, if the download can be resumed
+    ///
+    /// `partial_len` is the size of the partial file on disk; bytes past
+    /// `bytes_received` were never recorded and are discarded by the caller.
+    pub fn resume_offset(&self, partial_len: u64) -> Option<u64> {
+        let resumable = !self.complete
+            && self.accepts_ranges
+            && self.bytes_received > 0
+            && partial_len >= self.bytes_received
+            && self.if_range().is_some()
+            && self
+                .total_bytes
+                .is_none_or(|total| self.bytes_received < total);
+        resumable.then_some(self.bytes_received)
+    }
+
+    /// Validator for `If-Range`: a strong ETag, else `Last-Modified`
+    ///
+    /// Without one the server could append bytes of a changed file, so such
+    /// downloads always start over.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+
+    /// Forget the bytes received so far, keeping the URL and file name
+    pub fn restart(&mut self) {
+        self.bytes_received = 0;
+        self.total_bytes = None;
+        self.complete = false;
+    }
+}
+
+/// Download bookkeeping of one directory, persisted as `assets.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetManifest {
+    #[serde(skip)]
+    dir: PathBuf,
+    entries: BTreeMap<String, AssetEntry>,
+}
+
+impl AssetManifest {
+    /// Manifest of `dir`, empty when the directory has none yet
+    pub fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join(ASSET_MANIFEST_FILE);
+        let mut manifest = if path.exists() {
+            let text = std::fs::read_to_string(&path)?;
+            serde_json::from_str::<Self>(&text)
+                .with_context(|| format!("Invalid asset manifest {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        manifest.dir = dir.to_path_buf();
+        Ok(manifest)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write the manifest atomically, synced to disk
+    pub async fn save(&self) -> Result<()> {
+        self.save_with(FsyncPolicy::default()).await
+    }
+
+    /// Write the manifest atomically with `policy`, on the blocking pool
+    pub(crate) async fn save_with(&self, policy: FsyncPolicy) -> Result<()> {
+        let dir = self.dir.clone();
+        let json = serde_json::to_vec_pretty(self)?;
+        tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&dir)?;
+            write_atomic(&dir.join(ASSET_MANIFEST_FILE), &json, policy)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r)
+    }
+
+    pub fn get(&self, url: &Url) -> Option<&AssetEntry> {
+        self.entries.get(url.as_str())
+    }
+
+    pub fn insert(&mut self, entry: AssetEntry) {
+        self.entries.insert(entry.url.clone(), entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AssetEntry> {
+        self.entries.values()
+    }
+
+    /// Entries of downloads that were interrupted
+    pub fn partial(&self) -> impl Iterator<Item = &AssetEntry> {
+        self.entries.values().filter(|entry| !entry.complete)
+    }
+
+    /// Absolute path of `file`, a path relative to the download directory
+    pub fn path_of(&self, file: &Path) -> PathBuf {
+        self.dir.join(file)
+    }
+}
+
+/// Parsed `Content-Range: bytes <start>-<end>/<total>` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    pub start: u64,
+    pub end: u64,
+    /// `None` when the server sent `*`
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid Content-Range '{}'", value);
+        let range = value.trim().strip_prefix("bytes ").ok_or_else(invalid)?;
+        let (span, total) = range.split_once('/').ok_or_else(invalid)?;
+        let (start, end) = span.split_once('-').ok_or_else(invalid)?;
+        let start: u64 = start.trim().parse().map_err(|_| invalid())?;
+        let end: u64 = end.trim().parse().map_err(|_| invalid())?;
+        let total = match total.trim() {
+            "*" => None,
+            total => Some(total.parse::<u64>().map_err(|_| invalid())?),
+        };
+        if end < start || total.is_some_and(|total| end >= total) {
+            return Err(invalid());
+        }
+        Ok(Self { start, end, total })
+    }
+}
+
+/// File name for a URL: the readable URL stem plus the path's extension
+fn asset_file_name(url: &Url) -> String {
+    let stem = url_to_filename(url.as_str());
+    let extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|extension| {
+            !extension.is_empty()
+                && extension.len() <= 8
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    match extension {
+        Some(extension) => format!("{}.{}", stem, extension.to_ascii_lowercase()),
+        None => stem,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_partial_downloads_resume_from_the_recorded_offset() {
+        let url = Url::parse("https://example.com/files/Report.PDF?v=2").unwrap();
+        let mut entry = AssetEntry::new(&url);
+        assert!(entry.file.to_string_lossy().ends_with(".pdf"));
+        assert!(
+            entry
+                .partial_file()
+                .to_string_lossy()
+                .ends_with(".pdf.part")
+        );
+
+        entry.bytes_received = 4096;
+        entry.total_bytes = Some(10_000);
+        entry.etag = Some("\"abc\"".to_string());
+        assert_eq!(entry.resume_offset(4096), None);
+        entry.accepts_ranges = true;
+        assert_eq!(entry.resume_offset(4096), Some(4096));
+        assert_eq!(entry.resume_offset(5000), Some(4096));
+        assert_eq!(entry.resume_offset(100), None);
+        assert_eq!(entry.if_range(), Some("\"abc\""));
+
+        entry.etag = Some("W/\"abc\"".to_string());
+        assert_eq!(entry.resume_offset(4096), None);
+        entry.last_modified = Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string());
+        assert_eq!(entry.if_range(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(entry.resume_offset(4096), Some(4096));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = AssetManifest::open(dir.path()).unwrap();
+        manifest.insert(entry.clone());
+        manifest.save().await.unwrap();
+        let reopened = AssetManifest::open(dir.path()).unwrap();
+        assert_eq!(reopened.get(&url), Some(&entry));
+        assert_eq!(reopened.partial().count(), 1);
+
+        entry.restart();
+        assert_eq!(entry.resume_offset(4096), None);
+
+        assert_eq!(
+            ContentRange::parse("bytes 4096-9999/10000").unwrap(),
+            ContentRange {
+                start: 4096,
+                end: 9999,
+                total: Some(10_000)
+            }
+        );
+        assert_eq!(ContentRange::parse("bytes 0-9/*").unwrap().total, None);
+        assert!(ContentRange::parse("bytes 10-5/100").is_err());
+        assert!(ContentRange::parse("bytes */100").is_err());
+    }
+}
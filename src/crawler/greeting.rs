@@ -0,0 +1,250 @@
+/// Once-per-domain greeting before deep URLs
+///
+/// With a `GreetingConfig`, the first URL of every host waits until the host's
+/// homepage was fetched once; URLs of the host crawled meanwhile wait for the same
+/// greeting instead of sending their own. The outcome is kept for the rest of the
+/// crawl, so a host that blocked its greeting is known before any deep URL is sent.
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OnceCell};
+use url::Url;
+
+use super::shards::ShardRouter;
+
+tokio::task_local! {
+    /// User-Agent of the request being sent, which picks its cookie jar
+    static REQUEST_IDENTITY: String;
+}
+
+/// How a host answered its greeting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum GreetingOutcome {
+    /// The homepage answered normally, or was not fetched (the URL was the
+    /// homepage itself, or robots.txt disallows it)
+    Welcomed,
+    /// The homepage answered with a blocking status or a bot-detection page
+    Blocked { reason: String },
+    /// The homepage could not be fetched; the host's URLs still go ahead
+    Failed { error: String },
+}
+
+impl GreetingOutcome {
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, GreetingOutcome::Blocked { .. })
+    }
+}
+
+/// Greeting outcomes by host
+#[derive(Debug, Default)]
+pub struct DomainGreetings {
+    hosts: Mutex<HashMap<String, Arc<OnceCell<GreetingOutcome>>>>,
+}
+
+impl DomainGreetings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Outcome of greeting `host`, running `greet` only for the first caller
+    pub async fn greet<F, Fut>(&self, host: &str, greet: F) -> GreetingOutcome
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = GreetingOutcome>,
+    {
+        let cell = Arc::clone(self.hosts.lock().await.entry(host.to_string()).or_default());
+        cell.get_or_init(greet).await.clone()
+    }
+
+    /// Hosts greeted so far and how they answered
+    pub async fn outcomes(&self) -> BTreeMap<String, GreetingOutcome> {
+        self.hosts
+            .lock()
+            .await
+            .iter()
+            .filter_map(|(host, cell)| cell.get().map(|outcome| (host.clone(), outcome.clone())))
+            .collect()
+    }
+}
+
+/// Cookies kept apart per identity
+///
+/// Every client gets its own store, so a proxy route never sees the cookies set
+/// through another; within a store every User-Agent has its own jar. Requests
+/// sent outside `with_identity` share one anonymous jar. A sharded store keeps
+/// the jars of each domain shard apart, so sites of different shards never wait
+/// on one lock.
+#[derive(Debug)]
+pub struct IdentityCookies {
+    /// Jars by identity, one map per domain shard
+    shards: Vec<std::sync::Mutex<HashMap<String, Arc<Jar>>>>,
+    router: ShardRouter,
+}
+
+impl Default for IdentityCookies {
+    fn default() -> Self {
+        Self::sharded(ShardRouter::single())
+    }
+}
+
+impl IdentityCookies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store with the jars of every shard of `router` kept apart
+    pub fn sharded(router: ShardRouter) -> Self {
+        Self {
+            shards: (0..router.count()).map(|_| Default::default()).collect(),
+            router,
+        }
+    }
+
+    /// Run `send` with the cookies of `user_agent`, including the cookies set on
+    /// its redirect hops
+    pub async fn with_identity<F: Future>(user_agent: String, send: F) -> F::Output {
+        REQUEST_IDENTITY.scope(user_agent, send).await
+    }
+
+    fn jar(&self, url: &Url) -> Arc<Jar> {
+        let identity = REQUEST_IDENTITY.try_with(String::clone).unwrap_or_default();
+        let shard = self.router.shard_of(url.host_str().unwrap_or_default());
+        let mut jars = self.shards[shard].lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(jars.entry(identity).or_default())
+    }
+}
+
+impl CookieStore for IdentityCookies {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        self.jar(url).set_cookies(cookie_headers, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar(url).cookies(url)
+    }
+}
+
+/// Homepage of the URL's origin; `None` when the URL is the homepage
+pub fn homepage_of(url: &Url) -> Option<Url> {
+    let homepage = url.join("/").ok()?;
+    (homepage.as_str() != url.as_str()).then_some(homepage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_each_host_is_greeted_once() {
+        let greetings = Arc::new(DomainGreetings::new());
+        let sent = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let greetings = Arc::clone(&greetings);
+                let sent = Arc::clone(&sent);
+                tokio::spawn(async move {
+                    greetings
+                        .greet("news.example", || async {
+                            sent.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                            GreetingOutcome::Blocked {
+                                reason: "HTTP 403 Forbidden".to_string(),
+                            }
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().is_blocked());
+        }
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        let welcomed = greetings
+            .greet("docs.example", || async { GreetingOutcome::Welcomed })
+            .await;
+        assert_eq!(welcomed, GreetingOutcome::Welcomed);
+        let outcomes = greetings.outcomes().await;
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes["news.example"].is_blocked());
+
+        let article = Url::parse("https://news.example/2024/05/story?id=1").unwrap();
+        assert_eq!(
+            homepage_of(&article).unwrap().as_str(),
+            "https://news.example/"
+        );
+        assert_eq!(
+            homepage_of(&Url::parse("https://news.example").unwrap()),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_identities_keep_their_own_cookies() {
+        let cookies = IdentityCookies::new();
+        let url = Url::parse("https://news.example/").unwrap();
+        let set_cookie = HeaderValue::from_static("session=first-visitor; Path=/");
+
+        IdentityCookies::with_identity("Browser A".to_string(), async {
+            cookies.set_cookies(&mut std::iter::once(&set_cookie), &url);
+        })
+        .await;
+
+        let sent = |user_agent: &str| {
+            IdentityCookies::with_identity(user_agent.to_string(), async { cookies.cookies(&url) })
+        };
+        assert_eq!(
+            sent("Browser A").await,
+            Some(HeaderValue::from_static("session=first-visitor"))
+        );
+        assert_eq!(sent("Browser B").await, None);
+        assert_eq!(cookies.cookies(&url), None);
+    }
+
+    #[tokio::test]
+    async fn test_sharded_jars_keep_each_site_in_its_shard() {
+        let router = ShardRouter::new(4, Arc::new(crate::core::PublicSuffixes::builtin()));
+        let cookies = IdentityCookies::sharded(router.clone());
+        let sites = (0..16)
+            .map(|i| format!("site{}.example", i))
+            .collect::<Vec<_>>();
+        for site in &sites {
+            let url = Url::parse(&format!("https://www.{}/", site)).unwrap();
+            let set_cookie = HeaderValue::from_str(&format!(
+                "visitor={}; Domain={}; Path=/",
+                site.replace('.', "-"),
+                site
+            ))
+            .unwrap();
+            cookies.set_cookies(&mut std::iter::once(&set_cookie), &url);
+        }
+
+        // Another host of the site reads the cookie from the same shard
+        for site in &sites {
+            let url = Url::parse(&format!("https://blog.{}/", site)).unwrap();
+            let expected = format!("visitor={}", site.replace('.', "-"));
+            assert_eq!(
+                cookies.cookies(&url),
+                Some(HeaderValue::from_str(&expected).unwrap())
+            );
+        }
+        let mut stored = 0;
+        for (shard, jars) in cookies.shards.iter().enumerate() {
+            let jars = jars.lock().unwrap();
+            for site in &sites {
+                let url = Url::parse(&format!("https://{}/", site)).unwrap();
+                if jars.values().any(|jar| jar.cookies(&url).is_some()) {
+                    assert_eq!(router.shard_of(site), shard);
+                    stored += 1;
+                }
+            }
+        }
+        assert_eq!(stored, sites.len());
+    }
+}
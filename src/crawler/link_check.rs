@@ -0,0 +1,170 @@
+/// Bulk URL status checks (link checker)
+///
+/// Each URL gets one HEAD request (GET when HEAD is not supported) through the
+/// crawler's rate limiter, concurrency permits, proxies and identity, without
+/// downloading or processing content. The report lists status code, redirect
+/// target, latency and error class per URL, and can be written as CSV.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use url::Url;
+
+use crate::core::types::url_serde;
+
+/// Outcome of checking one URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkStatus {
+    Ok,
+    /// The URL redirects to `LinkCheck::final_url`
+    Redirected,
+    /// The server answered with a 4xx/5xx status
+    Broken,
+    /// Timeout, DNS, connection or proxy failure
+    Unreachable,
+}
+
+impl LinkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkStatus::Ok => "ok",
+            LinkStatus::Redirected => "redirected",
+            LinkStatus::Broken => "broken",
+            LinkStatus::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// Check result for a single URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCheck {
+    #[serde(with = "url_serde")]
+    pub url: Url,
+    pub status: LinkStatus,
+    pub status_code: Option<u16>,
+    /// Where the URL redirects to, if anywhere
+    #[serde(with = "url_serde::option")]
+    pub final_url: Option<Url>,
+    pub latency_ms: u64,
+    /// Error label as used in domain reports, e.g. `HTTP 404` or `Timeout (first_byte)`
+    pub error_class: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Check results for a URL list, in input order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkCheckReport {
+    pub checks: Vec<LinkCheck>,
+}
+
+impl LinkCheckReport {
+    /// Number of URLs with the given status
+    pub fn count(&self, status: LinkStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// URLs that answered with an error status or not at all
+    pub fn failures(&self) -> impl Iterator<Item = &LinkCheck> {
+        self.checks
+            .iter()
+            .filter(|check| matches!(check.status, LinkStatus::Broken | LinkStatus::Unreachable))
+    }
+
+    /// Write the report as CSV with a header row
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record([
+            "url",
+            "status",
+            "status_code",
+            "final_url",
+            "latency_ms",
+            "error_class",
+            "error",
+        ])?;
+        for check in &self.checks {
+            csv.write_record([
+                check.url.as_str(),
+                check.status.as_str(),
+                &check
+                    .status_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_default(),
+                check.final_url.as_ref().map(Url::as_str).unwrap_or(""),
+                &check.latency_ms.to_string(),
+                check.error_class.as_deref().unwrap_or(""),
+                check.error.as_deref().unwrap_or(""),
+            ])?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+
+    /// The report as a CSV string
+    pub fn to_csv(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        self.write_csv(&mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_and_csv() {
+        let report = LinkCheckReport {
+            checks: vec![
+                LinkCheck {
+                    url: Url::parse("https://a.example/").unwrap(),
+                    status: LinkStatus::Ok,
+                    status_code: Some(200),
+                    final_url: None,
+                    latency_ms: 12,
+                    error_class: None,
+                    error: None,
+                },
+                LinkCheck {
+                    url: Url::parse("http://a.example/old").unwrap(),
+                    status: LinkStatus::Redirected,
+                    status_code: Some(200),
+                    final_url: Some(Url::parse("https://a.example/new").unwrap()),
+                    latency_ms: 30,
+                    error_class: None,
+                    error: None,
+                },
+                LinkCheck {
+                    url: Url::parse("https://a.example/gone").unwrap(),
+                    status: LinkStatus::Broken,
+                    status_code: Some(404),
+                    final_url: None,
+                    latency_ms: 8,
+                    error_class: Some("HTTP 404".to_string()),
+                    error: Some("Not Found, with a comma".to_string()),
+                },
+            ],
+        };
+
+        assert_eq!(report.count(LinkStatus::Ok), 1);
+        assert_eq!(report.failures().count(), 1);
+
+        let csv = report.to_csv().unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "url,status,status_code,final_url,latency_ms,error_class,error"
+        );
+        assert_eq!(
+            lines[2],
+            "http://a.example/old,redirected,200,https://a.example/new,30,,"
+        );
+        assert_eq!(
+            lines[3],
+            "https://a.example/gone,broken,404,,8,HTTP 404,\"Not Found, with a comma\""
+        );
+    }
+}
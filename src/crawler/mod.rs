@@ -1,6 +1,22 @@
 // Main crawler logic and engine
 
+mod admission;
+pub mod api;
+pub mod download;
 pub mod engine;
+pub mod greeting;
+pub mod link_check;
+pub mod precheck;
+pub mod shards;
+pub mod visited;
 
 // Re-export crawler components
-pub use engine::WebCrawler;
+pub(crate) use admission::{UrlBudget, avoided_extension};
+pub use api::{ApiSeed, ApiWalk};
+pub use download::{ASSET_MANIFEST_FILE, AssetEntry, AssetManifest, ContentRange};
+pub use engine::{CrawlOutcome, CrawledPage, WebCrawler};
+pub use greeting::{DomainGreetings, GreetingOutcome, IdentityCookies, homepage_of};
+pub use link_check::{LinkCheck, LinkCheckReport, LinkStatus};
+pub use precheck::{SeedCheck, SeedPrecheckReport, SeedStatus};
+pub use shards::ShardRouter;
+pub use visited::{VisitedSet, VisitedState, VisitedStats};
@@ -0,0 +1,320 @@
+/// Seed URL health precheck
+///
+/// Every seed gets one short HEAD request (GET when HEAD is not supported) before
+/// the crawl starts, so dead seeds, redirects and robots blocks are known up front
+/// instead of being discovered one slow failure at a time.
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::core::RobotsChecker;
+use crate::core::types::url_serde;
+use crate::network::{Cassette, RobotsHandler};
+
+/// Outcome of checking one seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedStatus {
+    Ok,
+    /// The seed answers with a redirect to `SeedCheck::final_url`
+    Redirected,
+    /// The server answered with a 4xx/5xx status
+    Dead,
+    /// Timeout, DNS or connection failure
+    Unreachable,
+    /// robots.txt disallows the seed (or its redirect target)
+    RobotsBlocked,
+    /// The SSRF guard refuses the seed's address; no request was sent
+    AddressBlocked,
+}
+
+/// Precheck result for a single seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedCheck {
+    #[serde(with = "url_serde")]
+    pub seed: Url,
+    pub status: SeedStatus,
+    pub status_code: Option<u16>,
+    /// Where the seed redirects to, if anywhere
+    #[serde(with = "url_serde::option")]
+    pub final_url: Option<Url>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+impl SeedCheck {
+    pub fn is_crawlable(&self) -> bool {
+        matches!(self.status, SeedStatus::Ok | SeedStatus::Redirected)
+    }
+}
+
+/// Precheck results for a whole seed list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeedPrecheckReport {
+    pub checks: Vec<SeedCheck>,
+}
+
+impl SeedPrecheckReport {
+    /// Number of seeds with the given status
+    pub fn count(&self, status: SeedStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Seeds to crawl, in input order and without duplicates
+    ///
+    /// Failed seeds are kept unless `drop_failed` is set; redirecting seeds are
+    /// replaced by their target when `use_redirect_targets` is set.
+    pub fn seeds(&self, drop_failed: bool, use_redirect_targets: bool) -> Vec<Url> {
+        let mut seeds: Vec<Url> = Vec::with_capacity(self.checks.len());
+        for check in &self.checks {
+            if drop_failed && !check.is_crawlable() {
+                continue;
+            }
+            let seed = match (&check.final_url, use_redirect_targets) {
+                (Some(target), true) => target.clone(),
+                _ => check.seed.clone(),
+            };
+            if !seeds.contains(&seed) {
+                seeds.push(seed);
+            }
+        }
+        seeds
+    }
+}
+
+/// HEAD `url`, falling back to GET when the server does not support HEAD
+///
+/// The body of a GET response is never read. With a cassette the requests are
+/// recorded, or replayed without network access.
+pub(crate) async fn head_or_get(
+    client: &Client,
+    cassette: Option<&Cassette>,
+    url: &Url,
+    user_agent: &str,
+) -> Result<Response, anyhow::Error> {
+    let send = |method: reqwest::Method| async move {
+        let request = client
+            .request(method, url.clone())
+            .header(reqwest::header::USER_AGENT, user_agent)
+            .build()?;
+        match cassette {
+            Some(cassette) => cassette.execute(client, request).await,
+            None => Ok(client.execute(request).await?),
+        }
+    };
+    let response = send(reqwest::Method::HEAD).await?;
+    if matches!(
+        response.status(),
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+    ) {
+        return send(reqwest::Method::GET).await;
+    }
+    Ok(response)
+}
+
+/// Check one seed with a single request and its robots.txt, both within `timeout`
+pub(crate) async fn check_seed(
+    client: &Client,
+    cassette: Option<&Cassette>,
+    robots: &RobotsHandler,
+    seed: Url,
+    user_agent: &str,
+    timeout: Duration,
+) -> SeedCheck {
+    let start = Instant::now();
+    let deadline = tokio::time::Instant::from_std(start) + timeout;
+    let mut check = SeedCheck {
+        seed: seed.clone(),
+        status: SeedStatus::Ok,
+        status_code: None,
+        final_url: None,
+        error: None,
+        elapsed_ms: 0,
+    };
+
+    match tokio::time::timeout_at(deadline, head_or_get(client, cassette, &seed, user_agent)).await
+    {
+        Err(_) => {
+            check.status = SeedStatus::Unreachable;
+            check.error = Some(format!("no response within {}ms", timeout.as_millis()));
+        }
+        Ok(Err(e)) => {
+            check.status = SeedStatus::Unreachable;
+            check.error = Some(e.to_string());
+        }
+        Ok(Ok(response)) => {
+            check.status_code = Some(response.status().as_u16());
+            if response.url() != &seed {
+                check.status = SeedStatus::Redirected;
+                check.final_url = Some(response.url().clone());
+            }
+            if response.status().is_client_error() || response.status().is_server_error() {
+                check.status = SeedStatus::Dead;
+            }
+        }
+    }
+
+    if check.is_crawlable() {
+        let target = check.final_url.as_ref().unwrap_or(&seed);
+        if robots.override_reason(target).is_none() {
+            match tokio::time::timeout_at(deadline, robots.is_allowed_by_robots(target)).await {
+                Ok(Ok(true)) => {}
+                Ok(Ok(false)) => check.status = SeedStatus::RobotsBlocked,
+                Ok(Err(e)) => {
+                    tracing::debug!(
+                        url = %target,
+                        error = %e,
+                        "robots.txt check failed during precheck"
+                    );
+                }
+                // The seed answered; robots.txt is read again when it is crawled
+                Err(_) => {
+                    tracing::debug!(url = %target, "robots.txt check timed out during precheck");
+                }
+            }
+        }
+    }
+
+    check.elapsed_ms = start.elapsed().as_millis() as u64;
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(seed: &str, status: SeedStatus, final_url: Option<&str>) -> SeedCheck {
+        SeedCheck {
+            seed: Url::parse(seed).unwrap(),
+            status,
+            status_code: None,
+            final_url: final_url.map(|url| Url::parse(url).unwrap()),
+            error: None,
+            elapsed_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_report_seed_selection() {
+        let report = SeedPrecheckReport {
+            checks: vec![
+                check("https://a.example/", SeedStatus::Ok, None),
+                check("https://dead.example/", SeedStatus::Dead, None),
+                check(
+                    "http://a.example/",
+                    SeedStatus::Redirected,
+                    Some("https://a.example/"),
+                ),
+                check("https://b.example/", SeedStatus::RobotsBlocked, None),
+            ],
+        };
+
+        assert_eq!(report.count(SeedStatus::Dead), 1);
+        assert_eq!(report.seeds(false, false).len(), 4);
+        assert_eq!(
+            report.seeds(true, false),
+            vec![
+                Url::parse("https://a.example/").unwrap(),
+                Url::parse("http://a.example/").unwrap(),
+            ]
+        );
+        // The redirect target duplicates the first seed
+        assert_eq!(
+            report.seeds(true, true),
+            vec![Url::parse("https://a.example/").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replayed_prechecks_use_the_cassette() {
+        use crate::config::{CassetteConfig, CassetteMode};
+        use crate::network::CassetteEntry;
+        use std::sync::Arc;
+
+        // Nothing listens on port 9, so only the recording can answer
+        let seed = Url::parse("http://127.0.0.1:9/gone").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let entry = |method: &str, status: u16| CassetteEntry {
+            method: method.to_string(),
+            url: seed.clone(),
+            final_url: seed.clone(),
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        };
+        let lines = [entry("HEAD", 405), entry("GET", 404)]
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect::<String>();
+        std::fs::write(&path, lines).unwrap();
+        let cassette = Arc::new(
+            Cassette::open(&CassetteConfig {
+                path,
+                mode: CassetteMode::Replay,
+            })
+            .unwrap(),
+        );
+
+        let client = Client::new();
+        let robots = RobotsHandler::new(client.clone()).with_cassette(Arc::clone(&cassette));
+        let check = check_seed(
+            &client,
+            Some(&cassette),
+            &robots,
+            seed,
+            "test-agent",
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(check.status, SeedStatus::Dead);
+        assert_eq!(check.status_code, Some(404));
+    }
+
+    #[tokio::test]
+    async fn test_a_hanging_robots_txt_stays_within_the_timeout() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        // The seed answers at once, robots.txt never does
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    if request.contains("/robots.txt") {
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                        return;
+                    }
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        )
+                        .await;
+                });
+            }
+        });
+
+        let client = Client::new();
+        let robots = RobotsHandler::new(client.clone());
+        let start = Instant::now();
+        let check = check_seed(
+            &client,
+            None,
+            &robots,
+            base.join("page").unwrap(),
+            "test-agent",
+            Duration::from_millis(500),
+        )
+        .await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(check.status, SeedStatus::Ok);
+        assert_eq!(check.status_code, Some(200));
+    }
+}
@@ -0,0 +1,82 @@
+/// Domain sharding of per-site crawler state
+///
+/// A crawler keeps its per-site state, the rate-limit windows with their last
+/// request times, the robots.txt cache and the cookie jars, in maps that every
+/// task locks, and at high concurrency tasks queue up on those locks. With
+/// `domain_shards` configured, each of these is split into that many shards and
+/// every registrable domain is routed to one shard by hash. A site's state then
+/// lives in exactly one shard, and tasks on sites of other shards never wait on
+/// its locks. Hosts of one site (`www.example.com`, `blog.example.com`) share a
+/// shard, as they share a rate-limit window and their cookies.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::core::PublicSuffixes;
+use crate::network::{GlobalRateLimiter, RobotsHandler};
+
+/// Routes hosts to shards by registrable domain
+#[derive(Debug, Clone)]
+pub struct ShardRouter {
+    count: usize,
+    suffixes: Arc<PublicSuffixes>,
+}
+
+impl ShardRouter {
+    /// Route among `count` shards (at least one), grouping hosts with `suffixes`
+    pub fn new(count: usize, suffixes: Arc<PublicSuffixes>) -> Self {
+        Self {
+            count: count.max(1),
+            suffixes,
+        }
+    }
+
+    /// A single shard holding every site
+    pub fn single() -> Self {
+        Self::new(1, Arc::new(PublicSuffixes::builtin()))
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Shard of `host`; every host of a registrable domain maps to the same shard
+    pub fn shard_of(&self, host: &str) -> usize {
+        if self.count == 1 {
+            return 0;
+        }
+        let mut hasher = DefaultHasher::new();
+        self.suffixes.registrable_domain(host).hash(&mut hasher);
+        (hasher.finish() % self.count as u64) as usize
+    }
+}
+
+/// Rate limiter and robots.txt cache of the sites routed to one shard
+pub(crate) struct DomainShard {
+    pub(crate) rate_limiter: GlobalRateLimiter,
+    pub(crate) robots: RobotsHandler,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sites_stick_to_one_shard() {
+        let router = ShardRouter::new(4, Arc::new(PublicSuffixes::builtin()));
+        assert_eq!(router.count(), 4);
+        assert_eq!(
+            router.shard_of("news.example.co.uk"),
+            router.shard_of("WWW.Example.co.uk")
+        );
+
+        let shards: std::collections::HashSet<usize> = (0..64)
+            .map(|i| router.shard_of(&format!("site{}.example", i)))
+            .collect();
+        assert_eq!(shards.len(), 4);
+
+        let single = ShardRouter::new(0, Arc::new(PublicSuffixes::builtin()));
+        assert_eq!(single.count(), 1);
+        assert_eq!(single.shard_of("any.example"), 0);
+    }
+}
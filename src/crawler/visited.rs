@@ -0,0 +1,349 @@
+/// Two-tier visited-URL set
+///
+/// The Bloom filter is consulted first; a miss means the URL is definitely new.
+/// A hit is only "probably visited" and is confirmed against an exact set, so a
+/// Bloom false positive no longer silently drops a URL. The exact set keeps up to
+/// `max_in_memory` URLs verbatim; older entries are spilled to disk (if configured)
+/// and remembered as 64-bit hashes, up to `max_spilled_hashes`. Once hashes have
+/// been dropped, a hit found nowhere in memory is looked up in the spill file, or
+/// counted as visited when there is none. A restored set reads the spill file back.
+/// URLs are keyed with their host in ASCII form and without a trailing dot, so
+/// spellings of the same host count as one URL.
+use anyhow::Result;
+use bloom::{ASMS, BloomFilter};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use url::Url;
+
+use crate::config::{VisitedSetConfig, defaults};
+use crate::core::idn::normalize_host;
+
+/// Deduplication counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VisitedStats {
+    pub checked: u64,
+    pub new_urls: u64,
+    pub duplicates: u64,
+    /// Bloom filter hits that the exact set proved to be new URLs
+    pub false_positives_saved: u64,
+    /// URLs moved out of the verbatim in-memory tier
+    pub spilled: u64,
+    /// Hashes of spilled URLs dropped to stay within `max_spilled_hashes`
+    #[serde(default)]
+    pub hashes_dropped: u64,
+}
+
+/// Contents of a `VisitedSet`, saved with session checkpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VisitedState {
+    /// URLs kept verbatim, oldest first
+    pub urls: Vec<String>,
+    /// Hashes of the URLs spilled out of memory, oldest first
+    pub spilled_hashes: Vec<u64>,
+    /// Forgotten URLs, which the spill file may still list
+    #[serde(default)]
+    pub forgotten: Vec<String>,
+}
+
+impl VisitedState {
+    /// Forget `urls`, so a restored set lets them be fetched again
+    pub fn forget<'a>(&mut self, urls: impl IntoIterator<Item = &'a Url>) {
+        let keys: HashSet<String> = urls
+            .into_iter()
+            .map(|url| visited_key(url.as_str()))
+            .collect();
+        let hashes: HashSet<u64> = keys.iter().map(|key| url_hash(key)).collect();
+        self.urls.retain(|url| !keys.contains(url));
+        self.spilled_hashes.retain(|hash| !hashes.contains(hash));
+        self.forgotten.extend(keys);
+    }
+}
+
+pub struct VisitedSet {
+    bloom: BloomFilter,
+    config: VisitedSetConfig,
+    recent: HashSet<String>,
+    /// Insertion order of `recent`, oldest first
+    order: VecDeque<String>,
+    spilled_hashes: HashSet<u64>,
+    /// Insertion order of `spilled_hashes`, oldest first
+    spilled_order: VecDeque<u64>,
+    /// URLs whose spill file lines are ignored, because a checkpoint forgot them
+    forgotten: HashSet<String>,
+    spill_writer: Option<BufWriter<File>>,
+    stats: VisitedStats,
+}
+
+impl VisitedSet {
+    pub fn new(config: VisitedSetConfig) -> Result<Self> {
+        let spill_writer = match &config.spill_path {
+            Some(path) => Some(BufWriter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            )),
+            None => None,
+        };
+
+        Ok(Self {
+            bloom: BloomFilter::with_rate(
+                defaults::BLOOM_FALSE_POSITIVE_RATE,
+                defaults::BLOOM_CAPACITY,
+            ),
+            config,
+            recent: HashSet::new(),
+            order: VecDeque::new(),
+            spilled_hashes: HashSet::new(),
+            spilled_order: VecDeque::new(),
+            forgotten: HashSet::new(),
+            spill_writer,
+            stats: VisitedStats::default(),
+        })
+    }
+
+    /// Record `url` as visited; returns `false` if it was already visited
+    pub fn insert(&mut self, url: &str) -> bool {
+        self.stats.checked += 1;
+        let key = visited_key(url);
+
+        // Restored spilled hashes have no Bloom filter entry of their own
+        if !self.bloom.contains(&key) && self.spilled_hashes.contains(&url_hash(&key)) {
+            self.stats.duplicates += 1;
+            return false;
+        }
+        if self.bloom.contains(&key) {
+            if !self.config.exact_confirmation || self.contains_exact(&key) {
+                self.stats.duplicates += 1;
+                return false;
+            }
+            self.stats.false_positives_saved += 1;
+            tracing::debug!(url = %url, "Bloom filter false positive confirmed as new URL");
+        }
+
+        self.bloom.insert(&key);
+        self.forgotten.remove(&key);
+        if self.config.exact_confirmation {
+            self.remember(key);
+        }
+        self.stats.new_urls += 1;
+        true
+    }
+
+    pub fn stats(&self) -> VisitedStats {
+        self.stats.clone()
+    }
+
+    /// URLs and spilled hashes of the set, for a checkpoint
+    pub fn export_state(&self) -> VisitedState {
+        VisitedState {
+            urls: self.order.iter().cloned().collect(),
+            spilled_hashes: self.spilled_order.iter().copied().collect(),
+            forgotten: self.forgotten.iter().cloned().collect(),
+        }
+    }
+
+    /// Add the URLs of a checkpoint, and those of the spill file, to the set
+    pub fn restore_state(&mut self, state: VisitedState) {
+        self.forgotten.extend(state.forgotten);
+        for url in self.read_spill_file() {
+            if !self.forgotten.contains(&url) {
+                self.bloom.insert(&url);
+                self.remember_spilled(url_hash(&url));
+            }
+        }
+        for hash in state.spilled_hashes {
+            self.remember_spilled(hash);
+        }
+        for url in state.urls {
+            self.bloom.insert(&url);
+            self.remember(url);
+        }
+    }
+
+    fn contains_exact(&mut self, url: &str) -> bool {
+        if self.recent.contains(url) || self.spilled_hashes.contains(&url_hash(url)) {
+            return true;
+        }
+        if self.stats.hashes_dropped == 0 {
+            return false;
+        }
+        // Without a spill file a dropped hash cannot be told from a false positive
+        if self.config.spill_path.is_none() {
+            return true;
+        }
+        !self.forgotten.contains(url) && self.read_spill_file().any(|spilled| spilled == url)
+    }
+
+    /// Lines of the spill file, after flushing what is still buffered
+    fn read_spill_file(&mut self) -> impl Iterator<Item = String> + use<> {
+        if let Some(writer) = &mut self.spill_writer
+            && let Err(e) = writer.flush()
+        {
+            tracing::warn!(error = %e, "Failed to flush spilled visited URLs");
+        }
+        let file = match &self.config.spill_path {
+            Some(path) => match File::open(path) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to read spilled visited URLs");
+                    None
+                }
+            },
+            None => None,
+        };
+        file.into_iter()
+            .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok))
+    }
+
+    fn remember(&mut self, url: String) {
+        self.recent.insert(url.clone());
+        self.order.push_back(url);
+
+        while self.recent.len() > self.config.max_in_memory {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.recent.remove(&oldest);
+            self.remember_spilled(url_hash(&oldest));
+            if let Some(writer) = &mut self.spill_writer
+                && let Err(e) = writeln!(writer, "{}", oldest)
+            {
+                tracing::warn!(error = %e, "Failed to spill visited URL to disk");
+            }
+            self.stats.spilled += 1;
+        }
+    }
+
+    fn remember_spilled(&mut self, hash: u64) {
+        if !self.spilled_hashes.insert(hash) {
+            return;
+        }
+        self.spilled_order.push_back(hash);
+        while self.spilled_hashes.len() > self.config.max_spilled_hashes {
+            let Some(oldest) = self.spilled_order.pop_front() else {
+                break;
+            };
+            self.spilled_hashes.remove(&oldest);
+            self.stats.hashes_dropped += 1;
+        }
+    }
+}
+
+impl Drop for VisitedSet {
+    fn drop(&mut self) {
+        if let Some(writer) = &mut self.spill_writer {
+            let _ = writer.flush();
+        }
+    }
+}
+
+/// `url` as it is stored in the set
+fn visited_key(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            normalize_host(&mut parsed);
+            parsed.into()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+fn url_hash(url: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restored_sets_remember_verbatim_and_spilled_urls() {
+        let config = VisitedSetConfig {
+            max_in_memory: 1,
+            ..VisitedSetConfig::default()
+        };
+        let mut visited = VisitedSet::new(config.clone()).unwrap();
+        for url in [
+            "https://a.example/1",
+            "https://a.example/2",
+            "https://a.example/3",
+        ] {
+            assert!(visited.insert(url));
+        }
+        let mut state = visited.export_state();
+        assert_eq!(state.urls, ["https://a.example/3"]);
+        assert_eq!(
+            state.spilled_hashes,
+            [
+                url_hash("https://a.example/1"),
+                url_hash("https://a.example/2")
+            ]
+        );
+
+        // An interrupted task is fetched again after the restore
+        state.forget([&Url::parse("https://A.example/2").unwrap()]);
+        let mut restored = VisitedSet::new(config).unwrap();
+        restored.restore_state(state);
+        assert!(!restored.insert("https://a.example/1"));
+        assert!(!restored.insert("https://a.example/3"));
+        assert!(restored.insert("https://a.example/2"));
+        assert!(restored.insert("https://a.example/4"));
+    }
+
+    fn spilling_config(spill_path: Option<std::path::PathBuf>) -> VisitedSetConfig {
+        VisitedSetConfig {
+            max_in_memory: 1,
+            max_spilled_hashes: 1,
+            spill_path,
+            ..VisitedSetConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_dropped_hashes_are_confirmed_from_the_spill_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut visited = VisitedSet::new(spilling_config(Some(dir.path().join("spill")))).unwrap();
+        for path in 1..=4 {
+            assert!(visited.insert(&format!("https://a.example/{}", path)));
+        }
+        assert_eq!(visited.export_state().spilled_hashes.len(), 1);
+        assert_eq!(visited.stats().hashes_dropped, 2);
+
+        // The first URL is only in the Bloom filter and the spill file now
+        assert!(!visited.insert("https://a.example/1"));
+        assert!(visited.insert("https://a.example/5"));
+
+        // Without a spill file a hit whose hash was dropped counts as visited
+        let mut visited = VisitedSet::new(spilling_config(None)).unwrap();
+        for path in 1..=4 {
+            assert!(visited.insert(&format!("https://a.example/{}", path)));
+        }
+        assert!(!visited.insert("https://a.example/1"));
+        assert!(visited.insert("https://a.example/5"));
+    }
+
+    #[test]
+    fn test_restored_sets_read_the_spill_file_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = spilling_config(Some(dir.path().join("spill")));
+        let mut state = {
+            let mut visited = VisitedSet::new(config.clone()).unwrap();
+            for path in 1..=4 {
+                assert!(visited.insert(&format!("https://a.example/{}", path)));
+            }
+            visited.export_state()
+        };
+        state.forget([&Url::parse("https://a.example/2").unwrap()]);
+
+        let mut restored = VisitedSet::new(config).unwrap();
+        restored.restore_state(state);
+        // Dropped from the checkpoint's hashes, but listed in the spill file
+        assert!(!restored.insert("https://a.example/1"));
+        // Forgotten URLs are fetched again even though the spill file lists them
+        assert!(restored.insert("https://a.example/2"));
+        assert!(!restored.insert("https://a.example/4"));
+    }
+}
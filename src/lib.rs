@@ -25,11 +25,12 @@ pub use core::{
 };
 
 // Configuration
-pub use config::{EnvironmentConfig, HttpClientFactory, LatinWordFilter, WebCrawlerConfig};
+pub use config::{EnvironmentConfig, LatinWordFilter, ProxyConfig, WebCrawlerConfig};
 
 // Network components
 pub use network::{
-    ClientManager, DnsCache, DomainRequestTracker, GlobalRateLimiter, RobotsCache, RobotsHandler,
+    ClientManager, DnsCache, DomainRequestTracker, GlobalRateLimiter, HttpClientFactory,
+    HttpTracer, RobotsCache, RobotsHandler,
 };
 
 // Processing components - unified feature-based exports (with Level 3 enhancements)
@@ -54,6 +55,7 @@ pub use processing::{
     ExtensiveQueueManager,
     // Link discovery and URL validation (Enhanced with Feature 2: Extensive crawling)
     ExtractedLink,
+    HreflangAlternate,
     KeywordConfig,
     KeywordExtractor,
     KeywordMatchInfo,
@@ -94,7 +96,10 @@ pub use processing::{
 };
 
 // Session management - core functionality
-pub use session::{CrawlResultData, CrawlSession, CrawlSessionConfig, SessionResult};
+pub use session::{
+    ConfigUpdate, CrawlPolicy, CrawlResultData, CrawlSession, CrawlSessionConfig, Decision,
+    MetricsLogConfig, RemainingQueue, SessionResult,
+};
 
 // Logging - unified system
 pub use logging::{
@@ -114,13 +119,16 @@ pub use logging::{
 };
 
 // Storage components
-pub use storage::{CrawlMetadata, DataStorage, OutputFormat, StoredCrawlResult};
+pub use storage::{
+    CollisionPolicy, CrawlMetadata, DataStorage, FileNamingTemplate, ManifestConfig, OutputFormat,
+    ResultFilter, ResultPage, RetentionPolicy, StoredCrawlResult,
+};
 
 // Queue management
-pub use queue::TaskQueue;
+pub use queue::{QueueSnapshot, SeedGroup, TaskQueue};
 
 // Crawler components
-pub use crawler::WebCrawler;
+pub use crawler::{ApiSeed, ApiWalk, LinkCheckReport, SeedPrecheckReport, WebCrawler};
 
 /// Library metadata and version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
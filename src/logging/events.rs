@@ -1,9 +1,14 @@
 // Structured logging events for crawler operations
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+use super::timeline::EventTimeline;
+use crate::core::SkipReason;
+use crate::processing::ShortContentDiagnosis;
+
 /// Comprehensive crawl event logging
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlEvent {
@@ -29,11 +34,13 @@ pub enum CrawlEventType {
     Completed,
     Failed,
     Retrying,
-    Blocked, // By robots.txt
+    Blocked,          // By robots.txt
+    RobotsOverridden, // robots.txt bypassed by explicit configuration
     RateLimited,
     Cached, // Content was cached
     Redirected,
     Timeout,
+    Skipped, // Fetched, but not kept (e.g. too little content)
 }
 
 /// Performance monitoring events
@@ -59,6 +66,7 @@ pub enum PerformanceEventType {
     ContentExtraction,
     LinkExtraction,
     SessionComplete,
+    ConcurrencyAdjusted,
 }
 
 /// Error event logging
@@ -89,11 +97,35 @@ pub enum ErrorType {
 /// Main crawler event logger
 pub struct CrawlEventLogger {
     session_id: String,
+    /// Crawl events are also appended here when set
+    timeline: Option<Arc<EventTimeline>>,
 }
 
 impl CrawlEventLogger {
     pub fn new(session_id: String) -> Self {
-        Self { session_id }
+        Self {
+            session_id,
+            timeline: None,
+        }
+    }
+
+    /// Append every crawl event to `timeline`
+    pub fn with_timeline(mut self, timeline: Arc<EventTimeline>) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    fn record(&self, event: &CrawlEvent) {
+        if let Some(timeline) = &self.timeline
+            && let Err(e) = timeline.record(event)
+        {
+            warn!(
+                error = %e,
+                path = %timeline.path().display(),
+                session_id = %self.session_id,
+                "Failed to record event in the session timeline"
+            );
+        }
     }
 
     /// Log crawl start event
@@ -124,6 +156,7 @@ impl CrawlEventLogger {
             "Started crawling URL"
         );
 
+        self.record(&event);
         debug!(event = ?event, "Detailed crawl start event");
     }
 
@@ -171,6 +204,7 @@ impl CrawlEventLogger {
             "Successfully crawled URL"
         );
 
+        self.record(&event);
         debug!(event = ?event, "Detailed crawl success event");
     }
 
@@ -231,9 +265,74 @@ impl CrawlEventLogger {
             );
         }
 
+        self.record(&event);
         debug!(event = ?event, "Detailed crawl failure event");
     }
 
+    /// Log a URL skipped before it was fetched, with the reason
+    pub fn log_crawl_skipped(&self, url: &Url, duration: Duration, reason: &SkipReason) {
+        let event = CrawlEvent {
+            url: url.to_string(),
+            event_type: CrawlEventType::Skipped,
+            timestamp: SystemTime::now(),
+            duration_ms: Some(duration.as_millis() as u64),
+            status_code: None,
+            content_length: None,
+            word_count: None,
+            language: None,
+            depth: None,
+            retry_count: None,
+            user_agent: None,
+            proxy_used: None,
+            error_message: Some(reason.to_string()),
+            session_id: Some(self.session_id.clone()),
+        };
+
+        debug!(
+            url = %url,
+            reason = %reason,
+            session_id = %self.session_id,
+            event = "crawl_skipped",
+            "Crawl skipped"
+        );
+
+        self.record(&event);
+    }
+
+    /// Log a page dropped for having too little content, with its diagnosis
+    pub fn log_content_too_short(&self, diagnosis: &ShortContentDiagnosis, duration: Duration) {
+        let event = CrawlEvent {
+            url: diagnosis.url.clone(),
+            event_type: CrawlEventType::Skipped,
+            timestamp: SystemTime::now(),
+            duration_ms: Some(duration.as_millis() as u64),
+            status_code: Some(diagnosis.status_code),
+            content_length: None,
+            word_count: Some(diagnosis.word_count),
+            language: diagnosis.language.clone(),
+            depth: None,
+            retry_count: None,
+            user_agent: None,
+            proxy_used: None,
+            error_message: Some(diagnosis.to_string()),
+            session_id: Some(self.session_id.clone()),
+        };
+
+        info!(
+            url = %diagnosis.url,
+            word_count = diagnosis.word_count,
+            min_word_length = diagnosis.min_word_length,
+            language = ?diagnosis.language,
+            category = %diagnosis.category,
+            session_id = %self.session_id,
+            event = "content_too_short",
+            "Page skipped: content too short"
+        );
+
+        self.record(&event);
+        debug!(event = ?event, "Detailed content too short event");
+    }
+
     /// Log robots.txt blocking
     pub fn log_robots_blocked(&self, url: &Url, robots_url: &str) {
         let event = CrawlEvent {
@@ -261,9 +360,42 @@ impl CrawlEventLogger {
             "URL blocked by robots.txt"
         );
 
+        self.record(&event);
         debug!(event = ?event, "Detailed robots block event");
     }
 
+    /// Log a robots.txt bypass for an allowlisted domain (audit trail)
+    pub fn log_robots_override(&self, url: &Url, reason: &str) {
+        let event = CrawlEvent {
+            url: url.to_string(),
+            event_type: CrawlEventType::RobotsOverridden,
+            timestamp: SystemTime::now(),
+            duration_ms: None,
+            status_code: None,
+            content_length: None,
+            word_count: None,
+            language: None,
+            depth: None,
+            retry_count: None,
+            user_agent: None,
+            proxy_used: None,
+            error_message: Some(format!("robots.txt override: {}", reason)),
+            session_id: Some(self.session_id.clone()),
+        };
+
+        warn!(
+            url = %url,
+            domain = url.host_str().unwrap_or("unknown"),
+            reason = reason,
+            session_id = %self.session_id,
+            event = "robots_override",
+            "robots.txt check bypassed by configured override"
+        );
+
+        self.record(&event);
+        debug!(event = ?event, "Detailed robots override event");
+    }
+
     /// Log rate limiting
     pub fn log_rate_limited(&self, url: &Url, wait_time_ms: u64, domain: &str) {
         let event = CrawlEvent {
@@ -292,6 +424,7 @@ impl CrawlEventLogger {
             "Request rate limited"
         );
 
+        self.record(&event);
         debug!(event = ?event, "Detailed rate limit event");
     }
 
@@ -322,6 +455,7 @@ impl CrawlEventLogger {
             "Request timed out"
         );
 
+        self.record(&event);
         debug!(event = ?event, "Detailed timeout event");
     }
 
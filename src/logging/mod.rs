@@ -4,6 +4,10 @@
 /// combining the simple initialization functions with advanced crawl event tracking.
 pub mod events;
 pub mod formatter;
+pub mod progress;
+pub mod skips;
+pub mod timeline;
+pub mod webhook;
 
 use anyhow::Error;
 
@@ -12,6 +16,15 @@ pub use events::{
     CrawlEvent, CrawlEventLogger, ErrorEvent, ErrorType, PerformanceEvent, PerformanceEventType,
 };
 pub use formatter::{CrawlLogFormatter, JsonLogFormatter};
+pub use progress::ProgressDisplay;
+pub use skips::{
+    SKIPPED_FILE, SkipReport, SkipStage, SkippedUrl, load_skipped, session_skipped_key,
+    session_skipped_path,
+};
+pub use timeline::{EVENTS_FILE, EventTimeline, load_timeline, session_events_path};
+pub use webhook::{
+    WebhookConfig, WebhookEndpoint, WebhookEvent, WebhookEventKind, WebhookNotifier,
+};
 
 /// Logging initialization utilities
 
@@ -0,0 +1,114 @@
+/// Terminal progress display for long crawls
+///
+/// Renders `SessionProgress` snapshots as a live table on stderr: one summary line
+/// (throughput, queue depth, in-flight requests, error rate, ETA) and one line per
+/// busiest domain. Lines are redrawn in place, so tracing output should be kept to
+/// warnings or sent to a file while the display is active.
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+use crate::session::{DomainProgress, SessionProgress};
+
+/// Live progress table with a fixed number of domain rows
+pub struct ProgressDisplay {
+    bars: MultiProgress,
+    summary: ProgressBar,
+    domain_rows: Vec<ProgressBar>,
+}
+
+impl ProgressDisplay {
+    /// Display with up to `max_domains` domain rows
+    pub fn new(max_domains: usize) -> Self {
+        let bars = MultiProgress::new();
+        let summary = bars.add(ProgressBar::new_spinner());
+        if let Ok(style) = ProgressStyle::with_template("{spinner} {msg}") {
+            summary.set_style(style);
+        }
+        summary.enable_steady_tick(Duration::from_millis(200));
+
+        let row_style = ProgressStyle::with_template("  {msg}").ok();
+        let mut domain_rows = Vec::with_capacity(max_domains + 1);
+        // Header row, then one row per domain
+        for _ in 0..=max_domains {
+            let row = bars.add(ProgressBar::new_spinner());
+            if let Some(style) = &row_style {
+                row.set_style(style.clone());
+            }
+            domain_rows.push(row);
+        }
+
+        Self {
+            bars,
+            summary,
+            domain_rows,
+        }
+    }
+
+    pub fn update(&self, progress: &SessionProgress) {
+        self.summary.set_message(summary_line(progress));
+
+        let Some((header, rows)) = self.domain_rows.split_first() else {
+            return;
+        };
+        header.set_message(format!(
+            "{:<32} {:>9} {:>7} {:>9} {:>7}",
+            "domain", "in-flight", "queued", "processed", "errors"
+        ));
+        for (index, row) in rows.iter().enumerate() {
+            match progress.domains.get(index) {
+                Some(domain) => row.set_message(domain_line(domain)),
+                None => row.set_message(""),
+            }
+        }
+    }
+
+    /// Print a line above the table without breaking it
+    pub fn println(&self, line: &str) {
+        let _ = self.bars.println(line);
+    }
+
+    /// Remove the table from the terminal
+    pub fn finish(&self) {
+        for row in &self.domain_rows {
+            row.finish_and_clear();
+        }
+        self.summary.finish_and_clear();
+    }
+}
+
+fn summary_line(progress: &SessionProgress) -> String {
+    format!(
+        "{} | {} done ({} failed, {:.1}% errors) | {:.2} URLs/s | queue {} | in flight {} | ETA {}",
+        format_duration(progress.elapsed),
+        progress.processed,
+        progress.failed,
+        progress.error_rate() * 100.0,
+        progress.throughput,
+        progress.queue_depth,
+        progress.in_flight,
+        progress
+            .eta
+            .map(format_duration)
+            .unwrap_or_else(|| "--:--:--".to_string())
+    )
+}
+
+fn domain_line(domain: &DomainProgress) -> String {
+    let mut name = domain.domain.clone();
+    if name.chars().count() > 32 {
+        name = name.chars().take(31).collect::<String>() + "…";
+    }
+    format!(
+        "{:<32} {:>9} {:>7} {:>9} {:>6.1}%",
+        name,
+        domain.in_flight,
+        domain.queued,
+        domain.processed,
+        domain.error_rate() * 100.0
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
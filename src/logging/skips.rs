@@ -0,0 +1,380 @@
+/// Per-session report of URLs that were not crawled
+///
+/// The event log says why a URL was dropped only in passing. `SkipReport` appends
+/// one `SkippedUrl` per dropped URL to `skipped.jsonl` in the session directory:
+/// the typed `SkipReason`, the page the URL was found on and the stage that
+/// filtered it, so a coverage audit can account for every URL the crawl saw.
+use anyhow::Result;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use url::Url;
+
+use super::timeline::session_events_path;
+use crate::core::SkipReason;
+use crate::storage::data::part_key;
+use crate::storage::{StorageBackend, path_safe_component};
+
+/// File name of the report inside a session directory
+pub const SKIPPED_FILE: &str = "skipped.jsonl";
+
+/// Report file of `session_id` under the storage directory
+pub fn session_skipped_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    session_events_path(storage_dir, session_id).with_file_name(SKIPPED_FILE)
+}
+
+/// Object key of the report of `session_id`, relative to the storage directory
+pub fn session_skipped_key(session_id: &str) -> String {
+    format!(
+        "{}/{}",
+        path_safe_component(&format!("session_{}", session_id)),
+        SKIPPED_FILE
+    )
+}
+
+/// Where in the crawl a URL was filtered out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipStage {
+    /// Seed list checks before the crawl starts
+    Seed,
+    /// Link filters, scopes and budgets applied when a link is found
+    Discovery,
+    /// Checks when the task is dequeued
+    Frontier,
+    /// Visited sets and robots.txt, before any request is sent
+    Fetch,
+    /// Checks of the response and its text
+    Content,
+}
+
+impl std::fmt::Display for SkipStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipStage::Seed => write!(f, "seed"),
+            SkipStage::Discovery => write!(f, "discovery"),
+            SkipStage::Frontier => write!(f, "frontier"),
+            SkipStage::Fetch => write!(f, "fetch"),
+            SkipStage::Content => write!(f, "content"),
+        }
+    }
+}
+
+/// A URL that was not crawled, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedUrl {
+    pub url: String,
+    pub reason: SkipReason,
+    pub stage: SkipStage,
+    /// Page the URL was found on; `None` for seeds
+    pub source_url: Option<String>,
+    pub timestamp: SystemTime,
+}
+
+/// Skipped URLs buffered before they are written out
+const FLUSH_BYTES: usize = 64 * 1024;
+
+/// Appends skipped URLs to a JSONL report
+///
+/// Entries are buffered and appended to the file once `FLUSH_BYTES` have gathered,
+/// on `flush` and when the report is dropped. With a storage backend nothing is
+/// written locally: each `flush` uploads the buffered entries as the next part
+/// object, `skipped.part-00000.jsonl`, `skipped.part-00001.jsonl`, ...
+pub struct SkipReport {
+    path: PathBuf,
+    buffered: Mutex<String>,
+    recorded: AtomicU64,
+    /// Backend and object key the report is uploaded to instead of `path`
+    backend: Option<(Arc<dyn StorageBackend>, String)>,
+    uploaded_parts: AtomicU64,
+}
+
+impl std::fmt::Debug for SkipReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkipReport")
+            .field("path", &self.path)
+            .field("recorded", &self.recorded())
+            .field(
+                "backend",
+                &self
+                    .backend
+                    .as_ref()
+                    .map(|(backend, key)| backend.location(key)),
+            )
+            .finish()
+    }
+}
+
+impl SkipReport {
+    /// Open `path` for appending, creating its directory if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self::buffered(path, None))
+    }
+
+    /// Upload the report to `backend` as parts of the object `key` instead of
+    /// writing `path`
+    pub fn with_backend(path: &Path, backend: Arc<dyn StorageBackend>, key: &str) -> Self {
+        Self::buffered(path, Some((backend, key.to_string())))
+    }
+
+    fn buffered(path: &Path, backend: Option<(Arc<dyn StorageBackend>, String)>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            buffered: Mutex::new(String::new()),
+            recorded: AtomicU64::new(0),
+            backend,
+            uploaded_parts: AtomicU64::new(0),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the report is written, for logs
+    pub fn location(&self) -> String {
+        match &self.backend {
+            Some((backend, key)) => backend.location(key),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    /// URLs recorded since the report was opened
+    pub fn recorded(&self) -> u64 {
+        self.recorded.load(Ordering::Relaxed)
+    }
+
+    pub fn record(&self, skipped: &SkippedUrl) -> Result<()> {
+        let line = serde_json::to_string(skipped)?;
+        let full = {
+            let mut buffered = self.buffered.lock().unwrap_or_else(|e| e.into_inner());
+            buffered.push_str(&line);
+            buffered.push('\n');
+            self.recorded.fetch_add(1, Ordering::Relaxed);
+            // A backend only takes whole parts, uploaded by `flush`
+            (self.backend.is_none() && buffered.len() >= FLUSH_BYTES)
+                .then(|| std::mem::take(&mut *buffered))
+        };
+        match full {
+            Some(lines) => append_lines(&self.path, &lines),
+            None => Ok(()),
+        }
+    }
+
+    /// Write out the buffered entries, on the blocking pool or to the backend
+    pub async fn flush(&self) -> Result<()> {
+        let lines = std::mem::take(&mut *self.buffered.lock().unwrap_or_else(|e| e.into_inner()));
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let written = match &self.backend {
+            Some((backend, key)) => {
+                let part = self.uploaded_parts.load(Ordering::SeqCst);
+                let uploaded = backend
+                    .put(&part_key(key, part), Bytes::from(lines.clone()))
+                    .await;
+                if uploaded.is_ok() {
+                    self.uploaded_parts.fetch_add(1, Ordering::SeqCst);
+                }
+                uploaded
+            }
+            None => {
+                let path = self.path.clone();
+                let appended = lines.clone();
+                tokio::task::spawn_blocking(move || append_lines(&path, &appended))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|r| r)
+            }
+        };
+        if written.is_err() {
+            // Keep the entries for the next flush, ahead of those recorded meanwhile
+            self.buffered
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert_str(0, &lines);
+        }
+        written
+    }
+
+    /// Record `url` as skipped, logging instead of failing when the report cannot
+    /// be written
+    pub fn skip(&self, url: &Url, reason: SkipReason, stage: SkipStage, source: Option<&Url>) {
+        let skipped = SkippedUrl {
+            url: url.to_string(),
+            reason,
+            stage,
+            source_url: source.map(Url::to_string),
+            timestamp: SystemTime::now(),
+        };
+        if let Err(e) = self.record(&skipped) {
+            tracing::warn!(
+                url = %url,
+                path = %self.path.display(),
+                error = %e,
+                "Failed to record skipped URL"
+            );
+        }
+    }
+}
+
+impl Drop for SkipReport {
+    fn drop(&mut self) {
+        let lines = std::mem::take(self.buffered.get_mut().unwrap_or_else(|e| e.into_inner()));
+        if self.backend.is_none()
+            && !lines.is_empty()
+            && let Err(e) = append_lines(&self.path, &lines)
+        {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "Failed to write buffered skipped URLs"
+            );
+        }
+    }
+}
+
+fn append_lines(path: &Path, lines: &str) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Read a skip report, ignoring lines that do not parse
+pub fn load_skipped(path: &Path) -> Result<Vec<SkippedUrl>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut skipped = Vec::new();
+    for line in reader.lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            skipped.push(entry);
+        }
+    }
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::MemoryBackend;
+
+    #[tokio::test]
+    async fn test_skipped_urls_round_trip_with_their_source_and_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_skipped_path(dir.path(), "audit");
+        let report = SkipReport::open(&path).unwrap();
+
+        let page = Url::parse("https://a.example/").unwrap();
+        let link = Url::parse("https://a.example/private").unwrap();
+        report.skip(
+            &link,
+            SkipReason::RobotsBlocked,
+            SkipStage::Fetch,
+            Some(&page),
+        );
+        report.skip(
+            &Url::parse("https://a.example/?sort=desc").unwrap(),
+            SkipReason::ParamBudgetExceeded("a.example/".to_string()),
+            SkipStage::Discovery,
+            Some(&page),
+        );
+        report.skip(
+            &page,
+            SkipReason::TooShort {
+                word_count: 3,
+                min_words: 50,
+            },
+            SkipStage::Content,
+            None,
+        );
+        assert_eq!(report.recorded(), 3);
+        // Entries are buffered until flushed
+        assert!(!path.exists());
+        report.flush().await.unwrap();
+
+        let skipped = load_skipped(&path).unwrap();
+        assert_eq!(skipped.len(), 3);
+        assert_eq!(skipped[0].url, link.as_str());
+        assert_eq!(skipped[0].reason, SkipReason::RobotsBlocked);
+        assert_eq!(skipped[0].source_url.as_deref(), Some(page.as_str()));
+        assert_eq!(skipped[1].stage, SkipStage::Discovery);
+        assert_eq!(
+            skipped[1].reason,
+            SkipReason::ParamBudgetExceeded("a.example/".to_string())
+        );
+        assert_eq!(skipped[2].source_url, None);
+
+        let line = std::fs::read_to_string(&path).unwrap();
+        assert!(line.contains(r#""reason":{"kind":"robots_blocked"}"#));
+        assert!(line.contains(r#""stage":"discovery""#));
+
+        // Reports from before language skips carried their language still load
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"url":"https://a.example/fr","reason":{"kind":"language_not_accepted"},"#,
+                r#""stage":"content","source_url":null,"#,
+                r#""timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#,
+                "\n"
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            load_skipped(&path).unwrap()[0].reason,
+            SkipReason::LanguageNotAccepted {
+                language: "und".to_string(),
+                confidence: 0.0
+            }
+        );
+        let typed = SkipReason::LanguageNotAccepted {
+            language: "fra".to_string(),
+            confidence: 0.9,
+        };
+        let line = serde_json::to_string(&typed).unwrap();
+        assert_eq!(serde_json::from_str::<SkipReason>(&line).unwrap(), typed);
+        std::fs::remove_file(&path).unwrap();
+        report.skip(&page, SkipReason::QueueFull, SkipStage::Discovery, None);
+        report.flush().await.unwrap();
+
+        // Dropping the report writes what is left in the buffer
+        report.skip(&page, SkipReason::QueueFull, SkipStage::Discovery, None);
+        drop(report);
+        assert_eq!(load_skipped(&path).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reports_go_to_the_backend_in_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_skipped_path(dir.path(), "audit");
+        let backend = Arc::new(MemoryBackend::default());
+        let report =
+            SkipReport::with_backend(&path, backend.clone(), &session_skipped_key("audit"));
+
+        let url = Url::parse("https://a.example/").unwrap();
+        report.skip(&url, SkipReason::QueueFull, SkipStage::Discovery, None);
+        report.flush().await.unwrap();
+        report.skip(&url, SkipReason::RobotsBlocked, SkipStage::Fetch, None);
+        report.flush().await.unwrap();
+        drop(report);
+
+        let objects = backend.objects.lock().unwrap();
+        assert_eq!(
+            objects.keys().collect::<Vec<_>>(),
+            vec![
+                "session_audit/skipped.part-00000.jsonl",
+                "session_audit/skipped.part-00001.jsonl"
+            ]
+        );
+        assert!(!path.exists());
+    }
+}
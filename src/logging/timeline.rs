@@ -0,0 +1,120 @@
+/// Per-session event timeline
+///
+/// Every `CrawlEvent` of a session is appended to `events.jsonl` in the session's
+/// directory (`<storage>/session_<id>/`) as it happens, so the order of retries,
+/// rate limiting and failures can be reconstructed after the session, by the UI or
+/// by analysis scripts. Lines are written whole and unbuffered; a line cut short by
+/// a crash is skipped when the timeline is loaded.
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::events::CrawlEvent;
+use crate::storage::path_safe_component;
+
+/// File name of the timeline inside a session directory
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// Timeline file of `session_id` under the storage directory
+pub fn session_events_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    storage_dir
+        .join(path_safe_component(&format!("session_{}", session_id)))
+        .join(EVENTS_FILE)
+}
+
+/// Appends crawl events to a timeline file
+#[derive(Debug)]
+pub struct EventTimeline {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl EventTimeline {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, event: &CrawlEvent) -> Result<()> {
+        let line = format!("{}\n", serde_json::to_string(event)?);
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Events of a timeline file in the order they were recorded
+pub fn load_timeline(path: &Path) -> Result<Vec<CrawlEvent>> {
+    let file = File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open timeline {}: {}", path.display(), e))?;
+    let mut events = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(event) = serde_json::from_str(&line?) {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::events::{CrawlEventLogger, CrawlEventType};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use url::Url;
+
+    #[test]
+    fn test_logged_events_are_reloaded_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_events_path(dir.path(), "s1");
+        assert!(path.ends_with("session_s1/events.jsonl"));
+
+        let timeline = Arc::new(EventTimeline::open(&path).unwrap());
+        let logger = CrawlEventLogger::new("s1".to_string()).with_timeline(timeline);
+        let url = Url::parse("https://a.example/").unwrap();
+        logger.log_crawl_start(&url, Some(0), None);
+        logger.log_rate_limited(&url, 250, "a.example");
+        logger.log_crawl_failure(
+            &url,
+            Duration::from_millis(40),
+            "HTTP 503",
+            None,
+            None,
+            true,
+        );
+
+        // A line cut short by a crash is skipped
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"url\":\"https://a.ex")
+            .unwrap();
+
+        let events = load_timeline(&path).unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0].event_type, CrawlEventType::Started));
+        assert!(matches!(events[1].event_type, CrawlEventType::RateLimited));
+        assert!(matches!(events[2].event_type, CrawlEventType::Retrying));
+        assert_eq!(events[2].error_message.as_deref(), Some("HTTP 503"));
+        assert!(events[0].timestamp <= events[2].timestamp);
+        assert_eq!(events[1].session_id.as_deref(), Some("s1"));
+    }
+}
@@ -0,0 +1,451 @@
+/// Webhook notifications for crawl milestones
+///
+/// JSON payloads are POSTed to every configured endpoint when a session starts or
+/// completes, when the error rate crosses a threshold, when a domain keeps failing
+/// and when a session budget runs out. Payloads carry a `text` summary, so they can
+/// go straight to a Slack incoming webhook, and are signed with HMAC-SHA256 when
+/// the endpoint has a secret (`X-Crawler-Signature: sha256=<hex>`).
+///
+/// Deliveries run in the background and never fail the crawl; `flush` waits for the
+/// ones still in flight.
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::core::UrlOutcome;
+
+/// Header carrying the HMAC-SHA256 signature of the body
+pub const SIGNATURE_HEADER: &str = "X-Crawler-Signature";
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-Crawler-Event";
+
+/// Kinds of milestones an endpoint can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    SessionStarted,
+    SessionCompleted,
+    ErrorRateExceeded,
+    DomainFailing,
+    BudgetExhausted,
+}
+
+/// A webhook receiver
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Key for the signature header; unsigned when `None`
+    pub secret: Option<String>,
+    /// Events sent to this endpoint; empty sends all
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+}
+
+impl WebhookEndpoint {
+    fn wants(&self, kind: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Webhook endpoints, delivery and alert thresholds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// Retries after a failed delivery (network error or non-2xx response)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub retry_backoff_ms: u64,
+    pub timeout_ms: u64,
+    /// Failed share of processed URLs that triggers `error_rate_exceeded`
+    pub error_rate_threshold: f64,
+    /// URLs processed before the error rate is considered meaningful
+    pub min_requests_for_error_rate: usize,
+    /// Consecutive failures on one domain that trigger `domain_failing`
+    pub domain_failure_threshold: u32,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_retries: 3,
+            retry_backoff_ms: 500,
+            timeout_ms: 5000,
+            error_rate_threshold: 0.5,
+            min_requests_for_error_rate: 20,
+            domain_failure_threshold: 5,
+        }
+    }
+}
+
+/// A crawl milestone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    SessionStarted {
+        seeds: usize,
+    },
+    SessionCompleted {
+        processed: usize,
+        successful: usize,
+        failed: usize,
+        duration_secs: f64,
+        time_truncated: bool,
+    },
+    ErrorRateExceeded {
+        error_rate: f64,
+        threshold: f64,
+        processed: usize,
+    },
+    DomainFailing {
+        domain: String,
+        consecutive_failures: u32,
+    },
+    BudgetExhausted {
+        /// Name of the exhausted setting, e.g. `max_session_duration`
+        budget: String,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            WebhookEvent::SessionStarted { .. } => WebhookEventKind::SessionStarted,
+            WebhookEvent::SessionCompleted { .. } => WebhookEventKind::SessionCompleted,
+            WebhookEvent::ErrorRateExceeded { .. } => WebhookEventKind::ErrorRateExceeded,
+            WebhookEvent::DomainFailing { .. } => WebhookEventKind::DomainFailing,
+            WebhookEvent::BudgetExhausted { .. } => WebhookEventKind::BudgetExhausted,
+        }
+    }
+
+    /// One-line human-readable summary
+    pub fn summary(&self, session_id: &str) -> String {
+        match self {
+            WebhookEvent::SessionStarted { seeds } => {
+                format!("Crawl session {} started with {} seeds", session_id, seeds)
+            }
+            WebhookEvent::SessionCompleted {
+                processed,
+                successful,
+                failed,
+                duration_secs,
+                time_truncated,
+            } => format!(
+                "Crawl session {} {} after {:.0}s: {} processed, {} successful, {} failed",
+                session_id,
+                if *time_truncated {
+                    "stopped at its time box"
+                } else {
+                    "completed"
+                },
+                duration_secs,
+                processed,
+                successful,
+                failed
+            ),
+            WebhookEvent::ErrorRateExceeded {
+                error_rate,
+                threshold,
+                processed,
+            } => format!(
+                "Crawl session {}: error rate {:.0}% over {} URLs exceeds {:.0}%",
+                session_id,
+                error_rate * 100.0,
+                processed,
+                threshold * 100.0
+            ),
+            WebhookEvent::DomainFailing {
+                domain,
+                consecutive_failures,
+            } => format!(
+                "Crawl session {}: {} failed {} times in a row",
+                session_id, domain, consecutive_failures
+            ),
+            WebhookEvent::BudgetExhausted { budget } => {
+                format!("Crawl session {}: {} exhausted", session_id, budget)
+            }
+        }
+    }
+}
+
+/// Body of a webhook request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub session_id: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub text: String,
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+}
+
+/// Per-session counters behind the threshold events
+#[derive(Default)]
+struct OutcomeTracker {
+    processed: usize,
+    failed: usize,
+    error_rate_notified: bool,
+    failure_streaks: HashMap<String, u32>,
+    failing_domains: HashSet<String>,
+}
+
+/// Sends a session's milestones to the configured endpoints
+pub struct WebhookNotifier {
+    client: Client,
+    config: WebhookConfig,
+    session_id: String,
+    tracker: Mutex<OutcomeTracker>,
+    deliveries: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig, session_id: String) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(Self {
+            client,
+            config,
+            session_id,
+            tracker: Mutex::new(OutcomeTracker::default()),
+            deliveries: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Send `event` to every endpoint subscribed to it
+    pub fn notify(&self, event: WebhookEvent) {
+        let kind = event.kind();
+        let payload = WebhookPayload {
+            session_id: self.session_id.clone(),
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+            text: event.summary(&self.session_id),
+            event,
+        };
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to serialize webhook payload");
+                return;
+            }
+        };
+
+        let mut deliveries = self.deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        deliveries.retain(|delivery| !delivery.is_finished());
+        for endpoint in self
+            .config
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.wants(kind))
+        {
+            deliveries.push(tokio::spawn(deliver(
+                self.client.clone(),
+                endpoint.clone(),
+                kind,
+                body.clone(),
+                self.config.max_retries,
+                Duration::from_millis(self.config.retry_backoff_ms),
+            )));
+        }
+    }
+
+    /// Count a processed URL, sending threshold events when they are first crossed
+    ///
+    /// Skipped URLs are left out of the error rate and do not break or extend a
+    /// domain's failure streak.
+    pub fn record_outcome(&self, domain: &str, outcome: UrlOutcome) {
+        if outcome == UrlOutcome::Skipped {
+            return;
+        }
+        let mut events = Vec::new();
+        {
+            let mut tracker = self.tracker.lock().unwrap_or_else(|e| e.into_inner());
+            tracker.processed += 1;
+            if outcome == UrlOutcome::Crawled {
+                tracker.failure_streaks.remove(domain);
+                tracker.failing_domains.remove(domain);
+            } else {
+                tracker.failed += 1;
+                let streak = tracker
+                    .failure_streaks
+                    .entry(domain.to_string())
+                    .or_insert(0);
+                *streak += 1;
+                let streak = *streak;
+                if streak >= self.config.domain_failure_threshold
+                    && tracker.failing_domains.insert(domain.to_string())
+                {
+                    events.push(WebhookEvent::DomainFailing {
+                        domain: domain.to_string(),
+                        consecutive_failures: streak,
+                    });
+                }
+            }
+
+            let error_rate = tracker.failed as f64 / tracker.processed as f64;
+            if !tracker.error_rate_notified
+                && tracker.processed >= self.config.min_requests_for_error_rate
+                && error_rate >= self.config.error_rate_threshold
+            {
+                tracker.error_rate_notified = true;
+                events.push(WebhookEvent::ErrorRateExceeded {
+                    error_rate,
+                    threshold: self.config.error_rate_threshold,
+                    processed: tracker.processed,
+                });
+            }
+        }
+        for event in events {
+            self.notify(event);
+        }
+    }
+
+    /// Wait for deliveries still in flight
+    pub async fn flush(&self) {
+        let deliveries =
+            std::mem::take(&mut *self.deliveries.lock().unwrap_or_else(|e| e.into_inner()));
+        for delivery in deliveries {
+            let _ = delivery.await;
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body` keyed with `secret`
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Whether `signature` is the `sign_payload` signature of `body`, compared in
+/// constant time
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return false;
+    }
+    let Ok(digest) = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+async fn deliver(
+    client: Client,
+    endpoint: WebhookEndpoint,
+    kind: WebhookEventKind,
+    body: Vec<u8>,
+    max_retries: u32,
+    backoff: Duration,
+) {
+    let event_name = serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let signature = endpoint
+        .secret
+        .as_deref()
+        .map(|secret| sign_payload(secret, &body));
+
+    let mut delay = backoff;
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+
+        let mut request = client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &event_name)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => tracing::warn!(
+                endpoint = %endpoint.url,
+                event = %event_name,
+                status = response.status().as_u16(),
+                attempt,
+                "Webhook rejected"
+            ),
+            Err(e) => tracing::warn!(
+                endpoint = %endpoint.url,
+                event = %event_name,
+                error = %e,
+                attempt,
+                "Webhook delivery failed"
+            ),
+        }
+    }
+
+    tracing::error!(
+        endpoint = %endpoint.url,
+        event = %event_name,
+        "Giving up on webhook after {} retries",
+        max_retries
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_and_payload_shape() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let payload = WebhookPayload {
+            session_id: "s1".to_string(),
+            timestamp: 0,
+            text: String::new(),
+            event: WebhookEvent::DomainFailing {
+                domain: "a.example".to_string(),
+                consecutive_failures: 5,
+            },
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "domain_failing");
+        assert_eq!(json["domain"], "a.example");
+    }
+
+    #[test]
+    fn test_skips_are_not_failures() {
+        let notifier = WebhookNotifier::new(WebhookConfig::default(), "s1".to_string()).unwrap();
+        notifier.record_outcome("a.example", UrlOutcome::Failed);
+        for _ in 0..10 {
+            notifier.record_outcome("a.example", UrlOutcome::Skipped);
+        }
+        notifier.record_outcome("a.example", UrlOutcome::Failed);
+
+        let tracker = notifier.tracker.lock().unwrap();
+        assert_eq!((tracker.processed, tracker.failed), (2, 2));
+        assert_eq!(tracker.failure_streaks["a.example"], 2);
+    }
+}
@@ -0,0 +1,311 @@
+/// Record/replay of HTTP exchanges
+///
+/// In record mode every request the engine makes goes to the network as usual and
+/// its response (final URL after redirects, status, headers and body) is appended
+/// to a JSONL cassette. In replay mode responses are served from the cassette and
+/// nothing is sent; a request without a recording fails like a network error.
+/// Replayed crawls are deterministic, which makes them suitable for integration
+/// tests and for debugging extraction against a captured session.
+///
+/// Requests are matched on method and URL. A URL recorded several times replays
+/// its responses in order, repeating the last one.
+use anyhow::{Error, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures::stream::{self, StreamExt};
+use reqwest::ResponseBuilderExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+use crate::config::{CassetteConfig, CassetteMode};
+use crate::core::types::url_serde;
+
+/// One recorded exchange, a line of the cassette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub method: String,
+    #[serde(with = "url_serde")]
+    pub url: Url,
+    /// URL the response came from, after redirects
+    #[serde(with = "url_serde")]
+    pub final_url: Url,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Response body, base64-encoded
+    pub body: String,
+}
+
+impl CassetteEntry {
+    fn response_builder(&self) -> http::response::Builder {
+        let mut builder = http::Response::builder()
+            .status(self.status)
+            .url(self.final_url.clone());
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder
+    }
+
+    fn into_response(self) -> Result<reqwest::Response, Error> {
+        let body = STANDARD.decode(&self.body)?;
+        Ok(reqwest::Response::from(self.response_builder().body(body)?))
+    }
+}
+
+enum Tape {
+    Record(Arc<Mutex<std::fs::File>>),
+    Replay(Mutex<HashMap<(String, String), VecDeque<CassetteEntry>>>),
+}
+
+/// HTTP exchanges recorded to or replayed from a cassette file
+pub struct Cassette {
+    path: PathBuf,
+    tape: Tape,
+}
+
+impl Cassette {
+    /// Open the cassette of `config`, appending in record mode and loading it in replay mode
+    pub fn open(config: &CassetteConfig) -> Result<Self, Error> {
+        let tape = match config.mode {
+            CassetteMode::Record => {
+                if let Some(parent) = config.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&config.path)?;
+                Tape::Record(Arc::new(Mutex::new(file)))
+            }
+            CassetteMode::Replay => Tape::Replay(Mutex::new(load_entries(&config.path)?)),
+        };
+        Ok(Self {
+            path: config.path.clone(),
+            tape,
+        })
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.tape, Tape::Replay(_))
+    }
+
+    /// Execute `request` through the cassette
+    ///
+    /// Recording hands the response on as soon as its headers arrive; the body is
+    /// copied while the caller reads it, and the exchange is written to the
+    /// cassette once the body is read to the end. A body abandoned early, or cut
+    /// off by an error, is not recorded.
+    pub async fn execute(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, Error> {
+        let key = (request.method().to_string(), request.url().to_string());
+        match &self.tape {
+            Tape::Replay(entries) => {
+                let entry = {
+                    let mut entries = entries.lock().unwrap_or_else(|e| e.into_inner());
+                    let queue = entries.get_mut(&key).ok_or_else(|| {
+                        anyhow!(
+                            "No recorded response for {} {} in cassette {}",
+                            key.0,
+                            key.1,
+                            self.path.display()
+                        )
+                    })?;
+                    if queue.len() > 1 {
+                        queue.pop_front()
+                    } else {
+                        queue.front().cloned()
+                    }
+                };
+                entry
+                    .ok_or_else(|| anyhow!("Empty cassette entry for {}", key.1))?
+                    .into_response()
+            }
+            Tape::Record(file) => {
+                let url = request.url().clone();
+                let response = client.execute(request).await?;
+                let entry = CassetteEntry {
+                    method: key.0,
+                    url,
+                    final_url: response.url().clone(),
+                    status: response.status().as_u16(),
+                    headers: response
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.to_string(),
+                                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                            )
+                        })
+                        .collect(),
+                    body: String::new(),
+                };
+                let builder = entry.response_builder();
+                let state = (response.bytes_stream(), Vec::new(), entry, Arc::clone(file));
+                let body = stream::unfold(Some(state), |state| async move {
+                    let (mut chunks, mut body, mut entry, file) = state?;
+                    match chunks.next().await {
+                        Some(Ok(chunk)) => {
+                            body.extend_from_slice(&chunk);
+                            Some((Ok(chunk), Some((chunks, body, entry, file))))
+                        }
+                        Some(Err(e)) => Some((Err(e), None)),
+                        None => {
+                            entry.body = STANDARD.encode(&body);
+                            if let Err(e) = append_entry(&file, &entry) {
+                                tracing::warn!(
+                                    url = %entry.url,
+                                    error = %e,
+                                    "Failed to record exchange to the cassette"
+                                );
+                            }
+                            None
+                        }
+                    }
+                });
+                Ok(reqwest::Response::from(
+                    builder.body(reqwest::Body::wrap_stream(body))?,
+                ))
+            }
+        }
+    }
+}
+
+fn append_entry(file: &Mutex<std::fs::File>, entry: &CassetteEntry) -> Result<(), Error> {
+    let line = serde_json::to_string(entry)?;
+    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+fn load_entries(path: &Path) -> Result<HashMap<(String, String), VecDeque<CassetteEntry>>, Error> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow!("Failed to open cassette {}: {}", path.display(), e))?;
+    let mut entries: HashMap<(String, String), VecDeque<CassetteEntry>> = HashMap::new();
+    for (index, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: CassetteEntry = serde_json::from_str(&line).map_err(|e| {
+            anyhow!(
+                "Invalid cassette entry at {}:{}: {}",
+                path.display(),
+                index + 1,
+                e
+            )
+        })?;
+        entries
+            .entry((entry.method.clone(), entry.url.to_string()))
+            .or_default()
+            .push_back(entry);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recorded_responses_replay_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let url = Url::parse("https://a.example/page").unwrap();
+        let entry = |status: u16, body: &str| CassetteEntry {
+            method: "GET".to_string(),
+            url: url.clone(),
+            final_url: Url::parse("https://a.example/moved").unwrap(),
+            status,
+            headers: vec![("content-type".to_string(), "text/html".to_string())],
+            body: STANDARD.encode(body),
+        };
+        let lines = [entry(503, "busy"), entry(200, "<p>hello</p>")]
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect::<String>();
+        std::fs::write(&path, lines).unwrap();
+
+        let cassette = Cassette::open(&CassetteConfig {
+            path,
+            mode: CassetteMode::Replay,
+        })
+        .unwrap();
+        assert!(cassette.is_replay());
+        let client = reqwest::Client::new();
+
+        let first = cassette
+            .execute(&client, client.get(url.clone()).build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(first.status().as_u16(), 503);
+        for _ in 0..2 {
+            let response = cassette
+                .execute(&client, client.get(url.clone()).build().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.url().as_str(), "https://a.example/moved");
+            assert_eq!(response.headers()["content-type"], "text/html");
+            assert_eq!(response.text().await.unwrap(), "<p>hello</p>");
+        }
+
+        let unrecorded = client.get("https://b.example/").build().unwrap();
+        assert!(cassette.execute(&client, unrecorded).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recording_writes_the_exchange_once_the_body_is_read() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/page", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let _ = stream.read(&mut request).await;
+            let body = "<p>recorded</p>";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let cassette = Cassette::open(&CassetteConfig {
+            path: path.clone(),
+            mode: CassetteMode::Record,
+        })
+        .unwrap();
+        let client = reqwest::Client::new();
+        let response = cassette
+            .execute(&client, client.get(url.clone()).build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        // Nothing is written before the caller reads the body
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        assert_eq!(response.text().await.unwrap(), "<p>recorded</p>");
+
+        let replay = Cassette::open(&CassetteConfig {
+            path,
+            mode: CassetteMode::Replay,
+        })
+        .unwrap();
+        let replayed = replay
+            .execute(&client, client.get(url).build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(replayed.headers()["content-type"], "text/html");
+        assert_eq!(replayed.text().await.unwrap(), "<p>recorded</p>");
+    }
+}
@@ -1,14 +1,16 @@
 // HTTP client management and configuration
 use anyhow::Error;
-use reqwest::{Client, Proxy};
+use reqwest::Client;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 
-use crate::config::defaults;
+use crate::config::{ProxyConfig, defaults};
 
 /// HTTP client factory with common configuration and proxy support
+///
+/// Proxies are described by `ProxyConfig`; string helpers parse into it first.
 pub struct HttpClientFactory;
 
 impl HttpClientFactory {
@@ -26,14 +28,13 @@ impl HttpClientFactory {
 
     /// Create an HTTP client with proxy support
     pub fn create_proxy_client(proxy_url: &str, user_agent: &str) -> Result<Client, Error> {
-        let proxy = if proxy_url.starts_with("socks5://") {
-            Proxy::all(proxy_url)?
-        } else {
-            Proxy::http(proxy_url)?
-        };
+        Self::create_client_for_proxy(&ProxyConfig::parse(proxy_url)?, user_agent)
+    }
 
+    /// Create an HTTP client that sends all requests through `proxy`
+    pub fn create_client_for_proxy(proxy: &ProxyConfig, user_agent: &str) -> Result<Client, Error> {
         let client = Client::builder()
-            .proxy(proxy)
+            .proxy(proxy.to_reqwest()?)
             .redirect(reqwest::redirect::Policy::limited(defaults::MAX_REDIRECTS))
             .user_agent(user_agent)
             .timeout(Duration::from_secs(defaults::REQUEST_TIMEOUT_SECS))
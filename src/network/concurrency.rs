@@ -0,0 +1,257 @@
+/// Adaptive concurrency control (AIMD)
+///
+/// Response latency and outcomes are collected in windows, globally and per domain.
+/// When a window fills, its limit grows additively while p95 latency and the error
+/// rate stay under their targets, and shrinks multiplicatively as soon as either is
+/// exceeded. Per-domain limits are enforced with their own semaphores; the global
+/// limit is returned as an adjustment for the crawler to apply to its semaphore.
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::AdaptiveConcurrencyConfig;
+
+/// A changed concurrency limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcurrencyAdjustment {
+    /// Domain of the limit; `None` for the global limit
+    pub domain: Option<String>,
+    pub previous: usize,
+    pub limit: usize,
+    /// p95 latency of the window that caused the change
+    pub p95_latency_ms: u64,
+    pub error_rate: f64,
+}
+
+impl ConcurrencyAdjustment {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} concurrency {} -> {} (p95 {} ms, {:.1}% errors)",
+            self.domain.as_deref().unwrap_or("global"),
+            self.previous,
+            self.limit,
+            self.p95_latency_ms,
+            self.error_rate * 100.0
+        )
+    }
+}
+
+/// Samples of the current window
+#[derive(Debug, Default)]
+struct Window {
+    latencies_ms: Vec<u64>,
+    failures: usize,
+}
+
+impl Window {
+    fn push(&mut self, latency: Duration, success: bool) {
+        self.latencies_ms.push(latency.as_millis() as u64);
+        if !success {
+            self.failures += 1;
+        }
+    }
+
+    /// p95 latency and error rate, resetting the window
+    fn take(&mut self) -> (u64, f64) {
+        let mut latencies = std::mem::take(&mut self.latencies_ms);
+        let failures = std::mem::take(&mut self.failures);
+        latencies.sort_unstable();
+        let index = (latencies.len() * 95).div_ceil(100).saturating_sub(1);
+        let p95 = latencies.get(index).copied().unwrap_or(0);
+        (p95, failures as f64 / latencies.len().max(1) as f64)
+    }
+}
+
+struct DomainLimit {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+    window: Window,
+}
+
+/// AIMD controller for the global and per-domain request concurrency
+pub struct AdaptiveConcurrency {
+    config: AdaptiveConcurrencyConfig,
+    global: Mutex<Window>,
+    domains: Mutex<HashMap<String, DomainLimit>>,
+}
+
+impl AdaptiveConcurrency {
+    pub fn new(config: AdaptiveConcurrencyConfig) -> Self {
+        Self {
+            config,
+            global: Mutex::new(Window::default()),
+            domains: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a request slot on `domain`
+    pub async fn acquire(&self, domain: &str) -> Result<OwnedSemaphorePermit, Error> {
+        let semaphore = {
+            let mut domains = self.domains.lock().unwrap_or_else(|e| e.into_inner());
+            let initial = self
+                .config
+                .initial_domain_concurrency
+                .clamp(1, self.config.max_domain_concurrency.max(1));
+            Arc::clone(
+                &domains
+                    .entry(domain.to_string())
+                    .or_insert_with(|| DomainLimit {
+                        semaphore: Arc::new(Semaphore::new(initial)),
+                        limit: initial,
+                        window: Window::default(),
+                    })
+                    .semaphore,
+            )
+        };
+        Ok(semaphore.acquire_owned().await?)
+    }
+
+    /// Current limit of `domain`, once a request to it has been made
+    pub fn domain_limit(&self, domain: &str) -> Option<usize> {
+        self.domains
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(domain)
+            .map(|limit| limit.limit)
+    }
+
+    /// Record a response, returning the limits it changed
+    ///
+    /// `global_limit` is the crawler's current limit; a global adjustment is only
+    /// reported, not applied. Domain adjustments take effect immediately.
+    pub fn record(
+        &self,
+        domain: &str,
+        latency: Duration,
+        success: bool,
+        global_limit: usize,
+    ) -> Vec<ConcurrencyAdjustment> {
+        let window_size = self.config.window_size.max(1);
+        let mut adjustments = Vec::new();
+
+        {
+            let mut domains = self.domains.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(entry) = domains.get_mut(domain) {
+                entry.window.push(latency, success);
+                if entry.window.latencies_ms.len() >= window_size {
+                    let (p95, error_rate) = entry.window.take();
+                    let limit = self.next_limit(
+                        entry.limit,
+                        p95,
+                        error_rate,
+                        self.config.max_domain_concurrency,
+                    );
+                    if limit != entry.limit {
+                        resize_permits(&entry.semaphore, entry.limit, limit);
+                        adjustments.push(ConcurrencyAdjustment {
+                            domain: Some(domain.to_string()),
+                            previous: entry.limit,
+                            limit,
+                            p95_latency_ms: p95,
+                            error_rate,
+                        });
+                        entry.limit = limit;
+                    }
+                }
+            }
+        }
+
+        let mut global = self.global.lock().unwrap_or_else(|e| e.into_inner());
+        global.push(latency, success);
+        if global.latencies_ms.len() >= window_size {
+            let (p95, error_rate) = global.take();
+            let limit = self.next_limit(global_limit, p95, error_rate, self.config.max_concurrency);
+            if limit != global_limit {
+                adjustments.push(ConcurrencyAdjustment {
+                    domain: None,
+                    previous: global_limit,
+                    limit,
+                    p95_latency_ms: p95,
+                    error_rate,
+                });
+            }
+        }
+
+        adjustments
+    }
+
+    fn next_limit(&self, current: usize, p95_ms: u64, error_rate: f64, max: usize) -> usize {
+        let min = self.config.min_concurrency.max(1);
+        let max = max.max(min);
+        let overloaded =
+            p95_ms > self.config.target_p95_latency_ms || error_rate > self.config.max_error_rate;
+        let next = if overloaded {
+            (current as f64 * self.config.decrease_factor.clamp(0.0, 1.0)).floor() as usize
+        } else {
+            current.saturating_add(self.config.additive_increase)
+        };
+        next.clamp(min, max)
+    }
+}
+
+/// Resize a semaphore from `previous` to `limit` permits
+///
+/// Permits held by in-flight requests are retired as they are returned.
+pub fn resize_permits(semaphore: &Arc<Semaphore>, previous: usize, limit: usize) {
+    if limit > previous {
+        semaphore.add_permits(limit - previous);
+    } else if limit < previous {
+        let excess = previous - limit;
+        let forgotten = semaphore.forget_permits(excess);
+        if forgotten < excess {
+            let semaphore = Arc::clone(semaphore);
+            tokio::spawn(async move {
+                if let Ok(permits) = semaphore
+                    .acquire_many_owned((excess - forgotten) as u32)
+                    .await
+                {
+                    permits.forget();
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_grow_additively_and_shrink_multiplicatively() {
+        let controller = AdaptiveConcurrency::new(AdaptiveConcurrencyConfig {
+            initial_domain_concurrency: 4,
+            window_size: 10,
+            ..AdaptiveConcurrencyConfig::default()
+        });
+        drop(controller.acquire("a.example").await.unwrap());
+
+        let fast = Duration::from_millis(100);
+        let mut adjustments = Vec::new();
+        for _ in 0..10 {
+            adjustments.extend(controller.record("a.example", fast, true, 16));
+        }
+        assert_eq!(controller.domain_limit("a.example"), Some(5));
+        assert_eq!(adjustments.len(), 2);
+        assert_eq!(adjustments[1].domain, None);
+        assert_eq!(adjustments[1].limit, 17);
+
+        // Two failures in ten exceed the 10% error budget
+        let mut adjustments = Vec::new();
+        for i in 0..10 {
+            adjustments.extend(controller.record("a.example", fast, i >= 2, 17));
+        }
+        assert_eq!(controller.domain_limit("a.example"), Some(2));
+        assert_eq!(adjustments[1].limit, 8);
+        assert!((adjustments[0].error_rate - 0.2).abs() < 1e-9);
+
+        // Slow responses shrink the limit, but never below the minimum
+        for _ in 0..20 {
+            controller.record("a.example", Duration::from_secs(5), true, 1);
+        }
+        assert_eq!(controller.domain_limit("a.example"), Some(1));
+        assert_eq!(controller.domain_limit("b.example"), None);
+    }
+}
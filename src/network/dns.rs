@@ -1,6 +1,8 @@
 use anyhow::Error;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
 use std::collections::HashMap;
-use std::net::{IpAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -8,11 +10,16 @@ use tokio::sync::Mutex;
 use tokio::time::Instant;
 
 use crate::config::defaults;
-use crate::core::DnsResolver;
+use crate::core::{DnsResolver, host_key};
 
 /// DNS resolution implementation with caching
+///
+/// Clones share one cache.
+#[derive(Clone)]
 pub struct DnsCache {
     cache: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    /// Fixed addresses that bypass DNS
+    overrides: HashMap<String, IpAddr>,
 }
 
 impl Default for DnsCache {
@@ -25,21 +32,63 @@ impl DnsCache {
     pub fn new() -> Self {
         Self {
             cache: Arc::new(Mutex::new(HashMap::new())),
+            overrides: HashMap::new(),
         }
     }
 
+    /// Answer these hosts with a fixed address instead of resolving them
+    pub fn with_overrides(mut self, overrides: HashMap<String, IpAddr>) -> Self {
+        self.overrides = overrides
+            .into_iter()
+            .map(|(host, ip)| (host_key(&host), ip))
+            .collect();
+        self
+    }
+
+    /// Whether `hostname` resolves without a lookup: overridden, or cached and fresh
+    pub async fn is_cached(&self, hostname: &str) -> bool {
+        let hostname = host_key(hostname);
+        if self.overrides.contains_key(&hostname) {
+            return true;
+        }
+        let ttl = Duration::from_secs(defaults::DNS_CACHE_TTL_SECS);
+        self.cache
+            .lock()
+            .await
+            .get(&hostname)
+            .is_some_and(|(_, cached_at)| cached_at.elapsed() < ttl)
+    }
+
     pub async fn get_cache(&self) -> Arc<Mutex<HashMap<String, (String, Instant)>>> {
         self.cache.clone()
     }
 }
 
+/// Installed with `ClientBuilder::dns_resolver`, connections use the cached
+/// address, so hosts resolved ahead of time by `DnsPrefetcher` cost no lookup
+impl Resolve for DnsCache {
+    fn resolve(&self, name: Name) -> Resolving {
+        let cache = self.clone();
+        Box::pin(async move {
+            let ip = cache.resolve_hostname(name.as_str()).await?;
+            Ok(Box::new(std::iter::once(SocketAddr::new(ip, 0))) as Addrs)
+        })
+    }
+}
+
 impl DnsResolver for DnsCache {
     /// Resolve hostname to IP address with caching
     async fn resolve_hostname(&self, hostname: &str) -> Result<IpAddr, Error> {
+        // Host names are case-insensitive; overrides and the cache are keyed by host key
+        let hostname = host_key(hostname);
+        if let Some(ip) = self.overrides.get(&hostname) {
+            return Ok(*ip);
+        }
+
         // Check cache first with TTL validation
         {
             let dns_cache = self.cache.lock().await;
-            if let Some((ip_str, cached_at)) = dns_cache.get(hostname) {
+            if let Some((ip_str, cached_at)) = dns_cache.get(&hostname) {
                 let ttl = Duration::from_secs(defaults::DNS_CACHE_TTL_SECS);
                 if cached_at.elapsed() < ttl {
                     return Ok(IpAddr::from_str(ip_str)?);
@@ -48,7 +97,7 @@ impl DnsResolver for DnsCache {
         }
 
         // Cache miss or expired, perform DNS resolution
-        let hostname_clone = hostname.to_string();
+        let hostname_clone = hostname.clone();
         let resolved_ip = tokio::task::spawn_blocking(move || {
             let socket_addr = format!("{}:80", hostname_clone);
             socket_addr
@@ -65,10 +114,7 @@ impl DnsResolver for DnsCache {
         // Update cache with the resolved IP
         {
             let mut dns_cache = self.cache.lock().await;
-            dns_cache.insert(
-                hostname.to_string(),
-                (resolved_ip.to_string(), Instant::now()),
-            );
+            dns_cache.insert(hostname, (resolved_ip.to_string(), Instant::now()));
         }
 
         Ok(resolved_ip)
@@ -76,10 +122,15 @@ impl DnsResolver for DnsCache {
 
     /// Resolve domain to IP address with caching
     async fn resolve_domain(&self, domain: &str) -> Result<String, Error> {
+        let domain = host_key(domain);
+        if let Some(ip) = self.overrides.get(&domain) {
+            return Ok(ip.to_string());
+        }
+
         // Check cache first
         {
             let dns_cache = self.cache.lock().await;
-            if let Some((ip, cached_at)) = dns_cache.get(domain) {
+            if let Some((ip, cached_at)) = dns_cache.get(&domain) {
                 let ttl = Duration::from_secs(defaults::DNS_CACHE_TTL_SECS);
                 if cached_at.elapsed() < ttl {
                     return Ok(ip.clone());
@@ -88,7 +139,7 @@ impl DnsResolver for DnsCache {
         }
 
         // Cache miss or expired, perform DNS resolution
-        let domain_clone = domain.to_string();
+        let domain_clone = domain.clone();
         let resolved = tokio::task::spawn_blocking(move || {
             let socket_addr = format!("{}:80", domain_clone);
             socket_addr
@@ -104,7 +155,7 @@ impl DnsResolver for DnsCache {
         // Update cache
         {
             let mut dns_cache = self.cache.lock().await;
-            dns_cache.insert(domain.to_string(), (ip.clone(), Instant::now()));
+            dns_cache.insert(domain, (ip.clone(), Instant::now()));
         }
 
         Ok(ip)
@@ -130,3 +181,30 @@ impl DnsResolver for DnsCache {
         stats
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overrides_match_host_names_in_any_case() {
+        let ip: IpAddr = "10.0.0.7".parse().unwrap();
+        let cache = DnsCache::new()
+            .with_overrides(HashMap::from([("Staging.Corp.Example".to_string(), ip)]));
+
+        for host in ["staging.corp.example", "STAGING.corp.example"] {
+            assert!(cache.is_cached(host).await);
+            assert_eq!(cache.resolve_hostname(host).await.unwrap(), ip);
+            assert_eq!(cache.resolve_domain(host).await.unwrap(), "10.0.0.7");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolved_hosts_are_cached_under_their_host_key() {
+        let cache = DnsCache::new();
+        let ip = cache.resolve_hostname("LOCALHOST").await.unwrap();
+        assert!(ip.is_loopback());
+        assert!(cache.is_cached("localhost").await);
+        assert!(cache.get_dns_cache_stats().await.contains_key("localhost"));
+    }
+}
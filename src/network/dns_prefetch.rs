@@ -0,0 +1,179 @@
+/// DNS prefetching for queued tasks
+///
+/// A fetch resolves its host before connecting, so a crawl moving on to a new
+/// cluster of domains pays a cold lookup for each of them. `DnsPrefetcher` looks at
+/// the tasks about to be dequeued and resolves their hosts into the crawler's
+/// `DnsCache` ahead of time. Hosts already cached or overridden are left alone and
+/// at most `max_concurrent` lookups run at once.
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use url::{Host, Url};
+
+use super::dns::DnsCache;
+use crate::config::DnsPrefetchConfig;
+use crate::core::DnsResolver;
+use crate::queue::TaskQueue;
+
+/// Counts of the hosts the prefetcher looked at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsPrefetchStats {
+    /// Hosts resolved ahead of their fetch
+    pub resolved: u64,
+    /// Hosts skipped because the cache already had them
+    pub cached: u64,
+    pub failed: u64,
+}
+
+/// Resolves the hosts of upcoming tasks into a shared DNS cache
+pub struct DnsPrefetcher {
+    config: DnsPrefetchConfig,
+    resolver: DnsCache,
+    stats: Mutex<DnsPrefetchStats>,
+}
+
+impl DnsPrefetcher {
+    /// Prefetcher filling `resolver`, a clone of the cache fetches use
+    pub fn new(config: DnsPrefetchConfig, resolver: DnsCache) -> Self {
+        Self {
+            config,
+            resolver,
+            stats: Mutex::new(DnsPrefetchStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> DnsPrefetchStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Resolve the hosts of `urls` missing from the cache
+    ///
+    /// IP literals and repeated hosts are skipped. Returns how many hosts were
+    /// resolved.
+    pub async fn prefetch<'a>(&self, urls: impl IntoIterator<Item = &'a Url>) -> usize {
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        let mut cached = 0;
+        for url in urls {
+            let Some(Host::Domain(host)) = url.host() else {
+                continue;
+            };
+            if !seen.insert(host) {
+                continue;
+            }
+            if self.resolver.is_cached(host).await {
+                cached += 1;
+            } else {
+                missing.push(host.to_string());
+            }
+        }
+
+        let outcomes: Vec<bool> = stream::iter(missing)
+            .map(|host| async move {
+                match self.resolver.resolve_domain(&host).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        tracing::debug!(host = %host, error = %e, "DNS prefetch failed");
+                        false
+                    }
+                }
+            })
+            .buffer_unordered(self.config.max_concurrent.max(1))
+            .collect()
+            .await;
+        let resolved = outcomes.iter().filter(|resolved| **resolved).count();
+
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.resolved += resolved as u64;
+        stats.cached += cached;
+        stats.failed += (outcomes.len() - resolved) as u64;
+        resolved
+    }
+
+    /// Resolve the hosts of the next `lookahead` tasks of `queue`
+    pub async fn prefetch_upcoming(&self, queue: &TaskQueue) -> usize {
+        let urls = queue.upcoming_urls(self.config.lookahead).await;
+        self.prefetch(&urls).await
+    }
+
+    /// Prefetch for `queue` every `interval_ms` until the handle is aborted
+    pub fn schedule(self: Arc<Self>, queue: Arc<TaskQueue>) -> JoinHandle<()> {
+        let interval = Duration::from_millis(self.config.interval_ms.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                self.prefetch_upcoming(&queue).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskPriority;
+    use reqwest::dns::Resolve;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_prefetch_resolves_uncached_hosts_of_upcoming_tasks() {
+        let resolver = DnsCache::new().with_overrides(HashMap::from([(
+            "staging.internal".to_string(),
+            "10.0.0.5".parse().unwrap(),
+        )]));
+        let prefetcher = DnsPrefetcher::new(DnsPrefetchConfig::default(), resolver.clone());
+
+        let queue = TaskQueue::new(4, 0);
+        for url in [
+            "http://localhost/a",
+            "http://localhost/b",
+            "http://127.0.0.1/",
+            "http://staging.internal/",
+        ] {
+            queue
+                .enqueue_task(Url::parse(url).unwrap(), TaskPriority::Normal)
+                .await
+                .unwrap();
+        }
+
+        assert!(!resolver.is_cached("localhost").await);
+        assert_eq!(prefetcher.prefetch_upcoming(&queue).await, 1);
+        assert!(resolver.is_cached("localhost").await);
+        assert_eq!(
+            prefetcher.stats(),
+            DnsPrefetchStats {
+                resolved: 1,
+                cached: 1,
+                failed: 0,
+            }
+        );
+
+        // A second pass finds everything cached
+        assert_eq!(prefetcher.prefetch_upcoming(&queue).await, 0);
+        assert_eq!(prefetcher.stats().cached, 3);
+
+        // Connections resolving through the cache use the prefetched address
+        let cached = resolver.resolve_domain("localhost").await.unwrap();
+        let addresses: Vec<_> = Resolve::resolve(&resolver, "localhost".parse().unwrap())
+            .await
+            .unwrap()
+            .map(|address| address.ip().to_string())
+            .collect();
+        assert_eq!(addresses, vec![cached]);
+
+        let limited = DnsPrefetcher::new(
+            DnsPrefetchConfig {
+                lookahead: 0,
+                ..DnsPrefetchConfig::default()
+            },
+            DnsCache::new(),
+        );
+        assert_eq!(limited.prefetch_upcoming(&queue).await, 0);
+        assert_eq!(limited.stats(), DnsPrefetchStats::default());
+    }
+}
@@ -0,0 +1,138 @@
+/// Decoding of compressed response bodies
+///
+/// The crawler advertises gzip, deflate, brotli and zstd and decodes bodies itself,
+/// so the encoding of every response can be recorded with its result. Decoded
+/// output is capped: a body that would expand beyond the page size limit fails
+/// instead of filling memory (a "compression bomb").
+use anyhow::{Error, anyhow};
+use bytes::Bytes;
+use std::io::Read;
+
+/// Size of the brotli decoder's internal buffer
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+/// A content coding this crawler can decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Codings of a `Content-Encoding` header, in the order they were applied
+    ///
+    /// `identity` is dropped; unknown codings are an error since the body could
+    /// not be read.
+    pub fn parse_header(value: &str) -> Result<Vec<Self>, Error> {
+        value
+            .split(',')
+            .map(|coding| coding.trim().to_ascii_lowercase())
+            .filter(|coding| !coding.is_empty() && coding != "identity")
+            .map(|coding| match coding.as_str() {
+                "gzip" | "x-gzip" => Ok(ContentEncoding::Gzip),
+                "deflate" => Ok(ContentEncoding::Deflate),
+                "br" => Ok(ContentEncoding::Brotli),
+                "zstd" => Ok(ContentEncoding::Zstd),
+                other => Err(anyhow!("Unsupported content encoding: {}", other)),
+            })
+            .collect()
+    }
+
+    /// Token of the coding in `Content-Encoding`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Deflate => "deflate",
+            ContentEncoding::Brotli => "br",
+            ContentEncoding::Zstd => "zstd",
+        }
+    }
+
+    fn decode(self, body: &[u8], max_bytes: usize) -> Result<Vec<u8>, Error> {
+        match self {
+            ContentEncoding::Gzip => {
+                read_capped(flate2::read::MultiGzDecoder::new(body), max_bytes)
+            }
+            // "deflate" is meant to be zlib-wrapped, but some servers send raw deflate
+            ContentEncoding::Deflate => {
+                read_capped(flate2::read::ZlibDecoder::new(body), max_bytes)
+                    .or_else(|_| read_capped(flate2::read::DeflateDecoder::new(body), max_bytes))
+            }
+            ContentEncoding::Brotli => read_capped(
+                brotli_decompressor::Decompressor::new(body, BROTLI_BUFFER_SIZE),
+                max_bytes,
+            ),
+            ContentEncoding::Zstd => {
+                read_capped(zstd::stream::read::Decoder::new(body)?, max_bytes)
+            }
+        }
+    }
+}
+
+/// Decode a body received with `encodings`, failing once it exceeds `max_bytes`
+///
+/// Without encodings the chunks are returned as they are.
+pub fn decode_body(
+    chunks: Vec<Bytes>,
+    encodings: &[ContentEncoding],
+    max_bytes: usize,
+) -> Result<Vec<Bytes>, Error> {
+    if encodings.is_empty() {
+        return Ok(chunks);
+    }
+    let mut body = chunks.concat();
+    // Codings are listed in the order they were applied, so undo them backwards
+    for encoding in encodings.iter().rev() {
+        body = encoding
+            .decode(&body, max_bytes)
+            .map_err(|e| anyhow!("Failed to decode {} body: {}", encoding.as_str(), e))?;
+    }
+    Ok(vec![Bytes::from(body)])
+}
+
+fn read_capped(reader: impl Read, max_bytes: usize) -> Result<Vec<u8>, Error> {
+    let mut decoded = Vec::new();
+    reader
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut decoded)?;
+    if decoded.len() > max_bytes {
+        return Err(anyhow!("decoded body exceeds {} bytes", max_bytes));
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_stacked_encodings_decode_within_limit() {
+        let html =
+            "<html><body>".to_string() + &"compressible text ".repeat(200) + "</body></html>";
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(html.as_bytes()).unwrap();
+        let gzipped = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(gzipped.as_slice(), 0).unwrap();
+
+        let encodings = ContentEncoding::parse_header("gzip, identity, zstd").unwrap();
+        assert_eq!(
+            encodings,
+            vec![ContentEncoding::Gzip, ContentEncoding::Zstd]
+        );
+        let chunks = vec![
+            Bytes::copy_from_slice(&zstd[..10]),
+            Bytes::copy_from_slice(&zstd[10..]),
+        ];
+        let decoded = decode_body(chunks.clone(), &encodings, html.len()).unwrap();
+        assert_eq!(decoded.concat(), html.as_bytes());
+
+        let error = decode_body(chunks, &encodings, html.len() - 1).unwrap_err();
+        assert!(error.to_string().contains("exceeds"));
+        assert!(ContentEncoding::parse_header("compress").is_err());
+        let plain = vec![Bytes::from_static(b"plain")];
+        assert_eq!(decode_body(plain.clone(), &[], 1).unwrap(), plain);
+    }
+}
@@ -0,0 +1,90 @@
+/// Recognizing blocked responses for retry escalation
+///
+/// A request counts as blocked when it fails with one of the configured status codes
+/// or returns a bot-detection page. Such requests are retried right away through
+/// another proxy and/or User-Agent, and the escalations taken are kept with the URL
+/// so the final error explains what was tried.
+use crate::config::RetryEscalationConfig;
+
+/// Why a response counts as blocked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockReason {
+    /// A trigger status code with its reason phrase
+    Status(u16, String),
+    /// A bot-detection page, with the marker that matched
+    BotPage(String),
+}
+
+impl std::fmt::Display for BlockReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockReason::Status(code, reason) => write!(f, "HTTP {} {}", code, reason),
+            BlockReason::BotPage(marker) => write!(f, "bot-detection page ('{}')", marker),
+        }
+    }
+}
+
+/// Whether `status` is one of the configured trigger statuses
+pub fn is_trigger_status(config: &RetryEscalationConfig, status: u16) -> bool {
+    config.trigger_statuses.contains(&status)
+}
+
+/// The first bot-detection marker found in the start of the body, if any
+pub fn detect_bot_page<B: AsRef<[u8]>>(
+    config: &RetryEscalationConfig,
+    chunks: &[B],
+) -> Option<String> {
+    if config.bot_markers.is_empty() {
+        return None;
+    }
+
+    let mut head = Vec::with_capacity(config.bot_scan_bytes);
+    for chunk in chunks {
+        let remaining = config.bot_scan_bytes.saturating_sub(head.len());
+        if remaining == 0 {
+            break;
+        }
+        let chunk = chunk.as_ref();
+        head.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+    }
+    let head = String::from_utf8_lossy(&head).to_lowercase();
+
+    config
+        .bot_markers
+        .iter()
+        .find(|marker| !marker.is_empty() && head.contains(&marker.to_lowercase()))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bot_markers_are_found_in_the_scanned_prefix() {
+        let config = RetryEscalationConfig {
+            bot_scan_bytes: 48,
+            ..RetryEscalationConfig::default()
+        };
+
+        let challenge = ["<html><title>Please solve the ", "CAPTCHA</title>"];
+        assert_eq!(
+            detect_bot_page(&config, &challenge),
+            Some("captcha".to_string())
+        );
+
+        // Markers past the scanned prefix do not count
+        let article = [
+            "<html><body><p>A long article about bots and the web, ",
+            "how a captcha works",
+        ];
+        assert_eq!(detect_bot_page(&config, &article), None);
+
+        assert!(is_trigger_status(&config, 429));
+        assert!(!is_trigger_status(&config, 404));
+        assert_eq!(
+            BlockReason::Status(403, "Forbidden".to_string()).to_string(),
+            "HTTP 403 Forbidden"
+        );
+    }
+}
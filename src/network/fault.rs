@@ -0,0 +1,264 @@
+/// Fault injection for exercising failure handling (`fault-injection` feature)
+///
+/// A `FaultInjector` attached with `WebCrawler::with_fault_injector` sits in front of
+/// every page request and, at configured probabilities, delays it, answers with a
+/// synthetic error status without contacting the server, fails it as a dropped
+/// connection, or corrupts the body of the real response. Retry logic, circuit
+/// breakers and metrics see these faults exactly like real ones, so integration
+/// tests can drive them against a local server. A fixed `seed` makes runs repeatable.
+use anyhow::{Error, anyhow};
+use bytes::Bytes;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use reqwest::ResponseBuilderExt;
+use reqwest::header::{CONTENT_LENGTH, HeaderValue, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use url::Url;
+
+use crate::core::CrawlError;
+
+/// Probabilities and shapes of injected faults; every probability defaults to 0
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultInjectionConfig {
+    /// Seed of the fault dice; `None` seeds from entropy
+    pub seed: Option<u64>,
+    /// Probability that a request is held back before it is sent
+    pub delay_probability: f64,
+    /// Range the delay is drawn from, in milliseconds
+    pub delay_ms: (u64, u64),
+    /// Probability that a request is answered with one of `statuses` instead
+    pub status_probability: f64,
+    /// Synthetic statuses, picked uniformly; 429s carry `Retry-After: 1`
+    pub statuses: Vec<u16>,
+    /// Probability that a request fails as a reset connection
+    pub drop_probability: f64,
+    /// Probability that the body of a real response is truncated and garbled
+    pub corrupt_probability: f64,
+}
+
+impl Default for FaultInjectionConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            delay_probability: 0.0,
+            delay_ms: (100, 1000),
+            status_probability: 0.0,
+            statuses: vec![429, 500],
+            drop_probability: 0.0,
+            corrupt_probability: 0.0,
+        }
+    }
+}
+
+/// Number of faults injected so far, by kind
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultStats {
+    pub delayed: u64,
+    pub synthetic_statuses: u64,
+    pub dropped: u64,
+    pub corrupted: u64,
+}
+
+/// Decides and applies faults for requests
+#[derive(Debug)]
+pub struct FaultInjector {
+    config: FaultInjectionConfig,
+    rng: Mutex<StdRng>,
+    delayed: AtomicU64,
+    synthetic_statuses: AtomicU64,
+    dropped: AtomicU64,
+    corrupted: AtomicU64,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultInjectionConfig) -> Result<Self, Error> {
+        for (name, probability) in [
+            ("delay_probability", config.delay_probability),
+            ("status_probability", config.status_probability),
+            ("drop_probability", config.drop_probability),
+            ("corrupt_probability", config.corrupt_probability),
+        ] {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(anyhow!(
+                    "{} must be within 0..=1, got {}",
+                    name,
+                    probability
+                ));
+            }
+        }
+        if config.delay_ms.0 > config.delay_ms.1 {
+            return Err(anyhow!(
+                "delay_ms range {}..{} is empty",
+                config.delay_ms.0,
+                config.delay_ms.1
+            ));
+        }
+        if config.status_probability > 0.0 && config.statuses.is_empty() {
+            return Err(anyhow!("status_probability is set but statuses is empty"));
+        }
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            config,
+            rng: Mutex::new(rng),
+            delayed: AtomicU64::new(0),
+            synthetic_statuses: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            corrupted: AtomicU64::new(0),
+        })
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            delayed: self.delayed.load(Ordering::Relaxed),
+            synthetic_statuses: self.synthetic_statuses.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            corrupted: self.corrupted.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Apply the faults decided before sending: sleep, then possibly answer or fail
+    /// the request in place of the server
+    ///
+    /// `Ok(None)` means the request goes out as usual.
+    pub async fn before_request(&self, url: &Url) -> Result<Option<reqwest::Response>, Error> {
+        let (delay, status, drop) = {
+            let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+            let delay = rng
+                .gen_bool(self.config.delay_probability)
+                .then(|| rng.gen_range(self.config.delay_ms.0..=self.config.delay_ms.1));
+            let status = rng
+                .gen_bool(self.config.status_probability)
+                .then(|| self.config.statuses[rng.gen_range(0..self.config.statuses.len())]);
+            let drop = rng.gen_bool(self.config.drop_probability);
+            (delay, status, drop)
+        };
+
+        if let Some(delay_ms) = delay {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+        if drop {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(CrawlError::NetworkError(format!(
+                "connection reset by peer (injected fault) for {}",
+                url
+            ))
+            .into());
+        }
+        let Some(status) = status else {
+            return Ok(None);
+        };
+        self.synthetic_statuses.fetch_add(1, Ordering::Relaxed);
+        let mut builder = http::Response::builder().status(status).url(url.clone());
+        if status == 429 {
+            builder = builder.header(RETRY_AFTER, "1");
+        }
+        Ok(Some(reqwest::Response::from(
+            builder.body(format!("injected fault: HTTP {}", status))?,
+        )))
+    }
+
+    /// Possibly corrupt the body of a response the server sent
+    pub async fn after_response(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, Error> {
+        let corrupt = {
+            let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+            rng.gen_bool(self.config.corrupt_probability)
+        };
+        if !corrupt {
+            return Ok(response);
+        }
+        self.corrupted.fetch_add(1, Ordering::Relaxed);
+
+        let status = response.status();
+        let url = response.url().clone();
+        let mut headers = response.headers().clone();
+        let body = self.corrupt_body(&response.bytes().await?);
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+        let mut builder = http::Response::builder().status(status).url(url);
+        if let Some(builder_headers) = builder.headers_mut() {
+            *builder_headers = headers;
+        }
+        Ok(reqwest::Response::from(builder.body(body)?))
+    }
+
+    /// Cut the body at a random point and overwrite a few bytes with invalid UTF-8
+    fn corrupt_body(&self, body: &Bytes) -> Vec<u8> {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        let mut body = body.to_vec();
+        body.truncate(rng.gen_range(0..=body.len()));
+        for _ in 0..body.len().min(16) {
+            let index = rng.gen_range(0..body.len());
+            body[index] = 0xFF;
+        }
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_faults_follow_configured_probabilities() {
+        let url = Url::parse("https://a.example/page").unwrap();
+        let always_429 = FaultInjector::new(FaultInjectionConfig {
+            seed: Some(7),
+            status_probability: 1.0,
+            statuses: vec![429],
+            ..FaultInjectionConfig::default()
+        })
+        .unwrap();
+        let response = always_429.before_request(&url).await.unwrap().unwrap();
+        assert_eq!(response.status().as_u16(), 429);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+        assert_eq!(response.url(), &url);
+
+        let dropping = FaultInjector::new(FaultInjectionConfig {
+            seed: Some(7),
+            drop_probability: 1.0,
+            corrupt_probability: 1.0,
+            ..FaultInjectionConfig::default()
+        })
+        .unwrap();
+        let error = dropping.before_request(&url).await.unwrap_err();
+        assert!(CrawlError::from_anyhow_error(&error).is_retryable());
+
+        let original = "<html><body>".repeat(50);
+        let response = reqwest::Response::from(
+            http::Response::builder()
+                .status(200)
+                .body(original.clone())
+                .unwrap(),
+        );
+        let corrupted = dropping.after_response(response).await.unwrap();
+        assert_eq!(corrupted.status().as_u16(), 200);
+        assert_ne!(corrupted.bytes().await.unwrap(), original.as_bytes());
+        assert_eq!(
+            dropping.stats(),
+            FaultStats {
+                dropped: 1,
+                corrupted: 1,
+                ..FaultStats::default()
+            }
+        );
+
+        let quiet = FaultInjector::new(FaultInjectionConfig::default()).unwrap();
+        assert!(quiet.before_request(&url).await.unwrap().is_none());
+        assert!(
+            FaultInjector::new(FaultInjectionConfig {
+                drop_probability: 1.5,
+                ..FaultInjectionConfig::default()
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,171 @@
+/// Rendering per-domain header templates at request time
+///
+/// Templates are rendered once per request with a fresh timestamp and nonce, so
+/// signed API endpoints see a new signature on every attempt, retries included.
+use anyhow::Error;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::config::HeaderTemplate;
+
+/// Values substituted into one request's templates
+struct TemplateContext<'a> {
+    method: &'a str,
+    url: &'a Url,
+    timestamp_ms: u128,
+    nonce: String,
+    signature: Option<String>,
+}
+
+/// Header names and rendered values for a request to `url`
+pub fn render_headers(
+    template: &HeaderTemplate,
+    method: &str,
+    url: &Url,
+) -> Result<Vec<(String, String)>, Error> {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    render_with(template, method, url, timestamp_ms, nonce)
+}
+
+fn render_with(
+    template: &HeaderTemplate,
+    method: &str,
+    url: &Url,
+    timestamp_ms: u128,
+    nonce: String,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut context = TemplateContext {
+        method,
+        url,
+        timestamp_ms,
+        nonce,
+        signature: None,
+    };
+
+    let signed = template
+        .headers
+        .values()
+        .any(|value| value.contains("{signature}"));
+    if signed {
+        let secret = match (&template.secret, &template.secret_env) {
+            (Some(secret), _) => secret.clone(),
+            (None, Some(variable)) => std::env::var(variable).map_err(|_| {
+                anyhow::anyhow!("Header template secret variable {} is not set", variable)
+            })?,
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "Header template uses {{signature}} but has no secret"
+                ));
+            }
+        };
+        let message = render(&template.signing_string, &context)?;
+        context.signature = Some(hmac_sha256_hex(&secret, message.as_bytes()));
+    }
+
+    template
+        .headers
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), render(value, &context)?)))
+        .collect()
+}
+
+/// Substitute `{variable}`s; unknown variables are an error
+fn render(template: &str, context: &TemplateContext<'_>) -> Result<String, Error> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(anyhow::anyhow!(
+                "Unclosed variable in template '{}'",
+                template
+            ));
+        };
+        let name = &rest[start + 1..start + end];
+        match name {
+            "timestamp" => output.push_str(&(context.timestamp_ms / 1000).to_string()),
+            "timestamp_ms" => output.push_str(&context.timestamp_ms.to_string()),
+            "nonce" => output.push_str(&context.nonce),
+            "method" => output.push_str(context.method),
+            "host" => output.push_str(context.url.host_str().unwrap_or_default()),
+            "path" => {
+                output.push_str(context.url.path());
+                if let Some(query) = context.url.query() {
+                    output.push('?');
+                    output.push_str(query);
+                }
+            }
+            "signature" => match &context.signature {
+                Some(signature) => output.push_str(signature),
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "{{signature}} cannot be used in the signing string"
+                    ));
+                }
+            },
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown template variable {{{}}} in '{}'",
+                    other,
+                    template
+                ));
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn hmac_sha256_hex(secret: &str, message: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_are_rendered_and_signed() {
+        let mut template = HeaderTemplate {
+            secret: Some("key".to_string()),
+            signing_string: "{method} {path} {timestamp}".to_string(),
+            ..HeaderTemplate::default()
+        };
+        template
+            .headers
+            .insert("X-Timestamp".to_string(), "{timestamp}".to_string());
+        template
+            .headers
+            .insert("X-Nonce".to_string(), "n-{nonce}".to_string());
+        template
+            .headers
+            .insert("X-Signature".to_string(), "v1={signature}".to_string());
+        let url = Url::parse("https://api.example.com/v1/items?page=2").unwrap();
+
+        let headers =
+            render_with(&template, "GET", &url, 1_700_000_000_123, "abc".to_string()).unwrap();
+        assert_eq!(headers[0], ("X-Nonce".to_string(), "n-abc".to_string()));
+        let expected = hmac_sha256_hex("key", b"GET /v1/items?page=2 1700000000");
+        assert_eq!(headers[1].1, format!("v1={}", expected));
+        assert_eq!(headers[2].1, "1700000000");
+
+        template
+            .headers
+            .insert("X-Bad".to_string(), "{unknown}".to_string());
+        assert!(render_headers(&template, "GET", &url).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+/// User-Agent selection according to the session's identity profiles
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::config::{IdentityConfig, IdentityProfile, UaRotation, defaults};
+
+/// Picks the User-Agent for each request and remembers sticky choices
+pub struct IdentitySelector {
+    config: IdentityConfig,
+    /// The configured `user_agent`, sent by `IdentityProfile::Fixed`
+    fixed_user_agent: String,
+    /// Sticky choices keyed by domain (`PerDomain`) or by profile (`PerSession`)
+    sticky: Mutex<HashMap<String, String>>,
+}
+
+impl IdentitySelector {
+    pub fn new(config: IdentityConfig, fixed_user_agent: String) -> Self {
+        Self {
+            config,
+            fixed_user_agent,
+            sticky: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// User-Agent to send for a request to `url`
+    pub fn user_agent_for(&self, url: &Url) -> String {
+        let host = url.host_str().unwrap_or("");
+        let profile = self.config.profile_for(host);
+
+        let (candidates, rotation): (Vec<&str>, UaRotation) = match profile {
+            IdentityProfile::Fixed => return self.fixed_user_agent.clone(),
+            IdentityProfile::RotatingBrowser { rotation } => {
+                (defaults::USER_AGENTS.to_vec(), *rotation)
+            }
+            IdentityProfile::CustomList {
+                user_agents,
+                rotation,
+            } => (user_agents.iter().map(String::as_str).collect(), *rotation),
+        };
+        if candidates.is_empty() {
+            return self.fixed_user_agent.clone();
+        }
+
+        let Some(sticky_key) = Self::sticky_key(rotation, host, profile) else {
+            return Self::pick(&candidates);
+        };
+
+        let mut sticky = self.sticky.lock().unwrap_or_else(|e| e.into_inner());
+        sticky
+            .entry(sticky_key)
+            .or_insert_with(|| Self::pick(&candidates))
+            .clone()
+    }
+
+    /// A User-Agent other than `blocked` for `url`, replacing its sticky choice
+    ///
+    /// `Fixed` profiles keep their User-Agent, as does a profile without alternatives.
+    pub fn rotate_user_agent(&self, url: &Url, blocked: &str) -> String {
+        let host = url.host_str().unwrap_or("");
+        let profile = self.config.profile_for(host);
+
+        let (candidates, rotation): (Vec<&str>, UaRotation) = match profile {
+            IdentityProfile::Fixed => return blocked.to_string(),
+            IdentityProfile::RotatingBrowser { rotation } => {
+                (defaults::USER_AGENTS.to_vec(), *rotation)
+            }
+            IdentityProfile::CustomList {
+                user_agents,
+                rotation,
+            } => (user_agents.iter().map(String::as_str).collect(), *rotation),
+        };
+        let alternatives: Vec<&str> = candidates
+            .into_iter()
+            .filter(|candidate| *candidate != blocked)
+            .collect();
+        if alternatives.is_empty() {
+            return blocked.to_string();
+        }
+
+        let user_agent = Self::pick(&alternatives);
+        if let Some(sticky_key) = Self::sticky_key(rotation, host, profile) {
+            self.sticky
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(sticky_key, user_agent.clone());
+        }
+        user_agent
+    }
+
+    fn sticky_key(rotation: UaRotation, host: &str, profile: &IdentityProfile) -> Option<String> {
+        match rotation {
+            UaRotation::PerRequest => None,
+            UaRotation::PerDomain => Some(format!("domain:{}", host)),
+            // Profiles differ per domain, so session-wide choices are kept per profile
+            UaRotation::PerSession => Some(format!("session:{:?}", profile)),
+        }
+    }
+
+    fn pick(candidates: &[&str]) -> String {
+        let mut rng = rand::thread_rng();
+        candidates[rng.gen_range(0..candidates.len())].to_string()
+    }
+}
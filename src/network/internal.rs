@@ -0,0 +1,41 @@
+/// Internal network support
+///
+/// Applies an `InternalNetworkConfig` to an HTTP client builder: fixed host
+/// addresses (so staging hosts behind internal DNS need no /etc/hosts edits) and
+/// additional trusted CA bundles.
+use anyhow::Error;
+use reqwest::{Certificate, ClientBuilder};
+use std::net::SocketAddr;
+
+use crate::config::InternalNetworkConfig;
+use crate::core::host_key;
+
+/// Add the DNS overrides and CA bundles of `config` to `builder`
+pub fn apply_internal_network(
+    builder: ClientBuilder,
+    config: &InternalNetworkConfig,
+) -> Result<ClientBuilder, Error> {
+    let mut builder = builder;
+    for (host, ip) in &config.resolve {
+        // Port 0: reqwest connects to the port of the URL
+        builder = builder.resolve(&host_key(host), SocketAddr::new(*ip, 0));
+    }
+
+    for path in &config.ca_bundle_paths {
+        let pem = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read CA bundle {}: {}", path.display(), e))?;
+        let certificates = Certificate::from_pem_bundle(&pem)
+            .map_err(|e| anyhow::anyhow!("Invalid CA bundle {}: {}", path.display(), e))?;
+        if certificates.is_empty() {
+            return Err(anyhow::anyhow!(
+                "CA bundle {} contains no certificates",
+                path.display()
+            ));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
@@ -1,12 +1,50 @@
 // Network-related functionality
 
+pub mod cassette;
 pub mod client;
+pub mod concurrency;
 pub mod dns;
+pub mod dns_prefetch;
+pub mod encoding;
+pub mod escalation;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod geo_proxy;
+pub mod headers;
+pub mod identity;
+pub mod internal;
+pub mod protocol_fallback;
+pub mod proxy;
+pub mod proxy_source;
 pub mod rate_limit;
 pub mod robots;
+pub mod site_contact;
+pub mod sniff;
+pub mod ssrf;
+pub mod tls_cert;
+pub mod trace;
 
 // Re-export common networking components
+pub use cassette::{Cassette, CassetteEntry};
 pub use client::{ClientManager, HttpClientFactory};
+pub use concurrency::{AdaptiveConcurrency, ConcurrencyAdjustment, resize_permits};
 pub use dns::DnsCache;
+pub use dns_prefetch::{DnsPrefetchStats, DnsPrefetcher};
+pub use encoding::{ContentEncoding, decode_body};
+pub use escalation::{BlockReason, detect_bot_page, is_trigger_status};
+#[cfg(feature = "fault-injection")]
+pub use fault::{FaultInjectionConfig, FaultInjector, FaultStats};
+pub use geo_proxy::{GeoProxySelector, GeoRoute, GeoSelection};
+pub use headers::render_headers;
+pub use identity::IdentitySelector;
+pub use internal::apply_internal_network;
+pub use protocol_fallback::{ProtocolFallback, ProtocolProfile, is_protocol_error};
+pub use proxy::{apply_forward_proxy, check_forward_proxy, load_proxy_ca};
+pub use proxy_source::{ProxyPool, ProxyRefreshReport, ProxySourceManager, parse_proxy_list};
 pub use rate_limit::{DomainRequestTracker, GlobalRateLimiter};
 pub use robots::{RobotsCache, RobotsHandler};
+pub use site_contact::{SecurityTxt, SiteContact, apply_contact, parse_security_txt};
+pub use sniff::{SniffedType, body_prefix};
+pub use ssrf::{BlockedAddress, GuardedResolver, SsrfGuard, SsrfViolation, redirect_policy};
+pub use tls_cert::{CertificateInfo, parse_certificate};
+pub use trace::{HttpTraceRecord, HttpTracer, TraceTiming};
@@ -0,0 +1,179 @@
+/// Per-domain protocol fallback
+///
+/// Some old servers break on HTTP/2 negotiation or on modern TLS defaults. When a
+/// request fails with such an error the domain is retried with a more conservative
+/// profile: HTTP/1.1 only, then HTTP/1.1 with TLS capped at 1.2 and allowed down to
+/// the configured floor. The first profile that gets a response is remembered for
+/// the domain. Cipher suites are left to the platform TLS library, which the HTTP
+/// client does not let us configure; certificate errors never trigger a fallback.
+use anyhow::Error;
+use reqwest::tls::Version;
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::{ProtocolFallbackConfig, TlsVersionFloor};
+
+/// Error messages of failed TLS handshakes and HTTP/2 sessions (OpenSSL, Schannel,
+/// Secure Transport, hyper/h2)
+const PROTOCOL_ERROR_MARKERS: &[&str] = &[
+    "handshake failure",
+    "handshake_failure",
+    "wrong version number",
+    "unsupported protocol",
+    "no protocols available",
+    "alert protocol version",
+    "inappropriate fallback",
+    "unexpected eof",
+    "dh key too small",
+    "http2 error",
+    "protocol_error",
+    "frame with invalid size",
+    "received unexpected message from connection",
+];
+
+/// Client settings a domain is fetched with, from most to least modern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolProfile {
+    /// The crawler's regular client
+    Default,
+    /// HTTP/1.1 only
+    Http1Only,
+    /// HTTP/1.1 only, TLS between the configured floor and 1.2
+    LegacyTls,
+}
+
+impl ProtocolProfile {
+    /// The profile to try after this one failed, if any
+    pub fn next(self) -> Option<Self> {
+        match self {
+            ProtocolProfile::Default => Some(ProtocolProfile::Http1Only),
+            ProtocolProfile::Http1Only => Some(ProtocolProfile::LegacyTls),
+            ProtocolProfile::LegacyTls => None,
+        }
+    }
+
+    /// Adjust a client builder to this profile
+    pub fn apply(self, builder: ClientBuilder, config: &ProtocolFallbackConfig) -> ClientBuilder {
+        match self {
+            ProtocolProfile::Default => builder,
+            ProtocolProfile::Http1Only => builder.http1_only(),
+            ProtocolProfile::LegacyTls => builder
+                .http1_only()
+                .min_tls_version(match config.min_tls_version {
+                    TlsVersionFloor::Tls10 => Version::TLS_1_0,
+                    TlsVersionFloor::Tls11 => Version::TLS_1_1,
+                    TlsVersionFloor::Tls12 => Version::TLS_1_2,
+                })
+                .max_tls_version(Version::TLS_1_2),
+        }
+    }
+}
+
+/// Whether an error looks like a TLS or HTTP/2 incompatibility a fallback may fix
+pub fn is_protocol_error(error: &Error) -> bool {
+    let message = format!("{:#}", error).to_lowercase();
+    !message.contains("certificate")
+        && PROTOCOL_ERROR_MARKERS
+            .iter()
+            .any(|marker| message.contains(marker))
+}
+
+/// Fallback clients and the profile that works for each domain
+#[derive(Debug)]
+pub struct ProtocolFallback {
+    clients: HashMap<ProtocolProfile, Client>,
+    profiles: Mutex<HashMap<String, ProtocolProfile>>,
+}
+
+impl ProtocolFallback {
+    /// Build a client per fallback profile from `base`, which returns the settings
+    /// shared with the regular client
+    pub fn new(
+        config: &ProtocolFallbackConfig,
+        base: impl Fn() -> Result<ClientBuilder, Error>,
+    ) -> Result<Self, Error> {
+        let mut clients = HashMap::new();
+        let mut profile = ProtocolProfile::Default;
+        while let Some(next) = profile.next() {
+            clients.insert(next, next.apply(base()?, config).build()?);
+            profile = next;
+        }
+        Ok(Self {
+            clients,
+            profiles: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Profile the domain is fetched with; `Default` until a fallback worked
+    pub fn profile_for(&self, domain: &str) -> ProtocolProfile {
+        self.lock()
+            .get(domain)
+            .copied()
+            .unwrap_or(ProtocolProfile::Default)
+    }
+
+    /// Client of a fallback profile; `None` for `Default`
+    pub fn client(&self, profile: ProtocolProfile) -> Option<&Client> {
+        self.clients.get(&profile)
+    }
+
+    /// Keep using `profile` for the domain
+    pub fn remember(&self, domain: &str, profile: ProtocolProfile) {
+        if profile != ProtocolProfile::Default {
+            self.lock().insert(domain.to_string(), profile);
+        }
+    }
+
+    /// Domains that needed a fallback, with the profile that worked
+    pub fn profiles(&self) -> HashMap<String, ProtocolProfile> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ProtocolProfile>> {
+        self.profiles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_errors_walk_through_profiles() {
+        let handshake = anyhow::anyhow!("error trying to connect")
+            .context("error sending request for url (https://old.example/)");
+        assert!(!is_protocol_error(&handshake));
+        let handshake = anyhow::anyhow!(
+            "error:0A000102:SSL routines::unsupported protocol:ssl/statem/statem_lib.c:1993"
+        )
+        .context("error trying to connect");
+        assert!(is_protocol_error(&handshake));
+        assert!(is_protocol_error(&anyhow::anyhow!(
+            "http2 error: connection error detected: frame with invalid size"
+        )));
+        assert!(!is_protocol_error(&anyhow::anyhow!(
+            "handshake failure: certificate verify failed"
+        )));
+
+        let fallback =
+            ProtocolFallback::new(&ProtocolFallbackConfig::default(), || Ok(Client::builder()))
+                .unwrap();
+        assert!(fallback.client(ProtocolProfile::Default).is_none());
+        assert!(fallback.client(ProtocolProfile::LegacyTls).is_some());
+        assert_eq!(
+            fallback.profile_for("old.example"),
+            ProtocolProfile::Default
+        );
+        fallback.remember("old.example", ProtocolProfile::Default.next().unwrap());
+        fallback.remember("new.example", ProtocolProfile::Default);
+        assert_eq!(
+            fallback.profile_for("old.example"),
+            ProtocolProfile::Http1Only
+        );
+        assert_eq!(fallback.profiles().len(), 1);
+        assert_eq!(ProtocolProfile::LegacyTls.next(), None);
+    }
+}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::{Mutex, RwLock};
 
-use crate::core::{DomainRateLimit, RateLimiter};
+use crate::core::{DomainRateLimit, PublicSuffixes, RateLimiter, host_key};
 
 /// Tracks request timestamps for sliding window rate limiting
 #[derive(Debug)]
@@ -90,36 +90,81 @@ impl DomainRequestTracker {
 /// Global rate limiter for all domains with optimized locking
 pub struct GlobalRateLimiter {
     pub domain_trackers: Arc<RwLock<HashMap<String, DomainRequestTracker>>>,
-    default_rate_limit: DomainRateLimit,
-    domain_specific_limits: HashMap<String, DomainRateLimit>,
+    /// Default and per-domain limits; replaceable while crawling
+    limits: std::sync::RwLock<(DomainRateLimit, HashMap<String, DomainRateLimit>)>,
     last_cleanup: Arc<Mutex<tokio::time::Instant>>, // Track last cleanup time
+    /// Suffixes grouping hosts into the sites that share a request window
+    suffixes: Arc<PublicSuffixes>,
 }
 
 impl GlobalRateLimiter {
     pub fn new(default_rate_limit: DomainRateLimit) -> Self {
         Self {
             domain_trackers: Arc::new(RwLock::new(HashMap::new())),
-            default_rate_limit,
-            domain_specific_limits: HashMap::new(),
+            limits: std::sync::RwLock::new((default_rate_limit, HashMap::new())),
             last_cleanup: Arc::new(Mutex::new(tokio::time::Instant::now())),
+            suffixes: Arc::new(PublicSuffixes::builtin()),
         }
     }
 
+    /// Group hosts into sites with `suffixes` instead of the bundled list
+    pub fn with_public_suffixes(mut self, suffixes: Arc<PublicSuffixes>) -> Self {
+        self.suffixes = suffixes;
+        self
+    }
+
     /// Add domain-specific rate limit
+    ///
+    /// Domains are keyed in ASCII form, so a limit for "bücher.example" also applies
+    /// to requests to "xn--bcher-kva.example".
     pub fn add_domain_limit(&mut self, domain: String, rate_limit: DomainRateLimit) {
-        self.domain_specific_limits.insert(domain, rate_limit);
+        self.limits
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .1
+            .insert(host_key(&domain), rate_limit);
+    }
+
+    /// Replace the default and/or domain-specific limits of a running limiter
+    ///
+    /// Request history is kept, so a lowered limit takes effect immediately.
+    pub fn update_limits(
+        &self,
+        default_rate_limit: Option<DomainRateLimit>,
+        domain_limits: Option<HashMap<String, DomainRateLimit>>,
+    ) {
+        let mut limits = self.limits.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(default_rate_limit) = default_rate_limit {
+            limits.0 = default_rate_limit;
+        }
+        if let Some(domain_limits) = domain_limits {
+            limits.1 = domain_limits
+                .into_iter()
+                .map(|(domain, limit)| (host_key(&domain), limit))
+                .collect();
+        }
     }
 
-    /// Get rate limit for a specific domain
-    fn get_rate_limit(&self, domain: &str) -> &DomainRateLimit {
-        self.domain_specific_limits
-            .get(domain)
-            .unwrap_or(&self.default_rate_limit)
+    /// Tracker key and rate limit of a host
+    ///
+    /// A host with a limit of its own gets its own request window. Every other host
+    /// shares the window of its registrable domain, so `www.example.co.uk` and
+    /// `shop.example.co.uk` are paced as one site, under the site's limit if set.
+    fn tracker_for(&self, host: &str) -> (String, DomainRateLimit) {
+        let host = host_key(host);
+        let limits = self.limits.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(limit) = limits.1.get(&host) {
+            return (host, limit.clone());
+        }
+        let site = self.suffixes.registrable_domain(&host);
+        let limit = limits.1.get(&site).unwrap_or(&limits.0).clone();
+        (site, limit)
     }
 
     /// Check if request is allowed and apply rate limiting (optimized with RwLock)
     pub async fn check_and_wait(&self, domain: &str) -> Result<(), Error> {
-        let rate_limit = self.get_rate_limit(domain).clone();
+        let (domain, rate_limit) = self.tracker_for(domain);
+        let domain = domain.as_str();
 
         // Try to get read lock first to check if we can proceed
         let can_proceed = {
@@ -222,8 +267,36 @@ impl RateLimiter for GlobalRateLimiter {
     async fn get_current_request_count(&self, domain: &str) -> usize {
         let trackers = self.domain_trackers.read().await;
         trackers
-            .get(domain)
+            .get(&self.tracker_for(domain).0)
             .map(|tracker| tracker.request_timestamps.len())
             .unwrap_or(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_limiter_groups_hosts_with_its_own_suffixes() {
+        let bundled = GlobalRateLimiter::new(DomainRateLimit::default());
+        let overridden = GlobalRateLimiter::new(DomainRateLimit::default()).with_public_suffixes(
+            Arc::new(PublicSuffixes::builtin().with_overrides(["teams.example.com"])),
+        );
+
+        assert_eq!(
+            bundled.tracker_for("wiki.teams.example.com").0,
+            "example.com"
+        );
+        assert_eq!(
+            bundled.tracker_for("shop.example.com.vn").0,
+            "example.com.vn"
+        );
+        assert_eq!(
+            overridden.tracker_for("wiki.teams.example.com").0,
+            "teams.example.com"
+        );
+        // The override of one limiter leaves the other untouched
+        assert_eq!(bundled.tracker_for("teams.example.com").0, "example.com");
+    }
+}
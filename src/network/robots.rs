@@ -6,8 +6,9 @@ use tokio::sync::Mutex;
 use tokio::time::{Instant, sleep};
 use url::Url;
 
-use crate::config::defaults;
+use crate::config::{RobotsOverrideConfig, defaults};
 use crate::core::RobotsChecker;
+use crate::network::Cassette;
 
 // Type alias for complex robots cache entry
 type RobotsCacheEntry = (String, Option<u64>, Instant);
@@ -39,6 +40,10 @@ impl RobotsCache {
 pub struct RobotsHandler {
     cache: RobotsCache,
     client: reqwest::Client,
+    cassette: Option<Arc<Cassette>>,
+    overrides: Option<RobotsOverrideConfig>,
+    /// Lowercase product token matched against `User-agent` lines
+    user_agent_token: String,
 }
 
 impl RobotsHandler {
@@ -46,9 +51,45 @@ impl RobotsHandler {
         Self {
             cache: RobotsCache::new(),
             client,
+            cassette: None,
+            overrides: None,
+            user_agent_token: String::new(),
         }
     }
 
+    /// Select robots.txt groups using the product token of `user_agent`
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent_token = robots_token(user_agent);
+        self
+    }
+
+    /// Fetch robots.txt through a record/replay cassette
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    async fn fetch(&self, robots_url: &str) -> Result<reqwest::Response, Error> {
+        let request = self.client.get(robots_url).build()?;
+        match &self.cassette {
+            Some(cassette) => cassette.execute(&self.client, request).await,
+            None => Ok(self.client.execute(request).await?),
+        }
+    }
+
+    /// Bypass robots.txt for the configured domains
+    pub fn with_overrides(mut self, overrides: RobotsOverrideConfig) -> Self {
+        self.overrides = Some(overrides);
+        self
+    }
+
+    /// Return the override reason if robots.txt is bypassed for this URL
+    pub fn override_reason(&self, url: &Url) -> Option<&str> {
+        let overrides = self.overrides.as_ref()?;
+        let host = url.host_str()?;
+        overrides.matches(host).then_some(overrides.reason.as_str())
+    }
+
     pub async fn get_robots_cache(
         &self,
     ) -> Arc<Mutex<HashMap<String, (String, Option<u64>, Instant)>>> {
@@ -56,10 +97,19 @@ impl RobotsHandler {
     }
 }
 
+/// Path and query of `url`, the part robots.txt rules are matched against
+fn robots_path(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
 impl RobotsChecker for RobotsHandler {
     /// Check if URL is allowed by robots.txt
     async fn is_allowed_by_robots(&self, url: &Url) -> Result<bool, Error> {
-        let base_url = format!("{}://{}", url.scheme(), url.host_str().unwrap_or(""));
+        // The origin keeps a non-default port, which serves its own robots.txt
+        let base_url = url.origin().ascii_serialization();
         let robots_url = format!("{}/robots.txt", base_url);
 
         // Check cache first with TTL validation
@@ -74,7 +124,7 @@ impl RobotsChecker for RobotsHandler {
                     if let Some(delay) = crawl_delay {
                         sleep(Duration::from_millis(*delay)).await;
                     }
-                    return Ok(self.parse_robots_txt(robots_content, url.path()).0);
+                    return Ok(self.parse_robots_txt(robots_content, &robots_path(url)).0);
                 } else {
                     // Remove expired entry
                     cache.remove(&base_url);
@@ -83,12 +133,12 @@ impl RobotsChecker for RobotsHandler {
         }
 
         // Fetch and parse robots.txt
-        match self.client.get(&robots_url).send().await {
+        match self.fetch(&robots_url).await {
             Ok(response) => {
                 if response.status().is_success() {
                     let robots_content = response.text().await?;
                     let (is_allowed, crawl_delay) =
-                        self.parse_robots_txt(&robots_content, url.path());
+                        self.parse_robots_txt(&robots_content, &robots_path(url));
 
                     // Cache the result with crawl delay and timestamp
                     {
@@ -114,66 +164,280 @@ impl RobotsChecker for RobotsHandler {
         }
     }
 
-    /// Enhanced robots.txt parser - handles User-agent, Allow, Disallow, and Crawl-delay
+    /// Robots Exclusion Protocol evaluation (RFC 9309 / Google semantics)
+    ///
+    /// - The group for the most specific matching user-agent token is used, falling
+    ///   back to `*`; groups naming the same agent are merged.
+    /// - Paths match rules with `*` wildcards and a trailing `$` anchor.
+    /// - The longest matching rule wins; on a tie `Allow` wins.
     fn parse_robots_txt(&self, robots_content: &str, path: &str) -> (bool, Option<u64>) {
-        let mut in_wildcard_section = false;
-        let mut is_allowed = true;
-        let mut crawl_delay: Option<u64> = None;
-
-        for line in robots_content.lines() {
-            let line = line.trim();
+        let groups = parse_groups(robots_content);
+        let selected = select_groups(&groups, &self.user_agent_token);
 
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
+        let mut best: Option<&RobotsRule> = None;
+        for rule in selected.iter().flat_map(|group| &group.rules) {
+            // An empty Disallow allows everything and never outranks another rule
+            if rule.pattern.is_empty() || !pattern_matches(&rule.pattern, path) {
                 continue;
             }
+            let better = match best {
+                None => true,
+                Some(current) => {
+                    rule.specificity() > current.specificity()
+                        || (rule.specificity() == current.specificity()
+                            && rule.allow
+                            && !current.allow)
+                }
+            };
+            if better {
+                best = Some(rule);
+            }
+        }
 
-            // Check for User-agent directive
-            if line.to_lowercase().starts_with("user-agent:") {
-                let user_agent = line.split(':').nth(1).unwrap_or("").trim();
-                in_wildcard_section = user_agent == "*";
-                continue;
+        let is_allowed = best.is_none_or(|rule| rule.allow);
+        let crawl_delay = selected.iter().find_map(|group| group.crawl_delay);
+        (is_allowed, crawl_delay)
+    }
+}
+
+/// Product token used to select a robots.txt group from a User-Agent header
+///
+/// `Mozilla/5.0 (compatible; RustCrawler/1.0)` yields `rustcrawler`; a plain
+/// `RustCrawler/1.0 (+https://example.com)` yields the same.
+pub fn robots_token(user_agent: &str) -> String {
+    let product = match user_agent.split_once("compatible;") {
+        Some((_, rest)) => rest,
+        None => user_agent,
+    };
+    product
+        .trim()
+        .split(|c: char| c == '/' || c == ';' || c == ')' || c.is_whitespace())
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+#[derive(Debug, Clone)]
+struct RobotsRule {
+    allow: bool,
+    pattern: String,
+}
+
+impl RobotsRule {
+    /// Rule length used for precedence; a trailing `$` is not counted
+    fn specificity(&self) -> usize {
+        self.pattern.trim_end_matches('$').len()
+    }
+}
+
+#[derive(Debug, Default)]
+struct RobotsGroup {
+    agents: Vec<String>,
+    rules: Vec<RobotsRule>,
+    crawl_delay: Option<u64>,
+}
+
+fn parse_groups(robots_content: &str) -> Vec<RobotsGroup> {
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    // Consecutive User-agent lines share one group
+    let mut collecting_agents = false;
+
+    for line in robots_content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+
+        if key == "user-agent" {
+            if !collecting_agents {
+                groups.push(RobotsGroup::default());
+                collecting_agents = true;
+            }
+            if let Some(group) = groups.last_mut() {
+                group.agents.push(value.to_lowercase());
             }
+            continue;
+        }
 
-            // Only process rules for wildcard user agent
-            if in_wildcard_section {
-                if line.to_lowercase().starts_with("disallow:") {
-                    let disallowed_path = line.split(':').nth(1).unwrap_or("").trim();
-                    if !disallowed_path.is_empty() && path.starts_with(disallowed_path) {
-                        is_allowed = false;
-                    }
-                } else if line.to_lowercase().starts_with("allow:") {
-                    let allowed_path = line.split(':').nth(1).unwrap_or("").trim();
-                    if !allowed_path.is_empty() && path.starts_with(allowed_path) {
-                        is_allowed = true;
-                    }
-                } else if line.to_lowercase().starts_with("crawl-delay:") {
-                    // Parse crawl delay (in seconds, convert to milliseconds)
-                    if let Some(delay_str) = line.split(':').nth(1)
-                        && let Ok(delay_seconds) = delay_str.trim().parse::<f64>()
-                    {
-                        crawl_delay =
-                            Some((delay_seconds * defaults::SECONDS_TO_MS_MULTIPLIER) as u64);
-                    }
-                } else if line.to_lowercase().starts_with("request-rate:") {
-                    // Parse request rate (requests per time period)
-                    // Format: "Request-rate: requests/seconds"
-                    if let Some(rate_str) = line.split(':').nth(1) {
-                        let rate_parts: Vec<&str> = rate_str.trim().split('/').collect();
-                        if rate_parts.len() == 2
-                            && let (Ok(requests), Ok(seconds)) =
-                                (rate_parts[0].parse::<f64>(), rate_parts[1].parse::<f64>())
-                        {
-                            // Convert to delay between requests in milliseconds
-                            let delay_between_requests =
-                                (seconds / requests) * defaults::SECONDS_TO_MS_MULTIPLIER;
-                            crawl_delay = Some(delay_between_requests as u64);
-                        }
-                    }
+        collecting_agents = false;
+        // Rules before the first User-agent line belong to no group
+        let Some(group) = groups.last_mut() else {
+            continue;
+        };
+
+        match key.as_str() {
+            "disallow" | "allow" => group.rules.push(RobotsRule {
+                allow: key == "allow",
+                pattern: value.to_string(),
+            }),
+            "crawl-delay" => {
+                // Seconds, converted to milliseconds
+                if let Ok(delay_seconds) = value.parse::<f64>() {
+                    group.crawl_delay =
+                        Some((delay_seconds * defaults::SECONDS_TO_MS_MULTIPLIER) as u64);
                 }
             }
+            "request-rate" => {
+                // Format: "Request-rate: requests/seconds"
+                if let Some((requests, seconds)) = value.split_once('/')
+                    && let (Ok(requests), Ok(seconds)) = (
+                        requests.trim().parse::<f64>(),
+                        seconds.trim().parse::<f64>(),
+                    )
+                    && requests > 0.0
+                {
+                    let delay_between_requests =
+                        (seconds / requests) * defaults::SECONDS_TO_MS_MULTIPLIER;
+                    group.crawl_delay = Some(delay_between_requests as u64);
+                }
+            }
+            _ => {}
         }
+    }
 
-        (is_allowed, crawl_delay)
+    groups
+}
+
+/// Groups that apply to `token`: the most specific named match, else `*`
+fn select_groups<'a>(groups: &'a [RobotsGroup], token: &str) -> Vec<&'a RobotsGroup> {
+    let best_agent = groups
+        .iter()
+        .flat_map(|group| &group.agents)
+        .filter(|agent| {
+            agent.as_str() != "*" && !token.is_empty() && token.starts_with(agent.as_str())
+        })
+        .max_by_key(|agent| agent.len())
+        .map(String::as_str)
+        .unwrap_or("*");
+
+    groups
+        .iter()
+        .filter(|group| group.agents.iter().any(|agent| agent == best_agent))
+        .collect()
+}
+
+/// Match a robots.txt path pattern (`*` = any sequence, trailing `$` = end of path)
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(stripped) => (stripped, true),
+        None => (pattern, false),
+    };
+
+    let pattern = pattern.as_bytes();
+    let path = path.as_bytes();
+    // positions[i] = path offsets reachable after matching pattern[..i]
+    let mut positions = vec![0usize];
+    for &byte in pattern {
+        let mut next = Vec::new();
+        if byte == b'*' {
+            let start = positions.iter().copied().min().unwrap_or(0);
+            next.extend(start..=path.len());
+        } else {
+            for &position in &positions {
+                if position < path.len() && path[position] == byte {
+                    next.push(position + 1);
+                }
+            }
+        }
+        next.dedup();
+        if next.is_empty() {
+            return false;
+        }
+        positions = next;
+    }
+
+    !anchored || positions.contains(&path.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+User-agent: *
+Disallow: /private
+Allow: /private/public
+Disallow: /*.pdf$
+Disallow: /search?
+Crawl-delay: 2
+
+User-agent: RustCrawler
+User-agent: OtherBot
+Disallow: /no-rust/
+Allow: /no-rust/ok$
+
+User-agent: rustcrawler
+Disallow: /merged
+
+User-agent: RustCrawlerNews
+Disallow: /
+";
+
+    fn handler(user_agent: &str) -> RobotsHandler {
+        RobotsHandler::new(reqwest::Client::new()).with_user_agent(user_agent)
+    }
+
+    #[test]
+    fn test_robots_conformance_table() {
+        let cases: &[(&str, &str, bool)] = &[
+            // Wildcard group
+            ("GenericBot/1.0", "/", true),
+            ("GenericBot/1.0", "/private", false),
+            ("GenericBot/1.0", "/private/page", false),
+            ("GenericBot/1.0", "/private/public/page", true),
+            ("GenericBot/1.0", "/files/report.pdf", false),
+            ("GenericBot/1.0", "/files/report.pdf?download=1", true),
+            ("GenericBot/1.0", "/search?q=rust", false),
+            ("GenericBot/1.0", "/search", true),
+            // Named group replaces `*` entirely and merges same-agent groups
+            (
+                "Mozilla/5.0 (compatible; RustCrawler/1.0)",
+                "/private",
+                true,
+            ),
+            (
+                "Mozilla/5.0 (compatible; RustCrawler/1.0)",
+                "/no-rust/page",
+                false,
+            ),
+            (
+                "Mozilla/5.0 (compatible; RustCrawler/1.0)",
+                "/no-rust/ok",
+                true,
+            ),
+            (
+                "Mozilla/5.0 (compatible; RustCrawler/1.0)",
+                "/no-rust/ok/more",
+                false,
+            ),
+            (
+                "Mozilla/5.0 (compatible; RustCrawler/1.0)",
+                "/merged/page",
+                false,
+            ),
+            // Most specific agent wins
+            ("RustCrawlerNews/2.0", "/anything", false),
+        ];
+
+        for (user_agent, path, expected) in cases {
+            let (allowed, _) = handler(user_agent).parse_robots_txt(ROBOTS, path);
+            assert_eq!(allowed, *expected, "{} {}", user_agent, path);
+        }
+    }
+
+    #[test]
+    fn test_robots_precedence_and_delay() {
+        let robots = "User-agent: *\nDisallow: /page\nAllow: /page\nDisallow: /a*b\nAllow: /a*b/c\nCrawl-delay: 1.5\n";
+        let handler = handler("AnyBot");
+
+        // Equal length: Allow wins
+        assert!(handler.parse_robots_txt(robots, "/page").0);
+        // Longer rule wins regardless of order
+        assert!(!handler.parse_robots_txt(robots, "/axxb").0);
+        assert!(handler.parse_robots_txt(robots, "/axxb/c").0);
+        assert_eq!(handler.parse_robots_txt(robots, "/").1, Some(1500));
+        assert!(handler.parse_robots_txt("", "/anything").0);
     }
 }
@@ -0,0 +1,175 @@
+/// Contact details published by crawled sites
+///
+/// `security.txt` (RFC 9116, at `/.well-known/security.txt` or `/security.txt`) and
+/// `humans.txt` are fetched once per domain so domain reports can say whom to
+/// contact about a site. Files served as HTML (soft 404 pages) are ignored.
+use anyhow::Error;
+use reqwest::ClientBuilder;
+use reqwest::header::{FROM, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ContactConfig;
+
+/// Paths tried for security.txt, in order
+pub const SECURITY_TXT_PATHS: &[&str] = &["/.well-known/security.txt", "/security.txt"];
+pub const HUMANS_TXT_PATH: &str = "/humans.txt";
+
+/// Characters of humans.txt kept in the report
+const MAX_HUMANS_TXT_CHARS: usize = 4096;
+
+/// Fields of a security.txt file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecurityTxt {
+    /// `Contact` URIs (`mailto:`, `https:`, `tel:`), in preference order
+    pub contact: Vec<String>,
+    pub expires: Option<String>,
+    pub encryption: Vec<String>,
+    pub policy: Vec<String>,
+    pub acknowledgments: Vec<String>,
+    pub hiring: Vec<String>,
+    pub preferred_languages: Option<String>,
+    pub canonical: Vec<String>,
+}
+
+/// Contact details a domain publishes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SiteContact {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_txt: Option<SecurityTxt>,
+    /// Start of humans.txt, which has no fixed format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humans_txt: Option<String>,
+}
+
+impl SiteContact {
+    /// The preferred way to reach the site's security team, if published
+    pub fn primary_contact(&self) -> Option<&str> {
+        self.security_txt
+            .as_ref()
+            .and_then(|security_txt| security_txt.contact.first())
+            .map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.security_txt.is_none() && self.humans_txt.is_none()
+    }
+}
+
+/// Send the configured `From` address with every request of the client
+pub fn apply_contact(
+    builder: ClientBuilder,
+    config: &ContactConfig,
+) -> Result<ClientBuilder, Error> {
+    let Some(from) = &config.from else {
+        return Ok(builder);
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        FROM,
+        HeaderValue::from_str(from)
+            .map_err(|e| anyhow::anyhow!("Invalid From address '{}': {}", from, e))?,
+    );
+    Ok(builder.default_headers(headers))
+}
+
+/// Parse a security.txt file; `None` if it has no `Contact` field (required by RFC 9116)
+///
+/// Comments, unknown fields and the armor of a PGP cleartext signature are skipped.
+pub fn parse_security_txt(text: &str) -> Option<SecurityTxt> {
+    let mut security_txt = SecurityTxt::default();
+    let mut in_signature = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("-----BEGIN PGP SIGNATURE") {
+            in_signature = true;
+        } else if line.starts_with("-----END PGP SIGNATURE") {
+            in_signature = false;
+        }
+        if in_signature || line.starts_with('#') || line.starts_with("-----") {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        match name.trim().to_ascii_lowercase().as_str() {
+            "contact" => security_txt.contact.push(value),
+            "expires" => security_txt.expires = Some(value),
+            "encryption" => security_txt.encryption.push(value),
+            "policy" => security_txt.policy.push(value),
+            "acknowledgments" | "acknowledgements" => security_txt.acknowledgments.push(value),
+            "hiring" => security_txt.hiring.push(value),
+            "preferred-languages" => security_txt.preferred_languages = Some(value),
+            "canonical" => security_txt.canonical.push(value),
+            _ => {}
+        }
+    }
+    (!security_txt.contact.is_empty()).then_some(security_txt)
+}
+
+/// humans.txt trimmed to the part kept in reports; `None` when empty
+pub fn trim_humans_txt(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.chars().take(MAX_HUMANS_TXT_CHARS).collect())
+}
+
+/// Whether a `Content-Type` is plain text, as both files must be served
+pub fn is_plain_text(content_type: Option<&str>) -> bool {
+    content_type.is_none_or(|content_type| {
+        content_type
+            .trim()
+            .to_ascii_lowercase()
+            .starts_with("text/plain")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_security_txt_is_parsed() {
+        let text = "-----BEGIN PGP SIGNED MESSAGE-----\n\
+                    Hash: SHA256\n\
+                    \n\
+                    # Our security policy\n\
+                    Contact: mailto:security@example.com\n\
+                    contact: https://example.com/report\n\
+                    Expires: 2027-01-01T00:00:00.000Z\n\
+                    Preferred-Languages: en, de\n\
+                    Policy: https://example.com/policy\n\
+                    -----BEGIN PGP SIGNATURE-----\n\
+                    Contact: mailto:forged@example.com\n\
+                    -----END PGP SIGNATURE-----\n";
+        let security_txt = parse_security_txt(text).unwrap();
+        assert_eq!(
+            security_txt.contact,
+            vec!["mailto:security@example.com", "https://example.com/report"]
+        );
+        assert_eq!(
+            security_txt.expires.as_deref(),
+            Some("2027-01-01T00:00:00.000Z")
+        );
+        assert_eq!(security_txt.preferred_languages.as_deref(), Some("en, de"));
+        assert_eq!(security_txt.policy, vec!["https://example.com/policy"]);
+
+        let contact = SiteContact {
+            security_txt: Some(security_txt),
+            humans_txt: trim_humans_txt("\n/* TEAM */\nDeveloper: Ann\n"),
+        };
+        assert_eq!(
+            contact.primary_contact(),
+            Some("mailto:security@example.com")
+        );
+        assert_eq!(
+            contact.humans_txt.as_deref(),
+            Some("/* TEAM */\nDeveloper: Ann")
+        );
+
+        assert_eq!(parse_security_txt("<html>Not found</html>"), None);
+        assert!(is_plain_text(Some("text/plain; charset=utf-8")));
+        assert!(!is_plain_text(Some("text/html")));
+    }
+}
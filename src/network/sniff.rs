@@ -0,0 +1,119 @@
+/// Content sniffing of response bodies
+///
+/// Some servers label PDFs, archives and images as `text/html`. Their bodies are
+/// recognised by the magic bytes at the start, before the text pipeline spends
+/// time extracting "text" from megabytes of binary data.
+use std::fmt;
+
+/// Bytes at the start of a body that sniffing looks at
+pub const SNIFF_BYTES: usize = 512;
+
+/// A binary format recognised from a body's leading bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SniffedType {
+    Pdf,
+    /// Also the container of docx, xlsx, epub and jar files
+    Zip,
+    Gzip,
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    /// No known signature, but NUL bytes a text document would not contain
+    Binary,
+}
+
+impl SniffedType {
+    /// Binary format of a body starting with `prefix`, `None` for text
+    ///
+    /// Byte order marks and leading whitespace are text; only the first
+    /// `SNIFF_BYTES` bytes are looked at.
+    pub fn sniff(prefix: &[u8]) -> Option<Self> {
+        let prefix = &prefix[..prefix.len().min(SNIFF_BYTES)];
+        let signature = [
+            (&b"%PDF-"[..], SniffedType::Pdf),
+            (b"PK\x03\x04", SniffedType::Zip),
+            (b"PK\x05\x06", SniffedType::Zip),
+            (b"\x1f\x8b", SniffedType::Gzip),
+            (b"\x89PNG\r\n\x1a\n", SniffedType::Png),
+            (b"\xff\xd8\xff", SniffedType::Jpeg),
+            (b"GIF87a", SniffedType::Gif),
+            (b"GIF89a", SniffedType::Gif),
+        ]
+        .into_iter()
+        .find(|(magic, _)| prefix.starts_with(magic))
+        .map(|(_, kind)| kind);
+        if signature.is_some() {
+            return signature;
+        }
+        if prefix.len() >= 12 && prefix.starts_with(b"RIFF") && &prefix[8..12] == b"WEBP" {
+            return Some(SniffedType::Webp);
+        }
+        // UTF-16 text has NUL bytes too, but starts with a byte order mark
+        let utf16 = prefix.starts_with(b"\xff\xfe") || prefix.starts_with(b"\xfe\xff");
+        (!utf16 && prefix.contains(&0)).then_some(SniffedType::Binary)
+    }
+
+    /// MIME type of the format
+    pub fn mime(&self) -> &'static str {
+        match self {
+            SniffedType::Pdf => "application/pdf",
+            SniffedType::Zip => "application/zip",
+            SniffedType::Gzip => "application/gzip",
+            SniffedType::Png => "image/png",
+            SniffedType::Jpeg => "image/jpeg",
+            SniffedType::Gif => "image/gif",
+            SniffedType::Webp => "image/webp",
+            SniffedType::Binary => "application/octet-stream",
+        }
+    }
+}
+
+impl fmt::Display for SniffedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mime())
+    }
+}
+
+/// Leading bytes of a chunked body, up to `SNIFF_BYTES`
+pub fn body_prefix<B: AsRef<[u8]>>(chunks: &[B]) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(SNIFF_BYTES);
+    for chunk in chunks {
+        let remaining = SNIFF_BYTES - prefix.len();
+        if remaining == 0 {
+            break;
+        }
+        let chunk = chunk.as_ref();
+        prefix.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_bodies_are_recognised_across_chunks() {
+        let pdf: [&[u8]; 2] = [b"%P", b"DF-1.7\n%\xe2\xe3\xcf\xd3"];
+        let prefix = body_prefix(&pdf);
+        assert_eq!(SniffedType::sniff(&prefix), Some(SniffedType::Pdf));
+        assert_eq!(SniffedType::Pdf.mime(), "application/pdf");
+
+        assert_eq!(
+            SniffedType::sniff(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some(SniffedType::Webp)
+        );
+        assert_eq!(
+            SniffedType::sniff(b"\x00\x00\x01\x00\x01\x00"),
+            Some(SniffedType::Binary)
+        );
+        assert_eq!(SniffedType::sniff(b"\xef\xbb\xbf<!doctype html>"), None);
+        assert_eq!(SniffedType::sniff(b"\xff\xfe<\x00h\x00"), None);
+        assert_eq!(SniffedType::sniff(b"  <html><body>PK</body>"), None);
+
+        let long = vec![b"a".repeat(SNIFF_BYTES), b"\x00".to_vec()];
+        assert_eq!(body_prefix(&long).len(), SNIFF_BYTES);
+        assert_eq!(SniffedType::sniff(&body_prefix(&long)), None);
+    }
+}
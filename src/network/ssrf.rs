@@ -0,0 +1,471 @@
+/// SSRF protection for seeds and discovered links
+///
+/// `SsrfGuard` refuses URLs whose host is, or resolves to, a loopback, private,
+/// link-local or otherwise non-public address, and cloud metadata endpoints, unless
+/// the host or network is allowlisted. Every address a name resolves to is checked,
+/// so one private record among public ones is enough to refuse the URL, and a
+/// name that does not resolve is refused too. Clients also resolve through a
+/// `GuardedResolver`, which checks the addresses each connection is made to, so
+/// redirect targets and a DNS answer that changes after the check are covered.
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use url::{Host, Url};
+
+use crate::config::{InternalNetworkConfig, SsrfProtectionConfig};
+use crate::core::error::CrawlError;
+use crate::core::host_key;
+use crate::network::DnsCache;
+
+/// Host names of cloud instance metadata services
+const METADATA_HOSTS: &[&str] = &[
+    "metadata.google.internal",
+    "metadata.goog",
+    "metadata.azure.com",
+    "instance-data",
+    "instance-data.ec2.internal",
+];
+
+/// Addresses of cloud instance metadata services (AWS, GCP, Azure, OpenStack,
+/// Alibaba Cloud, AWS over IPv6)
+const METADATA_ADDRESSES: &[IpAddr] = &[
+    IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)),
+    IpAddr::V4(Ipv4Addr::new(169, 254, 170, 2)),
+    IpAddr::V4(Ipv4Addr::new(100, 100, 100, 200)),
+    IpAddr::V6(Ipv6Addr::new(0xfd00, 0x0ec2, 0, 0, 0, 0, 0, 0x0254)),
+];
+
+/// Kind of address a refused URL points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockedAddress {
+    CloudMetadata,
+    Loopback,
+    /// RFC 1918 and IPv6 unique local addresses
+    Private,
+    LinkLocal,
+    /// Carrier-grade NAT range 100.64.0.0/10
+    SharedAddressSpace,
+    /// 0.0.0.0, ::, broadcast, multicast and reserved ranges
+    NonRoutable,
+    /// The host name did not resolve, so its addresses could not be checked
+    Unresolved,
+}
+
+impl std::fmt::Display for BlockedAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockedAddress::CloudMetadata => write!(f, "cloud metadata endpoint"),
+            BlockedAddress::Loopback => write!(f, "loopback address"),
+            BlockedAddress::Private => write!(f, "private network address"),
+            BlockedAddress::LinkLocal => write!(f, "link-local address"),
+            BlockedAddress::SharedAddressSpace => write!(f, "shared address space"),
+            BlockedAddress::NonRoutable => write!(f, "non-routable address"),
+            BlockedAddress::Unresolved => write!(f, "host name that does not resolve"),
+        }
+    }
+}
+
+impl BlockedAddress {
+    /// Classify `ip`; `None` for ordinary public addresses
+    pub fn classify(ip: IpAddr) -> Option<Self> {
+        if METADATA_ADDRESSES.contains(&ip) {
+            return Some(BlockedAddress::CloudMetadata);
+        }
+        match ip {
+            IpAddr::V4(ip) => Self::classify_v4(ip),
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                // ::ffff:169.254.169.254 reaches the same host as the IPv4 address
+                Some(mapped) => Self::classify(IpAddr::V4(mapped)),
+                None => Self::classify_v6(ip),
+            },
+        }
+    }
+
+    fn classify_v4(ip: Ipv4Addr) -> Option<Self> {
+        let [a, b, ..] = ip.octets();
+        if ip.is_loopback() {
+            Some(BlockedAddress::Loopback)
+        } else if ip.is_private() {
+            Some(BlockedAddress::Private)
+        } else if ip.is_link_local() {
+            Some(BlockedAddress::LinkLocal)
+        } else if a == 100 && (64..128).contains(&b) {
+            Some(BlockedAddress::SharedAddressSpace)
+        } else if ip.is_unspecified()
+            || ip.is_broadcast()
+            || ip.is_multicast()
+            || a == 0
+            || a >= 240
+        {
+            Some(BlockedAddress::NonRoutable)
+        } else {
+            None
+        }
+    }
+
+    fn classify_v6(ip: Ipv6Addr) -> Option<Self> {
+        let first = ip.segments()[0];
+        if ip.is_loopback() {
+            Some(BlockedAddress::Loopback)
+        } else if first & 0xfe00 == 0xfc00 {
+            Some(BlockedAddress::Private)
+        } else if first & 0xffc0 == 0xfe80 {
+            Some(BlockedAddress::LinkLocal)
+        } else if ip.is_unspecified() || ip.is_multicast() {
+            Some(BlockedAddress::NonRoutable)
+        } else {
+            None
+        }
+    }
+
+    fn is_metadata(&self) -> bool {
+        *self == BlockedAddress::CloudMetadata
+    }
+}
+
+/// A URL refused by the guard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsrfViolation {
+    pub host: String,
+    /// Offending address; `None` when the host name itself is blocked
+    pub address: Option<IpAddr>,
+    pub kind: BlockedAddress,
+}
+
+impl std::fmt::Display for SsrfViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.address {
+            Some(address) if address.to_string() != self.host.trim_matches(['[', ']']) => {
+                write!(f, "{} resolves to {} ({})", self.host, address, self.kind)
+            }
+            _ => write!(f, "{} is a {}", self.host, self.kind),
+        }
+    }
+}
+
+impl From<SsrfViolation> for CrawlError {
+    fn from(violation: SsrfViolation) -> Self {
+        CrawlError::AddressBlocked(violation.to_string())
+    }
+}
+
+/// Checks URLs against `SsrfProtectionConfig`
+#[derive(Debug, Clone)]
+pub struct SsrfGuard {
+    config: SsrfProtectionConfig,
+    /// Hosts configured in `internal_network`, allowed on purpose
+    internal: Option<InternalNetworkConfig>,
+}
+
+impl SsrfGuard {
+    pub fn new(config: SsrfProtectionConfig) -> Self {
+        Self {
+            config,
+            internal: None,
+        }
+    }
+
+    /// Allow the hosts of an internal network configuration
+    pub fn with_internal_network(mut self, internal: Option<InternalNetworkConfig>) -> Self {
+        self.internal = internal;
+        self
+    }
+
+    pub fn config(&self) -> &SsrfProtectionConfig {
+        &self.config
+    }
+
+    /// Check a URL without resolving its host name
+    ///
+    /// Literal addresses, `localhost` and metadata host names are refused here;
+    /// other names need `check_resolved`.
+    pub fn check(&self, url: &Url) -> Result<(), SsrfViolation> {
+        let Some(host) = url.host() else {
+            return Ok(());
+        };
+        let name = url.host_str().unwrap_or_default();
+        if self.is_allowed_host(name) {
+            return Ok(());
+        }
+        match host {
+            Host::Ipv4(ip) => self.check_address(name, IpAddr::V4(ip)),
+            Host::Ipv6(ip) => self.check_address(name, IpAddr::V6(ip)),
+            Host::Domain(_) => {
+                let key = host_key(name);
+                let kind = if METADATA_HOSTS.contains(&key.as_str()) {
+                    BlockedAddress::CloudMetadata
+                } else if key == "localhost" || key.ends_with(".localhost") {
+                    BlockedAddress::Loopback
+                } else {
+                    return Ok(());
+                };
+                self.refuse(name, None, kind)
+            }
+        }
+    }
+
+    /// Check a URL and every address its host name resolves to
+    ///
+    /// A name that does not resolve is refused rather than let through unchecked.
+    pub async fn check_resolved(&self, url: &Url) -> Result<(), SsrfViolation> {
+        self.check(url)?;
+        let (Some(Host::Domain(name)), Some(port)) = (url.host(), url.port_or_known_default())
+        else {
+            return Ok(());
+        };
+        if !self.config.resolve_hosts || self.is_allowed_host(name) {
+            return Ok(());
+        }
+        match tokio::net::lookup_host((name, port)).await {
+            Ok(addresses) => self.check_addresses(name, addresses),
+            Err(_) => self.refuse(name, None, BlockedAddress::Unresolved),
+        }
+    }
+
+    /// Check the addresses `host` resolved to, unless the host is allowlisted
+    pub fn check_addresses(
+        &self,
+        host: &str,
+        addresses: impl IntoIterator<Item = SocketAddr>,
+    ) -> Result<(), SsrfViolation> {
+        if self.is_allowed_host(host) {
+            return Ok(());
+        }
+        for address in addresses {
+            self.check_address(host, address.ip())?;
+        }
+        Ok(())
+    }
+
+    fn check_address(&self, host: &str, ip: IpAddr) -> Result<(), SsrfViolation> {
+        if self
+            .config
+            .allowed_networks
+            .iter()
+            .any(|network| network.contains(&ip))
+        {
+            return Ok(());
+        }
+        match BlockedAddress::classify(ip) {
+            Some(kind) => self.refuse(host, Some(ip), kind),
+            None => Ok(()),
+        }
+    }
+
+    fn refuse(
+        &self,
+        host: &str,
+        address: Option<IpAddr>,
+        kind: BlockedAddress,
+    ) -> Result<(), SsrfViolation> {
+        let blocked = if kind.is_metadata() {
+            self.config.block_metadata_endpoints
+        } else {
+            self.config.block_private_networks
+        };
+        if !blocked {
+            return Ok(());
+        }
+        Err(SsrfViolation {
+            host: host.to_string(),
+            address,
+            kind,
+        })
+    }
+
+    fn is_allowed_host(&self, host: &str) -> bool {
+        let host = host_key(host);
+        self.config.allowed_hosts.iter().any(|allowed| {
+            let allowed = host_key(allowed);
+            host == allowed || host.ends_with(&format!(".{}", allowed))
+        }) || self
+            .internal
+            .as_ref()
+            .is_some_and(|internal| internal.is_internal(&host))
+    }
+}
+
+/// DNS resolver refusing names that resolve to addresses the guard blocks
+///
+/// Installed with `ClientBuilder::dns_resolver`, it sees every name a client
+/// connects to, including redirect targets, and checks the very addresses the
+/// connection will use. A name that fails to resolve fails the request.
+pub struct GuardedResolver {
+    guard: Arc<SsrfGuard>,
+    /// Resolves through this cache instead of the system resolver when set
+    cache: Option<DnsCache>,
+}
+
+impl GuardedResolver {
+    pub fn new(guard: Arc<SsrfGuard>) -> Self {
+        Self { guard, cache: None }
+    }
+
+    /// Resolve through `cache`, so prefetched hosts are still checked but not
+    /// looked up again
+    pub fn with_cache(mut self, cache: DnsCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let guard = Arc::clone(&self.guard);
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let host = name.as_str();
+            let addresses: Vec<SocketAddr> = match cache {
+                Some(cache) => cache.resolve(name.clone()).await?.collect(),
+                None => tokio::net::lookup_host((host, 0)).await?.collect(),
+            };
+            if addresses.is_empty() {
+                return Err(CrawlError::from(SsrfViolation {
+                    host: host.to_string(),
+                    address: None,
+                    kind: BlockedAddress::Unresolved,
+                })
+                .into());
+            }
+            guard
+                .check_addresses(host, addresses.iter().copied())
+                .map_err(CrawlError::from)?;
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Redirect policy following at most `max_redirects` hops, refusing targets the
+/// guard blocks without resolving their names
+pub fn redirect_policy(guard: Option<Arc<SsrfGuard>>, max_redirects: usize) -> Policy {
+    let Some(guard) = guard else {
+        return Policy::limited(max_redirects);
+    };
+    Policy::custom(move |attempt| {
+        if attempt.previous().len() > max_redirects {
+            attempt.error("too many redirects")
+        } else if let Err(violation) = guard.check(attempt.url()) {
+            attempt.error(CrawlError::from(violation))
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_private_and_metadata_addresses_are_refused() {
+        let guard = SsrfGuard::new(SsrfProtectionConfig::default());
+        for (value, kind) in [
+            (
+                "http://169.254.169.254/latest/meta-data/",
+                BlockedAddress::CloudMetadata,
+            ),
+            (
+                "http://metadata.google.internal/computeMetadata/v1/",
+                BlockedAddress::CloudMetadata,
+            ),
+            ("http://127.0.0.1:8080/", BlockedAddress::Loopback),
+            ("http://localhost/", BlockedAddress::Loopback),
+            ("http://[::1]/", BlockedAddress::Loopback),
+            ("http://10.1.2.3/", BlockedAddress::Private),
+            ("http://192.168.0.1/admin", BlockedAddress::Private),
+            ("http://[fd12::1]/", BlockedAddress::Private),
+            ("http://169.254.10.1/", BlockedAddress::LinkLocal),
+            (
+                "http://[::ffff:169.254.169.254]/",
+                BlockedAddress::CloudMetadata,
+            ),
+            ("http://100.64.0.1/", BlockedAddress::SharedAddressSpace),
+            ("http://0.0.0.0/", BlockedAddress::NonRoutable),
+        ] {
+            assert_eq!(
+                guard.check(&url(value)).unwrap_err().kind,
+                kind,
+                "{}",
+                value
+            );
+        }
+        assert!(guard.check(&url("https://93.184.216.34/")).is_ok());
+        assert!(guard.check(&url("https://example.com/")).is_ok());
+        assert_eq!(
+            guard
+                .check(&url("http://10.0.0.5/"))
+                .unwrap_err()
+                .to_string(),
+            "10.0.0.5 is a private network address"
+        );
+        assert!(
+            guard
+                .check_resolved(&url("http://localhost/"))
+                .await
+                .is_err()
+        );
+
+        let allowlisted = SsrfGuard::new(SsrfProtectionConfig {
+            allowed_hosts: vec!["localhost".to_string()],
+            allowed_networks: vec!["10.20.0.0/16".parse().unwrap()],
+            ..SsrfProtectionConfig::default()
+        })
+        .with_internal_network(Some(InternalNetworkConfig {
+            internal_domains: vec!["corp.example".to_string()],
+            ..InternalNetworkConfig::default()
+        }));
+        assert!(allowlisted.check(&url("http://localhost:3000/")).is_ok());
+        assert!(allowlisted.check(&url("http://10.20.1.1/")).is_ok());
+        assert!(allowlisted.check(&url("http://10.21.1.1/")).is_err());
+        assert!(
+            allowlisted
+                .check_resolved(&url("http://wiki.corp.example/"))
+                .await
+                .is_ok()
+        );
+
+        assert_eq!(
+            guard
+                .check_resolved(&url("http://unresolvable.invalid/"))
+                .await
+                .unwrap_err()
+                .kind,
+            BlockedAddress::Unresolved
+        );
+
+        // The resolver refuses names whose connection addresses are private
+        let resolver = GuardedResolver::new(Arc::new(guard.clone()));
+        let refused = resolver
+            .resolve("localhost".parse().unwrap())
+            .await
+            .err()
+            .unwrap();
+        assert!(refused.to_string().contains("loopback"), "{}", refused);
+        let resolved = GuardedResolver::new(Arc::new(allowlisted.clone()))
+            .resolve("localhost".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(
+            resolved
+                .map(|address| address.ip())
+                .all(|ip| ip.is_loopback())
+        );
+
+        let metadata_only = SsrfGuard::new(SsrfProtectionConfig {
+            block_private_networks: false,
+            ..SsrfProtectionConfig::default()
+        });
+        assert!(metadata_only.check(&url("http://192.168.0.1/")).is_ok());
+        assert!(
+            metadata_only
+                .check(&url("http://169.254.169.254/"))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+/// TLS certificate details for asset inventory
+///
+/// Reads the fields of an X.509 leaf certificate that inventories need (subject,
+/// issuer, subject alternative names, serial and validity) from the DER bytes
+/// reqwest exposes with `tls_info`.
+use anyhow::{Error, anyhow};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::der_parser::Oid;
+use x509_parser::extensions::GeneralName;
+use x509_parser::oid_registry::{
+    OID_X509_COMMON_NAME, OID_X509_COUNTRY_NAME, OID_X509_LOCALITY_NAME,
+    OID_X509_ORGANIZATION_NAME, OID_X509_ORGANIZATIONAL_UNIT, OID_X509_STATE_OR_PROVINCE_NAME,
+};
+use x509_parser::prelude::FromDer;
+use x509_parser::time::ASN1Time;
+use x509_parser::x509::X509Name;
+
+/// Name attributes kept in `subject` and `issuer`
+const NAME_ATTRIBUTES: &[(&Oid<'static>, &str)] = &[
+    (&OID_X509_COMMON_NAME, "CN"),
+    (&OID_X509_COUNTRY_NAME, "C"),
+    (&OID_X509_LOCALITY_NAME, "L"),
+    (&OID_X509_STATE_OR_PROVINCE_NAME, "ST"),
+    (&OID_X509_ORGANIZATION_NAME, "O"),
+    (&OID_X509_ORGANIZATIONAL_UNIT, "OU"),
+];
+
+/// Details of the certificate a server presented
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    /// Distinguished name, e.g. `CN=example.com, O=Example Ltd`
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names and IP addresses the certificate is valid for
+    pub san: Vec<String>,
+    /// Serial number in hex
+    pub serial: String,
+    /// RFC 3339 UTC timestamps
+    pub not_before: String,
+    pub not_after: String,
+    /// `not_after` as seconds since the Unix epoch, for sorting and expiry checks
+    pub not_after_unix: i64,
+}
+
+impl CertificateInfo {
+    /// Seconds from `now_unix` until the certificate expires (negative once expired)
+    pub fn seconds_until_expiry(&self, now_unix: i64) -> i64 {
+        self.not_after_unix - now_unix
+    }
+}
+
+/// Parse a DER-encoded X.509 certificate
+pub fn parse_certificate(der: &[u8]) -> Result<CertificateInfo, Error> {
+    let (_, certificate) =
+        X509Certificate::from_der(der).map_err(|e| anyhow!("Invalid certificate: {}", e))?;
+    let validity = certificate.validity();
+
+    let san = match certificate.subject_alternative_name() {
+        Ok(Some(extension)) => extension
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(ip) => match ip.len() {
+                    4 => <[u8; 4]>::try_from(*ip)
+                        .ok()
+                        .map(|octets| Ipv4Addr::from(octets).to_string()),
+                    16 => <[u8; 16]>::try_from(*ip)
+                        .ok()
+                        .map(|octets| Ipv6Addr::from(octets).to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        Ok(None) => Vec::new(),
+        Err(e) => return Err(anyhow!("Invalid subjectAltName: {}", e)),
+    };
+
+    Ok(CertificateInfo {
+        subject: format_name(certificate.subject()),
+        issuer: format_name(certificate.issuer()),
+        san,
+        serial: certificate
+            .raw_serial()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+        not_before: format_rfc3339(&validity.not_before),
+        not_after: format_rfc3339(&validity.not_after),
+        not_after_unix: validity.not_after.timestamp(),
+    })
+}
+
+/// `CN=..., O=...` from an X.501 Name, keeping the common attributes in order
+fn format_name(name: &X509Name) -> String {
+    name.iter_attributes()
+        .filter_map(|attribute| {
+            let (_, label) = NAME_ATTRIBUTES
+                .iter()
+                .find(|(oid, _)| *oid == attribute.attr_type())?;
+            let value = attribute.as_str().ok()?;
+            Some(format!("{}={}", label, value))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_rfc3339(time: &ASN1Time) -> String {
+    let time = time.to_datetime();
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    /// Leaf certificate for example.com issued by a throwaway test CA
+    const LEAF_PEM: &str = "\
+MIIBtTCCAVqgAwIBAgICCrwwCgYIKoZIzj0EAwIwPzELMAkGA1UEBhMCVVMxEzAR
+BgNVBAoMCkV4YW1wbGUgQ0ExGzAZBgNVBAMMEkV4YW1wbGUgSXNzdWluZyBDQTAe
+Fw0yNDAzMDEwMDAwMDBaFw0yNjEwMTYxMjMwNDVaMBYxFDASBgNVBAMMC2V4YW1w
+bGUuY29tMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEb7YWwpiShY8XlE9APDFj
+2wd7JS43ihgkDuWB2ZHolWdLXzTOdwm56saskCHp590bzq37CSo82vaLbSHQNKdo
+iaNvMG0wKwYDVR0RBCQwIoILZXhhbXBsZS5jb22CDSouZXhhbXBsZS5jb22HBMAA
+AgEwHQYDVR0OBBYEFBTlQdW5zPWXVJ//MjpCsPUxDs/jMB8GA1UdIwQYMBaAFCHb
+YR5a+IXzHfLnxkfRmu6TrHovMAoGCCqGSM49BAMCA0kAMEYCIQCirrvqCtIRo4t2
+LJMchfUZhCH+75X6r8kT43ZnXEPvLQIhAMMZ6gM3HyYvbN1QIb7Vxl0IvGBJQgxU
+NE3AwQjesvlC";
+
+    #[test]
+    fn test_certificate_fields_are_parsed() {
+        let der = STANDARD.decode(LEAF_PEM.replace('\n', "")).unwrap();
+
+        let info = parse_certificate(&der).unwrap();
+        assert_eq!(info.subject, "CN=example.com");
+        assert_eq!(info.issuer, "C=US, O=Example CA, CN=Example Issuing CA");
+        assert_eq!(info.san, vec!["example.com", "*.example.com", "192.0.2.1"]);
+        assert_eq!(info.serial, "0abc");
+        assert_eq!(info.not_before, "2024-03-01T00:00:00Z");
+        assert_eq!(info.not_after, "2026-10-16T12:30:45Z");
+        assert_eq!(info.not_after_unix, 1_792_153_845);
+        assert_eq!(info.seconds_until_expiry(1_792_153_800), 45);
+
+        assert!(parse_certificate(&der[..der.len() - 5]).is_err());
+    }
+}
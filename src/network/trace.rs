@@ -0,0 +1,208 @@
+/// HTTP request/response tracing for per-domain diagnostics
+///
+/// When enabled for a domain, every fetch is written to a JSONL trace file with the
+/// full request and response headers (secrets redacted) and the timings of the
+/// request itself. reqwest does not expose connection phases, so DNS, connect and
+/// TLS are part of the time to first byte rather than measured apart.
+use anyhow::Error;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::config::HttpTraceConfig;
+
+/// Replacement value written for redacted secrets
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Timings of the traced request in milliseconds; `None` when not measured
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraceTiming {
+    /// Time from sending the request until response headers arrived, including
+    /// DNS, connect and TLS when the request opened a new connection
+    pub ttfb_ms: Option<f64>,
+    pub body_ms: Option<f64>,
+    pub total_ms: f64,
+}
+
+/// One traced HTTP exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpTraceRecord {
+    pub timestamp: SystemTime,
+    pub session_id: String,
+    pub method: String,
+    pub url: String,
+    pub request_headers: Vec<(String, String)>,
+    pub status: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    pub body_bytes: usize,
+    pub timing: TraceTiming,
+    pub error: Option<String>,
+}
+
+impl HttpTraceRecord {
+    pub fn new(session_id: &str, method: &str, url: String) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            session_id: session_id.to_string(),
+            method: method.to_string(),
+            url,
+            request_headers: Vec::new(),
+            status: None,
+            response_headers: Vec::new(),
+            body_bytes: 0,
+            timing: TraceTiming::default(),
+            error: None,
+        }
+    }
+}
+
+/// Writes redacted trace records for the domains selected in `HttpTraceConfig`
+pub struct HttpTracer {
+    config: HttpTraceConfig,
+    session_id: String,
+    writer: Mutex<Option<tokio::fs::File>>,
+}
+
+impl HttpTracer {
+    pub fn new(config: HttpTraceConfig, session_id: String) -> Self {
+        Self {
+            config,
+            session_id,
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn output_path(&self) -> &PathBuf {
+        &self.config.output_path
+    }
+
+    /// Check whether the URL's domain is traced
+    pub fn should_trace(&self, url: &Url) -> bool {
+        url.host_str()
+            .map(|host| self.config.matches(host))
+            .unwrap_or(false)
+    }
+
+    /// Start a record for a request, redacting the URL
+    pub fn start_record(&self, method: &str, url: &Url) -> HttpTraceRecord {
+        HttpTraceRecord::new(&self.session_id, method, self.redact_url(url))
+    }
+
+    /// Header name/value pairs with configured secrets replaced
+    pub fn redact_headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str().to_string();
+                let value = if self.is_secret_header(&name) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// URL with credentials and configured query parameter values replaced
+    pub fn redact_url(&self, url: &Url) -> String {
+        let mut redacted = url.clone();
+        if redacted.password().is_some() {
+            let _ = redacted.set_password(Some(REDACTED));
+        }
+
+        if redacted.query().is_some() {
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .map(|(key, value)| {
+                    let value = if self
+                        .config
+                        .redact_query_params
+                        .iter()
+                        .any(|param| param.eq_ignore_ascii_case(&key))
+                    {
+                        REDACTED.to_string()
+                    } else {
+                        value.into_owned()
+                    };
+                    (key.into_owned(), value)
+                })
+                .collect();
+            redacted.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+
+        redacted.to_string()
+    }
+
+    fn is_secret_header(&self, name: &str) -> bool {
+        self.config
+            .redact_headers
+            .iter()
+            .any(|secret| secret.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether time to first byte and body download time are recorded
+    pub fn captures_timing(&self) -> bool {
+        self.config.capture_timing
+    }
+
+    /// Append a record to the trace file
+    pub async fn record(&self, record: &HttpTraceRecord) -> Result<(), Error> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut writer = self.writer.lock().await;
+        if writer.is_none() {
+            if let Some(parent) = self.config.output_path.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.config.output_path)
+                .await?;
+            *writer = Some(file);
+        }
+
+        if let Some(file) = writer.as_mut() {
+            file.write_all(line.as_bytes()).await?;
+            file.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Milliseconds since `started`, with sub-millisecond precision
+pub fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{AUTHORIZATION, HeaderValue, USER_AGENT};
+
+    #[test]
+    fn test_redacts_secret_headers_and_query_params() {
+        let tracer = HttpTracer::new(HttpTraceConfig::default(), "session".to_string());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert(USER_AGENT, HeaderValue::from_static("TestAgent/1.0"));
+        let redacted = tracer.redact_headers(&headers);
+        assert!(redacted.contains(&("authorization".to_string(), REDACTED.to_string())));
+        assert!(redacted.contains(&("user-agent".to_string(), "TestAgent/1.0".to_string())));
+
+        let url = Url::parse("https://user:pw@example.com/a?token=abc&page=2").unwrap();
+        let redacted_url = tracer.redact_url(&url);
+        assert!(!redacted_url.contains("abc"));
+        assert!(!redacted_url.contains(":pw@"));
+        assert!(redacted_url.contains("page=2"));
+    }
+}
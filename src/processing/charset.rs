@@ -0,0 +1,168 @@
+/// Character sets of HTML bodies
+///
+/// Pages declare their charset in the `Content-Type` header or in a
+/// `<meta charset>` / `<meta http-equiv="Content-Type">` tag near the top of the
+/// document, and a byte order mark overrides both. `detect_charset` resolves the
+/// declaration once per response; `decode_chunks` and `ChunkDecoder` turn the body
+/// into text with it, so EUC-KR or Shift_JIS pages are read as text rather than
+/// mojibake.
+use encoding_rs::{CoderResult, Decoder, Encoding, UTF_8};
+use regex::bytes::Regex;
+use std::sync::LazyLock;
+
+/// Bytes of the body searched for a `<meta>` charset declaration
+const META_PREFIX_BYTES: usize = 1024;
+
+static META_CHARSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta[^>]*?charset\s*=\s*["']?\s*([a-z0-9_:.\-]+)"#)
+        .expect("valid meta charset pattern")
+});
+
+/// Charset of a body from its byte order mark, `Content-Type` header or `<meta>`
+/// declaration, in that order; UTF-8 when nothing is declared
+pub fn detect_charset<B: AsRef<[u8]>>(
+    content_type: Option<&str>,
+    chunks: &[B],
+) -> &'static Encoding {
+    let mut prefix = Vec::with_capacity(META_PREFIX_BYTES);
+    for chunk in chunks {
+        let chunk = chunk.as_ref();
+        let remaining = META_PREFIX_BYTES - prefix.len();
+        prefix.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+        if prefix.len() == META_PREFIX_BYTES {
+            break;
+        }
+    }
+    if let Some((encoding, _)) = Encoding::for_bom(&prefix) {
+        return encoding;
+    }
+    let declared = content_type
+        .and_then(header_charset)
+        .or_else(|| meta_charset(&prefix));
+    match declared {
+        Some(encoding) => encoding,
+        None => UTF_8,
+    }
+}
+
+/// Text of a body received in chunks, decoded from `charset`
+///
+/// Malformed sequences become U+FFFD, as with `String::from_utf8_lossy`.
+pub fn decode_chunks<B: AsRef<[u8]>>(chunks: &[B], charset: &'static Encoding) -> String {
+    let body: Vec<u8> = chunks
+        .iter()
+        .flat_map(|chunk| chunk.as_ref().iter().copied())
+        .collect();
+    charset.decode(&body).0.into_owned()
+}
+
+/// Decodes a body chunk by chunk as it is fed to a consumer
+///
+/// Multi-byte sequences split across chunks are carried over to the next one, so
+/// every decoded piece is valid UTF-8 on its own.
+pub struct ChunkDecoder {
+    decoder: Decoder,
+    buffer: String,
+}
+
+impl ChunkDecoder {
+    pub fn new(charset: &'static Encoding) -> Self {
+        Self {
+            decoder: charset.new_decoder(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Text of the next chunk; `last` flushes a trailing incomplete sequence as U+FFFD
+    pub fn decode(&mut self, chunk: &[u8], last: bool) -> &str {
+        self.buffer.clear();
+        let mut input = chunk;
+        loop {
+            let needed = self
+                .decoder
+                .max_utf8_buffer_length(input.len())
+                .unwrap_or(input.len());
+            self.buffer.reserve(needed.max(4));
+            let (result, read, _) = self.decoder.decode_to_string(input, &mut self.buffer, last);
+            input = &input[read..];
+            if let CoderResult::InputEmpty = result {
+                return &self.buffer;
+            }
+        }
+    }
+}
+
+/// `text` encoded back into `charset`; characters it cannot represent become
+/// numeric character references
+pub fn encode_text(text: &str, charset: &'static Encoding) -> Vec<u8> {
+    charset.encode(text).0.into_owned()
+}
+
+fn header_charset(content_type: &str) -> Option<&'static Encoding> {
+    content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| Encoding::for_label(value.trim().trim_matches('"').as_bytes()))
+}
+
+fn meta_charset(prefix: &[u8]) -> Option<&'static Encoding> {
+    let label = META_CHARSET.captures(prefix)?.get(1)?.as_bytes();
+    // A document that could be read to find the tag is not UTF-16
+    Encoding::for_label(label).map(|encoding| match encoding.name() {
+        "UTF-16LE" | "UTF-16BE" => UTF_8,
+        _ => encoding,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charsets_come_from_the_bom_header_or_meta_tag() {
+        let korean = "<p>한국어 웹 페이지</p>";
+        let (euc_kr, _, _) = encoding_rs::EUC_KR.encode(korean);
+
+        let charset = detect_charset(Some("text/html; charset=EUC-KR"), &[&euc_kr]);
+        assert_eq!(charset, encoding_rs::EUC_KR);
+        assert_eq!(
+            decode_chunks(&[&euc_kr[..5], &euc_kr[5..]], charset),
+            korean
+        );
+
+        let page = [
+            &b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=Shift_JIS\">"[..],
+            &encoding_rs::SHIFT_JIS.encode("日本語のページ").0,
+        ]
+        .concat();
+        let charset = detect_charset(Some("text/html"), &[&page]);
+        assert_eq!(charset, encoding_rs::SHIFT_JIS);
+        assert!(decode_chunks(&[&page], charset).ends_with("日本語のページ"));
+        assert_eq!(
+            detect_charset(None, &[b"<meta charset='windows-1251'>"]),
+            encoding_rs::WINDOWS_1251
+        );
+
+        // The byte order mark wins over the header, and UTF-8 is the default
+        assert_eq!(
+            detect_charset(Some("text/html; charset=iso-8859-1"), &[b"\xEF\xBB\xBF<p>"]),
+            UTF_8
+        );
+        assert_eq!(detect_charset(None, &[b"<meta charset=\"utf-16\">"]), UTF_8);
+        assert_eq!(detect_charset(Some("text/html"), &[b"<p>plain</p>"]), UTF_8);
+
+        // Decoding chunk by chunk survives a character split between chunks
+        let mut decoder = ChunkDecoder::new(encoding_rs::EUC_KR);
+        let mut streamed = String::new();
+        for chunk in euc_kr.chunks(3) {
+            streamed.push_str(decoder.decode(chunk, false));
+        }
+        streamed.push_str(decoder.decode(&[], true));
+        assert_eq!(streamed, korean);
+
+        let masked = encode_text("[EMAIL] 님", encoding_rs::EUC_KR);
+        assert_eq!(decode_chunks(&[&masked], encoding_rs::EUC_KR), "[EMAIL] 님");
+    }
+}
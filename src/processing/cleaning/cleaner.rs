@@ -5,6 +5,7 @@ use std::collections::HashSet;
 
 use super::config::CleaningConfig;
 use super::rules::{CleaningEngine, CleaningRule};
+use super::stopwords::StopwordFilter;
 use crate::core::error::CrawlError;
 
 /// Result of text cleaning operation
@@ -14,6 +15,9 @@ pub struct CleaningResult {
     pub original_text: String,
     /// Cleaned text
     pub cleaned_text: String,
+    /// Cleaned text without stopwords, if `keyword_stopwords` is configured
+    #[serde(default)]
+    pub keyword_text: Option<String>,
     /// Cleaning statistics
     pub stats: CleaningStats,
     /// Whether any cleaning was performed
@@ -56,7 +60,10 @@ impl Default for CleaningStats {
 /// Main text cleaner
 pub struct TextCleaner {
     config: CleaningConfig,
+    profile_engine: Option<CleaningEngine>,
     custom_engine: Option<CleaningEngine>,
+    stopword_filters: Vec<StopwordFilter>,
+    keyword_filter: Option<StopwordFilter>,
     word_patterns: Option<Vec<Regex>>,
 }
 
@@ -65,6 +72,12 @@ impl TextCleaner {
     pub fn new(config: CleaningConfig) -> Result<Self, CrawlError> {
         config.validate()?;
 
+        let profile_engine = config
+            .language_profile
+            .as_ref()
+            .map(CleaningEngine::for_language)
+            .transpose()?;
+
         // Compile custom rules if provided
         let custom_engine = if let Some(ref custom_rules) = config.custom_rules {
             let rules: Result<Vec<CleaningRule>, _> = custom_rules
@@ -88,6 +101,21 @@ impl TextCleaner {
             None
         };
 
+        let stopword_languages = if config.word_filter.enabled {
+            config.word_filter.stopword_languages()?
+        } else {
+            Vec::new()
+        };
+        let stopword_filters = stopword_languages
+            .iter()
+            .map(StopwordFilter::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let keyword_filter = config
+            .keyword_stopwords
+            .as_ref()
+            .map(StopwordFilter::new)
+            .transpose()?;
+
         // Compile word patterns
         let word_patterns = if config.word_filter.enabled {
             if let Some(ref patterns) = config.word_filter.remove_patterns {
@@ -108,7 +136,10 @@ impl TextCleaner {
 
         Ok(Self {
             config,
+            profile_engine,
             custom_engine,
+            stopword_filters,
+            keyword_filter,
             word_patterns,
         })
     }
@@ -119,6 +150,7 @@ impl TextCleaner {
             return Ok(CleaningResult {
                 original_text: text.to_string(),
                 cleaned_text: text.to_string(),
+                keyword_text: None,
                 stats: CleaningStats::default(),
                 was_cleaned: false,
             });
@@ -131,6 +163,12 @@ impl TextCleaner {
         let original_chars = text.len();
         let original_words = text.split_whitespace().count();
 
+        // Apply the language profile while line breaks are intact
+        if let Some(ref engine) = self.profile_engine {
+            cleaned_text = engine.apply_rules(&cleaned_text);
+            operations_count += 1;
+        }
+
         // Apply character filtering
         if self.config.character_filter.enabled {
             cleaned_text = self.apply_character_filter(&cleaned_text)?;
@@ -177,6 +215,15 @@ impl TextCleaner {
             ));
         }
 
+        // Keyword analysis works on the cleaned text minus stopwords
+        let keyword_text = self.keyword_filter.as_ref().map(|filter| {
+            filter
+                .apply(&cleaned_text)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+
         let cleaned_chars = cleaned_text.len();
         let cleaned_words = cleaned_text.split_whitespace().count();
         let compression_ratio = if original_chars > 0 {
@@ -190,6 +237,7 @@ impl TextCleaner {
         Ok(CleaningResult {
             original_text: text.to_string(),
             cleaned_text,
+            keyword_text,
             stats: CleaningStats {
                 original_chars,
                 cleaned_chars,
@@ -253,6 +301,10 @@ impl TextCleaner {
     /// Apply word-based filtering
     fn apply_word_filter(&self, text: &str) -> Result<String, CrawlError> {
         let filter = &self.config.word_filter;
+        let text = self
+            .stopword_filters
+            .iter()
+            .fold(text.to_string(), |text, filter| filter.apply(&text));
         let words: Vec<&str> = text.split_whitespace().collect();
         let mut filtered_words = Vec::new();
 
@@ -2,6 +2,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+use crate::core::LangType;
 use crate::core::error::CrawlError;
 
 /// Length-based filtering configuration
@@ -81,7 +82,8 @@ pub struct WordFilter {
     pub remove_patterns: Option<Vec<String>>,
     /// Case-sensitive word matching
     pub case_sensitive: bool,
-    /// Remove stop words for specific languages
+    /// Remove the built-in stop words of these languages, given as language tags
+    /// such as "en" or "de"
     pub remove_stop_words: Option<Vec<String>>,
     /// Remove words containing numbers
     pub remove_numeric_words: bool,
@@ -103,6 +105,30 @@ impl Default for WordFilter {
     }
 }
 
+impl WordFilter {
+    /// Remove the built-in stopwords of a language
+    pub fn stopwords(language: LangType) -> Self {
+        Self {
+            enabled: true,
+            remove_stop_words: Some(vec![language.to_http_code().to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Languages whose stopwords are removed
+    pub fn stopword_languages(&self) -> Result<Vec<LangType>, CrawlError> {
+        self.remove_stop_words
+            .iter()
+            .flatten()
+            .map(|tag| {
+                LangType::from_language_tag(tag).ok_or_else(|| {
+                    CrawlError::CleaningConfigError(format!("No stop words for language '{}'", tag))
+                })
+            })
+            .collect()
+    }
+}
+
 /// Language-specific filtering configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanguageFilter {
@@ -154,6 +180,13 @@ pub struct CleaningConfig {
     pub language_filter: LanguageFilter,
     /// Custom cleaning rules
     pub custom_rules: Option<Vec<String>>,
+    /// Built-in rules for one language, applied before the other filters
+    #[serde(default)]
+    pub language_profile: Option<LangType>,
+    /// Language whose stopwords are dropped from `CleaningResult::keyword_text`,
+    /// the text meant for keyword analysis; `cleaned_text` keeps them
+    #[serde(default)]
+    pub keyword_stopwords: Option<LangType>,
     /// Preserve original formatting
     pub preserve_formatting: bool,
     /// Output empty result if all content removed
@@ -169,6 +202,8 @@ impl Default for CleaningConfig {
             word_filter: WordFilter::default(),
             language_filter: LanguageFilter::default(),
             custom_rules: None,
+            language_profile: None,
+            keyword_stopwords: None,
             preserve_formatting: true,
             allow_empty_result: false,
         }
@@ -215,6 +250,14 @@ impl CleaningConfig {
         }
     }
 
+    /// Basic cleaning with the built-in profile for a language
+    pub fn for_language(language: LangType) -> Self {
+        Self {
+            language_profile: Some(language),
+            ..Self::basic()
+        }
+    }
+
     /// Enable CJK (Chinese, Japanese, Korean) removal
     pub fn remove_cjk() -> Self {
         Self {
@@ -282,6 +325,8 @@ impl CleaningConfig {
 
         // Validate word filter patterns
         if self.word_filter.enabled {
+            self.word_filter.stopword_languages()?;
+
             if let Some(ref patterns) = self.word_filter.remove_patterns {
                 for pattern in patterns {
                     if let Err(e) = regex::Regex::new(pattern) {
@@ -329,7 +374,9 @@ impl CleaningConfig {
                 || self.character_filter.enabled
                 || self.word_filter.enabled
                 || self.language_filter.enabled
-                || self.custom_rules.is_some())
+                || self.custom_rules.is_some()
+                || self.language_profile.is_some()
+                || self.keyword_stopwords.is_some())
     }
 
     /// Enable length filtering with specific constraints
@@ -356,6 +403,12 @@ impl CleaningConfig {
         self
     }
 
+    /// Produce a `keyword_text` without the stopwords of a language
+    pub fn with_keyword_stopwords(mut self, language: LangType) -> Self {
+        self.keyword_stopwords = Some(language);
+        self
+    }
+
     /// Enable language filtering for specific scripts
     pub fn with_language_filter(mut self, remove_cjk: bool, remove_arabic: bool) -> Self {
         self.language_filter.enabled = true;
@@ -6,8 +6,10 @@ pub mod cleaner;
 /// Supports length-based filtering and custom cleaning rules.
 pub mod config;
 pub mod rules;
+pub mod stopwords;
 
 // Re-export all text cleaning components
 pub use cleaner::{CleaningResult, CleaningStats, TextCleaner};
 pub use config::{CharacterFilter, CleaningConfig, LanguageFilter, LengthFilter, WordFilter};
 pub use rules::{CleaningEngine, CleaningRule, RuleType};
+pub use stopwords::{StopwordFilter, stopwords};
@@ -2,8 +2,38 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::core::LangType;
 use crate::core::error::CrawlError;
 
+/// Full-width CJK punctuation and the ASCII it is normalized to
+const FULL_WIDTH_PUNCTUATION: &[(&str, &str)] = &[
+    ("，", ","),
+    ("、", ","),
+    ("．", "."),
+    ("。", "."),
+    ("：", ":"),
+    ("；", ";"),
+    ("！", "!"),
+    ("？", "?"),
+    ("（", "("),
+    ("）", ")"),
+    ("［", "["),
+    ("］", "]"),
+    ("｛", "{"),
+    ("｝", "}"),
+    ("＂", "\""),
+    ("＇", "'"),
+    ("／", "/"),
+    ("＆", "&"),
+    ("％", "%"),
+    ("＃", "#"),
+    ("＠", "@"),
+    ("＋", "+"),
+    ("＝", "="),
+    ("～", "~"),
+    ("\u{3000}", " "),
+];
+
 /// Type of cleaning rule
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RuleType {
@@ -178,6 +208,72 @@ impl CleaningEngine {
         ]
     }
 
+    /// Built-in cleaning profile for a language
+    ///
+    /// Latin-script profiles drop soft hyphens, and German also joins words
+    /// hyphenated across line breaks. CJK profiles normalize full-width punctuation
+    /// to ASCII; Korean also removes Hangul filler characters.
+    pub fn language_profile(language: &LangType) -> Vec<CleaningRule> {
+        let mut rules = Vec::new();
+        match language {
+            LangType::Eng | LangType::Fra | LangType::Deu => {
+                rules.push(CleaningRule::remove("remove_soft_hyphens", r"\u{00AD}"));
+            }
+            LangType::Cmn | LangType::Jpn | LangType::Kor => {
+                rules.extend(FULL_WIDTH_PUNCTUATION.iter().enumerate().map(
+                    |(i, (full_width, ascii))| {
+                        CleaningRule::replace(
+                            &format!("normalize_full_width_{}", i),
+                            full_width,
+                            ascii,
+                        )
+                    },
+                ));
+            }
+        }
+        match language {
+            LangType::Deu => {
+                // "Ein-\nund Ausgang" is a suspended hyphen, not a hyphenated word:
+                // the line break goes before the join below can see it
+                let mut rule = CleaningRule::replace(
+                    "keep_suspended_hyphens",
+                    r"(\p{L})-[ \t]*\r?\n\s*(und|oder|bis|sowie|bzw\.|beziehungsweise)\b",
+                    "$1- $2",
+                );
+                rule.priority = 61;
+                rules.push(rule);
+                let mut rule = CleaningRule::replace(
+                    "join_line_break_hyphenation",
+                    r"(\p{Ll})-[ \t]*\r?\n\s*(\p{Ll})",
+                    "$1$2",
+                );
+                // Runs before whitespace rules while the line breaks are still there
+                rule.priority = 60;
+                rules.push(rule);
+            }
+            LangType::Fra => {
+                rules.push(CleaningRule::replace(
+                    "normalize_narrow_spaces",
+                    r"[\u{00A0}\u{202F}]",
+                    " ",
+                ));
+            }
+            LangType::Kor => {
+                rules.push(CleaningRule::remove(
+                    "remove_hangul_fillers",
+                    r"[\u{115F}\u{1160}\u{3164}\u{FFA0}]",
+                ));
+            }
+            LangType::Eng | LangType::Cmn | LangType::Jpn => {}
+        }
+        rules
+    }
+
+    /// Engine for the built-in profile of a language
+    pub fn for_language(language: &LangType) -> Result<Self, CrawlError> {
+        Self::new(Self::language_profile(language))
+    }
+
     /// Get aggressive cleaning rules
     pub fn aggressive_rules() -> Vec<CleaningRule> {
         vec![
@@ -188,3 +284,32 @@ impl CleaningEngine {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_profiles() {
+        let german = CleaningEngine::for_language(&LangType::Deu).unwrap();
+        assert_eq!(
+            german.apply_rules("Versiche\u{00AD}rung und Ver-\n  sicherung, Nord-\nSüd"),
+            "Versicherung und Versicherung, Nord-\nSüd"
+        );
+        assert_eq!(
+            german.apply_rules("Ein-\nund Ausgang, Vor- oder\nNach-\noder Zwischenteil"),
+            "Ein- und Ausgang, Vor- oder\nNach- oder Zwischenteil"
+        );
+
+        let chinese = CleaningEngine::for_language(&LangType::Cmn).unwrap();
+        assert_eq!(
+            chinese.apply_rules("你好，世界！（测试）"),
+            "你好,世界!(测试)"
+        );
+        let japanese = CleaningEngine::for_language(&LangType::Jpn).unwrap();
+        assert_eq!(japanese.apply_rules("東京、大阪。"), "東京,大阪.");
+
+        let korean = CleaningEngine::for_language(&LangType::Kor).unwrap();
+        assert_eq!(korean.apply_rules("안녕\u{3164}하세요？"), "안녕하세요?");
+    }
+}
@@ -0,0 +1,297 @@
+/// Built-in stopword lists
+///
+/// Function words such as "the", "der", "의" or "の" dominate word counts of any
+/// corpus. The lists below cover the supported `LangType`s and back
+/// `WordFilter::stopwords` and `StopwordFilter`. Chinese and Japanese are not
+/// written with spaces between words, so `StopwordFilter` segments them first: a
+/// Chinese stopword only counts where forward maximum matching against a small
+/// lexicon does not find a longer word ("的" in "我的" but not in "目的"), and a
+/// Japanese one only as the trailing particles of a hiragana run ("東京の",
+/// "晴れです", but nothing of "ありがとう"). The other languages match whole words.
+use regex::{Regex, escape};
+use std::collections::HashSet;
+
+use crate::core::LangType;
+use crate::core::error::CrawlError;
+
+const ENGLISH: &str = "\
+    a about above after again against all also am an and any are as at be because been
+    before being below between both but by can could did do does doing down during each
+    few for from further had has have having he her here hers him his how i if in into is
+    it its itself just may me might more most must my no nor not now of off on once only
+    or other our ours out over own same she should so some such than that the their theirs
+    them then there these they this those through to too under until up us very was we
+    were what when where which while who whom why will with would you your yours";
+
+const FRENCH: &str = "\
+    à afin ai au aussi autre aux avec avez avons avoir c'est ce ceci cela ces cet cette
+    comme d'un d'une dans de des donc dont du elle elles en encore est et été être eu il
+    ils je l'on la le les leur leurs lui ma mais me même mes moi mon ne ni nos notre nous
+    on ont ou où par pas peu plus pour qu'il que qui sa sans se ses si son sont sous sur
+    ta te tes toi ton tous tout très tu un une vos votre vous y";
+
+const GERMAN: &str = "\
+    aber alle als also am an auch auf aus bei bin bis bist da damit dann das dass dem den
+    denn der des die dies diese diesem diesen dieser dieses doch dort du durch ein eine
+    einem einen einer eines er es für hat hatte hier ich ihr ihre im in ist ja jetzt kann
+    kein keine man mit nach nicht noch nur ob oder ohne schon sehr sein seine sich sie
+    sind so über um und uns unter vom von vor war waren was weil wenn wer wie wir wird
+    wurde zu zum zur";
+
+const CHINESE: &str = "\
+    的 了 着 过 吗 呢 吧 啊 我们 你们 他们 她们 它们 这个 那个 这些 那些 这样 那样 一个
+    一些 因为 所以 但是 而且 或者 如果 虽然 然后 以及 还是 就是 不是 没有 可以 已经 自己
+    什么 怎么 为了 之后 之前 其中";
+
+/// Common Chinese words containing a one-character stopword, kept whole
+const CHINESE_WORDS: &str = "\
+    目的 的确 的士 了解 了不起 除了 过去 过来 过程 不过 经过 通过 超过 着急 着手 着想
+    接着 随着 沿着 酒吧 吗啡";
+
+const JAPANESE: &str = "\
+    の に は を が と で も へ から まで より です ます でした ました である だった して
+    した する される いる ある ない こと もの ため よう この その あの これ それ あれ
+    そして しかし また および など";
+
+const KOREAN: &str = "\
+    의 이 그 저 것 수 등 및 또는 그리고 하지만 그러나 그래서 또한 이런 그런 저런 이것 그것
+    저것 우리 너희 그들 있다 없다 하다 되다 때문에 위해 대한 통해 같은 모든 다른";
+
+/// Built-in stopwords of a language, lowercase
+pub fn stopwords(language: &LangType) -> impl Iterator<Item = &'static str> {
+    let words = match language {
+        LangType::Eng => ENGLISH,
+        LangType::Fra => FRENCH,
+        LangType::Deu => GERMAN,
+        LangType::Cmn => CHINESE,
+        LangType::Jpn => JAPANESE,
+        LangType::Kor => KOREAN,
+    };
+    words.split_whitespace()
+}
+
+/// Regex matching the stopwords of a language written with spaces
+///
+/// Longer stopwords are tried first, so "l'on" wins over "on".
+fn stopword_pattern(language: &LangType) -> String {
+    let mut words: Vec<_> = stopwords(language).collect();
+    words.sort_by_key(|word| std::cmp::Reverse(word.chars().count()));
+    let alternatives = words
+        .iter()
+        .map(|word| escape(word))
+        .collect::<Vec<_>>()
+        .join("|");
+    format!(r"(?i)\b(?:{})\b", alternatives)
+}
+
+/// Replaces the built-in stopwords of a language with spaces
+#[derive(Debug, Clone)]
+pub struct StopwordFilter {
+    segmentation: Segmentation,
+}
+
+#[derive(Debug, Clone)]
+enum Segmentation {
+    Words(Regex),
+    /// Forward maximum matching over stopwords and the words kept whole
+    Chinese {
+        stopwords: HashSet<&'static str>,
+        words: HashSet<&'static str>,
+        longest: usize,
+    },
+    /// Particles ending hiragana runs
+    Japanese {
+        stopwords: HashSet<&'static str>,
+    },
+}
+
+impl StopwordFilter {
+    pub fn new(language: &LangType) -> Result<Self, CrawlError> {
+        let segmentation = match language {
+            LangType::Cmn => {
+                let stopwords: HashSet<&str> = stopwords(language).collect();
+                let words: HashSet<&str> = CHINESE_WORDS.split_whitespace().collect();
+                let longest = stopwords
+                    .iter()
+                    .chain(&words)
+                    .map(|word| word.chars().count())
+                    .max()
+                    .unwrap_or(1);
+                Segmentation::Chinese {
+                    stopwords,
+                    words,
+                    longest,
+                }
+            }
+            LangType::Jpn => Segmentation::Japanese {
+                stopwords: stopwords(language).collect(),
+            },
+            _ => Segmentation::Words(Regex::new(&stopword_pattern(language)).map_err(|e| {
+                CrawlError::CleaningRuleError(format!("Invalid stopword pattern: {}", e))
+            })?),
+        };
+        Ok(Self { segmentation })
+    }
+
+    /// `text` with every stopword replaced by a space
+    pub fn apply(&self, text: &str) -> String {
+        match &self.segmentation {
+            Segmentation::Words(pattern) => pattern.replace_all(text, " ").into_owned(),
+            Segmentation::Chinese {
+                stopwords,
+                words,
+                longest,
+            } => remove_chinese(text, stopwords, words, *longest),
+            Segmentation::Japanese { stopwords } => remove_japanese(text, stopwords),
+        }
+    }
+}
+
+fn remove_chinese(
+    text: &str,
+    stopwords: &HashSet<&str>,
+    words: &HashSet<&str>,
+    longest: usize,
+) -> String {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let start = chars[index].0;
+        // The longest lexicon entry starting here, if any
+        let matched = (1..=longest.min(chars.len() - index))
+            .rev()
+            .find_map(|len| {
+                let end = chars.get(index + len).map_or(text.len(), |(end, _)| *end);
+                let candidate = &text[start..end];
+                (stopwords.contains(candidate) || words.contains(candidate))
+                    .then_some((len, candidate))
+            });
+        match matched {
+            Some((len, candidate)) => {
+                if stopwords.contains(candidate) {
+                    result.push(' ');
+                } else {
+                    result.push_str(candidate);
+                }
+                index += len;
+            }
+            None => {
+                result.push(chars[index].1);
+                index += 1;
+            }
+        }
+    }
+    result
+}
+
+fn is_hiragana(c: char) -> bool {
+    ('\u{3041}'..='\u{309F}').contains(&c)
+}
+
+fn remove_japanese(text: &str, stopwords: &HashSet<&str>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let split = rest
+            .char_indices()
+            .find(|(_, next)| is_hiragana(*next) != is_hiragana(c))
+            .map_or(rest.len(), |(index, _)| index);
+        let (run, tail) = rest.split_at(split);
+        if is_hiragana(c) {
+            let kept = &run[..particle_suffix_start(run, stopwords)];
+            result.push_str(kept);
+            if kept.len() < run.len() {
+                result.push(' ');
+            }
+        } else {
+            result.push_str(run);
+        }
+        rest = tail;
+    }
+    result
+}
+
+/// Start of the longest suffix of a hiragana run made only of stopwords
+fn particle_suffix_start(run: &str, stopwords: &HashSet<&str>) -> usize {
+    let boundaries: Vec<usize> = run
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain([run.len()])
+        .collect();
+    // parsable[i]: run[boundaries[i]..] splits into stopwords
+    let mut parsable = vec![false; boundaries.len()];
+    parsable[boundaries.len() - 1] = true;
+    for i in (0..boundaries.len() - 1).rev() {
+        parsable[i] = (i + 1..boundaries.len())
+            .any(|j| parsable[j] && stopwords.contains(&run[boundaries[i]..boundaries[j]]));
+    }
+    boundaries
+        .iter()
+        .zip(&parsable)
+        .find(|(_, parsable)| **parsable)
+        .map_or(run.len(), |(index, _)| *index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::cleaning::{CleaningConfig, TextCleaner, WordFilter};
+
+    #[test]
+    fn test_stopwords_are_dropped_for_keyword_text_only() {
+        let cleaner =
+            TextCleaner::new(CleaningConfig::basic().with_keyword_stopwords(LangType::Deu))
+                .unwrap();
+        let result = cleaner
+            .clean_text("Der Hund und die Katze schlafen im Garten.")
+            .unwrap();
+        assert_eq!(
+            result.cleaned_text,
+            "Der Hund und die Katze schlafen im Garten."
+        );
+        assert_eq!(
+            result.keyword_text.as_deref(),
+            Some("Hund Katze schlafen Garten.")
+        );
+
+        let japanese =
+            TextCleaner::new(CleaningConfig::basic().with_keyword_stopwords(LangType::Jpn))
+                .unwrap();
+        let result = japanese.clean_text("東京の天気は晴れです").unwrap();
+        assert_eq!(result.keyword_text.as_deref(), Some("東京 天気 晴れ"));
+
+        // Stopwords inside longer words are kept
+        let kept = japanese
+            .clean_text("ありがとう、おもしろいものです")
+            .unwrap();
+        assert_eq!(kept.keyword_text.as_deref(), Some("ありがとう、おもしろい"));
+        let chinese =
+            TextCleaner::new(CleaningConfig::basic().with_keyword_stopwords(LangType::Cmn))
+                .unwrap();
+        let result = chinese.clean_text("我们的目的是了解过去").unwrap();
+        assert_eq!(result.keyword_text.as_deref(), Some("目的是了解过去"));
+
+        let filter = CleaningConfig {
+            enabled: true,
+            word_filter: WordFilter::stopwords(LangType::Eng),
+            ..CleaningConfig::default()
+        };
+        let result = TextCleaner::new(filter)
+            .unwrap()
+            .clean_text("The crawler of the web")
+            .unwrap();
+        assert_eq!(result.cleaned_text, "crawler web");
+        assert!(result.keyword_text.is_none());
+
+        let unknown = CleaningConfig {
+            enabled: true,
+            word_filter: WordFilter {
+                remove_stop_words: Some(vec!["xx".to_string()]),
+                ..WordFilter::stopwords(LangType::Eng)
+            },
+            ..CleaningConfig::default()
+        };
+        assert!(unknown.validate().is_err());
+    }
+}
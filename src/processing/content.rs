@@ -6,25 +6,83 @@
 /// - Text analysis and language detection integration
 /// - Enhanced keyword-based content filtering (Feature 1)
 use anyhow::Error;
-use lol_html::{HtmlRewriter, Settings, element};
+use encoding_rs::{Encoding, UTF_8};
+use lol_html::{HtmlRewriter, Settings, doc_comments, element};
 use regex::Regex;
+use scraper::{Html, Selector};
 use unicode_segmentation::UnicodeSegmentation;
 use whatlang::detect;
 
-use crate::config::{LatinWordFilter, defaults};
-use crate::core::{ContentProcessor, LangType};
+use crate::config::{LatinWordFilter, StructuredExtractionConfig, defaults};
+use crate::core::{ContentProcessor, LangType, SkipReason};
+use crate::processing::charset::{ChunkDecoder, decode_chunks};
+use crate::processing::language::{probe_language, resolve_language};
+use crate::processing::structured::{StructuredContent, extract_structured};
 
 // Re-export keyword filtering components (Level 3 extension)
 pub use crate::processing::keyword::{
+    AUTO_KEYWORDS_ANNOTATION, AutoKeywordExtractor, AutoKeywordStage, ExtractedKeyword,
     KeywordConfig, KeywordExtractor, KeywordMatchInfo, KeywordMatcher, KeywordMode, KeywordOptions,
-    MatchResult, MatchStats,
+    MatchResult, MatchStats, auto_keywords,
 };
 
+/// How words are counted, selected from the detected language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordCounting {
+    /// Whitespace-separated tokens containing a letter or digit (Latin scripts)
+    Whitespace,
+    /// Unicode word boundaries (UAX #29); every ideograph and kana counts as a word
+    Segmentation,
+}
+
+impl WordCounting {
+    /// CJK text has no spaces between words, so whitespace splitting would count
+    /// a whole sentence as one word. Unknown languages use segmentation.
+    pub fn for_language(language: Option<&LangType>) -> Self {
+        match language {
+            Some(LangType::Eng | LangType::Fra | LangType::Deu) => WordCounting::Whitespace,
+            Some(LangType::Cmn | LangType::Jpn | LangType::Kor) | None => {
+                WordCounting::Segmentation
+            }
+        }
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        match self {
+            WordCounting::Whitespace => text
+                .split_whitespace()
+                .filter(|token| token.chars().any(char::is_alphanumeric))
+                .count(),
+            WordCounting::Segmentation => text.unicode_words().count(),
+        }
+    }
+}
+
+/// Whether a word is written in a CJK script (Han, kana or Hangul)
+fn is_cjk_word(word: &str) -> bool {
+    word.chars().any(|c| {
+        matches!(c,
+            '\u{1100}'..='\u{11FF}'   // Hangul Jamo
+            | '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+            | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+            | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        )
+    })
+}
+
 /// Content processor with text extraction and validation
 pub struct ContentExtractor {
     regex_cache: regex::Regex,
     accepted_languages: Vec<LangType>,
     latin_word_filter: LatinWordFilter,
+    structured_extraction: Option<StructuredExtractionConfig>,
+    /// Leading text bytes the early language check looks at; 0 disables it
+    language_probe_bytes: usize,
+    /// Weight of a page's declared language against detection, as in
+    /// `resolve_language`
+    declared_language_weight: f64,
 }
 
 impl ContentExtractor {
@@ -39,85 +97,236 @@ impl ContentExtractor {
             regex_cache,
             accepted_languages,
             latin_word_filter,
+            structured_extraction: None,
+            language_probe_bytes: defaults::LANGUAGE_PROBE_BYTES,
+            declared_language_weight: 0.0,
         })
     }
+
+    /// Weigh the language a page declares against detection when accepting it
+    pub fn with_declared_language_weight(mut self, weight: f64) -> Self {
+        self.declared_language_weight = weight;
+        self
+    }
+
+    /// Check the language on this many leading bytes of text before full processing
+    pub fn with_language_probe(mut self, probe_bytes: usize) -> Self {
+        self.language_probe_bytes = probe_bytes;
+        self
+    }
+
+    /// Also extract tables and lists as structured data
+    pub fn with_structured_extraction(mut self, config: StructuredExtractionConfig) -> Self {
+        self.structured_extraction = Some(config);
+        self
+    }
+
+    /// Tables and lists of a streamed body
+    ///
+    /// Returns `None` when structured extraction is not configured or the page has
+    /// neither. Unlike text extraction this parses the whole document at once.
+    pub fn extract_structured_chunks<B: AsRef<[u8]>>(
+        &self,
+        chunks: &[B],
+        charset: &'static Encoding,
+    ) -> Option<StructuredContent> {
+        let config = self.structured_extraction.as_ref()?;
+        let content = extract_structured(&decode_chunks(chunks, charset), config);
+        (!content.is_empty()).then_some(content)
+    }
 }
 
-impl ContentProcessor for ContentExtractor {
-    /// Extract and validate content from HTML (optimized approach)
-    async fn extract_and_validate(&self, content: &str) -> Result<(String, usize), Error> {
-        // Early exit for empty content
-        if content.is_empty() {
-            return Ok((String::new(), 0));
+/// Position of `TextSink` in the rewritten HTML
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum SinkState {
+    #[default]
+    Text,
+    /// After a `<` that may open a tag
+    LessThan,
+    Tag,
+    /// Inside an attribute value quoted with the byte
+    Quoted(u8),
+}
+
+/// Incremental tag stripper fed by the lol_html output sink
+///
+/// Tags are replaced with a single space as they stream past, so neither the raw body
+/// nor the rewritten HTML ever has to be held as one contiguous buffer. A `>` in a
+/// quoted attribute value does not end the tag, and a `<` that opens no tag is text.
+/// Comments are removed by the rewriter before they reach the sink.
+#[derive(Default)]
+struct TextSink {
+    state: SinkState,
+    text: Vec<u8>,
+}
+
+impl TextSink {
+    fn push(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            self.state = match (self.state, byte) {
+                (SinkState::Text, b'<') => SinkState::LessThan,
+                (SinkState::Text, _) => {
+                    self.text.push(byte);
+                    SinkState::Text
+                }
+                (SinkState::LessThan, b'/' | b'!' | b'?')
+                | (SinkState::LessThan, b'a'..=b'z')
+                | (SinkState::LessThan, b'A'..=b'Z') => {
+                    self.text.push(b' ');
+                    SinkState::Tag
+                }
+                (SinkState::LessThan, b'<') => {
+                    self.text.push(b'<');
+                    SinkState::LessThan
+                }
+                (SinkState::LessThan, _) => {
+                    self.text.extend_from_slice(&[b'<', byte]);
+                    SinkState::Text
+                }
+                (SinkState::Tag, b'>') => SinkState::Text,
+                (SinkState::Tag, b'"' | b'\'') => SinkState::Quoted(byte),
+                (SinkState::Tag, _) => SinkState::Tag,
+                (SinkState::Quoted(quote), _) if byte == quote => SinkState::Tag,
+                (quoted, _) => quoted,
+            };
         }
+    }
 
-        // Quick check for very short content to avoid processing overhead
-        if content.len() < defaults::MIN_CONTENT_LENGTH_BYTES {
-            return Ok((String::new(), 0));
+    /// The stripped text; the rewriter writes UTF-8, so it is valid UTF-8
+    fn finish(mut self) -> Result<String, Error> {
+        if self.state == SinkState::LessThan {
+            self.text.push(b'<');
         }
+        Ok(String::from_utf8(self.text)?)
+    }
+}
 
-        // 1. Use lol-html for basic cleaning, then extract text manually
-        let mut cleaned_html = Vec::new();
+impl ContentExtractor {
+    /// Extract and validate content from a streamed body
+    ///
+    /// Chunks are decoded from `charset` one at a time and written straight into the
+    /// rewriter in the order they were received from the network (e.g. `Bytes` from
+    /// `reqwest::Response::chunk`); only the extracted text is retained.
+    pub fn extract_and_validate_chunks<I, B>(
+        &self,
+        chunks: I,
+        charset: &'static Encoding,
+    ) -> Result<(String, usize), Error>
+    where
+        I: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        Ok(self
+            .extract_checked_chunks(chunks, charset, None)?
+            .unwrap_or_default())
+    }
 
-        let mut rewriter = HtmlRewriter::new(
-            Settings {
-                element_content_handlers: vec![
-                    // Remove script and style content
-                    element!("script", |el| {
-                        el.remove();
-                        Ok(())
-                    }),
-                    element!("style", |el| {
-                        el.remove();
-                        Ok(())
-                    }),
-                    // Keep text content from other elements
-                    element!("*", |_el| {
-                        // Keep the element but we'll extract text later
-                        Ok(())
-                    }),
-                ],
-                ..Settings::default()
-            },
-            |c: &[u8]| {
-                cleaned_html.extend_from_slice(c);
-            },
-        );
+    /// Like `extract_and_validate_chunks`, but a page whose opening text is reliably
+    /// in a language outside the accepted list is skipped before the text is
+    /// normalized, filtered and counted, and the reason is returned
+    ///
+    /// `declared` is the language tag the page declares, weighed against detection
+    /// in both language checks.
+    pub fn extract_checked_chunks<I, B>(
+        &self,
+        chunks: I,
+        charset: &'static Encoding,
+        declared: Option<&str>,
+    ) -> Result<Result<(String, usize), SkipReason>, Error>
+    where
+        I: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        let mut sink = TextSink::default();
+        let mut decoder = ChunkDecoder::new(charset);
+        let mut total_bytes = 0;
 
-        rewriter.write(content.as_bytes())?;
-        rewriter.end()?;
+        {
+            // 1. Use lol-html to drop script/style content, stripping tags from the output
+            let mut rewriter = HtmlRewriter::new(
+                Settings {
+                    element_content_handlers: vec![
+                        // Remove script and style content
+                        element!("script", |el| {
+                            el.remove();
+                            Ok(())
+                        }),
+                        element!("style", |el| {
+                            el.remove();
+                            Ok(())
+                        }),
+                    ],
+                    document_content_handlers: vec![doc_comments!(|comment| {
+                        comment.remove();
+                        Ok(())
+                    })],
+                    ..Settings::default()
+                },
+                |c: &[u8]| sink.push(c),
+            );
+
+            for chunk in chunks {
+                let chunk = chunk.as_ref();
+                total_bytes += chunk.len();
+                rewriter.write(decoder.decode(chunk, false).as_bytes())?;
+            }
+            rewriter.write(decoder.decode(&[], true).as_bytes())?;
+            rewriter.end()?;
+        }
+        let text = sink.finish()?;
 
-        let cleaned_html = String::from_utf8_lossy(&cleaned_html);
+        // Early exit for empty or very short content to avoid processing overhead
+        if total_bytes < defaults::MIN_CONTENT_LENGTH_BYTES {
+            return Ok(Ok((String::new(), 0)));
+        }
 
-        // 2. Extract text using regex (faster than DOM parsing for large content)
-        let text = self.regex_cache.replace_all(&cleaned_html, " ");
+        // 2. Detect the language of the opening text, and stop here if it is not accepted
+        if let Some(reason) = probe_language(
+            &text,
+            &self.accepted_languages,
+            self.language_probe_bytes,
+            declared,
+            self.declared_language_weight,
+        ) {
+            return Ok(Err(reason));
+        }
 
         // 3. Clean and normalize whitespace
         let normalized = self.normalize_text(&text);
 
-        // 4. Count words and validate
-        let word_count = self.count_words(&normalized);
+        // 4. Detect the language, weighed against the declared one, which selects how
+        // words are counted
+        let page_language = resolve_language(
+            &normalized,
+            declared.map(str::to_string),
+            self.declared_language_weight,
+        );
+        let language = page_language.language;
 
-        // 5. Apply minimum word count filter
+        // 5. Count words and apply minimum word count filter
+        let word_count = WordCounting::for_language(language.as_ref()).count(&normalized);
         if word_count < defaults::MIN_WORD_COUNT_THRESHOLD {
-            return Ok((String::new(), 0));
+            return Ok(Ok((String::new(), 0)));
         }
 
-        // 6. Language detection and filtering
-        if !self.accepted_languages.is_empty() {
-            if let Some(detected) = detect(&normalized) {
-                if let Some(lang_type) = LangType::from_detected_lang(detected.lang()) {
-                    if !self.accepted_languages.contains(&lang_type) {
-                        return Ok((String::new(), 0));
-                    }
-                } else {
-                    // Language not supported
-                    return Ok((String::new(), 0));
-                }
+        // 6. Language filtering
+        let identified = language.is_some() || page_language.detection_confidence > 0.0;
+        if !self.accepted_languages.is_empty() && identified {
+            match &language {
+                Some(lang_type) if self.accepted_languages.contains(lang_type) => {}
+                // Not accepted or not supported
+                _ => return Ok(Ok((String::new(), 0))),
             }
         }
 
-        Ok((normalized, word_count))
+        Ok(Ok((normalized, word_count)))
+    }
+}
+
+impl ContentProcessor for ContentExtractor {
+    /// Extract and validate content from HTML (optimized approach)
+    async fn extract_and_validate(&self, content: &str) -> Result<(String, usize), Error> {
+        self.extract_and_validate_chunks(std::iter::once(content.as_bytes()), UTF_8)
     }
 
     fn extract_text_from_cleaned_html(&self, html: &str) -> String {
@@ -168,8 +377,10 @@ impl ContentExtractor {
         let filtered_words: Vec<&str> = words
             .iter()
             .filter(|word| {
-                // Apply minimum word length filter
-                if word.len() < self.latin_word_filter.min_word_length {
+                // Apply minimum word length filter (a single CJK character is a full word)
+                if !is_cjk_word(word)
+                    && word.chars().count() < self.latin_word_filter.min_word_length
+                {
                     return false;
                 }
 
@@ -199,9 +410,10 @@ impl ContentExtractor {
         }
     }
 
-    /// Count words in text using Unicode segmentation
+    /// Count words using the strategy for the text's detected language
     fn count_words(&self, text: &str) -> usize {
-        text.unicode_words().count()
+        let language = detect(text).and_then(|info| LangType::from_detected_lang(info.lang()));
+        WordCounting::for_language(language.as_ref()).count(text)
     }
 }
 
@@ -217,6 +429,18 @@ pub fn extract_title_from_html(content: &str) -> Option<String> {
         .map(|m| m.as_str().trim().to_string())
 }
 
+/// Whether `selector` matches an element of the HTML body
+pub fn html_matches_selector<B: AsRef<[u8]>>(
+    chunks: &[B],
+    charset: &'static Encoding,
+    selector: &Selector,
+) -> bool {
+    Html::parse_document(&decode_chunks(chunks, charset))
+        .select(selector)
+        .next()
+        .is_some()
+}
+
 /// Extract links from HTML content
 pub fn extract_links_from_html(content: &str) -> Vec<String> {
     let re = Regex::new(r#"href\s*=\s*["']([^"']+)["']"#).unwrap_or_else(|_| {
@@ -285,3 +509,155 @@ pub fn extract_image_urls(content: &str) -> Vec<String> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_extraction_matches_whole_body() {
+        let extractor = ContentExtractor::new(Vec::new(), LatinWordFilter::default()).unwrap();
+        let html = "<html><head><style>body { color: red; }</style></head><body>\
+                    <p>Streaming extraction keeps memory usage flat while parsing pages</p>\
+                    <script>var hidden = 1;</script><p>Every chunk flows through the rewriter sink</p>\
+                    </body></html>";
+
+        let whole = extractor
+            .extract_and_validate_chunks(std::iter::once(html.as_bytes()), UTF_8)
+            .unwrap();
+        let chunked = extractor
+            .extract_and_validate_chunks(html.as_bytes().chunks(7), UTF_8)
+            .unwrap();
+
+        assert_eq!(whole, chunked);
+        assert!(whole.1 > 0);
+        assert!(!whole.0.contains("hidden"));
+        assert!(!whole.0.contains("color"));
+    }
+
+    #[test]
+    fn test_pages_are_decoded_from_their_charset_and_attributes_stay_in_tags() {
+        // Without word filtering the text keeps its punctuation
+        let extractor = ContentExtractor::new(
+            Vec::new(),
+            LatinWordFilter {
+                excluded_words: Vec::new(),
+                min_word_length: 1,
+                ..LatinWordFilter::default()
+            },
+        )
+        .unwrap();
+        let html = "<html><body><!-- teaser > hidden --><p title=\"a > b\" data-x='<i>'>\
+                    우리는 오늘 오후에 공원에서 산책을 하고 근처 식당에서 저녁을 먹었습니다. \
+                    3 < 4 and 5 > 2 even in prose written by hand.</p></body></html>";
+        let (euc_kr, _, _) = encoding_rs::EUC_KR.encode(html);
+
+        let whole = extractor
+            .extract_and_validate_chunks(std::iter::once(&euc_kr[..]), encoding_rs::EUC_KR)
+            .unwrap();
+        // Chunks of 5 bytes split two-byte characters between them
+        let chunked = extractor
+            .extract_and_validate_chunks(euc_kr.chunks(5), encoding_rs::EUC_KR)
+            .unwrap();
+        assert_eq!(whole, chunked);
+        assert!(whole.0.starts_with("우리는 오늘 오후에"));
+        assert!(whole.0.contains("3 < 4 and 5 > 2"));
+        assert!(!whole.0.contains("a > b"));
+        assert!(!whole.0.contains("hidden"));
+        assert!(!whole.0.contains("'>"));
+    }
+
+    #[test]
+    fn test_cjk_pages_are_not_counted_as_one_word() {
+        let chinese = "我们今天下午去公园散步，然后在附近的餐厅吃晚饭。";
+        assert_eq!(WordCounting::Whitespace.count(chinese), 1);
+        assert!(WordCounting::Segmentation.count(chinese) >= 20);
+        assert_eq!(
+            WordCounting::for_language(Some(&LangType::Jpn)),
+            WordCounting::Segmentation
+        );
+        assert_eq!(WordCounting::Whitespace.count("Hello , world — again"), 3);
+
+        let extractor = ContentExtractor::new(
+            vec![LangType::Cmn],
+            LatinWordFilter {
+                min_word_length: 4,
+                ..LatinWordFilter::default()
+            },
+        )
+        .unwrap();
+        let html = format!("<html><body><p>{}</p></body></html>", chinese.repeat(3));
+        let (text, word_count) = extractor
+            .extract_and_validate_chunks(std::iter::once(html.as_bytes()), UTF_8)
+            .unwrap();
+        assert!(!text.is_empty());
+        assert!(word_count >= 60);
+    }
+
+    #[test]
+    fn test_off_language_pages_are_skipped_on_the_opening_text() {
+        let extractor = ContentExtractor::new(
+            vec![LangType::Eng],
+            LatinWordFilter {
+                excluded_words: Vec::new(),
+                ..LatinWordFilter::default()
+            },
+        )
+        .unwrap();
+        let spanish = "El rápido zorro marrón salta sobre el perro perezoso mientras \
+                       los niños juegan en el parque durante toda la tarde soleada. ";
+        let html = format!("<html><body><p>{}</p></body></html>", spanish.repeat(5));
+        match extractor
+            .extract_checked_chunks(std::iter::once(html.as_bytes()), UTF_8, None)
+            .unwrap()
+        {
+            Err(SkipReason::LanguageNotAccepted {
+                language,
+                confidence,
+            }) => {
+                assert_eq!(language, "spa");
+                assert!(confidence > 0.5);
+            }
+            other => panic!("expected a language skip, got {:?}", other),
+        }
+        // The plain extraction still reports the page as having no content
+        assert_eq!(
+            extractor
+                .extract_and_validate_chunks(std::iter::once(html.as_bytes()), UTF_8)
+                .unwrap(),
+            (String::new(), 0)
+        );
+
+        // A declaration the priors trust over detection keeps the page in both checks,
+        // while declaring another unaccepted language does not
+        let trusting = ContentExtractor::new(
+            vec![LangType::Eng],
+            LatinWordFilter {
+                excluded_words: Vec::new(),
+                ..LatinWordFilter::default()
+            },
+        )
+        .unwrap()
+        .with_declared_language_weight(1.0);
+        let (text, _) = trusting
+            .extract_checked_chunks(std::iter::once(html.as_bytes()), UTF_8, Some("en"))
+            .unwrap()
+            .unwrap();
+        assert!(text.contains("zorro"));
+        assert!(matches!(
+            trusting.extract_checked_chunks(std::iter::once(html.as_bytes()), UTF_8, Some("de")),
+            Ok(Err(SkipReason::LanguageNotAccepted { .. }))
+        ));
+
+        let english = "The quick brown fox jumps over the lazy dog while the children \
+                       play in the park during the whole sunny afternoon. ";
+        let html = format!("<html><body><p>{}</p></body></html>", english.repeat(5));
+        let (text, word_count) = extractor
+            .with_language_probe(0)
+            .extract_checked_chunks(std::iter::once(html.as_bytes()), UTF_8, None)
+            .unwrap()
+            .unwrap();
+        assert!(text.contains("quick brown fox"));
+        assert!(word_count > 50);
+    }
+}
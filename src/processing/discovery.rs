@@ -5,18 +5,29 @@ use crate::config::WebCrawlerConfig;
 /// functionality for web crawling operations, including:
 /// - Basic link extraction and categorization
 /// - Enhanced extensive crawling with auto-queue expansion (Feature 2)
+/// - `<base href>` handling and `<link rel="alternate" hreflang>` alternates
 use crate::core::ErrorUtils;
+use crate::core::LangType;
+use crate::core::idn::normalize_host;
+use crate::core::registrable_domain;
 use crate::core::types::url_serde;
+use crate::crawler::avoided_extension;
 use anyhow::Result;
+use lol_html::{HtmlRewriter, Settings, element};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashSet;
+use std::sync::LazyLock;
 use url::Url;
 
 // Re-export extensive crawling components (Level 3 extension)
 pub use crate::processing::extensive::{
-    CategoryPriorityAdjustments, CrawlDepth, DepthPriorityAdjustments, DiscoveryStats, DomainScope,
-    ExtensiveConfig, ExtensiveQueueManager, LinkCategory, LinkFilter, LinkProcessor,
-    PriorityConfig, PriorityThresholds, ProcessedLink, QueueStatus,
+    CategoryPriorityAdjustments, CategoryRule, CrawlDepth, DepthPriorityAdjustments,
+    DiscoveryStats, DomainSample, DomainScope, ExtensiveConfig, ExtensiveQueueManager,
+    FrontierSample, FrontierSampler, LinkCategorization, LinkCategorizer, LinkCategory, LinkFilter,
+    LinkImportance, LinkProcessor, ParamBudgetTracker, PriorityConfig, PriorityThresholds,
+    ProcessedLink, QueryParamBudget, QueueStatus, SAMPLING_REPORT_FILE, SamplingConfig,
+    SamplingDecision, SamplingReport, session_sampling_path,
 };
 
 /// Link extraction and discovery functionality
@@ -34,6 +45,93 @@ pub struct ExtractedLink {
     pub anchor_text: String,
     pub link_type: LinkType,
     pub depth: usize,
+    /// Part of the page the link appears in
+    #[serde(default)]
+    pub region: LinkRegion,
+    /// `rel` attribute of the element, lowercased
+    #[serde(default)]
+    pub rel: Option<String>,
+}
+
+/// Part of a page a link appears in, from its enclosing sectioning elements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkRegion {
+    /// `<head>`, e.g. `<link>` elements
+    Head,
+    /// `<nav>`
+    Navigation,
+    /// `<header>`
+    Header,
+    /// `<footer>`
+    Footer,
+    /// `<aside>`
+    Aside,
+    /// `<main>` or `<article>`
+    Main,
+    /// Anywhere else
+    #[default]
+    Body,
+}
+
+impl LinkRegion {
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "head" => Some(LinkRegion::Head),
+            "nav" => Some(LinkRegion::Navigation),
+            "header" => Some(LinkRegion::Header),
+            "footer" => Some(LinkRegion::Footer),
+            "aside" => Some(LinkRegion::Aside),
+            "main" | "article" => Some(LinkRegion::Main),
+            _ => None,
+        }
+    }
+}
+
+static SECTION_TAG: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?i)<(/?)(head|nav|header|footer|aside|main|article)\b[^>]*>")
+        .expect("valid sectioning tag pattern")
+});
+
+/// Byte ranges of the sectioning elements of a document, in opening order
+///
+/// Unclosed elements extend to the end of the document.
+fn region_spans(html: &str) -> Vec<(usize, usize, LinkRegion)> {
+    let mut spans = Vec::new();
+    let mut open: Vec<(String, usize, LinkRegion)> = Vec::new();
+    for capture in SECTION_TAG.captures_iter(html) {
+        let (Some(whole), Some(name)) = (capture.get(0), capture.get(2)) else {
+            continue;
+        };
+        let name = name.as_str().to_ascii_lowercase();
+        let closing = capture
+            .get(1)
+            .is_some_and(|slash| !slash.as_str().is_empty());
+        if !closing {
+            if let Some(region) = LinkRegion::from_tag(&name) {
+                open.push((name, whole.start(), region));
+            }
+        } else if let Some(index) = open
+            .iter()
+            .rposition(|(open_name, _, _)| *open_name == name)
+        {
+            let (_, start, region) = open.remove(index);
+            spans.push((start, whole.end(), region));
+        }
+    }
+    spans.extend(
+        open.into_iter()
+            .map(|(_, start, region)| (start, html.len(), region)),
+    );
+    spans
+}
+
+/// Region of the innermost sectioning element containing `offset`
+fn region_at(spans: &[(usize, usize, LinkRegion)], offset: usize) -> LinkRegion {
+    spans
+        .iter()
+        .filter(|(start, end, _)| *start <= offset && offset < *end)
+        .min_by_key(|(start, end, _)| end - start)
+        .map_or(LinkRegion::Body, |(_, _, region)| *region)
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,6 +143,107 @@ pub enum LinkType {
     Document,  // PDF, DOC, etc.
 }
 
+/// Alternate language version of a page declared with `<link rel="alternate" hreflang>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HreflangAlternate {
+    /// Language tag as declared, e.g. "en-GB" or "x-default"
+    pub hreflang: String,
+    #[serde(with = "url_serde")]
+    pub url: Url,
+}
+
+impl HreflangAlternate {
+    /// Check whether the alternate is in one of the accepted languages
+    ///
+    /// Tags are compared by primary subtag, so "en-GB" matches `LangType::Eng`;
+    /// "x-default" never matches. An empty list accepts every alternate.
+    pub fn matches_languages(&self, accepted: &[LangType]) -> bool {
+        let primary = primary_subtag(&self.hreflang);
+        accepted.is_empty()
+            || accepted
+                .iter()
+                .any(|lang| primary_subtag(lang.to_http_code()) == primary)
+    }
+}
+
+/// Whether a `rel` attribute value lists `token`
+fn has_rel_token(rel: &str, token: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|value| value.eq_ignore_ascii_case(token))
+}
+
+/// Whether a `rel` attribute marks the link as the next or previous page of a series
+pub fn is_pagination_rel(rel: &str) -> bool {
+    ["next", "prev", "previous"]
+        .iter()
+        .any(|token| has_rel_token(rel, token))
+}
+
+static REL_ATTRIBUTE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"\srel\s*=\s*["']([^"']*)["']"#).expect("valid rel attribute pattern")
+});
+
+/// Lowercased `rel` attribute of an opening tag
+fn rel_attribute(tag: &str) -> Option<String> {
+    REL_ATTRIBUTE
+        .captures(tag)
+        .and_then(|capture| capture.get(1))
+        .map(|rel| rel.as_str().trim().to_ascii_lowercase())
+}
+
+fn primary_subtag(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase()
+}
+
+/// Link context declared in a document: the effective base URL and hreflang alternates
+#[derive(Debug, Clone)]
+pub struct PageLinkMetadata {
+    /// `<base href>` resolved against the page URL, or the page URL itself
+    pub base_url: Url,
+    pub alternates: Vec<HreflangAlternate>,
+    /// `<meta http-equiv="refresh">` target
+    pub meta_refresh: Option<MetaRefresh>,
+    /// `<link rel="canonical">` target
+    pub canonical: Option<Url>,
+    /// `lang` attribute of the `<html>` element
+    pub html_lang: Option<String>,
+    /// First `rel="next"` target of a `<link>` or `<a>` element
+    pub next_page: Option<Url>,
+}
+
+/// A `<meta http-equiv="refresh" content="5; url=...">` redirect
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaRefresh {
+    pub delay_secs: u64,
+    pub url: Url,
+}
+
+/// Parse a meta refresh `content` value; a refresh without a URL reloads the page and is ignored
+pub fn parse_meta_refresh(content: &str, base_url: &Url) -> Option<MetaRefresh> {
+    let (delay, rest) = match content.split_once([';', ',']) {
+        Some((delay, rest)) => (delay, rest),
+        None => (content, ""),
+    };
+    let delay_secs = delay.trim().parse::<f64>().ok()?.max(0.0) as u64;
+
+    let rest = rest.trim();
+    let target = match rest.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url=") => &rest[4..],
+        _ => rest,
+    };
+    let target = target.trim().trim_matches(|c| c == '\'' || c == '"').trim();
+    if target.is_empty() {
+        return None;
+    }
+
+    let url = base_url.join(target).ok()?;
+    matches!(url.scheme(), "http" | "https").then_some(MetaRefresh { delay_secs, url })
+}
+
 #[derive(Debug, Default)]
 pub struct LinkStats {
     pub total: usize,
@@ -102,16 +301,26 @@ impl LinkExtractor {
     ) -> Result<Vec<ExtractedLink>> {
         let mut links = Vec::new();
 
+        // Relative links resolve against <base href> when the document declares one
+        let base_url = document_base_url(html, current_url);
+        let current_url = &base_url;
+        let regions = region_spans(html);
+
         // Extract href attributes from anchor tags
         let href_regex =
             regex::Regex::new(r#"<a[^>]+href\s*=\s*["']([^"']+)["'][^>]*>([^<]*)</a>"#)?;
         for capture in href_regex.captures_iter(html) {
             if let Some(href) = capture.get(1) {
                 let anchor_text = capture.get(2).map(|m| m.as_str()).unwrap_or("").to_string();
+                let region = region_at(&regions, href.start());
                 if let Ok(resolved_url) = current_url.join(href.as_str())
-                    && let Some(extracted_link) =
-                        self.process_link(resolved_url, anchor_text, current_depth + 1)
+                    && let Some(mut extracted_link) =
+                        self.process_link(resolved_url, anchor_text, current_depth + 1, region)
                 {
+                    // Read rel from the opening tag only, not the anchor text
+                    let tag_start = capture.get(0).map_or(href.start(), |tag| tag.start());
+                    let tag_end = capture.get(2).map_or(href.end(), |text| text.start());
+                    extracted_link.rel = rel_attribute(&html[tag_start..tag_end]);
                     links.push(extracted_link);
                 }
             }
@@ -122,8 +331,12 @@ impl LinkExtractor {
         for capture in img_regex.captures_iter(html) {
             if let Some(src) = capture.get(1)
                 && let Ok(resolved_url) = current_url.join(src.as_str())
-                && let Some(extracted_link) =
-                    self.process_link(resolved_url, "image".to_string(), current_depth + 1)
+                && let Some(extracted_link) = self.process_link(
+                    resolved_url,
+                    "image".to_string(),
+                    current_depth + 1,
+                    region_at(&regions, src.start()),
+                )
             {
                 links.push(extracted_link);
             }
@@ -134,9 +347,14 @@ impl LinkExtractor {
         for capture in link_regex.captures_iter(html) {
             if let Some(href) = capture.get(1)
                 && let Ok(resolved_url) = current_url.join(href.as_str())
-                && let Some(extracted_link) =
-                    self.process_link(resolved_url, "stylesheet".to_string(), current_depth + 1)
+                && let Some(mut extracted_link) = self.process_link(
+                    resolved_url,
+                    "stylesheet".to_string(),
+                    current_depth + 1,
+                    region_at(&regions, href.start()),
+                )
             {
+                extracted_link.rel = rel_attribute(&capture[0]);
                 links.push(extracted_link);
             }
         }
@@ -145,7 +363,13 @@ impl LinkExtractor {
     }
 
     /// Process a single link and determine its type
-    fn process_link(&self, url: Url, anchor_text: String, depth: usize) -> Option<ExtractedLink> {
+    fn process_link(
+        &self,
+        url: Url,
+        anchor_text: String,
+        depth: usize,
+        region: LinkRegion,
+    ) -> Option<ExtractedLink> {
         // Skip invalid URLs
         if !ErrorUtils::is_valid_crawl_url(url.as_str()) {
             return None;
@@ -175,6 +399,8 @@ impl LinkExtractor {
             anchor_text,
             link_type,
             depth,
+            region,
+            rel: None,
         })
     }
 
@@ -283,6 +509,144 @@ impl LinkExtractor {
     }
 }
 
+// Document Link Context
+
+/// Extract `<base href>` and hreflang alternates from a (possibly chunked) HTML body
+///
+/// Only the first `<base>` element counts, as in browsers. Alternates with
+/// unparseable URLs are skipped, and duplicates are dropped.
+pub fn extract_link_metadata<I, B>(chunks: I, page_url: &Url) -> Result<PageLinkMetadata>
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    let base_href: RefCell<Option<String>> = RefCell::new(None);
+    let raw_alternates: RefCell<Vec<(String, String)>> = RefCell::new(Vec::new());
+    let refresh_content: RefCell<Option<String>> = RefCell::new(None);
+    let canonical_href: RefCell<Option<String>> = RefCell::new(None);
+    let html_lang: RefCell<Option<String>> = RefCell::new(None);
+    let next_href: RefCell<Option<String>> = RefCell::new(None);
+    let record_next = |rel: Option<String>, href: Option<String>| {
+        let mut next_href = next_href.borrow_mut();
+        if next_href.is_none() && rel.is_some_and(|rel| has_rel_token(&rel, "next")) {
+            *next_href = href;
+        }
+    };
+
+    {
+        let mut rewriter = HtmlRewriter::new(
+            Settings {
+                element_content_handlers: vec![
+                    element!("base[href]", |el| {
+                        let mut base_href = base_href.borrow_mut();
+                        if base_href.is_none() {
+                            *base_href = el.get_attribute("href");
+                        }
+                        Ok(())
+                    }),
+                    element!("link[hreflang][href]", |el| {
+                        let is_alternate = el.get_attribute("rel").is_some_and(|rel| {
+                            rel.split_ascii_whitespace()
+                                .any(|token| token.eq_ignore_ascii_case("alternate"))
+                        });
+                        if is_alternate
+                            && let (Some(hreflang), Some(href)) =
+                                (el.get_attribute("hreflang"), el.get_attribute("href"))
+                        {
+                            raw_alternates.borrow_mut().push((hreflang, href));
+                        }
+                        Ok(())
+                    }),
+                    element!("meta[http-equiv][content]", |el| {
+                        let is_refresh = el
+                            .get_attribute("http-equiv")
+                            .is_some_and(|value| value.trim().eq_ignore_ascii_case("refresh"));
+                        let mut refresh_content = refresh_content.borrow_mut();
+                        if is_refresh && refresh_content.is_none() {
+                            *refresh_content = el.get_attribute("content");
+                        }
+                        Ok(())
+                    }),
+                    element!("html[lang]", |el| {
+                        let mut html_lang = html_lang.borrow_mut();
+                        if html_lang.is_none() {
+                            *html_lang = el.get_attribute("lang");
+                        }
+                        Ok(())
+                    }),
+                    element!("link[rel][href]", |el| {
+                        let is_canonical = el
+                            .get_attribute("rel")
+                            .is_some_and(|rel| has_rel_token(&rel, "canonical"));
+                        let mut canonical_href = canonical_href.borrow_mut();
+                        if is_canonical && canonical_href.is_none() {
+                            *canonical_href = el.get_attribute("href");
+                        }
+                        record_next(el.get_attribute("rel"), el.get_attribute("href"));
+                        Ok(())
+                    }),
+                    element!("a[rel][href]", |el| {
+                        record_next(el.get_attribute("rel"), el.get_attribute("href"));
+                        Ok(())
+                    }),
+                ],
+                ..Settings::default()
+            },
+            |_: &[u8]| {},
+        );
+
+        for chunk in chunks {
+            rewriter.write(chunk.as_ref())?;
+        }
+        rewriter.end()?;
+    }
+
+    let base_url = base_href
+        .into_inner()
+        .and_then(|href| page_url.join(href.trim()).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let mut alternates: Vec<HreflangAlternate> = Vec::new();
+    for (hreflang, href) in raw_alternates.into_inner() {
+        let Ok(url) = base_url.join(href.trim()) else {
+            continue;
+        };
+        let alternate = HreflangAlternate {
+            hreflang: hreflang.trim().to_string(),
+            url,
+        };
+        if !alternates.contains(&alternate) {
+            alternates.push(alternate);
+        }
+    }
+
+    let meta_refresh = refresh_content
+        .into_inner()
+        .and_then(|content| parse_meta_refresh(&content, &base_url));
+    let canonical = canonical_href
+        .into_inner()
+        .and_then(|href| base_url.join(href.trim()).ok());
+    let next_page = next_href
+        .into_inner()
+        .and_then(|href| base_url.join(href.trim()).ok());
+
+    Ok(PageLinkMetadata {
+        base_url,
+        alternates,
+        meta_refresh,
+        canonical,
+        html_lang: html_lang.into_inner(),
+        next_page,
+    })
+}
+
+/// Effective base URL for resolving relative links in `html`
+pub fn document_base_url(html: &str, page_url: &Url) -> Url {
+    extract_link_metadata(std::iter::once(html.as_bytes()), page_url)
+        .map(|metadata| metadata.base_url)
+        .unwrap_or_else(|_| page_url.clone())
+}
+
 // URL Validation Functions
 
 /// Check if a URL is valid for crawling based on configuration
@@ -313,15 +677,11 @@ pub fn is_valid_crawl_url(url: &Url, config: &WebCrawlerConfig) -> bool {
     }
 
     // Check file extensions that should be avoided
+    if avoided_extension(url, &config.avoid_url_extensions).is_some() {
+        return false;
+    }
     if let Some(path) = url.path_segments() {
         if let Some(last_segment) = path.last() {
-            // Check against configured avoid_url_extensions
-            for extension in &config.avoid_url_extensions {
-                if last_segment.ends_with(extension) {
-                    return false;
-                }
-            }
-
             // Check against common binary file extensions
             let blocked_extensions = [
                 ".pdf", ".jpg", ".png", ".gif", ".mp4", ".zip", ".exe", ".rar", ".tar", ".gz",
@@ -429,8 +789,9 @@ pub fn is_suitable_for_deep_crawl(url: &Url) -> bool {
 pub fn normalize_url(url: &Url) -> String {
     let mut normalized = url.clone();
 
-    // Remove fragment
+    // Remove fragment and the trailing dot of the host
     normalized.set_fragment(None);
+    normalize_host(&mut normalized);
 
     // Remove common tracking parameters
     let tracking_params = [
@@ -479,9 +840,15 @@ pub fn extract_domain(url: &Url) -> Option<String> {
     url.domain().map(|d| d.to_string())
 }
 
-/// Check if URL belongs to same domain
+/// Check if two URLs belong to the same site, i.e. share a registrable domain
+///
+/// `www.example.co.uk` and `shop.example.co.uk` are the same site;
+/// `alice.github.io` and `bob.github.io` are not.
 pub fn is_same_domain(url1: &Url, url2: &Url) -> bool {
-    url1.domain() == url2.domain()
+    match (url1.host_str(), url2.host_str()) {
+        (Some(host1), Some(host2)) => registrable_domain(host1) == registrable_domain(host2),
+        _ => false,
+    }
 }
 
 /// Check if URL is an asset (CSS, JS, images)
@@ -529,6 +896,76 @@ mod tests {
         assert!(!normalized.contains("#section"));
     }
 
+    #[test]
+    fn test_base_href_and_hreflang_alternates() {
+        let html = r#"<html><head>
+            <base href="https://cdn.example.com/site/">
+            <link rel="alternate" hreflang="fr-FR" href="fr/page">
+            <link rel="alternate" hreflang="x-default" href="https://example.com/page">
+            <link rel="stylesheet" hreflang="de" href="style.css">
+        </head><body></body></html>"#;
+        let page_url = Url::parse("https://example.com/page").unwrap();
+
+        let metadata = extract_link_metadata([html], &page_url).unwrap();
+        assert_eq!(metadata.base_url.as_str(), "https://cdn.example.com/site/");
+        assert_eq!(metadata.alternates.len(), 2);
+        assert_eq!(
+            metadata.alternates[0].url.as_str(),
+            "https://cdn.example.com/site/fr/page"
+        );
+        assert!(metadata.alternates[0].matches_languages(&[LangType::Fra]));
+        assert!(!metadata.alternates[1].matches_languages(&[LangType::Eng]));
+        assert!(metadata.alternates[0].matches_languages(&[]));
+        assert!(metadata.alternates[1].matches_languages(&[]));
+    }
+
+    #[test]
+    fn test_link_region_is_innermost_section() {
+        let html = "<html><body><header><nav><a href=\"/a\">A</a></nav>\
+                    <a href=\"/b\">B</a></header><main><a href=\"/c\">C</a></main>\
+                    <a href=\"/d\">D</a><FOOTER><a href=\"/e\">E</a></body></html>";
+        let spans = region_spans(html);
+        let region = |href: &str| region_at(&spans, html.find(href).unwrap());
+        assert_eq!(region("/a"), LinkRegion::Navigation);
+        assert_eq!(region("/b"), LinkRegion::Header);
+        assert_eq!(region("/c"), LinkRegion::Main);
+        assert_eq!(region("/d"), LinkRegion::Body);
+        // Unclosed elements run to the end of the document
+        assert_eq!(region("/e"), LinkRegion::Footer);
+    }
+
+    #[test]
+    fn test_meta_refresh_and_canonical() {
+        let html = r#"<html><head>
+            <META HTTP-EQUIV="Refresh" CONTENT="0; URL='/new/home.html'">
+            <link rel="canonical" href="/home">
+        </head></html>"#;
+        let page_url = Url::parse("https://legacy.example.com/index.html").unwrap();
+
+        let metadata = extract_link_metadata([html], &page_url).unwrap();
+        let refresh = metadata.meta_refresh.unwrap();
+        assert_eq!(refresh.delay_secs, 0);
+        assert_eq!(
+            refresh.url.as_str(),
+            "https://legacy.example.com/new/home.html"
+        );
+        assert_eq!(
+            metadata.canonical.unwrap().as_str(),
+            "https://legacy.example.com/home"
+        );
+
+        let base = Url::parse("https://example.com/").unwrap();
+        assert_eq!(parse_meta_refresh("30", &base), None);
+        assert_eq!(
+            parse_meta_refresh("5;url=next", &base)
+                .unwrap()
+                .url
+                .as_str(),
+            "https://example.com/next"
+        );
+        assert_eq!(parse_meta_refresh("0; url=javascript:void(0)", &base), None);
+    }
+
     #[test]
     fn test_is_same_domain() {
         let url1 = Url::parse("https://example.com/page1").unwrap();
@@ -537,5 +974,11 @@ mod tests {
 
         assert!(is_same_domain(&url1, &url2));
         assert!(!is_same_domain(&url1, &url3));
+
+        let shop = Url::parse("https://shop.example.co.uk/").unwrap();
+        let www = Url::parse("https://www.example.co.uk/").unwrap();
+        let other = Url::parse("https://www.other.co.uk/").unwrap();
+        assert!(is_same_domain(&shop, &www));
+        assert!(!is_same_domain(&www, &other));
     }
 }
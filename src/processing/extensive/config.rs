@@ -1,9 +1,13 @@
 /// Configuration for extensive crawling
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use url::Url;
 
+use super::link_processor::LinkCategory;
+use crate::config::PaginationConfig;
 use crate::core::error::CrawlError;
+use crate::core::registrable_domain;
+use crate::processing::discovery::LinkRegion;
 
 /// Crawl depth configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -27,7 +31,7 @@ impl Default for CrawlDepth {
 pub enum DomainScope {
     /// Stay within the same domain
     SameDomain,
-    /// Allow subdomains of the original domain
+    /// Allow every host of the original site, i.e. of its registrable domain
     Subdomains,
     /// Allow specific domains
     AllowList(HashSet<String>),
@@ -90,6 +94,62 @@ impl Default for LinkFilter {
     }
 }
 
+/// Cap on distinct query-parameter combinations crawled per path
+///
+/// Faceted navigation (`/search?page=N&sort=...`) can multiply the frontier; once a
+/// path has used its budget, further variants are skipped. Links are considered in
+/// priority order, so the least important variants are the ones dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryParamBudget {
+    /// Maximum distinct query strings queued per scheme, host and path
+    pub max_variants_per_path: usize,
+    /// Parameters ignored when comparing variants (e.g. tracking parameters)
+    pub ignored_params: Vec<String>,
+}
+
+impl Default for QueryParamBudget {
+    fn default() -> Self {
+        Self {
+            max_variants_per_path: 5,
+            ignored_params: vec![
+                "utm_source".to_string(),
+                "utm_medium".to_string(),
+                "utm_campaign".to_string(),
+                "utm_content".to_string(),
+                "utm_term".to_string(),
+                "fbclid".to_string(),
+                "gclid".to_string(),
+            ],
+        }
+    }
+}
+
+/// Random sampling of discovered links
+///
+/// Only `percent` of each registrable domain's links are queued, for estimating
+/// site size and content statistics without a full crawl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// Share of every domain's discovered links that is crawled, in percent
+    pub percent: f64,
+    /// Seed of the sample; `None` draws one per run (reported for reproduction)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Share per registrable domain, overriding `percent`
+    #[serde(default)]
+    pub domain_percent: HashMap<String, f64>,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            percent: 10.0,
+            seed: None,
+            domain_percent: HashMap::new(),
+        }
+    }
+}
+
 /// Priority scoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriorityConfig {
@@ -107,6 +167,12 @@ pub struct PriorityConfig {
     pub query_penalty: u8,
     /// Priority penalty for fragments
     pub fragment_penalty: u8,
+    /// Largest boost for links the discovered link graph rates as important
+    ///
+    /// Importance is estimated online with OPIC (see `importance`); a link with the
+    /// average importance gets half the boost. 0 disables link-graph scoring.
+    #[serde(default)]
+    pub importance_boost: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +183,12 @@ pub struct CategoryPriorityAdjustments {
     pub resource_penalty: u8,
     pub media_penalty: u8,
     pub other_boost: u8,
+    #[serde(default)]
+    pub navigation_boost: u8,
+    #[serde(default)]
+    pub article_boost: u8,
+    #[serde(default)]
+    pub pagination_boost: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,6 +212,9 @@ impl Default for PriorityConfig {
                 resource_penalty: 30,
                 media_penalty: 20,
                 other_boost: 5,
+                navigation_boost: 0,
+                article_boost: 25,
+                pagination_boost: 10,
             },
             anchor_text_boost: 10,
             depth_adjustments: DepthPriorityAdjustments {
@@ -150,6 +225,78 @@ impl Default for PriorityConfig {
             pattern_boost: 30,
             query_penalty: 5,
             fragment_penalty: 3,
+            importance_boost: 0,
+        }
+    }
+}
+
+/// A rule assigning a category to links
+///
+/// A rule matches when any path pattern (regex on the URL path and query) or anchor
+/// keyword (case-insensitive substring) matches, and, if `regions` is non-empty, the
+/// link lies in one of them. A rule without patterns or keywords matches on region
+/// alone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    /// Reported in `ProcessedLink::category_reason`
+    pub name: String,
+    pub category: LinkCategory,
+    #[serde(default)]
+    pub path_patterns: Vec<String>,
+    #[serde(default)]
+    pub anchor_keywords: Vec<String>,
+    #[serde(default)]
+    pub regions: Vec<LinkRegion>,
+}
+
+/// Rules for categorizing page links, tried in order
+///
+/// File extensions (documents, media, resources) are recognized first; links no rule
+/// matches are internal or external by host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCategorization {
+    pub rules: Vec<CategoryRule>,
+}
+
+impl Default for LinkCategorization {
+    fn default() -> Self {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+        Self {
+            rules: vec![
+                CategoryRule {
+                    name: "pagination".to_string(),
+                    category: LinkCategory::Pagination,
+                    path_patterns: strings(&[r"(?i)/page/\d+/?$", r"(?i)[?&](page|p)=\d+"]),
+                    anchor_keywords: strings(&[
+                        "next page",
+                        "previous page",
+                        "older posts",
+                        "newer posts",
+                    ]),
+                    regions: Vec::new(),
+                },
+                CategoryRule {
+                    name: "article".to_string(),
+                    category: LinkCategory::Article,
+                    path_patterns: strings(&[
+                        r"/\d{4}/\d{2}/",
+                        r"(?i)/(article|articles|post|posts|blog|news|story)/[^/?#]+",
+                    ]),
+                    anchor_keywords: Vec::new(),
+                    regions: Vec::new(),
+                },
+                CategoryRule {
+                    name: "navigation".to_string(),
+                    category: LinkCategory::Navigation,
+                    path_patterns: Vec::new(),
+                    anchor_keywords: Vec::new(),
+                    regions: vec![
+                        LinkRegion::Navigation,
+                        LinkRegion::Header,
+                        LinkRegion::Footer,
+                    ],
+                },
+            ],
         }
     }
 }
@@ -200,6 +347,22 @@ pub struct ExtensiveConfig {
     pub priority_patterns: Option<Vec<String>>,
     /// Respect robots.txt for discovered URLs
     pub respect_robots: bool,
+    /// Per-path query-parameter variant budget, `None` for unlimited variants
+    #[serde(default)]
+    pub query_param_budget: Option<QueryParamBudget>,
+    /// Link categorization rules
+    #[serde(default)]
+    pub categorization: LinkCategorization,
+    /// Page budget of pagination chains, `None` for chains of any length
+    #[serde(default)]
+    pub pagination: Option<PaginationConfig>,
+    /// URLs queued per registrable domain, shared by every seed of that domain;
+    /// `None` for no domain budget
+    #[serde(default)]
+    pub max_urls_per_domain: Option<usize>,
+    /// Queue only a random share of each domain's links; `None` queues them all
+    #[serde(default)]
+    pub sampling: Option<SamplingConfig>,
 }
 
 impl Default for ExtensiveConfig {
@@ -216,6 +379,11 @@ impl Default for ExtensiveConfig {
             queue_delay_ms: Some(100),
             priority_patterns: None,
             respect_robots: true,
+            query_param_budget: None,
+            categorization: LinkCategorization::default(),
+            pagination: None,
+            max_urls_per_domain: None,
+            sampling: None,
         }
     }
 }
@@ -270,6 +438,31 @@ impl ExtensiveConfig {
             }
         }
 
+        if let Some(budget) = &self.query_param_budget
+            && budget.max_variants_per_path == 0
+        {
+            return Err(CrawlError::ExtensiveConfigError(
+                "Query parameter budget must allow at least one variant per path".to_string(),
+            ));
+        }
+
+        if self.max_urls_per_domain == Some(0) {
+            return Err(CrawlError::ExtensiveConfigError(
+                "Domain budget must allow at least one URL per domain".to_string(),
+            ));
+        }
+
+        if let Some(sampling) = &self.sampling
+            && let Some(percent) = std::iter::once(&sampling.percent)
+                .chain(sampling.domain_percent.values())
+                .find(|percent| !(**percent > 0.0 && **percent <= 100.0))
+        {
+            return Err(CrawlError::ExtensiveConfigError(format!(
+                "Sampling share must be above 0% and at most 100%, got {}%",
+                percent
+            )));
+        }
+
         // Validate URL length constraints
         if let (Some(min_len), Some(max_len)) = (
             self.link_filter.min_url_length,
@@ -316,6 +509,17 @@ impl ExtensiveConfig {
             }
         }
 
+        for rule in &self.categorization.rules {
+            for pattern in &rule.path_patterns {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    return Err(CrawlError::ExtensiveConfigError(format!(
+                        "Invalid path pattern '{}' in category rule '{}': {}",
+                        pattern, rule.name, e
+                    )));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -328,13 +532,12 @@ impl ExtensiveConfig {
     pub fn is_domain_allowed(&self, url: &Url, base_url: &Url) -> bool {
         match &self.domain_scope {
             DomainScope::SameDomain => url.domain() == base_url.domain(),
-            DomainScope::Subdomains => {
-                if let (Some(url_domain), Some(base_domain)) = (url.domain(), base_url.domain()) {
-                    url_domain == base_domain || url_domain.ends_with(&format!(".{}", base_domain))
-                } else {
-                    false
+            DomainScope::Subdomains => match (url.domain(), base_url.domain()) {
+                (Some(url_domain), Some(base_domain)) => {
+                    registrable_domain(url_domain) == registrable_domain(base_domain)
                 }
-            }
+                _ => false,
+            },
             DomainScope::AllowList(allowed) => url
                 .domain()
                 .map_or(false, |domain| allowed.contains(domain)),
@@ -0,0 +1,178 @@
+/// Frontier timeline for extensive crawling
+///
+/// Records per-minute queue activity (links discovered and queued, drop reasons,
+/// queue depth per domain) so priority and scope settings can be tuned from what a
+/// crawl actually did.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Instant;
+
+/// Frontier activity during one minute of the crawl
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrontierSample {
+    /// Minutes since the queue manager was created
+    pub minute: u64,
+    /// Pages whose links were added
+    pub pages: usize,
+    pub discovered: usize,
+    pub queued: usize,
+    pub dequeued: usize,
+    /// Links not queued, by reason
+    pub dropped: BTreeMap<String, usize>,
+    /// Queued tasks per domain at the end of the minute
+    pub queue_depth_by_domain: BTreeMap<String, usize>,
+}
+
+impl FrontierSample {
+    /// Links discovered per page in this minute
+    pub fn discovery_rate(&self) -> f64 {
+        if self.pages == 0 {
+            0.0
+        } else {
+            self.discovered as f64 / self.pages as f64
+        }
+    }
+}
+
+/// Minute-by-minute record of the frontier
+#[derive(Debug, Clone)]
+pub struct FrontierTimeline {
+    started: Instant,
+    samples: Vec<FrontierSample>,
+}
+
+impl Default for FrontierTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrontierTimeline {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Sample for the current minute, closing the previous one with `queue_depth`
+    pub(crate) fn current(&mut self, queue_depth: &HashMap<String, usize>) -> &mut FrontierSample {
+        let minute = self.started.elapsed().as_secs() / 60;
+        if self.samples.last().is_none_or(|last| last.minute != minute) {
+            if let Some(last) = self.samples.last_mut() {
+                last.queue_depth_by_domain = depth_snapshot(queue_depth);
+            }
+            self.samples.push(FrontierSample {
+                minute,
+                ..FrontierSample::default()
+            });
+        }
+        self.samples.last_mut().expect("sample pushed above")
+    }
+
+    pub(crate) fn record_drop(&mut self, queue_depth: &HashMap<String, usize>, reason: &str) {
+        *self
+            .current(queue_depth)
+            .dropped
+            .entry(reason.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// All samples, with the last one showing `queue_depth` as its current depth
+    pub fn samples(&self, queue_depth: &HashMap<String, usize>) -> Vec<FrontierSample> {
+        let mut samples = self.samples.clone();
+        if let Some(last) = samples.last_mut() {
+            last.queue_depth_by_domain = depth_snapshot(queue_depth);
+        }
+        samples
+    }
+
+    /// Render samples as a long-format CSV: `minute,metric,key,value`
+    ///
+    /// One row per value (`pages`, `discovered`, `queued`, `dequeued`,
+    /// `discovery_rate`, `dropped`/reason and `queue_depth`/domain), which keeps the
+    /// column set fixed however many domains or drop reasons appear.
+    pub fn to_csv(samples: &[FrontierSample]) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(["minute", "metric", "key", "value"])?;
+        for sample in samples {
+            let minute = sample.minute.to_string();
+            let totals = [
+                ("pages", sample.pages as f64),
+                ("discovered", sample.discovered as f64),
+                ("queued", sample.queued as f64),
+                ("dequeued", sample.dequeued as f64),
+                ("discovery_rate", sample.discovery_rate()),
+            ];
+            for (metric, value) in totals {
+                writer.write_record([minute.as_str(), metric, "", &value.to_string()])?;
+            }
+            for (reason, count) in &sample.dropped {
+                writer.write_record([minute.as_str(), "dropped", reason, &count.to_string()])?;
+            }
+            for (domain, depth) in &sample.queue_depth_by_domain {
+                writer.write_record([
+                    minute.as_str(),
+                    "queue_depth",
+                    domain,
+                    &depth.to_string(),
+                ])?;
+            }
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+}
+
+/// Write samples to `path` as CSV (`.csv` extension) or pretty-printed JSON
+pub fn write_timeline(samples: &[FrontierSample], path: &Path) -> Result<()> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let content = if is_csv {
+        FrontierTimeline::to_csv(samples)?
+    } else {
+        serde_json::to_string_pretty(samples)?
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn depth_snapshot(queue_depth: &HashMap<String, usize>) -> BTreeMap<String, usize> {
+    queue_depth
+        .iter()
+        .filter(|(_, depth)| **depth > 0)
+        .map(|(domain, depth)| (domain.clone(), *depth))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_records_drops_and_depth() {
+        let mut timeline = FrontierTimeline::new();
+        let mut depth = HashMap::new();
+        timeline.current(&depth).discovered += 3;
+        timeline.record_drop(&depth, "queue_full");
+        timeline.record_drop(&depth, "queue_full");
+        depth.insert("a.example".to_string(), 2);
+        depth.insert("b.example".to_string(), 0);
+
+        let samples = timeline.samples(&depth);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].dropped["queue_full"], 2);
+        assert_eq!(
+            samples[0].queue_depth_by_domain.keys().collect::<Vec<_>>(),
+            vec!["a.example"]
+        );
+
+        let csv = FrontierTimeline::to_csv(&samples).unwrap();
+        assert!(csv.starts_with("minute,metric,key,value\n"));
+        assert!(csv.contains("0,discovered,,3\n"));
+        assert!(csv.contains("0,dropped,queue_full,2\n"));
+        assert!(csv.contains("0,queue_depth,a.example,2\n"));
+    }
+}
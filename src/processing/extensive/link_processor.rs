@@ -4,9 +4,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use url::Url;
 
-use super::config::ExtensiveConfig;
+use super::config::{ExtensiveConfig, LinkCategorization};
 use crate::core::error::CrawlError;
-use crate::processing::discovery::ExtractedLink;
+use crate::processing::discovery::{ExtractedLink, LinkRegion, is_pagination_rel};
 
 /// Category of discovered link
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -21,6 +21,12 @@ pub enum LinkCategory {
     Document,
     /// Media link (video, audio)
     Media,
+    /// Site navigation (menus, header and footer links)
+    Navigation,
+    /// Article or post page
+    Article,
+    /// Next/previous page of a listing
+    Pagination,
     /// Other/Unknown
     Other,
 }
@@ -34,6 +40,9 @@ pub struct ProcessedLink {
     pub normalized_url: String,
     /// Link category
     pub category: LinkCategory,
+    /// Why the link got its category, e.g. the matching categorization rule
+    #[serde(default)]
+    pub category_reason: String,
     /// Crawl depth for this link
     pub depth: usize,
     /// Priority score (higher = more important)
@@ -44,12 +53,149 @@ pub struct ProcessedLink {
     pub reason: String,
 }
 
+/// Categorization rule with compiled patterns
+struct CompiledRule {
+    name: String,
+    category: LinkCategory,
+    path_patterns: Vec<Regex>,
+    /// Lowercased
+    anchor_keywords: Vec<String>,
+    regions: Vec<LinkRegion>,
+}
+
+impl CompiledRule {
+    fn matches(&self, url: &Url, anchor_text: &str, region: LinkRegion) -> bool {
+        if !self.regions.is_empty() && !self.regions.contains(&region) {
+            return false;
+        }
+        if self.path_patterns.is_empty() && self.anchor_keywords.is_empty() {
+            return !self.regions.is_empty();
+        }
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let anchor_text = anchor_text.to_lowercase();
+        self.path_patterns
+            .iter()
+            .any(|pattern| pattern.is_match(&path))
+            || self
+                .anchor_keywords
+                .iter()
+                .any(|keyword| anchor_text.contains(keyword.as_str()))
+    }
+}
+
+/// Compiled `LinkCategorization` rules
+pub struct LinkCategorizer {
+    rules: Vec<CompiledRule>,
+}
+
+impl LinkCategorizer {
+    pub fn new(categorization: &LinkCategorization) -> Result<Self, CrawlError> {
+        let mut rules = Vec::with_capacity(categorization.rules.len());
+        for rule in &categorization.rules {
+            let path_patterns = rule
+                .path_patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<Result<Vec<Regex>, _>>()
+                .map_err(|e| {
+                    CrawlError::ExtensiveConfigError(format!(
+                        "Failed to compile path pattern of category rule '{}': {}",
+                        rule.name, e
+                    ))
+                })?;
+            rules.push(CompiledRule {
+                name: rule.name.clone(),
+                category: rule.category.clone(),
+                path_patterns,
+                anchor_keywords: rule
+                    .anchor_keywords
+                    .iter()
+                    .map(|keyword| keyword.to_lowercase())
+                    .collect(),
+                regions: rule.regions.clone(),
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Categorize a link found on `base_url`, returning the category and why
+    ///
+    /// File extensions are checked first, then `rel="next"`/`rel="prev"`, then the
+    /// categorization rules in order; remaining links are internal or external by host.
+    pub fn categorize(
+        &self,
+        url: &Url,
+        base_url: &Url,
+        anchor_text: &str,
+        region: LinkRegion,
+        rel: Option<&str>,
+    ) -> (LinkCategory, String) {
+        if let Some(path) = url.path_segments().and_then(|segments| segments.last()) {
+            if let Some(extension) = path.split('.').last() {
+                let ext = extension.to_lowercase();
+
+                // Document extensions
+                if [
+                    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf",
+                ]
+                .contains(&ext.as_str())
+                {
+                    return (LinkCategory::Document, format!("Extension '{}'", ext));
+                }
+
+                // Media extensions
+                if ["mp3", "mp4", "avi", "mov", "wmv", "flv", "wav", "ogg"].contains(&ext.as_str())
+                {
+                    return (LinkCategory::Media, format!("Extension '{}'", ext));
+                }
+
+                // Resource extensions
+                if [
+                    "jpg", "jpeg", "png", "gif", "bmp", "svg", "webp", "css", "js", "ico",
+                ]
+                .contains(&ext.as_str())
+                {
+                    return (LinkCategory::Resource, format!("Extension '{}'", ext));
+                }
+            }
+        }
+
+        if let Some(rel) = rel
+            && is_pagination_rel(rel)
+        {
+            return (LinkCategory::Pagination, format!("rel=\"{}\"", rel));
+        }
+
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(url, anchor_text, region))
+        {
+            return (rule.category.clone(), format!("Rule '{}'", rule.name));
+        }
+
+        // Determine if internal or external
+        match (url.host_str(), base_url.host_str()) {
+            (Some(url_host), Some(page_host)) if url_host == page_host => {
+                (LinkCategory::Internal, "Same host as the page".to_string())
+            }
+            (Some(_), Some(_)) => (LinkCategory::External, "Different host".to_string()),
+            _ => (LinkCategory::Other, "No host".to_string()),
+        }
+    }
+}
+
 /// Link processor for extensive crawling
 pub struct LinkProcessor {
     config: ExtensiveConfig,
     include_patterns: Option<Vec<Regex>>,
     exclude_patterns: Option<Vec<Regex>>,
     priority_patterns: Option<Vec<Regex>>,
+    categorizer: LinkCategorizer,
 }
 
 impl LinkProcessor {
@@ -96,11 +242,14 @@ impl LinkProcessor {
             None
         };
 
+        let categorizer = LinkCategorizer::new(&config.categorization)?;
+
         Ok(Self {
             config,
             include_patterns,
             exclude_patterns,
             priority_patterns,
+            categorizer,
         })
     }
 
@@ -153,7 +302,13 @@ impl LinkProcessor {
         let mut normalized_url = url.clone();
         self.config.normalize_url(&mut normalized_url);
 
-        let category = self.categorize_link(&url);
+        let (category, category_reason) = self.categorizer.categorize(
+            &url,
+            base_url,
+            &extracted_link.anchor_text,
+            extracted_link.region,
+            extracted_link.rel.as_deref(),
+        );
         let next_depth = current_depth + 1;
 
         let (should_crawl, reason) = self.should_crawl_link(&url, base_url, next_depth, &category);
@@ -163,6 +318,7 @@ impl LinkProcessor {
             extracted_link,
             normalized_url: normalized_url.to_string(),
             category,
+            category_reason,
             depth: next_depth,
             priority,
             should_crawl,
@@ -170,6 +326,16 @@ impl LinkProcessor {
         })
     }
 
+    /// Check one URL against the depth, scope, extension and pattern filters
+    ///
+    /// Returns whether the link would be crawled and why, as `process_links` decides.
+    pub fn evaluate_link(&self, url: &Url, base_url: &Url, depth: usize) -> (bool, String) {
+        let (category, _) = self
+            .categorizer
+            .categorize(url, base_url, "", LinkRegion::Body, None);
+        self.should_crawl_link(url, base_url, depth, &category)
+    }
+
     /// Determine if a link should be crawled
     fn should_crawl_link(
         &self,
@@ -252,50 +418,6 @@ impl LinkProcessor {
         (true, "Passed all filters".to_string())
     }
 
-    /// Categorize a link based on its URL
-    fn categorize_link(&self, url: &Url) -> LinkCategory {
-        if let Some(path) = url.path_segments().and_then(|segments| segments.last()) {
-            if let Some(extension) = path.split('.').last() {
-                let ext = extension.to_lowercase();
-
-                // Document extensions
-                if [
-                    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf",
-                ]
-                .contains(&ext.as_str())
-                {
-                    return LinkCategory::Document;
-                }
-
-                // Media extensions
-                if ["mp3", "mp4", "avi", "mov", "wmv", "flv", "wav", "ogg"].contains(&ext.as_str())
-                {
-                    return LinkCategory::Media;
-                }
-
-                // Resource extensions
-                if [
-                    "jpg", "jpeg", "png", "gif", "bmp", "svg", "webp", "css", "js", "ico",
-                ]
-                .contains(&ext.as_str())
-                {
-                    return LinkCategory::Resource;
-                }
-            }
-        }
-
-        // Determine if internal or external
-        if let (Some(url_host), Some(current_host)) = (url.host_str(), url.host_str()) {
-            if url_host == current_host {
-                LinkCategory::Internal
-            } else {
-                LinkCategory::External
-            }
-        } else {
-            LinkCategory::Other
-        }
-    }
-
     /// Calculate priority score for a link
     fn calculate_priority(
         &self,
@@ -321,6 +443,13 @@ impl LinkProcessor {
                 priority = priority.saturating_sub(adjustments.resource_penalty)
             }
             LinkCategory::Media => priority = priority.saturating_sub(adjustments.media_penalty),
+            LinkCategory::Navigation => {
+                priority = priority.saturating_add(adjustments.navigation_boost)
+            }
+            LinkCategory::Article => priority = priority.saturating_add(adjustments.article_boost),
+            LinkCategory::Pagination => {
+                priority = priority.saturating_add(adjustments.pagination_boost)
+            }
             LinkCategory::Other => priority = priority.saturating_add(adjustments.other_boost),
         }
 
@@ -391,3 +520,105 @@ impl LinkProcessor {
         filtered_links
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::discovery::LinkType;
+
+    fn link(url: &str, anchor_text: &str, region: LinkRegion) -> ExtractedLink {
+        ExtractedLink {
+            url: Url::parse(url).unwrap(),
+            anchor_text: anchor_text.to_string(),
+            link_type: LinkType::Internal,
+            depth: 1,
+            region,
+            rel: None,
+        }
+    }
+
+    #[test]
+    fn test_links_are_categorized_by_configured_rules() {
+        let processor = LinkProcessor::new(ExtensiveConfig::same_domain(3)).unwrap();
+        let base = Url::parse("https://news.example/").unwrap();
+        let links = processor
+            .process_links(
+                vec![
+                    link(
+                        "https://news.example/about",
+                        "About",
+                        LinkRegion::Navigation,
+                    ),
+                    link(
+                        "https://news.example/2024/05/launch",
+                        "Launch",
+                        LinkRegion::Main,
+                    ),
+                    link(
+                        "https://news.example/archive",
+                        "Next page",
+                        LinkRegion::Main,
+                    ),
+                    link("https://news.example/contact", "Contact", LinkRegion::Main),
+                    link("https://other.example/", "Partner", LinkRegion::Body),
+                ],
+                &base,
+                0,
+            )
+            .unwrap();
+
+        let category = |url: &str| {
+            let link = links
+                .iter()
+                .find(|link| link.extracted_link.url.as_str() == url)
+                .unwrap();
+            (link.category.clone(), link.category_reason.clone())
+        };
+        assert_eq!(
+            category("https://news.example/about"),
+            (LinkCategory::Navigation, "Rule 'navigation'".to_string())
+        );
+        assert_eq!(
+            category("https://news.example/2024/05/launch").0,
+            LinkCategory::Article
+        );
+        assert_eq!(
+            category("https://news.example/archive").0,
+            LinkCategory::Pagination
+        );
+        assert_eq!(
+            category("https://news.example/contact").0,
+            LinkCategory::Internal
+        );
+        assert_eq!(category("https://other.example/").0, LinkCategory::External);
+
+        // With the default adjustments articles outrank navigation
+        let priority = |url: &str| {
+            links
+                .iter()
+                .find(|link| link.extracted_link.url.as_str() == url)
+                .unwrap()
+                .priority
+        };
+        assert!(
+            priority("https://news.example/2024/05/launch")
+                > priority("https://news.example/about")
+        );
+
+        // The rules also categorize links outside a link processor
+        let categorizer = LinkCategorizer::new(&LinkCategorization::default()).unwrap();
+        let canonical = Url::parse("https://news.example/2024/05/launch").unwrap();
+        assert_eq!(
+            categorizer
+                .categorize(&canonical, &base, "", LinkRegion::Head, Some("canonical"))
+                .0,
+            LinkCategory::Article
+        );
+        assert_eq!(
+            categorizer
+                .categorize(&base, &canonical, "", LinkRegion::Head, Some("next"))
+                .0,
+            LinkCategory::Pagination
+        );
+    }
+}
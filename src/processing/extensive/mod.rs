@@ -4,13 +4,25 @@
 /// from crawled pages. When enabled, discovered links are automatically added to the
 /// crawl queue for future processing.
 pub mod config;
+pub mod frontier;
+pub mod importance;
 pub mod link_processor;
+pub mod param_budget;
 pub mod queue_manager;
+pub mod sampling;
 
 // Re-export all extensive crawling components
 pub use config::{
-    CategoryPriorityAdjustments, CrawlDepth, DepthPriorityAdjustments, DomainScope,
-    ExtensiveConfig, LinkFilter, PriorityConfig, PriorityThresholds,
+    CategoryPriorityAdjustments, CategoryRule, CrawlDepth, DepthPriorityAdjustments, DomainScope,
+    ExtensiveConfig, LinkCategorization, LinkFilter, PriorityConfig, PriorityThresholds,
+    QueryParamBudget, SamplingConfig,
 };
-pub use link_processor::{LinkCategory, LinkProcessor, ProcessedLink};
+pub use frontier::FrontierSample;
+pub use importance::LinkImportance;
+pub use link_processor::{LinkCategorizer, LinkCategory, LinkProcessor, ProcessedLink};
+pub use param_budget::ParamBudgetTracker;
 pub use queue_manager::{DiscoveryStats, ExtensiveQueueManager, QueueStatus};
+pub use sampling::{
+    DomainSample, FrontierSampler, SAMPLING_REPORT_FILE, SamplingDecision, SamplingReport,
+    session_sampling_path,
+};
@@ -0,0 +1,139 @@
+/// Query-parameter variant budgeting for extensive crawling
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+use super::config::QueryParamBudget;
+use crate::core::types::SkipReason;
+
+/// Tracks the distinct query strings queued for each path
+#[derive(Debug, Clone)]
+pub struct ParamBudgetTracker {
+    budget: QueryParamBudget,
+    variants: HashMap<String, HashSet<String>>,
+}
+
+impl ParamBudgetTracker {
+    pub fn new(budget: QueryParamBudget) -> Self {
+        Self {
+            budget,
+            variants: HashMap::new(),
+        }
+    }
+
+    /// Record the URL's query variant, or reject it if its path is out of budget
+    ///
+    /// URLs without (non-ignored) query parameters never consume budget, and a
+    /// variant that was already admitted is admitted again.
+    pub fn admit(&mut self, url: &Url) -> Result<(), SkipReason> {
+        let Some(signature) = self.query_signature(url) else {
+            return Ok(());
+        };
+
+        let path_key = Self::path_key(url);
+        let variants = self.variants.entry(path_key.clone()).or_default();
+        if variants.contains(&signature) {
+            return Ok(());
+        }
+        if variants.len() >= self.budget.max_variants_per_path {
+            return Err(SkipReason::ParamBudgetExceeded(path_key));
+        }
+
+        variants.insert(signature);
+        Ok(())
+    }
+
+    /// Number of distinct variants admitted for the URL's path
+    pub fn variants_for(&self, url: &Url) -> usize {
+        self.variants
+            .get(&Self::path_key(url))
+            .map(|variants| variants.len())
+            .unwrap_or(0)
+    }
+
+    /// Forget all admitted variants
+    pub fn reset(&mut self) {
+        self.variants.clear();
+    }
+
+    fn path_key(url: &Url) -> String {
+        format!(
+            "{}://{}{}",
+            url.scheme(),
+            url.host_str().unwrap_or(""),
+            url.path()
+        )
+    }
+
+    /// Order-insensitive query signature, `None` when nothing counts toward the budget
+    fn query_signature(&self, url: &Url) -> Option<String> {
+        let mut pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| {
+                !self
+                    .budget
+                    .ignored_params
+                    .iter()
+                    .any(|ignored| ignored.eq_ignore_ascii_case(key))
+            })
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        if pairs.is_empty() {
+            return None;
+        }
+
+        pairs.sort();
+        Some(
+            pairs
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join("&"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_over_the_path_budget_are_rejected_in_order() {
+        let mut tracker = ParamBudgetTracker::new(QueryParamBudget {
+            max_variants_per_path: 2,
+            ignored_params: vec!["utm_source".to_string()],
+        });
+        let admit =
+            |tracker: &mut ParamBudgetTracker, url: &str| tracker.admit(&Url::parse(url).unwrap());
+
+        assert!(admit(&mut tracker, "https://a.example/search?page=1").is_ok());
+        assert!(admit(&mut tracker, "https://a.example/search?page=2&sort=asc").is_ok());
+        // The budget is spent: a third variant is turned away
+        assert_eq!(
+            admit(&mut tracker, "https://a.example/search?page=3"),
+            Err(SkipReason::ParamBudgetExceeded(
+                "https://a.example/search".to_string()
+            ))
+        );
+        // while admitted variants, in any parameter order or with ignored
+        // parameters, and URLs without a query still pass
+        assert!(admit(&mut tracker, "https://a.example/search?sort=asc&page=2").is_ok());
+        assert!(
+            admit(
+                &mut tracker,
+                "https://a.example/search?page=1&utm_source=feed"
+            )
+            .is_ok()
+        );
+        assert!(admit(&mut tracker, "https://a.example/search").is_ok());
+        assert!(admit(&mut tracker, "https://a.example/search?utm_source=feed").is_ok());
+        // Other paths have budgets of their own
+        assert!(admit(&mut tracker, "https://a.example/tags?page=3").is_ok());
+        assert_eq!(
+            tracker.variants_for(&Url::parse("https://a.example/search").unwrap()),
+            2
+        );
+
+        tracker.reset();
+        assert!(admit(&mut tracker, "https://a.example/search?page=3").is_ok());
+    }
+}
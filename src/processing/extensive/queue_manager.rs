@@ -1,13 +1,22 @@
 /// Queue management for extensive crawling
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 use super::config::ExtensiveConfig;
-use super::link_processor::ProcessedLink;
+use super::frontier::{FrontierSample, FrontierTimeline, write_timeline};
+use super::importance::LinkImportance;
+use super::link_processor::{LinkCategory, ProcessedLink};
+use super::param_budget::ParamBudgetTracker;
+use super::sampling::{FrontierSampler, SamplingReport};
 use crate::core::error::CrawlError;
-use crate::core::types::CrawlTask;
+use crate::core::registrable_domain;
+use crate::core::types::{CrawlTask, PaginationChain, SkipReason};
+use crate::logging::{SkipReport, SkipStage};
+use crate::processing::semantic::{RelevanceModel, boost_link_priorities};
 
 /// Status of the extensive crawling queue
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +42,14 @@ pub struct DiscoveryStats {
     pub queued: usize,
     /// Links filtered out
     pub filtered: usize,
+    /// Links skipped because their path exhausted its query-parameter budget
+    pub param_budget_skipped: usize,
+    /// Links skipped because their registrable domain spent its URL budget
+    #[serde(default)]
+    pub domain_budget_skipped: usize,
+    /// Links left out of the random sample of their domain
+    #[serde(default)]
+    pub sampled_out: usize,
     /// Links by category
     pub category_distribution: HashMap<String, usize>,
     /// Average priority score
@@ -47,6 +64,9 @@ impl Default for DiscoveryStats {
             total_discovered: 0,
             queued: 0,
             filtered: 0,
+            param_budget_skipped: 0,
+            domain_budget_skipped: 0,
+            sampled_out: 0,
             category_distribution: HashMap::new(),
             average_priority: 0.0,
             discovery_rate: 0.0,
@@ -61,38 +81,227 @@ pub struct ExtensiveQueueManager {
     processed_urls: HashMap<String, Instant>,
     stats: DiscoveryStats,
     pages_processed: usize,
+    param_budget: Option<ParamBudgetTracker>,
+    timeline: FrontierTimeline,
+    /// Queued tasks per domain, kept for the timeline
+    domain_depth: HashMap<String, usize>,
+    /// Link-graph importance, when `PriorityConfig::importance_boost` is set
+    importance: Option<LinkImportance>,
+    /// URLs seeded or queued per registrable domain, across all seeds of the domain
+    domain_urls: HashMap<String, usize>,
+    /// Random sample of the frontier, when `ExtensiveConfig::sampling` is set
+    sampler: Option<FrontierSampler>,
+    /// Report of the links left out, with the reason and the page they were found on
+    skips: Option<Arc<SkipReport>>,
+    /// Model scoring anchor texts and the largest priority boost it may add
+    relevance: Option<(Arc<dyn RelevanceModel>, u8)>,
 }
 
 impl ExtensiveQueueManager {
     /// Create a new extensive queue manager
     pub fn new(config: ExtensiveConfig) -> Result<Self, CrawlError> {
+        let param_budget = config
+            .query_param_budget
+            .clone()
+            .map(ParamBudgetTracker::new);
+
+        let importance = (config.priority_config.importance_boost > 0).then(LinkImportance::new);
+        let sampler = config.sampling.clone().map(FrontierSampler::new);
+
         Ok(Self {
             config,
             queue: VecDeque::new(),
             processed_urls: HashMap::new(),
             stats: DiscoveryStats::default(),
             pages_processed: 0,
+            param_budget,
+            timeline: FrontierTimeline::new(),
+            domain_depth: HashMap::new(),
+            importance,
+            domain_urls: HashMap::new(),
+            sampler,
+            skips: None,
+            relevance: None,
         })
     }
 
+    /// Boost links found on scored pages by up to `max_boost`, from the parent page's
+    /// relevance and the relevance of their anchor text (see `boost_link_priorities`)
+    pub fn with_relevance_boost(mut self, model: Arc<dyn RelevanceModel>, max_boost: u8) -> Self {
+        self.relevance = Some((model, max_boost));
+        self
+    }
+
+    /// Record every link left out of the queue, and why, in `report`
+    pub fn with_skip_report(mut self, report: Arc<SkipReport>) -> Self {
+        self.skips = Some(report);
+        self
+    }
+
+    fn record_skip(&self, url: &url::Url, reason: SkipReason, source: Option<&CrawlTask>) {
+        if let Some(report) = &self.skips {
+            report.skip(
+                url,
+                reason,
+                SkipStage::Discovery,
+                source.map(|parent| &parent.url),
+            );
+        }
+    }
+
+    /// Record the session's seeds before their links are added
+    ///
+    /// Seeds count as processed and against their registrable domain's budget, so a
+    /// seed reached again through a sibling seed of the same site is not queued a
+    /// second time.
+    pub fn register_seeds(&mut self, seeds: &[url::Url]) {
+        let now = Instant::now();
+        for seed in seeds {
+            let mut normalized = seed.clone();
+            self.config.normalize_url(&mut normalized);
+            if self
+                .processed_urls
+                .insert(normalized.to_string(), now)
+                .is_none()
+            {
+                *self.domain_urls.entry(domain_of(seed)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// URLs seeded or queued so far per registrable domain
+    pub fn domain_url_counts(&self) -> &HashMap<String, usize> {
+        &self.domain_urls
+    }
+
     /// Add discovered links to the queue
     pub async fn add_discovered_links(
         &mut self,
         processed_links: Vec<ProcessedLink>,
+    ) -> Result<usize, CrawlError> {
+        self.add_links(None, processed_links).await
+    }
+
+    /// Add links discovered on `parent`'s page, recording the parent and its
+    /// relevance score as their provenance
+    pub async fn add_child_links(
+        &mut self,
+        parent: &CrawlTask,
+        processed_links: Vec<ProcessedLink>,
+    ) -> Result<usize, CrawlError> {
+        self.add_links(Some(parent), processed_links).await
+    }
+
+    async fn add_links(
+        &mut self,
+        parent: Option<&CrawlTask>,
+        mut processed_links: Vec<ProcessedLink>,
     ) -> Result<usize, CrawlError> {
         if !self.config.should_crawl_extensively() {
             return Ok(0);
         }
 
+        // The parent's importance flows to every link it has, queued or not
+        if let (Some(importance), Some(parent)) = (self.importance.as_mut(), parent) {
+            importance.distribute(
+                parent.url.as_str(),
+                processed_links
+                    .iter()
+                    .filter(|link| link.should_crawl)
+                    .map(|link| link.normalized_url.as_str()),
+            );
+            let boost = self.config.priority_config.importance_boost as f64;
+            for link in &mut processed_links {
+                let score = importance.score(&link.normalized_url);
+                link.priority = link.priority.saturating_add((boost * score).round() as u8);
+            }
+        }
+
+        // Links of a page on the crawl's topic are likelier to be on topic too
+        if let (Some((model, max_boost)), Some(page_score)) =
+            (&self.relevance, parent.and_then(|parent| parent.relevance))
+        {
+            boost_link_priorities(&mut processed_links, model.as_ref(), page_score, *max_boost);
+        }
+
+        // Highest priority first, so parameter budgets are spent on the most important variants
+        processed_links.sort_by_key(|link| std::cmp::Reverse(link.priority));
+
         let mut added_count = 0;
         let start_time = Instant::now();
+        let link_count = processed_links.len();
+        {
+            let sample = self.timeline.current(&self.domain_depth);
+            sample.pages += 1;
+            sample.discovered += link_count;
+        }
 
-        for processed_link in processed_links {
+        let source = parent;
+        let mut links = processed_links.into_iter().enumerate();
+        for (index, processed_link) in links.by_ref() {
             // Update discovery statistics
             self.update_discovery_stats(&processed_link);
 
             if !processed_link.should_crawl {
+                self.record_skip(
+                    &processed_link.extracted_link.url,
+                    SkipReason::LinkFiltered(processed_link.reason.clone()),
+                    source,
+                );
+                self.stats.filtered += 1;
+                self.timeline.record_drop(
+                    &self.domain_depth,
+                    &format!("{:?}", processed_link.category),
+                );
+                continue;
+            }
+
+            // Links of a scoped seed stay within the seed's own depth and domain limits
+            if let Some(parent) = parent
+                && let Some(scope) = &parent.scope
+                && !(scope.allows_depth(parent.depth + 1)
+                    && scope.allows_url(&processed_link.extracted_link.url))
+            {
+                tracing::debug!(
+                    url = %processed_link.extracted_link.url,
+                    seed_domain = %scope.domain,
+                    depth = parent.depth + 1,
+                    "Skipping link outside its seed's scope"
+                );
+                self.record_skip(
+                    &processed_link.extracted_link.url,
+                    SkipReason::OutOfScope(format!("the scope of seed domain {}", scope.domain)),
+                    source,
+                );
+                self.stats.filtered += 1;
+                self.timeline.record_drop(&self.domain_depth, "seed_scope");
+                continue;
+            }
+
+            // Pagination links continue their parent's chain, up to the chain's page budget
+            let pagination = match parent {
+                Some(parent) if processed_link.category == LinkCategory::Pagination => {
+                    Some(PaginationChain::following(parent))
+                }
+                _ => None,
+            };
+            if let (Some(chain), Some(budget)) = (&pagination, &self.config.pagination)
+                && chain.page > budget.max_pages_per_chain
+            {
+                tracing::debug!(
+                    url = %processed_link.extracted_link.url,
+                    chain = %chain.id,
+                    page = chain.page,
+                    "Skipping page beyond its pagination chain budget"
+                );
+                self.record_skip(
+                    &processed_link.extracted_link.url,
+                    SkipReason::PaginationBudgetExceeded(chain.id.clone()),
+                    source,
+                );
                 self.stats.filtered += 1;
+                self.timeline
+                    .record_drop(&self.domain_depth, "pagination_budget");
                 continue;
             }
 
@@ -101,7 +310,14 @@ impl ExtensiveQueueManager {
                 let elapsed = start_time.duration_since(*last_processed);
                 if elapsed < Duration::from_secs(3600) {
                     // Don't re-crawl within 1 hour
+                    self.record_skip(
+                        &processed_link.extracted_link.url,
+                        SkipReason::AlreadyVisited,
+                        source,
+                    );
                     self.stats.filtered += 1;
+                    self.timeline
+                        .record_drop(&self.domain_depth, "recently_processed");
                     continue;
                 }
             }
@@ -109,10 +325,75 @@ impl ExtensiveQueueManager {
             // Check queue capacity
             if let Some(max_size) = self.config.max_queue_size {
                 if self.queue.len() >= max_size {
+                    *self
+                        .timeline
+                        .current(&self.domain_depth)
+                        .dropped
+                        .entry("queue_full".to_string())
+                        .or_insert(0) += link_count - index;
+                    self.record_skip(
+                        &processed_link.extracted_link.url,
+                        SkipReason::QueueFull,
+                        source,
+                    );
                     break;
                 }
             }
 
+            let url = url::Url::parse(&processed_link.normalized_url)
+                .map_err(|_| CrawlError::InvalidUrl(processed_link.normalized_url.clone()))?;
+
+            // Only the domain's random sample is queued; the URL is not drawn again
+            if let Some(sampler) = self.sampler.as_mut()
+                && let Err(reason) = sampler.admit(&url)
+            {
+                tracing::debug!(url = %url, reason = %reason, "Skipping link outside the sample");
+                self.record_skip(&url, reason, source);
+                self.stats.filtered += 1;
+                self.stats.sampled_out += 1;
+                self.timeline.record_drop(&self.domain_depth, "sampling");
+                self.processed_urls
+                    .insert(processed_link.normalized_url, start_time);
+                continue;
+            }
+
+            // Every seed of a registrable domain draws from the same URL budget
+            let domain = domain_of(&url);
+            if let Some(max_urls) = self.config.max_urls_per_domain
+                && self.domain_urls.get(&domain).copied().unwrap_or(0) >= max_urls
+            {
+                tracing::debug!(
+                    url = %url,
+                    domain = %domain,
+                    max_urls,
+                    "Skipping link beyond its domain's URL budget"
+                );
+                self.record_skip(&url, SkipReason::DomainBudgetExceeded(domain), source);
+                self.stats.filtered += 1;
+                self.stats.domain_budget_skipped += 1;
+                self.timeline
+                    .record_drop(&self.domain_depth, "domain_budget");
+                continue;
+            }
+
+            // Check the per-path query-parameter budget
+            if let Some(tracker) = self.param_budget.as_mut()
+                && let Err(reason) = tracker.admit(&url)
+            {
+                tracing::debug!(
+                    url = %url,
+                    priority = processed_link.priority,
+                    reason = ?reason,
+                    "Skipping query-parameter variant"
+                );
+                self.record_skip(&url, reason, source);
+                self.stats.filtered += 1;
+                self.stats.param_budget_skipped += 1;
+                self.timeline
+                    .record_drop(&self.domain_depth, "param_budget");
+                continue;
+            }
+
             // Create crawl task using configurable priority thresholds
             let thresholds = &self.config.priority_thresholds;
             let task_priority = if processed_link.priority <= thresholds.low_threshold {
@@ -125,14 +406,23 @@ impl ExtensiveQueueManager {
                 crate::core::types::TaskPriority::Critical
             };
 
-            let crawl_task = CrawlTask::new(
-                url::Url::parse(&processed_link.normalized_url)
-                    .map_err(|_| CrawlError::InvalidUrl(processed_link.normalized_url.clone()))?,
-                task_priority,
-                3, // max_retries - this could also be configurable if needed
-            );
+            let category = format!("{:?}", processed_link.category);
+            let mut crawl_task = match parent {
+                Some(parent) => {
+                    CrawlTask::discovered_from(parent, url, task_priority, Some(category))
+                }
+                None => CrawlTask::new_with_depth(
+                    url,
+                    task_priority,
+                    3, // max_retries - this could also be configurable if needed
+                    processed_link.depth,
+                ),
+            };
+            crawl_task.pagination = pagination;
 
-            self.queue.push_back(crawl_task);
+            self.push_task(crawl_task);
+            *self.domain_urls.entry(domain).or_insert(0) += 1;
+            self.timeline.current(&self.domain_depth).queued += 1;
             self.processed_urls
                 .insert(processed_link.normalized_url, start_time);
             self.stats.queued += 1;
@@ -143,6 +433,14 @@ impl ExtensiveQueueManager {
                 sleep(Duration::from_millis(delay_ms)).await;
             }
         }
+        // Links after the queue filled up
+        for (_, processed_link) in links {
+            self.record_skip(
+                &processed_link.extracted_link.url,
+                SkipReason::QueueFull,
+                source,
+            );
+        }
 
         self.pages_processed += 1;
         self.update_discovery_rate();
@@ -152,7 +450,35 @@ impl ExtensiveQueueManager {
 
     /// Get the next URL to crawl
     pub fn get_next_url(&mut self) -> Option<CrawlTask> {
-        self.queue.pop_front()
+        let task = self.queue.pop_front()?;
+        if let Some(depth) = self.domain_depth.get_mut(task.url.host_str().unwrap_or("")) {
+            *depth = depth.saturating_sub(1);
+        }
+        self.timeline.current(&self.domain_depth).dequeued += 1;
+        Some(task)
+    }
+
+    /// Seed, per-domain counts and left-out links of the frontier sample
+    pub fn sampling_report(&self) -> Option<SamplingReport> {
+        self.sampler.as_ref().map(FrontierSampler::report)
+    }
+
+    /// Per-minute frontier activity since the manager was created
+    pub fn frontier_timeline(&self) -> Vec<FrontierSample> {
+        self.timeline.samples(&self.domain_depth)
+    }
+
+    /// Write the frontier timeline to `path` as CSV (`.csv`) or JSON
+    pub fn export_frontier_timeline(&self, path: &Path) -> anyhow::Result<()> {
+        write_timeline(&self.frontier_timeline(), path)
+    }
+
+    fn push_task(&mut self, task: CrawlTask) {
+        *self
+            .domain_depth
+            .entry(task.url.host_str().unwrap_or("").to_string())
+            .or_insert(0) += 1;
+        self.queue.push_back(task);
     }
 
     /// Get current queue status
@@ -208,9 +534,21 @@ impl ExtensiveQueueManager {
     }
 
     /// Prioritize queue by moving high-priority items to front
+    ///
+    /// With link-graph scoring, tasks of equal priority are ordered by their current
+    /// importance, which includes links found after they were queued.
     pub fn prioritize_queue(&mut self) {
         let mut queue_vec: Vec<CrawlTask> = self.queue.drain(..).collect();
-        queue_vec.sort_by(|a, b| b.priority.cmp(&a.priority));
+        let cash = |task: &CrawlTask| {
+            self.importance
+                .as_ref()
+                .map_or(0.0, |importance| importance.cash(task.url.as_str()))
+        };
+        queue_vec.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| cash(b).total_cmp(&cash(a)))
+        });
         self.queue.extend(queue_vec);
     }
 
@@ -255,6 +593,7 @@ impl ExtensiveQueueManager {
     /// Import queue state from persistence
     pub fn import_queue(&mut self, tasks: Vec<CrawlTask>) -> Result<(), CrawlError> {
         self.queue.clear();
+        self.domain_depth.clear();
 
         // Validate and add tasks
         for task in tasks {
@@ -263,9 +602,259 @@ impl ExtensiveQueueManager {
                     break;
                 }
             }
-            self.queue.push_back(task);
+            self.push_task(task);
         }
 
         Ok(())
     }
 }
+
+/// Registrable domain of a URL, the key of the shared domain budget
+fn domain_of(url: &url::Url) -> String {
+    registrable_domain(url.host_str().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PaginationConfig;
+    use crate::core::types::{SeedScope, TaskPriority};
+    use crate::processing::discovery::{ExtractedLink, LinkRegion, LinkType};
+    use crate::processing::extensive::config::{CrawlDepth, DomainScope};
+    use crate::processing::extensive::link_processor::LinkProcessor;
+    use url::Url;
+
+    fn links(processor: &LinkProcessor, parent: &CrawlTask, urls: &[&str]) -> Vec<ProcessedLink> {
+        let extracted = urls
+            .iter()
+            .map(|url| ExtractedLink {
+                url: Url::parse(url).unwrap(),
+                anchor_text: String::new(),
+                link_type: LinkType::Internal,
+                depth: parent.depth + 1,
+                region: LinkRegion::Main,
+                rel: None,
+            })
+            .collect();
+        processor
+            .process_links(extracted, &parent.url, parent.depth)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_links_of_relevant_pages_are_boosted_by_their_anchor_text() {
+        use crate::processing::semantic::{EmbeddingRelevanceModel, HashedBagOfWordsEmbedder};
+
+        let config = ExtensiveConfig {
+            queue_delay_ms: None,
+            ..ExtensiveConfig::new(CrawlDepth::Unlimited, DomainScope::Unrestricted)
+        };
+        let processor = LinkProcessor::new(config.clone()).unwrap();
+        let manager = || {
+            let model = EmbeddingRelevanceModel::new(
+                Box::new(HashedBagOfWordsEmbedder::default()),
+                &["rust async runtime tokio futures".to_string()],
+            )
+            .unwrap();
+            ExtensiveQueueManager::new(config.clone())
+                .unwrap()
+                .with_relevance_boost(Arc::new(model), 100)
+        };
+
+        let parent = CrawlTask::new(
+            Url::parse("https://a.example/").unwrap(),
+            TaskPriority::High,
+            3,
+        );
+        let mut found = links(
+            &processor,
+            &parent,
+            &["https://a.example/baking", "https://a.example/tokio"],
+        );
+        found[0].extracted_link.anchor_text = "Banana bread with walnuts".to_string();
+        found[1].extracted_link.anchor_text = "The tokio async runtime".to_string();
+
+        // Without a score for the parent page the links keep their order
+        let mut unscored = manager();
+        unscored
+            .add_child_links(&parent, found.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            unscored.get_next_url().unwrap().url.as_str(),
+            "https://a.example/baking"
+        );
+
+        let mut scored = manager();
+        let mut parent = parent;
+        parent.relevance = Some(0.8);
+        scored.add_child_links(&parent, found).await.unwrap();
+        let first = scored.get_next_url().unwrap();
+        assert_eq!(first.url.as_str(), "https://a.example/tokio");
+        assert_eq!(first.provenance.unwrap().parent_relevance, Some(0.8));
+    }
+
+    #[tokio::test]
+    async fn test_links_follow_their_seeds_scope() {
+        let config = ExtensiveConfig {
+            queue_delay_ms: None,
+            ..ExtensiveConfig::new(CrawlDepth::Unlimited, DomainScope::Unrestricted)
+        };
+        let processor = LinkProcessor::new(config.clone()).unwrap();
+        let mut manager = ExtensiveQueueManager::new(config).unwrap();
+
+        let shallow_url = Url::parse("https://a.example/").unwrap();
+        let mut shallow = CrawlTask::new(shallow_url.clone(), TaskPriority::High, 3);
+        shallow.scope = Some(SeedScope::for_seed(&shallow_url).with_max_depth(1));
+        let deep_url = Url::parse("https://b.example/").unwrap();
+        let mut deep = CrawlTask::new(deep_url.clone(), TaskPriority::High, 3);
+        deep.scope = Some(
+            SeedScope::for_seed(&deep_url)
+                .with_max_depth(4)
+                .with_subdomains(),
+        );
+
+        let urls = [
+            "https://a.example/page",
+            "https://docs.a.example/page",
+            "https://docs.b.example/page",
+            "https://b.example/page",
+            "https://notb.example/page",
+        ];
+        let added = manager
+            .add_child_links(&shallow, links(&processor, &shallow, &urls))
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+        let child = manager.get_next_url().unwrap();
+        assert_eq!(child.url.as_str(), "https://a.example/page");
+        assert_eq!(child.scope, shallow.scope);
+
+        // The shallow seed's child is at its depth limit, so its links are dropped
+        let grandchild = ["https://a.example/deeper"];
+        let added = manager
+            .add_child_links(&child, links(&processor, &child, &grandchild))
+            .await
+            .unwrap();
+        assert_eq!(added, 0);
+
+        let added = manager
+            .add_child_links(&deep, links(&processor, &deep, &urls))
+            .await
+            .unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(manager.get_discovery_stats().filtered, 8);
+    }
+
+    #[tokio::test]
+    async fn test_seeds_of_one_domain_share_dedup_and_budget() {
+        let config = ExtensiveConfig {
+            queue_delay_ms: None,
+            max_urls_per_domain: Some(5),
+            ..ExtensiveConfig::new(CrawlDepth::Unlimited, DomainScope::Unrestricted)
+        };
+        let processor = LinkProcessor::new(config.clone()).unwrap();
+        let mut manager = ExtensiveQueueManager::new(config).unwrap();
+        let home = CrawlTask::new(
+            Url::parse("https://example.com/").unwrap(),
+            TaskPriority::High,
+            3,
+        );
+        let blog = CrawlTask::new(
+            Url::parse("https://blog.example.com/").unwrap(),
+            TaskPriority::High,
+            3,
+        );
+        manager.register_seeds(&[home.url.clone(), blog.url.clone()]);
+
+        // The sibling seed is not queued again from the home page
+        let urls = [
+            "https://blog.example.com/",
+            "https://example.com/a",
+            "https://example.com/b",
+        ];
+        let added = manager
+            .add_child_links(&home, links(&processor, &home, &urls))
+            .await
+            .unwrap();
+        assert_eq!(added, 2);
+
+        let urls = [
+            "https://example.com/a",
+            "https://blog.example.com/x",
+            "https://blog.example.com/y",
+            "https://other.example/z",
+        ];
+        let added = manager
+            .add_child_links(&blog, links(&processor, &blog, &urls))
+            .await
+            .unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(manager.domain_url_counts()["example.com"], 5);
+        assert_eq!(manager.get_discovery_stats().domain_budget_skipped, 1);
+        let queued: Vec<String> = std::iter::from_fn(|| manager.get_next_url())
+            .map(|task| task.url.to_string())
+            .collect();
+        assert_eq!(
+            queued,
+            [
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://blog.example.com/x",
+                "https://other.example/z",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pagination_chains_stop_at_their_page_budget() {
+        let config = ExtensiveConfig {
+            queue_delay_ms: None,
+            pagination: Some(PaginationConfig {
+                follow_next: true,
+                max_pages_per_chain: 3,
+            }),
+            ..ExtensiveConfig::new(CrawlDepth::Unlimited, DomainScope::SameDomain)
+        };
+        let processor = LinkProcessor::new(config.clone()).unwrap();
+        let mut manager = ExtensiveQueueManager::new(config).unwrap();
+        let next = |parent: &CrawlTask, url: &str, rel: Option<&str>| {
+            let link = ExtractedLink {
+                url: Url::parse(url).unwrap(),
+                anchor_text: "more".to_string(),
+                link_type: LinkType::Internal,
+                depth: parent.depth + 1,
+                region: LinkRegion::Main,
+                rel: rel.map(str::to_string),
+            };
+            processor
+                .process_links(vec![link], &parent.url, parent.depth)
+                .unwrap()
+        };
+
+        let first = CrawlTask::new(
+            Url::parse("https://news.example/story").unwrap(),
+            TaskPriority::High,
+            3,
+        );
+        let links = next(&first, "https://news.example/story-continued", Some("next"));
+        assert_eq!(links[0].category, LinkCategory::Pagination);
+        manager.add_child_links(&first, links).await.unwrap();
+        let second = manager.get_next_url().unwrap();
+        let chain = second.pagination.clone().unwrap();
+        assert_eq!(chain.id, "https://news.example/story");
+        assert_eq!(chain.page, 2);
+
+        // URL patterns count as pagination without a rel attribute
+        let links = next(&second, "https://news.example/story?page=3", None);
+        manager.add_child_links(&second, links).await.unwrap();
+        let third = manager.get_next_url().unwrap();
+        assert_eq!(third.pagination.as_ref().unwrap().id, chain.id);
+        assert_eq!(third.pagination.as_ref().unwrap().page, 3);
+
+        let links = next(&third, "https://news.example/story?page=4", Some("next"));
+        let added = manager.add_child_links(&third, links).await.unwrap();
+        assert_eq!(added, 0);
+        assert!(manager.get_next_url().is_none());
+    }
+}
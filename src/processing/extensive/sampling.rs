@@ -0,0 +1,224 @@
+/// Random sampling of the discovery frontier
+///
+/// Crawling a fixed share of every domain's discovered links is enough to estimate
+/// site size and content statistics without a full crawl. Each link's draw is the
+/// SHA-256 of the run's seed and the URL, so a URL's decision does not depend on
+/// the order links are found in, and a run repeated with the same seed crawls the
+/// same sample. The first `MAX_RECORDED_DECISIONS` links left out are recorded with
+/// their draw; the per-domain counts cover every link.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use super::config::SamplingConfig;
+use crate::core::registrable_domain;
+use crate::core::types::SkipReason;
+use crate::storage::path_safe_component;
+
+/// File name of a session's sampling report within its session directory
+pub const SAMPLING_REPORT_FILE: &str = "sampling.json";
+
+/// Links left out that are kept with their draw; later ones are only counted
+const MAX_RECORDED_DECISIONS: usize = 10_000;
+
+/// Path of the sampling report of `session_id` under `storage_dir`
+pub fn session_sampling_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    storage_dir
+        .join(path_safe_component(&format!("session_{}", session_id)))
+        .join(SAMPLING_REPORT_FILE)
+}
+
+/// A link left out of the sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingDecision {
+    pub url: String,
+    /// Registrable domain the link was sampled under
+    pub domain: String,
+    /// Share of the domain's links crawled, in percent
+    pub percent: f64,
+    /// The link's draw in `[0, 100)`; links drawing below `percent` are crawled
+    pub draw: f64,
+}
+
+/// Links kept and left out for one domain
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DomainSample {
+    pub percent: f64,
+    pub kept: usize,
+    pub skipped: usize,
+}
+
+impl DomainSample {
+    /// Distinct links the domain would have yielded without sampling
+    pub fn estimated_links(&self) -> usize {
+        if self.percent <= 0.0 {
+            return self.kept + self.skipped;
+        }
+        (self.kept as f64 * 100.0 / self.percent).round() as usize
+    }
+}
+
+/// Seed, per-domain counts and skipped links of a sampled run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingReport {
+    /// Seed that reproduces the sample
+    pub seed: u64,
+    pub domains: BTreeMap<String, DomainSample>,
+    /// The first links left out; `DomainSample::skipped` counts all of them
+    pub skipped: Vec<SamplingDecision>,
+}
+
+impl SamplingReport {
+    /// Write the report to `path` as JSON
+    pub fn export(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Decides which discovered links belong to the sample
+#[derive(Debug, Clone)]
+pub struct FrontierSampler {
+    config: SamplingConfig,
+    seed: u64,
+    domains: HashMap<String, DomainSample>,
+    skipped: Vec<SamplingDecision>,
+}
+
+impl FrontierSampler {
+    /// Sampler for one run; without a configured seed a random one is drawn
+    pub fn new(config: SamplingConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        Self {
+            config,
+            seed,
+            domains: HashMap::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Share of `domain`'s links that is crawled, in percent
+    pub fn percent_for(&self, domain: &str) -> f64 {
+        self.config
+            .domain_percent
+            .iter()
+            .find(|(overridden, _)| registrable_domain(overridden) == domain)
+            .map_or(self.config.percent, |(_, percent)| *percent)
+    }
+
+    /// Keep the URL in the sample or leave it out, recording the decision
+    pub fn admit(&mut self, url: &Url) -> Result<(), SkipReason> {
+        let domain = registrable_domain(url.host_str().unwrap_or_default());
+        let percent = self.percent_for(&domain);
+        let draw = draw(self.seed, url);
+        let sample = self.domains.entry(domain.clone()).or_default();
+        sample.percent = percent;
+        if draw < percent {
+            sample.kept += 1;
+            return Ok(());
+        }
+        sample.skipped += 1;
+        if self.skipped.len() < MAX_RECORDED_DECISIONS {
+            self.skipped.push(SamplingDecision {
+                url: url.to_string(),
+                domain: domain.clone(),
+                percent,
+                draw,
+            });
+        }
+        Err(SkipReason::SampledOut { domain, percent })
+    }
+
+    /// The first links left out, in the order they were found
+    pub fn skipped(&self) -> &[SamplingDecision] {
+        &self.skipped
+    }
+
+    pub fn report(&self) -> SamplingReport {
+        SamplingReport {
+            seed: self.seed,
+            domains: self
+                .domains
+                .iter()
+                .map(|(domain, sample)| (domain.clone(), *sample))
+                .collect(),
+            skipped: self.skipped.clone(),
+        }
+    }
+
+    /// Write the report to `path` as JSON
+    pub fn export_report(&self, path: &Path) -> Result<()> {
+        self.report().export(path)
+    }
+}
+
+/// Draw of a URL in `[0, 100)`, fixed for a given seed
+fn draw(seed: u64, url: &Url) -> f64 {
+    let digest = Sha256::new()
+        .chain_update(seed.to_le_bytes())
+        .chain_update(url.as_str().as_bytes())
+        .finalize();
+    let value = u64::from_le_bytes(digest[..8].try_into().expect("digest has 8 bytes"));
+    (value >> 11) as f64 / (1u64 << 53) as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_are_reproducible_and_per_domain() {
+        let config = SamplingConfig {
+            percent: 25.0,
+            seed: Some(7),
+            domain_percent: [("Docs.Example".to_string(), 100.0)].into(),
+        };
+        let urls: Vec<Url> = (0..400)
+            .map(|i| Url::parse(&format!("https://www.example.com/page/{}", i)).unwrap())
+            .collect();
+
+        let mut sampler = FrontierSampler::new(config.clone());
+        let kept: Vec<bool> = urls.iter().map(|url| sampler.admit(url).is_ok()).collect();
+        let kept_count = kept.iter().filter(|kept| **kept).count();
+        assert!((60..140).contains(&kept_count), "kept {}", kept_count);
+
+        // The same seed keeps the same URLs, whatever order they arrive in
+        let mut repeat = FrontierSampler::new(config.clone());
+        for (url, kept) in urls.iter().zip(&kept).rev() {
+            assert_eq!(repeat.admit(url).is_ok(), *kept);
+        }
+
+        let report = sampler.report();
+        assert_eq!(report.seed, 7);
+        let sample = report.domains["example.com"];
+        assert_eq!(sample.kept, kept_count);
+        assert_eq!(sample.skipped, 400 - kept_count);
+        assert_eq!(sample.estimated_links(), kept_count * 4);
+        assert_eq!(report.skipped.len(), 400 - kept_count);
+        assert!(report.skipped.iter().all(|decision| decision.draw >= 25.0));
+
+        let docs = Url::parse("https://api.docs.example/reference").unwrap();
+        assert!(sampler.admit(&docs).is_ok());
+        let skipped = urls
+            .iter()
+            .find(|url| FrontierSampler::new(config.clone()).admit(url).is_err())
+            .unwrap();
+        match sampler.admit(skipped) {
+            Err(SkipReason::SampledOut { domain, percent }) => {
+                assert_eq!(domain, "example.com");
+                assert_eq!(percent, 25.0);
+            }
+            other => panic!("expected a sampled-out link, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+/// Consent walls and other interstitial pages
+///
+/// Cookie consent overlays (OneTrust, Cookiebot, ...) and "please enable JavaScript"
+/// shells answer with 200 and a few hundred words of boilerplate, so they would pass
+/// as content. A page counts as an interstitial when its HTML carries one of the
+/// configured markers and its text stays within `InterstitialConfig::max_words`.
+use encoding_rs::Encoding;
+
+use crate::config::InterstitialConfig;
+use crate::processing::charset::decode_chunks;
+
+/// Kind of interstitial page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterstitialKind {
+    /// Cookie consent wall, which consent cookies may get past
+    ConsentWall,
+    /// Page that shows its content only with JavaScript
+    JavaScriptRequired,
+}
+
+/// An interstitial marker found in a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interstitial {
+    pub kind: InterstitialKind,
+    pub marker: String,
+}
+
+impl std::fmt::Display for Interstitial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            InterstitialKind::ConsentWall => write!(f, "consent wall ('{}')", self.marker),
+            InterstitialKind::JavaScriptRequired => {
+                write!(f, "JavaScript-required page ('{}')", self.marker)
+            }
+        }
+    }
+}
+
+/// The first interstitial marker in the body, consent markers first
+///
+/// Real articles can embed a consent SDK too, so callers still compare the word
+/// count against `max_words` before treating the page as an interstitial.
+pub fn find_interstitial_marker<B: AsRef<[u8]>>(
+    config: &InterstitialConfig,
+    chunks: &[B],
+    charset: &'static Encoding,
+) -> Option<Interstitial> {
+    let body = decode_chunks(chunks, charset).to_lowercase();
+
+    let find = |markers: &[String], kind| {
+        markers
+            .iter()
+            .find(|marker| !marker.is_empty() && body.contains(&marker.to_lowercase()))
+            .map(|marker| Interstitial {
+                kind,
+                marker: marker.clone(),
+            })
+    };
+    find(&config.consent_markers, InterstitialKind::ConsentWall).or_else(|| {
+        find(
+            &config.javascript_markers,
+            InterstitialKind::JavaScriptRequired,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::UTF_8;
+
+    #[test]
+    fn test_markers_are_found_across_chunks() {
+        let config = InterstitialConfig::default();
+
+        let consent = [
+            "<html><body><div id=\"onetrust-ban",
+            "ner-sdk\">We value your privacy</div></body></html>",
+        ];
+        let found = find_interstitial_marker(&config, &consent, UTF_8).unwrap();
+        assert_eq!(found.kind, InterstitialKind::ConsentWall);
+        assert_eq!(found.to_string(), "consent wall ('onetrust-banner-sdk')");
+
+        let shell = ["<noscript>Please enable JavaScript to view this page</noscript>"];
+        assert_eq!(
+            find_interstitial_marker(&config, &shell, UTF_8).map(|found| found.kind),
+            Some(InterstitialKind::JavaScriptRequired)
+        );
+
+        let article = ["<html><body><p>An article about cookies and consent</p></body></html>"];
+        assert_eq!(find_interstitial_marker(&config, &article, UTF_8), None);
+    }
+}
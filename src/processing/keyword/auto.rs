@@ -0,0 +1,286 @@
+/// Automatic keyword extraction
+///
+/// Unlike `KeywordExtractor`, which looks for configured keywords, this picks the
+/// top keywords of a page by itself so a corpus can be tagged by topic:
+///
+/// - RAKE splits the text into candidate phrases at punctuation and at the
+///   stopwords of the page's language (see `cleaning::stopwords`), and scores each
+///   word by degree/frequency of its co-occurrences within the page.
+/// - TF-IDF scores single terms by their frequency in the page against the number
+///   of pages of the session containing them. In a pipeline it reads the
+///   segmented keyword text of the clean stage, when there is one. Document frequencies accumulate as
+///   pages are processed, so the first pages of a session are scored against a
+///   small corpus and mostly favour their own frequent terms.
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::config::{AutoKeywordConfig, AutoKeywordMethod};
+use crate::core::LangType;
+use crate::processing::cleaning::stopwords;
+use crate::processing::pipeline::{
+    ContentDocument, ContentStage, KEYWORD_TEXT_ANNOTATION, StageOutcome,
+};
+
+/// Annotation the pipeline stage stores its keywords under
+pub const AUTO_KEYWORDS_ANNOTATION: &str = "keywords";
+
+/// Shortest word considered a keyword
+const MIN_WORD_CHARS: usize = 3;
+
+/// A keyword or keyphrase picked from a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedKeyword {
+    /// Lowercased keyword or keyphrase
+    pub term: String,
+    /// Score under the configured method; only comparable within one page
+    pub score: f64,
+}
+
+/// Document frequencies of the pages processed so far
+#[derive(Debug, Default)]
+struct Corpus {
+    documents: usize,
+    document_frequency: HashMap<String, usize>,
+}
+
+/// Picks the top keywords of page texts
+#[derive(Debug)]
+pub struct AutoKeywordExtractor {
+    config: AutoKeywordConfig,
+    corpus: Mutex<Corpus>,
+}
+
+impl AutoKeywordExtractor {
+    pub fn new(config: AutoKeywordConfig) -> Self {
+        Self {
+            config,
+            corpus: Mutex::new(Corpus::default()),
+        }
+    }
+
+    /// Top keywords of English `text`, best first; TF-IDF also adds the page to the corpus
+    pub fn extract(&self, text: &str) -> Vec<ExtractedKeyword> {
+        self.extract_in(text, None)
+    }
+
+    /// Top keywords of `text`, skipping the stopwords of its language (English if unknown)
+    pub fn extract_in(&self, text: &str, language: Option<&LangType>) -> Vec<ExtractedKeyword> {
+        let stopwords: HashSet<&str> = stopwords(language.unwrap_or(&LangType::Eng)).collect();
+        let mut keywords = match self.config.method {
+            AutoKeywordMethod::Rake => self.rake(text, &stopwords),
+            AutoKeywordMethod::TfIdf => self.tf_idf(text, &stopwords),
+        };
+        keywords.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        keywords.truncate(self.config.top_n);
+        keywords
+    }
+
+    /// Number of pages the TF-IDF corpus has seen
+    pub fn corpus_size(&self) -> usize {
+        self.corpus
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .documents
+    }
+
+    fn rake(&self, text: &str, stopwords: &HashSet<&str>) -> Vec<ExtractedKeyword> {
+        // Candidate phrases are runs of content words within a sentence fragment
+        let mut phrases: Vec<Vec<String>> = Vec::new();
+        for fragment in text.split(|c: char| ".,;:!?()[]{}\"\n\t|".contains(c)) {
+            let mut phrase = Vec::new();
+            for word in words(fragment) {
+                if is_content_word(&word, stopwords) {
+                    phrase.push(word);
+                } else if !phrase.is_empty() {
+                    phrases.push(std::mem::take(&mut phrase));
+                }
+            }
+            if !phrase.is_empty() {
+                phrases.push(phrase);
+            }
+        }
+        phrases.retain(|phrase| phrase.len() <= self.config.max_phrase_words.max(1));
+
+        let mut frequency: HashMap<&str, f64> = HashMap::new();
+        let mut degree: HashMap<&str, f64> = HashMap::new();
+        for phrase in &phrases {
+            for word in phrase {
+                *frequency.entry(word).or_default() += 1.0;
+                *degree.entry(word).or_default() += phrase.len() as f64;
+            }
+        }
+
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for phrase in &phrases {
+            let score = phrase
+                .iter()
+                .map(|word| degree[word.as_str()] / frequency[word.as_str()])
+                .sum();
+            scores.insert(phrase.join(" "), score);
+        }
+        scores
+            .into_iter()
+            .map(|(term, score)| ExtractedKeyword { term, score })
+            .collect()
+    }
+
+    fn tf_idf(&self, text: &str, stopwords: &HashSet<&str>) -> Vec<ExtractedKeyword> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for word in words(text).filter(|word| is_content_word(word, stopwords)) {
+            *counts.entry(word).or_default() += 1;
+        }
+        let total: usize = counts.values().sum();
+        if total == 0 {
+            return Vec::new();
+        }
+
+        let mut corpus = self.corpus.lock().unwrap_or_else(|e| e.into_inner());
+        corpus.documents += 1;
+        for term in counts.keys() {
+            *corpus.document_frequency.entry(term.clone()).or_default() += 1;
+        }
+        let documents = corpus.documents as f64;
+        counts
+            .into_iter()
+            .map(|(term, count)| {
+                let frequency = corpus.document_frequency[&term] as f64;
+                let idf = ((1.0 + documents) / (1.0 + frequency)).ln() + 1.0;
+                ExtractedKeyword {
+                    score: count as f64 / total as f64 * idf,
+                    term,
+                }
+            })
+            .collect()
+    }
+}
+
+fn is_content_word(word: &str, stopwords: &HashSet<&str>) -> bool {
+    word.chars().count() >= MIN_WORD_CHARS
+        && !stopwords.contains(word)
+        && !word.chars().all(|c| c.is_numeric())
+}
+
+/// Lowercased words of `text`, keeping inner apostrophes and hyphens
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '-'))
+        .map(|word| word.trim_matches(|c| c == '\'' || c == '-').to_lowercase())
+        .filter(|word| !word.is_empty())
+}
+
+/// Pipeline stage storing the page's top keywords in the `keywords` annotation
+pub struct AutoKeywordStage {
+    extractor: AutoKeywordExtractor,
+}
+
+impl AutoKeywordStage {
+    pub fn new(config: AutoKeywordConfig) -> Self {
+        Self {
+            extractor: AutoKeywordExtractor::new(config),
+        }
+    }
+}
+
+impl ContentStage for AutoKeywordStage {
+    fn name(&self) -> &str {
+        AUTO_KEYWORDS_ANNOTATION
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        // RAKE needs the stopwords as phrase delimiters; TF-IDF counts the words of
+        // the segmented, stopword-free keyword text when the clean stage left one
+        let text = match (
+            &self.extractor.config.method,
+            document.annotations.get(KEYWORD_TEXT_ANNOTATION),
+        ) {
+            (AutoKeywordMethod::TfIdf, Some(Value::String(keyword_text))) => keyword_text,
+            _ => &document.text,
+        };
+        let keywords = self.extractor.extract_in(text, document.language.as_ref());
+        document.annotations.insert(
+            AUTO_KEYWORDS_ANNOTATION.to_string(),
+            serde_json::to_value(keywords)?,
+        );
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Keywords left by an `AutoKeywordStage`, `None` if the document did not pass one
+pub fn auto_keywords(document: &ContentDocument) -> Option<Vec<ExtractedKeyword>> {
+    document
+        .annotations
+        .get(AUTO_KEYWORDS_ANNOTATION)
+        .cloned()
+        .and_then(|keywords| serde_json::from_value(keywords).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(keywords: &[ExtractedKeyword]) -> Vec<&str> {
+        keywords
+            .iter()
+            .map(|keyword| keyword.term.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_rake_and_tf_idf_pick_page_topics() {
+        let rake = AutoKeywordExtractor::new(AutoKeywordConfig {
+            top_n: 2,
+            ..AutoKeywordConfig::default()
+        });
+        let text = "Rust web crawler is built on async Rust. A web crawler should respect \
+                    the rate limits of each site.";
+        let keywords = rake.extract(text);
+        assert_eq!(terms(&keywords), vec!["rust web crawler", "web crawler"]);
+        assert!(keywords[0].score > keywords[1].score);
+
+        let tf_idf = AutoKeywordExtractor::new(AutoKeywordConfig {
+            method: AutoKeywordMethod::TfIdf,
+            top_n: 1,
+            ..AutoKeywordConfig::default()
+        });
+        tf_idf.extract("Crawler scheduling: the crawler queue orders pages.");
+        tf_idf.extract("Crawler storage writes pages to disk.");
+        // "crawler" is frequent on the page but appears on every page of the session
+        let keywords = tf_idf.extract("Crawler crawler politeness politeness delays.");
+        assert_eq!(terms(&keywords), vec!["politeness"]);
+        assert_eq!(tf_idf.corpus_size(), 3);
+        assert!(tf_idf.extract("the of 2024").is_empty());
+        assert_eq!(tf_idf.corpus_size(), 3);
+    }
+
+    #[test]
+    fn test_tf_idf_stage_reads_the_segmented_keyword_text() {
+        use crate::processing::language::CleaningConfig;
+        use crate::processing::pipeline::CleanStage;
+
+        let mut document =
+            ContentDocument::new(url::Url::parse("https://example.jp/").unwrap(), Vec::new());
+        document.text = "東京タワーの展望台からの眺め".to_string();
+        document.language = Some(LangType::Jpn);
+        CleanStage::new(CleaningConfig::basic().with_keyword_stopwords(LangType::Jpn))
+            .unwrap()
+            .process(&mut document)
+            .unwrap();
+        AutoKeywordStage::new(AutoKeywordConfig {
+            method: AutoKeywordMethod::TfIdf,
+            ..AutoKeywordConfig::default()
+        })
+        .process(&mut document)
+        .unwrap();
+
+        let keywords = auto_keywords(&document).unwrap();
+        let mut terms = terms(&keywords);
+        terms.sort();
+        assert_eq!(terms, vec!["展望台", "東京タワー"]);
+    }
+}
@@ -2,11 +2,17 @@
 ///
 /// This module provides functionality to crawl and filter content based on target keywords.
 /// When enabled, only content containing the specified keywords will be returned.
+/// `auto` instead picks the top keywords of each page without a configured list.
+pub mod auto;
 pub mod config;
 pub mod extractor;
 pub mod matcher;
 
 // Re-export all keyword processing components
+pub use auto::{
+    AUTO_KEYWORDS_ANNOTATION, AutoKeywordExtractor, AutoKeywordStage, ExtractedKeyword,
+    auto_keywords,
+};
 pub use config::{KeywordConfig, KeywordMode, KeywordOptions};
 pub use extractor::{KeywordExtractor, KeywordMatchInfo};
 pub use matcher::{KeywordMatcher, MatchResult, MatchStats};
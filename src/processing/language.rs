@@ -1,15 +1,16 @@
-use crate::core::LangType;
+use crate::core::{LangType, SkipReason};
 /// Language detection and analysis
 ///
 /// This module provides comprehensive language detection and analysis capabilities,
 /// integrating with the core LangType system and providing utility functions.
 /// Enhanced with advanced text cleaning and preprocessing (Feature 3).
+use serde::{Deserialize, Serialize};
 use whatlang::{Lang, detect};
 
 // Re-export text cleaning components (Level 3 extension)
 pub use crate::processing::cleaning::{
     CharacterFilter, CleaningConfig, CleaningEngine, CleaningResult, CleaningRule, CleaningStats,
-    LanguageFilter, LengthFilter, RuleType, TextCleaner, WordFilter,
+    LanguageFilter, LengthFilter, RuleType, StopwordFilter, TextCleaner, WordFilter,
 };
 
 /// Detect language from content using whatlang crate
@@ -44,6 +45,127 @@ pub fn detect_language_type(content: &str) -> Option<LangType> {
     }
 }
 
+/// Detect the language of the first `probe_bytes` of `text` and check it against `accepted`
+///
+/// Returns the skip reason only for a reliable detection outside the list, so a
+/// short or mixed-language opening never rejects a page that the full text would
+/// accept. A declared language weighs in as in `resolve_language`: an accepted
+/// declaration that outweighs the detection keeps the page. An empty list or a
+/// zero `probe_bytes` never skips.
+pub fn probe_language(
+    text: &str,
+    accepted: &[LangType],
+    probe_bytes: usize,
+    declared: Option<&str>,
+    declared_weight: f64,
+) -> Option<SkipReason> {
+    if accepted.is_empty() || probe_bytes == 0 {
+        return None;
+    }
+    let probe = &text[..text.floor_char_boundary(probe_bytes)];
+    let probe = probe.split_whitespace().collect::<Vec<_>>().join(" ");
+    let info = detect(&probe).filter(|info| info.is_reliable())?;
+    let language = weigh_languages(
+        declared.and_then(LangType::from_language_tag),
+        LangType::from_detected_lang(info.lang()),
+        Some(info.confidence()),
+        declared_weight,
+    );
+    let accepted_language = language.is_some_and(|language| accepted.contains(&language));
+    (!accepted_language).then(|| SkipReason::LanguageNotAccepted {
+        language: info.lang().code().to_string(),
+        confidence: info.confidence(),
+    })
+}
+
+/// Language a page declares and the language detected from its text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageLanguage {
+    /// Tag from `<html lang>`, else the first `Content-Language` tag
+    pub declared: Option<String>,
+    /// Statistical detection of the text alone
+    pub detected: Option<LangType>,
+    /// Confidence of the statistical detection, `0.0..=1.0`
+    pub detection_confidence: f64,
+    /// Declared and detected language weighed against each other
+    pub language: Option<LangType>,
+}
+
+/// The language tag a page declares
+///
+/// `<html lang>` describes the document itself and wins over the response's
+/// `Content-Language` header, of which only the first tag is used.
+pub fn declared_language(
+    html_lang: Option<&str>,
+    content_language: Option<&str>,
+) -> Option<String> {
+    html_lang
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .or_else(|| {
+            content_language
+                .and_then(|header| header.split(',').next())
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+        })
+        .map(str::to_string)
+}
+
+/// Detect the language of `text`, using the declared language as a prior
+///
+/// The declared language scores `declared_weight` and the statistical detection
+/// `(1 - declared_weight) * confidence`; the higher score wins. Short texts, which
+/// whatlang detects with low confidence, therefore follow the declaration, while a
+/// confident detection overrides a wrong `lang` attribute. A weight of 0 ignores the
+/// declaration unless nothing could be detected.
+pub fn resolve_language(
+    text: &str,
+    declared: Option<String>,
+    declared_weight: f64,
+) -> PageLanguage {
+    let detection = detect(text);
+    let detected = detection
+        .as_ref()
+        .and_then(|info| LangType::from_detected_lang(info.lang()));
+    let confidence = detection.as_ref().map(|info| info.confidence());
+    let language = weigh_languages(
+        declared.as_deref().and_then(LangType::from_language_tag),
+        detected.clone(),
+        confidence,
+        declared_weight,
+    );
+
+    PageLanguage {
+        declared,
+        detected,
+        detection_confidence: confidence.unwrap_or(0.0),
+        language,
+    }
+}
+
+/// The declared language against a detection with `confidence` (`None` when
+/// nothing was detected), as `resolve_language` weighs them
+fn weigh_languages(
+    declared: Option<LangType>,
+    detected: Option<LangType>,
+    confidence: Option<f64>,
+    declared_weight: f64,
+) -> Option<LangType> {
+    let weight = declared_weight.clamp(0.0, 1.0);
+    match (declared, confidence) {
+        (None, _) => detected,
+        (Some(declared), None) => Some(declared),
+        (Some(declared), Some(confidence)) => {
+            if detected.as_ref() == Some(&declared) || weight >= (1.0 - weight) * confidence {
+                Some(declared)
+            } else {
+                // May be `None`: a confident detection of an unsupported language
+                detected
+            }
+        }
+    }
+}
+
 /// Convert language code to display name
 pub fn language_code_to_name(code: &str) -> &'static str {
     match code {
@@ -138,3 +260,35 @@ pub fn analyze_language_stats(content: &str) -> LanguageStats {
         word_count,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_language_outweighs_unreliable_detection() {
+        assert_eq!(
+            declared_language(Some(" fr-CA "), Some("en, de")),
+            Some("fr-CA".to_string())
+        );
+        assert_eq!(
+            declared_language(Some(""), Some("de-DE, en")),
+            Some("de-DE".to_string())
+        );
+
+        let long_english = "The quick brown fox jumps over the lazy dog while the \
+                            farmer watches from the porch and drinks his morning coffee";
+        let confident = resolve_language(long_english, Some("de".to_string()), 0.3);
+        assert_eq!(confident.detected, Some(LangType::Eng));
+        assert_eq!(confident.language, Some(LangType::Eng));
+        assert_eq!(confident.declared.as_deref(), Some("de"));
+
+        // Declared language wins when detection fails or is given no weight over it
+        let empty = resolve_language("", Some("ja".to_string()), 0.3);
+        assert_eq!(empty.language, Some(LangType::Jpn));
+        let declared_only = resolve_language(long_english, Some("de".to_string()), 1.0);
+        assert_eq!(declared_only.language, Some(LangType::Deu));
+        let detection_only = resolve_language(long_english, Some("de".to_string()), 0.0);
+        assert_eq!(detection_only.language, Some(LangType::Eng));
+    }
+}
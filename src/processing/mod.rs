@@ -12,9 +12,18 @@
 ///
 /// Building blocks are assembled here for unified content processing capabilities.
 // Core processing modules (each enhanced with Level 3 features)
+pub mod charset; // Character sets of HTML bodies
 pub mod content; // Basic content + keyword filtering (Feature 1)
 pub mod discovery; // Basic discovery + extensive crawling (Feature 2)
+pub mod interstitial; // Consent walls and JavaScript-required pages
 pub mod language; // Basic language + text cleaning (Feature 3)
+pub mod pii; // Masking of personal data before storage
+pub mod pipeline; // Ordered, user-extensible content stages
+pub mod reprocess; // Offline re-extraction of stored sessions
+pub mod sanitize; // Sanitized HTML for safe rendering
+pub mod semantic; // Embedding-based relevance scoring
+pub mod short_content; // Diagnostics for pages dropped as too short
+pub mod structured; // Tables and lists as structured data
 
 // Level 3 feature modules (internal organization only)
 mod cleaning; // Feature 3: Text cleaning
@@ -22,8 +31,13 @@ mod extensive; // Feature 2: Extensive crawling
 mod keyword; // Feature 1: Keyword-based filtering
 
 // Re-export main processing components (unified interface)
+pub use charset::{ChunkDecoder, decode_chunks, detect_charset};
 pub use content::{
+    AUTO_KEYWORDS_ANNOTATION,
+    AutoKeywordExtractor,
+    AutoKeywordStage,
     ContentExtractor,
+    ExtractedKeyword,
     // Enhanced Feature 1: Keyword-based content filtering
     KeywordConfig,
     KeywordExtractor,
@@ -33,35 +47,60 @@ pub use content::{
     KeywordOptions,
     MatchResult,
     MatchStats,
+    WordCounting,
+    auto_keywords,
     extract_links_from_html,
     extract_title_from_html,
+    html_matches_selector,
 };
 pub use discovery::{
     CategoryPriorityAdjustments,
+    CategoryRule,
     // Enhanced Feature 2: Extensive crawling with auto-queue expansion
     CrawlDepth,
     DepthPriorityAdjustments,
     DiscoveryStats,
+    DomainSample,
     DomainScope,
     ExtensiveConfig,
     ExtensiveQueueManager,
     ExtractedLink,
+    FrontierSample,
+    FrontierSampler,
+    HreflangAlternate,
+    LinkCategorization,
+    LinkCategorizer,
     LinkCategory,
     LinkExtractor,
     LinkFilter,
+    LinkImportance,
     LinkProcessor,
+    LinkRegion,
     LinkStats,
     LinkType,
+    MetaRefresh,
+    PageLinkMetadata,
+    ParamBudgetTracker,
     PriorityConfig,
     PriorityThresholds,
     ProcessedLink,
+    QueryParamBudget,
     QueueStatus,
+    SAMPLING_REPORT_FILE,
+    SamplingConfig,
+    SamplingDecision,
+    SamplingReport,
+    document_base_url,
+    extract_link_metadata,
     is_asset_url,
     is_document_url,
     is_same_domain,
     is_valid_crawl_url,
     normalize_url,
+    parse_meta_refresh,
+    session_sampling_path,
 };
+pub use interstitial::{Interstitial, InterstitialKind, find_interstitial_marker};
 pub use language::{
     // Enhanced Feature 3: Advanced text cleaning and preprocessing
     CharacterFilter,
@@ -73,13 +112,34 @@ pub use language::{
     ContentDifficulty,
     LanguageFilter,
     LengthFilter,
+    PageLanguage,
     RuleType,
     TextCleaner,
     WordFilter,
     analyze_language_stats,
+    declared_language,
     detect_language,
     detect_language_type,
     estimate_content_difficulty,
     estimate_reading_time,
     get_language_confidence,
+    probe_language,
+    resolve_language,
 };
+pub use pii::{PII_ANNOTATION, PiiScrubber, PiiStage, pii_counts};
+pub use pipeline::{
+    CleanStage, ContentDocument, ContentPipeline, ContentStage, ExtractStage,
+    KEYWORD_TEXT_ANNOTATION, KeywordStage, LanguageStage, PipelineOutcome, QualityStage,
+    StageOutcome,
+};
+pub use reprocess::{ReprocessReport, reprocess_session};
+pub use sanitize::{HtmlSanitizer, SanitizeConfig};
+pub use semantic::{
+    EmbeddingRelevanceModel, HashedBagOfWordsEmbedder, RelevanceModel, RelevanceStage,
+    TextEmbedder, boost_link_priorities,
+};
+pub use short_content::{
+    ContentCategory, SHORT_PAGES_FILE, ShortContentDiagnosis, ShortPagesReport,
+    session_short_pages_path,
+};
+pub use structured::{ExtractedList, ExtractedTable, StructuredContent, extract_structured};
@@ -0,0 +1,255 @@
+/// Masking of personal data in page text
+///
+/// Emails, phone numbers and national-ID-like numbers are replaced with a marker
+/// naming the entity (`[EMAIL]`, `[PHONE]`, `[US_SSN]`, ...) before results are
+/// stored, and the number of masked entities of each kind is kept with the result.
+/// National ID formats come in per-locale sets; configs can add their own patterns.
+///
+/// Detection is pattern based: it favours formats that rarely occur by accident
+/// (separated digit groups, fixed ID layouts) over catching every variant.
+use anyhow::{Error, anyhow};
+use encoding_rs::Encoding;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::PiiConfig;
+use crate::processing::charset::{decode_chunks, encode_text};
+use crate::processing::pipeline::{ContentDocument, ContentStage, StageOutcome};
+use crate::processing::structured::StructuredContent;
+
+/// Annotation the pipeline stage stores its counts under
+pub const PII_ANNOTATION: &str = "pii";
+
+const EMAIL: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+/// International numbers with a `+` prefix, and North American `(555) 123-4567` style
+const PHONE: &str = r"\+\d{1,3}(?:[\s.-]?\(?\d{1,4}\)?){2,5}|\(?\b\d{3}\)?[\s.-]\d{3}[\s.-]\d{4}\b";
+/// Digits a match needs to count as a phone number
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 8..=15;
+
+/// National ID patterns of a locale
+fn locale_patterns(locale: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    Some(match locale.to_ascii_lowercase().as_str() {
+        "us" => &[("us_ssn", r"\b\d{3}-\d{2}-\d{4}\b")],
+        "uk" => &[(
+            "uk_nino",
+            r"\b[A-CEGHJ-PR-TW-Z]{2}\s?\d{2}\s?\d{2}\s?\d{2}\s?[A-D]\b",
+        )],
+        "in" => &[
+            ("in_aadhaar", r"\b\d{4}\s\d{4}\s\d{4}\b"),
+            ("in_pan", r"\b[A-Z]{5}\d{4}[A-Z]\b"),
+        ],
+        "sg" => &[("sg_nric", r"\b[STFGM]\d{7}[A-Z]\b")],
+        _ => return None,
+    })
+}
+
+/// Compiled PII patterns
+#[derive(Debug, Clone)]
+pub struct PiiScrubber {
+    /// Entity name and pattern, applied in order
+    patterns: Vec<(String, Regex)>,
+}
+
+impl PiiScrubber {
+    pub fn new(config: &PiiConfig) -> Result<Self, Error> {
+        // Emails go first, since their local parts may look like numbers
+        let mut patterns = vec![("email".to_string(), Regex::new(EMAIL)?)];
+        for locale in &config.locales {
+            let ids =
+                locale_patterns(locale).ok_or_else(|| anyhow!("Unknown PII locale: {}", locale))?;
+            for (name, pattern) in ids {
+                patterns.push((name.to_string(), Regex::new(pattern)?));
+            }
+        }
+        for (name, pattern) in &config.custom_patterns {
+            let regex = Regex::new(pattern)
+                .map_err(|e| anyhow!("Invalid PII pattern '{}': {}", name, e))?;
+            patterns.push((name.clone(), regex));
+        }
+        patterns.push(("phone".to_string(), Regex::new(PHONE)?));
+        Ok(Self { patterns })
+    }
+
+    /// `text` with every match masked, and the number of matches per entity
+    pub fn scrub(&self, text: &str) -> (String, BTreeMap<String, usize>) {
+        let mut text = text.to_string();
+        let mut counts = BTreeMap::new();
+        for (name, regex) in &self.patterns {
+            let mask = format!("[{}]", name.to_ascii_uppercase());
+            let mut found = 0;
+            let scrubbed = regex.replace_all(&text, |captures: &regex::Captures| {
+                let matched = &captures[0];
+                if name == "phone"
+                    && !PHONE_DIGITS.contains(&matched.chars().filter(char::is_ascii_digit).count())
+                {
+                    return matched.to_string();
+                }
+                found += 1;
+                mask.clone()
+            });
+            if found > 0 {
+                text = scrubbed.into_owned();
+                counts.insert(name.clone(), found);
+            }
+        }
+        (text, counts)
+    }
+
+    /// `text` with every match masked
+    pub fn mask(&self, text: &str) -> String {
+        self.scrub(text).0
+    }
+
+    /// Mask the captions, cells and items of extracted tables and lists
+    pub fn mask_structured(&self, structured: &mut StructuredContent) {
+        for table in &mut structured.tables {
+            if let Some(caption) = &mut table.caption {
+                *caption = self.mask(caption);
+            }
+            for cell in table
+                .headers
+                .iter_mut()
+                .chain(table.rows.iter_mut().flatten())
+            {
+                *cell = self.mask(cell);
+            }
+        }
+        for item in structured
+            .lists
+            .iter_mut()
+            .flat_map(|list| list.items.iter_mut())
+        {
+            *item = self.mask(item);
+        }
+    }
+
+    /// A raw HTML body with every match in its source masked
+    ///
+    /// Matches are looked for in the markup as well as the text, so addresses in
+    /// `mailto:` links and attributes are masked too. The body is decoded from
+    /// `charset` and encoded back, so it stays in the charset it declares.
+    pub fn mask_html<B: AsRef<[u8]>>(&self, chunks: &[B], charset: &'static Encoding) -> Vec<u8> {
+        encode_text(&self.mask(&decode_chunks(chunks, charset)), charset)
+    }
+}
+
+/// Pipeline stage masking PII in the document text
+///
+/// The counts are stored in the `pii` annotation as an object of entity counts.
+pub struct PiiStage {
+    scrubber: PiiScrubber,
+}
+
+impl PiiStage {
+    pub fn new(scrubber: PiiScrubber) -> Self {
+        Self { scrubber }
+    }
+}
+
+impl ContentStage for PiiStage {
+    fn name(&self) -> &str {
+        PII_ANNOTATION
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let (text, counts) = self.scrubber.scrub(&document.text);
+        if !counts.is_empty() {
+            document.set_text(text);
+        }
+        document
+            .annotations
+            .insert(PII_ANNOTATION.to_string(), serde_json::to_value(counts)?);
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Counts left by a `PiiStage`, `None` if the document did not pass one
+pub fn pii_counts(document: &ContentDocument) -> Option<BTreeMap<String, usize>> {
+    document
+        .annotations
+        .get(PII_ANNOTATION)
+        .cloned()
+        .and_then(|counts: Value| serde_json::from_value(counts).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entities_are_masked_and_counted() {
+        let config = PiiConfig {
+            locales: vec!["us".to_string(), "uk".to_string()],
+            custom_patterns: [("employee_id".to_string(), r"EMP-\d{6}".to_string())].into(),
+        };
+        let scrubber = PiiScrubber::new(&config).unwrap();
+        let text = "Mail jane.doe+news@example.co.uk or call +44 20 7946 0958 / (555) 123-4567. \
+                    SSN 123-45-6789, NI AB 12 34 56 C, badge EMP-004211. \
+                    Published 2024-01-15, order 12345, v1.2.3.";
+        let (scrubbed, counts) = scrubber.scrub(text);
+        assert_eq!(
+            scrubbed,
+            "Mail [EMAIL] or call [PHONE] / [PHONE]. \
+             SSN [US_SSN], NI [UK_NINO], badge [EMPLOYEE_ID]. \
+             Published 2024-01-15, order 12345, v1.2.3."
+        );
+        let expected: BTreeMap<String, usize> = [
+            ("email", 1),
+            ("employee_id", 1),
+            ("phone", 2),
+            ("uk_nino", 1),
+            ("us_ssn", 1),
+        ]
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+        assert_eq!(counts, expected);
+
+        let unknown = PiiConfig {
+            locales: vec!["xx".to_string()],
+            ..PiiConfig::default()
+        };
+        assert!(PiiScrubber::new(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_structured_content_and_html_are_masked() {
+        let scrubber = PiiScrubber::new(&PiiConfig::default()).unwrap();
+        let mut structured = StructuredContent {
+            tables: vec![crate::processing::structured::ExtractedTable {
+                caption: Some("Contacts of jane@example.com".to_string()),
+                headers: vec!["Name".to_string(), "Email".to_string()],
+                rows: vec![vec!["Jane".to_string(), "jane@example.com".to_string()]],
+            }],
+            lists: vec![crate::processing::structured::ExtractedList {
+                ordered: false,
+                items: vec!["Call (555) 123-4567".to_string()],
+            }],
+        };
+        scrubber.mask_structured(&mut structured);
+        assert_eq!(
+            structured.tables[0].caption.as_deref(),
+            Some("Contacts of [EMAIL]")
+        );
+        assert_eq!(structured.tables[0].rows[0][1], "[EMAIL]");
+        assert_eq!(structured.lists[0].items[0], "Call [PHONE]");
+
+        let html = scrubber.mask_html(
+            &[
+                &b"<a href=\"mailto:jane@exam"[..],
+                &b"ple.com\">Jane</a>"[..],
+            ],
+            encoding_rs::UTF_8,
+        );
+        assert_eq!(html, b"<a href=\"mailto:[EMAIL]\">Jane</a>");
+        let korean = encoding_rs::EUC_KR
+            .encode("<p>연락처 jane@example.com</p>")
+            .0;
+        let html = scrubber.mask_html(&[&korean], encoding_rs::EUC_KR);
+        assert_eq!(
+            html,
+            &*encoding_rs::EUC_KR.encode("<p>연락처 [EMAIL]</p>").0
+        );
+    }
+}
@@ -0,0 +1,577 @@
+/// Multi-stage content processing pipeline
+///
+/// Content flows through an ordered list of `ContentStage` trait objects
+/// (extract → clean → language detect → keyword filter → quality score by default).
+/// Users can register their own stages (NER, embeddings, ...) anywhere in the chain
+/// without patching the built-in processors.
+use anyhow::Error;
+use bytes::Bytes;
+use encoding_rs::{Encoding, UTF_8};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
+use url::Url;
+
+use crate::config::{WebCrawlerConfig, defaults};
+use crate::core::{LangType, SkipReason};
+use crate::processing::content::{
+    AutoKeywordStage, ContentExtractor, KeywordConfig, KeywordMatcher, KeywordMode, MatchResult,
+    WordCounting,
+};
+use crate::processing::language::{CleaningConfig, TextCleaner, resolve_language};
+use crate::processing::pii::{PiiScrubber, PiiStage};
+
+/// Word count at which the default quality stage stops rewarding length
+const DEFAULT_QUALITY_TARGET_WORDS: usize = 300;
+
+/// Annotation `CleanStage` stores the stopword-free keyword text under
+pub const KEYWORD_TEXT_ANNOTATION: &str = "keyword_text";
+
+/// Document passed between pipeline stages
+#[derive(Debug, Clone)]
+pub struct ContentDocument {
+    /// URL the content was fetched from
+    pub url: Url,
+    /// Raw response body as received from the network
+    pub raw_body: Vec<Bytes>,
+    /// Character set `raw_body` is decoded from
+    pub charset: &'static Encoding,
+    /// Extracted text, filled in by the extraction stage
+    pub text: String,
+    /// Word count of `text`
+    pub word_count: usize,
+    /// Detected language
+    pub language: Option<LangType>,
+    /// Language tag the page declares (`<html lang>` or `Content-Language`)
+    pub declared_language: Option<String>,
+    /// Keyword match result, if keyword filtering ran
+    pub keyword_matches: Option<MatchResult>,
+    /// Quality score in `0.0..=1.0`, if quality scoring ran
+    pub quality_score: Option<f32>,
+    /// Semantic relevance in `0.0..=1.0`, if a relevance model ran
+    pub relevance_score: Option<f32>,
+    /// Free-form output of custom stages, keyed by stage name
+    pub annotations: HashMap<String, Value>,
+}
+
+impl ContentDocument {
+    pub fn new(url: Url, raw_body: Vec<Bytes>) -> Self {
+        Self {
+            url,
+            raw_body,
+            charset: UTF_8,
+            text: String::new(),
+            word_count: 0,
+            language: None,
+            declared_language: None,
+            keyword_matches: None,
+            quality_score: None,
+            relevance_score: None,
+            annotations: HashMap::new(),
+        }
+    }
+
+    /// Decode the body from `charset` instead of UTF-8
+    pub fn with_charset(mut self, charset: &'static Encoding) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Weigh `declared` against detection in the language checks
+    pub fn with_declared_language(mut self, declared: Option<String>) -> Self {
+        self.declared_language = declared;
+        self
+    }
+
+    /// Replace the text and recount words
+    pub fn set_text(&mut self, text: String) {
+        self.word_count = WordCounting::for_language(self.language.as_ref()).count(&text);
+        self.text = text;
+    }
+}
+
+/// Decision returned by a stage
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageOutcome {
+    /// Pass the document on to the next stage
+    Continue,
+    /// Drop the document, with a reason for logging
+    Reject(String),
+    /// Drop the document for a reason the crawler reports as its own skip kind
+    Skip(SkipReason),
+}
+
+/// A single step of the content pipeline
+pub trait ContentStage: Send + Sync {
+    /// Unique stage name, used for ordering and annotations
+    fn name(&self) -> &str;
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error>;
+}
+
+/// Final result of running the pipeline
+#[derive(Debug, Clone)]
+pub enum PipelineOutcome {
+    Accepted(ContentDocument),
+    Rejected {
+        stage: String,
+        reason: String,
+        /// Typed reason when the stage skipped with `StageOutcome::Skip`
+        skip: Option<SkipReason>,
+        document: ContentDocument,
+    },
+}
+
+/// Ordered list of content stages
+#[derive(Default)]
+pub struct ContentPipeline {
+    stages: Vec<Box<dyn ContentStage>>,
+}
+
+impl std::fmt::Debug for ContentPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentPipeline")
+            .field("stages", &self.stage_names())
+            .finish()
+    }
+}
+
+impl ContentPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the standard extract → clean → language → PII → keyword → quality chain,
+    /// followed by automatic keywords for the pages that pass it
+    pub fn from_config(config: &WebCrawlerConfig) -> Result<Self, Error> {
+        let declared_weight = config
+            .language_priors
+            .as_ref()
+            .map_or(0.0, |priors| priors.declared_weight);
+        let extractor = ContentExtractor::new(
+            config.accepted_languages.clone(),
+            config.latin_word_filter.clone(),
+        )?
+        .with_language_probe(
+            config
+                .language_probe_bytes
+                .unwrap_or(defaults::LANGUAGE_PROBE_BYTES),
+        )
+        .with_declared_language_weight(declared_weight);
+
+        // A crawl limited to one language knows which stopwords its keywords skip
+        let mut cleaning = CleaningConfig::default();
+        if config.auto_keywords.is_some()
+            && let [language] = config.accepted_languages.as_slice()
+        {
+            cleaning = CleaningConfig {
+                enabled: true,
+                ..cleaning.with_keyword_stopwords(language.clone())
+            };
+        }
+
+        let mut pipeline = Self::new()
+            .with_stage(ExtractStage::new(extractor))
+            .with_stage(CleanStage::new(cleaning)?)
+            .with_stage(
+                LanguageStage::new(config.accepted_languages.clone())
+                    .with_declared_weight(declared_weight),
+            );
+
+        if let Some(pii) = &config.pii {
+            pipeline = pipeline.with_stage(PiiStage::new(PiiScrubber::new(pii)?));
+        }
+
+        if config.enable_keyword_filtering && !config.target_words.is_empty() {
+            let keyword_config = KeywordConfig::new(config.target_words.clone(), KeywordMode::Any);
+            pipeline = pipeline.with_stage(KeywordStage::new(keyword_config)?);
+        }
+
+        pipeline = pipeline.with_stage(QualityStage::new(0.0, DEFAULT_QUALITY_TARGET_WORDS));
+
+        if let Some(auto_keywords) = &config.auto_keywords {
+            pipeline = pipeline.with_stage(AutoKeywordStage::new(auto_keywords.clone()));
+        }
+
+        Ok(pipeline)
+    }
+
+    /// Append a stage to the end of the pipeline
+    pub fn with_stage(mut self, stage: impl ContentStage + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Insert a stage directly before the named stage
+    pub fn insert_before(
+        &mut self,
+        existing: &str,
+        stage: impl ContentStage + 'static,
+    ) -> Result<(), Error> {
+        let index = self.position(existing)?;
+        self.stages.insert(index, Box::new(stage));
+        Ok(())
+    }
+
+    /// Insert a stage directly after the named stage
+    pub fn insert_after(
+        &mut self,
+        existing: &str,
+        stage: impl ContentStage + 'static,
+    ) -> Result<(), Error> {
+        let index = self.position(existing)?;
+        self.stages.insert(index + 1, Box::new(stage));
+        Ok(())
+    }
+
+    /// Remove the named stage, returning whether it was present
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.stages.len();
+        self.stages.retain(|stage| stage.name() != name);
+        self.stages.len() != before
+    }
+
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Run every stage in order, stopping at the first rejection
+    pub fn run(&self, document: ContentDocument) -> Result<PipelineOutcome, Error> {
+        self.run_without(document, &[])
+    }
+
+    /// Run every stage except the named ones, e.g. a filter a live setting replaced
+    pub fn run_without(
+        &self,
+        mut document: ContentDocument,
+        skipped: &[&str],
+    ) -> Result<PipelineOutcome, Error> {
+        for stage in self
+            .stages
+            .iter()
+            .filter(|stage| !skipped.contains(&stage.name()))
+        {
+            match stage.process(&mut document)? {
+                StageOutcome::Continue => {}
+                StageOutcome::Reject(reason) => {
+                    return Ok(PipelineOutcome::Rejected {
+                        stage: stage.name().to_string(),
+                        reason,
+                        skip: None,
+                        document,
+                    });
+                }
+                StageOutcome::Skip(skip) => {
+                    return Ok(PipelineOutcome::Rejected {
+                        stage: stage.name().to_string(),
+                        reason: skip.to_string(),
+                        skip: Some(skip),
+                        document,
+                    });
+                }
+            }
+        }
+
+        Ok(PipelineOutcome::Accepted(document))
+    }
+
+    fn position(&self, name: &str) -> Result<usize, Error> {
+        self.stages
+            .iter()
+            .position(|stage| stage.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("Pipeline stage not found: {}", name))
+    }
+}
+
+// ============================================================================
+// Built-in stages
+// ============================================================================
+
+/// Extract text from the raw body (script/style removal, tag stripping, thresholds)
+pub struct ExtractStage {
+    extractor: ContentExtractor,
+}
+
+impl ExtractStage {
+    pub fn new(extractor: ContentExtractor) -> Self {
+        Self { extractor }
+    }
+}
+
+impl ContentStage for ExtractStage {
+    fn name(&self) -> &str {
+        "extract"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let (text, word_count) = match self.extractor.extract_checked_chunks(
+            &document.raw_body,
+            document.charset,
+            document.declared_language.as_deref(),
+        )? {
+            Ok(extracted) => extracted,
+            Err(reason) => return Ok(StageOutcome::Skip(reason)),
+        };
+        if word_count == 0 {
+            return Ok(StageOutcome::Reject("No content extracted".to_string()));
+        }
+
+        document.text = text;
+        document.word_count = word_count;
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Apply `TextCleaner` rules to the extracted text
+pub struct CleanStage {
+    cleaner: TextCleaner,
+}
+
+impl CleanStage {
+    pub fn new(config: CleaningConfig) -> Result<Self, Error> {
+        Ok(Self {
+            cleaner: TextCleaner::new(config)?,
+        })
+    }
+}
+
+impl ContentStage for CleanStage {
+    fn name(&self) -> &str {
+        "clean"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let result = self.cleaner.clean_text(&document.text)?;
+        if result.was_cleaned {
+            document.set_text(result.cleaned_text);
+        }
+        if let Some(keyword_text) = result.keyword_text {
+            document.annotations.insert(
+                KEYWORD_TEXT_ANNOTATION.to_string(),
+                Value::String(keyword_text),
+            );
+        }
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Detect the document language, optionally rejecting unlisted languages
+pub struct LanguageStage {
+    accepted_languages: Vec<LangType>,
+    declared_weight: f64,
+}
+
+impl LanguageStage {
+    /// An empty list accepts every language
+    pub fn new(accepted_languages: Vec<LangType>) -> Self {
+        Self {
+            accepted_languages,
+            declared_weight: 0.0,
+        }
+    }
+
+    /// Weigh the document's declared language against detection, as in
+    /// `resolve_language`
+    pub fn with_declared_weight(mut self, weight: f64) -> Self {
+        self.declared_weight = weight;
+        self
+    }
+}
+
+impl ContentStage for LanguageStage {
+    fn name(&self) -> &str {
+        "language"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        document.language = resolve_language(
+            &document.text,
+            document.declared_language.clone(),
+            self.declared_weight,
+        )
+        .language;
+        document.word_count =
+            WordCounting::for_language(document.language.as_ref()).count(&document.text);
+
+        if self.accepted_languages.is_empty() {
+            return Ok(StageOutcome::Continue);
+        }
+
+        match &document.language {
+            Some(lang) if self.accepted_languages.contains(lang) => Ok(StageOutcome::Continue),
+            Some(lang) => Ok(StageOutcome::Reject(format!(
+                "Language {:?} not accepted",
+                lang
+            ))),
+            None => Ok(StageOutcome::Reject("Language not detected".to_string())),
+        }
+    }
+}
+
+/// Reject documents that do not match the configured keywords
+pub struct KeywordStage {
+    matcher: KeywordMatcher,
+}
+
+impl KeywordStage {
+    pub fn new(config: KeywordConfig) -> Result<Self, Error> {
+        Ok(Self {
+            matcher: KeywordMatcher::new(config)?,
+        })
+    }
+}
+
+impl ContentStage for KeywordStage {
+    fn name(&self) -> &str {
+        "keyword"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let result = self.matcher.match_keywords(&document.text)?;
+        let found = result.found;
+        document.keyword_matches = Some(result);
+
+        if found {
+            Ok(StageOutcome::Continue)
+        } else {
+            Ok(StageOutcome::Reject("No keyword matches".to_string()))
+        }
+    }
+}
+
+/// Heuristic quality score from lexical diversity and length
+pub struct QualityStage {
+    min_score: f32,
+    target_words: usize,
+}
+
+impl QualityStage {
+    /// `target_words` is the length at which the length component saturates
+    pub fn new(min_score: f32, target_words: usize) -> Self {
+        Self {
+            min_score,
+            target_words: target_words.max(1),
+        }
+    }
+}
+
+impl ContentStage for QualityStage {
+    fn name(&self) -> &str {
+        "quality"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let words: Vec<String> = document
+            .text
+            .unicode_words()
+            .map(|w| w.to_lowercase())
+            .collect();
+        if words.is_empty() {
+            document.quality_score = Some(0.0);
+            return Ok(StageOutcome::Reject("Empty document".to_string()));
+        }
+
+        let unique: HashSet<&String> = words.iter().collect();
+        let diversity = unique.len() as f32 / words.len() as f32;
+        let length = (words.len() as f32 / self.target_words as f32).min(1.0);
+        let score = (diversity + length) / 2.0;
+        document.quality_score = Some(score);
+
+        if score < self.min_score {
+            Ok(StageOutcome::Reject(format!(
+                "Quality score {:.2} below minimum {:.2}",
+                score, self.min_score
+            )))
+        } else {
+            Ok(StageOutcome::Continue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LatinWordFilter;
+
+    struct TagStage;
+
+    impl ContentStage for TagStage {
+        fn name(&self) -> &str {
+            "tag"
+        }
+
+        fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+            document
+                .annotations
+                .insert("tag".to_string(), Value::from(document.word_count));
+            Ok(StageOutcome::Continue)
+        }
+    }
+
+    fn document(html: &str) -> ContentDocument {
+        ContentDocument::new(
+            Url::parse("https://example.com/").unwrap(),
+            vec![Bytes::copy_from_slice(html.as_bytes())],
+        )
+    }
+
+    #[test]
+    fn test_custom_stage_runs_after_extract() {
+        let extractor = ContentExtractor::new(Vec::new(), LatinWordFilter::default()).unwrap();
+        let mut pipeline = ContentPipeline::new()
+            .with_stage(ExtractStage::new(extractor))
+            .with_stage(QualityStage::new(0.0, 100));
+        pipeline.insert_after("extract", TagStage).unwrap();
+        assert_eq!(pipeline.stage_names(), vec!["extract", "tag", "quality"]);
+
+        let html = "<html><body><p>Pipelines let users register custom processing stages \
+                    for named entity recognition or embedding generation workloads</p></body></html>";
+        match pipeline.run(document(html)).unwrap() {
+            PipelineOutcome::Accepted(doc) => {
+                assert!(doc.annotations.contains_key("tag"));
+                assert!(doc.quality_score.is_some());
+            }
+            PipelineOutcome::Rejected { stage, reason, .. } => {
+                panic!("rejected by {}: {}", stage, reason)
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejection_stops_pipeline() {
+        let extractor = ContentExtractor::new(Vec::new(), LatinWordFilter::default()).unwrap();
+        let pipeline = ContentPipeline::new()
+            .with_stage(ExtractStage::new(extractor))
+            .with_stage(TagStage);
+
+        match pipeline.run(document("<p>too short</p>")).unwrap() {
+            PipelineOutcome::Rejected {
+                stage, document, ..
+            } => {
+                assert_eq!(stage, "extract");
+                assert!(document.annotations.is_empty());
+            }
+            PipelineOutcome::Accepted(_) => panic!("short content should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_language_skips_stay_typed() {
+        let extractor =
+            ContentExtractor::new(vec![LangType::Eng], LatinWordFilter::default()).unwrap();
+        let pipeline = ContentPipeline::new().with_stage(ExtractStage::new(extractor));
+        let html = "<p>Le conseil municipal a voté hier soir le budget de la ville pour \
+                    l'année prochaine, avec une hausse des dépenses consacrées aux écoles \
+                    et aux transports publics de toute la région.</p>";
+
+        match pipeline.run(document(html)).unwrap() {
+            PipelineOutcome::Rejected { stage, skip, .. } => {
+                assert_eq!(stage, "extract");
+                assert!(matches!(
+                    skip,
+                    Some(SkipReason::LanguageNotAccepted { ref language, .. }) if language == "fra"
+                ));
+            }
+            PipelineOutcome::Accepted(_) => panic!("French page should be skipped"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+/// Offline re-extraction of stored sessions
+///
+/// Reads the results of a stored session, runs each page through the current
+/// `ContentPipeline` and stores the outcome as a new derived session. Pages are
+/// rebuilt from their retained raw HTML (`StoredCrawlResult::raw_html_hash`) or,
+/// without one, from the stored text. No network requests are made, so changed
+/// cleaning rules, keyword settings or custom stages can be tried without re-crawling.
+use anyhow::Result;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::processing::charset::detect_charset;
+use crate::processing::keyword::auto_keywords;
+use crate::processing::pii::pii_counts;
+use crate::processing::pipeline::{ContentDocument, ContentPipeline, PipelineOutcome};
+use crate::storage::{DataStorage, ResultFilter, SCHEMA_VERSION, StoredCrawlResult};
+
+/// Outcome of reprocessing a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReprocessReport {
+    pub source_session_id: String,
+    /// Session id the re-extracted results were stored under
+    pub session_id: String,
+    /// Results stored in the derived session
+    pub stored: usize,
+    /// Pages rebuilt from retained raw HTML
+    pub from_raw_html: usize,
+    /// Pages rebuilt from stored text, without raw HTML
+    pub from_text: usize,
+    /// Pages dropped by a pipeline stage, with the stage and reason
+    pub rejected: Vec<(String, String)>,
+    /// Pages that could not be reprocessed, with the reason
+    pub errors: Vec<(String, String)>,
+}
+
+/// Re-run `pipeline` over `session_id` in `source`, storing the results in `target`
+///
+/// The derived session gets a new id; results keep their URL, metadata and raw
+/// HTML reference. Failed results without content are skipped.
+pub async fn reprocess_session(
+    source: &DataStorage,
+    session_id: &str,
+    pipeline: &ContentPipeline,
+    target: &DataStorage,
+) -> Result<ReprocessReport> {
+    let mut report = ReprocessReport {
+        source_session_id: session_id.to_string(),
+        session_id: uuid::Uuid::new_v4().to_string(),
+        ..ReprocessReport::default()
+    };
+
+    for result in source.read_session(session_id, ResultFilter::default())? {
+        let result = result?;
+        let url = result.url.clone();
+        match reprocess_result(source, result, pipeline, &report.session_id) {
+            Ok(Reprocessed::Stored { result, from_raw }) => {
+                if from_raw {
+                    report.from_raw_html += 1;
+                } else {
+                    report.from_text += 1;
+                }
+                target.store_result(&result).await?;
+                report.stored += 1;
+            }
+            Ok(Reprocessed::Rejected(reason)) => report.rejected.push((url, reason)),
+            Ok(Reprocessed::NoContent) => {}
+            Err(e) => report.errors.push((url, e.to_string())),
+        }
+    }
+    target.flush().await?;
+    Ok(report)
+}
+
+enum Reprocessed {
+    Stored {
+        result: Box<StoredCrawlResult>,
+        from_raw: bool,
+    },
+    Rejected(String),
+    NoContent,
+}
+
+fn reprocess_result(
+    source: &DataStorage,
+    mut result: StoredCrawlResult,
+    pipeline: &ContentPipeline,
+    session_id: &str,
+) -> Result<Reprocessed> {
+    let (body, from_raw) = match (&result.raw_html_hash, &result.content) {
+        (Some(hash), _) => (source.load_raw_html(hash)?, true),
+        // Extraction keeps entities as written, so stored text reads back unchanged
+        (None, Some(text)) => (text.clone().into_bytes(), false),
+        (None, None) => return Ok(Reprocessed::NoContent),
+    };
+
+    let body = vec![Bytes::from(body)];
+    // Stored text is UTF-8; a raw body keeps the charset it was served in
+    let charset = if from_raw {
+        detect_charset(result.metadata.content_type.as_deref(), &body)
+    } else {
+        encoding_rs::UTF_8
+    };
+    let document = ContentDocument::new(Url::parse(&result.url)?, body).with_charset(charset);
+    let document = match pipeline.run(document)? {
+        PipelineOutcome::Accepted(document) => document,
+        PipelineOutcome::Rejected { stage, reason, .. } => {
+            return Ok(Reprocessed::Rejected(format!("{}: {}", stage, reason)));
+        }
+    };
+
+    let scrubbed = pii_counts(&document);
+    let keywords = auto_keywords(&document);
+    let language = document.language.as_ref().map(|lang| format!("{:?}", lang));
+    result.schema_version = SCHEMA_VERSION;
+    result.content = Some(document.text);
+    result.word_count = document.word_count;
+    result.language = language.clone();
+    result.detected_language = language;
+    result.relevance_score = document.relevance_score.or(result.relevance_score);
+    if let Some(scrubbed) = scrubbed {
+        // Stored text was masked already; raw HTML is scrubbed from scratch
+        if from_raw {
+            result.pii_scrubbed = scrubbed;
+        } else {
+            for (kind, count) in scrubbed {
+                *result.pii_scrubbed.entry(kind).or_insert(0) += count;
+            }
+        }
+    }
+    if let Some(keywords) = keywords {
+        result.keywords = keywords;
+    }
+    result.metadata.crawl_session_id = session_id.to_string();
+    result.timestamp = std::time::SystemTime::now();
+    Ok(Reprocessed::Stored {
+        result: Box::new(result),
+        from_raw,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LatinWordFilter;
+    use crate::processing::content::ContentExtractor;
+    use crate::processing::pipeline::ExtractStage;
+    use crate::storage::{OutputFormat, RawHtmlConfig};
+
+    fn stored(url: &str, content: &str, raw_html_hash: Option<String>) -> StoredCrawlResult {
+        StoredCrawlResult {
+            raw_html_hash,
+            ..StoredCrawlResult::for_test(url)
+                .with_content(content)
+                .with_session("s1")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_is_reextracted_without_fetching() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = DataStorage::new(dir.path(), OutputFormat::Jsonl)
+            .unwrap()
+            .with_raw_html(&RawHtmlConfig::default());
+        let raw = "<html><body><p>The retained body of this page is extracted again \
+                   with the current pipeline</p><script>track()</script></body></html>";
+        let hash = storage.store_raw_html(vec![raw]).await.unwrap();
+        let text = "Pages without retained raw HTML are rebuilt from their stored text instead, \
+                    where entities such as &amp;lt; are kept as they were extracted";
+        storage
+            .store_result(&stored("https://a.example/", "stale", hash))
+            .await
+            .unwrap();
+        storage
+            .store_result(&stored("https://b.example/", text, None))
+            .await
+            .unwrap();
+
+        let words = LatinWordFilter {
+            exclude_numeric: false,
+            excluded_words: Vec::new(),
+            min_word_length: 0,
+        };
+        let extractor = ContentExtractor::new(Vec::new(), words).unwrap();
+        let pipeline = ContentPipeline::new().with_stage(ExtractStage::new(extractor));
+        let report = reprocess_session(&storage, "s1", &pipeline, &storage)
+            .await
+            .unwrap();
+        assert_eq!(
+            (report.stored, report.from_raw_html, report.from_text),
+            (2, 1, 1)
+        );
+        assert!(report.errors.is_empty());
+
+        let derived: Vec<StoredCrawlResult> = storage
+            .read_session(&report.session_id, ResultFilter::default())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        let content = |url: &str| {
+            derived
+                .iter()
+                .find(|result| result.url == url)
+                .and_then(|result| result.content.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            content("https://a.example/"),
+            "The retained body of this page is extracted again with the current pipeline"
+        );
+        assert_eq!(content("https://b.example/"), text);
+    }
+}
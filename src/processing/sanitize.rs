@@ -0,0 +1,174 @@
+/// Sanitized HTML of crawled pages
+///
+/// Pages are stored as plain text by default. For rendering crawled pages in the
+/// desktop app, results can also carry the page's HTML cleaned by `ammonia`: only
+/// an allowlist of formatting tags and attributes is kept, so scripts, styles,
+/// frames, forms and `on*` event handlers are gone, and relative links and image
+/// sources are made absolute against the page's base URL.
+use ammonia::{Builder, UrlRelative};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use url::Url;
+
+use super::charset::decode_chunks;
+use super::discovery::document_base_url;
+
+/// Elements dropped together with everything inside them
+const DROPPED_WITH_CONTENT: &[&str] = &[
+    "script", "style", "iframe", "noscript", "template", "object", "embed", "svg", "math",
+];
+
+/// Sanitized HTML storage settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanitizeConfig {
+    /// Store only the sanitized HTML, leaving the plain text `content` empty; the
+    /// text is still indexed for search and used for keywords and feed summaries
+    pub replace_text: bool,
+    /// Keep `<img>` elements (with absolute sources)
+    pub keep_images: bool,
+    /// `rel` added to every link; `None` leaves links without one
+    pub link_rel: Option<String>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            replace_text: false,
+            keep_images: true,
+            link_rel: Some("noopener noreferrer nofollow".to_string()),
+        }
+    }
+}
+
+/// Cleans page HTML for safe rendering
+///
+/// The allowlists are built once; only the base URL changes between pages, so
+/// pages are cleaned one at a time.
+#[derive(Debug)]
+pub struct HtmlSanitizer {
+    config: SanitizeConfig,
+    builder: Mutex<Builder<'static>>,
+}
+
+impl HtmlSanitizer {
+    pub fn new(config: SanitizeConfig) -> Self {
+        // The builder borrows its strings for good; one short `rel` per sanitizer
+        let link_rel: Option<&'static str> = config.link_rel.clone().map(|rel| &*rel.leak());
+        let mut builder = Builder::default();
+        builder
+            .clean_content_tags(DROPPED_WITH_CONTENT.iter().copied().collect::<HashSet<_>>())
+            .link_rel(link_rel);
+        if !config.keep_images {
+            builder.rm_tags(["img"]);
+        }
+        Self {
+            config,
+            builder: Mutex::new(builder),
+        }
+    }
+
+    pub fn config(&self) -> &SanitizeConfig {
+        &self.config
+    }
+
+    /// Sanitized HTML of a page fetched from `page_url`
+    ///
+    /// Relative URLs resolve against the page's `<base href>`, else `page_url`.
+    pub fn sanitize(&self, html: &str, page_url: &Url) -> String {
+        let base = document_base_url(html, page_url);
+        let mut builder = self.builder.lock().unwrap_or_else(|e| e.into_inner());
+        builder
+            .url_relative(UrlRelative::RewriteWithBase(base))
+            .clean(html)
+            .to_string()
+    }
+
+    /// Sanitized HTML of a raw body received in chunks, decoded from `charset`
+    pub fn sanitize_chunks<B: AsRef<[u8]>>(
+        &self,
+        chunks: &[B],
+        charset: &'static Encoding,
+        page_url: &Url,
+    ) -> String {
+        self.sanitize(&decode_chunks(chunks, charset), page_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitized_html_is_inert_with_absolute_urls() {
+        let html = r#"<html><head><style>body { color: red }</style>
+            <script>alert(1)</script></head>
+            <body onload="steal()">
+              <h1 style="color: red">Title</h1>
+              <p onclick="steal()">Read <a href="/docs/guide" onmouseover="x()">the guide</a>
+                 or <a href="javascript:alert(1)">this</a>.</p>
+              <img src="img/logo.png" onerror="steal()">
+              <iframe src="https://evil.example/"><p>frame fallback</p></iframe>
+              <form action="/login"><input name="password"></form>
+            </body></html>"#;
+        let page = Url::parse("https://example.com/articles/one").unwrap();
+
+        let sanitizer = HtmlSanitizer::new(SanitizeConfig::default());
+        let clean = sanitizer.sanitize(html, &page);
+        for unsafe_fragment in [
+            "<script",
+            "alert",
+            "<style",
+            "color",
+            "onload",
+            "onclick",
+            "onmouseover",
+            "onerror",
+            "<iframe",
+            "evil.example",
+            "frame fallback",
+            "<form",
+            "<input",
+            "javascript:",
+        ] {
+            assert!(
+                !clean.contains(unsafe_fragment),
+                "{} left in {}",
+                unsafe_fragment,
+                clean
+            );
+        }
+        assert!(clean.contains("<h1>Title</h1>"));
+        assert!(clean.contains(
+            r#"<a href="https://example.com/docs/guide" rel="noopener noreferrer nofollow">"#
+        ));
+        assert!(clean.contains(r#"<img src="https://example.com/articles/img/logo.png">"#));
+
+        let based = sanitizer.sanitize(
+            r#"<base href="https://cdn.example.com/"><img src="a.png">"#,
+            &page,
+        );
+        assert!(based.contains("https://cdn.example.com/a.png"));
+
+        let text_only = HtmlSanitizer::new(SanitizeConfig {
+            keep_images: false,
+            link_rel: None,
+            ..SanitizeConfig::default()
+        });
+        let clean = text_only.sanitize_chunks(
+            &[&html.as_bytes()[..200], &html.as_bytes()[200..]],
+            encoding_rs::UTF_8,
+            &page,
+        );
+        assert!(!clean.contains("<img"));
+        assert!(clean.contains(r#"<a href="https://example.com/docs/guide">"#));
+
+        // The page is decoded from its charset before it is cleaned
+        let korean = encoding_rs::EUC_KR
+            .encode("<p>서울의 <b>날씨</b></p><script>x()</script>")
+            .0;
+        let clean = sanitizer.sanitize_chunks(&[&korean], encoding_rs::EUC_KR, &page);
+        assert_eq!(clean, "<p>서울의 <b>날씨</b></p>");
+    }
+}
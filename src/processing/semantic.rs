@@ -0,0 +1,211 @@
+/// Semantic relevance scoring for focused crawls
+///
+/// This module provides the integration point for embedding-based relevance:
+/// - `RelevanceModel`: scores text against a topic (0.0 = unrelated, 1.0 = on topic)
+/// - `TextEmbedder`: turns text into a vector; `EmbeddingRelevanceModel` compares
+///   page embeddings to a topic embedding with cosine similarity
+/// - `HashedBagOfWordsEmbedder`: dependency-free embedder, useful as a baseline
+/// - `OnnxSentenceEmbedder`: ONNX sentence-embedding models (`semantic-onnx` feature)
+///
+/// Scores feed the content pipeline (`RelevanceStage`), link prioritization
+/// (`boost_link_priorities`) and stored results.
+use anyhow::Error;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::processing::discovery::ProcessedLink;
+use crate::processing::pipeline::{ContentDocument, ContentStage, StageOutcome};
+
+#[cfg(feature = "semantic-onnx")]
+mod onnx;
+#[cfg(feature = "semantic-onnx")]
+pub use onnx::OnnxSentenceEmbedder;
+
+/// Scores how relevant a text is to the crawl topic
+pub trait RelevanceModel: Send + Sync {
+    /// Relevance in `0.0..=1.0`
+    fn score(&self, text: &str) -> f32;
+}
+
+/// Turns text into a fixed-size embedding vector
+pub trait TextEmbedder: Send + Sync {
+    fn embed(&self, text: &str) -> Result<Vec<f32>, Error>;
+}
+
+/// Relevance as cosine similarity between a page embedding and the topic embedding
+pub struct EmbeddingRelevanceModel {
+    embedder: Box<dyn TextEmbedder>,
+    topic: Vec<f32>,
+}
+
+impl EmbeddingRelevanceModel {
+    /// The topic embedding is the mean of the embeddings of `topic_texts`
+    pub fn new(embedder: Box<dyn TextEmbedder>, topic_texts: &[String]) -> Result<Self, Error> {
+        if topic_texts.is_empty() {
+            return Err(anyhow::anyhow!("At least one topic text is required"));
+        }
+
+        let mut topic: Vec<f32> = Vec::new();
+        for text in topic_texts {
+            let embedding = embedder.embed(text)?;
+            if topic.is_empty() {
+                topic = vec![0.0; embedding.len()];
+            } else if topic.len() != embedding.len() {
+                return Err(anyhow::anyhow!(
+                    "Embedding size mismatch: {} vs {}",
+                    topic.len(),
+                    embedding.len()
+                ));
+            }
+            for (acc, value) in topic.iter_mut().zip(embedding) {
+                *acc += value;
+            }
+        }
+        let count = topic_texts.len() as f32;
+        topic.iter_mut().for_each(|value| *value /= count);
+
+        Ok(Self { embedder, topic })
+    }
+}
+
+impl RelevanceModel for EmbeddingRelevanceModel {
+    fn score(&self, text: &str) -> f32 {
+        match self.embedder.embed(text) {
+            Ok(embedding) => cosine_similarity(&embedding, &self.topic).clamp(0.0, 1.0),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to embed text for relevance scoring");
+                0.0
+            }
+        }
+    }
+}
+
+/// Feature-hashed, L2-normalized term frequency vectors
+pub struct HashedBagOfWordsEmbedder {
+    dimensions: usize,
+}
+
+impl HashedBagOfWordsEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+}
+
+impl Default for HashedBagOfWordsEmbedder {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl TextEmbedder for HashedBagOfWordsEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>, Error> {
+        let mut vector = vec![0.0; self.dimensions];
+        for word in text.unicode_words() {
+            let mut hasher = DefaultHasher::new();
+            word.to_lowercase().hash(&mut hasher);
+            vector[(hasher.finish() % self.dimensions as u64) as usize] += 1.0;
+        }
+        normalize(&mut vector);
+        Ok(vector)
+    }
+}
+
+/// Cosine similarity of two vectors, 0.0 when either is zero or sizes differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+pub(crate) fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// Pipeline stage recording the relevance score, optionally rejecting off-topic pages
+pub struct RelevanceStage {
+    model: Arc<dyn RelevanceModel>,
+    min_score: Option<f32>,
+}
+
+impl RelevanceStage {
+    pub fn new(model: Arc<dyn RelevanceModel>, min_score: Option<f32>) -> Self {
+        Self { model, min_score }
+    }
+}
+
+impl ContentStage for RelevanceStage {
+    fn name(&self) -> &str {
+        "relevance"
+    }
+
+    fn process(&self, document: &mut ContentDocument) -> Result<StageOutcome, Error> {
+        let score = self.model.score(&document.text);
+        document.relevance_score = Some(score);
+
+        match self.min_score {
+            Some(min_score) if score < min_score => Ok(StageOutcome::Reject(format!(
+                "Relevance score {:.2} below minimum {:.2}",
+                score, min_score
+            ))),
+            _ => Ok(StageOutcome::Continue),
+        }
+    }
+}
+
+/// Raise link priorities for focused crawling
+///
+/// Each link is boosted by up to `max_boost` based on the average of the relevance of the
+/// page it was found on and the relevance of its anchor text. Links are re-sorted by priority.
+pub fn boost_link_priorities(
+    links: &mut [ProcessedLink],
+    model: &dyn RelevanceModel,
+    page_score: f32,
+    max_boost: u8,
+) {
+    for link in links.iter_mut() {
+        let anchor_score = if link.extracted_link.anchor_text.trim().is_empty() {
+            0.0
+        } else {
+            model.score(&link.extracted_link.anchor_text)
+        };
+        let combined = ((page_score + anchor_score) / 2.0).clamp(0.0, 1.0);
+        let boost = (combined * max_boost as f32).round() as u8;
+        link.priority = link.priority.saturating_add(boost);
+    }
+
+    links.sort_by_key(|link| std::cmp::Reverse(link.priority));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_model_prefers_on_topic_text() {
+        let model = EmbeddingRelevanceModel::new(
+            Box::new(HashedBagOfWordsEmbedder::default()),
+            &["rust async runtime tokio futures".to_string()],
+        )
+        .unwrap();
+
+        let on_topic = model.score("The tokio runtime drives rust futures in async code");
+        let off_topic = model.score("Banana bread recipe with walnuts and cinnamon");
+        assert!(on_topic > off_topic);
+        assert!((0.0..=1.0).contains(&on_topic));
+    }
+}
@@ -0,0 +1,149 @@
+/// ONNX sentence-embedding loader (e.g. all-MiniLM-L6-v2 exported to ONNX)
+use anyhow::Error;
+use ort::session::Session;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::value::Tensor;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+use super::{TextEmbedder, normalize};
+
+/// Inputs longer than this are truncated before inference
+const MAX_SEQUENCE_LENGTH: usize = 256;
+
+/// Sentence embedder backed by an ONNX Runtime session
+///
+/// The model must take `input_ids` and `attention_mask` (plus optionally
+/// `token_type_ids`) and return token embeddings as its first output; sentence
+/// embeddings are produced by attention-masked mean pooling.
+pub struct OnnxSentenceEmbedder {
+    session: Session,
+    tokenizer: Tokenizer,
+    uses_token_type_ids: bool,
+}
+
+impl OnnxSentenceEmbedder {
+    /// Load a model and its HuggingFace `tokenizer.json`
+    pub fn load<P: AsRef<Path>, T: AsRef<Path>>(
+        model_path: P,
+        tokenizer_path: T,
+    ) -> Result<Self, Error> {
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .commit_from_file(model_path)?;
+        let tokenizer = Tokenizer::from_file(tokenizer_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+        let uses_token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+
+        Ok(Self {
+            session,
+            tokenizer,
+            uses_token_type_ids,
+        })
+    }
+}
+
+impl TextEmbedder for OnnxSentenceEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>, Error> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+
+        let length = encoding.get_ids().len().min(MAX_SEQUENCE_LENGTH);
+        let ids: Vec<i64> = encoding.get_ids()[..length]
+            .iter()
+            .map(|&id| id as i64)
+            .collect();
+        let mask: Vec<i64> = encoding.get_attention_mask()[..length]
+            .iter()
+            .map(|&m| m as i64)
+            .collect();
+        let shape = [1usize, length];
+
+        let input_ids = Tensor::from_array((shape, ids))?;
+        let attention_mask = Tensor::from_array((shape, mask.clone()))?;
+        let outputs = if self.uses_token_type_ids {
+            let token_type_ids = Tensor::from_array((shape, vec![0i64; length]))?;
+            self.session.run(ort::inputs![
+                "input_ids" => input_ids,
+                "attention_mask" => attention_mask,
+                "token_type_ids" => token_type_ids,
+            ]?)?
+        } else {
+            self.session.run(ort::inputs![
+                "input_ids" => input_ids,
+                "attention_mask" => attention_mask,
+            ]?)?
+        };
+
+        // Token embeddings: [1, length, hidden]
+        let (output_shape, values) = outputs[0].try_extract_raw_tensor::<f32>()?;
+        let mut pooled = mean_pool(&output_shape, values, &mask)?;
+
+        normalize(&mut pooled);
+        Ok(pooled)
+    }
+}
+
+/// Attention-masked mean of token embeddings shaped `[1, mask.len(), hidden]`
+fn mean_pool(shape: &[i64], values: &[f32], mask: &[i64]) -> Result<Vec<f32>, Error> {
+    let hidden = match *shape {
+        [1, positions, hidden] if positions as usize == mask.len() && hidden > 0 => hidden as usize,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unexpected embedding output shape {:?}, expected [1, {}, hidden]",
+                shape,
+                mask.len()
+            ));
+        }
+    };
+    if values.len() != mask.len() * hidden {
+        return Err(anyhow::anyhow!(
+            "Embedding output has {} values for shape {:?}",
+            values.len(),
+            shape
+        ));
+    }
+
+    let mut pooled = vec![0.0f32; hidden];
+    let mut tokens = 0.0f32;
+    for (token, &m) in values.chunks_exact(hidden).zip(mask) {
+        if m == 0 {
+            continue;
+        }
+        for (acc, value) in pooled.iter_mut().zip(token) {
+            *acc += value;
+        }
+        tokens += 1.0;
+    }
+    if tokens > 0.0 {
+        pooled.iter_mut().for_each(|value| *value /= tokens);
+    }
+    Ok(pooled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_tokens_are_left_out_of_the_mean() {
+        let values = [1.0, 2.0, 3.0, 4.0, 100.0, 100.0];
+        let pooled = mean_pool(&[1, 3, 2], &values, &[1, 1, 0]).unwrap();
+        assert_eq!(pooled, vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_unexpected_output_shapes_are_errors() {
+        // Already pooled
+        assert!(mean_pool(&[1, 2], &[1.0, 2.0], &[1, 1]).is_err());
+        // Fewer positions than tokens
+        assert!(mean_pool(&[1, 1, 2], &[1.0, 2.0], &[1, 1]).is_err());
+        // Too few values for the declared shape
+        assert!(mean_pool(&[1, 2, 2], &[1.0, 2.0], &[1, 1]).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+/// Diagnostics for pages rejected as too short
+///
+/// A page whose text stays below `min_word_length` is dropped, but the reason it is
+/// short matters when tuning the threshold: a login form or a 404 page with a 200
+/// status should stay dropped, while a real but terse page suggests the threshold
+/// is too high. `ShortContentDiagnosis` records the word count, language and a
+/// guessed `ContentCategory` of every such page, and `ShortPagesReport` appends
+/// them to `short_pages.jsonl` in the session directory.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use url::Url;
+
+use super::interstitial::{Interstitial, InterstitialKind};
+use crate::storage::path_safe_component;
+
+/// File name of the report inside a session directory
+pub const SHORT_PAGES_FILE: &str = "short_pages.jsonl";
+
+const LOGIN_PATH_MARKERS: &[&str] = &["login", "signin", "sign-in", "sign_in", "logon", "auth"];
+const LOGIN_TEXT_MARKERS: &[&str] = &[
+    "sign in",
+    "log in",
+    "forgot password",
+    "forgot your password",
+    "username",
+    "password",
+];
+const ERROR_TEXT_MARKERS: &[&str] = &[
+    "page not found",
+    "404 not found",
+    "error 404",
+    "404 error",
+    "page could not be found",
+    "page cannot be found",
+    "page does not exist",
+    "page doesn't exist",
+    "access denied",
+    "403 forbidden",
+    "something went wrong",
+    "an error occurred",
+    "internal server error",
+    "service unavailable",
+];
+const CONSENT_TEXT_MARKERS: &[&str] = &[
+    "accept cookies",
+    "accept all cookies",
+    "cookie settings",
+    "cookie preferences",
+    "we value your privacy",
+    "manage consent",
+];
+
+/// Whether a path segment of `url`, without its extension, is a login marker;
+/// `/auth/callback` and `/login.php` are login paths, `/author/jane` is not
+fn is_login_path(url: &Url) -> bool {
+    url.path_segments().into_iter().flatten().any(|segment| {
+        let stem = segment.split('.').next().unwrap_or(segment);
+        LOGIN_PATH_MARKERS
+            .iter()
+            .any(|marker| stem.eq_ignore_ascii_case(marker))
+    })
+}
+
+/// Best guess at what a short page is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentCategory {
+    LoginPage,
+    /// Error page served with a success status ("soft 404")
+    ErrorPage,
+    ConsentWall,
+    /// A page that is simply short: a redirect stub, placeholder or terse page
+    Stub,
+}
+
+impl ContentCategory {
+    /// Guess the category from the page URL, its text and any interstitial marker
+    ///
+    /// Consent walls are checked first, then login and error pages; anything else
+    /// is a stub.
+    pub fn guess(url: &Url, text: &str, interstitial: Option<&Interstitial>) -> Self {
+        let text = text.to_lowercase();
+        let contains_any = |markers: &[&str]| markers.iter().any(|marker| text.contains(marker));
+
+        if interstitial.is_some_and(|found| found.kind == InterstitialKind::ConsentWall)
+            || contains_any(CONSENT_TEXT_MARKERS)
+        {
+            ContentCategory::ConsentWall
+        } else if is_login_path(url) || contains_any(LOGIN_TEXT_MARKERS) {
+            ContentCategory::LoginPage
+        } else if contains_any(ERROR_TEXT_MARKERS) {
+            ContentCategory::ErrorPage
+        } else {
+            ContentCategory::Stub
+        }
+    }
+}
+
+impl std::fmt::Display for ContentCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentCategory::LoginPage => write!(f, "login page"),
+            ContentCategory::ErrorPage => write!(f, "error page"),
+            ContentCategory::ConsentWall => write!(f, "consent wall"),
+            ContentCategory::Stub => write!(f, "stub"),
+        }
+    }
+}
+
+/// Why a page was dropped as too short
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortContentDiagnosis {
+    pub url: String,
+    pub status_code: u16,
+    pub word_count: usize,
+    pub min_word_length: usize,
+    /// Resolved language of the text, as an HTTP language code
+    pub language: Option<String>,
+    pub category: ContentCategory,
+    pub timestamp: SystemTime,
+}
+
+impl std::fmt::Display for ShortContentDiagnosis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Content too short: {} words < {} minimum ({})",
+            self.word_count, self.min_word_length, self.category
+        )
+    }
+}
+
+/// Report file of `session_id` under the storage directory
+pub fn session_short_pages_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    storage_dir
+        .join(path_safe_component(&format!("session_{}", session_id)))
+        .join(SHORT_PAGES_FILE)
+}
+
+/// Diagnoses buffered before they are written out
+const FLUSH_BYTES: usize = 64 * 1024;
+
+/// Appends short-page diagnoses to a JSONL report
+///
+/// Diagnoses are buffered and appended to the file once `FLUSH_BYTES` have
+/// gathered, on `flush` and when the report is dropped.
+#[derive(Debug)]
+pub struct ShortPagesReport {
+    path: PathBuf,
+    buffered: Mutex<String>,
+}
+
+impl ShortPagesReport {
+    /// Open `path` for appending, creating its directory if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            buffered: Mutex::new(String::new()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, diagnosis: &ShortContentDiagnosis) -> Result<()> {
+        let line = serde_json::to_string(diagnosis)?;
+        let full = {
+            let mut buffered = self.buffered.lock().unwrap_or_else(|e| e.into_inner());
+            buffered.push_str(&line);
+            buffered.push('\n');
+            (buffered.len() >= FLUSH_BYTES).then(|| std::mem::take(&mut *buffered))
+        };
+        match full {
+            Some(lines) => append_lines(&self.path, &lines),
+            None => Ok(()),
+        }
+    }
+
+    /// Write out the buffered diagnoses on the blocking pool
+    pub async fn flush(&self) -> Result<()> {
+        let lines = std::mem::take(&mut *self.buffered.lock().unwrap_or_else(|e| e.into_inner()));
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let path = self.path.clone();
+        let appended = lines.clone();
+        let written = tokio::task::spawn_blocking(move || append_lines(&path, &appended))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|r| r);
+        if written.is_err() {
+            // Keep the diagnoses for the next flush, ahead of those recorded meanwhile
+            self.buffered
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert_str(0, &lines);
+        }
+        written
+    }
+}
+
+impl Drop for ShortPagesReport {
+    fn drop(&mut self) {
+        let lines = std::mem::take(self.buffered.get_mut().unwrap_or_else(|e| e.into_inner()));
+        if !lines.is_empty()
+            && let Err(e) = append_lines(&self.path, &lines)
+        {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "Failed to write buffered short-page diagnoses"
+            );
+        }
+    }
+}
+
+fn append_lines(path: &Path, lines: &str) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_short_pages_are_categorised_and_reported() {
+        let url = |path: &str| Url::parse(&format!("https://example.com{}", path)).unwrap();
+        let consent = Interstitial {
+            kind: InterstitialKind::ConsentWall,
+            marker: "onetrust".to_string(),
+        };
+
+        assert_eq!(
+            ContentCategory::guess(&url("/article"), "Welcome", Some(&consent)),
+            ContentCategory::ConsentWall
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/account/signin"), "Welcome back", None),
+            ContentCategory::LoginPage
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/a"), "Email Password Forgot password?", None),
+            ContentCategory::LoginPage
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/old"), "Sorry, Page Not Found", None),
+            ContentCategory::ErrorPage
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/moved"), "This page has moved.", None),
+            ContentCategory::Stub
+        );
+        // Markers are whole path segments, and figures or "not found" alone are not errors
+        assert_eq!(
+            ContentCategory::guess(&url("/auth/callback"), "Redirecting", None),
+            ContentCategory::LoginPage
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/login.php"), "Welcome", None),
+            ContentCategory::LoginPage
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/author/jane"), "Jane Doe", None),
+            ContentCategory::Stub
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/a"), "Route 404 reopens; cause not found yet", None),
+            ContentCategory::Stub
+        );
+        assert_eq!(
+            ContentCategory::guess(&url("/a"), "404 Not Found", None),
+            ContentCategory::ErrorPage
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_short_pages_path(dir.path(), "s1");
+        let report = ShortPagesReport::open(&path).unwrap();
+        let diagnosis = ShortContentDiagnosis {
+            url: url("/old").to_string(),
+            status_code: 200,
+            word_count: 4,
+            min_word_length: 50,
+            language: Some("en".to_string()),
+            category: ContentCategory::ErrorPage,
+            timestamp: SystemTime::now(),
+        };
+        assert_eq!(
+            diagnosis.to_string(),
+            "Content too short: 4 words < 50 minimum (error page)"
+        );
+        report.record(&diagnosis).unwrap();
+        // Diagnoses are buffered until the report is flushed
+        assert!(!path.exists());
+        report.flush().await.unwrap();
+        let line = std::fs::read_to_string(&path).unwrap();
+        assert!(line.contains("\"category\":\"error_page\""));
+        assert!(line.ends_with('\n'));
+    }
+}
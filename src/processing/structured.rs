@@ -0,0 +1,223 @@
+/// Structured extraction of HTML tables and lists
+///
+/// Text extraction flattens a page to words, which loses the layout of statistics
+/// tables and enumerations. This module keeps them as data: tables as a header row
+/// plus row/column cell text (with `colspan` cells repeated so columns line up), and
+/// `<ol>`/`<ul>` lists as item arrays. Nested tables and lists are emitted on their
+/// own rather than folded into their parent's cells and items.
+use scraper::node::Node;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::config::StructuredExtractionConfig;
+
+/// Upper bound for `colspan`, so a malformed attribute cannot blow up a row
+const MAX_COLSPAN: usize = 64;
+
+/// A table as header and row/column cell text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedTable {
+    pub caption: Option<String>,
+    /// Cells of the `<thead>` row, or of a leading row made only of `<th>` cells
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// An ordered or unordered list
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedList {
+    pub ordered: bool,
+    pub items: Vec<String>,
+}
+
+/// Tables and lists of one page, in document order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StructuredContent {
+    pub tables: Vec<ExtractedTable>,
+    pub lists: Vec<ExtractedList>,
+}
+
+impl StructuredContent {
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty() && self.lists.is_empty()
+    }
+}
+
+/// Extract the tables and lists of an HTML document
+pub fn extract_structured(html: &str, config: &StructuredExtractionConfig) -> StructuredContent {
+    let document = Html::parse_document(html);
+    let mut content = StructuredContent::default();
+
+    if config.tables
+        && let Ok(selector) = Selector::parse("table")
+    {
+        content.tables = document
+            .select(&selector)
+            .filter(|table| !(config.skip_navigation && in_navigation(table)))
+            .filter_map(|table| extract_table(&table))
+            .collect();
+    }
+
+    if config.lists
+        && let Ok(selector) = Selector::parse("ol, ul")
+    {
+        content.lists = document
+            .select(&selector)
+            .filter(|list| !(config.skip_navigation && in_navigation(list)))
+            .map(|list| extract_list(&list))
+            .filter(|list| list.items.len() >= config.min_list_items.max(1))
+            .collect();
+    }
+
+    content
+}
+
+fn extract_table(table: &ElementRef) -> Option<ExtractedTable> {
+    let row_selector = Selector::parse("tr").ok()?;
+    let mut extracted = ExtractedTable {
+        caption: child_elements(table)
+            .find(|child| child.value().name() == "caption")
+            .map(|caption| element_text(&caption, &[]))
+            .filter(|caption| !caption.is_empty()),
+        ..ExtractedTable::default()
+    };
+
+    // Rows of nested tables belong to those tables
+    let rows = table.select(&row_selector).filter(|row| {
+        row.ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|ancestor| ancestor.value().name() == "table")
+            .is_some_and(|owner| owner.id() == table.id())
+    });
+
+    for row in rows {
+        let mut cells = Vec::new();
+        let mut all_headers = true;
+        for cell in child_elements(&row) {
+            let name = cell.value().name();
+            if name != "td" && name != "th" {
+                continue;
+            }
+            all_headers &= name == "th";
+            let span = cell
+                .value()
+                .attr("colspan")
+                .and_then(|span| span.trim().parse::<usize>().ok())
+                .unwrap_or(1)
+                .clamp(1, MAX_COLSPAN);
+            let text = element_text(&cell, &["table"]);
+            cells.extend(std::iter::repeat_n(text, span));
+        }
+        if cells.is_empty() {
+            continue;
+        }
+
+        let in_thead = row
+            .parent()
+            .and_then(ElementRef::wrap)
+            .is_some_and(|parent| parent.value().name() == "thead");
+        let leading_header_row =
+            all_headers && extracted.headers.is_empty() && extracted.rows.is_empty();
+        if extracted.headers.is_empty() && (in_thead || leading_header_row) {
+            extracted.headers = cells;
+        } else {
+            extracted.rows.push(cells);
+        }
+    }
+
+    (!extracted.headers.is_empty() || !extracted.rows.is_empty()).then_some(extracted)
+}
+
+fn extract_list(list: &ElementRef) -> ExtractedList {
+    ExtractedList {
+        ordered: list.value().name() == "ol",
+        items: child_elements(list)
+            .filter(|child| child.value().name() == "li")
+            .map(|item| element_text(&item, &["ol", "ul"]))
+            .filter(|item| !item.is_empty())
+            .collect(),
+    }
+}
+
+fn child_elements<'a>(element: &ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element.children().filter_map(ElementRef::wrap)
+}
+
+/// Whether the element sits in page chrome rather than content
+fn in_navigation(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| matches!(ancestor.value().name(), "nav" | "header" | "footer"))
+}
+
+/// Whitespace-normalized text of an element, leaving out `skipped` descendants
+fn element_text(element: &ElementRef, skipped: &[&str]) -> String {
+    let mut words = Vec::new();
+    collect_words(element, skipped, &mut words);
+    words.join(" ")
+}
+
+fn collect_words<'a>(element: &ElementRef<'a>, skipped: &[&str], words: &mut Vec<&'a str>) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => words.extend(text.split_whitespace()),
+            Node::Element(el) if matches!(el.name(), "script" | "style") => {}
+            Node::Element(el) if skipped.contains(&el.name()) => {}
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    collect_words(&child, skipped, words);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_and_lists_keep_their_structure() {
+        let html = r#"<html><body>
+            <nav><ul><li>Home</li><li>About</li></ul></nav>
+            <table>
+              <caption>Population by year</caption>
+              <thead><tr><th>Country</th><th>2020</th><th>2021</th></tr></thead>
+              <tbody>
+                <tr><td>France</td><td>67.4</td><td>67.7</td></tr>
+                <tr><td>Germany</td><td colspan="2">83.2</td></tr>
+              </tbody>
+            </table>
+            <ol><li>First <b>step</b></li><li>Second<ul><li>a</li><li>b</li></ul></li></ol>
+            <ul><li>Lonely</li></ul>
+        </body></html>"#;
+
+        let content = extract_structured(html, &StructuredExtractionConfig::default());
+
+        assert_eq!(content.tables.len(), 1);
+        let table = &content.tables[0];
+        assert_eq!(table.caption.as_deref(), Some("Population by year"));
+        assert_eq!(table.headers, vec!["Country", "2020", "2021"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["France", "67.4", "67.7"],
+                vec!["Germany", "83.2", "83.2"]
+            ]
+        );
+
+        // The nav menu and the single-item list are dropped; the nested list stands alone
+        assert_eq!(content.lists.len(), 2);
+        assert!(content.lists[0].ordered);
+        assert_eq!(content.lists[0].items, vec!["First step", "Second"]);
+        assert_eq!(content.lists[1].items, vec!["a", "b"]);
+
+        let tables_only = StructuredExtractionConfig {
+            lists: false,
+            ..StructuredExtractionConfig::default()
+        };
+        assert!(extract_structured(html, &tables_only).lists.is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+/// Weighted round-robin across seed groups
+///
+/// Seeds can be tagged with a group (a customer, a project) that their discovered
+/// links inherit. Pending tasks are kept per group and dequeued by smooth weighted
+/// round-robin, so a group with weight 3 gets three tasks for every one of a group
+/// with weight 1, and a huge site cannot starve the other groups of a crawl.
+/// Within a group tasks keep their priority order.
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use url::Url;
+
+use crate::core::{Region, SeedScope};
+use crate::crawler::ApiSeed;
+
+/// Group of tasks without an explicit group
+pub const DEFAULT_GROUP: &str = "default";
+
+/// Seeds crawled as one group with a share of the queue
+#[derive(Debug, Clone)]
+pub struct SeedGroup {
+    pub name: String,
+    /// Relative share of dequeues; 0 counts as 1
+    pub weight: u32,
+    pub seeds: Vec<Url>,
+    /// Key/value pairs per seed, copied into the results of the seed and its links
+    pub metadata: HashMap<Url, BTreeMap<String, String>>,
+    /// Depth and domain limits per seed; seeds without one follow the crawl-wide limits
+    pub scopes: HashMap<Url, SeedScope>,
+    /// Region each seed is meant to be fetched from, inherited by its links
+    pub regions: HashMap<Url, Region>,
+    /// JSON listings whose item URLs are crawled as further seeds of the group
+    pub api_seeds: Vec<ApiSeed>,
+}
+
+impl SeedGroup {
+    pub fn new(name: impl Into<String>, seeds: Vec<Url>) -> Self {
+        Self {
+            name: name.into(),
+            weight: 1,
+            seeds,
+            metadata: HashMap::new(),
+            scopes: HashMap::new(),
+            regions: HashMap::new(),
+            api_seeds: Vec::new(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Attach metadata such as `campaign` or a source row id to a seed
+    pub fn with_metadata(mut self, seed: &Url, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata.insert(seed.clone(), metadata);
+        self
+    }
+
+    /// Give a seed its own depth limit and domain scope
+    pub fn with_scope(mut self, seed: &Url, scope: SeedScope) -> Self {
+        self.scopes.insert(seed.clone(), scope);
+        self
+    }
+
+    /// Fetch a seed and its links through proxies of `region`
+    pub fn with_region(mut self, seed: &Url, region: Region) -> Self {
+        self.regions.insert(seed.clone(), region);
+        self
+    }
+
+    /// Crawl the items listed by a paginated JSON API as seeds of the group
+    pub fn with_api_seed(mut self, seed: ApiSeed) -> Self {
+        self.api_seeds.push(seed);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GroupHeap<T: Ord> {
+    heap: BinaryHeap<T>,
+    /// Smooth weighted round-robin credit
+    current: i64,
+}
+
+/// Per-group priority heaps dequeued by weighted round-robin
+#[derive(Debug, Clone)]
+pub(crate) struct WeightedGroups<T: Ord> {
+    groups: BTreeMap<String, GroupHeap<T>>,
+    weights: HashMap<String, u32>,
+}
+
+impl<T: Ord> Default for WeightedGroups<T> {
+    fn default() -> Self {
+        Self {
+            groups: BTreeMap::new(),
+            weights: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Ord> WeightedGroups<T> {
+    pub(crate) fn set_weight(&mut self, group: &str, weight: u32) {
+        self.weights.insert(group.to_string(), weight.max(1));
+    }
+
+    /// Weights set with `set_weight`, by group
+    pub(crate) fn weights(&self) -> &HashMap<String, u32> {
+        &self.weights
+    }
+
+    pub(crate) fn weight(&self, group: &str) -> u32 {
+        self.weights.get(group).copied().unwrap_or(1)
+    }
+
+    pub(crate) fn push(&mut self, group: &str, item: T) {
+        self.groups
+            .entry(group.to_string())
+            .or_insert_with(|| GroupHeap {
+                heap: BinaryHeap::new(),
+                current: 0,
+            })
+            .heap
+            .push(item);
+    }
+
+    /// The highest-priority item of the group whose turn it is
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let mut total = 0;
+        let mut chosen: Option<(&String, i64)> = None;
+        for (name, group) in &mut self.groups {
+            if group.heap.is_empty() {
+                continue;
+            }
+            let weight = i64::from(self.weights.get(name).copied().unwrap_or(1));
+            group.current += weight;
+            total += weight;
+            if chosen.is_none_or(|(_, current)| group.current > current) {
+                chosen = Some((name, group.current));
+            }
+        }
+        let name = chosen?.0.clone();
+
+        let group = self.groups.get_mut(&name)?;
+        group.current -= total;
+        let item = group.heap.pop();
+        if group.heap.is_empty() {
+            self.groups.remove(&name);
+        }
+        item
+    }
+
+    /// The next `n` items `pop` would return, in that order, without removing them
+    pub(crate) fn peek(&self, n: usize) -> Vec<&T> {
+        let mut groups: Vec<(i64, i64, VecDeque<&T>)> = self
+            .groups
+            .iter()
+            .map(|(name, group)| {
+                let weight = i64::from(self.weights.get(name).copied().unwrap_or(1));
+                (weight, group.current, largest(&group.heap, n).into())
+            })
+            .collect();
+        let mut items = Vec::with_capacity(n);
+        while items.len() < n {
+            let mut total = 0;
+            let mut chosen: Option<(usize, i64)> = None;
+            for (index, (weight, current, queued)) in groups.iter_mut().enumerate() {
+                if queued.is_empty() {
+                    continue;
+                }
+                *current += *weight;
+                total += *weight;
+                if chosen.is_none_or(|(_, chosen_current)| *current > chosen_current) {
+                    chosen = Some((index, *current));
+                }
+            }
+            let Some((index, _)) = chosen else {
+                break;
+            };
+            let (_, current, queued) = &mut groups[index];
+            *current -= total;
+            items.extend(queued.pop_front());
+        }
+        items
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.groups.values().map(|group| group.heap.len()).sum()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.groups.values().flat_map(|group| group.heap.iter())
+    }
+
+    /// Pending items per group, by group name
+    pub(crate) fn group_lengths(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.groups
+            .iter()
+            .map(|(name, group)| (name.as_str(), group.heap.len()))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.groups.clear();
+    }
+}
+
+/// The `n` largest items of a heap, largest first
+fn largest<T: Ord>(heap: &BinaryHeap<T>, n: usize) -> Vec<&T> {
+    let mut kept = BinaryHeap::with_capacity(n + 1);
+    for item in heap.iter() {
+        kept.push(Reverse(item));
+        if kept.len() > n {
+            kept.pop();
+        }
+    }
+    kept.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(item)| item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_are_dequeued_by_weight() {
+        let mut groups = WeightedGroups::default();
+        groups.set_weight("big", 3);
+        for priority in 0..20 {
+            groups.push("big", priority);
+        }
+        for priority in 0..2 {
+            groups.push("small", priority);
+        }
+
+        let upcoming: Vec<i32> = groups.peek(8).into_iter().copied().collect();
+        let first: Vec<i32> = (0..8).filter_map(|_| groups.pop()).collect();
+        assert_eq!(upcoming, first);
+        // Big gets three turns per small turn, highest priority first in each group
+        assert_eq!(first, vec![19, 18, 1, 17, 16, 15, 0, 14]);
+        assert_eq!(groups.len(), 14);
+        assert_eq!(
+            groups.group_lengths().collect::<Vec<_>>(),
+            vec![("big", 14)]
+        );
+        assert_eq!(groups.weight("small"), 1);
+    }
+}
@@ -0,0 +1,157 @@
+/// JSONL frontier file for feeding URLs into a running crawl
+///
+/// Other tools append candidate URLs to the file, one JSON object per line, and the
+/// queue tails it for new entries while the crawl runs:
+///
+/// ```text
+/// {"url":"https://example.com/a"}
+/// {"url":"https://example.com/b","priority":"High","depth":1,"group":"partners","metadata":{"source":"csv-row-123"}}
+/// ```
+///
+/// Only `url` is required. `priority` is one of `Low`, `Normal` (default), `Medium`,
+/// `High` or `Critical`; `depth` (default 0) counts towards the session's maximum
+/// depth; `group` names a seed group (the default group when absent); `metadata` is
+/// copied into the results like seed metadata. Unknown fields are ignored, so the
+/// format can grow without breaking producers.
+///
+/// Producers must append whole lines terminated by `\n`. A trailing line without a
+/// newline is treated as still being written and read on a later poll. Lines that
+/// are not valid entries are logged and skipped. If the file shrinks (rotated or
+/// truncated), it is read again from the start.
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+use crate::core::TaskPriority;
+use crate::core::types::url_serde;
+
+/// How often a running crawl checks the frontier file for new lines
+pub const DEFAULT_FRONTIER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One line of the frontier file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrontierEntry {
+    #[serde(with = "url_serde")]
+    pub url: Url,
+    #[serde(default = "default_priority")]
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub depth: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+fn default_priority() -> TaskPriority {
+    TaskPriority::Normal
+}
+
+impl FrontierEntry {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            priority: default_priority(),
+            depth: 0,
+            group: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// The entry as a line for appending to a frontier file
+    pub fn to_line(&self) -> Result<String, Error> {
+        Ok(format!("{}\n", serde_json::to_string(self)?))
+    }
+}
+
+/// Reads the entries appended to a frontier file since the last poll
+pub struct FrontierTail {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl FrontierTail {
+    /// Tail `path` from its start; a missing file counts as empty until created
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            offset: 0,
+        }
+    }
+
+    /// Complete lines appended since the last poll, parsed
+    ///
+    /// Invalid lines come back as errors naming the line so the caller can log them.
+    pub fn poll(&mut self) -> Result<Vec<Result<FrontierEntry, String>>, Error> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+
+        // Leave a line that is still being written for the next poll
+        let Some(end) = appended.iter().rposition(|byte| *byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        self.offset += end as u64 + 1;
+
+        Ok(String::from_utf8_lossy(&appended[..end])
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str::<FrontierEntry>(line)
+                    .map_err(|e| format!("invalid frontier entry '{}': {}", line, e))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_only_complete_appended_lines_are_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frontier.jsonl");
+        let mut tail = FrontierTail::new(&path);
+        assert!(tail.poll().unwrap().is_empty());
+
+        let mut entry = FrontierEntry::new(Url::parse("https://a.example/").unwrap());
+        entry.priority = TaskPriority::High;
+        entry
+            .metadata
+            .insert("source".to_string(), "csv-row-123".to_string());
+        let mut file = std::fs::File::create(&path).unwrap();
+        write!(
+            file,
+            "{}not json\n{{\"url\":\"https://b.example/\"",
+            entry.to_line().unwrap()
+        )
+        .unwrap();
+
+        let first = tail.poll().unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0], Ok(entry));
+        assert!(first[1].is_err());
+
+        writeln!(file, ",\"depth\":2}}").unwrap();
+        let second = tail.poll().unwrap();
+        let entry = second[0].as_ref().unwrap();
+        assert_eq!(entry.url.as_str(), "https://b.example/");
+        assert_eq!((entry.priority, entry.depth), (TaskPriority::Normal, 2));
+        assert!(tail.poll().unwrap().is_empty());
+    }
+}
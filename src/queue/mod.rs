@@ -1,8 +1,15 @@
 // Task queue management and caching utilities
 
 pub mod cache;
+pub mod fairness;
+pub mod frontier_file;
 pub mod task_queue;
 
 // Re-export queue components
 pub use cache::TtlCache;
-pub use task_queue::TaskQueue;
+pub use fairness::{DEFAULT_GROUP, SeedGroup};
+pub use frontier_file::{DEFAULT_FRONTIER_POLL_INTERVAL, FrontierEntry, FrontierTail};
+pub use task_queue::{
+    BatchEnqueueEntry, BatchEnqueueReport, DomainQueueSnapshot, EnqueueOutcome, QueueSnapshot,
+    QueueState, QueuedTaskSummary, TaskQueue,
+};
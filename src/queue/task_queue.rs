@@ -1,15 +1,21 @@
-use crate::core::types::TaskContent;
-use crate::core::{CrawlTask, QueueStats, TaskPriority, TaskResult, TaskStatus};
+use super::fairness::{DEFAULT_GROUP, WeightedGroups};
+use super::frontier_file::{FrontierEntry, FrontierTail};
+use crate::core::types::{TaskContent, TaskCounts};
+use crate::core::{
+    CrawlTask, GroupQueueStats, PaginationChain, PublicSuffixes, QueueStats, Region, SeedScope,
+    TaskPriority, TaskResult, TaskStatus,
+};
+use crate::processing::LinkImportance;
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
-use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::sync::{RwLock, Semaphore, mpsc};
 use tokio::time::sleep;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use url::Url;
 
 /// Serializable queue state for persistence
@@ -20,12 +26,134 @@ pub struct QueueState {
     pub retry_queue: Vec<CrawlTask>,
     pub stats: QueueStats,
     pub timestamp: std::time::SystemTime,
+    /// Dequeue shares of the seed groups given a weight
+    #[serde(default)]
+    pub group_weights: HashMap<String, u32>,
+}
+
+/// Point-in-time view of the queue for debugging stalled crawls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    /// Task counts grouped by domain, sorted by domain name
+    pub domains: Vec<DomainQueueSnapshot>,
+    /// Tasks that would be dequeued next, in dequeue order
+    pub next_tasks: Vec<QueuedTaskSummary>,
+    /// Retry tasks that are still waiting for their backoff to expire
+    pub waiting_retries: usize,
+    pub stats: QueueStats,
+    pub timestamp: std::time::SystemTime,
+}
+
+/// Per-domain breakdown of a `QueueSnapshot`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainQueueSnapshot {
+    pub domain: String,
+    pub pending: usize,
+    pub in_progress: usize,
+    pub retrying: usize,
+    pub dead: usize,
+}
+
+/// Lightweight description of a queued task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTaskSummary {
+    pub id: String,
+    pub url: String,
+    pub priority: TaskPriority,
+    pub status: TaskStatus,
+    pub attempt_count: u32,
+    pub depth: usize,
+    pub error_message: Option<String>,
+}
+
+impl From<&CrawlTask> for QueuedTaskSummary {
+    fn from(task: &CrawlTask) -> Self {
+        Self {
+            id: task.id.clone(),
+            url: task.url.to_string(),
+            priority: task.priority,
+            status: task.status.clone(),
+            attempt_count: task.attempt_count,
+            depth: task.depth,
+            error_message: task.error_message.clone(),
+        }
+    }
+}
+
+/// What happened to one URL of a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum EnqueueOutcome {
+    /// Entered the frontier as a new task
+    Accepted { task_id: String },
+    /// Already queued, in progress or finished, or listed earlier in the batch;
+    /// `task_id` is the task that has the URL
+    Duplicate { task_id: String },
+    /// Outside the hosts the queue was scoped to
+    OutOfScope,
+    /// Its site already has as many URLs as the queue's per-site budget allows
+    DomainBudgetExceeded { domain: String },
+    /// Not an absolute HTTP(S) URL
+    Invalid { error: String },
+}
+
+/// Outcome of one URL of a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchEnqueueEntry {
+    /// The URL as given
+    pub url: String,
+    #[serde(flatten)]
+    pub outcome: EnqueueOutcome,
+}
+
+/// Per-URL outcomes of `TaskQueue::enqueue_batch`, in input order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchEnqueueReport {
+    pub entries: Vec<BatchEnqueueEntry>,
+    pub accepted: usize,
+    pub duplicates: usize,
+    pub out_of_scope: usize,
+    #[serde(default)]
+    pub over_budget: usize,
+    pub invalid: usize,
+}
+
+impl BatchEnqueueReport {
+    fn push(&mut self, url: String, outcome: EnqueueOutcome) {
+        match &outcome {
+            EnqueueOutcome::Accepted { .. } => self.accepted += 1,
+            EnqueueOutcome::Duplicate { .. } => self.duplicates += 1,
+            EnqueueOutcome::OutOfScope => self.out_of_scope += 1,
+            EnqueueOutcome::DomainBudgetExceeded { .. } => self.over_budget += 1,
+            EnqueueOutcome::Invalid { .. } => self.invalid += 1,
+        }
+        self.entries.push(BatchEnqueueEntry { url, outcome });
+    }
+
+    /// IDs of the tasks created, in input order
+    pub fn task_ids(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match &entry.outcome {
+                EnqueueOutcome::Accepted { task_id } => Some(task_id.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Entries of URLs that did not enter the frontier
+    pub fn rejected(&self) -> impl Iterator<Item = &BatchEnqueueEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| !matches!(entry.outcome, EnqueueOutcome::Accepted { .. }))
+    }
 }
 
 /// Message queue for managing crawl tasks with priority and retry logic
 pub struct TaskQueue {
-    // Priority queue for pending tasks (BinaryHeap is max-heap, so higher priority first)
-    pending_tasks: Arc<RwLock<BinaryHeap<PrioritizedTask>>>,
+    // Priority queues for pending tasks per seed group, dequeued by weighted round-robin.
+    // Every move of a task between the collections below holds this write lock, and
+    // locks are taken in the order pending, retry, in-progress, completed/failed, stats
+    pending_tasks: Arc<RwLock<WeightedGroups<PrioritizedTask>>>,
 
     // Tasks currently being processed
     in_progress_tasks: Arc<RwLock<HashMap<String, CrawlTask>>>,
@@ -46,9 +174,20 @@ pub struct TaskQueue {
 
     // Statistics
     stats: Arc<RwLock<QueueStats>>,
+    // Completed and dead counts per seed group
+    group_counts: Arc<RwLock<BTreeMap<String, TaskCounts>>>,
 
     // Configuration
     max_retries: u32,
+    // Hosts batch-enqueued and frontier-file URLs must belong to; empty allows any host
+    scopes: Vec<SeedScope>,
+    // Link-graph importance of discovered URLs, when links are scored
+    importance: Option<Arc<RwLock<LinkImportance>>>,
+    // Most URLs queued per registrable domain, seeds included; None is unbounded
+    domain_budget: Option<usize>,
+    domain_urls: Arc<RwLock<HashMap<String, usize>>>,
+    // Suffix list and site overrides the domain budget keys by
+    suffixes: Arc<PublicSuffixes>,
     base_retry_delay: Duration,
     max_retry_delay: Duration,
     backoff_multiplier: f64,
@@ -80,6 +219,7 @@ impl Ord for PrioritizedTask {
         self.task
             .priority
             .cmp(&other.task.priority)
+            .then_with(|| self.task.importance.total_cmp(&other.task.importance))
             .then_with(|| other.task.created_at().cmp(&self.task.created_at()))
     }
 }
@@ -90,7 +230,7 @@ impl TaskQueue {
         let (result_sender, result_receiver) = mpsc::unbounded_channel();
 
         Self {
-            pending_tasks: Arc::new(RwLock::new(BinaryHeap::new())),
+            pending_tasks: Arc::new(RwLock::new(WeightedGroups::default())),
             in_progress_tasks: Arc::new(RwLock::new(HashMap::new())),
             completed_tasks: Arc::new(RwLock::new(Vec::new())),
             failed_tasks: Arc::new(RwLock::new(Vec::new())),
@@ -99,23 +239,197 @@ impl TaskQueue {
             result_receiver: Arc::new(RwLock::new(Some(result_receiver))),
             semaphore: Arc::new(Semaphore::new(max_concurrent_tasks)),
             stats: Arc::new(RwLock::new(QueueStats::default())),
+            group_counts: Arc::new(RwLock::new(BTreeMap::new())),
             max_retries,
+            scopes: Vec::new(),
+            importance: None,
+            domain_budget: None,
+            domain_urls: Arc::new(RwLock::new(HashMap::new())),
+            suffixes: Arc::new(PublicSuffixes::builtin()),
             base_retry_delay: Duration::from_millis(1000),
             max_retry_delay: Duration::from_millis(30000),
             backoff_multiplier: 2.0,
         }
     }
 
+    /// Accept batch-enqueued and frontier-file URLs only on the hosts of `scopes`
+    pub fn with_scopes(mut self, scopes: Vec<SeedScope>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Queue at most `max` URLs per site, keyed by registrable domain
+    ///
+    /// Seeds and restored tasks count towards their site's budget but are always
+    /// queued; discovered links and batch-enqueued URLs over it are turned away, so
+    /// seeds on sibling hosts of one site share a single budget.
+    pub fn with_domain_budget(mut self, max: usize) -> Self {
+        self.domain_budget = Some(max);
+        self
+    }
+
+    /// Key the domain budget by `suffixes` instead of the bundled list, e.g. the
+    /// crawler's list with its internal domains as sites of their own
+    pub fn with_public_suffixes(mut self, suffixes: Arc<PublicSuffixes>) -> Self {
+        self.suffixes = suffixes;
+        self
+    }
+
+    /// Count `url` against its site's budget; when `enforce` and the budget is
+    /// spent, the URL is not counted and the site's domain is returned
+    async fn count_domain_url(&self, url: &Url, enforce: bool) -> Result<(), String> {
+        if self.domain_budget.is_none() {
+            return Ok(());
+        }
+        let domain = self
+            .suffixes
+            .registrable_domain(url.host_str().unwrap_or(""));
+        let mut domain_urls = self.domain_urls.write().await;
+        let count = domain_urls.entry(domain.clone()).or_default();
+        if enforce && self.domain_budget.is_some_and(|max| *count >= max) {
+            return Err(domain);
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Order discovered links of equal priority by their link-graph importance
+    ///
+    /// Importance is estimated online with OPIC (see `LinkImportance`): pages pass
+    /// their cash on to the links given to `distribute_importance`, and a link is
+    /// scored when it is enqueued.
+    pub fn with_link_importance(mut self) -> Self {
+        self.importance = Some(Arc::new(RwLock::new(LinkImportance::new())));
+        self
+    }
+
+    /// Pass the importance of `page` on to the URLs it links to
+    pub async fn distribute_importance<'a>(
+        &self,
+        page: &Url,
+        links: impl IntoIterator<Item = &'a Url>,
+    ) {
+        if let Some(importance) = &self.importance {
+            importance
+                .write()
+                .await
+                .distribute(page.as_str(), links.into_iter().map(Url::as_str));
+        }
+    }
+
+    async fn importance_of(&self, url: &Url) -> f64 {
+        match &self.importance {
+            Some(importance) => importance.read().await.score(url.as_str()),
+            None => 0.0,
+        }
+    }
+
     /// Add a new task to the queue
     pub async fn enqueue_task(&self, url: Url, priority: TaskPriority) -> Result<String, Error> {
-        let task = CrawlTask::new(url, priority, self.max_retries);
-        let task_id = task.id.clone();
+        self.push_task(CrawlTask::new(url, priority, self.max_retries))
+            .await
+    }
+
+    /// Add a seed to a seed group, with metadata carried into its results and the
+    /// seed's own scope and region, if it has them
+    pub async fn enqueue_grouped(
+        &self,
+        url: Url,
+        priority: TaskPriority,
+        group: &str,
+        metadata: BTreeMap<String, String>,
+        scope: Option<SeedScope>,
+        region: Option<Region>,
+    ) -> Result<String, Error> {
+        let mut task = CrawlTask::new(url, priority, self.max_retries);
+        task.group = Some(group.to_string());
+        task.metadata = metadata;
+        task.scope = scope;
+        task.region = region;
+        self.push_task(task).await
+    }
+
+    /// Add a task read from a frontier file
+    pub async fn enqueue_entry(&self, entry: FrontierEntry) -> Result<String, Error> {
+        self.push_task(self.entry_task(entry)).await
+    }
+
+    fn entry_task(&self, entry: FrontierEntry) -> CrawlTask {
+        let mut task =
+            CrawlTask::new_with_depth(entry.url, entry.priority, self.max_retries, entry.depth);
+        task.group = entry.group;
+        task.metadata = entry.metadata;
+        task
+    }
+
+    /// Set a seed group's share of dequeues relative to the other groups (default 1)
+    pub async fn set_group_weight(&self, group: &str, weight: u32) {
+        self.pending_tasks.write().await.set_weight(group, weight);
+    }
 
-        let prioritized_task = PrioritizedTask { task };
+    /// Add a link discovered on `parent`'s page, carrying its depth and provenance
+    ///
+    /// The link is turned away when it leaves the scope of `parent`'s seed or
+    /// its site's URL budget is spent.
+    pub async fn enqueue_discovered(
+        &self,
+        parent: &CrawlTask,
+        url: Url,
+        priority: TaskPriority,
+        link_category: Option<String>,
+    ) -> Result<EnqueueOutcome, Error> {
+        let mut task = CrawlTask::discovered_from(parent, url, priority, link_category);
+        task.max_retries = self.max_retries;
+        task.importance = self.importance_of(&task.url).await;
+        self.push_budgeted(task).await
+    }
+
+    /// Add the next page of a paginated series as a task in `chain`, within the
+    /// same limits as `enqueue_discovered`
+    pub async fn enqueue_next_page(
+        &self,
+        parent: &CrawlTask,
+        url: Url,
+        chain: PaginationChain,
+    ) -> Result<EnqueueOutcome, Error> {
+        let mut task = CrawlTask::discovered_from(
+            parent,
+            url,
+            TaskPriority::Normal,
+            Some("Pagination".to_string()),
+        );
+        task.max_retries = self.max_retries;
+        task.pagination = Some(chain);
+        task.importance = self.importance_of(&task.url).await;
+        self.push_budgeted(task).await
+    }
+
+    async fn push_budgeted(&self, task: CrawlTask) -> Result<EnqueueOutcome, Error> {
+        // Links of a scoped seed stay within the seed's own depth and domain limits
+        if let Some(scope) = &task.scope
+            && !(scope.allows_depth(task.depth) && scope.allows_url(&task.url))
+        {
+            return Ok(EnqueueOutcome::OutOfScope);
+        }
+        if let Err(domain) = self.count_domain_url(&task.url, true).await {
+            return Ok(EnqueueOutcome::DomainBudgetExceeded { domain });
+        }
+        let task_id = self.insert_task(task).await?;
+        Ok(EnqueueOutcome::Accepted { task_id })
+    }
+
+    async fn push_task(&self, task: CrawlTask) -> Result<String, Error> {
+        // Seeds always enter the frontier, but use up their site's budget
+        let _ = self.count_domain_url(&task.url, false).await;
+        self.insert_task(task).await
+    }
+
+    async fn insert_task(&self, task: CrawlTask) -> Result<String, Error> {
+        let task_id = task.id.clone();
 
         {
             let mut pending = self.pending_tasks.write().await;
-            pending.push(prioritized_task);
+            push_pending(&mut pending, task);
         }
 
         // Update stats
@@ -128,35 +442,126 @@ impl TaskQueue {
         Ok(task_id)
     }
 
-    /// Add multiple tasks at once
-    pub async fn enqueue_batch(
+    /// Add multiple tasks at once, reporting what happened to every URL
+    ///
+    /// URLs that fail to parse, fall outside the queue's scopes or are already
+    /// known to the queue (pending, in progress, retrying or finished, or earlier
+    /// in the batch), and URLs of sites whose budget is spent, are reported instead
+    /// of enqueued.
+    pub async fn enqueue_batch<S: AsRef<str>>(
         &self,
-        urls: Vec<(Url, TaskPriority)>,
-    ) -> Result<Vec<String>, Error> {
-        let mut task_ids = Vec::new();
-
-        {
-            let mut pending = self.pending_tasks.write().await;
-            let mut stats = self.stats.write().await;
+        urls: impl IntoIterator<Item = (S, TaskPriority)>,
+    ) -> BatchEnqueueReport {
+        let tasks = urls
+            .into_iter()
+            .map(|(raw, priority)| {
+                let raw = raw.as_ref();
+                let task = match Url::parse(raw.trim()) {
+                    Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => {
+                        Ok(CrawlTask::new(url, priority, self.max_retries))
+                    }
+                    Ok(url) => Err(format!("Unsupported URL scheme '{}'", url.scheme())),
+                    Err(e) => Err(e.to_string()),
+                };
+                (raw.to_string(), task)
+            })
+            .collect();
+        self.enqueue_tasks(tasks).await
+    }
 
-            for (url, priority) in urls {
-                let task = CrawlTask::new(url, priority, self.max_retries);
-                let task_id = task.id.clone();
-                task_ids.push(task_id);
+    /// Add tasks read from a frontier file, reporting what happened to every entry
+    /// as `enqueue_batch` does
+    pub async fn enqueue_entries(&self, entries: Vec<FrontierEntry>) -> BatchEnqueueReport {
+        let tasks = entries
+            .into_iter()
+            .map(|entry| (entry.url.to_string(), Ok(self.entry_task(entry))))
+            .collect();
+        self.enqueue_tasks(tasks).await
+    }
 
-                let prioritized_task = PrioritizedTask { task };
-                pending.push(prioritized_task);
+    async fn enqueue_tasks(
+        &self,
+        tasks: Vec<(String, Result<CrawlTask, String>)>,
+    ) -> BatchEnqueueReport {
+        let mut report = BatchEnqueueReport::default();
+        let batch: HashSet<&str> = tasks
+            .iter()
+            .filter_map(|(_, task)| task.as_ref().ok())
+            .map(|task| task.url.as_str())
+            .collect();
 
-                stats.counts.total += 1;
-                stats.counts.pending += 1;
+        // Tasks only move between collections under the pending lock, so holding it
+        // keeps every task in sight while the queue is searched for the batch's URLs
+        let mut pending = self.pending_tasks.write().await;
+        let mut known: HashMap<String, String> = HashMap::new();
+        let mut remember = |task: &CrawlTask| {
+            if batch.contains(task.url.as_str()) {
+                known
+                    .entry(task.url.to_string())
+                    .or_insert_with(|| task.id.clone());
+            }
+        };
+        pending
+            .iter()
+            .for_each(|prioritized| remember(&prioritized.task));
+        self.retry_queue.read().await.iter().for_each(&mut remember);
+        self.in_progress_tasks
+            .read()
+            .await
+            .values()
+            .for_each(&mut remember);
+        self.completed_tasks
+            .read()
+            .await
+            .iter()
+            .for_each(&mut remember);
+        self.failed_tasks
+            .read()
+            .await
+            .iter()
+            .for_each(&mut remember);
+
+        let mut stats = self.stats.write().await;
+        for (raw, task) in &tasks {
+            let task = match task {
+                Ok(task) => task,
+                Err(error) => {
+                    let error = error.clone();
+                    report.push(raw.clone(), EnqueueOutcome::Invalid { error });
+                    continue;
+                }
+            };
+            if !self.scopes.is_empty()
+                && !self.scopes.iter().any(|scope| scope.allows_url(&task.url))
+            {
+                report.push(raw.clone(), EnqueueOutcome::OutOfScope);
+                continue;
+            }
+            if let Some(task_id) = known.get(task.url.as_str()) {
+                let task_id = task_id.clone();
+                report.push(raw.clone(), EnqueueOutcome::Duplicate { task_id });
+                continue;
+            }
+            if let Err(domain) = self.count_domain_url(&task.url, true).await {
+                report.push(raw.clone(), EnqueueOutcome::DomainBudgetExceeded { domain });
+                continue;
             }
+
+            let task_id = task.id.clone();
+            known.insert(task.url.to_string(), task_id.clone());
+            push_pending(&mut pending, task.clone());
+            stats.counts.total += 1;
+            stats.counts.pending += 1;
+            report.push(raw.clone(), EnqueueOutcome::Accepted { task_id });
         }
 
-        Ok(task_ids)
+        report
     }
 
     /// Get the next task to process
     pub async fn dequeue_task(&self) -> Option<CrawlTask> {
+        let mut pending = self.pending_tasks.write().await;
+
         // First check retry queue for ready tasks
         {
             let mut retry_queue = self.retry_queue.write().await;
@@ -186,27 +591,24 @@ impl TaskQueue {
         }
 
         // Then check pending tasks
-        {
-            let mut pending = self.pending_tasks.write().await;
-            if let Some(prioritized_task) = pending.pop() {
-                let mut task = prioritized_task.task;
-                task.mark_in_progress();
-
-                // Move to in-progress
-                {
-                    let mut in_progress = self.in_progress_tasks.write().await;
-                    in_progress.insert(task.id.clone(), task.clone());
-                }
+        if let Some(prioritized_task) = pending.pop() {
+            let mut task = prioritized_task.task;
+            task.mark_in_progress();
 
-                // Update stats
-                {
-                    let mut stats = self.stats.write().await;
-                    stats.counts.pending = stats.counts.pending.saturating_sub(1);
-                    stats.counts.in_progress += 1;
-                }
+            // Move to in-progress
+            {
+                let mut in_progress = self.in_progress_tasks.write().await;
+                in_progress.insert(task.id.clone(), task.clone());
+            }
 
-                return Some(task);
+            // Update stats
+            {
+                let mut stats = self.stats.write().await;
+                stats.counts.pending = stats.counts.pending.saturating_sub(1);
+                stats.counts.in_progress += 1;
             }
+
+            return Some(task);
         }
 
         None
@@ -219,6 +621,8 @@ impl TaskQueue {
         content: Option<String>,
         processing_time: Duration,
     ) -> Result<(), Error> {
+        // Held until the task is in the completed list
+        let _pending = self.pending_tasks.write().await;
         let task = {
             let mut in_progress = self.in_progress_tasks.write().await;
             in_progress.remove(task_id)
@@ -243,6 +647,13 @@ impl TaskQueue {
 
             let _ = self.result_sender.send(result);
 
+            self.group_counts
+                .write()
+                .await
+                .entry(task_group(&task).to_string())
+                .or_default()
+                .completed += 1;
+
             // Move to completed
             {
                 let mut completed = self.completed_tasks.write().await;
@@ -274,6 +685,20 @@ impl TaskQueue {
         Ok(())
     }
 
+    /// Put an interrupted in-progress task back in the pending queue without counting an attempt
+    pub async fn requeue_task(&self, task_id: &str) {
+        let mut pending = self.pending_tasks.write().await;
+        let task = self.in_progress_tasks.write().await.remove(task_id);
+        if let Some(mut task) = task {
+            task.status = TaskStatus::Pending;
+            push_pending(&mut pending, task);
+
+            let mut stats = self.stats.write().await;
+            stats.counts.in_progress = stats.counts.in_progress.saturating_sub(1);
+            stats.counts.pending += 1;
+        }
+    }
+
     /// Mark a task as failed and potentially retry
     pub async fn fail_task(
         &self,
@@ -281,6 +706,28 @@ impl TaskQueue {
         error: String,
         processing_time: Duration,
     ) -> Result<(), Error> {
+        self.fail(task_id, error, processing_time, false).await
+    }
+
+    /// Mark a task as failed for good (e.g. 410 Gone), skipping its remaining retries
+    pub async fn fail_task_permanently(
+        &self,
+        task_id: &str,
+        error: String,
+        processing_time: Duration,
+    ) -> Result<(), Error> {
+        self.fail(task_id, error, processing_time, true).await
+    }
+
+    async fn fail(
+        &self,
+        task_id: &str,
+        error: String,
+        processing_time: Duration,
+        permanent: bool,
+    ) -> Result<(), Error> {
+        // Held until the task is in the retry queue or the failed list
+        let _pending = self.pending_tasks.write().await;
         let task = {
             let mut in_progress = self.in_progress_tasks.write().await;
             in_progress.remove(task_id)
@@ -299,7 +746,11 @@ impl TaskQueue {
                 None
             };
 
-            task.mark_failed(error.clone(), retry_delay);
+            if permanent {
+                task.mark_permanently_failed(error.clone());
+            } else {
+                task.mark_failed(error.clone(), retry_delay);
+            }
 
             // Send result
             let result = TaskResult {
@@ -323,6 +774,13 @@ impl TaskQueue {
                 stats.counts.in_progress = stats.counts.in_progress.saturating_sub(1);
                 stats.counts.retrying += 1;
             } else {
+                self.group_counts
+                    .write()
+                    .await
+                    .entry(task_group(&task).to_string())
+                    .or_default()
+                    .dead += 1;
+
                 // Task is dead, move to failed
                 let mut failed = self.failed_tasks.write().await;
                 failed.push(task);
@@ -344,9 +802,47 @@ impl TaskQueue {
         Ok(())
     }
 
-    /// Get current queue statistics
+    /// Get current queue statistics, including per-group counts
     pub async fn get_stats(&self) -> QueueStats {
-        self.stats.read().await.clone()
+        let mut stats = self.stats.read().await.clone();
+        stats.groups = self.group_stats().await;
+        stats
+    }
+
+    async fn group_stats(&self) -> Vec<GroupQueueStats> {
+        let mut groups = self.group_counts.read().await.clone();
+        let pending = self.pending_tasks.read().await;
+        for (group, count) in pending.group_lengths() {
+            groups.entry(group.to_string()).or_default().pending = count as u64;
+        }
+        for task in self.in_progress_tasks.read().await.values() {
+            groups
+                .entry(task_group(task).to_string())
+                .or_default()
+                .in_progress += 1;
+        }
+        for task in self.retry_queue.read().await.iter() {
+            groups
+                .entry(task_group(task).to_string())
+                .or_default()
+                .retrying += 1;
+        }
+
+        groups
+            .into_iter()
+            .map(|(group, mut counts)| {
+                counts.total = counts.pending
+                    + counts.in_progress
+                    + counts.retrying
+                    + counts.completed
+                    + counts.dead;
+                GroupQueueStats {
+                    weight: pending.weight(&group),
+                    group,
+                    counts,
+                }
+            })
+            .collect()
     }
 
     /// Get number of pending tasks
@@ -368,6 +864,11 @@ impl TaskQueue {
             .count()
     }
 
+    /// Get number of tasks waiting for a retry, ready or not
+    pub async fn retry_count(&self) -> usize {
+        self.retry_queue.read().await.len()
+    }
+
     /// Check if queue has any work available
     pub async fn has_work(&self) -> bool {
         let pending_count = self.pending_count().await;
@@ -400,6 +901,7 @@ impl TaskQueue {
 
     /// Process retry queue to move ready tasks back to pending
     pub async fn process_retry_queue(&self) {
+        let mut pending = self.pending_tasks.write().await;
         let mut ready_tasks = Vec::new();
 
         {
@@ -418,12 +920,10 @@ impl TaskQueue {
         }
 
         if !ready_tasks.is_empty() {
-            let mut pending = self.pending_tasks.write().await;
             let mut stats = self.stats.write().await;
 
             for task in ready_tasks {
-                let prioritized_task = PrioritizedTask { task };
-                pending.push(prioritized_task);
+                push_pending(&mut pending, task);
 
                 stats.counts.retrying = stats.counts.retrying.saturating_sub(1);
                 stats.counts.pending += 1;
@@ -486,15 +986,87 @@ impl TaskQueue {
         long_running
     }
 
-    /// Save queue state to file for crash recovery
-    pub async fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
-        let pending: Vec<CrawlTask> = self
-            .pending_tasks
+    /// URLs of the next `next_n` tasks to be dequeued, ready retries first
+    pub async fn upcoming_urls(&self, next_n: usize) -> Vec<Url> {
+        let mut urls: Vec<Url> = self
+            .retry_queue
             .read()
             .await
             .iter()
-            .map(|pt| pt.task.clone())
+            .filter(|task| task.is_ready_for_retry())
+            .take(next_n)
+            .map(|task| task.url.clone())
             .collect();
+        if urls.len() < next_n {
+            let pending = self.pending_tasks.read().await;
+            urls.extend(
+                pending
+                    .peek(next_n - urls.len())
+                    .into_iter()
+                    .map(|prioritized| prioritized.task.url.clone()),
+            );
+        }
+        urls
+    }
+
+    /// Capture per-domain task counts and the next `next_n` tasks to be dequeued
+    pub async fn snapshot(&self, next_n: usize) -> QueueSnapshot {
+        let mut domains: BTreeMap<String, DomainQueueSnapshot> = BTreeMap::new();
+        let mut next_tasks = Vec::new();
+        let mut waiting_retries = 0;
+
+        // Ready retries are dequeued before pending tasks
+        {
+            let retry_queue = self.retry_queue.read().await;
+            for task in retry_queue.iter() {
+                domain_snapshot(&mut domains, &task.url).retrying += 1;
+                if !task.is_ready_for_retry() {
+                    waiting_retries += 1;
+                } else if next_tasks.len() < next_n {
+                    next_tasks.push(QueuedTaskSummary::from(task));
+                }
+            }
+        }
+
+        {
+            let pending = self.pending_tasks.read().await;
+            for prioritized in pending.iter() {
+                domain_snapshot(&mut domains, &prioritized.task.url).pending += 1;
+            }
+
+            let remaining = next_n.saturating_sub(next_tasks.len());
+            next_tasks.extend(
+                pending
+                    .peek(remaining)
+                    .into_iter()
+                    .map(|prioritized| QueuedTaskSummary::from(&prioritized.task)),
+            );
+        }
+
+        for task in self.in_progress_tasks.read().await.values() {
+            domain_snapshot(&mut domains, &task.url).in_progress += 1;
+        }
+
+        for task in self.failed_tasks.read().await.iter() {
+            domain_snapshot(&mut domains, &task.url).dead += 1;
+        }
+
+        QueueSnapshot {
+            domains: domains.into_values().collect(),
+            next_tasks,
+            waiting_retries,
+            stats: self.get_stats().await,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    /// Pending, in-progress and retrying tasks with the queue statistics
+    pub async fn export_state(&self) -> QueueState {
+        let (pending, group_weights) = {
+            let pending_tasks = self.pending_tasks.read().await;
+            let pending: Vec<CrawlTask> = pending_tasks.iter().map(|pt| pt.task.clone()).collect();
+            (pending, pending_tasks.weights().clone())
+        };
 
         let in_progress: Vec<CrawlTask> = self
             .in_progress_tasks
@@ -508,15 +1080,19 @@ impl TaskQueue {
 
         let stats = self.stats.read().await.clone();
 
-        let state = QueueState {
+        QueueState {
             pending_tasks: pending,
             in_progress_tasks: in_progress,
             retry_queue,
             stats,
             timestamp: std::time::SystemTime::now(),
-        };
+            group_weights,
+        }
+    }
 
-        let json = serde_json::to_string_pretty(&state)?;
+    /// Save queue state to file for crash recovery
+    pub async fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(&self.export_state().await)?;
         fs::write(path, json).await?;
 
         Ok(())
@@ -526,13 +1102,33 @@ impl TaskQueue {
     pub async fn load_state<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
         let content = fs::read_to_string(path).await?;
         let state: QueueState = serde_json::from_str(&content)?;
+        self.restore_state(state).await;
+        Ok(())
+    }
 
-        // Restore pending tasks
+    /// Replace the queue contents with a saved state
+    ///
+    /// Tasks that were in progress when the state was saved are pending again.
+    pub async fn restore_state(&self, state: QueueState) {
+        self.domain_urls.write().await.clear();
+        for task in state
+            .pending_tasks
+            .iter()
+            .chain(&state.retry_queue)
+            .chain(&state.in_progress_tasks)
+        {
+            let _ = self.count_domain_url(&task.url, false).await;
+        }
+
+        // Restore pending tasks and the groups' weights
         {
             let mut pending = self.pending_tasks.write().await;
             pending.clear();
+            for (group, weight) in &state.group_weights {
+                pending.set_weight(group, *weight);
+            }
             for task in state.pending_tasks {
-                pending.push(PrioritizedTask { task });
+                push_pending(&mut pending, task);
             }
         }
 
@@ -551,21 +1147,20 @@ impl TaskQueue {
             for mut task in state.in_progress_tasks {
                 task.status = TaskStatus::Pending;
                 task.attempt_count = 0; // Reset attempt count for interrupted tasks
-                pending.push(PrioritizedTask { task });
+                push_pending(&mut pending, task);
             }
         }
 
         // Restore stats (but reset current counts as they may be stale)
         {
+            let pending_count = self.pending_tasks.read().await.len() as u64;
             let mut stats = self.stats.write().await;
             *stats = state.stats;
-            let pending_count = self.pending_tasks.read().await.len() as u64;
             stats.counts.pending = pending_count;
             stats.counts.in_progress = 0; // No tasks in progress after recovery
         }
 
         info!("Queue state restored from checkpoint");
-        Ok(())
     }
 
     /// Create a new TaskQueue with persistence support
@@ -594,6 +1189,74 @@ impl TaskQueue {
             }
         });
     }
+
+    /// Enqueue the entries other tools append to a JSONL frontier file
+    ///
+    /// The file is polled every `interval` until the returned task is aborted; see
+    /// `frontier_file` for the format.
+    pub fn tail_frontier_file<P: AsRef<Path>>(
+        queue: Arc<TaskQueue>,
+        path: P,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let mut tail = FrontierTail::new(path.as_ref());
+
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+            loop {
+                interval_timer.tick().await;
+
+                let entries = match tail.poll() {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        error!("Failed to read frontier file: {}", e);
+                        continue;
+                    }
+                };
+                let mut batch = Vec::new();
+                for entry in entries {
+                    match entry {
+                        Ok(entry) => batch.push(entry),
+                        Err(e) => warn!("Skipping {}", e),
+                    }
+                }
+                if batch.is_empty() {
+                    continue;
+                }
+                let report = queue.enqueue_entries(batch).await;
+                for rejected in report.rejected() {
+                    debug!(
+                        "Frontier entry {} not enqueued: {:?}",
+                        rejected.url, rejected.outcome
+                    );
+                }
+            }
+        })
+    }
+}
+
+/// Seed group of a task, `DEFAULT_GROUP` when untagged
+fn task_group(task: &CrawlTask) -> &str {
+    task.group.as_deref().unwrap_or(DEFAULT_GROUP)
+}
+
+fn push_pending(pending: &mut WeightedGroups<PrioritizedTask>, task: CrawlTask) {
+    let group = task_group(&task).to_string();
+    pending.push(&group, PrioritizedTask { task });
+}
+
+/// Get or create the snapshot entry for the URL's domain
+fn domain_snapshot<'a>(
+    domains: &'a mut BTreeMap<String, DomainQueueSnapshot>,
+    url: &Url,
+) -> &'a mut DomainQueueSnapshot {
+    let domain = url.host_str().unwrap_or("unknown");
+    domains
+        .entry(domain.to_string())
+        .or_insert_with(|| DomainQueueSnapshot {
+            domain: domain.to_string(),
+            ..Default::default()
+        })
 }
 
 /// Background task processor that continuously processes the queue
@@ -668,3 +1331,359 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_a_requeued_task_is_dequeued_again() {
+        let queue = TaskQueue::new(2, 1);
+        let url = Url::parse("https://example.com/").unwrap();
+        queue
+            .enqueue_task(url.clone(), TaskPriority::Normal)
+            .await
+            .unwrap();
+
+        let task = queue.dequeue_task().await.unwrap();
+        assert!(queue.dequeue_task().await.is_none());
+        queue.requeue_task(&task.id).await;
+
+        assert_eq!(queue.in_progress_count().await, 0);
+        assert_eq!(queue.pending_count().await, 1);
+        let stats = queue.get_stats().await;
+        assert_eq!((stats.counts.pending, stats.counts.in_progress), (1, 0));
+        assert_eq!(queue.dequeue_task().await.unwrap().id, task.id);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_a_task_moving_between_queues_is_never_enqueued_again() {
+        let mut queue = TaskQueue::new(2, u32::MAX);
+        queue.base_retry_delay = Duration::ZERO;
+        let queue = Arc::new(queue);
+        let url = "https://example.com/moving";
+        queue.enqueue_batch([(url, TaskPriority::Normal)]).await;
+
+        // The task cycles pending -> in progress -> retry -> (in progress | pending)
+        let mover = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move {
+                for round in 0..2000 {
+                    let task = queue.dequeue_task().await.unwrap();
+                    queue
+                        .fail_task(&task.id, "retry".to_string(), Duration::ZERO)
+                        .await
+                        .unwrap();
+                    if round % 2 == 0 {
+                        queue.process_retry_queue().await;
+                    }
+                }
+            })
+        };
+
+        while !mover.is_finished() {
+            let report = queue.enqueue_batch([(url, TaskPriority::Normal)]).await;
+            assert_eq!(report.accepted, 0, "enqueued a task that was moving");
+            tokio::task::yield_now().await;
+        }
+        mover.await.unwrap();
+        let stats = queue.get_stats().await;
+        assert_eq!(stats.counts.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_enqueue_reports_every_url() {
+        let seed = Url::parse("https://example.com/").unwrap();
+        let queue = TaskQueue::new(2, 1).with_scopes(vec![SeedScope::for_seed(&seed)]);
+        let existing = queue
+            .enqueue_task(seed.clone(), TaskPriority::High)
+            .await
+            .unwrap();
+
+        let report = queue
+            .enqueue_batch([
+                ("https://example.com/a", TaskPriority::Normal),
+                ("https://example.com/", TaskPriority::Normal),
+                ("https://example.com/a", TaskPriority::Low),
+                ("https://other.example/", TaskPriority::Normal),
+                ("not a url", TaskPriority::Normal),
+                ("ftp://example.com/file", TaskPriority::Normal),
+            ])
+            .await;
+
+        assert_eq!(
+            (
+                report.accepted,
+                report.duplicates,
+                report.out_of_scope,
+                report.invalid
+            ),
+            (1, 2, 1, 2)
+        );
+        let accepted: Vec<&str> = report.task_ids().collect();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(
+            report.entries[1].outcome,
+            EnqueueOutcome::Duplicate { task_id: existing }
+        );
+        assert_eq!(
+            report.entries[2].outcome,
+            EnqueueOutcome::Duplicate {
+                task_id: accepted[0].to_string()
+            }
+        );
+        assert_eq!(report.entries[3].outcome, EnqueueOutcome::OutOfScope);
+        assert_eq!(report.rejected().count(), 5);
+        assert_eq!(queue.pending_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_frontier_entries_are_scoped_and_deduplicated() {
+        let seed = Url::parse("https://example.com/").unwrap();
+        let queue = TaskQueue::new(2, 1).with_scopes(vec![SeedScope::for_seed(&seed)]);
+        queue
+            .enqueue_task(seed.clone(), TaskPriority::High)
+            .await
+            .unwrap();
+        let crawling = queue.dequeue_task().await.unwrap();
+
+        let entry = |url: &str| FrontierEntry {
+            url: Url::parse(url).unwrap(),
+            priority: TaskPriority::Normal,
+            depth: 2,
+            group: Some("news".to_string()),
+            metadata: BTreeMap::new(),
+        };
+        let report = queue
+            .enqueue_entries(vec![
+                entry("https://example.com/"),
+                entry("https://other.example/"),
+                entry("https://example.com/latest"),
+            ])
+            .await;
+        assert_eq!(
+            report.entries[0].outcome,
+            EnqueueOutcome::Duplicate {
+                task_id: crawling.id
+            }
+        );
+        assert_eq!(report.entries[1].outcome, EnqueueOutcome::OutOfScope);
+        assert_eq!(report.accepted, 1);
+
+        let task = queue.dequeue_task().await.unwrap();
+        assert_eq!(task.url.path(), "/latest");
+        assert_eq!((task.depth, task.group.as_deref()), (2, Some("news")));
+    }
+
+    #[tokio::test]
+    async fn test_important_links_are_dequeued_first_within_a_priority() {
+        let queue = TaskQueue::new(2, 1).with_link_importance();
+        let url = |path: &str| {
+            Url::parse("https://example.com/")
+                .unwrap()
+                .join(path)
+                .unwrap()
+        };
+        let home = CrawlTask::new(url("/"), TaskPriority::High, 1);
+        let about = CrawlTask::new(url("/about"), TaskPriority::High, 1);
+
+        // Both pages link to the hub; only the home page links to the leaf
+        queue
+            .distribute_importance(&home.url, [&url("/leaf"), &url("/hub")])
+            .await;
+        queue
+            .distribute_importance(&about.url, [&url("/hub")])
+            .await;
+        for (parent, path) in [(&home, "/leaf"), (&about, "/hub")] {
+            queue
+                .enqueue_discovered(parent, url(path), TaskPriority::Normal, None)
+                .await
+                .unwrap();
+        }
+        queue
+            .enqueue_discovered(&home, url("/urgent"), TaskPriority::High, None)
+            .await
+            .unwrap();
+
+        let mut order = Vec::new();
+        while let Some(task) = queue.dequeue_task().await {
+            order.push(task.url.path().to_string());
+        }
+        assert_eq!(order, vec!["/urgent", "/hub", "/leaf"]);
+    }
+    #[tokio::test]
+    async fn test_discovered_links_carry_their_parents_provenance() {
+        let queue = TaskQueue::new(2, 1);
+        let mut parent = CrawlTask::new(
+            Url::parse("https://example.com/").unwrap(),
+            TaskPriority::High,
+            1,
+        );
+        parent.relevance = Some(0.6);
+        queue
+            .enqueue_discovered(
+                &parent,
+                Url::parse("https://example.com/about").unwrap(),
+                TaskPriority::Normal,
+                Some("Content".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let child = queue.dequeue_task().await.unwrap();
+        assert_eq!(child.depth, 1);
+        assert_eq!(child.relevance, None);
+        let provenance = child.provenance.unwrap();
+        assert_eq!(provenance.parent_url, parent.url);
+        assert_eq!(provenance.parent_relevance, Some(0.6));
+        assert_eq!(provenance.link_category.as_deref(), Some("Content"));
+    }
+
+    #[tokio::test]
+    async fn test_sibling_hosts_share_a_site_budget() {
+        let queue = TaskQueue::new(2, 1).with_domain_budget(3);
+        let url = |raw: &str| Url::parse(raw).unwrap();
+
+        // Seeds are always queued, but count towards their site's budget
+        for seed in ["https://example.co.uk/", "https://news.example.co.uk/"] {
+            queue
+                .enqueue_task(url(seed), TaskPriority::High)
+                .await
+                .unwrap();
+        }
+        let parent = CrawlTask::new(url("https://example.co.uk/"), TaskPriority::High, 1);
+        let outcome = |path: &str| {
+            let queue = &queue;
+            let parent = &parent;
+            let link = url(path);
+            async move {
+                queue
+                    .enqueue_discovered(parent, link, TaskPriority::Normal, None)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert!(matches!(
+            outcome("https://www.example.co.uk/a").await,
+            EnqueueOutcome::Accepted { .. }
+        ));
+        assert_eq!(
+            outcome("https://blog.example.co.uk/b").await,
+            EnqueueOutcome::DomainBudgetExceeded {
+                domain: "example.co.uk".to_string()
+            }
+        );
+        // Other sites under the same public suffix have budgets of their own
+        assert!(matches!(
+            outcome("https://other.co.uk/").await,
+            EnqueueOutcome::Accepted { .. }
+        ));
+
+        let report = queue
+            .enqueue_batch([("https://example.co.uk/c", TaskPriority::Normal)])
+            .await;
+        assert_eq!(report.over_budget, 1);
+        assert_eq!(queue.get_stats().await.counts.pending, 4);
+    }
+
+    #[tokio::test]
+    async fn test_internal_domains_have_budgets_of_their_own() {
+        let suffixes = PublicSuffixes::builtin().with_overrides(["staging.corp.example"]);
+        let queue = TaskQueue::new(2, 1)
+            .with_domain_budget(1)
+            .with_public_suffixes(Arc::new(suffixes));
+        let url = |raw: &str| Url::parse(raw).unwrap();
+        queue
+            .enqueue_task(url("https://www.corp.example/"), TaskPriority::High)
+            .await
+            .unwrap();
+
+        let parent = CrawlTask::new(url("https://www.corp.example/"), TaskPriority::High, 1);
+        let outcome = queue
+            .enqueue_discovered(
+                &parent,
+                url("https://api.staging.corp.example/"),
+                TaskPriority::Normal,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(outcome, EnqueueOutcome::Accepted { .. }));
+        assert_eq!(
+            queue
+                .enqueue_discovered(
+                    &parent,
+                    url("https://docs.corp.example/"),
+                    TaskPriority::Normal,
+                    None,
+                )
+                .await
+                .unwrap(),
+            EnqueueOutcome::DomainBudgetExceeded {
+                domain: "corp.example".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_weights_survive_a_restore() {
+        let queue = TaskQueue::new(2, 1);
+        queue.set_group_weight("news", 3).await;
+        queue
+            .enqueue_grouped(
+                Url::parse("https://example.com/").unwrap(),
+                TaskPriority::High,
+                "news",
+                BTreeMap::new(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&queue.export_state().await).unwrap();
+
+        let restored = TaskQueue::new(2, 1);
+        restored
+            .restore_state(serde_json::from_str(&json).unwrap())
+            .await;
+        assert_eq!(restored.pending_tasks.read().await.weight("news"), 3);
+        assert_eq!(restored.pending_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_next_pages_stay_within_their_seeds_scope() {
+        let queue = TaskQueue::new(2, 1);
+        let url = |raw: &str| Url::parse(raw).unwrap();
+        let mut parent = CrawlTask::new(url("https://example.com/series"), TaskPriority::High, 1);
+        parent.scope = Some(SeedScope::for_seed(&parent.url).with_max_depth(1));
+        let chain = PaginationChain::following(&parent);
+
+        let next = queue
+            .enqueue_next_page(
+                &parent,
+                url("https://example.com/series?page=2"),
+                chain.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(next, EnqueueOutcome::Accepted { .. }));
+        let elsewhere = queue
+            .enqueue_next_page(&parent, url("https://other.example/series?page=2"), chain)
+            .await
+            .unwrap();
+        assert_eq!(elsewhere, EnqueueOutcome::OutOfScope);
+
+        // The second page is at the seed's depth limit, so its next page is not queued
+        let second = queue.dequeue_task().await.unwrap();
+        let beyond = queue
+            .enqueue_next_page(
+                &second,
+                url("https://example.com/series?page=3"),
+                PaginationChain::following(&second),
+            )
+            .await
+            .unwrap();
+        assert_eq!(beyond, EnqueueOutcome::OutOfScope);
+        assert_eq!(queue.get_stats().await.counts.pending, 0);
+    }
+}
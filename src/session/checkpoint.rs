@@ -0,0 +1,713 @@
+/// Session checkpoints and configuration drift on resumption
+///
+/// A checkpoint holds the session's queue together with a `ConfigFingerprint`: the
+/// settings that decide which pages a session crawls and how fast. When a session
+/// is resumed under a configuration whose fingerprint differs, the results before
+/// and after the checkpoint were produced under different rules. `ConfigDrift`
+/// lists those differences, classified as scope narrowed or widened, depth, filter,
+/// URL identity or rate-limit changes, so resumption can refuse them unless
+/// acknowledged and record them in the session directory (`config_drift.json`).
+/// The checkpoint also keeps the visited URLs, so a resumed session does not fetch
+/// the pages of the first run again.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::live_config::ConfigUpdate;
+use super::manager::CrawlSessionConfig;
+use crate::core::{DomainRateLimit, SeedScope, host_key};
+use crate::crawler::VisitedState;
+use crate::logging::session_events_path;
+use crate::queue::QueueState;
+use crate::storage::durable::{FsyncPolicy, write_atomic};
+
+/// File name of the acknowledged drift inside a session directory
+pub const CONFIG_DRIFT_FILE: &str = "config_drift.json";
+
+/// Drift record of `session_id` under the storage directory
+pub fn session_config_drift_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    session_events_path(storage_dir, session_id).with_file_name(CONFIG_DRIFT_FILE)
+}
+
+/// Where and how often the session saves a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    pub interval_secs: u64,
+}
+
+impl CheckpointConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval_secs: 60,
+        }
+    }
+}
+
+/// Settings of a session that change which pages it crawls, or how fast
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigFingerprint {
+    pub base_url: BTreeSet<String>,
+    pub accepted_languages: BTreeSet<String>,
+    pub avoid_url_extensions: BTreeSet<String>,
+    pub target_words: BTreeSet<String>,
+    pub enable_keyword_filtering: bool,
+    pub min_word_length: usize,
+    pub enable_extension_crawling: bool,
+    pub max_depth: usize,
+    pub max_crawl_depth: usize,
+    pub max_total_urls: usize,
+    pub max_concurrent_requests: usize,
+    pub default_rate_limit: Option<DomainRateLimit>,
+    pub domain_rate_limits: BTreeMap<String, DomainRateLimit>,
+    /// Depth and domain limits of the seeds given one, by seed URL
+    #[serde(default)]
+    pub seed_scopes: BTreeMap<String, SeedScope>,
+    // Settings kept as JSON, whose maps serialize with sorted keys
+    #[serde(default)]
+    pub fragments: Option<Value>,
+    #[serde(default)]
+    pub public_suffixes: Option<Value>,
+    #[serde(default)]
+    pub internal_network: Option<Value>,
+    #[serde(default)]
+    pub robots_override: Option<Value>,
+    #[serde(default)]
+    pub ssrf_protection: Option<Value>,
+    #[serde(default)]
+    pub language_priors: Option<Value>,
+    /// Keyword list set with `CrawlSession::update_config`
+    #[serde(default)]
+    pub live_target_words: BTreeSet<String>,
+    #[serde(default)]
+    pub live_allowed_domains: BTreeSet<String>,
+    #[serde(default)]
+    pub live_blocked_domains: BTreeSet<String>,
+}
+
+impl ConfigFingerprint {
+    pub fn of(config: &CrawlSessionConfig) -> Self {
+        let crawler = &config.crawler_config;
+        let json = |value: Option<Value>| value.filter(|value| !value.is_null());
+        Self {
+            base_url: crawler.base_url.iter().cloned().collect(),
+            accepted_languages: crawler
+                .accepted_languages
+                .iter()
+                .map(|language| format!("{:?}", language))
+                .collect(),
+            avoid_url_extensions: crawler.avoid_url_extensions.iter().cloned().collect(),
+            target_words: crawler.target_words.iter().cloned().collect(),
+            enable_keyword_filtering: crawler.enable_keyword_filtering,
+            min_word_length: crawler.min_word_length,
+            enable_extension_crawling: crawler.enable_extension_crawling,
+            max_depth: config.max_depth,
+            max_crawl_depth: crawler.max_crawl_depth,
+            max_total_urls: crawler.max_total_urls,
+            max_concurrent_requests: config.max_concurrent_requests,
+            default_rate_limit: crawler.default_rate_limit.clone(),
+            domain_rate_limits: crawler
+                .domain_rate_limits
+                .iter()
+                .flatten()
+                .map(|(domain, limit)| (host_key(domain), limit.clone()))
+                .collect(),
+            seed_scopes: BTreeMap::new(),
+            fragments: json(serde_json::to_value(&crawler.fragments).ok()),
+            public_suffixes: json(serde_json::to_value(&crawler.public_suffixes).ok()),
+            internal_network: json(serde_json::to_value(&crawler.internal_network).ok()),
+            robots_override: json(serde_json::to_value(&crawler.robots_override).ok()),
+            ssrf_protection: json(serde_json::to_value(&crawler.ssrf_protection).ok()),
+            language_priors: json(serde_json::to_value(&crawler.language_priors).ok()),
+            live_target_words: BTreeSet::new(),
+            live_allowed_domains: BTreeSet::new(),
+            live_blocked_domains: BTreeSet::new(),
+        }
+    }
+
+    /// The fingerprint with the live updates applied to the session so far
+    pub fn with_update(mut self, update: &ConfigUpdate) -> Self {
+        if let Some(limit) = &update.default_rate_limit {
+            self.default_rate_limit = Some(limit.clone());
+        }
+        if let Some(limits) = &update.domain_rate_limits {
+            self.domain_rate_limits = limits
+                .iter()
+                .map(|(domain, limit)| (host_key(domain), limit.clone()))
+                .collect();
+        }
+        if let Some(max_concurrent_requests) = update.max_concurrent_requests {
+            self.max_concurrent_requests = max_concurrent_requests;
+        }
+        if let Some(words) = &update.target_words {
+            self.live_target_words = words.iter().cloned().collect();
+        }
+        if let Some(domains) = &update.allowed_domains {
+            self.live_allowed_domains = domains.iter().map(|domain| host_key(domain)).collect();
+        }
+        if let Some(domains) = &update.blocked_domains {
+            self.live_blocked_domains = domains.iter().map(|domain| host_key(domain)).collect();
+        }
+        self
+    }
+}
+
+/// What a configuration change does to the resumed crawl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// Fewer pages are in scope than before: pages already stored would now be skipped
+    ScopeNarrowed,
+    /// More pages are in scope than before: earlier pages did not follow them
+    ScopeWidened,
+    DepthChanged,
+    /// Content filters changed, so stored pages were kept or dropped by other rules
+    FilterChanged,
+    /// URLs are deduplicated or grouped into sites by other rules
+    UrlIdentityChanged,
+    RateLimitChanged,
+}
+
+impl std::fmt::Display for DriftKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DriftKind::ScopeNarrowed => write!(f, "scope narrowed"),
+            DriftKind::ScopeWidened => write!(f, "scope widened"),
+            DriftKind::DepthChanged => write!(f, "depth changed"),
+            DriftKind::FilterChanged => write!(f, "filter changed"),
+            DriftKind::UrlIdentityChanged => write!(f, "URL identity changed"),
+            DriftKind::RateLimitChanged => write!(f, "rate limit changed"),
+        }
+    }
+}
+
+/// One setting that differs from the checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub setting: String,
+    pub kind: DriftKind,
+    pub before: String,
+    pub after: String,
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} -> {}",
+            self.setting, self.kind, self.before, self.after
+        )
+    }
+}
+
+/// Differences between the configuration of a checkpoint and the current one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigDrift {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDrift {
+    pub fn between(before: &ConfigFingerprint, after: &ConfigFingerprint) -> Self {
+        let mut drift = Self::default();
+
+        drift.compare_set("base_url", &before.base_url, &after.base_url, true);
+        drift.compare_set(
+            "accepted_languages",
+            &before.accepted_languages,
+            &after.accepted_languages,
+            true,
+        );
+        drift.compare_set(
+            "avoid_url_extensions",
+            &before.avoid_url_extensions,
+            &after.avoid_url_extensions,
+            false,
+        );
+        drift.compare_scope(
+            "enable_extension_crawling",
+            before.enable_extension_crawling,
+            after.enable_extension_crawling,
+        );
+        drift.compare_limit(
+            "max_total_urls",
+            before.max_total_urls,
+            after.max_total_urls,
+        );
+        // robots.txt overrides and internal hosts let the crawl reach more pages;
+        // SSRF protection refuses some
+        drift.compare_restriction(
+            "robots_override",
+            &before.robots_override,
+            &after.robots_override,
+            false,
+        );
+        drift.compare_restriction(
+            "internal_network",
+            &before.internal_network,
+            &after.internal_network,
+            false,
+        );
+        drift.compare_restriction(
+            "ssrf_protection",
+            &before.ssrf_protection,
+            &after.ssrf_protection,
+            true,
+        );
+        drift.compare_allow_list(
+            "live_allowed_domains",
+            &before.live_allowed_domains,
+            &after.live_allowed_domains,
+        );
+        drift.compare_set(
+            "live_blocked_domains",
+            &before.live_blocked_domains,
+            &after.live_blocked_domains,
+            false,
+        );
+        drift.compare_seed_scopes(&before.seed_scopes, &after.seed_scopes);
+
+        drift.compare(
+            "max_depth",
+            &before.max_depth,
+            &after.max_depth,
+            DriftKind::DepthChanged,
+        );
+        drift.compare(
+            "max_crawl_depth",
+            &before.max_crawl_depth,
+            &after.max_crawl_depth,
+            DriftKind::DepthChanged,
+        );
+
+        drift.compare(
+            "target_words",
+            &before.target_words,
+            &after.target_words,
+            DriftKind::FilterChanged,
+        );
+        drift.compare(
+            "enable_keyword_filtering",
+            &before.enable_keyword_filtering,
+            &after.enable_keyword_filtering,
+            DriftKind::FilterChanged,
+        );
+        drift.compare(
+            "min_word_length",
+            &before.min_word_length,
+            &after.min_word_length,
+            DriftKind::FilterChanged,
+        );
+        drift.compare(
+            "language_priors",
+            &before.language_priors,
+            &after.language_priors,
+            DriftKind::FilterChanged,
+        );
+        drift.compare(
+            "live_target_words",
+            &before.live_target_words,
+            &after.live_target_words,
+            DriftKind::FilterChanged,
+        );
+
+        drift.compare(
+            "fragments",
+            &before.fragments,
+            &after.fragments,
+            DriftKind::UrlIdentityChanged,
+        );
+        drift.compare(
+            "public_suffixes",
+            &before.public_suffixes,
+            &after.public_suffixes,
+            DriftKind::UrlIdentityChanged,
+        );
+
+        drift.compare(
+            "max_concurrent_requests",
+            &before.max_concurrent_requests,
+            &after.max_concurrent_requests,
+            DriftKind::RateLimitChanged,
+        );
+        drift.compare(
+            "default_rate_limit",
+            &before.default_rate_limit,
+            &after.default_rate_limit,
+            DriftKind::RateLimitChanged,
+        );
+        let domains: BTreeSet<&String> = before
+            .domain_rate_limits
+            .keys()
+            .chain(after.domain_rate_limits.keys())
+            .collect();
+        for domain in domains {
+            drift.compare(
+                &format!("domain_rate_limits.{}", domain),
+                &before.domain_rate_limits.get(domain),
+                &after.domain_rate_limits.get(domain),
+                DriftKind::RateLimitChanged,
+            );
+        }
+
+        drift
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changes that take pages out of the scope of the crawl
+    pub fn narrowed(&self) -> impl Iterator<Item = &ConfigChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == DriftKind::ScopeNarrowed)
+    }
+
+    fn compare<T: Serialize>(&mut self, setting: &str, before: &T, after: &T, kind: DriftKind) {
+        let (before, after) = (describe(before), describe(after));
+        if before != after {
+            self.changes.push(ConfigChange {
+                setting: setting.to_string(),
+                kind,
+                before,
+                after,
+            });
+        }
+    }
+
+    /// A list of allowed values (`allow_list`) or of excluded ones
+    ///
+    /// Any edit that takes pages out of scope makes the change a narrowing, also
+    /// when other entries were added at the same time.
+    fn compare_set(
+        &mut self,
+        setting: &str,
+        before: &BTreeSet<String>,
+        after: &BTreeSet<String>,
+        allow_list: bool,
+    ) {
+        let narrowed = if allow_list {
+            before.difference(after).next().is_some()
+        } else {
+            after.difference(before).next().is_some()
+        };
+        let kind = if narrowed {
+            DriftKind::ScopeNarrowed
+        } else {
+            DriftKind::ScopeWidened
+        };
+        self.compare(setting, before, after, kind);
+    }
+
+    /// A limit whose decrease narrows the scope
+    fn compare_scope<T: Serialize + PartialOrd>(&mut self, setting: &str, before: T, after: T) {
+        let kind = if after < before {
+            DriftKind::ScopeNarrowed
+        } else {
+            DriftKind::ScopeWidened
+        };
+        self.compare(setting, &before, &after, kind);
+    }
+
+    /// A count limit where 0 means unlimited
+    fn compare_limit(&mut self, setting: &str, before: usize, after: usize) {
+        let narrowed = after != 0 && (before == 0 || after < before);
+        let kind = if narrowed {
+            DriftKind::ScopeNarrowed
+        } else {
+            DriftKind::ScopeWidened
+        };
+        self.compare(setting, &before, &after, kind);
+    }
+
+    /// An allow list where an empty list allows everything
+    fn compare_allow_list(
+        &mut self,
+        setting: &str,
+        before: &BTreeSet<String>,
+        after: &BTreeSet<String>,
+    ) {
+        match (before.is_empty(), after.is_empty()) {
+            (_, true) => self.compare(setting, before, after, DriftKind::ScopeWidened),
+            (true, false) => self.compare(setting, before, after, DriftKind::ScopeNarrowed),
+            (false, false) => self.compare_set(setting, before, after, true),
+        }
+    }
+
+    /// An optional setting that refuses pages when `restricts`, or permits more
+    /// pages otherwise; changing its contents counts as a narrowing
+    fn compare_restriction(
+        &mut self,
+        setting: &str,
+        before: &Option<Value>,
+        after: &Option<Value>,
+        restricts: bool,
+    ) {
+        let widened = match (before, after) {
+            (Some(_), None) => restricts,
+            (None, Some(_)) => !restricts,
+            _ => false,
+        };
+        let kind = if widened {
+            DriftKind::ScopeWidened
+        } else {
+            DriftKind::ScopeNarrowed
+        };
+        self.compare(setting, before, after, kind);
+    }
+
+    /// Scopes of the seeds present in both; a seed without a scope follows the
+    /// crawl-wide limits
+    pub(crate) fn compare_seed_scopes(
+        &mut self,
+        before: &BTreeMap<String, SeedScope>,
+        after: &BTreeMap<String, SeedScope>,
+    ) {
+        let seeds: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        for seed in seeds {
+            let setting = format!("seed_scopes.{}", seed);
+            let (before, after) = (before.get(seed), after.get(seed));
+            let kind = match (before, after) {
+                (Some(before), Some(after)) if before.max_depth != after.max_depth => {
+                    DriftKind::DepthChanged
+                }
+                (Some(before), Some(after))
+                    if before.domain == after.domain
+                        && (after.include_subdomains || !before.include_subdomains) =>
+                {
+                    DriftKind::ScopeWidened
+                }
+                (Some(_), None) => DriftKind::ScopeWidened,
+                _ => DriftKind::ScopeNarrowed,
+            };
+            self.compare(&setting, &before, &after, kind);
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let changes: Vec<String> = self.changes.iter().map(ToString::to_string).collect();
+        write!(f, "{}", changes.join("; "))
+    }
+}
+
+fn describe<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Queue and configuration fingerprint of a session at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCheckpoint {
+    pub session_id: String,
+    pub config: ConfigFingerprint,
+    pub queue: QueueState,
+    /// URLs fetched before the checkpoint; empty in checkpoints of older versions
+    #[serde(default)]
+    pub visited: VisitedState,
+    pub timestamp: SystemTime,
+}
+
+impl SessionCheckpoint {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read checkpoint {}", path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(
+            path,
+            serde_json::to_string_pretty(self)?.as_bytes(),
+            FsyncPolicy::default(),
+        )
+    }
+}
+
+/// One resumption's drift, as appended to `config_drift.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftRecord {
+    pub timestamp: SystemTime,
+    pub drift: ConfigDrift,
+}
+
+/// Drift records of a session, oldest first
+///
+/// Files written before records were kept hold a single bare `ConfigDrift`.
+pub fn load_drift_records(path: &Path) -> Result<Vec<DriftRecord>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    if let Ok(records) = serde_json::from_str(&content) {
+        return Ok(records);
+    }
+    let drift: ConfigDrift = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse drift record {}", path.display()))?;
+    let timestamp = std::fs::metadata(path)?.modified()?;
+    Ok(vec![DriftRecord { timestamp, drift }])
+}
+
+/// Append `drift` to the records kept at `path`
+pub fn append_drift_record(path: &Path, drift: &ConfigDrift) -> Result<()> {
+    let mut records = load_drift_records(path)?;
+    records.push(DriftRecord {
+        timestamp: SystemTime::now(),
+        drift: drift.clone(),
+    });
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomic(
+        path,
+        serde_json::to_string_pretty(&records)?.as_bytes(),
+        FsyncPolicy::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::RateConfig;
+
+    #[test]
+    fn test_drift_is_classified_by_its_effect() {
+        let mut config = CrawlSessionConfig::default();
+        config.crawler_config.base_url = vec![
+            "https://a.example".to_string(),
+            "https://b.example".to_string(),
+        ];
+        let before = ConfigFingerprint::of(&config);
+        assert!(ConfigDrift::between(&before, &ConfigFingerprint::of(&config)).is_empty());
+
+        config.crawler_config.base_url.pop();
+        config
+            .crawler_config
+            .avoid_url_extensions
+            .push(".zip".to_string());
+        config.crawler_config.max_total_urls += 100;
+        config.max_depth += 1;
+        config.crawler_config.domain_rate_limits = Some(
+            [(
+                "A.example".to_string(),
+                DomainRateLimit {
+                    rate: RateConfig {
+                        max_requests_per_second: 1,
+                        window_size_ms: 1000,
+                    },
+                },
+            )]
+            .into(),
+        );
+        let drift = ConfigDrift::between(&before, &ConfigFingerprint::of(&config));
+
+        let kinds: Vec<(&str, DriftKind)> = drift
+            .changes
+            .iter()
+            .map(|change| (change.setting.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("base_url", DriftKind::ScopeNarrowed),
+                ("avoid_url_extensions", DriftKind::ScopeNarrowed),
+                ("max_total_urls", DriftKind::ScopeWidened),
+                ("max_depth", DriftKind::DepthChanged),
+                ("domain_rate_limits.a.example", DriftKind::RateLimitChanged),
+            ]
+        );
+        assert_eq!(drift.narrowed().count(), 2);
+        assert!(drift.to_string().starts_with(
+            "base_url (scope narrowed): [\"https://a.example\",\"https://b.example\"] -> [\"https://a.example\"]"
+        ));
+    }
+
+    #[test]
+    fn test_drift_covers_unlimited_totals_seed_scopes_and_live_updates() {
+        let mut config = CrawlSessionConfig::default();
+        config.crawler_config.max_total_urls = 0;
+        let before = ConfigFingerprint::of(&config);
+
+        // 0 means unlimited, so any other total narrows the crawl
+        config.crawler_config.max_total_urls = 5000;
+        config.crawler_config.fragments = Some(Default::default());
+        let after = ConfigFingerprint::of(&config).with_update(&ConfigUpdate {
+            allowed_domains: Some(vec!["A.example".to_string()]),
+            ..ConfigUpdate::default()
+        });
+        let drift = ConfigDrift::between(&before, &after);
+        let kinds: Vec<(&str, DriftKind)> = drift
+            .changes
+            .iter()
+            .map(|change| (change.setting.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("max_total_urls", DriftKind::ScopeNarrowed),
+                ("live_allowed_domains", DriftKind::ScopeNarrowed),
+                ("fragments", DriftKind::UrlIdentityChanged),
+            ]
+        );
+        assert_eq!(
+            after.live_allowed_domains,
+            BTreeSet::from(["a.example".to_string()])
+        );
+
+        let seed = url::Url::parse("https://a.example/").unwrap();
+        let scope = SeedScope::for_seed(&seed);
+        let mut drift = ConfigDrift::default();
+        drift.compare_seed_scopes(
+            &BTreeMap::from([(seed.to_string(), scope.clone())]),
+            &BTreeMap::from([(
+                seed.to_string(),
+                SeedScope {
+                    max_depth: Some(2),
+                    ..scope.clone()
+                },
+            )]),
+        );
+        drift.compare_seed_scopes(
+            &BTreeMap::from([(seed.to_string(), scope.clone())]),
+            &BTreeMap::from([(
+                seed.to_string(),
+                SeedScope {
+                    include_subdomains: true,
+                    ..scope
+                },
+            )]),
+        );
+        let kinds: Vec<DriftKind> = drift.changes.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, [DriftKind::DepthChanged, DriftKind::ScopeWidened]);
+    }
+
+    #[test]
+    fn test_drift_records_are_appended() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_config_drift_path(dir.path(), "resumed");
+        let mut drift = ConfigDrift::default();
+        drift.compare_limit("max_total_urls", 10, 20);
+
+        // A record left by an older version holds one bare drift
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string(&drift).unwrap()).unwrap();
+        append_drift_record(&path, &drift).unwrap();
+        append_drift_record(&path, &drift).unwrap();
+
+        let records = load_drift_records(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(
+            records
+                .iter()
+                .all(|record| record.drift.changes[0].kind == DriftKind::ScopeWidened)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+/// Configuration changes for running crawl sessions
+///
+/// A `ConfigUpdate` is validated when submitted and applied by the session between
+/// two tasks, so a task never sees half of an update.
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::{DomainRateLimit, host_key};
+use crate::processing::{KeywordConfig, KeywordMatcher, KeywordMode};
+
+/// Settings that can be changed on a live session; `None` leaves a setting unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigUpdate {
+    /// Replaces the default per-domain rate limit
+    pub default_rate_limit: Option<DomainRateLimit>,
+    /// Replaces all domain-specific rate limits
+    pub domain_rate_limits: Option<HashMap<String, DomainRateLimit>>,
+    /// New maximum number of concurrent requests, also the ceiling of adaptive concurrency
+    pub max_concurrent_requests: Option<usize>,
+    /// Pages must contain one of these words, in place of the configured target
+    /// words; an empty list disables keyword filtering
+    pub target_words: Option<Vec<String>>,
+    /// Only crawl these domains and their subdomains; an empty list removes the restriction
+    pub allowed_domains: Option<Vec<String>>,
+    /// Never crawl these domains or their subdomains
+    pub blocked_domains: Option<Vec<String>>,
+}
+
+impl ConfigUpdate {
+    pub fn validate(&self) -> Result<(), Error> {
+        let limits = self.default_rate_limit.iter().chain(
+            self.domain_rate_limits
+                .iter()
+                .flat_map(|limits| limits.values()),
+        );
+        for limit in limits {
+            if limit.rate.max_requests_per_second == 0 || limit.rate.window_size_ms == 0 {
+                return Err(anyhow::anyhow!(
+                    "Rate limits need at least one request per non-empty window"
+                ));
+            }
+        }
+        if self.max_concurrent_requests == Some(0) {
+            return Err(anyhow::anyhow!(
+                "max_concurrent_requests must be at least 1"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Names of the settings this update changes
+    pub fn changed_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.default_rate_limit.is_some() {
+            fields.push("default_rate_limit");
+        }
+        if self.domain_rate_limits.is_some() {
+            fields.push("domain_rate_limits");
+        }
+        if self.max_concurrent_requests.is_some() {
+            fields.push("max_concurrent_requests");
+        }
+        if self.target_words.is_some() {
+            fields.push("target_words");
+        }
+        if self.allowed_domains.is_some() {
+            fields.push("allowed_domains");
+        }
+        if self.blocked_domains.is_some() {
+            fields.push("blocked_domains");
+        }
+        fields
+    }
+
+    /// Fold a newer update into this one (newer values win)
+    pub fn merge(&mut self, newer: ConfigUpdate) {
+        if newer.default_rate_limit.is_some() {
+            self.default_rate_limit = newer.default_rate_limit;
+        }
+        if newer.domain_rate_limits.is_some() {
+            self.domain_rate_limits = newer.domain_rate_limits;
+        }
+        if newer.max_concurrent_requests.is_some() {
+            self.max_concurrent_requests = newer.max_concurrent_requests;
+        }
+        if newer.target_words.is_some() {
+            self.target_words = newer.target_words;
+        }
+        if newer.allowed_domains.is_some() {
+            self.allowed_domains = newer.allowed_domains;
+        }
+        if newer.blocked_domains.is_some() {
+            self.blocked_domains = newer.blocked_domains;
+        }
+    }
+}
+
+/// Session-side filters that live updates can replace
+#[derive(Default)]
+pub(crate) struct LiveFilters {
+    keyword_matcher: Option<KeywordMatcher>,
+    allowed_domains: Vec<String>,
+    blocked_domains: Vec<String>,
+}
+
+impl LiveFilters {
+    /// Apply the filter part of an update; fails without changing anything
+    pub(crate) fn apply(&mut self, update: &ConfigUpdate) -> Result<(), Error> {
+        let keyword_matcher = match &update.target_words {
+            Some(words) if words.is_empty() => Some(None),
+            Some(words) => Some(Some(KeywordMatcher::new(KeywordConfig::new(
+                words.clone(),
+                KeywordMode::CaseInsensitive,
+            ))?)),
+            None => None,
+        };
+
+        if let Some(keyword_matcher) = keyword_matcher {
+            self.keyword_matcher = keyword_matcher;
+        }
+        if let Some(domains) = &update.allowed_domains {
+            self.allowed_domains = normalize_domains(domains);
+        }
+        if let Some(domains) = &update.blocked_domains {
+            self.blocked_domains = normalize_domains(domains);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn accepts_host(&self, host: &str) -> bool {
+        let host = host_key(host);
+        if self
+            .blocked_domains
+            .iter()
+            .any(|domain| domain_matches(&host, domain))
+        {
+            return false;
+        }
+        self.allowed_domains.is_empty()
+            || self
+                .allowed_domains
+                .iter()
+                .any(|domain| domain_matches(&host, domain))
+    }
+
+    pub(crate) fn accepts_text(&self, text: &str) -> bool {
+        match &self.keyword_matcher {
+            Some(matcher) => matcher
+                .match_keywords(text)
+                .map(|result| result.found)
+                .unwrap_or(true),
+            None => true,
+        }
+    }
+}
+
+fn normalize_domains(domains: &[String]) -> Vec<String> {
+    domains
+        .iter()
+        .map(|domain| host_key(domain))
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_filters_can_be_widened_again() {
+        let mut filters = LiveFilters::default();
+        filters
+            .apply(&ConfigUpdate {
+                target_words: Some(vec!["zeppelin".to_string()]),
+                allowed_domains: Some(vec!["News.Example".to_string()]),
+                blocked_domains: Some(vec!["ads.news.example".to_string()]),
+                ..ConfigUpdate::default()
+            })
+            .unwrap();
+        assert!(filters.accepts_host("www.news.example"));
+        assert!(!filters.accepts_host("ads.news.example"));
+        assert!(!filters.accepts_host("docs.example"));
+        assert!(filters.accepts_text("The Zeppelin landed"));
+        assert!(!filters.accepts_text("The airship landed"));
+
+        filters
+            .apply(&ConfigUpdate {
+                target_words: Some(Vec::new()),
+                allowed_domains: Some(Vec::new()),
+                ..ConfigUpdate::default()
+            })
+            .unwrap();
+        assert!(filters.accepts_text("The airship landed"));
+        assert!(filters.accepts_host("docs.example"));
+        assert!(!filters.accepts_host("ads.news.example"));
+    }
+
+    #[test]
+    fn test_updates_merge_and_validate() {
+        let mut queued = ConfigUpdate {
+            max_concurrent_requests: Some(2),
+            target_words: Some(vec!["zeppelin".to_string()]),
+            ..ConfigUpdate::default()
+        };
+        queued.merge(ConfigUpdate {
+            max_concurrent_requests: Some(8),
+            ..ConfigUpdate::default()
+        });
+        assert_eq!(queued.max_concurrent_requests, Some(8));
+        assert_eq!(queued.target_words, Some(vec!["zeppelin".to_string()]));
+        assert_eq!(
+            queued.changed_fields(),
+            vec!["max_concurrent_requests", "target_words"]
+        );
+        assert!(queued.validate().is_ok());
+
+        queued.max_concurrent_requests = Some(0);
+        assert!(queued.validate().is_err());
+    }
+}
@@ -1,18 +1,53 @@
 use anyhow::Error;
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use url::Url;
 use uuid::Uuid;
 
-use crate::config::WebCrawlerConfig;
-use crate::core::types::TaskContent;
-use crate::crawler::WebCrawler;
-use crate::logging::CrawlEventLogger;
-use crate::queue::TaskQueue;
-use crate::storage::{DataStorage, StoredCrawlResult};
+use crate::config::{ProxyConfig, SeedPrecheckConfig, WebCrawlerConfig};
+use crate::core::types::{
+    CrawlTask, PaginationChain, SeedScope, SkipReason, TaskContent, TaskPriority,
+};
+use crate::core::{CrawlError, UrlOutcome, host_key};
+use crate::crawler::{CrawlOutcome, SeedPrecheckReport, SeedStatus, WebCrawler};
+use crate::logging::{
+    CrawlEventLogger, EventTimeline, SkipReport, SkipStage, WebhookConfig, WebhookEvent,
+    WebhookNotifier, session_events_path, session_skipped_key, session_skipped_path,
+};
+use crate::processing::{
+    ContentPipeline, ExtractedKeyword, FrontierSampler, HreflangAlternate, HtmlSanitizer,
+    LinkCategorization, LinkCategorizer, LinkRegion, PageLanguage, SamplingConfig, SanitizeConfig,
+    ShortPagesReport, StructuredContent, session_sampling_path, session_short_pages_path,
+};
+use crate::queue::{
+    DEFAULT_FRONTIER_POLL_INTERVAL, DEFAULT_GROUP, EnqueueOutcome, QueueSnapshot, SeedGroup,
+    TaskQueue,
+};
+use crate::storage::{
+    CrawlerMetrics, DataStorage, FeedConfig, FileNamingTemplate, ManifestConfig,
+    ObjectStorageConfig, OnStored, RawHtmlConfig, RetentionPolicy, SCHEMA_VERSION,
+    ScreenshotCapture, ScreenshotConfig, ScreenshotRef, SessionFeed, StorageWriter,
+    StorageWriterConfig, StoredCrawlResult, raw_html::DEFAULT_RAW_HTML_DIR,
+};
 
+use super::checkpoint::{
+    CheckpointConfig, ConfigDrift, ConfigFingerprint, SessionCheckpoint, append_drift_record,
+    session_config_drift_path,
+};
+use super::live_config::{ConfigUpdate, LiveFilters};
+use super::metrics_log::{MetricsLog, MetricsLogConfig, session_metrics_path};
+use super::progress::SessionProgress;
+use super::seed_history::{
+    SeedAdmission, SeedBlacklistReport, SeedHistory, SeedHistoryConfig, session_seed_blacklist_path,
+};
 use super::statistics::SessionStatistics;
+use super::stop::{StopConditions, StopReason, StopTracker};
 
 /// High-level configuration for a crawl session
 #[derive(Debug, Clone)]
@@ -24,6 +59,93 @@ pub struct CrawlSessionConfig {
     pub session_timeout: Option<Duration>,
     pub enable_storage: bool,
     pub storage_path: Option<String>,
+    /// Output file naming template, `None` keeps the built-in naming
+    pub output_naming: Option<FileNamingTemplate>,
+    /// Content processing pipeline; `None` uses the built-in extractor, or the
+    /// standard pipeline of the crawler configuration when it filters by keyword
+    pub content_pipeline: Option<Arc<ContentPipeline>>,
+    /// Enqueue hreflang alternates whose language is in the accepted languages (all
+    /// alternates when no language is accepted explicitly)
+    pub enqueue_hreflang_alternates: bool,
+    /// Order discovered links of equal priority by the importance the link graph
+    /// found so far gives them (OPIC)
+    pub link_importance: bool,
+    /// Most URLs queued per site (registrable domain), seeds included; further
+    /// links to the site are skipped. `None` is unbounded
+    pub max_urls_per_domain: Option<usize>,
+    /// Crawl only a random share of each site's discovered links; links left out
+    /// go to the skip report, and the per-site counts to `sampling.json` of the
+    /// session directory when storage is enabled
+    pub sampling: Option<SamplingConfig>,
+    /// Rules that categorize canonical, hreflang and next-page links; the category
+    /// is kept with the task and stored in the result metadata as `link_category`.
+    /// `None` records the kind of link instead
+    pub link_categorization: Option<LinkCategorization>,
+    /// Retention limits for the storage directory, applied when the session starts
+    pub retention: Option<RetentionPolicy>,
+    /// Upload results to an S3 or GCS bucket instead of `storage_path` (`object-storage` feature)
+    pub object_storage: Option<ObjectStorageConfig>,
+    /// Keep compressed raw HTML, referenced from results by hash, for later re-extraction
+    pub raw_html: Option<RawHtmlConfig>,
+    /// Directory of a full-text search index fed during the crawl (`search` feature)
+    pub search_index_path: Option<PathBuf>,
+    /// Hard time box for the crawl
+    ///
+    /// The session stops dequeuing once the remaining time is below the worst-case
+    /// task time (total request plus processing timeout, at most a quarter of the
+    /// duration) and lets the running tasks finish. Tasks still running when the
+    /// time box ends are cut off and put back in the queue. Results are stored and
+    /// the session is reported as time-truncated.
+    pub max_session_duration: Option<Duration>,
+    /// Check seeds with one short request each before crawling
+    pub seed_precheck: Option<SeedPrecheckConfig>,
+    /// Endpoints notified of session milestones
+    pub webhooks: Option<WebhookConfig>,
+    /// End the session early on a success count, error rate or matching page
+    pub stop_conditions: Option<StopConditions>,
+    /// Key/value pairs added to the results of a domain and its subdomains;
+    /// seed metadata takes precedence on conflicting keys
+    pub domain_metadata: Option<HashMap<String, BTreeMap<String, String>>>,
+    /// JSONL file other tools append URLs to during the crawl (see `queue::frontier_file`)
+    ///
+    /// Lines are picked up while the session runs; the session still ends once its
+    /// queue is drained. URLs already known to the queue are not added again.
+    pub frontier_file: Option<PathBuf>,
+    /// Hosts URLs of the frontier file must belong to; empty accepts any host
+    pub frontier_scopes: Vec<SeedScope>,
+    /// Append queue, throughput and error metrics to the session's `metrics.csv`
+    /// every interval; needs storage to be enabled
+    pub metrics_log: Option<MetricsLogConfig>,
+    /// List every output file with its SHA-256 in a manifest once the session is
+    /// stored, signed if a key is given; needs storage to be enabled
+    pub integrity_manifest: Option<ManifestConfig>,
+    /// Record word count, language and a category guess of every page dropped as
+    /// too short in `short_pages.jsonl` of the session directory; needs storage
+    pub short_pages_report: bool,
+    /// Record every URL that was not crawled, with its `SkipReason`, source page and
+    /// filter stage, in `skipped.jsonl` of the session directory; needs storage
+    pub skip_report: bool,
+    /// File the session's crawl events are appended to; `None` records them in
+    /// `events.jsonl` of the session directory when storage is enabled
+    pub event_timeline: Option<PathBuf>,
+    /// Screenshot format and viewport; pages are captured when a browser backend
+    /// is set in `screenshot_capture` and storage is enabled, unless the crawler
+    /// uses SSRF protection or provider proxies (see `WebCrawler::browser_route`)
+    pub screenshots: Option<ScreenshotConfig>,
+    /// Headless-browser backend that captures the screenshots
+    pub screenshot_capture: Option<Arc<dyn ScreenshotCapture>>,
+    /// Store each page's HTML sanitized for safe rendering, beside or instead of
+    /// its plain text; needs storage
+    pub sanitized_html: Option<SanitizeConfig>,
+    /// Save the queue and a fingerprint of this configuration periodically and when
+    /// the crawl ends, for `CrawlSession::resume`
+    pub checkpoint: Option<CheckpointConfig>,
+    /// Track seed outcomes across scheduled runs and skip or deprioritize seeds
+    /// that failed several runs in a row
+    pub seed_history: Option<SeedHistoryConfig>,
+    /// Write an Atom or RSS feed of the pages stored as new or changed to
+    /// `feed.xml` of the session directory; needs storage
+    pub feed: Option<FeedConfig>,
 }
 
 impl Default for CrawlSessionConfig {
@@ -36,6 +158,35 @@ impl Default for CrawlSessionConfig {
             session_timeout: Some(Duration::from_secs(300)), // 5 minutes
             enable_storage: true,
             storage_path: Some("./crawl_data".to_string()),
+            output_naming: None,
+            content_pipeline: None,
+            enqueue_hreflang_alternates: false,
+            link_importance: false,
+            max_urls_per_domain: None,
+            sampling: None,
+            link_categorization: None,
+            retention: None,
+            object_storage: None,
+            raw_html: None,
+            search_index_path: None,
+            max_session_duration: None,
+            seed_precheck: None,
+            webhooks: None,
+            stop_conditions: None,
+            domain_metadata: None,
+            frontier_file: None,
+            frontier_scopes: Vec::new(),
+            metrics_log: None,
+            integrity_manifest: None,
+            short_pages_report: false,
+            skip_report: false,
+            event_timeline: None,
+            screenshots: None,
+            screenshot_capture: None,
+            sanitized_html: None,
+            checkpoint: None,
+            seed_history: None,
+            feed: None,
         }
     }
 }
@@ -47,9 +198,31 @@ pub struct SessionResult {
     pub total_urls_processed: usize,
     pub successful_crawls: usize,
     pub failed_crawls: usize,
+    /// URLs skipped by the visited sets, robots.txt or content checks
+    pub skipped_crawls: usize,
     pub total_duration: Duration,
     pub results: Vec<CrawlResultData>,
     pub statistics: SessionStatistics,
+    /// The session stopped at `max_session_duration` with work left in the queue
+    pub time_truncated: bool,
+    /// Queue contents left behind by a time-truncated session
+    pub remaining_queue: Option<RemainingQueue>,
+    /// Seed health check results, if the precheck ran
+    pub seed_precheck: Option<SeedPrecheckReport>,
+    /// Seeds blacklisted after failing earlier runs, with seed history configured
+    pub seed_blacklist: Option<SeedBlacklistReport>,
+    /// The stop condition that ended the session early, if any
+    pub stop_reason: Option<StopReason>,
+    /// The page that satisfied a keyword or selector stop condition
+    pub matched_page: Option<CrawlResultData>,
+}
+
+/// Work left in the queue when a time-boxed or stopped session wound down
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemainingQueue {
+    pub pending: usize,
+    /// Failed tasks waiting for a retry
+    pub retries: usize,
 }
 
 /// Individual crawl result data
@@ -57,9 +230,82 @@ pub struct SessionResult {
 pub struct CrawlResultData {
     pub url: Url,
     pub content: Option<TaskContent>,
+    /// Fetch or HTTP error of a failed URL
     pub error: Option<String>,
+    /// Why the URL was skipped; a skipped URL is neither crawled nor failed
+    pub skip: Option<SkipReason>,
     pub duration: Duration,
     pub status_code: Option<u16>,
+    /// Alternate language versions declared by the page
+    pub alternates: Vec<HreflangAlternate>,
+    /// User-Agent the page was fetched with
+    pub user_agent: Option<String>,
+    /// Meta refresh hops from the requested URL to the page the content came from
+    pub redirect_chain: Vec<Url>,
+    /// Link distance from the seed the URL was discovered from
+    pub depth: usize,
+    /// Page the URL was discovered on; `None` for seeds
+    pub parent_url: Option<Url>,
+    /// Tables and lists, when structured extraction is configured
+    pub structured: Option<StructuredContent>,
+    /// Declared and detected language of the page
+    pub language: PageLanguage,
+    /// Domain and seed metadata, stored with the result as `metadata.custom`
+    pub metadata: BTreeMap<String, String>,
+    /// Semantic relevance in `0.0..=1.0`, when the pipeline has a relevance stage
+    pub relevance_score: Option<f32>,
+    /// Hash of the retained raw body
+    pub raw_html_hash: Option<String>,
+    /// Stored screenshot of the page
+    pub screenshot: Option<ScreenshotRef>,
+    /// Sanitized HTML of the page, when configured
+    pub sanitized_html: Option<String>,
+    /// Personal data entities masked in the content, by kind
+    pub pii_scrubbed: BTreeMap<String, usize>,
+    /// Top keywords of the content, when automatic keywords are configured
+    pub keywords: Vec<ExtractedKeyword>,
+    /// `Content-Encoding` the page was received with
+    pub content_encoding: Option<String>,
+    /// Pagination chain the page was crawled in
+    pub pagination: Option<PaginationChain>,
+    /// MIME type sniffed from a binary body served in place of a text document
+    pub sniffed_type: Option<String>,
+}
+
+impl CrawlResultData {
+    pub fn outcome(&self) -> UrlOutcome {
+        match (&self.content, &self.skip) {
+            (Some(_), _) => UrlOutcome::Crawled,
+            (None, Some(_)) => UrlOutcome::Skipped,
+            (None, None) => UrlOutcome::Failed,
+        }
+    }
+}
+
+/// Storage writer and feed of a running session
+struct ResultStore {
+    writer: StorageWriter,
+    feed: Option<SessionFeed>,
+    /// Results handed to the writer
+    sent: usize,
+}
+
+/// Background task aborted when the session returns, also on an early error
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Why `process_crawl_queue` returned
+enum QueueExit {
+    /// The queue ran empty or the session timeout passed
+    Drained,
+    /// `max_session_duration` left no time for another task
+    TimeBoxed,
+    Stopped(StopReason),
 }
 
 /// High-level crawl session manager that orchestrates the entire crawl process
@@ -70,29 +316,329 @@ pub struct CrawlSession {
     task_queue: Arc<TaskQueue>,
     event_logger: CrawlEventLogger,
     statistics: Arc<Mutex<SessionStatistics>>,
-    storage: Option<DataStorage>,
+    storage: Option<Arc<DataStorage>>,
+    /// Per-domain request metrics, written as domain reports at session end
+    metrics: Arc<CrawlerMetrics>,
+    #[cfg(feature = "search")]
+    search_index: Option<Arc<crate::storage::SearchIndex>>,
+    /// Submitted by `update_config`, applied before the next task starts
+    pending_update: Mutex<Option<ConfigUpdate>>,
+    /// Every update applied so far, merged, for checkpoints
+    applied_update: Mutex<ConfigUpdate>,
+    live_filters: Mutex<LiveFilters>,
+    /// Scopes of the seeds crawled so far, by seed URL, for checkpoints
+    seed_scopes: std::sync::Mutex<BTreeMap<String, SeedScope>>,
+    /// Whether a resumed session accepts seeds whose scope changed since the checkpoint
+    resume_drift_allowed: Option<bool>,
+    /// Held while a checkpoint is written, so saves never overlap
+    checkpoint_lock: Arc<Mutex<()>>,
+    webhooks: Option<WebhookNotifier>,
+    skips: Option<Arc<SkipReport>>,
+    short_pages: Option<Arc<ShortPagesReport>>,
+    /// Built once from `sanitized_html`, shared by every page
+    sanitizer: Option<Arc<HtmlSanitizer>>,
+    /// Compiled from `link_categorization`
+    categorizer: Option<LinkCategorizer>,
+    /// Drawn from `sampling`
+    sampler: Option<std::sync::Mutex<FrontierSampler>>,
 }
 
 impl CrawlSession {
     /// Create a new crawl session
     pub async fn new(config: CrawlSessionConfig) -> Result<Self, Error> {
-        let session_id = Uuid::new_v4().to_string();
+        Self::with_session_id(config, Uuid::new_v4().to_string()).await
+    }
+
+    /// Continue a checkpointed session with its queue and visited URLs
+    ///
+    /// Settings that change the crawl's scope, depth, filters or rate limits must
+    /// match the checkpoint, unless `allow_config_change` acknowledges the
+    /// difference; acknowledged changes are logged and appended to
+    /// `config_drift.json` in the session directory. Live updates made with
+    /// `update_config` are not carried over and count as changes too. Call
+    /// `execute_crawl` with no further seeds to continue; seeds given again are
+    /// held to the scope they had in the checkpoint the same way.
+    pub async fn resume(
+        config: CrawlSessionConfig,
+        checkpoint_path: &Path,
+        allow_config_change: bool,
+    ) -> Result<Self, Error> {
+        let checkpoint = SessionCheckpoint::load(checkpoint_path)?;
+        // Seed scopes are compared when the seeds are given again
+        let current = ConfigFingerprint {
+            seed_scopes: checkpoint.config.seed_scopes.clone(),
+            ..ConfigFingerprint::of(&config)
+        };
+        let drift = ConfigDrift::between(&checkpoint.config, &current);
+        if !drift.is_empty() && !allow_config_change {
+            return Err(anyhow::anyhow!(
+                "Configuration of session {} changed since its checkpoint ({}); resume with --allow-config-change to accept",
+                checkpoint.session_id,
+                drift
+            ));
+        }
+
+        let mut session = Self::with_session_id(config, checkpoint.session_id).await?;
+        session.resume_drift_allowed = Some(allow_config_change);
+        *session
+            .seed_scopes
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner()) = checkpoint.config.seed_scopes;
+        session.task_queue.restore_state(checkpoint.queue).await;
+        session.crawler.restore_visited(checkpoint.visited).await;
+        session.record_drift(&drift).await?;
+        tracing::info!(
+            session_id = %session.session_id,
+            pending = session.task_queue.pending_count().await,
+            "Session resumed from checkpoint"
+        );
+        Ok(session)
+    }
+
+    /// Save the queue, visited URLs and configuration fingerprint to `path`
+    ///
+    /// The file is written on the blocking thread pool; saves are serialized, so
+    /// an older checkpoint never replaces a newer one.
+    pub async fn save_checkpoint(&self, path: &Path) -> Result<(), Error> {
+        let guard = Arc::clone(&self.checkpoint_lock).lock_owned().await;
+        let checkpoint = self.checkpoint().await;
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let _guard = guard;
+            checkpoint.save(&path)
+        })
+        .await?
+    }
+
+    async fn checkpoint(&self) -> SessionCheckpoint {
+        let seed_scopes = self
+            .seed_scopes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let config = ConfigFingerprint {
+            seed_scopes,
+            ..ConfigFingerprint::of(&self.config)
+        }
+        .with_update(&*self.applied_update.lock().await);
+        let queue = self.task_queue.export_state().await;
+        // Tasks that are fetched again after resuming must not count as visited
+        let mut visited = self.crawler.export_visited().await;
+        visited.forget(
+            queue
+                .in_progress_tasks
+                .iter()
+                .chain(&queue.retry_queue)
+                .map(|task| &task.url),
+        );
+        SessionCheckpoint {
+            session_id: self.session_id.clone(),
+            config,
+            queue,
+            visited,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    /// Save a checkpoint every interval; runs until dropped
+    async fn checkpoint_periodically(&self, config: &CheckpointConfig) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+        // The first tick completes at once, before anything was crawled
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.save_checkpoint(&config.path).await {
+                tracing::warn!(
+                    session_id = %self.session_id,
+                    path = %config.path.display(),
+                    error = %e,
+                    "Failed to save session checkpoint"
+                );
+            }
+        }
+    }
+
+    /// Remember the scopes of `groups` for checkpoints
+    ///
+    /// On a resumed session, a seed whose scope differs from the one in the
+    /// checkpoint is configuration drift, refused unless the resumption allowed it.
+    async fn merge_seed_scopes(&self, groups: &[SeedGroup]) -> Result<(), Error> {
+        let scopes: BTreeMap<String, SeedScope> = groups
+            .iter()
+            .flat_map(|group| {
+                group
+                    .seeds
+                    .iter()
+                    .filter_map(|seed| Some((seed.to_string(), group.scopes.get(seed)?.clone())))
+            })
+            .collect();
+        let drift = {
+            let mut known = self.seed_scopes.lock().unwrap_or_else(|e| e.into_inner());
+            let mut drift = ConfigDrift::default();
+            if self.resume_drift_allowed.is_some() {
+                let given: HashSet<String> = groups
+                    .iter()
+                    .flat_map(|group| group.seeds.iter().map(Url::to_string))
+                    .collect();
+                let before: BTreeMap<String, SeedScope> = known
+                    .iter()
+                    .filter(|(seed, _)| given.contains(*seed))
+                    .map(|(seed, scope)| (seed.clone(), scope.clone()))
+                    .collect();
+                let after: BTreeMap<String, SeedScope> = scopes
+                    .iter()
+                    .filter(|(seed, _)| before.contains_key(*seed))
+                    .map(|(seed, scope)| (seed.clone(), scope.clone()))
+                    .collect();
+                drift.compare_seed_scopes(&before, &after);
+            }
+            if !drift.is_empty() && self.resume_drift_allowed != Some(true) {
+                return Err(anyhow::anyhow!(
+                    "Seed scopes of session {} changed since its checkpoint ({}); resume with --allow-config-change to accept",
+                    self.session_id,
+                    drift
+                ));
+            }
+            known.extend(scopes);
+            drift
+        };
+        self.record_drift(&drift).await
+    }
+
+    /// Log acknowledged configuration drift and append it to the session's drift record
+    async fn record_drift(&self, drift: &ConfigDrift) -> Result<(), Error> {
+        if drift.is_empty() {
+            return Ok(());
+        }
+        for change in &drift.changes {
+            tracing::warn!(
+                session_id = %self.session_id,
+                event = "config_drift",
+                setting = %change.setting,
+                kind = %change.kind,
+                before = %change.before,
+                after = %change.after,
+                "Resuming with a changed configuration"
+            );
+        }
+        if let Some(storage) = &self.storage {
+            let path = session_config_drift_path(storage.output_dir(), &self.session_id);
+            let drift = drift.clone();
+            tokio::task::spawn_blocking(move || append_drift_record(&path, &drift)).await??;
+        }
+        Ok(())
+    }
+
+    async fn with_session_id(
+        config: CrawlSessionConfig,
+        session_id: String,
+    ) -> Result<Self, Error> {
+        // Crawl events are kept in the session directory next to the results
+        let timeline_path = match &config.event_timeline {
+            Some(path) => Some(path.clone()),
+            None if config.enable_storage => {
+                let storage_path = config.storage_path.as_deref().unwrap_or("./crawl_data");
+                Some(session_events_path(Path::new(storage_path), &session_id))
+            }
+            None => None,
+        };
+        let timeline = match timeline_path {
+            Some(path) => Some(Arc::new(EventTimeline::open(&path)?)),
+            None => None,
+        };
 
         // Create crawler
-        let crawler = Arc::new(WebCrawler::new(
+        let mut crawler = WebCrawler::new_with_session(
             config.crawler_config.clone(),
             config.max_concurrent_requests,
             config.max_depth,
-        )?);
+            Some(session_id.clone()),
+        )?;
+        if let Some(timeline) = &timeline {
+            crawler = crawler.with_event_timeline(Arc::clone(timeline));
+        }
+        let short_pages = if config.enable_storage && config.short_pages_report {
+            let storage_path = config.storage_path.as_deref().unwrap_or("./crawl_data");
+            let report = Arc::new(ShortPagesReport::open(&session_short_pages_path(
+                Path::new(storage_path),
+                &session_id,
+            ))?);
+            crawler = crawler.with_short_pages_report(Arc::clone(&report));
+            Some(report)
+        } else {
+            None
+        };
+        // Results and reports go to the same bucket, over one connection
+        let backend = match &config.object_storage {
+            Some(object_storage) if config.enable_storage => Some(object_storage.connect()?),
+            _ => None,
+        };
+        let skips = if config.enable_storage && config.skip_report {
+            let storage_path = Path::new(config.storage_path.as_deref().unwrap_or("./crawl_data"));
+            let path = session_skipped_path(storage_path, &session_id);
+            let report = Arc::new(match &backend {
+                Some(backend) => SkipReport::with_backend(
+                    &path,
+                    Arc::clone(backend),
+                    &session_skipped_key(&session_id),
+                ),
+                None => SkipReport::open(&path)?,
+            });
+            crawler = crawler.with_skip_report(Arc::clone(&report));
+            Some(report)
+        } else {
+            None
+        };
+        // Keyword filtering is a pipeline stage, so a keyword-filtered crawl needs one
+        let keyword_filtered = config.crawler_config.enable_keyword_filtering
+            && !config.crawler_config.target_words.is_empty();
+        let content_pipeline = match &config.content_pipeline {
+            Some(pipeline) => Some(Arc::clone(pipeline)),
+            None if keyword_filtered => Some(Arc::new(ContentPipeline::from_config(
+                &config.crawler_config,
+            )?)),
+            None => None,
+        };
+        if let Some(pipeline) = content_pipeline {
+            crawler = crawler.with_content_pipeline(pipeline);
+        }
+        if let Some(selector) = config
+            .stop_conditions
+            .as_ref()
+            .map(StopConditions::selector)
+            .transpose()?
+            .flatten()
+        {
+            crawler = crawler.with_match_selector(selector);
+        }
+        let metrics = Arc::new(CrawlerMetrics::new());
+        crawler = crawler.with_metrics(Arc::clone(&metrics));
+        if config.enable_storage && (config.raw_html.is_some() || config.sanitized_html.is_some()) {
+            crawler = crawler.with_raw_body_retention();
+        }
+        // A failed check is reported, not fatal: the proxy may only block the probe URL
+        if let Err(e) = crawler.verify_forward_proxy().await {
+            tracing::warn!(error = %e, "Forward proxy health check failed");
+        }
+        let crawler = Arc::new(crawler);
 
         // Create task queue
-        let task_queue = Arc::new(TaskQueue::new(
-            config.max_concurrent_requests,
-            config.max_retries,
-        ));
+        let mut task_queue = TaskQueue::new(config.max_concurrent_requests, config.max_retries)
+            .with_scopes(config.frontier_scopes.clone())
+            .with_public_suffixes(crawler.public_suffixes());
+        if config.link_importance {
+            task_queue = task_queue.with_link_importance();
+        }
+        if let Some(max) = config.max_urls_per_domain {
+            task_queue = task_queue.with_domain_budget(max);
+        }
+        let task_queue = Arc::new(task_queue);
 
         // Create event logger
-        let event_logger = CrawlEventLogger::new(session_id.clone());
+        let mut event_logger = CrawlEventLogger::new(session_id.clone());
+        if let Some(timeline) = timeline {
+            event_logger = event_logger.with_timeline(timeline);
+        }
 
         // Create statistics tracker
         let statistics = Arc::new(Mutex::new(SessionStatistics::new()));
@@ -100,14 +646,92 @@ impl CrawlSession {
         // Create storage if enabled
         let storage = if config.enable_storage {
             let storage_path = config.storage_path.as_deref().unwrap_or("./crawl_data");
-            Some(DataStorage::new(
-                storage_path,
-                crate::storage::OutputFormat::Json,
-            )?)
+            let mut storage = DataStorage::new(storage_path, crate::storage::OutputFormat::Json)?
+                .with_filename_index(true);
+            if let Some(naming) = &config.output_naming {
+                storage = storage.with_naming(naming)?;
+            }
+            storage.recover().await?;
+            if let Some(retention) = &config.retention {
+                // Stores shared with later sessions outlive any retention period
+                let mut retention = retention.clone();
+                if let Some(persistent) = &config.crawler_config.persistent_visited {
+                    retention
+                        .protected_paths
+                        .push(persistent.store_path.clone());
+                }
+                if let Some(change_detection) = &config.crawler_config.change_detection {
+                    retention
+                        .protected_paths
+                        .push(change_detection.store_path.clone());
+                }
+                if let Some(raw_html) = &config.raw_html {
+                    retention.protected_paths.push(
+                        raw_html
+                            .dir
+                            .clone()
+                            .unwrap_or_else(|| Path::new(storage_path).join(DEFAULT_RAW_HTML_DIR)),
+                    );
+                }
+                storage = storage.with_retention(retention);
+                storage.gc().await?;
+            }
+            if let Some(raw_html) = &config.raw_html {
+                storage = storage.with_raw_html(raw_html);
+            }
+            if config.screenshot_capture.is_some()
+                && let Some(screenshots) = &config.screenshots
+            {
+                storage = storage.with_screenshots(screenshots);
+            }
+            if let (Some(object_storage), Some(backend)) = (&config.object_storage, backend) {
+                storage = storage.with_backend(backend);
+                tracing::info!(
+                    session_id = %session_id,
+                    url = %object_storage.url,
+                    "Results are uploaded to object storage"
+                );
+            }
+            Some(Arc::new(storage))
         } else {
             None
         };
 
+        #[cfg(feature = "search")]
+        let search_index = match &config.search_index_path {
+            Some(path) => Some(Arc::new(crate::storage::SearchIndex::open_or_create(path)?)),
+            None => None,
+        };
+        #[cfg(not(feature = "search"))]
+        if config.search_index_path.is_some() {
+            tracing::warn!(
+                session_id = %session_id,
+                "search_index_path is set but the crate was built without the `search` feature"
+            );
+        }
+
+        let webhooks = match &config.webhooks {
+            Some(webhook_config) => Some(WebhookNotifier::new(
+                webhook_config.clone(),
+                session_id.clone(),
+            )?),
+            None => None,
+        };
+
+        let sanitizer = config
+            .sanitized_html
+            .clone()
+            .map(|sanitize| Arc::new(HtmlSanitizer::new(sanitize)));
+        let categorizer = config
+            .link_categorization
+            .as_ref()
+            .map(LinkCategorizer::new)
+            .transpose()?;
+        let sampler = config
+            .sampling
+            .clone()
+            .map(|sampling| std::sync::Mutex::new(FrontierSampler::new(sampling)));
+
         Ok(Self {
             session_id,
             config,
@@ -116,35 +740,365 @@ impl CrawlSession {
             event_logger,
             statistics,
             storage,
+            metrics,
+            #[cfg(feature = "search")]
+            search_index,
+            pending_update: Mutex::new(None),
+            applied_update: Mutex::new(ConfigUpdate::default()),
+            live_filters: Mutex::new(LiveFilters::default()),
+            seed_scopes: std::sync::Mutex::new(BTreeMap::new()),
+            resume_drift_allowed: None,
+            checkpoint_lock: Arc::new(Mutex::new(())),
+            webhooks,
+            skips,
+            short_pages,
+            sanitizer,
+            categorizer,
+            sampler,
         })
     }
 
+    /// Change rate limits, concurrency, keyword list or domain scope of a running session
+    ///
+    /// The update is validated immediately and applied as a whole before the next
+    /// task starts. Updates submitted before that point are merged, newer values winning.
+    pub async fn update_config(&self, update: ConfigUpdate) -> Result<(), Error> {
+        update.validate()?;
+        // Reject invalid keyword lists now rather than at the safe point
+        LiveFilters::default().apply(&update)?;
+
+        tracing::info!(
+            session_id = %self.session_id,
+            fields = ?update.changed_fields(),
+            "Configuration update queued"
+        );
+
+        let mut pending = self.pending_update.lock().await;
+        match pending.as_mut() {
+            Some(queued) => queued.merge(update),
+            None => *pending = Some(update),
+        }
+        Ok(())
+    }
+
+    /// Apply a queued configuration update (called between tasks)
+    async fn apply_pending_update(&self) {
+        let Some(update) = self.pending_update.lock().await.take() else {
+            return;
+        };
+
+        if let Err(e) = self.live_filters.lock().await.apply(&update) {
+            tracing::warn!(
+                session_id = %self.session_id,
+                error = %e,
+                "Configuration update rejected"
+            );
+            return;
+        }
+        if update.default_rate_limit.is_some() || update.domain_rate_limits.is_some() {
+            self.crawler.update_rate_limits(
+                update.default_rate_limit.clone(),
+                update.domain_rate_limits.clone(),
+            );
+        }
+        if let Some(max_concurrent_requests) = update.max_concurrent_requests {
+            self.crawler
+                .set_max_concurrent_requests(max_concurrent_requests);
+        }
+        // The live list, even an empty one, takes over from the configured target words
+        if update.target_words.is_some() {
+            self.crawler.replace_keyword_stage();
+        }
+        self.applied_update.lock().await.merge(update.clone());
+
+        tracing::info!(
+            session_id = %self.session_id,
+            event = "config_updated",
+            fields = ?update.changed_fields(),
+            "Configuration update applied"
+        );
+    }
+
     /// Execute the crawl session with provided URLs
     pub async fn execute_crawl(&self, urls: Vec<Url>) -> Result<SessionResult, Error> {
+        self.execute_grouped_crawl(vec![SeedGroup::new(DEFAULT_GROUP, urls)])
+            .await
+    }
+
+    /// Execute the crawl session with seeds in weighted groups
+    ///
+    /// The queue takes turns between groups in proportion to their weights, and
+    /// links discovered from a seed stay in its group.
+    pub async fn execute_grouped_crawl(
+        &self,
+        groups: Vec<SeedGroup>,
+    ) -> Result<SessionResult, Error> {
         let start_time = Instant::now();
 
+        // The rotation is filled before any request, and kept fresh while the crawl runs
+        let proxy_refresh = self
+            .crawler
+            .schedule_proxy_refresh()
+            .await?
+            .map(AbortOnDrop);
+
+        let mut groups = groups;
+
+        // Seeds that failed earlier runs in a row are skipped or sent to the back
+        let mut seed_history = self.config.seed_history.as_ref().and_then(|config| {
+            match SeedHistory::load(&config.path) {
+                Ok(history) => Some((config, history)),
+                Err(e) => {
+                    tracing::warn!(
+                        session_id = %self.session_id,
+                        error = %e,
+                        "Failed to load seed history; seeds are not blacklisted"
+                    );
+                    None
+                }
+            }
+        });
+        let mut seed_blacklist = SeedBlacklistReport::default();
+        let mut deprioritized: HashSet<Url> = HashSet::new();
+        if let Some((config, history)) = &mut seed_history {
+            for group in &mut groups {
+                group
+                    .seeds
+                    .retain(|seed| match history.admit(seed, config) {
+                        SeedAdmission::Skip => {
+                            seed_blacklist.skipped.push(seed.to_string());
+                            self.record_skip(
+                                seed,
+                                SkipReason::SeedBlacklisted,
+                                SkipStage::Seed,
+                                None,
+                            );
+                            false
+                        }
+                        SeedAdmission::Deprioritize => {
+                            seed_blacklist.deprioritized.push(seed.to_string());
+                            deprioritized.insert(seed.clone());
+                            true
+                        }
+                        SeedAdmission::Crawl | SeedAdmission::Retry => true,
+                    });
+            }
+        }
+        let tracked_seeds: Vec<Url> = groups
+            .iter()
+            .flat_map(|group| group.seeds.iter().cloned())
+            .collect();
+
+        let seed_precheck = match &self.config.seed_precheck {
+            Some(precheck_config) => {
+                let mut report = SeedPrecheckReport::default();
+                for group in &mut groups {
+                    let group_report = self.precheck_seeds(&group.seeds).await;
+                    if precheck_config.drop_failed_seeds {
+                        for check in group_report.checks.iter().filter(|c| !c.is_crawlable()) {
+                            let reason = match check.status {
+                                SeedStatus::RobotsBlocked => SkipReason::RobotsBlocked,
+                                status => SkipReason::SeedFailedPrecheck(
+                                    match (&check.error, check.status_code) {
+                                        (Some(error), _) => format!("{:?}: {}", status, error),
+                                        (None, Some(code)) => {
+                                            format!("{:?}: HTTP {}", status, code)
+                                        }
+                                        (None, None) => format!("{:?}", status),
+                                    },
+                                ),
+                            };
+                            self.record_skip(&check.seed, reason, SkipStage::Seed, None);
+                        }
+                    }
+                    // Redirect targets replacing a seed keep its metadata
+                    for check in &group_report.checks {
+                        if let (Some(target), Some(metadata)) =
+                            (&check.final_url, group.metadata.get(&check.seed).cloned())
+                        {
+                            group.metadata.entry(target.clone()).or_insert(metadata);
+                        }
+                    }
+                    group.seeds = group_report.seeds(
+                        precheck_config.drop_failed_seeds,
+                        precheck_config.use_redirect_targets,
+                    );
+                    report.checks.extend(group_report.checks);
+                }
+                Some(report)
+            }
+            None => None,
+        };
+
+        // Items listed by JSON APIs join their group's seeds, with the endpoint's metadata
+        for group in &mut groups {
+            let mut known_seeds: HashSet<Url> = group.seeds.iter().cloned().collect();
+            for api_seed in std::mem::take(&mut group.api_seeds) {
+                let walk = match self.crawler.walk_api(&api_seed).await {
+                    Ok(walk) => walk,
+                    Err(e) => {
+                        tracing::warn!(
+                            session_id = %self.session_id,
+                            endpoint = %api_seed.endpoint,
+                            error = %e,
+                            "Failed to walk API listing"
+                        );
+                        continue;
+                    }
+                };
+                if let Some(reason) = &walk.truncated {
+                    tracing::warn!(
+                        session_id = %self.session_id,
+                        endpoint = %api_seed.endpoint,
+                        pages = walk.pages,
+                        reason = %reason,
+                        "API listing walk stopped early"
+                    );
+                }
+                let metadata = group.metadata.get(&api_seed.endpoint).cloned();
+                for item_url in walk.item_urls {
+                    if let Some(metadata) = &metadata {
+                        group
+                            .metadata
+                            .entry(item_url.clone())
+                            .or_insert_with(|| metadata.clone());
+                    }
+                    if known_seeds.insert(item_url.clone()) {
+                        group.seeds.push(item_url);
+                    }
+                }
+            }
+        }
+        self.merge_seed_scopes(&groups).await?;
+        let seed_count: usize = groups.iter().map(|group| group.seeds.len()).sum();
+
         // Log session start
         tracing::info!(
             session_id = %self.session_id,
-            url_count = urls.len(),
+            url_count = seed_count,
+            groups = groups.len(),
             "Starting crawl session"
         );
 
         // Initialize statistics
         {
             let mut stats = self.statistics.lock().await;
-            stats.session_started(urls.len());
+            stats.session_started(seed_count);
         }
+        self.notify(WebhookEvent::SessionStarted { seeds: seed_count });
 
         // Enqueue initial URLs
-        for url in &urls {
+        for group in &groups {
             self.task_queue
-                .enqueue_task(url.clone(), crate::core::TaskPriority::High)
-                .await?;
+                .set_group_weight(&group.name, group.weight)
+                .await;
+            for url in &group.seeds {
+                self.task_queue
+                    .enqueue_grouped(
+                        url.clone(),
+                        if deprioritized.contains(url) {
+                            TaskPriority::Low
+                        } else {
+                            TaskPriority::High
+                        },
+                        &group.name,
+                        group.metadata.get(url).cloned().unwrap_or_default(),
+                        group.scopes.get(url).cloned(),
+                        group.regions.get(url).cloned(),
+                    )
+                    .await?;
+            }
         }
 
-        // Process crawl queue
-        let results = self.process_crawl_queue().await?;
+        // Pick up URLs appended to the frontier file while the crawl runs
+        let frontier_tail = self.config.frontier_file.as_ref().map(|path| {
+            TaskQueue::tail_frontier_file(
+                self.task_queue.clone(),
+                path,
+                DEFAULT_FRONTIER_POLL_INTERVAL,
+            )
+        });
+
+        // Resolve the hosts of upcoming tasks before they are fetched
+        let dns_prefetch = self
+            .crawler
+            .schedule_dns_prefetch(Arc::clone(&self.task_queue));
+
+        // Chart the run: a metrics row every interval and a last one when the queue is done
+        let metrics_log = match (&self.config.metrics_log, &self.storage) {
+            (Some(log_config), Some(storage)) => {
+                let log = MetricsLog::new(
+                    session_metrics_path(storage.output_dir(), &self.session_id),
+                    Arc::clone(&self.statistics),
+                    Arc::clone(&self.task_queue),
+                    Arc::clone(&self.metrics),
+                );
+                let interval = Duration::from_secs(log_config.interval_secs.max(1));
+                Some((log.clone(), log.schedule(interval)))
+            }
+            _ => None,
+        };
+
+        // Results are stored while the crawl runs, not only once it is over
+        let mut result_store = self
+            .storage
+            .as_ref()
+            .map(|storage| self.open_result_store(storage));
+
+        // Process crawl queue, saving checkpoints alongside
+        let process = self.process_crawl_queue(start_time, result_store.as_mut());
+        let processed = match &self.config.checkpoint {
+            Some(checkpoint) => tokio::select! {
+                processed = process => processed,
+                () = self.checkpoint_periodically(checkpoint) => unreachable!("checkpoints run until dropped"),
+            },
+            None => process.await,
+        };
+        if let Some(checkpoint) = &self.config.checkpoint
+            && let Err(e) = self.save_checkpoint(&checkpoint.path).await
+        {
+            tracing::warn!(
+                session_id = %self.session_id,
+                error = %e,
+                "Failed to save session checkpoint"
+            );
+        }
+        if let Some(frontier_tail) = frontier_tail {
+            frontier_tail.abort();
+        }
+        drop(proxy_refresh);
+        if let Some(dns_prefetch) = dns_prefetch {
+            dns_prefetch.abort();
+        }
+        if let Some((log, schedule)) = metrics_log {
+            schedule.abort();
+            log.record().await;
+        }
+        let (results, exit) = processed?;
+        let time_truncated = matches!(exit, QueueExit::TimeBoxed);
+        let stop_reason = match exit {
+            QueueExit::Stopped(reason) => Some(reason),
+            _ => None,
+        };
+        let remaining_queue = if time_truncated || stop_reason.is_some() {
+            Some(RemainingQueue {
+                pending: self.task_queue.pending_count().await,
+                retries: self.task_queue.retry_count().await,
+            })
+        } else {
+            None
+        };
+
+        let seed_blacklist = seed_history.map(|(config, mut history)| {
+            self.record_seed_outcomes(
+                config,
+                &mut history,
+                &tracked_seeds,
+                seed_precheck.as_ref(),
+                &results,
+                seed_blacklist,
+            )
+        });
 
         let total_duration = start_time.elapsed();
 
@@ -157,7 +1111,67 @@ impl CrawlSession {
 
         // Store results if storage is enabled
         if let Some(storage) = &self.storage {
-            self.store_results_to_storage(&results, storage).await?;
+            if let Some(store) = result_store {
+                self.close_result_store(store, storage).await?;
+            }
+
+            let reports = self.metrics.domain_reports(10).await;
+            let report_dir = storage
+                .store_domain_reports(&self.session_id, &reports)
+                .await?;
+            storage.flush().await?;
+            tracing::info!(
+                session_id = %self.session_id,
+                domains = reports.len(),
+                path = %report_dir.display(),
+                "Domain reports written"
+            );
+
+            if let Some(skips) = &self.skips {
+                skips.flush().await?;
+                tracing::info!(
+                    session_id = %self.session_id,
+                    skipped = skips.recorded(),
+                    location = %skips.location(),
+                    "Skip report written"
+                );
+            }
+            if let Some(short_pages) = &self.short_pages {
+                short_pages.flush().await?;
+            }
+
+            if let Some(sampler) = &self.sampler {
+                let report = sampler.lock().unwrap_or_else(|e| e.into_inner()).report();
+                let path = session_sampling_path(
+                    Path::new(
+                        self.config
+                            .storage_path
+                            .as_deref()
+                            .unwrap_or("./crawl_data"),
+                    ),
+                    &self.session_id,
+                );
+                let written = path.clone();
+                tokio::task::spawn_blocking(move || report.export(&written))
+                    .await
+                    .map_err(Error::from)
+                    .and_then(|r| r)?;
+                tracing::info!(
+                    session_id = %self.session_id,
+                    path = %path.display(),
+                    "Sampling report written"
+                );
+            }
+
+            if let Some(manifest) = &self.config.integrity_manifest
+                && let Err(e) = storage.write_manifest(&self.session_id, manifest).await
+            {
+                tracing::warn!(
+                    session_id = %self.session_id,
+                    error = %e,
+                    "Failed to write integrity manifest"
+                );
+            }
         }
 
         // Log session completion
@@ -165,132 +1179,538 @@ impl CrawlSession {
             session_id = %self.session_id,
             duration_ms = total_duration.as_millis(),
             total_processed = results.len(),
+            time_truncated,
+            stop_reason = ?stop_reason,
+            remaining = ?remaining_queue,
             "Crawl session completed"
         );
 
+        let matched_page = stop_reason
+            .as_ref()
+            .and_then(StopReason::matched_url)
+            .and_then(|url| results.iter().rev().find(|result| &result.url == url))
+            .cloned();
+
+        let successful_crawls = results.iter().filter(|r| r.content.is_some()).count();
+        let skipped_crawls = results.iter().filter(|r| r.skip.is_some()).count();
+        let failed_crawls = results.len() - successful_crawls - skipped_crawls;
+        if let Some(webhooks) = &self.webhooks {
+            if time_truncated {
+                webhooks.notify(WebhookEvent::BudgetExhausted {
+                    budget: "max_session_duration".to_string(),
+                });
+            }
+            webhooks.notify(WebhookEvent::SessionCompleted {
+                processed: results.len(),
+                successful: successful_crawls,
+                failed: failed_crawls,
+                duration_secs: total_duration.as_secs_f64(),
+                time_truncated,
+            });
+            webhooks.flush().await;
+        }
+
         Ok(SessionResult {
             session_id: self.session_id.clone(),
             total_urls_processed: results.len(),
-            successful_crawls: results.iter().filter(|r| r.content.is_some()).count(),
-            failed_crawls: results.iter().filter(|r| r.content.is_none()).count(),
+            successful_crawls,
+            failed_crawls,
+            skipped_crawls,
             total_duration,
             results,
             statistics: final_stats,
+            time_truncated,
+            remaining_queue,
+            seed_precheck,
+            seed_blacklist,
+            stop_reason,
+            matched_page,
         })
     }
 
+    /// Record whether each tracked seed could be crawled, save the seed history
+    /// and report the blacklisted seeds
+    ///
+    /// A seed counts as crawled when its own page (or the redirect target the
+    /// precheck replaced it with) produced content, and as failed only on a fetch
+    /// or HTTP error; skipped seeds and seeds the session never got to are not
+    /// recorded.
+    fn record_seed_outcomes(
+        &self,
+        config: &SeedHistoryConfig,
+        history: &mut SeedHistory,
+        tracked_seeds: &[Url],
+        seed_precheck: Option<&SeedPrecheckReport>,
+        results: &[CrawlResultData],
+        mut report: SeedBlacklistReport,
+    ) -> SeedBlacklistReport {
+        let mut outcomes: HashMap<Url, Result<(), String>> = HashMap::new();
+        let mut seed_of: HashMap<&Url, &Url> = HashMap::new();
+        for check in seed_precheck.iter().flat_map(|report| &report.checks) {
+            if matches!(check.status, SeedStatus::Dead | SeedStatus::Unreachable) {
+                let error = check
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| format!("Seed precheck: {:?}", check.status));
+                outcomes.insert(check.seed.clone(), Err(error));
+            }
+            if let Some(target) = &check.final_url {
+                seed_of.insert(target, &check.seed);
+            }
+        }
+        for result in results.iter().filter(|result| result.depth == 0) {
+            let seed = seed_of.get(&result.url).copied().unwrap_or(&result.url);
+            let outcome = match (&result.content, &result.error) {
+                (Some(_), _) => Ok(()),
+                (None, Some(error)) => Err(error.clone()),
+                (None, None) => continue,
+            };
+            outcomes.insert(seed.clone(), outcome);
+        }
+
+        let mut newly_blacklisted = Vec::new();
+        for seed in tracked_seeds {
+            let Some(outcome) = outcomes.remove(seed) else {
+                continue;
+            };
+            let succeeded = outcome.is_ok();
+            if history.record(seed, outcome, config) {
+                if succeeded {
+                    report.recovered.push(seed.to_string());
+                } else {
+                    newly_blacklisted.push(seed.clone());
+                }
+            }
+        }
+        if let Err(e) = history.save(&config.path) {
+            tracing::warn!(
+                session_id = %self.session_id,
+                error = %e,
+                "Failed to save seed history"
+            );
+        }
+
+        report.action = config.action;
+        report.blacklisted =
+            SeedBlacklistReport::from_history(history, config.action, &newly_blacklisted)
+                .blacklisted;
+        tracing::info!(
+            session_id = %self.session_id,
+            blacklisted = report.blacklisted.len(),
+            newly_blacklisted = newly_blacklisted.len(),
+            skipped = report.skipped.len(),
+            recovered = report.recovered.len(),
+            "Seed history updated"
+        );
+        if let Some(storage) = &self.storage {
+            let path = session_seed_blacklist_path(storage.output_dir(), &self.session_id);
+            if let Err(e) = report.save(&path) {
+                tracing::warn!(
+                    session_id = %self.session_id,
+                    error = %e,
+                    "Failed to write seed blacklist report"
+                );
+            }
+        }
+        report
+    }
+
+    fn notify(&self, event: WebhookEvent) {
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.notify(event);
+        }
+    }
+
+    /// Check seeds for dead links, redirects and robots blocks without crawling them
+    ///
+    /// Uses the session's precheck settings, or the defaults if none are configured.
+    pub async fn precheck_seeds(&self, seeds: &[Url]) -> SeedPrecheckReport {
+        let config = self.config.seed_precheck.clone().unwrap_or_default();
+        let report = self.crawler.precheck_seeds(seeds, &config).await;
+
+        for check in report.checks.iter().filter(|check| !check.is_crawlable()) {
+            tracing::warn!(
+                session_id = %self.session_id,
+                url = %check.seed,
+                status = ?check.status,
+                status_code = ?check.status_code,
+                error = ?check.error,
+                "Seed failed precheck"
+            );
+        }
+        tracing::info!(
+            session_id = %self.session_id,
+            seeds = report.checks.len(),
+            ok = report.count(SeedStatus::Ok),
+            redirected = report.count(SeedStatus::Redirected),
+            dead = report.count(SeedStatus::Dead),
+            unreachable = report.count(SeedStatus::Unreachable),
+            robots_blocked = report.count(SeedStatus::RobotsBlocked),
+            address_blocked = report.count(SeedStatus::AddressBlocked),
+            "Seed precheck completed"
+        );
+        report
+    }
+
+    /// Moment after which no new task is started, if the session is time-boxed
+    fn wind_down_at(&self, session_start: Instant) -> Option<Instant> {
+        let duration = self.config.max_session_duration?;
+        let timeouts = self
+            .config
+            .crawler_config
+            .timeouts
+            .clone()
+            .unwrap_or_default();
+        let worst_case_task = (timeouts.total() + timeouts.processing()).min(duration / 4);
+        Some(session_start + duration - worst_case_task)
+    }
+
     /// Process the crawl queue and collect results
-    async fn process_crawl_queue(&self) -> Result<Vec<CrawlResultData>, Error> {
+    ///
+    /// Also returns why processing ended.
+    async fn process_crawl_queue(
+        &self,
+        session_start: Instant,
+        mut result_store: Option<&mut ResultStore>,
+    ) -> Result<(Vec<CrawlResultData>, QueueExit), Error> {
         let mut results = Vec::new();
+        let mut stop_tracker = self.config.stop_conditions.clone().map(StopTracker::new);
         let timeout = self
             .config
             .session_timeout
             .unwrap_or(Duration::from_secs(300));
         let start_time = Instant::now();
+        let wind_down_at = self.wind_down_at(session_start);
+        // Tasks still running at the end of the time box are cut off and requeued
+        let time_box_ends = self
+            .config
+            .max_session_duration
+            .map(|duration| tokio::time::Instant::from_std(session_start + duration));
+
+        loop {
+            // Safe point: no task is in flight
+            self.apply_pending_update().await;
 
-        while let Some(task) = self.task_queue.dequeue_task().await {
-            // Check timeout
+            // Deadlines are checked before dequeuing so no task is dropped
+            if let Some(wind_down_at) = wind_down_at
+                && Instant::now() >= wind_down_at
+                && self.task_queue.has_work().await
+            {
+                tracing::warn!(
+                    session_id = %self.session_id,
+                    "Session time box reached, winding down"
+                );
+                return Ok((results, QueueExit::TimeBoxed));
+            }
             if start_time.elapsed() > timeout {
                 tracing::warn!(
                     session_id = %self.session_id,
                     "Session timeout reached, stopping crawl"
                 );
+                self.notify(WebhookEvent::BudgetExhausted {
+                    budget: "session_timeout".to_string(),
+                });
                 break;
             }
 
-            let url = task.url.clone();
-            let task_start = Instant::now();
-
-            // Log crawl start
-            self.event_logger
-                .log_crawl_start(&url, Some(0), Some("CrawlSession/1.0"));
-
-            // Execute crawl
-            match self.crawler.init_crawling(url.clone()).await {
-                Ok(Some(content)) => {
-                    let duration = task_start.elapsed();
-                    let task_content = TaskContent {
-                        content: content.clone(),
-                        word_count: content.split_whitespace().count(),
-                        detected_language: None, // Could be enhanced with language detection
-                    };
-
-                    // Complete task in queue
-                    let _ = self
-                        .task_queue
-                        .complete_task(&task.id, Some(content), duration)
-                        .await;
-
-                    let result = CrawlResultData {
-                        url: url.clone(),
-                        content: Some(task_content),
-                        error: None,
-                        duration,
-                        status_code: Some(200),
-                    };
-
-                    // Update statistics
-                    {
-                        let mut stats = self.statistics.lock().await;
-                        stats.url_completed(true, duration);
+            // Up to the concurrency limit of tasks are crawled at once; their outcomes
+            // are then handled one by one
+            let mut batch = Vec::new();
+            while batch.len() < self.crawler.max_concurrent_requests()
+                && let Some(task) = self.task_queue.dequeue_task().await
+            {
+                batch.push(task);
+            }
+            if batch.is_empty() {
+                break;
+            }
+            let crawled = futures::future::join_all(batch.into_iter().map(|task| async move {
+                let Some(time_box_ends) = time_box_ends else {
+                    return Ok(self.crawl_task(task).await);
+                };
+                let task_id = task.id.clone();
+                tokio::time::timeout_at(time_box_ends, self.crawl_task(task))
+                    .await
+                    .map_err(|_| task_id)
+            }))
+            .await;
+            let mut interrupted = 0;
+            let mut finished = Vec::new();
+            for crawl in crawled {
+                match crawl {
+                    Ok(crawl) => finished.extend(crawl),
+                    Err(task_id) => {
+                        self.task_queue.requeue_task(&task_id).await;
+                        interrupted += 1;
                     }
-
-                    results.push(result);
                 }
-                Ok(None) => {
-                    let duration = task_start.elapsed();
-
-                    // Mark task as failed
-                    let _ = self
-                        .task_queue
-                        .fail_task(&task.id, "No content extracted".to_string(), duration)
-                        .await;
-
-                    let result = CrawlResultData {
-                        url: url.clone(),
-                        content: None,
-                        error: Some("No content extracted".to_string()),
-                        duration,
-                        status_code: None,
-                    };
-
-                    // Update statistics
+            }
+
+            let mut stop_reason = None;
+            for (mut task, task_start, outcome) in finished {
+                let url = task.url.clone();
+                let parent_url = task
+                    .provenance
+                    .as_ref()
+                    .map(|provenance| provenance.parent_url.clone());
+
+                // Apply the live keyword list
+                let mut selector_matched = false;
+                let outcome = match outcome {
+                    Ok(CrawlOutcome::Page(page))
+                        if !self.live_filters.lock().await.accepts_text(&page.text) =>
                     {
-                        let mut stats = self.statistics.lock().await;
-                        stats.url_completed(false, duration);
+                        tracing::debug!(
+                            session_id = %self.session_id,
+                            url = %url,
+                            "Page does not contain any of the updated target words"
+                        );
+                        self.record_skip(
+                            &url,
+                            SkipReason::ContentFiltered,
+                            SkipStage::Content,
+                            parent_url.as_ref(),
+                        );
+                        Ok(CrawlOutcome::Skipped(SkipReason::ContentFiltered))
                     }
+                    outcome => outcome,
+                };
+                match outcome {
+                    Ok(CrawlOutcome::Page(page)) => {
+                        let page = *page;
+                        let duration = task_start.elapsed();
+                        task.relevance = page.relevance_score;
+                        selector_matched = page.selector_matched;
+                        let content = page.text;
+                        let detected_language = page.language.language.clone();
+                        let task_content = TaskContent {
+                            content: content.clone(),
+                            word_count: page.word_count,
+                            detected_language: detected_language.clone(),
+                        };
 
-                    results.push(result);
-                }
-                Err(e) => {
-                    let duration = task_start.elapsed();
-
-                    // Mark task as failed
-                    let _ = self
-                        .task_queue
-                        .fail_task(
-                            &task.id,
-                            format!("Network error: {}", e),
-                            task_start.elapsed(),
-                        )
-                        .await;
-
-                    let result = CrawlResultData {
-                        url: url.clone(),
-                        content: None,
-                        error: Some(e.to_string()),
-                        duration,
-                        status_code: None,
-                    };
-
-                    // Update statistics
-                    {
-                        let mut stats = self.statistics.lock().await;
-                        stats.url_completed(false, duration);
+                        let mut links: Vec<&Url> = Vec::new();
+                        if self.config.enqueue_hreflang_alternates {
+                            links.extend(page.alternates.iter().map(|alternate| &alternate.url));
+                        }
+                        links.extend(page.canonical.iter().chain(&page.next_page));
+                        self.task_queue
+                            .distribute_importance(&task.url, links)
+                            .await;
+                        if self.config.enqueue_hreflang_alternates {
+                            self.enqueue_alternates(&task, &page.alternates).await;
+                        }
+                        if let Some(canonical) = &page.canonical {
+                            self.enqueue_canonical(&task, canonical).await;
+                        }
+                        if let Some(next_page) = &page.next_page {
+                            self.enqueue_next_page(&task, next_page).await;
+                        }
+                        // With storage the writer records the visit once the page is stored
+                        if self.storage.is_none() {
+                            self.crawler.record_visit(&url, &page.redirect_chain).await;
+                        }
+
+                        // Complete task in queue
+                        let _ = self
+                            .task_queue
+                            .complete_task(&task.id, Some(content), duration)
+                            .await;
+
+                        // The page's final URL, after meta refresh hops, resolves its links
+                        let sanitized_html = self
+                            .sanitize_html(
+                                page.redirect_chain.last().unwrap_or(&url),
+                                page.raw_body.as_deref(),
+                                page.charset,
+                            )
+                            .await;
+                        let screenshot = self
+                            .capture_screenshot(&url, page.proxy.as_ref(), &page.user_agent)
+                            .await;
+                        let result = CrawlResultData {
+                            url: url.clone(),
+                            content: Some(task_content),
+                            error: None,
+                            skip: None,
+                            duration,
+                            status_code: Some(200),
+                            alternates: page.alternates,
+                            user_agent: Some(page.user_agent),
+                            redirect_chain: page.redirect_chain,
+                            depth: task.depth,
+                            parent_url: parent_url.clone(),
+                            structured: page.structured,
+                            language: page.language,
+                            metadata: self.result_metadata(&task),
+                            relevance_score: page.relevance_score,
+                            raw_html_hash: self
+                                .store_raw_html(&url, page.raw_body.as_deref())
+                                .await,
+                            screenshot,
+                            sanitized_html,
+                            pii_scrubbed: page.pii_scrubbed,
+                            keywords: page.keywords,
+                            content_encoding: page.content_encoding,
+                            pagination: task.pagination.clone(),
+                            sniffed_type: None,
+                        };
+
+                        // Update statistics
+                        {
+                            let mut stats = self.statistics.lock().await;
+                            stats.url_completed(
+                                url.host_str().unwrap_or("unknown"),
+                                true,
+                                duration,
+                            );
+                            if let Some(language) = &detected_language {
+                                stats.language_detected(&format!("{:?}", language));
+                            }
+                        }
+
+                        results.push(result);
                     }
+                    Ok(CrawlOutcome::Skipped(reason)) => {
+                        let duration = task_start.elapsed();
+
+                        // A skipped URL is done with; retrying it would only skip it again
+                        let _ = self
+                            .task_queue
+                            .complete_task(&task.id, None, duration)
+                            .await;
+
+                        let sniffed_type = match &reason {
+                            SkipReason::BinaryContent(mime) => Some(mime.clone()),
+                            _ => None,
+                        };
+                        let result = CrawlResultData {
+                            url: url.clone(),
+                            content: None,
+                            error: None,
+                            skip: Some(reason),
+                            duration,
+                            status_code: None,
+                            alternates: Vec::new(),
+                            user_agent: None,
+                            redirect_chain: Vec::new(),
+                            depth: task.depth,
+                            parent_url: parent_url.clone(),
+                            structured: None,
+                            language: PageLanguage::default(),
+                            metadata: self.result_metadata(&task),
+                            relevance_score: None,
+                            raw_html_hash: None,
+                            screenshot: None,
+                            sanitized_html: None,
+                            pii_scrubbed: BTreeMap::new(),
+                            keywords: Vec::new(),
+                            content_encoding: None,
+                            pagination: task.pagination.clone(),
+                            sniffed_type,
+                        };
 
-                    results.push(result);
+                        // Update statistics
+                        self.statistics.lock().await.url_skipped();
+
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        let duration = task_start.elapsed();
+
+                        // Mark task as failed; permanent failures are not retried
+                        let error = format!("Network error: {}", e);
+                        let crawl_error = CrawlError::from_anyhow_error(&e);
+                        let _ = match crawl_error.permanent_failure() {
+                            Some(failure) => {
+                                tracing::info!(url = %url, failure = %failure, "Permanent failure, not retrying");
+                                self.task_queue
+                                    .fail_task_permanently(&task.id, error, duration)
+                                    .await
+                            }
+                            None => self.task_queue.fail_task(&task.id, error, duration).await,
+                        };
+
+                        let result = CrawlResultData {
+                            url: url.clone(),
+                            content: None,
+                            error: Some(e.to_string()),
+                            skip: None,
+                            duration,
+                            status_code: None,
+                            alternates: Vec::new(),
+                            user_agent: None,
+                            redirect_chain: Vec::new(),
+                            depth: task.depth,
+                            parent_url: parent_url.clone(),
+                            structured: None,
+                            language: PageLanguage::default(),
+                            metadata: self.result_metadata(&task),
+                            relevance_score: None,
+                            raw_html_hash: None,
+                            screenshot: None,
+                            sanitized_html: None,
+                            pii_scrubbed: BTreeMap::new(),
+                            keywords: Vec::new(),
+                            content_encoding: None,
+                            pagination: task.pagination.clone(),
+                            sniffed_type: None,
+                        };
+
+                        // Update statistics
+                        {
+                            let mut stats = self.statistics.lock().await;
+                            stats.url_completed(
+                                url.host_str().unwrap_or("unknown"),
+                                false,
+                                duration,
+                            );
+                        }
+
+                        results.push(result);
+                    }
+                }
+
+                if let (Some(store), Some(result)) = (result_store.as_deref_mut(), results.last()) {
+                    self.store_result(store, result).await?;
+                }
+
+                if let Some(webhooks) = &self.webhooks
+                    && let Some(result) = results.last()
+                {
+                    webhooks.record_outcome(url.host_str().unwrap_or("unknown"), result.outcome());
                 }
+
+                if stop_reason.is_none()
+                    && let (Some(tracker), Some(result)) = (stop_tracker.as_mut(), results.last())
+                    && let Some(reason) = tracker.record(
+                        &url,
+                        result.outcome(),
+                        result
+                            .content
+                            .as_ref()
+                            .map(|content| content.content.as_str()),
+                        selector_matched,
+                    )
+                {
+                    tracing::info!(
+                        session_id = %self.session_id,
+                        reason = %reason,
+                        "Stop condition met, stopping crawl"
+                    );
+                    stop_reason = Some(reason);
+                }
+            }
+            if let Some(reason) = stop_reason {
+                return Ok((results, QueueExit::Stopped(reason)));
+            }
+            if interrupted > 0 {
+                tracing::warn!(
+                    session_id = %self.session_id,
+                    interrupted,
+                    "Session time box ended with tasks in flight, requeued them"
+                );
+                return Ok((results, QueueExit::TimeBoxed));
             }
 
             // Check if queue is empty
@@ -299,62 +1719,521 @@ impl CrawlSession {
             }
         }
 
-        Ok(results)
+        Ok((results, QueueExit::Drained))
     }
 
-    /// Store results to configured storage
-    async fn store_results_to_storage(
+    /// Crawl a dequeued task, with when it started and how it went
+    ///
+    /// `None` when the task left the domain scope and was completed as a skip.
+    async fn crawl_task(
         &self,
-        results: &[CrawlResultData],
-        storage: &DataStorage,
+        mut task: CrawlTask,
+    ) -> Option<(CrawlTask, Instant, Result<CrawlOutcome, Error>)> {
+        task.url = self.crawler.canonical_url(task.url);
+        let url = task.url.clone();
+        let parent_url = task
+            .provenance
+            .as_ref()
+            .map(|provenance| provenance.parent_url.clone());
+        let task_start = Instant::now();
+
+        // Domain scope may have been narrowed by a configuration update
+        if !self
+            .live_filters
+            .lock()
+            .await
+            .accepts_host(url.host_str().unwrap_or(""))
+        {
+            tracing::info!(
+                session_id = %self.session_id,
+                url = %url,
+                "Skipping URL outside the updated domain scope"
+            );
+            self.record_skip(
+                &url,
+                SkipReason::OutOfScope("the updated domain scope".to_string()),
+                SkipStage::Frontier,
+                parent_url.as_ref(),
+            );
+            let _ = self
+                .task_queue
+                .complete_task(&task.id, None, task_start.elapsed())
+                .await;
+            return None;
+        }
+
+        // Log crawl start
+        self.event_logger
+            .log_crawl_start(&url, Some(task.depth as u32), Some("CrawlSession/1.0"));
+
+        let outcome = self
+            .crawler
+            .crawl_outcome(url, task.region.clone(), parent_url.as_ref())
+            .await;
+        Some((task, task_start, outcome))
+    }
+
+    /// Enqueue the alternates of a page that are in one of the accepted languages
+    async fn enqueue_alternates(&self, parent: &CrawlTask, alternates: &[HreflangAlternate]) {
+        let accepted = &self.config.crawler_config.accepted_languages;
+        for alternate in alternates
+            .iter()
+            .filter(|alternate| alternate.url != parent.url)
+        {
+            if !alternate.matches_languages(accepted) {
+                self.record_skip(
+                    &alternate.url,
+                    SkipReason::LinkFiltered(format!(
+                        "hreflang {} is not an accepted language",
+                        alternate.hreflang
+                    )),
+                    SkipStage::Discovery,
+                    Some(&parent.url),
+                );
+                continue;
+            }
+            self.enqueue_discovered(parent, &alternate.url, "hreflang", "alternate", None)
+                .await;
+        }
+    }
+
+    /// Enqueue a page's canonical URL if the navigation policy allows following it
+    async fn enqueue_canonical(&self, parent: &CrawlTask, canonical: &Url) {
+        let follow = self
+            .config
+            .crawler_config
+            .meta_navigation
+            .clone()
+            .unwrap_or_default()
+            .follow_canonical;
+        if !follow.allows(&parent.url, canonical) {
+            self.record_skip(
+                canonical,
+                SkipReason::OutOfScope("the canonical-link policy".to_string()),
+                SkipStage::Discovery,
+                Some(&parent.url),
+            );
+            return;
+        }
+        self.enqueue_discovered(parent, canonical, "canonical", "canonical", None)
+            .await;
+    }
+
+    /// Enqueue a page's `rel="next"` page while its chain is within the page budget
+    async fn enqueue_next_page(&self, parent: &CrawlTask, next_page: &Url) {
+        let Some(pagination) = &self.config.crawler_config.pagination else {
+            return;
+        };
+        let chain = PaginationChain::following(parent);
+        if !pagination.follow_next {
+            return;
+        }
+        if chain.page > pagination.max_pages_per_chain {
+            self.record_skip(
+                next_page,
+                SkipReason::PaginationBudgetExceeded(chain.id),
+                SkipStage::Discovery,
+                Some(&parent.url),
+            );
+            return;
+        }
+        self.enqueue_discovered(parent, next_page, "Pagination", "next", Some(chain))
+            .await;
+    }
+
+    /// Enqueue a URL found on `parent`'s page, recording it in the skip report when
+    /// the session's checks leave it out
+    ///
+    /// `kind` is the link's category unless categorization rules are configured;
+    /// `rel` is the relation the `<link>` element declared it with.
+    async fn enqueue_discovered(
+        &self,
+        parent: &CrawlTask,
+        url: &Url,
+        kind: &str,
+        rel: &str,
+        pagination: Option<PaginationChain>,
+    ) {
+        if !self
+            .live_filters
+            .lock()
+            .await
+            .accepts_host(url.host_str().unwrap_or(""))
+        {
+            self.record_skip(
+                url,
+                SkipReason::OutOfScope("the updated domain scope".to_string()),
+                SkipStage::Discovery,
+                Some(&parent.url),
+            );
+            return;
+        }
+        // Links of a scoped seed stay within the seed's own depth and domain limits
+        if let Some(scope) = &parent.scope
+            && !(scope.allows_depth(parent.depth + 1) && scope.allows_url(url))
+        {
+            self.record_skip(
+                url,
+                SkipReason::OutOfScope(format!("the scope of seed domain {}", scope.domain)),
+                SkipStage::Discovery,
+                Some(&parent.url),
+            );
+            return;
+        }
+        // Only the site's random sample is queued
+        if let Some(sampler) = &self.sampler {
+            let admitted = sampler.lock().unwrap_or_else(|e| e.into_inner()).admit(url);
+            if let Err(reason) = admitted {
+                self.record_skip(url, reason, SkipStage::Discovery, Some(&parent.url));
+                return;
+            }
+        }
+        let category = match &self.categorizer {
+            Some(categorizer) => {
+                let (category, _) =
+                    categorizer.categorize(url, &parent.url, "", LinkRegion::Head, Some(rel));
+                format!("{:?}", category)
+            }
+            None => kind.to_string(),
+        };
+        let category = category.as_str();
+        let enqueued = match pagination {
+            Some(chain) => {
+                self.task_queue
+                    .enqueue_next_page(parent, url.clone(), chain)
+                    .await
+            }
+            None => {
+                self.task_queue
+                    .enqueue_discovered(
+                        parent,
+                        url.clone(),
+                        TaskPriority::Normal,
+                        Some(category.to_string()),
+                    )
+                    .await
+            }
+        };
+        match enqueued {
+            Ok(EnqueueOutcome::DomainBudgetExceeded { domain }) => self.record_skip(
+                url,
+                SkipReason::DomainBudgetExceeded(domain),
+                SkipStage::Discovery,
+                Some(&parent.url),
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                session_id = %self.session_id,
+                url = %url,
+                category,
+                error = %e,
+                "Failed to enqueue discovered URL"
+            ),
+        }
+    }
+
+    /// Add a URL to the session's skip report, if it keeps one
+    fn record_skip(&self, url: &Url, reason: SkipReason, stage: SkipStage, source: Option<&Url>) {
+        if let Some(report) = &self.skips {
+            report.skip(url, reason, stage, source);
+        }
+    }
+
+    /// Keep a page's raw body in the raw HTML store; failures only lose the blob
+    async fn store_raw_html(&self, url: &Url, body: Option<&[bytes::Bytes]>) -> Option<String> {
+        let (storage, body) = (self.storage.as_ref()?, body?);
+        storage
+            .store_raw_html(body.to_vec())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(url = %url, error = %e, "Failed to store raw HTML");
+                None
+            })
+    }
+
+    /// Sanitized HTML of a crawled page's raw body, when configured
+    async fn sanitize_html(
+        &self,
+        url: &Url,
+        body: Option<&[bytes::Bytes]>,
+        charset: &'static encoding_rs::Encoding,
+    ) -> Option<String> {
+        self.storage.as_ref()?;
+        let sanitizer = Arc::clone(self.sanitizer.as_ref()?);
+        let body = body?.to_vec();
+        let url = url.clone();
+        let scrubber = self.crawler.pii_scrubber().cloned();
+        tokio::task::spawn_blocking(move || {
+            let html = sanitizer.sanitize_chunks(&body, charset, &url);
+            // Sanitizing decodes entities, so the output is masked once more
+            match scrubber {
+                Some(scrubber) => scrubber.mask(&html),
+                None => html,
+            }
+        })
+        .await
+        .ok()
+    }
+
+    /// Capture and store a screenshot of a crawled page; failures only lose the image
+    ///
+    /// The browser goes through `proxy` (or the forward proxy) with `user_agent`,
+    /// as the page was fetched.
+    async fn capture_screenshot(
+        &self,
+        url: &Url,
+        proxy: Option<&ProxyConfig>,
+        user_agent: &str,
+    ) -> Option<ScreenshotRef> {
+        let storage = self.storage.as_ref()?;
+        let capture = self.config.screenshot_capture.as_ref()?;
+        let config = self.config.screenshots.as_ref()?;
+        let stored = match self.crawler.browser_route(url, proxy, user_agent).await {
+            Ok(route) => match capture.capture(url, config, &route).await {
+                Ok(screenshot) => storage.store_screenshot(screenshot).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        stored.unwrap_or_else(|e| {
+            tracing::warn!(url = %url, error = %e, "Failed to capture screenshot");
+            None
+        })
+    }
+
+    /// Metadata of the task's domain (most specific match) overlaid with its own
+    fn result_metadata(&self, task: &CrawlTask) -> BTreeMap<String, String> {
+        let host = host_key(task.url.host_str().unwrap_or(""));
+        let mut metadata = self
+            .config
+            .domain_metadata
+            .iter()
+            .flatten()
+            .filter(|(domain, _)| {
+                let domain = host_key(domain);
+                host == domain || host.ends_with(&format!(".{}", domain))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, metadata)| metadata.clone())
+            .unwrap_or_default();
+        metadata.extend(task.metadata.clone());
+        if let Some(category) = task
+            .provenance
+            .as_ref()
+            .and_then(|provenance| provenance.link_category.clone())
+        {
+            metadata
+                .entry("link_category".to_string())
+                .or_insert(category);
+        }
+        metadata
+    }
+
+    /// Start the writer that stores results as the crawl produces them
+    fn open_result_store(&self, storage: &Arc<DataStorage>) -> ResultStore {
+        let feed = self.config.feed.clone().and_then(|config| {
+            SessionFeed::open(config, &self.session_id)
+                .map_err(|e| tracing::warn!(error = %e, "Could not open the feed series"))
+                .ok()
+        });
+        // A page counts as visited for later sessions only once it is stored
+        let on_stored = self
+            .config
+            .crawler_config
+            .persistent_visited
+            .is_some()
+            .then(|| {
+                let crawler = self.crawler.clone();
+                Box::new(move |result: &StoredCrawlResult| {
+                    // Only fetched pages carry a status code
+                    let url = Url::parse(&result.url)
+                        .ok()
+                        .filter(|_| result.metadata.status_code.is_some());
+                    let redirect_chain: Vec<Url> = result
+                        .metadata
+                        .redirect_chain
+                        .iter()
+                        .filter_map(|hop| Url::parse(hop).ok())
+                        .collect();
+                    let crawler = crawler.clone();
+                    Box::pin(async move {
+                        if let Some(url) = url {
+                            crawler.record_visit(&url, &redirect_chain).await;
+                        }
+                    }) as BoxFuture<'static, ()>
+                }) as OnStored
+            });
+        ResultStore {
+            writer: StorageWriter::spawn_with(
+                storage.clone(),
+                StorageWriterConfig::default(),
+                on_stored,
+            ),
+            feed,
+            sent: 0,
+        }
+    }
+
+    /// Hand a finished result to the storage writer
+    async fn store_result(
+        &self,
+        store: &mut ResultStore,
+        result: &CrawlResultData,
     ) -> Result<(), Error> {
-        for result in results {
-            let stored_result = StoredCrawlResult {
-                url: result.url.to_string(),
-                title: result
-                    .content
-                    .as_ref()
-                    .map(|c| extract_title_from_html(&c.content))
-                    .flatten(),
-                content: result.content.as_ref().map(|c| c.content.clone()),
-                word_count: result.content.as_ref().map(|c| c.word_count).unwrap_or(0),
-                language: result
-                    .content
-                    .as_ref()
-                    .and_then(|c| c.detected_language.as_ref())
-                    .map(|lang| format!("{:?}", lang)),
-                links_found: result
-                    .content
-                    .as_ref()
-                    .map(|c| extract_links_from_html(&c.content))
-                    .unwrap_or_default(),
-                metadata: crate::storage::CrawlMetadata {
-                    status_code: result.status_code,
-                    content_type: Some("text/html".to_string()),
-                    content_length: result.content.as_ref().map(|c| c.content.len() as u64),
-                    response_time_ms: result.duration.as_millis() as u64,
-                    depth: 0,
-                    parent_url: None,
-                    crawl_session_id: self.session_id.clone(),
-                },
-                timestamp: std::time::SystemTime::now(),
-            };
+        let mut stored_result = self.stored_result(result);
+        #[cfg(feature = "search")]
+        if let Some(index) = &self.search_index {
+            index.add(&stored_result)?;
+        }
+        if let Some(feed) = &mut store.feed {
+            feed.observe(&stored_result);
+        }
+        // The text is indexed and summarized, but only the sanitized HTML is written
+        let replace_text = self
+            .config
+            .sanitized_html
+            .as_ref()
+            .is_some_and(|config| config.replace_text);
+        if replace_text && stored_result.sanitized_html.is_some() {
+            stored_result.content = None;
+        }
+        store.writer.send(stored_result).await?;
+        store.sent += 1;
+        Ok(())
+    }
 
-            storage.store_result(&stored_result).await?;
+    /// Wait for the writer to store every result, then write the feed and commit
+    /// the search index
+    async fn close_result_store(
+        &self,
+        store: ResultStore,
+        storage: &Arc<DataStorage>,
+    ) -> Result<(), Error> {
+        let report = store.writer.close().await?;
+        if report.failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} results could not be stored",
+                report.failed,
+                store.sent
+            ));
+        }
+
+        if let Some(feed) = store.feed {
+            storage.write_feed(&feed).await?;
+            let saved = tokio::task::spawn_blocking(move || feed.save_series())
+                .await
+                .map_err(Error::from)
+                .and_then(|r| r)?;
+            if let Some(path) = saved {
+                tracing::info!(path = %path.display(), "Series feed updated");
+            }
+        }
+
+        #[cfg(feature = "search")]
+        if let Some(index) = &self.search_index {
+            let index = Arc::clone(index);
+            tokio::task::spawn_blocking(move || index.commit()).await??;
         }
 
         Ok(())
     }
 
+    /// A result as it is written to storage
+    fn stored_result(&self, result: &CrawlResultData) -> StoredCrawlResult {
+        StoredCrawlResult {
+            schema_version: SCHEMA_VERSION,
+            url: result.url.to_string(),
+            title: result
+                .content
+                .as_ref()
+                .map(|c| extract_title_from_html(&c.content))
+                .flatten()
+                .map(|title| match self.crawler.pii_scrubber() {
+                    Some(scrubber) => scrubber.mask(&title),
+                    None => title,
+                }),
+            content: result.content.as_ref().map(|c| c.content.clone()),
+            word_count: result.content.as_ref().map(|c| c.word_count).unwrap_or(0),
+            language: result
+                .content
+                .as_ref()
+                .and_then(|c| c.detected_language.as_ref())
+                .map(|lang| format!("{:?}", lang)),
+            links_found: result
+                .content
+                .as_ref()
+                .map(|c| extract_links_from_html(&c.content))
+                .unwrap_or_default(),
+            relevance_score: result.relevance_score,
+            alternates: result.alternates.clone(),
+            structured: result.structured.clone(),
+            declared_language: result.language.declared.clone(),
+            detected_language: result
+                .language
+                .detected
+                .as_ref()
+                .map(|lang| format!("{:?}", lang)),
+            raw_html_hash: result.raw_html_hash.clone(),
+            screenshot: result.screenshot.clone(),
+            sanitized_html: result.sanitized_html.clone(),
+            pii_scrubbed: result.pii_scrubbed.clone(),
+            keywords: result.keywords.clone(),
+            metadata: crate::storage::CrawlMetadata {
+                status_code: result.status_code,
+                content_type: Some("text/html".to_string()),
+                content_length: result.content.as_ref().map(|c| c.content.len() as u64),
+                content_encoding: result.content_encoding.clone(),
+                response_time_ms: result.duration.as_millis() as u64,
+                depth: result.depth,
+                parent_url: result.parent_url.as_ref().map(|url| url.to_string()),
+                crawl_session_id: self.session_id.clone(),
+                user_agent: result.user_agent.clone(),
+                redirect_chain: result
+                    .redirect_chain
+                    .iter()
+                    .map(|url| url.to_string())
+                    .collect(),
+                custom: result.metadata.clone(),
+                pagination: result.pagination.clone(),
+                sniffed_type: result.sniffed_type.clone(),
+            },
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
     /// Get real-time session statistics
     pub async fn get_statistics(&self) -> SessionStatistics {
         self.statistics.lock().await.clone()
     }
 
+    /// Current throughput, queue depth, in-flight requests and ETA, also per domain
+    pub async fn progress(&self) -> SessionProgress {
+        let queue = self.task_queue.snapshot(0).await;
+        let statistics = self.statistics.lock().await.clone();
+        SessionProgress::new(&statistics, &queue)
+    }
+
+    /// Session progress every `interval`, for live displays
+    pub fn progress_updates(&self, interval: Duration) -> impl Stream<Item = SessionProgress> + '_ {
+        stream::unfold(
+            tokio::time::interval(interval),
+            move |mut ticker| async move {
+                ticker.tick().await;
+                Some((self.progress().await, ticker))
+            },
+        )
+    }
+
     /// Get session ID
     pub fn session_id(&self) -> &str {
         &self.session_id
     }
+
+    /// Per-domain task counts and the next `next_n` tasks of the session's queue,
+    /// also while `execute_crawl` runs
+    pub async fn queue_snapshot(&self, next_n: usize) -> QueueSnapshot {
+        self.task_queue.snapshot(next_n).await
+    }
 }
 
 /// Extract title from HTML content
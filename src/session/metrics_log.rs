@@ -0,0 +1,196 @@
+/// Session metrics recorded as a time series
+///
+/// `SessionProgress` and `MetricsSnapshot` only describe the moment they are taken.
+/// While a session runs with a `MetricsLogConfig`, one row of queue, throughput and
+/// error metrics is appended to `metrics.csv` in the session directory
+/// (`<storage>/session_<id>/`) every interval, plus a last row when the crawl ends,
+/// so a run can be charted afterwards without a metrics server.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use super::progress::SessionProgress;
+use super::statistics::SessionStatistics;
+use crate::logging::session_events_path;
+use crate::queue::TaskQueue;
+use crate::storage::{CrawlerMetrics, MetricsSnapshot};
+
+/// File name of the metrics series inside a session directory
+pub const METRICS_FILE: &str = "metrics.csv";
+
+/// Metrics series file of `session_id` under the storage directory
+pub fn session_metrics_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    session_events_path(storage_dir, session_id).with_file_name(METRICS_FILE)
+}
+
+/// How often a metrics row is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsLogConfig {
+    pub interval_secs: u64,
+}
+
+impl Default for MetricsLogConfig {
+    fn default() -> Self {
+        Self { interval_secs: 10 }
+    }
+}
+
+/// One row of the metrics series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsRow {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub elapsed_secs: f64,
+    pub processed: usize,
+    pub successful: usize,
+    pub failed: usize,
+    /// Pending and retrying tasks
+    pub queue_depth: usize,
+    pub in_flight: usize,
+    /// Processed URLs per second since the session started
+    pub throughput: f64,
+    /// Share of processed URLs that failed, between 0 and 1
+    pub error_rate: f64,
+    /// HTTP requests sent, including retries and robots.txt fetches
+    pub requests: u64,
+    pub avg_response_time_ms: f64,
+    pub bytes_per_second: f64,
+}
+
+impl MetricsRow {
+    pub fn new(progress: &SessionProgress, snapshot: &MetricsSnapshot) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            elapsed_secs: progress.elapsed.as_secs_f64(),
+            processed: progress.processed,
+            successful: progress.successful,
+            failed: progress.failed,
+            queue_depth: progress.queue_depth,
+            in_flight: progress.in_flight,
+            throughput: progress.throughput,
+            error_rate: progress.error_rate(),
+            requests: snapshot.total_requests,
+            avg_response_time_ms: snapshot.avg_response_time_ms,
+            bytes_per_second: snapshot.bytes_per_second,
+        }
+    }
+}
+
+/// Append `row` to the series at `path`, writing the header first if the file is new
+pub fn append_metrics_row(path: &Path, row: &MetricsRow) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let is_new = file.metadata()?.len() == 0;
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(is_new)
+        .from_writer(file);
+    writer.serialize(row)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Rows of the series at `path`, oldest first
+pub fn load_metrics(path: &Path) -> Result<Vec<MetricsRow>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    Ok(reader.deserialize().collect::<Result<_, _>>()?)
+}
+
+/// Writes the metrics series of one session
+#[derive(Clone)]
+pub(crate) struct MetricsLog {
+    path: PathBuf,
+    statistics: Arc<Mutex<SessionStatistics>>,
+    task_queue: Arc<TaskQueue>,
+    metrics: Arc<CrawlerMetrics>,
+}
+
+impl MetricsLog {
+    pub(crate) fn new(
+        path: PathBuf,
+        statistics: Arc<Mutex<SessionStatistics>>,
+        task_queue: Arc<TaskQueue>,
+        metrics: Arc<CrawlerMetrics>,
+    ) -> Self {
+        Self {
+            path,
+            statistics,
+            task_queue,
+            metrics,
+        }
+    }
+
+    /// Append a row for the current state; failures are logged and skipped
+    pub(crate) async fn record(&self) {
+        let queue = self.task_queue.snapshot(0).await;
+        let progress = SessionProgress::new(&*self.statistics.lock().await, &queue);
+        let row = MetricsRow::new(&progress, &self.metrics.get_snapshot().await);
+        if let Err(e) = append_metrics_row(&self.path, &row) {
+            tracing::warn!(path = %self.path.display(), error = %e, "Failed to write metrics row");
+        }
+    }
+
+    /// Record a row every `interval` in the background until the handle is aborted
+    pub(crate) fn schedule(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.record().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    #[tokio::test]
+    async fn test_rows_are_appended_under_one_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_metrics_path(dir.path(), "s1");
+        assert!(path.ends_with("session_s1/metrics.csv"));
+
+        let statistics = Arc::new(Mutex::new(SessionStatistics::new()));
+        statistics.lock().await.session_started(2);
+        let task_queue = Arc::new(TaskQueue::new(2, 0));
+        task_queue
+            .enqueue_task(
+                Url::parse("https://a.example/").unwrap(),
+                crate::core::TaskPriority::Normal,
+            )
+            .await
+            .unwrap();
+        let metrics = Arc::new(CrawlerMetrics::new());
+        let log = MetricsLog::new(path.clone(), statistics.clone(), task_queue, metrics);
+
+        log.record().await;
+        statistics
+            .lock()
+            .await
+            .url_completed("a.example", false, Duration::from_millis(5));
+        log.record().await;
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches("timestamp").count(), 1);
+        let rows = load_metrics(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].queue_depth, 1);
+        assert_eq!(rows[0].processed, 0);
+        assert_eq!(rows[1].failed, 1);
+        assert_eq!(rows[1].error_rate, 1.0);
+    }
+}
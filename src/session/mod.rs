@@ -2,9 +2,38 @@
 ///
 /// This module provides high-level session orchestration for crawl operations,
 /// abstracting away the complexity of managing crawlers, queues, and results.
+pub mod checkpoint;
+pub mod live_config;
 pub mod manager;
+pub mod metrics_log;
+pub mod policy;
+pub mod progress;
+pub mod seed_history;
 pub mod statistics;
+pub mod stop;
 
 // Re-export main functionality
-pub use manager::{CrawlResultData, CrawlSession, CrawlSessionConfig, SessionResult};
-pub use statistics::{RealTimeStats, SessionStatistics};
+pub use checkpoint::{
+    CONFIG_DRIFT_FILE, CheckpointConfig, ConfigChange, ConfigDrift, ConfigFingerprint, DriftKind,
+    DriftRecord, SessionCheckpoint, append_drift_record, load_drift_records,
+    session_config_drift_path,
+};
+pub use live_config::ConfigUpdate;
+pub use manager::{
+    CrawlResultData, CrawlSession, CrawlSessionConfig, RemainingQueue, SessionResult,
+};
+pub use metrics_log::{
+    METRICS_FILE, MetricsLogConfig, MetricsRow, append_metrics_row, load_metrics,
+    session_metrics_path,
+};
+pub use policy::{CheckOutcome, CrawlPolicy, Decision, PolicyCheck};
+pub use progress::{DomainProgress, SessionProgress};
+pub use seed_history::{
+    BlacklistAction, BlacklistedSeed, SEED_BLACKLIST_FILE, SeedAdmission, SeedBlacklistReport,
+    SeedHistory, SeedHistoryConfig, SeedRecord, session_seed_blacklist_path,
+};
+pub use statistics::{
+    DomainDelta, DomainStatistics, LanguageDelta, MetricDelta, RealTimeStats, SessionStatistics,
+    StatisticsComparison,
+};
+pub use stop::{StopConditions, StopReason, StopTracker};
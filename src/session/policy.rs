@@ -0,0 +1,478 @@
+/// URL policy evaluation without fetching
+///
+/// `CrawlPolicy` runs the checks a session applies before crawling a seed (scheme,
+/// SSRF protection, domain scope, avoided extensions, link filters, URL budget, robots.txt)
+/// and reports the outcome of each, so seed lists can be validated up front and
+/// skipped seeds explained. The URL is canonicalized first, as the crawl does, and
+/// the extension and budget checks are the crawler's own. Only robots.txt is
+/// fetched and, with SSRF protection, host names resolved. Per-session state such
+/// as the visited set and query-parameter budgets is not part of the policy.
+use anyhow::Error;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::live_config::{ConfigUpdate, LiveFilters};
+use crate::config::{FragmentConfig, WebCrawlerConfig};
+use crate::core::types::url_serde;
+use crate::core::{RobotsChecker, SkipReason};
+use crate::crawler::{UrlBudget, avoided_extension};
+use crate::network::{RobotsHandler, SsrfGuard, apply_internal_network};
+use crate::processing::{ExtensiveConfig, LinkProcessor};
+
+/// A single policy check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyCheck {
+    /// Only http and https URLs are crawled
+    Scheme,
+    /// Private, link-local and cloud metadata addresses, with SSRF protection on
+    Address,
+    /// Allowed and blocked domains
+    Scope,
+    /// `avoid_url_extensions` of the crawler configuration
+    Extension,
+    /// Include/exclude patterns, extension lists and scope of the extensive config
+    LinkFilter,
+    /// Total URL budget of the crawl (`max_total_urls`)
+    Budget,
+    Robots,
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckOutcome {
+    pub check: PolicyCheck,
+    pub passed: bool,
+    pub reason: String,
+}
+
+/// Outcome of all checks for one URL, in evaluation order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    /// Evaluated URL with the fragment policy applied
+    #[serde(with = "url_serde")]
+    pub url: Url,
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl Decision {
+    pub fn is_allowed(&self) -> bool {
+        self.checks.iter().all(|outcome| outcome.passed)
+    }
+
+    /// The first failed check, i.e. the one the crawl would report
+    pub fn skipped_by(&self) -> Option<&CheckOutcome> {
+        self.checks.iter().find(|outcome| !outcome.passed)
+    }
+}
+
+/// The checks a crawl applies to a URL, runnable on their own
+pub struct CrawlPolicy {
+    robots: RobotsHandler,
+    ssrf_guard: Option<SsrfGuard>,
+    filters: LiveFilters,
+    avoid_extensions: Vec<String>,
+    link_processor: Option<LinkProcessor>,
+    seeds: Vec<Url>,
+    fragments: FragmentConfig,
+    /// Never drawn from: every URL is evaluated as if nothing had been crawled yet
+    url_budget: UrlBudget,
+}
+
+impl CrawlPolicy {
+    /// Build the policy of a crawler configuration
+    pub fn new(config: &WebCrawlerConfig) -> Result<Self, Error> {
+        let timeouts = config.timeouts.clone().unwrap_or_default();
+        let mut builder = Client::builder()
+            .user_agent(config.user_agent.clone())
+            .connect_timeout(timeouts.connect())
+            .timeout(timeouts.total());
+        if let Some(internal_network) = &config.internal_network {
+            builder = apply_internal_network(builder, internal_network)?;
+        }
+        let client = builder.build()?;
+        let mut robots = RobotsHandler::new(client).with_user_agent(&config.user_agent);
+        if let Some(overrides) = config.robots_override.clone() {
+            robots = robots.with_overrides(overrides);
+        }
+
+        Ok(Self {
+            robots,
+            ssrf_guard: config.ssrf_protection.clone().map(|protection| {
+                SsrfGuard::new(protection).with_internal_network(config.internal_network.clone())
+            }),
+            filters: LiveFilters::default(),
+            avoid_extensions: config.avoid_url_extensions.clone(),
+            link_processor: None,
+            seeds: config
+                .base_url
+                .iter()
+                .filter_map(|url| Url::parse(url).ok())
+                .collect(),
+            fragments: config.fragments.clone().unwrap_or_default(),
+            url_budget: UrlBudget::new(config.max_total_urls),
+        })
+    }
+
+    /// Restrict to `allowed` domains (empty allows all) and exclude `blocked` ones
+    pub fn with_scope(mut self, allowed: Vec<String>, blocked: Vec<String>) -> Result<Self, Error> {
+        self.filters.apply(&ConfigUpdate {
+            allowed_domains: Some(allowed),
+            blocked_domains: Some(blocked),
+            ..ConfigUpdate::default()
+        })?;
+        Ok(self)
+    }
+
+    /// Apply the link filters of an extensive crawl configuration
+    pub fn with_link_filter(mut self, config: ExtensiveConfig) -> Result<Self, Error> {
+        self.link_processor = Some(LinkProcessor::new(config)?);
+        Ok(self)
+    }
+
+    /// Evaluate a URL as a seed (depth 0, nothing crawled yet)
+    ///
+    /// The checks see the URL with the fragment policy applied, as the crawl does.
+    pub async fn evaluate(&self, url: &Url) -> Decision {
+        let url = &self.fragments.canonicalize(url.clone());
+        let mut checks = Vec::with_capacity(8);
+        let mut record = |check: PolicyCheck, passed: bool, reason: String| {
+            checks.push(CheckOutcome {
+                check,
+                passed,
+                reason,
+            })
+        };
+
+        let http = matches!(url.scheme(), "http" | "https");
+        record(
+            PolicyCheck::Scheme,
+            http,
+            format!("Scheme '{}'", url.scheme()),
+        );
+
+        let address_check = match &self.ssrf_guard {
+            Some(guard) => Some(guard.check_resolved(url).await),
+            None => None,
+        };
+        let address_blocked = matches!(address_check, Some(Err(_)));
+        match address_check {
+            Some(Ok(())) => record(PolicyCheck::Address, true, "Public address".to_string()),
+            Some(Err(violation)) => record(PolicyCheck::Address, false, violation.to_string()),
+            None => {}
+        }
+
+        let host = url.host_str().unwrap_or("");
+        let in_scope = self.filters.accepts_host(host);
+        record(
+            PolicyCheck::Scope,
+            in_scope,
+            if in_scope {
+                format!("Host '{}' is in scope", host)
+            } else {
+                format!("Host '{}' is outside the domain scope", host)
+            },
+        );
+
+        match avoided_extension(url, &self.avoid_extensions) {
+            Some(extension) => record(
+                PolicyCheck::Extension,
+                false,
+                format!("Extension '{}' is avoided", extension),
+            ),
+            None => record(
+                PolicyCheck::Extension,
+                true,
+                "No avoided extension".to_string(),
+            ),
+        }
+
+        if let Some(processor) = &self.link_processor {
+            // Scope is relative to the seed on the same host, or to the URL itself
+            let base = self
+                .seeds
+                .iter()
+                .find(|seed| seed.host_str() == url.host_str())
+                .or(self.seeds.first())
+                .unwrap_or(url);
+            let (passed, reason) = processor.evaluate_link(url, base, 0);
+            record(PolicyCheck::LinkFilter, passed, reason);
+        }
+
+        if self.url_budget.has_room() {
+            record(
+                PolicyCheck::Budget,
+                true,
+                format!("Within the budget of {} URLs", self.url_budget.max()),
+            );
+        } else {
+            record(
+                PolicyCheck::Budget,
+                false,
+                SkipReason::UrlBudgetExceeded(self.url_budget.max()).to_string(),
+            );
+        }
+
+        if !http {
+            record(
+                PolicyCheck::Robots,
+                false,
+                "robots.txt needs an http(s) URL".to_string(),
+            );
+        } else if address_blocked {
+            record(
+                PolicyCheck::Robots,
+                false,
+                "robots.txt is not fetched from a blocked address".to_string(),
+            );
+        } else if let Some(reason) = self.robots.override_reason(url) {
+            record(
+                PolicyCheck::Robots,
+                true,
+                format!("robots.txt overridden: {}", reason),
+            );
+        } else {
+            match self.robots.is_allowed_by_robots(url).await {
+                Ok(true) => record(
+                    PolicyCheck::Robots,
+                    true,
+                    "Allowed by robots.txt".to_string(),
+                ),
+                Ok(false) => record(
+                    PolicyCheck::Robots,
+                    false,
+                    "Disallowed by robots.txt".to_string(),
+                ),
+                Err(e) => record(
+                    PolicyCheck::Robots,
+                    false,
+                    format!("robots.txt check failed: {}", e),
+                ),
+            }
+        }
+
+        Decision {
+            url: url.clone(),
+            checks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FragmentPolicy, RobotsOverrideConfig, SsrfProtectionConfig};
+    use crate::crawler::{CrawlOutcome, WebCrawler};
+    use crate::processing::{DomainScope, LinkFilter};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A server answering every path with the same article
+    async fn serve_article() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let _ = stream.read(&mut buffer).await;
+                    let body = format!(
+                        "<html><head><title>Article</title></head><body><p>{}</p></body></html>",
+                        "The crawler reads this article about rivers and mountains. ".repeat(20)
+                    );
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_decision_reports_failed_checks() {
+        let config = WebCrawlerConfig {
+            robots_override: Some(RobotsOverrideConfig {
+                domains: vec!["example.com".to_string()],
+                include_subdomains: true,
+                reason: "test".to_string(),
+            }),
+            ..WebCrawlerConfig::default()
+        };
+        let policy = CrawlPolicy::new(&config)
+            .unwrap()
+            .with_scope(vec![], vec!["blocked.example.com".to_string()])
+            .unwrap();
+
+        let allowed = policy
+            .evaluate(&Url::parse("https://example.com/page").unwrap())
+            .await;
+        assert!(allowed.is_allowed());
+
+        let pdf = policy
+            .evaluate(&Url::parse("https://example.com/report.pdf").unwrap())
+            .await;
+        assert_eq!(pdf.skipped_by().unwrap().check, PolicyCheck::Extension);
+
+        let blocked = policy
+            .evaluate(&Url::parse("https://blocked.example.com/report.pdf").unwrap())
+            .await;
+        let failed: Vec<PolicyCheck> = blocked
+            .checks
+            .iter()
+            .filter(|outcome| !outcome.passed)
+            .map(|outcome| outcome.check)
+            .collect();
+        assert_eq!(failed, vec![PolicyCheck::Scope, PolicyCheck::Extension]);
+    }
+
+    #[tokio::test]
+    async fn test_seeds_resolving_to_private_addresses_are_refused() {
+        let config = WebCrawlerConfig {
+            ssrf_protection: Some(SsrfProtectionConfig::default()),
+            robots_override: Some(RobotsOverrideConfig {
+                domains: vec!["example.com".to_string()],
+                include_subdomains: true,
+                reason: "test".to_string(),
+            }),
+            ..WebCrawlerConfig::default()
+        };
+        let policy = CrawlPolicy::new(&config).unwrap();
+
+        let metadata = policy
+            .evaluate(&Url::parse("http://169.254.169.254/latest/meta-data/").unwrap())
+            .await;
+        let refused = metadata.skipped_by().unwrap();
+        assert_eq!(refused.check, PolicyCheck::Address);
+        assert_eq!(
+            refused.reason,
+            "169.254.169.254 is a cloud metadata endpoint"
+        );
+        assert!(
+            metadata
+                .checks
+                .iter()
+                .any(|outcome| outcome.check == PolicyCheck::Robots && !outcome.passed)
+        );
+
+        let local = policy
+            .evaluate(&Url::parse("http://localhost:8080/admin").unwrap())
+            .await;
+        assert_eq!(local.skipped_by().unwrap().check, PolicyCheck::Address);
+    }
+
+    #[tokio::test]
+    async fn test_fragments_follow_the_domain_policy() {
+        let config = WebCrawlerConfig {
+            robots_override: Some(RobotsOverrideConfig {
+                domains: vec!["example.com".to_string()],
+                include_subdomains: true,
+                reason: "test".to_string(),
+            }),
+            fragments: Some(FragmentConfig {
+                default_policy: FragmentPolicy::Strip,
+                domain_policies: [
+                    ("app.example.com".to_string(), FragmentPolicy::KeepHashbang),
+                    ("docs.example.com".to_string(), FragmentPolicy::Keep),
+                ]
+                .into(),
+            }),
+            ..WebCrawlerConfig::default()
+        };
+        let policy = CrawlPolicy::new(&config).unwrap();
+        for (url, expected) in [
+            ("https://example.com/page#top", "https://example.com/page"),
+            (
+                "https://app.example.com/#/route/x",
+                "https://app.example.com/#/route/x",
+            ),
+            (
+                "https://app.example.com/#!/inbox",
+                "https://app.example.com/#!/inbox",
+            ),
+            (
+                "https://app.example.com/help#section-2",
+                "https://app.example.com/help",
+            ),
+            (
+                "https://docs.example.com/guide#install",
+                "https://docs.example.com/guide#install",
+            ),
+        ] {
+            let decision = policy.evaluate(&Url::parse(url).unwrap()).await;
+            assert_eq!(decision.url.as_str(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_decides_as_the_crawl_does() {
+        let base = serve_article().await;
+        let config = |max_total_urls: usize| WebCrawlerConfig {
+            robots_override: Some(RobotsOverrideConfig {
+                domains: vec!["127.0.0.1".to_string()],
+                include_subdomains: false,
+                reason: "test".to_string(),
+            }),
+            max_total_urls,
+            ..WebCrawlerConfig::default()
+        };
+        let extensive = ExtensiveConfig {
+            domain_scope: DomainScope::Unrestricted,
+            link_filter: LinkFilter {
+                exclude_patterns: Some(vec!["#comments$".to_string()]),
+                ..LinkFilter::default()
+            },
+            ..ExtensiveConfig::default()
+        };
+        let policy = CrawlPolicy::new(&config(1))
+            .unwrap()
+            .with_link_filter(extensive)
+            .unwrap();
+        let crawler = WebCrawler::new(config(1), 2, 1).unwrap();
+
+        // The fragment is dropped before the link filter sees the URL
+        let post = base.join("post#comments").unwrap();
+        let decision = policy.evaluate(&post).await;
+        assert!(decision.is_allowed(), "{:?}", decision.skipped_by());
+        assert_eq!(decision.url, base.join("post").unwrap());
+        let outcome = crawler.crawl_outcome(post, None, None).await.unwrap();
+        assert!(matches!(outcome, CrawlOutcome::Page(_)));
+
+        // Avoided extensions are skipped by the crawl too
+        let report = base.join("report.pdf").unwrap();
+        assert_eq!(
+            policy.evaluate(&report).await.skipped_by().unwrap().check,
+            PolicyCheck::Extension
+        );
+        let outcome = crawler.crawl_outcome(report, None, None).await.unwrap();
+        assert!(matches!(
+            outcome,
+            CrawlOutcome::Skipped(SkipReason::ExtensionBlocked(extension)) if extension == ".pdf"
+        ));
+
+        // The crawl fetches no more URLs than its budget; with none, nothing is fetched
+        let outcome = crawler
+            .crawl_outcome(base.join("other").unwrap(), None, None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            CrawlOutcome::Skipped(SkipReason::UrlBudgetExceeded(1))
+        ));
+        let policy = CrawlPolicy::new(&config(0)).unwrap();
+        let crawler = WebCrawler::new(config(0), 2, 1).unwrap();
+        let decision = policy.evaluate(&base).await;
+        let skipped = decision.skipped_by().unwrap();
+        assert_eq!(skipped.check, PolicyCheck::Budget);
+        assert_eq!(skipped.reason, "total URL budget of 0 exceeded");
+        let outcome = crawler.crawl_outcome(base, None, None).await.unwrap();
+        assert!(matches!(
+            outcome,
+            CrawlOutcome::Skipped(SkipReason::UrlBudgetExceeded(0))
+        ));
+    }
+}
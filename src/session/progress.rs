@@ -0,0 +1,190 @@
+/// Live progress of a running session
+///
+/// Combines the session statistics (completed URLs per domain) with a queue snapshot
+/// (queued and in-flight tasks per domain) into one view for progress displays:
+/// throughput, queue depth, error rate and an ETA, globally and per domain.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::statistics::SessionStatistics;
+use crate::queue::QueueSnapshot;
+
+/// Progress of one domain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DomainProgress {
+    pub domain: String,
+    pub in_flight: usize,
+    /// Pending and retrying tasks
+    pub queued: usize,
+    pub processed: usize,
+    pub failed: usize,
+}
+
+impl DomainProgress {
+    /// Share of processed URLs that failed, between 0 and 1
+    pub fn error_rate(&self) -> f64 {
+        if self.processed == 0 {
+            0.0
+        } else {
+            self.failed as f64 / self.processed as f64
+        }
+    }
+}
+
+/// Progress of the whole session at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionProgress {
+    pub elapsed: Duration,
+    pub processed: usize,
+    pub successful: usize,
+    pub failed: usize,
+    /// Pending and retrying tasks
+    pub queue_depth: usize,
+    pub in_flight: usize,
+    /// Processed URLs per second since the session started
+    pub throughput: f64,
+    /// Time to finish the current queue at the current throughput
+    pub eta: Option<Duration>,
+    /// Busiest domains first (in flight, then queued, then processed)
+    pub domains: Vec<DomainProgress>,
+}
+
+impl SessionProgress {
+    pub fn new(statistics: &SessionStatistics, queue: &QueueSnapshot) -> Self {
+        let mut domains: BTreeMap<&str, DomainProgress> = BTreeMap::new();
+        for (domain, stats) in &statistics.domains {
+            let progress = domains.entry(domain).or_default();
+            progress.processed = stats.processed;
+            progress.failed = stats.processed.saturating_sub(stats.successful);
+        }
+        for snapshot in &queue.domains {
+            let progress = domains.entry(&snapshot.domain).or_default();
+            progress.in_flight = snapshot.in_progress;
+            progress.queued = snapshot.pending + snapshot.retrying;
+        }
+
+        let mut domains: Vec<DomainProgress> = domains
+            .into_iter()
+            .map(|(domain, progress)| DomainProgress {
+                domain: domain.to_string(),
+                ..progress
+            })
+            .collect();
+        domains.sort_by(|a, b| {
+            (b.in_flight, b.queued, b.processed).cmp(&(a.in_flight, a.queued, a.processed))
+        });
+
+        let elapsed = statistics
+            .start_time
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+        let queue_depth = domains.iter().map(|domain| domain.queued).sum();
+        let in_flight = domains.iter().map(|domain| domain.in_flight).sum();
+        Self::from_counts(
+            elapsed,
+            statistics.successful_urls,
+            statistics.failed_urls,
+            queue_depth,
+            in_flight,
+            domains,
+        )
+    }
+
+    fn from_counts(
+        elapsed: Duration,
+        successful: usize,
+        failed: usize,
+        queue_depth: usize,
+        in_flight: usize,
+        domains: Vec<DomainProgress>,
+    ) -> Self {
+        let processed = successful + failed;
+        let throughput = if elapsed.is_zero() {
+            0.0
+        } else {
+            processed as f64 / elapsed.as_secs_f64()
+        };
+        let remaining = queue_depth + in_flight;
+        let eta = if remaining == 0 {
+            Some(Duration::ZERO)
+        } else if throughput > 0.0 {
+            Some(Duration::from_secs_f64(remaining as f64 / throughput))
+        } else {
+            None
+        };
+
+        Self {
+            elapsed,
+            processed,
+            successful,
+            failed,
+            queue_depth,
+            in_flight,
+            throughput,
+            eta,
+            domains,
+        }
+    }
+
+    /// Share of processed URLs that failed, between 0 and 1
+    pub fn error_rate(&self) -> f64 {
+        if self.processed == 0 {
+            0.0
+        } else {
+            self.failed as f64 / self.processed as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::{DomainQueueSnapshot, TaskQueue};
+
+    #[tokio::test]
+    async fn test_progress_merges_statistics_and_queue() {
+        let mut statistics = SessionStatistics::new();
+        statistics.url_completed("a.example", true, Duration::from_millis(100));
+        statistics.url_completed("a.example", false, Duration::from_millis(100));
+        statistics.url_completed("b.example", true, Duration::from_millis(100));
+        // Skipped URLs are neither processed nor failed
+        statistics.url_skipped();
+        statistics.url_skipped();
+
+        let mut queue = TaskQueue::new(4, 3).snapshot(0).await;
+        queue.domains = vec![
+            DomainQueueSnapshot {
+                domain: "b.example".to_string(),
+                pending: 3,
+                in_progress: 2,
+                retrying: 1,
+                dead: 0,
+            },
+            DomainQueueSnapshot {
+                domain: "c.example".to_string(),
+                pending: 2,
+                ..DomainQueueSnapshot::default()
+            },
+        ];
+
+        let progress = SessionProgress::new(&statistics, &queue);
+        assert_eq!(progress.processed, 3);
+        assert_eq!(progress.queue_depth, 6);
+        assert_eq!(progress.in_flight, 2);
+        assert!((progress.error_rate() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            progress
+                .domains
+                .iter()
+                .map(|domain| domain.domain.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b.example", "c.example", "a.example"]
+        );
+        assert_eq!(progress.domains[2].error_rate(), 0.5);
+
+        // 3 URLs in 3 seconds leaves 8 remaining for 8 seconds
+        let timed = SessionProgress::from_counts(Duration::from_secs(3), 2, 1, 6, 2, vec![]);
+        assert_eq!(timed.eta, Some(Duration::from_secs(8)));
+    }
+}
@@ -14,6 +14,8 @@ pub enum ResultStatus {
     InProgress,
     Completed,
     Failed(String),
+    /// Skipped by the visited sets, robots.txt or content checks
+    Skipped,
 }
 
 impl ResultCollector {
@@ -34,6 +36,8 @@ impl ResultCollector {
     pub fn add_result(&mut self, result: CrawlResultData) {
         let status = if result.content.is_some() {
             ResultStatus::Completed
+        } else if result.skip.is_some() {
+            ResultStatus::Skipped
         } else {
             ResultStatus::Failed(result.error.clone().unwrap_or_default())
         };
@@ -62,6 +66,7 @@ impl ResultCollector {
                 ResultStatus::InProgress => counts.in_progress += 1,
                 ResultStatus::Completed => counts.completed += 1,
                 ResultStatus::Failed(_) => counts.failed += 1,
+                ResultStatus::Skipped => counts.skipped += 1,
             }
         }
 
@@ -75,11 +80,12 @@ pub struct StatusCounts {
     pub in_progress: usize,
     pub completed: usize,
     pub failed: usize,
+    pub skipped: usize,
 }
 
 impl StatusCounts {
     pub fn total(&self) -> usize {
-        self.pending + self.in_progress + self.completed + self.failed
+        self.pending + self.in_progress + self.completed + self.failed + self.skipped
     }
 
     pub fn success_rate(&self) -> f64 {
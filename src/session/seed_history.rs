@@ -0,0 +1,308 @@
+/// Seed success history across scheduled runs
+///
+/// Recurring crawls keep re-fetching seeds whose sites died long ago. With a
+/// `SeedHistoryConfig`, every session records in a history file shared by its runs
+/// whether each seed could be crawled, and a seed that failed
+/// `max_consecutive_failures` runs in a row is blacklisted: later runs skip it or
+/// enqueue it at low priority. After `retry_after_runs` skipped runs a blacklisted
+/// seed gets one more chance, and a single success clears it. Each session lists
+/// the blacklisted seeds in `seed_blacklist.json` of its session directory.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use url::Url;
+
+use crate::logging::session_events_path;
+use crate::storage::durable::{FsyncPolicy, write_atomic};
+
+/// File name of the blacklist report inside a session directory
+pub const SEED_BLACKLIST_FILE: &str = "seed_blacklist.json";
+
+/// Blacklist report of `session_id` under the storage directory
+pub fn session_seed_blacklist_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    session_events_path(storage_dir, session_id).with_file_name(SEED_BLACKLIST_FILE)
+}
+
+/// What a run does with a blacklisted seed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlacklistAction {
+    /// Leave the seed out of the run
+    #[default]
+    Skip,
+    /// Crawl the seed after everything else of its group
+    Deprioritize,
+}
+
+/// Where seed history is kept and when a seed is blacklisted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedHistoryConfig {
+    /// JSON history shared by the scheduled runs of a crawl
+    pub path: PathBuf,
+    /// Failed runs in a row after which a seed is blacklisted
+    pub max_consecutive_failures: u32,
+    pub action: BlacklistAction,
+    /// Skipped runs after which a blacklisted seed is tried again; `None` keeps
+    /// it out until its history entry is removed
+    pub retry_after_runs: Option<u32>,
+}
+
+impl SeedHistoryConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_consecutive_failures: 3,
+            action: BlacklistAction::default(),
+            retry_after_runs: Some(10),
+        }
+    }
+}
+
+/// A seed's record across runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedRecord {
+    /// Runs that crawled the seed or found it failing
+    pub runs: u32,
+    pub successes: u32,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_success: Option<SystemTime>,
+    pub last_run: Option<SystemTime>,
+    /// When the seed was blacklisted; `None` while it is not
+    pub blacklisted_at: Option<SystemTime>,
+    /// Runs that skipped the seed since it was blacklisted or last retried
+    pub skipped_runs: u32,
+}
+
+/// How a run treats one seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedAdmission {
+    Crawl,
+    /// Blacklisted with `BlacklistAction::Deprioritize`
+    Deprioritize,
+    /// Blacklisted with `BlacklistAction::Skip`
+    Skip,
+    /// Blacklisted, but due for another chance
+    Retry,
+}
+
+/// A blacklisted seed in a session's report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlacklistedSeed {
+    pub seed: String,
+    pub consecutive_failures: u32,
+    pub runs: u32,
+    pub successes: u32,
+    pub last_error: Option<String>,
+    pub last_success: Option<SystemTime>,
+    pub blacklisted_at: Option<SystemTime>,
+    /// The seed was blacklisted by this session
+    pub newly_blacklisted: bool,
+}
+
+/// Blacklisted seeds after a session, and what the session did with them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeedBlacklistReport {
+    pub action: BlacklistAction,
+    pub blacklisted: Vec<BlacklistedSeed>,
+    /// Blacklisted seeds the session left out
+    pub skipped: Vec<String>,
+    /// Blacklisted seeds the session crawled at low priority
+    pub deprioritized: Vec<String>,
+    /// Seeds that were blacklisted and succeeded in this session
+    pub recovered: Vec<String>,
+}
+
+/// Per-seed outcomes of past runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeedHistory {
+    seeds: BTreeMap<String, SeedRecord>,
+}
+
+impl SeedHistory {
+    /// History at `path`, empty when no run wrote one yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("Invalid seed history {}", path.display()))
+    }
+
+    /// Write the history atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(
+            path,
+            &serde_json::to_vec_pretty(self)?,
+            FsyncPolicy::default(),
+        )
+    }
+
+    pub fn get(&self, seed: &Url) -> Option<&SeedRecord> {
+        self.seeds.get(seed.as_str())
+    }
+
+    /// Decide how this run treats `seed`, counting a skip against its retry wait
+    pub fn admit(&mut self, seed: &Url, config: &SeedHistoryConfig) -> SeedAdmission {
+        let Some(record) = self.seeds.get_mut(seed.as_str()) else {
+            return SeedAdmission::Crawl;
+        };
+        if record.blacklisted_at.is_none() {
+            return SeedAdmission::Crawl;
+        }
+        if config
+            .retry_after_runs
+            .is_some_and(|runs| record.skipped_runs >= runs)
+        {
+            record.skipped_runs = 0;
+            return SeedAdmission::Retry;
+        }
+        match config.action {
+            BlacklistAction::Skip => {
+                record.skipped_runs += 1;
+                SeedAdmission::Skip
+            }
+            BlacklistAction::Deprioritize => SeedAdmission::Deprioritize,
+        }
+    }
+
+    /// Record whether the seed could be crawled in this run
+    ///
+    /// Returns `true` when the outcome changed the seed's blacklisting.
+    pub fn record(
+        &mut self,
+        seed: &Url,
+        outcome: Result<(), String>,
+        config: &SeedHistoryConfig,
+    ) -> bool {
+        let now = SystemTime::now();
+        let record = self.seeds.entry(seed.to_string()).or_default();
+        record.runs += 1;
+        record.last_run = Some(now);
+        match outcome {
+            Ok(()) => {
+                record.successes += 1;
+                record.consecutive_failures = 0;
+                record.last_error = None;
+                record.last_success = Some(now);
+                record.skipped_runs = 0;
+                record.blacklisted_at.take().is_some()
+            }
+            Err(error) => {
+                record.consecutive_failures += 1;
+                record.last_error = Some(error);
+                let blacklist = record.blacklisted_at.is_none()
+                    && record.consecutive_failures >= config.max_consecutive_failures.max(1);
+                if blacklist {
+                    record.blacklisted_at = Some(now);
+                    record.skipped_runs = 0;
+                }
+                blacklist
+            }
+        }
+    }
+
+    /// Seeds currently blacklisted
+    pub fn blacklisted(&self) -> impl Iterator<Item = (&str, &SeedRecord)> {
+        self.seeds
+            .iter()
+            .filter(|(_, record)| record.blacklisted_at.is_some())
+            .map(|(seed, record)| (seed.as_str(), record))
+    }
+}
+
+impl SeedBlacklistReport {
+    /// Report of the seeds `history` has blacklisted, marking those in `newly`
+    pub fn from_history(history: &SeedHistory, action: BlacklistAction, newly: &[Url]) -> Self {
+        Self {
+            action,
+            blacklisted: history
+                .blacklisted()
+                .map(|(seed, record)| BlacklistedSeed {
+                    seed: seed.to_string(),
+                    consecutive_failures: record.consecutive_failures,
+                    runs: record.runs,
+                    successes: record.successes,
+                    last_error: record.last_error.clone(),
+                    last_success: record.last_success,
+                    blacklisted_at: record.blacklisted_at,
+                    newly_blacklisted: newly.iter().any(|url| url.as_str() == seed),
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Write the report as JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeds_are_blacklisted_after_consecutive_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SeedHistoryConfig {
+            max_consecutive_failures: 2,
+            retry_after_runs: Some(2),
+            ..SeedHistoryConfig::new(dir.path().join("seeds.json"))
+        };
+        let dead = Url::parse("https://dead.example/").unwrap();
+        let alive = Url::parse("https://alive.example/").unwrap();
+
+        let mut history = SeedHistory::load(&config.path).unwrap();
+        assert!(!history.record(&dead, Err("HTTP error: 503".to_string()), &config));
+        assert!(!history.record(&alive, Err("timeout".to_string()), &config));
+        assert!(!history.record(&alive, Ok(()), &config));
+        assert!(history.record(&dead, Err("HTTP error: 410".to_string()), &config));
+        history.save(&config.path).unwrap();
+
+        let mut history = SeedHistory::load(&config.path).unwrap();
+        assert_eq!(history.admit(&alive, &config), SeedAdmission::Crawl);
+        assert_eq!(history.admit(&dead, &config), SeedAdmission::Skip);
+        assert_eq!(history.admit(&dead, &config), SeedAdmission::Skip);
+        assert_eq!(history.admit(&dead, &config), SeedAdmission::Retry);
+        // Failing the retry keeps it blacklisted without counting as new
+        assert!(!history.record(&dead, Err("HTTP error: 410".to_string()), &config));
+        assert_eq!(history.admit(&dead, &config), SeedAdmission::Skip);
+
+        let report =
+            SeedBlacklistReport::from_history(&history, config.action, std::slice::from_ref(&dead));
+        assert_eq!(report.blacklisted.len(), 1);
+        assert_eq!(report.blacklisted[0].seed, dead.as_str());
+        assert_eq!(report.blacklisted[0].consecutive_failures, 3);
+        assert_eq!(
+            report.blacklisted[0].last_error.as_deref(),
+            Some("HTTP error: 410")
+        );
+        assert!(report.blacklisted[0].newly_blacklisted);
+
+        let deprioritize = SeedHistoryConfig {
+            action: BlacklistAction::Deprioritize,
+            retry_after_runs: None,
+            ..config.clone()
+        };
+        assert_eq!(
+            history.admit(&dead, &deprioritize),
+            SeedAdmission::Deprioritize
+        );
+        // One success clears the blacklisting
+        assert!(history.record(&dead, Ok(()), &config));
+        assert_eq!(history.admit(&dead, &config), SeedAdmission::Crawl);
+        assert_eq!(history.blacklisted().count(), 0);
+    }
+}
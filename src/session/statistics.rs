@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
 use std::time::{Duration, Instant};
 
 /// Session-level statistics and metrics
@@ -9,9 +12,43 @@ pub struct SessionStatistics {
     pub processed_urls: usize,
     pub successful_urls: usize,
     pub failed_urls: usize,
+    /// URLs skipped by the visited sets, robots.txt or content checks; not in
+    /// `processed_urls`
+    pub skipped_urls: usize,
     pub total_processing_time: Duration,
     pub average_processing_time: Duration,
     pub throughput_urls_per_second: f64,
+    /// Per-domain counts, keyed by host
+    pub domains: HashMap<String, DomainStatistics>,
+    /// Successful pages per detected language
+    pub languages: HashMap<String, usize>,
+}
+
+/// Statistics of one domain within a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainStatistics {
+    pub processed: usize,
+    pub successful: usize,
+    pub total_processing_time: Duration,
+}
+
+impl DomainStatistics {
+    /// Success rate as percentage
+    pub fn success_rate(&self) -> f64 {
+        if self.processed == 0 {
+            0.0
+        } else {
+            (self.successful as f64 / self.processed as f64) * 100.0
+        }
+    }
+
+    pub fn average_processing_time(&self) -> Duration {
+        if self.processed == 0 {
+            Duration::ZERO
+        } else {
+            self.total_processing_time / self.processed as u32
+        }
+    }
 }
 
 impl SessionStatistics {
@@ -23,9 +60,12 @@ impl SessionStatistics {
             processed_urls: 0,
             successful_urls: 0,
             failed_urls: 0,
+            skipped_urls: 0,
             total_processing_time: Duration::from_millis(0),
             average_processing_time: Duration::from_millis(0),
             throughput_urls_per_second: 0.0,
+            domains: HashMap::new(),
+            languages: HashMap::new(),
         }
     }
 
@@ -46,11 +86,18 @@ impl SessionStatistics {
         }
     }
 
-    /// Record a completed URL
-    pub fn url_completed(&mut self, success: bool, processing_time: Duration) {
+    /// Record a completed URL of `domain`
+    pub fn url_completed(&mut self, domain: &str, success: bool, processing_time: Duration) {
         self.processed_urls += 1;
         self.total_processing_time += processing_time;
 
+        let domain_stats = self.domains.entry(domain.to_string()).or_default();
+        domain_stats.processed += 1;
+        domain_stats.total_processing_time += processing_time;
+        if success {
+            domain_stats.successful += 1;
+        }
+
         if success {
             self.successful_urls += 1;
         } else {
@@ -63,6 +110,16 @@ impl SessionStatistics {
         }
     }
 
+    /// Record a URL that was skipped rather than fetched or failed
+    pub fn url_skipped(&mut self) {
+        self.skipped_urls += 1;
+    }
+
+    /// Record the detected language of a successful page
+    pub fn language_detected(&mut self, language: &str) {
+        *self.languages.entry(language.to_string()).or_insert(0) += 1;
+    }
+
     /// Get success rate as percentage
     pub fn success_rate(&self) -> f64 {
         if self.processed_urls == 0 {
@@ -91,6 +148,195 @@ impl SessionStatistics {
             None
         }
     }
+
+    /// Compare this session (the baseline) with `other` (the candidate)
+    ///
+    /// Deltas are `other - self`, so a positive throughput change means the
+    /// candidate configuration was faster.
+    pub fn compare(&self, other: &SessionStatistics) -> StatisticsComparison {
+        let domain_names: BTreeSet<&String> =
+            self.domains.keys().chain(other.domains.keys()).collect();
+        let domains = domain_names
+            .into_iter()
+            .map(|domain| {
+                let baseline = self.domains.get(domain).cloned().unwrap_or_default();
+                let candidate = other.domains.get(domain).cloned().unwrap_or_default();
+                DomainDelta {
+                    domain: domain.clone(),
+                    processed: MetricDelta::new(
+                        baseline.processed as f64,
+                        candidate.processed as f64,
+                    ),
+                    success_rate: MetricDelta::new(
+                        baseline.success_rate(),
+                        candidate.success_rate(),
+                    ),
+                    average_processing_ms: MetricDelta::new(
+                        baseline.average_processing_time().as_secs_f64() * 1000.0,
+                        candidate.average_processing_time().as_secs_f64() * 1000.0,
+                    ),
+                }
+            })
+            .collect();
+
+        let language_names: BTreeSet<&String> = self
+            .languages
+            .keys()
+            .chain(other.languages.keys())
+            .collect();
+        let languages = language_names
+            .into_iter()
+            .map(|language| LanguageDelta {
+                language: language.clone(),
+                share: MetricDelta::new(
+                    self.language_share(language),
+                    other.language_share(language),
+                ),
+            })
+            .collect();
+
+        StatisticsComparison {
+            processed_urls: MetricDelta::new(
+                self.processed_urls as f64,
+                other.processed_urls as f64,
+            ),
+            throughput_urls_per_second: MetricDelta::new(
+                self.throughput_urls_per_second,
+                other.throughput_urls_per_second,
+            ),
+            success_rate: MetricDelta::new(self.success_rate(), other.success_rate()),
+            average_processing_ms: MetricDelta::new(
+                self.average_processing_time.as_secs_f64() * 1000.0,
+                other.average_processing_time.as_secs_f64() * 1000.0,
+            ),
+            domains,
+            languages,
+        }
+    }
+
+    /// Percentage of language-tagged pages in `language`
+    fn language_share(&self, language: &str) -> f64 {
+        let total: usize = self.languages.values().sum();
+        if total == 0 {
+            0.0
+        } else {
+            (self.languages.get(language).copied().unwrap_or(0) as f64 / total as f64) * 100.0
+        }
+    }
+}
+
+/// A metric in the baseline and candidate sessions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub baseline: f64,
+    pub candidate: f64,
+}
+
+impl MetricDelta {
+    pub fn new(baseline: f64, candidate: f64) -> Self {
+        Self {
+            baseline,
+            candidate,
+        }
+    }
+
+    /// Absolute change from baseline to candidate
+    pub fn change(&self) -> f64 {
+        self.candidate - self.baseline
+    }
+
+    /// Relative change in percent, `None` when the baseline is zero
+    pub fn percent_change(&self) -> Option<f64> {
+        (self.baseline != 0.0).then(|| self.change() / self.baseline * 100.0)
+    }
+}
+
+/// Change of one domain between two sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainDelta {
+    pub domain: String,
+    pub processed: MetricDelta,
+    /// Success rate in percent
+    pub success_rate: MetricDelta,
+    pub average_processing_ms: MetricDelta,
+}
+
+/// Change of one language's share of pages (in percent) between two sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageDelta {
+    pub language: String,
+    pub share: MetricDelta,
+}
+
+/// Structured difference between two sessions, from `SessionStatistics::compare`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatisticsComparison {
+    pub processed_urls: MetricDelta,
+    pub throughput_urls_per_second: MetricDelta,
+    /// Success rate in percent
+    pub success_rate: MetricDelta,
+    pub average_processing_ms: MetricDelta,
+    /// All domains seen in either session, sorted by name
+    pub domains: Vec<DomainDelta>,
+    /// All languages seen in either session, sorted by name
+    pub languages: Vec<LanguageDelta>,
+}
+
+impl StatisticsComparison {
+    /// Plain-text report of the comparison
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "=== Session Comparison (baseline -> candidate) ===");
+        let totals = [
+            ("Processed URLs", &self.processed_urls),
+            ("Throughput (URLs/s)", &self.throughput_urls_per_second),
+            ("Success rate (%)", &self.success_rate),
+            ("Avg processing (ms)", &self.average_processing_ms),
+        ];
+        for (name, delta) in totals {
+            let _ = writeln!(report, "{:<22} {}", name, format_delta(delta));
+        }
+
+        if !self.domains.is_empty() {
+            let _ = writeln!(report, "\nDomains (success rate %, avg ms):");
+            for domain in &self.domains {
+                let _ = writeln!(
+                    report,
+                    "  {:<30} {} | {}",
+                    domain.domain,
+                    format_delta(&domain.success_rate),
+                    format_delta(&domain.average_processing_ms)
+                );
+            }
+        }
+
+        if !self.languages.is_empty() {
+            let _ = writeln!(report, "\nLanguages (share of pages %):");
+            for language in &self.languages {
+                let _ = writeln!(
+                    report,
+                    "  {:<30} {}",
+                    language.language,
+                    format_delta(&language.share)
+                );
+            }
+        }
+        report
+    }
+}
+
+fn format_delta(delta: &MetricDelta) -> String {
+    let percent = delta
+        .percent_change()
+        .map(|percent| format!(" ({:+.1}%)", percent))
+        .unwrap_or_default();
+    format!(
+        "{:.2} -> {:.2} ({:+.2}){}",
+        delta.baseline,
+        delta.candidate,
+        delta.change(),
+        percent
+    )
 }
 
 /// Real-time statistics for monitoring
@@ -122,3 +368,39 @@ impl RealTimeStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_sessions() {
+        let mut baseline = SessionStatistics::new();
+        baseline.url_completed("a.example", true, Duration::from_millis(200));
+        baseline.url_completed("a.example", false, Duration::from_millis(400));
+        baseline.language_detected("Eng");
+
+        let mut candidate = SessionStatistics::new();
+        candidate.url_completed("a.example", true, Duration::from_millis(100));
+        candidate.url_completed("b.example", true, Duration::from_millis(100));
+        candidate.language_detected("Eng");
+        candidate.language_detected("Deu");
+
+        let comparison = baseline.compare(&candidate);
+        assert_eq!(comparison.success_rate.change(), 50.0);
+        let percent = comparison.average_processing_ms.percent_change().unwrap();
+        assert!((percent + 66.67).abs() < 0.01);
+        assert_eq!(
+            comparison
+                .domains
+                .iter()
+                .map(|delta| delta.domain.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a.example", "b.example"]
+        );
+        assert_eq!(comparison.domains[1].processed.percent_change(), None);
+        assert_eq!(comparison.languages[1].language, "Eng");
+        assert_eq!(comparison.languages[1].share.change(), -50.0);
+        assert!(comparison.report().contains("Success rate (%)"));
+    }
+}
@@ -0,0 +1,276 @@
+/// Conditions that end a session before its queue is drained
+///
+/// A session can stop after a number of successful pages, when the failure rate
+/// over its most recent results gets too high, or at the first page that contains a
+/// target keyword or matches a CSS selector. The last two serve "find the first page
+/// that matches" crawls; the matching page is returned with the session result.
+use anyhow::Error;
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use url::Url;
+
+use crate::core::UrlOutcome;
+use crate::core::types::url_serde;
+
+/// Stop conditions of a session; unset conditions never trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopConditions {
+    /// Stop after this many successful pages
+    pub max_successes: Option<usize>,
+    /// Stop when the share of failures among the last `error_window` results exceeds this (0-1)
+    pub max_error_rate: Option<f64>,
+    pub error_window: usize,
+    /// Stop at the first page whose text contains one of these (case-insensitive)
+    pub match_keywords: Vec<String>,
+    /// Stop at the first page where this CSS selector matches an element
+    pub match_selector: Option<String>,
+}
+
+impl Default for StopConditions {
+    fn default() -> Self {
+        Self {
+            max_successes: None,
+            max_error_rate: None,
+            error_window: 20,
+            match_keywords: Vec::new(),
+            match_selector: None,
+        }
+    }
+}
+
+impl StopConditions {
+    /// The parsed `match_selector`, if set
+    pub fn selector(&self) -> Result<Option<Selector>, Error> {
+        self.match_selector
+            .as_deref()
+            .map(|selector| {
+                Selector::parse(selector)
+                    .map_err(|e| anyhow::anyhow!("Invalid match_selector '{}': {:?}", selector, e))
+            })
+            .transpose()
+    }
+}
+
+/// Why a session stopped early
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StopReason {
+    MaxSuccesses(usize),
+    ErrorRate {
+        rate: f64,
+        window: usize,
+    },
+    KeywordMatch {
+        #[serde(with = "url_serde")]
+        url: Url,
+        keyword: String,
+    },
+    SelectorMatch {
+        #[serde(with = "url_serde")]
+        url: Url,
+        selector: String,
+    },
+}
+
+impl StopReason {
+    /// The page that satisfied a keyword or selector condition
+    pub fn matched_url(&self) -> Option<&Url> {
+        match self {
+            StopReason::KeywordMatch { url, .. } | StopReason::SelectorMatch { url, .. } => {
+                Some(url)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::MaxSuccesses(count) => write!(f, "{} successful pages", count),
+            StopReason::ErrorRate { rate, window } => write!(
+                f,
+                "{:.1}% errors over the last {} results",
+                rate * 100.0,
+                window
+            ),
+            StopReason::KeywordMatch { url, keyword } => {
+                write!(f, "keyword '{}' found on {}", keyword, url)
+            }
+            StopReason::SelectorMatch { url, selector } => {
+                write!(f, "selector '{}' matched on {}", selector, url)
+            }
+        }
+    }
+}
+
+/// Evaluates the stop conditions as results come in
+pub struct StopTracker {
+    conditions: StopConditions,
+    keywords: Vec<String>,
+    successes: usize,
+    recent: VecDeque<bool>,
+}
+
+impl StopTracker {
+    pub fn new(conditions: StopConditions) -> Self {
+        let keywords = conditions
+            .match_keywords
+            .iter()
+            .map(|keyword| keyword.to_lowercase())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        Self {
+            conditions,
+            keywords,
+            successes: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Record one result, returning the condition it satisfies
+    ///
+    /// `text` is the extracted text of a successful page; `selector_matched` whether
+    /// the configured selector matched it. Skipped URLs count neither as successes
+    /// nor towards the error rate.
+    pub fn record(
+        &mut self,
+        url: &Url,
+        outcome: UrlOutcome,
+        text: Option<&str>,
+        selector_matched: bool,
+    ) -> Option<StopReason> {
+        if outcome == UrlOutcome::Skipped {
+            return None;
+        }
+        let success = outcome == UrlOutcome::Crawled;
+        if let Some(text) = text {
+            if selector_matched && let Some(selector) = &self.conditions.match_selector {
+                return Some(StopReason::SelectorMatch {
+                    url: url.clone(),
+                    selector: selector.clone(),
+                });
+            }
+            if !self.keywords.is_empty() {
+                let text = text.to_lowercase();
+                if let Some(index) = self
+                    .keywords
+                    .iter()
+                    .position(|keyword| text.contains(keyword.as_str()))
+                {
+                    return Some(StopReason::KeywordMatch {
+                        url: url.clone(),
+                        keyword: self.conditions.match_keywords[index].clone(),
+                    });
+                }
+            }
+            self.successes += 1;
+        }
+
+        if let Some(max_successes) = self.conditions.max_successes
+            && self.successes >= max_successes
+        {
+            return Some(StopReason::MaxSuccesses(self.successes));
+        }
+
+        let window = self.conditions.error_window.max(1);
+        self.recent.push_back(success);
+        if self.recent.len() > window {
+            self.recent.pop_front();
+        }
+        if let Some(max_error_rate) = self.conditions.max_error_rate
+            && self.recent.len() == window
+        {
+            let failures = self.recent.iter().filter(|success| !**success).count();
+            let rate = failures as f64 / window as f64;
+            if rate > max_error_rate {
+                return Some(StopReason::ErrorRate { rate, window });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::html_matches_selector;
+
+    #[test]
+    fn test_conditions_trigger_in_order() {
+        let url = Url::parse("https://a.example/").unwrap();
+
+        let mut successes = StopTracker::new(StopConditions {
+            max_successes: Some(2),
+            ..StopConditions::default()
+        });
+        assert_eq!(
+            successes.record(&url, UrlOutcome::Crawled, Some("one"), false),
+            None
+        );
+        assert_eq!(
+            successes.record(&url, UrlOutcome::Failed, None, false),
+            None
+        );
+        assert_eq!(
+            successes.record(&url, UrlOutcome::Crawled, Some("two"), false),
+            Some(StopReason::MaxSuccesses(2))
+        );
+
+        // The error rate is only judged once the window is full
+        let mut errors = StopTracker::new(StopConditions {
+            max_error_rate: Some(0.5),
+            error_window: 4,
+            ..StopConditions::default()
+        });
+        for _ in 0..3 {
+            assert_eq!(errors.record(&url, UrlOutcome::Failed, None, false), None);
+            // Skips in between neither fill the window nor dilute the failures
+            assert_eq!(errors.record(&url, UrlOutcome::Skipped, None, false), None);
+        }
+        assert_eq!(
+            errors.record(&url, UrlOutcome::Crawled, Some("ok"), false),
+            Some(StopReason::ErrorRate {
+                rate: 0.75,
+                window: 4
+            })
+        );
+
+        let conditions = StopConditions {
+            match_keywords: vec!["Quarterly Report".to_string()],
+            match_selector: Some("table.results".to_string()),
+            ..StopConditions::default()
+        };
+        let mut matches = StopTracker::new(conditions.clone());
+        let found = matches
+            .record(
+                &url,
+                UrlOutcome::Crawled,
+                Some("the quarterly report is out"),
+                false,
+            )
+            .unwrap();
+        assert_eq!(found.matched_url(), Some(&url));
+        assert!(found.to_string().contains("'Quarterly Report'"));
+
+        let selector = conditions.selector().unwrap().unwrap();
+        assert!(html_matches_selector(
+            &["<table class=\"res", "ults\"><tr><td>1</td></tr></table>"],
+            encoding_rs::UTF_8,
+            &selector
+        ));
+        assert!(!html_matches_selector(
+            &["<table></table>"],
+            encoding_rs::UTF_8,
+            &selector
+        ));
+        assert!(
+            StopConditions {
+                match_selector: Some("[".to_string()),
+                ..StopConditions::default()
+            }
+            .selector()
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+/// Remote destinations for stored output
+///
+/// `DataStorage` writes to its output directory unless a backend is set. With a
+/// backend, whole files (JSON results, session summaries, domain reports) are
+/// uploaded as objects under the same relative path. Object stores cannot append,
+/// so record files (JSONL, CSV, the filename index) are buffered per object and
+/// each `DataStorage::flush` uploads what was buffered since the previous one as a
+/// numbered part, `<name>.part-00000.<ext>`, `<name>.part-00001.<ext>`, ...
+/// Recovery, retention, migration and `load_results` only see the local directory.
+use anyhow::Result;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Destination that stores whole objects by relative path
+pub trait StorageBackend: Send + Sync {
+    /// Write `content` as the object at `path`, replacing any previous version
+    fn put<'a>(&'a self, path: &'a str, content: Bytes) -> BoxFuture<'a, Result<()>>;
+
+    /// Full location of `path`, for logs
+    fn location(&self, path: &str) -> String;
+}
+
+/// S3 or GCS destination (uploads require the `object-storage` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStorageConfig {
+    /// Bucket and prefix, e.g. `s3://bucket/crawls` or `gs://bucket/crawls`
+    pub url: String,
+    /// Store settings such as `aws_region`, `aws_endpoint` or `google_service_account`;
+    /// credentials are read from the environment unless given here
+    pub options: HashMap<String, String>,
+    /// Objects larger than this are sent as a multipart upload in parts of this size
+    pub part_size_bytes: usize,
+    /// Retries of a failed request, with exponential backoff
+    pub max_retries: usize,
+    /// No retry is started this long after the first attempt
+    pub retry_timeout_secs: u64,
+}
+
+impl Default for ObjectStorageConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            options: HashMap::new(),
+            part_size_bytes: 10 * 1024 * 1024, // 10 MiB
+            max_retries: 10,
+            retry_timeout_secs: 180,
+        }
+    }
+}
+
+impl ObjectStorageConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_part_size(mut self, bytes: usize) -> Self {
+        self.part_size_bytes = bytes;
+        self
+    }
+
+    pub fn with_retries(mut self, max_retries: usize, timeout_secs: u64) -> Self {
+        self.max_retries = max_retries;
+        self.retry_timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Open the configured bucket as a storage backend
+    #[cfg(feature = "object-storage")]
+    pub fn connect(&self) -> Result<Arc<dyn StorageBackend>> {
+        Ok(Arc::new(super::object_store::ObjectStoreBackend::new(
+            self,
+        )?))
+    }
+
+    /// Open the configured bucket as a storage backend
+    #[cfg(not(feature = "object-storage"))]
+    pub fn connect(&self) -> Result<Arc<dyn StorageBackend>> {
+        Err(anyhow::anyhow!(
+            "Object storage at {} requires the `object-storage` feature",
+            self.url
+        ))
+    }
+}
+
+/// Backend keeping objects in memory, for tests
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryBackend {
+    pub(crate) objects: std::sync::Mutex<std::collections::BTreeMap<String, Bytes>>,
+}
+
+#[cfg(test)]
+impl StorageBackend for MemoryBackend {
+    fn put<'a>(&'a self, path: &'a str, content: Bytes) -> BoxFuture<'a, Result<()>> {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), content);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn location(&self, path: &str) -> String {
+        format!("memory://{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DataStorage, OutputFormat, StoredCrawlResult};
+
+    #[tokio::test]
+    async fn test_records_are_uploaded_on_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(MemoryBackend::default());
+        let storage = DataStorage::new(dir.path(), OutputFormat::Jsonl)
+            .unwrap()
+            .with_backend(backend.clone());
+
+        storage.store_domain_reports("s1", &[]).await.unwrap();
+        assert!(
+            backend
+                .objects
+                .lock()
+                .unwrap()
+                .contains_key("domain_reports_s1/index.csv")
+        );
+
+        let result: StoredCrawlResult = serde_json::from_str(
+            r#"{"url":"https://a.example/","title":null,"content":"text","word_count":1,"language":null,"links_found":[],"metadata":{"status_code":200,"content_type":null,"content_length":null,"response_time_ms":5,"depth":0,"parent_url":null,"crawl_session_id":"s1"},"timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#,
+        )
+        .unwrap();
+        storage.store_result(&result).await.unwrap();
+        storage.store_result(&result).await.unwrap();
+        assert_eq!(backend.objects.lock().unwrap().len(), 1);
+
+        storage.flush().await.unwrap();
+        // A later flush adds a part instead of replacing the first one
+        storage.store_result(&result).await.unwrap();
+        storage.flush().await.unwrap();
+        storage.flush().await.unwrap();
+        let objects = backend.objects.lock().unwrap();
+        let parts: Vec<(&String, &Bytes)> = objects
+            .iter()
+            .filter(|(name, _)| name.ends_with(".jsonl"))
+            .collect();
+        assert_eq!(parts.len(), 2);
+        let lines = |records: &Bytes| records.iter().filter(|&&byte| byte == b'\n').count();
+        assert!(parts[0].0.ends_with(".part-00000.jsonl"));
+        assert_eq!(lines(parts[0].1), 2);
+        assert!(parts[1].0.ends_with(".part-00001.jsonl"));
+        assert_eq!(lines(parts[1].1), 1);
+        // Nothing is written to the local directory
+        assert!(!dir.path().join(parts[0].0).exists());
+        assert!(
+            !dir.path()
+                .join("domain_reports_s1")
+                .join("index.csv")
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_every_csv_part_starts_with_the_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(MemoryBackend::default());
+        let storage = DataStorage::new(dir.path(), OutputFormat::Csv)
+            .unwrap()
+            .with_backend(backend.clone());
+        let result: StoredCrawlResult = serde_json::from_str(
+            r#"{"url":"https://a.example/","title":null,"content":"text","word_count":1,"language":null,"links_found":[],"metadata":{"status_code":200,"content_type":null,"content_length":null,"response_time_ms":5,"depth":0,"parent_url":null,"crawl_session_id":"s1"},"timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#,
+        )
+        .unwrap();
+
+        storage.store_result(&result).await.unwrap();
+        storage.store_result(&result).await.unwrap();
+        storage.flush().await.unwrap();
+        storage.store_result(&result).await.unwrap();
+        storage.flush().await.unwrap();
+
+        let objects = backend.objects.lock().unwrap();
+        let parts: Vec<String> = objects
+            .iter()
+            .filter(|(name, _)| name.ends_with(".csv"))
+            .map(|(_, content)| String::from_utf8(content.to_vec()).unwrap())
+            .collect();
+        assert_eq!(parts.len(), 2);
+        let header = parts[0].lines().next().unwrap();
+        assert!(header.starts_with("schema_version,url"));
+        assert_eq!(parts[0].lines().count(), 3);
+        assert_eq!(parts[1].lines().next(), Some(header));
+        assert_eq!(parts[1].lines().count(), 2);
+    }
+}
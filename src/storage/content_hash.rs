@@ -0,0 +1,188 @@
+/// Content hashes per URL for change detection across crawls
+///
+/// Every crawl of a URL records a SHA-256 of the raw response body and of its
+/// normalized text (lowercased, whitespace collapsed) in an append-only JSONL file.
+/// The latest record per URL wins when the file is loaded, and `compact` rewrites
+/// the file with only those records.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::durable::{FsyncPolicy, append_record, write_atomic};
+
+/// Hashes of one response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentHashes {
+    /// SHA-256 of the raw body bytes, hex encoded
+    pub raw: String,
+    /// SHA-256 of the normalized extracted text, hex encoded
+    pub text: String,
+}
+
+/// Hex SHA-256 of the raw body, fed chunk by chunk
+pub fn hash_body<B: AsRef<[u8]>>(chunks: &[B]) -> String {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk.as_ref());
+    }
+    to_hex(&hasher.finalize())
+}
+
+/// Hex SHA-256 of `text` with case and whitespace differences removed
+pub fn hash_text(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for (index, word) in text.split_whitespace().enumerate() {
+        if index > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.to_lowercase().as_bytes());
+    }
+    to_hex(&hasher.finalize())
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Stored hashes of a URL's latest crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentHashRecord {
+    pub url: String,
+    pub hashes: ContentHashes,
+    pub crawled_at: SystemTime,
+}
+
+/// Persistent URL -> content hash store
+pub struct ContentHashStore {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, ContentHashRecord>>,
+    fsync_policy: FsyncPolicy,
+}
+
+impl ContentHashStore {
+    /// Load the store at `path`, creating it on first write
+    ///
+    /// Lines that do not parse (e.g. a record torn by a crash) are skipped.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut entries = HashMap::new();
+        if path.exists() {
+            for line in std::fs::read_to_string(path)?.lines() {
+                if let Ok(record) = serde_json::from_str::<ContentHashRecord>(line) {
+                    entries.insert(record.url.clone(), record);
+                }
+            }
+        } else if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: Mutex::new(entries),
+            fsync_policy: FsyncPolicy::default(),
+        })
+    }
+
+    pub fn with_fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync_policy = policy;
+        self
+    }
+
+    /// Hashes recorded by the latest crawl of `url`
+    pub fn get(&self, url: &str) -> Option<ContentHashes> {
+        self.lock().get(url).map(|record| record.hashes.clone())
+    }
+
+    /// Record the hashes of a new crawl, returning the previous ones
+    pub fn record(&self, url: &str, hashes: ContentHashes) -> Result<Option<ContentHashes>> {
+        let record = ContentHashRecord {
+            url: url.to_string(),
+            hashes,
+            crawled_at: SystemTime::now(),
+        };
+        let line = serde_json::to_string(&record)?;
+
+        let mut entries = self.lock();
+        append_record(&self.path, &line, self.fsync_policy)?;
+        Ok(entries
+            .insert(url.to_string(), record)
+            .map(|previous| previous.hashes))
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget URLs last crawled before `cutoff`; `compact` drops them from the file
+    pub fn retain_crawled_since(&self, cutoff: SystemTime) {
+        self.lock().retain(|_, record| record.crawled_at >= cutoff);
+    }
+
+    /// Rewrite the file with one record per URL
+    pub fn compact(&self) -> Result<()> {
+        let entries = self.lock();
+        let mut content = String::new();
+        for record in entries.values() {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+        write_atomic(&self.path, content.as_bytes(), self.fsync_policy)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ContentHashRecord>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_keeps_latest_hashes_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hashes.jsonl");
+
+        assert_eq!(hash_text("Hello  World\n"), hash_text("hello world"));
+        assert_ne!(hash_body(&["<p>a</p>"]), hash_body(&["<p>", "b</p>"]));
+        assert_eq!(hash_body(&["<p>a</p>"]), hash_body(&["<p>", "a</p>"]));
+
+        let first = ContentHashes {
+            raw: hash_body(&["v1"]),
+            text: hash_text("v1"),
+        };
+        let second = ContentHashes {
+            raw: hash_body(&["v2"]),
+            text: hash_text("v2"),
+        };
+        let store = ContentHashStore::open(&path).unwrap();
+        assert_eq!(
+            store.record("https://a.example/", first.clone()).unwrap(),
+            None
+        );
+        assert_eq!(
+            store.record("https://a.example/", second.clone()).unwrap(),
+            Some(first)
+        );
+
+        let reopened = ContentHashStore::open(&path).unwrap();
+        assert_eq!(reopened.get("https://a.example/"), Some(second.clone()));
+        reopened.compact().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(
+            ContentHashStore::open(&path)
+                .unwrap()
+                .get("https://a.example/"),
+            Some(second)
+        );
+    }
+}
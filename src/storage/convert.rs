@@ -0,0 +1,350 @@
+/// Conversion of stored results between output formats
+///
+/// `convert` reads the result files of one format under a session directory and
+/// writes them as a single file of another format next to it, e.g.
+/// `crawl_data/session_1` becomes `crawl_data/session_1.csv`. Records are read one
+/// at a time, also from JSON arrays, and written as they are read, so a session is
+/// never held in memory.
+///
+/// Every field survives a round trip through any format. CSV gets one column per
+/// scalar field, named after it (`session_id` for the crawl session), and
+/// JSON-encoded cells for lists, maps and the timestamp (see `record_stream`); an
+/// empty cell is read back as a missing value. CSV sources must carry that header,
+/// as CSV files written while crawling do; the header-less files written by older
+/// versions are rejected, since what they dropped cannot be recovered. Parquet
+/// files have the same columns (see `parquet`).
+use anyhow::{Result, anyhow};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::data::{OutputFormat, StoredCrawlResult};
+use super::naming::FILENAME_INDEX;
+use super::parquet::{ParquetRecordWriter, read_parquet};
+use super::record_stream::{CSV_LAYOUT, RecordStreamWriter, record_from_cells};
+use crate::logging::EVENTS_FILE;
+
+/// Outcome of a finished conversion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvertSummary {
+    pub path: PathBuf,
+    pub from: OutputFormat,
+    pub to: OutputFormat,
+    pub files_read: usize,
+    pub records: usize,
+    /// Lines and rows that were not stored results
+    pub skipped: usize,
+}
+
+/// Convert the `from` result files under `session_dir` into one `to` file beside it
+///
+/// `session_dir` may also be a single result file. The output replaces any
+/// previous conversion once it is complete.
+pub fn convert(session_dir: &Path, from: OutputFormat, to: OutputFormat) -> Result<ConvertSummary> {
+    let mut files = Vec::new();
+    if session_dir.is_file() {
+        files.push(session_dir.to_path_buf());
+    } else {
+        collect_files(session_dir, extension(from), &mut files)?;
+        files.sort();
+    }
+    let destination = session_dir.with_extension(extension(to));
+
+    let mut staging = destination.as_os_str().to_owned();
+    staging.push(".partial");
+    let staging = PathBuf::from(staging);
+    let mut writer = RecordWriter::new(to, BufWriter::new(File::create(&staging)?))?;
+
+    let mut summary = ConvertSummary {
+        path: destination.clone(),
+        from,
+        to,
+        files_read: files.len(),
+        records: 0,
+        skipped: 0,
+    };
+    let mut write = |record: Option<StoredCrawlResult>| -> Result<()> {
+        match record {
+            Some(record) => {
+                writer.write(&record)?;
+                summary.records += 1;
+            }
+            None => summary.skipped += 1,
+        }
+        Ok(())
+    };
+    for path in &files {
+        match from {
+            OutputFormat::Jsonl => {
+                for line in BufReader::new(File::open(path)?).lines() {
+                    let line = line?;
+                    if !line.trim().is_empty() {
+                        write(serde_json::from_str(&line).ok())?;
+                    }
+                }
+            }
+            OutputFormat::Json => read_json(path, &mut write)?,
+            OutputFormat::Csv => {
+                let mut reader = csv::Reader::from_path(path)?;
+                let columns = csv_columns(reader.headers()?).map_err(|missing| {
+                    anyhow!(
+                        "{} is not a converted CSV file (no '{}' column)",
+                        path.display(),
+                        missing
+                    )
+                })?;
+                for row in reader.records() {
+                    let row = row?;
+                    write(record_from_cells(
+                        columns.iter().map(|index| row.get(*index)),
+                    ))?;
+                }
+            }
+            OutputFormat::Parquet => {
+                for record in read_parquet(path)? {
+                    write(record?)?;
+                }
+            }
+        }
+    }
+
+    let file = writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&staging, &destination)?;
+    Ok(summary)
+}
+
+fn extension(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Jsonl => "jsonl",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Parquet => "parquet",
+    }
+}
+
+/// Results of a JSON file holding one result or an array of them, handed to
+/// `write` one at a time; unrelated JSON files are neither and are passed over
+fn read_json(
+    path: &Path,
+    write: &mut dyn FnMut(Option<StoredCrawlResult>) -> Result<()>,
+) -> Result<()> {
+    let mut failed = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(File::open(path)?));
+    // A document that is neither fails to parse; only errors of `write` count
+    let _ = JsonResults {
+        write,
+        failed: &mut failed,
+    }
+    .deserialize(&mut deserializer);
+    failed.map_or(Ok(()), Err)
+}
+
+/// Streams the results of a JSON document into `write`, keeping the first error
+/// it returns in `failed`
+struct JsonResults<'a> {
+    write: &'a mut dyn FnMut(Option<StoredCrawlResult>) -> Result<()>,
+    failed: &'a mut Option<anyhow::Error>,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonResults<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonResults<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a stored result or an array of them")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, map: A) -> Result<(), A::Error> {
+        let value = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        if let Ok(result) = serde_json::from_value(value) {
+            self.forward(Some(result))?;
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<Value>()? {
+            self.forward(serde_json::from_value(value).ok())?;
+        }
+        Ok(())
+    }
+}
+
+impl JsonResults<'_> {
+    fn forward<E: de::Error>(&mut self, result: Option<StoredCrawlResult>) -> Result<(), E> {
+        (self.write)(result).map_err(|e| {
+            let message = e.to_string();
+            *self.failed = Some(e);
+            E::custom(message)
+        })
+    }
+}
+
+fn collect_files(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, extension, files)?;
+        } else if !matches!(
+            path.file_name().and_then(|s| s.to_str()),
+            Some(FILENAME_INDEX | EVENTS_FILE)
+        ) && path.extension().and_then(|s| s.to_str()) == Some(extension)
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Writer of one output file in any format, one record at a time
+pub(super) enum RecordWriter {
+    Records(RecordStreamWriter<BufWriter<File>>),
+    Parquet(Box<ParquetRecordWriter<BufWriter<File>>>),
+    Json {
+        writer: BufWriter<File>,
+        first: bool,
+    },
+}
+
+impl RecordWriter {
+    pub(super) fn new(format: OutputFormat, mut writer: BufWriter<File>) -> Result<Self> {
+        Ok(match format {
+            OutputFormat::Json => {
+                writer.write_all(b"[\n")?;
+                RecordWriter::Json {
+                    writer,
+                    first: true,
+                }
+            }
+            OutputFormat::Parquet => {
+                RecordWriter::Parquet(Box::new(ParquetRecordWriter::new(writer)?))
+            }
+            _ => RecordWriter::Records(RecordStreamWriter::new(format, writer, true)?),
+        })
+    }
+
+    pub(super) fn write(&mut self, result: &StoredCrawlResult) -> Result<()> {
+        match self {
+            RecordWriter::Records(writer) => writer.write(result)?,
+            RecordWriter::Parquet(writer) => writer.write(result)?,
+            RecordWriter::Json { writer, first } => {
+                if !*first {
+                    writer.write_all(b",\n")?;
+                }
+                *first = false;
+                serde_json::to_writer_pretty(&mut *writer, result)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(self) -> Result<File> {
+        let writer = match self {
+            RecordWriter::Records(writer) => writer.finish()?,
+            RecordWriter::Parquet(writer) => writer.finish()?,
+            RecordWriter::Json { mut writer, first } => {
+                writer.write_all(if first { b"]\n" } else { b"\n]\n" })?;
+                writer
+            }
+        };
+        Ok(writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
+/// Index of each layout column in a CSV header, or the first column missing
+fn csv_columns(header: &csv::StringRecord) -> Result<Vec<usize>, &'static str> {
+    CSV_LAYOUT
+        .iter()
+        .map(|(name, _, _)| {
+            header
+                .iter()
+                .position(|column| column == *name)
+                .ok_or(*name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_survive_conversion_through_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_dir = dir.path().join("session_1");
+        std::fs::create_dir_all(session_dir.join("a.example")).unwrap();
+
+        let mut result = StoredCrawlResult {
+            links_found: vec!["https://a.example/b".to_string()],
+            relevance_score: Some(0.5),
+            declared_language: Some("en".to_string()),
+            ..StoredCrawlResult::for_test("https://a.example/")
+                .with_title("Say \"hi\", twice")
+                .with_content("line one\nline two")
+                .with_language("Eng")
+                .with_session("session_1")
+        };
+        result.metadata.content_type = Some("text/html".to_string());
+        result.metadata.response_time_ms = 12;
+        result.metadata.depth = 1;
+        result
+            .metadata
+            .custom
+            .insert("source".to_string(), "csv-row-123".to_string());
+        let first = serde_json::to_string(&result).unwrap();
+        result.url = "https://a.example/b".to_string();
+        let second = serde_json::to_string(&result).unwrap();
+        std::fs::write(
+            session_dir.join("a.example/results.jsonl"),
+            format!("{}\nnot a result\n{}\n", first, second),
+        )
+        .unwrap();
+
+        let summary = convert(&session_dir, OutputFormat::Jsonl, OutputFormat::Csv).unwrap();
+        assert_eq!((summary.records, summary.skipped), (2, 1));
+        assert_eq!(summary.path, dir.path().join("session_1.csv"));
+
+        let to_json = convert(&summary.path, OutputFormat::Csv, OutputFormat::Json).unwrap();
+        let to_parquet = convert(&to_json.path, OutputFormat::Json, OutputFormat::Parquet).unwrap();
+        assert_eq!(to_parquet.records, 2);
+        let to_jsonl =
+            convert(&to_parquet.path, OutputFormat::Parquet, OutputFormat::Jsonl).unwrap();
+        let lines = std::fs::read_to_string(&to_jsonl.path).unwrap();
+        assert_eq!(lines, format!("{}\n{}\n", first, second));
+
+        // Array elements that are not results are skipped one by one
+        std::fs::write(
+            dir.path().join("mixed.json"),
+            format!("[{}, {{\"url\": 1}}]", first),
+        )
+        .unwrap();
+        let mixed = convert(
+            &dir.path().join("mixed.json"),
+            OutputFormat::Json,
+            OutputFormat::Jsonl,
+        )
+        .unwrap();
+        assert_eq!((mixed.records, mixed.skipped), (1, 1));
+        std::fs::write(dir.path().join("lossy.csv"), "https://a.example/,,4\n").unwrap();
+        assert!(
+            convert(
+                &dir.path().join("lossy.csv"),
+                OutputFormat::Csv,
+                OutputFormat::Json
+            )
+            .is_err()
+        );
+    }
+}
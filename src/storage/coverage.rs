@@ -0,0 +1,234 @@
+/// Coverage comparison between two sessions of the same site
+///
+/// Stored results record each crawled page and the links found on it, which
+/// together form the session's link graph. `SessionFrontier` collects every URL a
+/// session crawled or discovered and groups them into sections (host plus first
+/// path segment, e.g. `example.com/news/`). `FrontierDiff` compares two frontiers
+/// section by section, so a configuration change that silently stops the crawler
+/// from reaching a part of the site shows up as a lost or shrunk section.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use url::Url;
+
+use super::data::DataStorage;
+use super::reader::ResultFilter;
+use crate::core::host_key;
+use crate::core::idn::normalize_host;
+
+/// URLs one session crawled or discovered
+#[derive(Debug, Clone, Default)]
+pub struct SessionFrontier {
+    /// Pages with a stored result
+    pub crawled: BTreeSet<String>,
+    /// Crawled pages and every link found on them
+    pub discovered: BTreeSet<String>,
+}
+
+impl SessionFrontier {
+    /// Add a crawled page and the links found on it
+    ///
+    /// URLs are compared as `Url` serializes them, so spellings of the same host
+    /// count once.
+    pub fn record(&mut self, url: &str, links: &[String]) {
+        let url = frontier_key(url);
+        self.crawled.insert(url.clone());
+        self.discovered.insert(url);
+        self.discovered
+            .extend(links.iter().map(|link| frontier_key(link)));
+    }
+
+    /// Discovered URLs per section
+    pub fn sections(&self) -> BTreeMap<String, usize> {
+        let mut sections = BTreeMap::new();
+        for url in &self.discovered {
+            if let Some(section) = section_of(url) {
+                *sections.entry(section).or_insert(0) += 1;
+            }
+        }
+        sections
+    }
+}
+
+/// Discovered URLs of one section in both sessions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionCoverage {
+    pub section: String,
+    pub base_urls: usize,
+    pub compare_urls: usize,
+}
+
+/// Coverage changes from a base session to a later one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrontierDiff {
+    pub base_session: String,
+    pub compare_session: String,
+    pub base_discovered: usize,
+    pub compare_discovered: usize,
+    pub base_crawled: usize,
+    pub compare_crawled: usize,
+    /// URLs only the later session discovered
+    pub new_urls: Vec<String>,
+    /// URLs only the base session discovered
+    pub lost_urls: Vec<String>,
+    /// Every section of either session, in section order
+    pub sections: Vec<SectionCoverage>,
+}
+
+impl FrontierDiff {
+    pub fn compare(
+        base_session: &str,
+        base: &SessionFrontier,
+        compare_session: &str,
+        compare: &SessionFrontier,
+    ) -> Self {
+        let base_sections = base.sections();
+        let compare_sections = compare.sections();
+        let names: BTreeSet<&String> = base_sections
+            .keys()
+            .chain(compare_sections.keys())
+            .collect();
+        let sections = names
+            .into_iter()
+            .map(|section| SectionCoverage {
+                section: section.clone(),
+                base_urls: base_sections.get(section).copied().unwrap_or(0),
+                compare_urls: compare_sections.get(section).copied().unwrap_or(0),
+            })
+            .collect();
+
+        Self {
+            base_session: base_session.to_string(),
+            compare_session: compare_session.to_string(),
+            base_discovered: base.discovered.len(),
+            compare_discovered: compare.discovered.len(),
+            base_crawled: base.crawled.len(),
+            compare_crawled: compare.crawled.len(),
+            new_urls: compare
+                .discovered
+                .difference(&base.discovered)
+                .cloned()
+                .collect(),
+            lost_urls: base
+                .discovered
+                .difference(&compare.discovered)
+                .cloned()
+                .collect(),
+            sections,
+        }
+    }
+
+    /// Sections only the later session reached
+    pub fn new_sections(&self) -> impl Iterator<Item = &SectionCoverage> {
+        self.sections
+            .iter()
+            .filter(|section| section.base_urls == 0)
+    }
+
+    /// Sections the later session no longer reached
+    pub fn lost_sections(&self) -> impl Iterator<Item = &SectionCoverage> {
+        self.sections
+            .iter()
+            .filter(|section| section.compare_urls == 0)
+    }
+
+    /// Sections still reached, but with less than `ratio` of their former URLs
+    pub fn shrunk_sections(&self, ratio: f64) -> impl Iterator<Item = &SectionCoverage> {
+        self.sections.iter().filter(move |section| {
+            section.compare_urls > 0
+                && (section.compare_urls as f64) < section.base_urls as f64 * ratio
+        })
+    }
+}
+
+fn frontier_key(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            normalize_host(&mut parsed);
+            parsed.into()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Section of a URL: its host and first path segment, e.g. `example.com/news/`
+pub fn section_of(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = host_key(url.host_str()?);
+    Some(
+        match url.path_segments().and_then(|mut segments| segments.next()) {
+            Some(first) if !first.is_empty() => format!("{}/{}/", host, first),
+            _ => format!("{}/", host),
+        },
+    )
+}
+
+impl DataStorage {
+    /// Crawled and discovered URLs of a stored session
+    pub fn session_frontier(&self, session_id: &str) -> Result<SessionFrontier> {
+        let mut frontier = SessionFrontier::default();
+        for result in self.read_session(session_id, ResultFilter::default())? {
+            let result = result?;
+            frontier.record(&result.url, &result.links_found);
+        }
+        Ok(frontier)
+    }
+
+    /// Compare the coverage of two stored sessions
+    pub fn diff_frontiers(
+        &self,
+        base_session: &str,
+        compare_session: &str,
+    ) -> Result<FrontierDiff> {
+        Ok(FrontierDiff::compare(
+            base_session,
+            &self.session_frontier(base_session)?,
+            compare_session,
+            &self.session_frontier(compare_session)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_reports_new_lost_and_shrunk_sections() {
+        let links = |urls: &[&str]| urls.iter().map(|url| url.to_string()).collect::<Vec<_>>();
+        let mut base = SessionFrontier::default();
+        base.record(
+            "https://example.com/",
+            &links(&[
+                "https://example.com/news/1",
+                "https://example.com/news/2",
+                "https://example.com/news/3",
+                "https://example.com/shop/a",
+                "https://Example.com/about",
+            ]),
+        );
+        let mut later = SessionFrontier::default();
+        later.record(
+            "https://example.com/",
+            &links(&[
+                "https://example.com/news/1",
+                "https://example.com/blog/x",
+                "https://example.com/about",
+            ]),
+        );
+
+        let diff = FrontierDiff::compare("s1", &base, "s2", &later);
+        assert_eq!(diff.base_discovered, 6);
+        assert_eq!(diff.compare_crawled, 1);
+        assert_eq!(diff.new_urls, ["https://example.com/blog/x"]);
+        assert_eq!(diff.lost_urls.len(), 3);
+
+        fn names<'a>(sections: impl Iterator<Item = &'a SectionCoverage>) -> Vec<&'a str> {
+            sections.map(|section| section.section.as_str()).collect()
+        }
+        assert_eq!(names(diff.new_sections()), ["example.com/blog/"]);
+        assert_eq!(names(diff.lost_sections()), ["example.com/shop/"]);
+        assert_eq!(names(diff.shrunk_sections(0.5)), ["example.com/news/"]);
+        assert_eq!(section_of("not a url"), None);
+    }
+}
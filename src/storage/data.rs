@@ -1,21 +1,62 @@
 use anyhow::Result;
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::OpenOptions;
-use std::hash::{DefaultHasher, Hasher};
-use std::io::Write;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use tokio::fs;
+use tokio::task::JoinHandle;
+
+use super::backend::StorageBackend;
+use super::durable::{
+    FsyncPolicy, RecoveryReport, append_record, recover_jsonl_tail, write_atomic, write_atomic_with,
+};
+use super::feed::{SessionFeed, session_feed_path};
+use super::manifest::{IntegrityManifest, ManifestConfig, ManifestVerification, manifest_path};
+use super::metrics::DomainReport;
+use super::migrate::{MigrationReport, SCHEMA_VERSION, legacy_schema_version, migrate_dir};
+use super::naming::{
+    CollisionPolicy, FILENAME_INDEX, FileNamer, FileNamingTemplate, FilenameIndexEntry,
+    NamingContext, next_free_path, path_safe_component, url_to_filename,
+};
+use super::raw_html::{DEFAULT_RAW_HTML_DIR, RawHtmlConfig, RawHtmlStore};
+use super::record_stream::{RecordStreamWriter, encode_record};
+use super::retention::{GcReport, RetentionPolicy, collect_garbage};
+use super::screenshots::{
+    DEFAULT_SCREENSHOT_DIR, Screenshot, ScreenshotConfig, ScreenshotRef, ScreenshotStore,
+};
+use crate::core::PaginationChain;
+use crate::logging::{CrawlEvent, load_timeline, session_events_path};
+use crate::processing::{ExtractedKeyword, HreflangAlternate, StructuredContent};
 
 /// Data storage and output management
 pub struct DataStorage {
     output_dir: PathBuf,
     format: OutputFormat,
     compression: bool,
+    naming: Option<FileNamer>,
+    sequence: AtomicU64,
+    write_index: bool,
+    retention: Option<RetentionPolicy>,
+    fsync_policy: FsyncPolicy,
+    backend: Option<Arc<dyn StorageBackend>>,
+    /// Records appended per object since the last `flush`
+    buffered_records: Mutex<BTreeMap<String, String>>,
+    /// Flushes that uploaded records; numbers the part objects of each flush
+    flush_sequence: AtomicU64,
+    /// CSV objects whose header was buffered for the backend; held while a row is
+    /// appended, so a file's header is always its first line
+    csv_headers: Mutex<HashSet<String>>,
+    raw_html: Option<RawHtmlStore>,
+    screenshots: Option<ScreenshotStore>,
+    /// Local files written or referenced through this storage, listed by the
+    /// integrity manifest
+    written: Mutex<BTreeSet<PathBuf>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OutputFormat {
     Json,
     Jsonl, // JSON Lines
@@ -23,15 +64,63 @@ pub enum OutputFormat {
     Parquet,
 }
 
+impl std::str::FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            "parquet" => Ok(OutputFormat::Parquet),
+            other => Err(anyhow::anyhow!("Unknown output format: {}", other)),
+        }
+    }
+}
+
 /// Crawl result for storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredCrawlResult {
+    /// Output schema the record was written with; `storage::migrate` upgrades old ones
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub url: String,
     pub title: Option<String>,
     pub content: Option<String>,
     pub word_count: usize,
     pub language: Option<String>,
     pub links_found: Vec<String>,
+    /// Semantic relevance score, when a relevance model is configured
+    #[serde(default)]
+    pub relevance_score: Option<f32>,
+    /// Alternate language versions declared with hreflang
+    #[serde(default)]
+    pub alternates: Vec<HreflangAlternate>,
+    /// Tables and lists, when structured extraction is configured
+    #[serde(default)]
+    pub structured: Option<StructuredContent>,
+    /// Language the page declares (`<html lang>` or `Content-Language`)
+    #[serde(default)]
+    pub declared_language: Option<String>,
+    /// Statistically detected language; `language` weighs it against the declared one
+    #[serde(default)]
+    pub detected_language: Option<String>,
+    /// SHA-256 of the raw body in the raw HTML store, when raw HTML is retained
+    #[serde(default)]
+    pub raw_html_hash: Option<String>,
+    /// Page screenshot in the screenshot store, when screenshots are captured
+    #[serde(default)]
+    pub screenshot: Option<ScreenshotRef>,
+    /// Page HTML stripped of scripts, styles, frames and event handlers, with
+    /// absolute URLs, when sanitized HTML is stored
+    #[serde(default)]
+    pub sanitized_html: Option<String>,
+    /// Personal data entities masked in the content, by kind; empty without PII scrubbing
+    #[serde(default)]
+    pub pii_scrubbed: BTreeMap<String, usize>,
+    /// Top keywords of the content, best first; empty without automatic keywords
+    #[serde(default)]
+    pub keywords: Vec<ExtractedKeyword>,
     pub metadata: CrawlMetadata,
     pub timestamp: SystemTime,
 }
@@ -41,10 +130,98 @@ pub struct CrawlMetadata {
     pub status_code: Option<u16>,
     pub content_type: Option<String>,
     pub content_length: Option<u64>,
+    /// `Content-Encoding` the body was received with; `None` when uncompressed
+    #[serde(default)]
+    pub content_encoding: Option<String>,
     pub response_time_ms: u64,
     pub depth: usize,
     pub parent_url: Option<String>,
     pub crawl_session_id: String,
+    /// User-Agent the page was fetched with
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Meta refresh hops from the requested URL to the stored page; empty without redirects
+    #[serde(default)]
+    pub redirect_chain: Vec<String>,
+    /// Key/value pairs attached to the seed or its domain, passed through untouched
+    #[serde(default)]
+    pub custom: BTreeMap<String, String>,
+    /// Pagination chain of the page, for reassembling articles split across pages
+    #[serde(default)]
+    pub pagination: Option<PaginationChain>,
+    /// MIME type sniffed from the body when it is binary, whatever the declared type
+    #[serde(default)]
+    pub sniffed_type: Option<String>,
+}
+
+#[cfg(test)]
+impl StoredCrawlResult {
+    /// A page fetched with status 200 in session `session` and nothing extracted;
+    /// tests set what they check with the `with_*` methods or struct update syntax
+    pub(crate) fn for_test(url: &str) -> Self {
+        Self {
+            schema_version: super::SCHEMA_VERSION,
+            url: url.to_string(),
+            title: None,
+            content: None,
+            word_count: 0,
+            language: None,
+            links_found: Vec::new(),
+            relevance_score: None,
+            alternates: Vec::new(),
+            structured: None,
+            declared_language: None,
+            detected_language: None,
+            raw_html_hash: None,
+            screenshot: None,
+            sanitized_html: None,
+            pii_scrubbed: BTreeMap::new(),
+            keywords: Vec::new(),
+            metadata: CrawlMetadata {
+                status_code: Some(200),
+                content_type: None,
+                content_length: None,
+                content_encoding: None,
+                response_time_ms: 0,
+                depth: 0,
+                parent_url: None,
+                crawl_session_id: "session".to_string(),
+                user_agent: None,
+                redirect_chain: Vec::new(),
+                custom: BTreeMap::new(),
+                pagination: None,
+                sniffed_type: None,
+            },
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// Set the content and its word count
+    pub(crate) fn with_content(mut self, content: &str) -> Self {
+        self.word_count = content.split_whitespace().count();
+        self.content = Some(content.to_string());
+        self
+    }
+
+    pub(crate) fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub(crate) fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    pub(crate) fn with_session(mut self, session_id: &str) -> Self {
+        self.metadata.crawl_session_id = session_id.to_string();
+        self
+    }
+
+    pub(crate) fn with_status(mut self, status_code: u16) -> Self {
+        self.metadata.status_code = Some(status_code);
+        self
+    }
 }
 
 /// Custom formatter trait for extensible output formats
@@ -65,6 +242,18 @@ impl DataStorage {
             output_dir,
             format,
             compression: false,
+            naming: None,
+            sequence: AtomicU64::new(0),
+            write_index: false,
+            retention: None,
+            fsync_policy: FsyncPolicy::default(),
+            backend: None,
+            buffered_records: Mutex::new(BTreeMap::new()),
+            flush_sequence: AtomicU64::new(0),
+            csv_headers: Mutex::new(HashSet::new()),
+            raw_html: None,
+            screenshots: None,
+            written: Mutex::new(BTreeSet::new()),
         })
     }
 
@@ -74,10 +263,341 @@ impl DataStorage {
         self
     }
 
+    /// Use a naming template instead of the built-in `crawl_{timestamp}_{url}` scheme
+    pub fn with_naming(mut self, template: &FileNamingTemplate) -> Result<Self> {
+        self.naming = Some(template.compile()?);
+        Ok(self)
+    }
+
+    /// Record every stored file in `filename_index.jsonl` so file names can be mapped back to URLs
+    pub fn with_filename_index(mut self, enabled: bool) -> Self {
+        self.write_index = enabled;
+        self
+    }
+
+    /// Control when written data is forced to disk
+    pub fn with_fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync_policy = policy;
+        self
+    }
+
+    /// Keep compressed raw bodies in a content-addressed blob store
+    ///
+    /// Blobs are always written locally, also when results go to a backend.
+    pub fn with_raw_html(mut self, config: &RawHtmlConfig) -> Self {
+        let dir = config
+            .dir
+            .clone()
+            .unwrap_or_else(|| self.output_dir.join(DEFAULT_RAW_HTML_DIR));
+        self.raw_html = Some(RawHtmlStore::new(
+            &dir,
+            config.compression_level,
+            self.fsync_policy,
+        ));
+        self
+    }
+
+    /// Store a raw body, returning its hash; `None` without raw HTML retention
+    ///
+    /// Compression and the write run on the blocking pool.
+    pub async fn store_raw_html<B>(&self, chunks: Vec<B>) -> Result<Option<String>>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        let Some(store) = self.raw_html.clone() else {
+            return Ok(None);
+        };
+        let (hash, store) = tokio::task::spawn_blocking(move || {
+            let hash = store.put(&chunks)?;
+            Ok::<_, anyhow::Error>((hash, store))
+        })
+        .await??;
+        lock(&self.written).insert(store.path_for(&hash));
+        Ok(Some(hash))
+    }
+
+    /// Raw body referenced by a result's `raw_html_hash`
+    pub fn load_raw_html(&self, hash: &str) -> Result<Vec<u8>> {
+        let store = self
+            .raw_html
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Raw HTML retention is not enabled"))?;
+        store.get(hash)
+    }
+
+    /// Keep page screenshots in a content-addressed image store
+    ///
+    /// Images are always written locally, also when results go to a backend.
+    pub fn with_screenshots(mut self, config: &ScreenshotConfig) -> Self {
+        let dir = config
+            .dir
+            .clone()
+            .unwrap_or_else(|| self.output_dir.join(DEFAULT_SCREENSHOT_DIR));
+        self.screenshots = Some(ScreenshotStore::new(&dir, self.fsync_policy));
+        self
+    }
+
+    /// Store a screenshot, returning its reference; `None` without a screenshot store
+    ///
+    /// The image is written on the blocking pool.
+    pub async fn store_screenshot(&self, screenshot: Screenshot) -> Result<Option<ScreenshotRef>> {
+        let Some(store) = self.screenshots.clone() else {
+            return Ok(None);
+        };
+        let (reference, store) = tokio::task::spawn_blocking(move || {
+            let reference = store.put(&screenshot)?;
+            Ok::<_, anyhow::Error>((reference, store))
+        })
+        .await??;
+        lock(&self.written).insert(store.path_for(&reference));
+        Ok(Some(reference))
+    }
+
+    /// Image referenced by a result's `screenshot`
+    pub fn load_screenshot(&self, screenshot: &ScreenshotRef) -> Result<Vec<u8>> {
+        let store = self
+            .screenshots
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Screenshot capture is not enabled"))?;
+        store.get(screenshot)
+    }
+
+    /// Upload output to `backend` instead of writing it to the output directory
+    ///
+    /// Record files are buffered in memory until `flush`, which uploads each as a
+    /// new numbered part object and drops the buffer.
+    pub fn with_backend(mut self, backend: Arc<dyn StorageBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Upload buffered record files to the backend; a no-op without one
+    ///
+    /// Object stores cannot append, so the records of each flush go to their own
+    /// object, `<name>.part-<flush>.<ext>`, instead of replacing what earlier flushes
+    /// uploaded. Listing a file's parts in order gives all of its records.
+    pub async fn flush(&self) -> Result<()> {
+        let Some(backend) = &self.backend else {
+            return Ok(());
+        };
+
+        let (buffered, headed) = {
+            // Rows buffered after this point start a new part, which needs its own
+            // CSV header
+            let mut csv_headers = lock(&self.csv_headers);
+            let buffered = std::mem::take(&mut *lock(&self.buffered_records));
+            let headed: HashSet<String> = buffered
+                .keys()
+                .filter(|key| csv_headers.remove(*key))
+                .cloned()
+                .collect();
+            (buffered, headed)
+        };
+        if buffered.is_empty() {
+            return Ok(());
+        }
+        let sequence = self.flush_sequence.fetch_add(1, Ordering::SeqCst);
+        let mut pending = buffered.into_iter();
+        while let Some((key, content)) = pending.next() {
+            let part = part_key(&key, sequence);
+            if let Err(e) = backend.put(&part, Bytes::from(content.clone())).await {
+                // Keep what was not uploaded for the next flush
+                let mut csv_headers = lock(&self.csv_headers);
+                let mut buffered = lock(&self.buffered_records);
+                for (key, content) in std::iter::once((key, content)).chain(pending) {
+                    let entry = buffered.entry(key.clone()).or_default();
+                    if headed.contains(&key) && !csv_headers.insert(key) {
+                        // Rows buffered meanwhile started over with a header; the
+                        // restored rows already have one
+                        let header_end = entry.find('\n').map_or(entry.len(), |end| end + 1);
+                        entry.replace_range(..header_end, "");
+                    }
+                    entry.insert_str(0, &content);
+                }
+                return Err(e);
+            }
+            tracing::debug!(location = %backend.location(&part), "Uploaded record file part");
+        }
+        Ok(())
+    }
+
+    pub fn fsync_policy(&self) -> FsyncPolicy {
+        self.fsync_policy
+    }
+
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    /// Event timeline recorded for `session_id`, oldest event first
+    pub fn load_session_events(&self, session_id: &str) -> Result<Vec<CrawlEvent>> {
+        load_timeline(&session_events_path(&self.output_dir, session_id))
+    }
+
+    /// Repair JSONL files in the output directory after an unclean shutdown
+    ///
+    /// A crash can only tear the record being appended, so only the end of each
+    /// file is read: a torn trailing record is cut off and kept in a `.corrupt`
+    /// file next to the original. `recover_jsonl` repairs a whole file. Only
+    /// repaired files are reported.
+    pub async fn recover(&self) -> Result<Vec<RecoveryReport>> {
+        let mut reports = Vec::new();
+        let mut entries = fs::read_dir(&self.output_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                continue;
+            }
+
+            let policy = self.fsync_policy;
+            let report =
+                tokio::task::spawn_blocking(move || recover_jsonl_tail(&path, policy)).await??;
+            if report.repaired {
+                tracing::warn!(
+                    path = %report.path.display(),
+                    "Cut a torn record off a JSONL output file"
+                );
+                reports.push(report);
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Upgrade results and session summaries in the output directory to `SCHEMA_VERSION`
+    pub async fn migrate(&self) -> Result<MigrationReport> {
+        let dir = self.output_dir.clone();
+        let policy = self.fsync_policy;
+        let report = tokio::task::spawn_blocking(move || migrate_dir(&dir, policy)).await??;
+        tracing::info!(
+            output_dir = %self.output_dir.display(),
+            files_migrated = report.files_migrated.len(),
+            records_migrated = report.records_migrated,
+            errors = report.errors.len(),
+            schema_version = SCHEMA_VERSION,
+            "Storage migration finished"
+        );
+        Ok(report)
+    }
+
+    /// Write the integrity manifest of `session_id`, listing every file written
+    /// through this storage and the contents of the session directory
+    ///
+    /// Only local output can be listed; with a storage backend this fails.
+    pub async fn write_manifest(
+        &self,
+        session_id: &str,
+        config: &ManifestConfig,
+    ) -> Result<PathBuf> {
+        if self.backend.is_some() {
+            return Err(anyhow::anyhow!(
+                "Integrity manifests cover local output only"
+            ));
+        }
+        let dir = self.output_dir.clone();
+        let id = session_id.to_string();
+        let key = config.signing_key.clone();
+        let files = lock(&self.written).clone();
+        let manifest = tokio::task::spawn_blocking(move || {
+            let mut manifest = IntegrityManifest::build(&dir, &id, files)?;
+            if let Some(key) = key {
+                manifest.sign(&key)?;
+            }
+            Ok::<_, anyhow::Error>(manifest)
+        })
+        .await??;
+
+        let path = manifest_path(&self.output_dir, session_id);
+        self.write_to_file(&path, serde_json::to_string_pretty(&manifest)?)
+            .await?;
+        tracing::info!(
+            session_id,
+            files = manifest.files.len(),
+            signed = manifest.signature.is_some(),
+            path = %path.display(),
+            "Integrity manifest written"
+        );
+        Ok(path)
+    }
+
+    /// Check the output directory against the manifest of `session_id`
+    pub async fn verify_manifest(
+        &self,
+        session_id: &str,
+        key: Option<&str>,
+    ) -> Result<ManifestVerification> {
+        let manifest = IntegrityManifest::load(&manifest_path(&self.output_dir, session_id))?;
+        let dir = self.output_dir.clone();
+        let key = key.map(str::to_string);
+        tokio::task::spawn_blocking(move || manifest.verify(&dir, key.as_deref())).await?
+    }
+
+    /// Write the feed of new and changed pages into its session directory
+    pub async fn write_feed(&self, feed: &SessionFeed) -> Result<PathBuf> {
+        let path = session_feed_path(&self.output_dir, feed.session_id());
+        if self.backend.is_none()
+            && let Some(parent) = path.parent()
+        {
+            fs::create_dir_all(parent).await?;
+        }
+        self.write_to_file(&path, feed.render()).await?;
+        tracing::info!(
+            session_id = feed.session_id(),
+            entries = feed.entries().len(),
+            path = %path.display(),
+            "Session feed written"
+        );
+        Ok(path)
+    }
+
+    /// Bound the output directory by age and size (applied by `gc`)
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = Some(policy);
+        self
+    }
+
+    /// Prune old outputs according to the retention policy
+    ///
+    /// Without a retention policy nothing is removed.
+    pub async fn gc(&self) -> Result<GcReport> {
+        let Some(policy) = &self.retention else {
+            return Ok(GcReport::default());
+        };
+
+        let report = collect_garbage(&self.output_dir, policy).await?;
+        tracing::info!(
+            output_dir = %self.output_dir.display(),
+            files_removed = report.files_removed,
+            bytes_freed = report.bytes_freed,
+            files_kept = report.files_kept,
+            bytes_kept = report.bytes_kept,
+            errors = report.errors.len(),
+            "Storage garbage collection finished"
+        );
+        Ok(report)
+    }
+
+    /// Run `gc` every `interval` in the background until the handle is aborted
+    pub fn schedule_gc(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.gc().await {
+                    tracing::warn!(
+                        output_dir = %self.output_dir.display(),
+                        error = %e,
+                        "Storage garbage collection failed"
+                    );
+                }
+            }
+        })
+    }
+
     /// Store a single crawl result
     pub async fn store_result(&self, result: &StoredCrawlResult) -> Result<()> {
-        let filename = self.generate_filename(&result.url, &result.timestamp);
-        let filepath = self.output_dir.join(filename);
+        let (filepath, append) = self.resolve_output_path(result).await?;
 
         match &self.format {
             OutputFormat::Json => {
@@ -85,40 +605,81 @@ impl DataStorage {
                 self.write_to_file(&filepath, content).await?;
             }
             OutputFormat::Jsonl => {
-                let content = serde_json::to_string(result)?;
-                self.append_to_file(&filepath, format!("{}\n", content))
-                    .await?;
+                let content = format!("{}\n", serde_json::to_string(result)?);
+                if append {
+                    self.append_to_file(&filepath, content).await?;
+                } else {
+                    self.write_to_file(&filepath, content).await?;
+                }
             }
             OutputFormat::Csv => {
-                self.store_as_csv(result, &filepath).await?;
+                if !append && filepath.exists() {
+                    fs::remove_file(&filepath).await?;
+                }
+                self.store_as_csv(result, &filepath, append).await?;
             }
             OutputFormat::Parquet => {
                 return Err(anyhow::anyhow!("Parquet format not yet implemented"));
             }
         }
 
+        if self.write_index {
+            self.append_index_entry(&filepath, &result.url).await?;
+        }
+
         Ok(())
     }
 
+    /// Load the filename index as a map from relative file path to URL
+    pub async fn load_filename_index(&self) -> Result<HashMap<String, String>> {
+        let index_path = self.output_dir.join(FILENAME_INDEX);
+        if !index_path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = fs::read_to_string(&index_path).await?;
+        let mut index = HashMap::new();
+        for line in content.lines() {
+            if let Ok(entry) = serde_json::from_str::<FilenameIndexEntry>(line) {
+                index.insert(entry.file, entry.url);
+            }
+        }
+
+        Ok(index)
+    }
+
     /// Store multiple results in batch
     pub async fn store_batch(&self, results: &[StoredCrawlResult]) -> Result<()> {
         match &self.format {
-            OutputFormat::Jsonl => {
+            OutputFormat::Jsonl | OutputFormat::Csv => {
                 let filename = format!(
-                    "batch_{}.jsonl",
+                    "batch_{}.{}",
                     SystemTime::now()
                         .duration_since(SystemTime::UNIX_EPOCH)?
-                        .as_secs()
+                        .as_secs(),
+                    self.file_extension()
                 );
                 let filepath = self.output_dir.join(filename);
 
-                let mut content = String::new();
-                for result in results {
-                    content.push_str(&serde_json::to_string(result)?);
-                    content.push('\n');
+                if self.backend.is_some() {
+                    let mut writer = RecordStreamWriter::new(self.format, Vec::new(), true)?;
+                    for result in results {
+                        writer.write(result)?;
+                    }
+                    let content = String::from_utf8(writer.finish()?)?;
+                    self.write_to_file(&filepath, content).await?;
+                } else {
+                    // Streamed into the file record by record
+                    lock(&self.written).insert(filepath.clone());
+                    write_atomic_with(&filepath, self.fsync_policy, |file| {
+                        let mut writer = RecordStreamWriter::new(self.format, file, true)?;
+                        for result in results {
+                            writer.write(result)?;
+                        }
+                        writer.finish()?;
+                        Ok(())
+                    })?;
                 }
-
-                self.write_to_file(&filepath, content).await?;
             }
             OutputFormat::Json => {
                 let filename = format!(
@@ -149,7 +710,7 @@ impl DataStorage {
         session_id: &str,
         summary: &CrawlSessionSummary,
     ) -> Result<()> {
-        let filename = format!("session_summary_{}.json", session_id);
+        let filename = path_safe_component(&format!("session_summary_{}.json", session_id));
         let filepath = self.output_dir.join(filename);
 
         let content = serde_json::to_string_pretty(summary)?;
@@ -158,6 +719,78 @@ impl DataStorage {
         Ok(())
     }
 
+    /// Write one JSON report per domain plus an `index.csv` overview
+    ///
+    /// Reports go to `domain_reports_{session_id}/` next to the session summary.
+    pub async fn store_domain_reports(
+        &self,
+        session_id: &str,
+        reports: &[DomainReport],
+    ) -> Result<PathBuf> {
+        let dir = self.output_dir.join(path_safe_component(&format!(
+            "domain_reports_{}",
+            session_id
+        )));
+        fs::create_dir_all(&dir).await?;
+
+        let mut index = csv::Writer::from_writer(Vec::new());
+        index.write_record([
+            "domain",
+            "display_domain",
+            "requests",
+            "pages_fetched",
+            "failures",
+            "bytes",
+            "avg_latency_ms",
+            "robots_blocks",
+            "top_error",
+            "cert_not_after",
+            "contact",
+        ])?;
+
+        for report in reports {
+            let filepath = dir.join(format!("{}.json", path_safe_component(&report.domain)));
+            self.write_to_file(&filepath, serde_json::to_string_pretty(report)?)
+                .await?;
+
+            let top_error = report
+                .errors_by_class
+                .iter()
+                .max_by_key(|(_, count)| **count)
+                .map(|(class, count)| format!("{} ({})", class, count))
+                .unwrap_or_default();
+            index.write_record([
+                report.domain.clone(),
+                report
+                    .display_domain
+                    .clone()
+                    .unwrap_or_else(|| report.domain.clone()),
+                report.requests.to_string(),
+                report.pages_fetched.to_string(),
+                report.failures.to_string(),
+                report.bytes.to_string(),
+                format!("{:.1}", report.avg_latency_ms),
+                report.robots_blocks.to_string(),
+                top_error,
+                report
+                    .certificate
+                    .as_ref()
+                    .map(|certificate| certificate.not_after.clone())
+                    .unwrap_or_default(),
+                report
+                    .contact
+                    .as_ref()
+                    .and_then(|contact| contact.primary_contact())
+                    .unwrap_or_default()
+                    .to_string(),
+            ])?;
+        }
+
+        let index = String::from_utf8(index.into_inner()?)?;
+        self.write_to_file(&dir.join("index.csv"), index).await?;
+        Ok(dir)
+    }
+
     /// Load stored results for analysis
     pub async fn load_results(&self, pattern: Option<&str>) -> Result<Vec<StoredCrawlResult>> {
         let mut results = Vec::new();
@@ -166,6 +799,10 @@ impl DataStorage {
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
 
+            if path.file_name().and_then(|s| s.to_str()) == Some(FILENAME_INDEX) {
+                continue;
+            }
+
             if let Some(pattern) = pattern
                 && !path.to_string_lossy().contains(pattern)
             {
@@ -264,63 +901,176 @@ impl DataStorage {
         Self::new("./crawl_data", format)
     }
 
+    /// Resolve where a result is written and whether it is appended to an existing file
+    async fn resolve_output_path(&self, result: &StoredCrawlResult) -> Result<(PathBuf, bool)> {
+        let line_based = matches!(self.format, OutputFormat::Jsonl | OutputFormat::Csv);
+
+        let Some(namer) = &self.naming else {
+            let filename = self.generate_filename(&result.url, &result.timestamp);
+            return Ok((self.output_dir.join(filename), line_based));
+        };
+
+        // Scoped variable for DRY
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        let relative = namer.render(&NamingContext {
+            url: &result.url,
+            timestamp: &result.timestamp,
+            session_id: &result.metadata.crawl_session_id,
+            sequence,
+            extension: self.file_extension(),
+        });
+        let filepath = self.output_dir.join(relative);
+
+        if let Some(parent) = filepath.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        Ok(match namer.on_collision() {
+            CollisionPolicy::Overwrite => (filepath, false),
+            CollisionPolicy::Append if line_based => (filepath, true),
+            CollisionPolicy::Append | CollisionPolicy::Suffix => (next_free_path(&filepath), false),
+        })
+    }
+
+    /// File extension for the configured output format
+    fn file_extension(&self) -> &'static str {
+        match &self.format {
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Parquet => "parquet",
+        }
+    }
+
     /// Helper method to generate filename
     fn generate_filename(&self, url: &str, timestamp: &SystemTime) -> String {
-        let mut hasher = DefaultHasher::new();
-        hasher.write(url.as_bytes());
-        let url_hash = format!("{:x}", hasher.finish());
         let timestamp_secs = timestamp
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
 
-        let extension = match &self.format {
-            OutputFormat::Json => "json",
-            OutputFormat::Jsonl => "jsonl",
-            OutputFormat::Csv => "csv",
-            OutputFormat::Parquet => "parquet",
-        };
-
-        format!("crawl_{}_{}.{}", timestamp_secs, &url_hash[..8], extension)
+        format!(
+            "crawl_{}_{}.{}",
+            timestamp_secs,
+            url_to_filename(url),
+            self.file_extension()
+        )
     }
 
     /// Write content to file
     async fn write_to_file(&self, path: &Path, content: String) -> Result<()> {
-        if self.compression {
-            // TODO: Implement compression
-            fs::write(path, content).await?;
-        } else {
-            fs::write(path, content).await?;
+        if let Some(backend) = &self.backend {
+            return backend
+                .put(&self.relative_key(path), Bytes::from(content))
+                .await;
         }
-        Ok(())
+        // TODO: Implement compression
+        lock(&self.written).insert(path.to_path_buf());
+        let path = path.to_path_buf();
+        let policy = self.fsync_policy;
+        tokio::task::spawn_blocking(move || write_atomic(&path, content.as_bytes(), policy)).await?
+    }
+
+    /// Append a filename → URL mapping to the index file
+    async fn append_index_entry(&self, filepath: &Path, url: &str) -> Result<()> {
+        let entry = FilenameIndexEntry {
+            file: self.relative_key(filepath),
+            url: url.to_string(),
+            timestamp: SystemTime::now(),
+        };
+
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        self.append_to_file(&self.output_dir.join(FILENAME_INDEX), line)
+            .await
     }
 
-    /// Append content to file
+    /// Append a newline-terminated record to file
     async fn append_to_file(&self, path: &Path, content: String) -> Result<()> {
-        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-        file.write_all(content.as_bytes())?;
-        Ok(())
+        if self.backend.is_some() {
+            let mut buffered = lock(&self.buffered_records);
+            let records = buffered.entry(self.relative_key(path)).or_default();
+            records.push_str(content.trim_end_matches('\n'));
+            records.push('\n');
+            return Ok(());
+        }
+        self.append_blocking(path, content).await
     }
 
-    /// Store result as CSV
-    async fn store_as_csv(&self, result: &StoredCrawlResult, path: &Path) -> Result<()> {
-        // Implementation for CSV storage
-        let csv_line = format!(
-            "{},{},{},{},{},{}\n",
-            result.url,
-            result.title.as_deref().unwrap_or(""),
-            result.word_count,
-            result.language.as_deref().unwrap_or(""),
-            result.metadata.response_time_ms,
-            result.metadata.status_code.unwrap_or(0)
-        );
+    /// Append a record on the blocking pool, where an fsync does not stall the runtime
+    async fn append_blocking(&self, path: &Path, record: String) -> Result<()> {
+        lock(&self.written).insert(path.to_path_buf());
+        let path = path.to_path_buf();
+        let policy = self.fsync_policy;
+        tokio::task::spawn_blocking(move || append_record(&path, &record, policy)).await?
+    }
+
+    /// Path below the output directory with `/` separators
+    fn relative_key(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.output_dir).unwrap_or(path);
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Append a result as a CSV row, starting the file with the header
+    ///
+    /// A file is started whenever the naming template moves on to a new file, when
+    /// `append` is off (the file was just removed) and for the built-in per-result
+    /// names; rows appended to an existing file get no second header.
+    ///
+    /// With a backend every flush uploads a new part, so each part starts with the
+    /// header again.
+    async fn store_as_csv(
+        &self,
+        result: &StoredCrawlResult,
+        path: &Path,
+        append: bool,
+    ) -> Result<()> {
+        if self.backend.is_some() {
+            let key = self.relative_key(path);
+            let mut csv_headers = lock(&self.csv_headers);
+            let mut buffered = lock(&self.buffered_records);
+            if !append {
+                csv_headers.remove(&key);
+                buffered.remove(&key);
+            }
+            let header = csv_headers.insert(key.clone());
+            let row = encode_record(OutputFormat::Csv, result, header)?;
+            buffered.entry(key).or_default().push_str(&row);
+            return Ok(());
+        }
 
-        self.append_to_file(path, csv_line).await
+        let header = fs::metadata(path)
+            .await
+            .map_or(true, |metadata| metadata.len() == 0);
+        let row = encode_record(OutputFormat::Csv, result, header)?;
+        self.append_blocking(path, row).await
+    }
+}
+
+/// Lock a buffer, also after a panic in another writer
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Object key of one flush's part of the record file `key`
+pub(crate) fn part_key(key: &str, sequence: u64) -> String {
+    let name_start = key.rfind('/').map_or(0, |slash| slash + 1);
+    match key[name_start..].rfind('.') {
+        Some(dot) => {
+            let (stem, extension) = key.split_at(name_start + dot);
+            format!("{}.part-{:05}{}", stem, sequence, extension)
+        }
+        None => format!("{}.part-{:05}", key, sequence),
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlSessionSummary {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub session_id: String,
     pub start_time: SystemTime,
     pub end_time: SystemTime,
@@ -0,0 +1,326 @@
+/// Crash-safe file writes for crawl output
+///
+/// - Whole-file formats (JSON, summaries) are written to a temporary file in the
+///   same directory and renamed over the target, so readers see either the old or
+///   the new file, never a half-written one.
+/// - Record formats (JSONL, CSV, the filename index) are appended one complete,
+///   newline-terminated record per write. A torn trailing record left by a crash is
+///   cut off before the first append of the process, `recover_jsonl_tail` cuts it
+///   off at startup and `recover_jsonl` repairs a whole file.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Files whose tail was checked by this process
+///
+/// Only a crash tears a record, so the check runs once per file. Later appends
+/// skip it: a record another thread is still writing would look torn.
+static CHECKED_TAILS: LazyLock<Mutex<HashSet<PathBuf>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// When written data is forced to disk with fsync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FsyncPolicy {
+    /// Leave flushing to the OS (fastest, a power loss may lose recent writes)
+    Never,
+    /// Sync temporary files before they replace the target (default)
+    #[default]
+    AtomicWritesOnly,
+    /// Additionally sync after every appended record
+    Always,
+}
+
+/// Result of scanning a JSONL file for damaged records
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub path: PathBuf,
+    /// Records kept; only counted by a full scan with `recover_jsonl`
+    pub valid_records: usize,
+    /// Records that were not valid JSON and were moved to the `.corrupt` file
+    pub dropped_records: usize,
+    /// Whether the file was rewritten
+    pub repaired: bool,
+}
+
+/// Replace `path` with `content` atomically
+pub fn write_atomic(path: &Path, content: &[u8], policy: FsyncPolicy) -> Result<()> {
+    write_atomic_with(path, policy, |writer| Ok(writer.write_all(content)?))
+}
+
+/// Replace `path` atomically with what `write` streams into a buffered writer
+pub fn write_atomic_with<F>(path: &Path, policy: FsyncPolicy, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let temp_path = temp_path_for(path);
+
+    let write_result = (|| -> Result<()> {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        if policy != FsyncPolicy::Never {
+            file.sync_all()?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = write_result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    std::fs::rename(&temp_path, path)?;
+    if policy != FsyncPolicy::Never {
+        sync_parent_dir(path);
+    }
+    Ok(())
+}
+
+/// Set the modification time of `path` to now
+///
+/// Content-addressed stores call this when they reuse a file, so retention by age
+/// keeps files that are still referenced by new results.
+pub fn touch(path: &Path) -> Result<()> {
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(std::time::SystemTime::now())?;
+    Ok(())
+}
+
+/// Append one newline-terminated record to `path`
+///
+/// The first append of the process to a file truncates any partial trailing
+/// record from an interrupted earlier run, so a torn write never merges with the
+/// next record.
+pub fn append_record(path: &Path, record: &str, policy: FsyncPolicy) -> Result<()> {
+    let mut file = {
+        let mut checked = CHECKED_TAILS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        if !checked.contains(path) {
+            if let Some(end) = partial_tail_start(&mut file)? {
+                truncate_tail(&file, end)?;
+            }
+            checked.insert(path.to_path_buf());
+        }
+        file
+    };
+
+    let mut line = String::with_capacity(record.len() + 1);
+    line.push_str(record.trim_end_matches('\n'));
+    line.push('\n');
+    // A single write on an O_APPEND handle keeps concurrent records from interleaving
+    if let Err(e) = file.write_all(line.as_bytes()) {
+        // The failed write may have left a partial record for the next append to cut
+        CHECKED_TAILS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(path);
+        return Err(e.into());
+    }
+
+    if policy == FsyncPolicy::Always {
+        file.sync_data()?;
+    }
+    Ok(())
+}
+
+/// Cut a torn trailing record off a JSONL file without reading the rest of it
+///
+/// The cut bytes are appended to `<file>.corrupt` for inspection.
+pub fn recover_jsonl_tail(path: &Path, policy: FsyncPolicy) -> Result<RecoveryReport> {
+    let mut report = RecoveryReport {
+        path: path.to_path_buf(),
+        ..RecoveryReport::default()
+    };
+    let torn = {
+        let mut checked = CHECKED_TAILS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let Some(end) = partial_tail_start(&mut file)? else {
+            checked.insert(path.to_path_buf());
+            return Ok(report);
+        };
+        let mut torn = Vec::new();
+        file.seek(SeekFrom::Start(end))?;
+        file.read_to_end(&mut torn)?;
+        truncate_tail(&file, end)?;
+        if policy != FsyncPolicy::Never {
+            file.sync_data()?;
+        }
+        checked.insert(path.to_path_buf());
+        torn
+    };
+    append_record(&corrupt_path(path), &String::from_utf8_lossy(&torn), policy)?;
+    report.dropped_records = 1;
+    report.repaired = true;
+    Ok(report)
+}
+
+/// Scan a JSONL file, dropping a torn tail and any lines that are not valid JSON
+///
+/// Dropped lines are appended to `<file>.corrupt` for inspection; the repaired file
+/// replaces the original atomically.
+pub fn recover_jsonl(path: &Path, policy: FsyncPolicy) -> Result<RecoveryReport> {
+    let mut report = RecoveryReport {
+        path: path.to_path_buf(),
+        ..RecoveryReport::default()
+    };
+
+    let content = std::fs::read(path)?;
+    let text = String::from_utf8_lossy(&content);
+    let torn_tail = !content.is_empty() && !content.ends_with(b"\n");
+
+    let mut valid = String::with_capacity(text.len());
+    let mut dropped = String::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if serde_json::from_str::<serde_json::Value>(line).is_ok() {
+            valid.push_str(line);
+            valid.push('\n');
+            report.valid_records += 1;
+        } else {
+            dropped.push_str(line);
+            dropped.push('\n');
+            report.dropped_records += 1;
+        }
+    }
+
+    if report.dropped_records > 0 || torn_tail {
+        if !dropped.is_empty() {
+            append_record(&corrupt_path(path), &dropped, policy)?;
+        }
+        write_atomic(path, valid.as_bytes(), policy)?;
+        report.repaired = true;
+    }
+
+    Ok(report)
+}
+
+fn corrupt_path(path: &Path) -> PathBuf {
+    let mut corrupt_path = path.as_os_str().to_owned();
+    corrupt_path.push(".corrupt");
+    PathBuf::from(corrupt_path)
+}
+
+/// End of the last complete record, if a partial one follows it
+fn partial_tail_start(file: &mut File) -> Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(None);
+    }
+
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::Start(len - 1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(None);
+    }
+
+    // Walk back to the end of the last complete record
+    let mut end = len - 1;
+    let mut buffer = [0u8; 4096];
+    while end > 0 {
+        let start = end.saturating_sub(buffer.len() as u64);
+        let chunk = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(position) = chunk.iter().rposition(|&byte| byte == b'\n') {
+            end = start + position as u64 + 1;
+            break;
+        }
+        end = start;
+    }
+    Ok(Some(end))
+}
+
+fn truncate_tail(file: &File, end: u64) -> Result<()> {
+    tracing::warn!(
+        bytes = file.metadata()?.len() - end,
+        "Truncating partial record left by an interrupted write"
+    );
+    file.set_len(end)?;
+    Ok(())
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        uuid::Uuid::new_v4().simple()
+    ))
+}
+
+/// Persist a rename by syncing the containing directory (no-op where unsupported)
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_after_torn_write_and_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+
+        // An earlier run crashed in the middle of the second record
+        std::fs::write(&path, "{\"url\":\"a\"}\n{\"url\":").unwrap();
+        append_record(&path, r#"{"url":"c"}"#, FsyncPolicy::Never).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"url\":\"a\"}\n{\"url\":\"c\"}\n"
+        );
+
+        std::fs::write(&path, "{\"url\":\"a\"}\nnot json\n{\"url\":").unwrap();
+        let report = recover_jsonl(&path, FsyncPolicy::Never).unwrap();
+        assert!(report.repaired);
+        assert_eq!(report.valid_records, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"url\":\"a\"}\n");
+
+        // At startup only the torn tail is cut, and kept next to the file
+        std::fs::write(&path, "{\"url\":\"a\"}\n{\"url\":\"b\"}\n{\"ur").unwrap();
+        let report = recover_jsonl_tail(&path, FsyncPolicy::Never).unwrap();
+        assert!(report.repaired);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"url\":\"a\"}\n{\"url\":\"b\"}\n"
+        );
+        assert!(
+            std::fs::read_to_string(corrupt_path(&path))
+                .unwrap()
+                .ends_with("{\"ur\n")
+        );
+        assert!(
+            !recover_jsonl_tail(&path, FsyncPolicy::Never)
+                .unwrap()
+                .repaired
+        );
+    }
+}
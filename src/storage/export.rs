@@ -0,0 +1,162 @@
+/// Export stored crawl results into a single file in another format
+///
+/// The session's results are streamed from the result files to the export one
+/// record at a time on the blocking thread pool, so neither memory use nor the
+/// async runtime depends on the size of the session.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use super::convert::RecordWriter;
+use super::data::{DataStorage, OutputFormat};
+use super::durable::FsyncPolicy;
+use super::reader::ResultFilter;
+
+/// Progress of a running export, reported after every record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub exported: usize,
+    pub total: usize,
+}
+
+/// Outcome of a finished export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: PathBuf,
+    pub format: OutputFormat,
+    pub records: usize,
+}
+
+impl DataStorage {
+    /// Write the stored results of `session_id` to `destination`
+    ///
+    /// The results are counted first, for `on_progress`, then streamed to a
+    /// temporary file next to the destination, which replaces it once the export
+    /// is complete.
+    pub async fn export_results<F>(
+        &self,
+        session_id: &str,
+        format: OutputFormat,
+        destination: &Path,
+        mut on_progress: F,
+    ) -> Result<ExportSummary>
+    where
+        F: FnMut(ExportProgress) + Send + 'static,
+    {
+        let counted = self.read_session(session_id, ResultFilter::default())?;
+        let results = self.read_session(session_id, ResultFilter::default())?;
+        let sync = self.fsync_policy() != FsyncPolicy::Never;
+        let destination = destination.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            let mut total = 0;
+            for result in counted {
+                result?;
+                total += 1;
+            }
+
+            if let Some(parent) = destination.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut staging = destination.as_os_str().to_owned();
+            staging.push(".partial");
+            let staging = PathBuf::from(staging);
+
+            let written = (|| {
+                let mut writer =
+                    RecordWriter::new(format, BufWriter::new(File::create(&staging)?))?;
+                let mut exported = 0;
+                for result in results {
+                    writer.write(&result?)?;
+                    exported += 1;
+                    on_progress(ExportProgress {
+                        exported,
+                        total: total.max(exported),
+                    });
+                }
+                let file = writer.finish()?;
+                if sync {
+                    file.sync_all()?;
+                }
+                Ok::<_, anyhow::Error>(exported)
+            })();
+            let records = match written {
+                Ok(records) => records,
+                Err(e) => {
+                    let _ = std::fs::remove_file(&staging);
+                    return Err(e);
+                }
+            };
+
+            // Readers never see a half-written export
+            std::fs::rename(&staging, &destination)?;
+
+            Ok(ExportSummary {
+                path: destination,
+                format,
+                records,
+            })
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StoredCrawlResult;
+    use std::sync::{Arc, Mutex};
+
+    fn result(url: &str, session: &str) -> StoredCrawlResult {
+        StoredCrawlResult::for_test(url)
+            .with_title("Title")
+            .with_content("some text")
+            .with_language("Eng")
+            .with_session(session)
+    }
+
+    #[tokio::test]
+    async fn test_export_streams_one_sessions_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = DataStorage::new(dir.path().join("results"), OutputFormat::Jsonl).unwrap();
+        storage
+            .store_batch(&[
+                result("https://a.example/1", "s1"),
+                result("https://b.example/", "s2"),
+                result("https://a.example/2", "s1"),
+            ])
+            .await
+            .unwrap();
+
+        for format in [OutputFormat::Jsonl, OutputFormat::Json, OutputFormat::Csv] {
+            let destination = dir.path().join("exports").join(format!("s1.{:?}", format));
+            let progress = Arc::new(Mutex::new(Vec::new()));
+            let reported = Arc::clone(&progress);
+            let summary = storage
+                .export_results("s1", format, &destination, move |progress| {
+                    reported
+                        .lock()
+                        .unwrap()
+                        .push((progress.exported, progress.total));
+                })
+                .await
+                .unwrap();
+            assert_eq!(summary.records, 2);
+            assert_eq!(*progress.lock().unwrap(), vec![(1, 2), (2, 2)]);
+
+            let exported = std::fs::read_to_string(&destination).unwrap();
+            assert!(exported.contains("https://a.example/2"));
+            assert!(!exported.contains("https://b.example/"));
+        }
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path().join("exports"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .filter(|name| name.to_string_lossy().ends_with(".partial"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+/// Atom and RSS feeds of new and changed pages
+///
+/// Monitoring a site means finding out what changed since the last crawl. With a
+/// `FeedConfig`, every session writes `feed.xml` to its session directory, listing
+/// the pages it stored that are new or whose text changed, with their titles and
+/// a summary of their text. A session on its own lists every page it stored. Crawls
+/// scheduled as a series share a `series_dir`, which remembers the text hash of
+/// every page seen within `forget_after_days` in a `ContentHashStore` and keeps a
+/// feed of the latest entries of the whole series, so a feed reader can subscribe
+/// to one URL across runs. A session holds a lock on the series directory from
+/// `SessionFeed::open` until it is dropped, so overlapping runs of a series fail
+/// to open it instead of overwriting each other's state.
+use anyhow::Result;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::content_hash::{ContentHashStore, ContentHashes, hash_text};
+use super::data::StoredCrawlResult;
+use super::durable::{FsyncPolicy, write_atomic};
+use super::naming::civil_date;
+use crate::logging::session_events_path;
+
+/// File name of a feed, in a session directory and in a series directory
+pub const FEED_FILE: &str = "feed.xml";
+/// Entries of a crawl series, in its series directory
+pub const FEED_STATE_FILE: &str = "feed_state.json";
+/// Text hashes of the pages of a crawl series, in its series directory
+pub const FEED_HASHES_FILE: &str = "page_hashes.jsonl";
+/// Held by the session writing a series
+const FEED_LOCK_FILE: &str = "feed.lock";
+
+/// Feed of `session_id` under the storage directory
+pub fn session_feed_path(storage_dir: &Path, session_id: &str) -> PathBuf {
+    session_events_path(storage_dir, session_id).with_file_name(FEED_FILE)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedFormat {
+    #[default]
+    Atom,
+    /// RSS 2.0
+    Rss,
+}
+
+/// Feed output settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedConfig {
+    pub format: FeedFormat,
+    /// Feed title; `None` names the feed after the session
+    pub title: Option<String>,
+    /// Site the feed is about, linked from the feed
+    pub link: Option<String>,
+    /// Directory shared by the scheduled crawls of a series; `None` keeps the feed
+    /// to the session
+    pub series_dir: Option<PathBuf>,
+    /// Characters of page text in an entry's summary
+    pub summary_chars: usize,
+    /// Entries kept in the series feed, newest first
+    pub max_entries: usize,
+    /// Pages the series has not seen for this many days are forgotten, and are
+    /// new again when they come back; `None` remembers every page
+    pub forget_after_days: Option<u64>,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            format: FeedFormat::default(),
+            title: None,
+            link: None,
+            series_dir: None,
+            summary_chars: 280,
+            max_entries: 500,
+            forget_after_days: Some(90),
+        }
+    }
+}
+
+/// Why a page is in the feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedChange {
+    New,
+    Changed,
+}
+
+impl FeedChange {
+    fn term(self) -> &'static str {
+        match self {
+            FeedChange::New => "new",
+            FeedChange::Changed => "changed",
+        }
+    }
+}
+
+/// One new or changed page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedEntry {
+    /// Unique per page version
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub summary: String,
+    pub change: FeedChange,
+    pub session_id: String,
+    pub updated: SystemTime,
+}
+
+/// What a series remembers between crawls besides its page hashes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FeedState {
+    /// Text hash of every page seen, by URL; only read from series written before
+    /// the hashes moved to `FEED_HASHES_FILE`
+    #[serde(default, skip_serializing)]
+    pages: BTreeMap<String, String>,
+    /// Latest entries of the series, newest first
+    entries: Vec<FeedEntry>,
+}
+
+/// Page hashes of a series, with the lock that keeps other runs out
+struct Series {
+    hashes: ContentHashStore,
+    _lock: File,
+}
+
+/// New and changed pages of one session
+pub struct SessionFeed {
+    config: FeedConfig,
+    session_id: String,
+    state: FeedState,
+    series: Option<Series>,
+    /// Hashes of the pages this session stored, added to the series on save
+    seen: BTreeMap<String, ContentHashes>,
+    entries: Vec<FeedEntry>,
+}
+
+impl SessionFeed {
+    /// Feed of `session_id`, continuing the series of `config` when it has one
+    ///
+    /// Fails when another session holds the series directory.
+    pub fn open(config: FeedConfig, session_id: &str) -> Result<Self> {
+        let Some(dir) = &config.series_dir else {
+            return Ok(Self {
+                config,
+                session_id: session_id.to_string(),
+                state: FeedState::default(),
+                series: None,
+                seen: BTreeMap::new(),
+                entries: Vec::new(),
+            });
+        };
+        std::fs::create_dir_all(dir)?;
+        let lock = File::create(dir.join(FEED_LOCK_FILE))?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(anyhow::anyhow!(
+                    "Feed series {} is being written by another session",
+                    dir.display()
+                ));
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        let mut state: FeedState = match std::fs::read(dir.join(FEED_STATE_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => FeedState::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let hashes = ContentHashStore::open(&dir.join(FEED_HASHES_FILE))?;
+        for (url, text) in std::mem::take(&mut state.pages) {
+            if hashes.get(&url).is_none() {
+                let raw = String::new();
+                hashes.record(&url, ContentHashes { raw, text })?;
+            }
+        }
+        if let Some(days) = config.forget_after_days {
+            let cutoff = SystemTime::now() - Duration::from_secs(days * 86_400);
+            hashes.retain_crawled_since(cutoff);
+        }
+        hashes.compact()?;
+        Ok(Self {
+            config,
+            session_id: session_id.to_string(),
+            state,
+            series: Some(Series {
+                hashes,
+                _lock: lock,
+            }),
+            seen: BTreeMap::new(),
+            entries: Vec::new(),
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Add a stored page to the feed if it is new or its text changed
+    ///
+    /// Pages stored with their sanitized HTML in place of the text are summarized
+    /// from the text of that HTML.
+    pub fn observe(&mut self, result: &StoredCrawlResult) -> Option<FeedChange> {
+        let text = match (&result.content, &result.sanitized_html) {
+            (Some(content), _) => content.clone(),
+            (None, Some(html)) => html_text(html),
+            (None, None) => String::new(),
+        };
+        let text = text.as_str();
+        let hash = hash_text(text);
+        let hashes = ContentHashes {
+            raw: result.raw_html_hash.clone().unwrap_or_default(),
+            text: hash.clone(),
+        };
+        let previous = match self.seen.insert(result.url.clone(), hashes) {
+            Some(previous) => Some(previous.text),
+            None => self
+                .series
+                .as_ref()
+                .and_then(|series| series.hashes.get(&result.url))
+                .map(|previous| previous.text),
+        };
+        let change = match previous {
+            None => FeedChange::New,
+            Some(previous) if previous != hash => FeedChange::Changed,
+            Some(_) => return None,
+        };
+
+        let id = Sha256::new()
+            .chain_update(result.url.as_bytes())
+            .chain_update(b"\n")
+            .chain_update(hash.as_bytes())
+            .finalize();
+        let summary = summarize(text, self.config.summary_chars);
+        self.entries.push(FeedEntry {
+            id: format!(
+                "urn:sha256:{}",
+                id.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+            ),
+            url: result.url.clone(),
+            title: result
+                .title
+                .clone()
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or_else(|| result.url.clone()),
+            summary,
+            change,
+            session_id: self.session_id.clone(),
+            updated: result.timestamp,
+        });
+        Some(change)
+    }
+
+    /// Entries of this session, in the order the pages were stored
+    pub fn entries(&self) -> &[FeedEntry] {
+        &self.entries
+    }
+
+    /// The session's feed document
+    pub fn render(&self) -> String {
+        let title = self
+            .config
+            .title
+            .clone()
+            .unwrap_or_else(|| format!("Crawl session {}", self.session_id));
+        let entries: Vec<&FeedEntry> = self.entries.iter().rev().collect();
+        render(
+            &self.config,
+            &title,
+            &format!("urn:crawl-session:{}", self.session_id),
+            &entries,
+        )
+    }
+
+    /// Record the session in its series and rewrite the series feed
+    ///
+    /// Returns the series feed's path; `None` without a series.
+    pub fn save_series(self) -> Result<Option<PathBuf>> {
+        let (Some(dir), Some(series)) = (self.config.series_dir.clone(), &self.series) else {
+            return Ok(None);
+        };
+        for (url, hashes) in self.seen {
+            series.hashes.record(&url, hashes)?;
+        }
+        let mut state = self.state;
+        let mut entries = self.entries;
+        entries.reverse();
+        entries.append(&mut state.entries);
+        entries.truncate(self.config.max_entries);
+        state.entries = entries;
+
+        let title = self
+            .config
+            .title
+            .clone()
+            .unwrap_or_else(|| format!("Crawl series {}", dir.display()));
+        let series_id = format!("urn:crawl-series:{}", hash_text(&dir.to_string_lossy()));
+        let feed = render(
+            &self.config,
+            &title,
+            &series_id,
+            &state.entries.iter().collect::<Vec<_>>(),
+        );
+        write_atomic(
+            &dir.join(FEED_STATE_FILE),
+            &serde_json::to_vec(&state)?,
+            FsyncPolicy::default(),
+        )?;
+        let path = dir.join(FEED_FILE);
+        write_atomic(&path, feed.as_bytes(), FsyncPolicy::default())?;
+        Ok(Some(path))
+    }
+}
+
+/// Text of an HTML fragment, without its markup
+fn html_text(html: &str) -> String {
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whitespace-collapsed start of `text`, ending in an ellipsis when cut
+fn summarize(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+    let mut summary: String = collapsed.chars().take(max_chars).collect();
+    summary.push('…');
+    summary
+}
+
+fn render(config: &FeedConfig, title: &str, id: &str, entries: &[&FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_else(SystemTime::now);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    match config.format {
+        FeedFormat::Atom => {
+            xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+            xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+            xml.push_str(&format!("  <id>{}</id>\n", escape(id)));
+            xml.push_str(&format!("  <updated>{}</updated>\n", rfc3339(updated)));
+            if let Some(link) = &config.link {
+                xml.push_str(&format!("  <link href=\"{}\"/>\n", escape(link)));
+            }
+            for entry in entries {
+                xml.push_str("  <entry>\n");
+                xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+                xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(&entry.url)));
+                xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.id)));
+                xml.push_str(&format!(
+                    "    <updated>{}</updated>\n",
+                    rfc3339(entry.updated)
+                ));
+                xml.push_str(&format!(
+                    "    <category term=\"{}\"/>\n",
+                    entry.change.term()
+                ));
+                xml.push_str(&format!(
+                    "    <summary>{}</summary>\n",
+                    escape(&entry.summary)
+                ));
+                xml.push_str("  </entry>\n");
+            }
+            xml.push_str("</feed>\n");
+        }
+        FeedFormat::Rss => {
+            xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+            xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+            xml.push_str(&format!(
+                "  <link>{}</link>\n",
+                escape(config.link.as_deref().unwrap_or_default())
+            ));
+            xml.push_str(&format!(
+                "  <description>New and changed pages of {}</description>\n",
+                escape(title)
+            ));
+            xml.push_str(&format!(
+                "  <lastBuildDate>{}</lastBuildDate>\n",
+                rfc2822(updated)
+            ));
+            for entry in entries {
+                xml.push_str("  <item>\n");
+                xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+                xml.push_str(&format!("    <link>{}</link>\n", escape(&entry.url)));
+                xml.push_str(&format!(
+                    "    <guid isPermaLink=\"false\">{}</guid>\n",
+                    escape(&entry.id)
+                ));
+                xml.push_str(&format!(
+                    "    <pubDate>{}</pubDate>\n",
+                    rfc2822(entry.updated)
+                ));
+                xml.push_str(&format!(
+                    "    <category>{}</category>\n",
+                    entry.change.term()
+                ));
+                xml.push_str(&format!(
+                    "    <description>{}</description>\n",
+                    escape(&entry.summary)
+                ));
+                xml.push_str("  </item>\n");
+            }
+            xml.push_str("</channel>\n</rss>\n");
+        }
+    }
+    xml
+}
+
+/// Text escaped for XML, without characters XML 1.0 does not allow
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || matches!(c, '\u{FFFE}' | '\u{FFFF}') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn seconds_of_day(timestamp: SystemTime) -> (u64, u64, u64) {
+    let secs = timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86_400;
+    (secs / 3_600, secs / 60 % 60, secs % 60)
+}
+
+/// `2024-03-01T08:30:00Z`, as Atom dates are written
+fn rfc3339(timestamp: SystemTime) -> String {
+    let (year, month, day) = civil_date(&timestamp);
+    let (hour, minute, second) = seconds_of_day(timestamp);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// `Fri, 01 Mar 2024 08:30:00 GMT`, as RSS dates are written
+fn rfc2822(timestamp: SystemTime) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let days = timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 86_400;
+    let (year, month, day) = civil_date(&timestamp);
+    let (hour, minute, second) = seconds_of_day(timestamp);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        hour,
+        minute,
+        second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn page(url: &str, title: &str, content: &str) -> StoredCrawlResult {
+        StoredCrawlResult {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_281_800),
+            ..StoredCrawlResult::for_test(url)
+                .with_title(title)
+                .with_content(content)
+                .with_session("feed")
+        }
+    }
+
+    #[test]
+    fn test_series_feed_lists_new_and_changed_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FeedConfig {
+            series_dir: Some(dir.path().join("series")),
+            summary_chars: 12,
+            ..FeedConfig::default()
+        };
+
+        let mut first = SessionFeed::open(config.clone(), "run-1").unwrap();
+        let home = page("https://a.example/", "Home & <News>", "Welcome to the site");
+        let about = page("https://a.example/about", "About", "About us");
+        assert_eq!(first.observe(&home), Some(FeedChange::New));
+        assert_eq!(first.observe(&about), Some(FeedChange::New));
+        let atom = first.render();
+        assert!(atom.contains("<title>Home &amp; &lt;News&gt;</title>"));
+        assert!(atom.contains("<summary>Welcome to t…</summary>"));
+        assert!(atom.contains("<updated>2024-03-01T08:30:00Z</updated>"));
+        first.save_series().unwrap();
+
+        // Only the page whose text changed shows up in the next run
+        let mut second = SessionFeed::open(config.clone(), "run-2").unwrap();
+        // An overlapping run cannot open the series
+        assert!(SessionFeed::open(config.clone(), "run-overlap").is_err());
+        assert_eq!(second.observe(&about), None);
+        let changed = page("https://a.example/", "Home", "Welcome  to the NEW site");
+        assert_eq!(second.observe(&changed), Some(FeedChange::Changed));
+        assert_eq!(second.entries().len(), 1);
+        let series_feed = second.save_series().unwrap().unwrap();
+        let series = std::fs::read_to_string(series_feed).unwrap();
+        assert_eq!(series.matches("<entry>").count(), 3);
+        assert!(series.find("changed").unwrap() < series.find("term=\"new\"").unwrap());
+        assert!(
+            !std::fs::read_to_string(dir.path().join("series").join(FEED_STATE_FILE))
+                .unwrap()
+                .contains("\"pages\"")
+        );
+
+        // Pages unseen for longer than the series remembers are new again
+        let mut forgetful = SessionFeed::open(
+            FeedConfig {
+                forget_after_days: Some(0),
+                ..config.clone()
+            },
+            "run-3",
+        )
+        .unwrap();
+        assert_eq!(forgetful.observe(&about), Some(FeedChange::New));
+        drop(forgetful);
+
+        // Pages stored as sanitized HTML are summarized from its text
+        let mut sanitized = page("https://a.example/html", "Html", "");
+        sanitized.content = None;
+        sanitized.sanitized_html = Some("<p>Fish &amp; <b>chips</b></p>".to_string());
+        let mut feed = SessionFeed::open(FeedConfig::default(), "run-4").unwrap();
+        feed.observe(&sanitized);
+        assert_eq!(feed.entries()[0].summary, "Fish & chips");
+
+        let mut rss = SessionFeed::open(
+            FeedConfig {
+                format: FeedFormat::Rss,
+                link: Some("https://a.example/".to_string()),
+                ..FeedConfig::default()
+            },
+            "run-5",
+        )
+        .unwrap();
+        rss.observe(&page("https://a.example/x", "", "Body\u{1}text"));
+        let xml = rss.render();
+        assert!(xml.contains("<pubDate>Fri, 01 Mar 2024 08:30:00 GMT</pubDate>"));
+        assert!(xml.contains("<title>https://a.example/x</title>"));
+        assert!(xml.contains("<description>Bodytext</description>"));
+        assert!(rss.save_series().unwrap().is_none());
+    }
+}
@@ -0,0 +1,286 @@
+/// Integrity manifests for archived crawl output
+///
+/// At the end of a session with a `ManifestConfig`, every file the session wrote,
+/// and everything in its session directory, is listed with its byte size and
+/// SHA-256 in `manifest_<session>.json`. Output of other sessions in the same
+/// directory is left out. With a signing key the manifest also carries an
+/// HMAC-SHA256 signature over its contents. `verify` later reports files that went
+/// missing, changed or appeared in the session directory since, and whether the
+/// signature still matches, so archived data can be checked for completeness and
+/// tampering.
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::naming::path_safe_component;
+use crate::logging::session_events_path;
+use crate::logging::webhook::{sign_payload, verify_signature};
+
+/// File name prefix of manifests, which are left out of every manifest
+pub const MANIFEST_PREFIX: &str = "manifest_";
+
+/// Manifest file of `session_id` in an output directory
+pub fn manifest_path(output_dir: &Path, session_id: &str) -> PathBuf {
+    output_dir.join(path_safe_component(&format!(
+        "{}{}.json",
+        MANIFEST_PREFIX, session_id
+    )))
+}
+
+/// Whether and how a session writes an integrity manifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestConfig {
+    /// Key the manifest is signed with; `None` writes it unsigned
+    pub signing_key: Option<String>,
+}
+
+/// One file of a session's output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the output directory, `/`-separated; absolute for files
+    /// kept outside it
+    pub path: String,
+    pub size: u64,
+    /// Hex SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// Files of a session's output at the end of the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityManifest {
+    pub session_id: String,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    /// Files in path order
+    pub files: Vec<ManifestEntry>,
+    /// `sha256=<hex>` HMAC of the manifest without this field
+    pub signature: Option<String>,
+}
+
+/// Differences between a manifest and its output directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestVerification {
+    /// Listed files that no longer exist
+    pub missing: Vec<String>,
+    /// Listed files whose size or hash differs
+    pub modified: Vec<String>,
+    /// Files in the session directory that are not listed
+    pub unlisted: Vec<String>,
+    /// Whether the signature matches the key; `None` if no key was given. With a
+    /// key, an unsigned manifest does not match.
+    pub signature_valid: Option<bool>,
+}
+
+impl ManifestVerification {
+    /// No listed file is missing or changed and the signature, if checked, matches
+    ///
+    /// Unlisted files are reported but do not fail the check.
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.signature_valid != Some(false)
+    }
+}
+
+impl IntegrityManifest {
+    /// Hash `files` and everything in the session directory of `session_id`
+    ///
+    /// Files that no longer exist are left out.
+    pub fn build<I>(output_dir: &Path, session_id: &str, files: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        let mut paths: BTreeSet<PathBuf> = files.into_iter().collect();
+        paths.extend(session_files(output_dir, session_id)?);
+        let mut entries = BTreeMap::new();
+        for path in paths {
+            let relative = relative_path(output_dir, &path);
+            if is_manifest(&relative) || !path.is_file() {
+                continue;
+            }
+            entries.insert(relative.clone(), hash_file(&path, relative)?);
+        }
+        Ok(Self {
+            session_id: session_id.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            files: entries.into_values().collect(),
+            signature: None,
+        })
+    }
+
+    /// Sign the manifest with `key`, replacing any earlier signature
+    pub fn sign(&mut self, key: &str) -> Result<()> {
+        self.signature = Some(sign_payload(key, &self.signed_bytes()?));
+        Ok(())
+    }
+
+    /// Compare the manifest with the current contents of `output_dir`
+    pub fn verify(&self, output_dir: &Path, key: Option<&str>) -> Result<ManifestVerification> {
+        let mut verification = ManifestVerification::default();
+        for entry in &self.files {
+            let path = output_dir.join(&entry.path);
+            if !path.is_file() {
+                verification.missing.push(entry.path.clone());
+            } else if hash_file(&path, entry.path.clone())? != *entry {
+                verification.modified.push(entry.path.clone());
+            }
+        }
+        let listed: BTreeSet<&str> = self.files.iter().map(|entry| entry.path.as_str()).collect();
+        verification.unlisted = session_files(output_dir, &self.session_id)?
+            .iter()
+            .map(|path| relative_path(output_dir, path))
+            .filter(|relative| !listed.contains(relative.as_str()))
+            .collect();
+        if let Some(key) = key {
+            let signed = self.signed_bytes()?;
+            verification.signature_valid = Some(
+                self.signature
+                    .as_deref()
+                    .is_some_and(|signature| verify_signature(key, &signed, signature)),
+            );
+        }
+        Ok(verification)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Compact JSON of the manifest with the signature left out
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        Ok(serde_json::to_vec(&unsigned)?)
+    }
+}
+
+/// Files below the session directory of `session_id`, in path order
+fn session_files(output_dir: &Path, session_id: &str) -> Result<Vec<PathBuf>> {
+    let events = session_events_path(output_dir, session_id);
+    let Some(root) = events.parent() else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// `path` relative to `root` with `/` separators; files outside `root` keep their
+/// full path
+fn relative_path(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.to_string_lossy().into_owned(),
+    }
+}
+
+fn is_manifest(relative: &str) -> bool {
+    relative.starts_with(MANIFEST_PREFIX) && relative.ends_with(".json")
+}
+
+fn hash_file(path: &Path, relative: String) -> Result<ManifestEntry> {
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut File::open(path)?, &mut hasher)
+        .map_err(|e| anyhow!("Cannot hash {}: {}", path.display(), e))?;
+    let sha256 = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(ManifestEntry {
+        path: relative,
+        size,
+        sha256,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_detects_changes_and_bad_signatures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("results.jsonl"), "{\"url\":\"a\"}\n").unwrap();
+        std::fs::create_dir(dir.path().join("session_s1")).unwrap();
+        std::fs::write(dir.path().join("session_s1/events.jsonl"), "{}\n").unwrap();
+        // Output of another session is not part of this one's manifest
+        std::fs::write(dir.path().join("other.jsonl"), "{}\n").unwrap();
+
+        let mut manifest =
+            IntegrityManifest::build(dir.path(), "s1", [dir.path().join("results.jsonl")]).unwrap();
+        manifest.sign("secret").unwrap();
+        let path = manifest_path(dir.path(), "s1");
+        std::fs::write(&path, serde_json::to_string_pretty(&manifest).unwrap()).unwrap();
+
+        let manifest = IntegrityManifest::load(&path).unwrap();
+        let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["results.jsonl", "session_s1/events.jsonl"]);
+        assert_eq!(manifest.files[0].size, 12);
+        let verification = manifest.verify(dir.path(), Some("secret")).unwrap();
+        assert!(verification.is_intact());
+        assert_eq!(verification.signature_valid, Some(true));
+        assert!(verification.unlisted.is_empty());
+
+        std::fs::write(dir.path().join("results.jsonl"), "{\"url\":\"b\"}\n").unwrap();
+        std::fs::remove_file(dir.path().join("session_s1/events.jsonl")).unwrap();
+        std::fs::write(dir.path().join("later.jsonl"), "").unwrap();
+        std::fs::write(dir.path().join("session_s1/skipped.jsonl"), "").unwrap();
+        let verification = manifest.verify(dir.path(), Some("wrong")).unwrap();
+        assert!(!verification.is_intact());
+        assert_eq!(verification.modified, ["results.jsonl"]);
+        assert_eq!(verification.missing, ["session_s1/events.jsonl"]);
+        assert_eq!(verification.unlisted, ["session_s1/skipped.jsonl"]);
+        assert_eq!(verification.signature_valid, Some(false));
+
+        let mut tampered = manifest.clone();
+        tampered.files.pop();
+        assert_ne!(
+            tampered
+                .verify(dir.path(), Some("secret"))
+                .unwrap()
+                .signature_valid,
+            Some(true)
+        );
+
+        // Stripping the signature does not pass a keyed check
+        let mut unsigned = manifest.clone();
+        unsigned.signature = None;
+        assert_eq!(
+            unsigned
+                .verify(dir.path(), Some("secret"))
+                .unwrap()
+                .signature_valid,
+            Some(false)
+        );
+        assert_eq!(
+            unsigned.verify(dir.path(), None).unwrap().signature_valid,
+            None
+        );
+    }
+}
@@ -1,9 +1,24 @@
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use url::Url;
+
+use crate::core::error::CrawlError;
+use crate::core::{display_host, host_key};
+use crate::network::{CertificateInfo, SiteContact};
+
+/// Domain the metrics of `url` are recorded under
+fn domain_key(url: &Url) -> String {
+    url.host_str()
+        .map(host_key)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Distinct path sections tracked per domain for the report's top paths
+const MAX_TRACKED_PATHS: usize = 1000;
 
 /// Comprehensive metrics collection for the crawler
 #[derive(Debug)]
@@ -30,17 +45,51 @@ pub struct CrawlerMetrics {
     start_time: Instant,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct DomainMetrics {
     pub requests_count: u64,
     pub success_count: u64,
     pub failure_count: u64,
     pub avg_response_time_ms: f64,
     pub total_bytes: u64,
+    /// Failures counted by error class (e.g. `HTTP 404`, `Timeout (connect)`)
+    pub errors_by_class: HashMap<String, u64>,
+    /// URLs not fetched because robots.txt disallows them
+    pub robots_blocks: u64,
+    /// Certificate presented over TLS, when certificate capture is enabled
+    pub certificate: Option<CertificateInfo>,
+    /// Contacts from the domain's security.txt and humans.txt
+    pub contact: Option<SiteContact>,
+    /// Requests per top-level path section (`/news/`, `/blog/`, ...)
+    #[serde(skip)]
+    pub path_counts: HashMap<String, u64>,
     #[serde(skip)]
     pub last_request_time: Option<Instant>,
 }
 
+/// End-of-session report for one domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainReport {
+    /// Host in ASCII (punycode) form
+    pub domain: String,
+    /// Unicode form of an internationalized `domain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_domain: Option<String>,
+    pub requests: u64,
+    pub pages_fetched: u64,
+    pub failures: u64,
+    pub bytes: u64,
+    pub avg_latency_ms: f64,
+    pub errors_by_class: BTreeMap<String, u64>,
+    pub robots_blocks: u64,
+    /// Most requested path sections with their request counts
+    pub top_paths: Vec<(String, u64)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<CertificateInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<SiteContact>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MetricsSnapshot {
     pub uptime_secs: u64,
@@ -102,6 +151,108 @@ impl CrawlerMetrics {
             .await;
     }
 
+    /// Record a fetched page, attributing it to its domain and path section
+    pub async fn record_page(&self, url: &Url, response_time: Duration, bytes: u64) {
+        let domain = domain_key(url);
+        self.record_success(&domain, response_time, bytes).await;
+        self.record_path(&domain, url).await;
+    }
+
+    /// Record a failed fetch with its error class
+    pub async fn record_error(&self, url: &Url, response_time: Duration, error: &anyhow::Error) {
+        let domain = domain_key(url);
+        self.record_failure(&domain, response_time).await;
+        self.record_path(&domain, url).await;
+
+        let class = error_class(&CrawlError::from_anyhow_error(error));
+        let mut stats = self.domain_stats.write().await;
+        *stats
+            .entry(domain)
+            .or_default()
+            .errors_by_class
+            .entry(class)
+            .or_insert(0) += 1;
+    }
+
+    /// Record a URL skipped because robots.txt disallows it
+    pub async fn record_robots_block(&self, url: &Url) {
+        let domain = domain_key(url);
+        let mut stats = self.domain_stats.write().await;
+        stats.entry(domain).or_default().robots_blocks += 1;
+    }
+
+    /// Whether a certificate has been recorded for the URL's domain
+    pub async fn has_certificate(&self, url: &Url) -> bool {
+        let domain = domain_key(url);
+        let stats = self.domain_stats.read().await;
+        stats
+            .get(&domain)
+            .is_some_and(|metrics| metrics.certificate.is_some())
+    }
+
+    /// Record the certificate the URL's domain presented
+    pub async fn record_certificate(&self, url: &Url, certificate: CertificateInfo) {
+        let domain = domain_key(url);
+        let mut stats = self.domain_stats.write().await;
+        stats.entry(domain).or_default().certificate = Some(certificate);
+    }
+
+    /// Record the contact details a domain publishes
+    pub async fn record_contact(&self, domain: &str, contact: SiteContact) {
+        let mut stats = self.domain_stats.write().await;
+        stats.entry(host_key(domain)).or_default().contact = Some(contact);
+    }
+
+    /// Per-domain reports, sorted by domain
+    pub async fn domain_reports(&self, top_paths: usize) -> Vec<DomainReport> {
+        let stats = self.domain_stats.read().await;
+        let mut reports: Vec<DomainReport> = stats
+            .iter()
+            .map(|(domain, metrics)| {
+                let mut paths: Vec<(String, u64)> = metrics
+                    .path_counts
+                    .iter()
+                    .map(|(path, count)| (path.clone(), *count))
+                    .collect();
+                paths.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                paths.truncate(top_paths);
+
+                let display = display_host(domain);
+                DomainReport {
+                    display_domain: (display != *domain).then_some(display),
+                    domain: domain.clone(),
+                    requests: metrics.requests_count,
+                    pages_fetched: metrics.success_count,
+                    failures: metrics.failure_count,
+                    bytes: metrics.total_bytes,
+                    avg_latency_ms: metrics.avg_response_time_ms,
+                    errors_by_class: metrics.errors_by_class.clone().into_iter().collect(),
+                    robots_blocks: metrics.robots_blocks,
+                    top_paths: paths,
+                    certificate: metrics.certificate.clone(),
+                    contact: metrics.contact.clone(),
+                }
+            })
+            .collect();
+        reports.sort_by(|a, b| a.domain.cmp(&b.domain));
+        reports
+    }
+
+    async fn record_path(&self, domain: &str, url: &Url) {
+        let section = match url.path_segments().and_then(|mut segments| segments.next()) {
+            Some(first) if !first.is_empty() => format!("/{}/", first),
+            _ => "/".to_string(),
+        };
+
+        let mut stats = self.domain_stats.write().await;
+        let path_counts = &mut stats.entry(domain.to_string()).or_default().path_counts;
+        if let Some(count) = path_counts.get_mut(&section) {
+            *count += 1;
+        } else if path_counts.len() < MAX_TRACKED_PATHS {
+            path_counts.insert(section, 1);
+        }
+    }
+
     /// Record a retry
     pub fn record_retry(&self) {
         self.retried_requests.fetch_add(1, Ordering::Relaxed);
@@ -129,14 +280,7 @@ impl CrawlerMetrics {
         bytes: u64,
     ) {
         let mut stats = self.domain_stats.write().await;
-        let domain_metrics = stats.entry(domain.to_string()).or_insert(DomainMetrics {
-            requests_count: 0,
-            success_count: 0,
-            failure_count: 0,
-            avg_response_time_ms: 0.0,
-            total_bytes: 0,
-            last_request_time: None,
-        });
+        let domain_metrics = stats.entry(host_key(domain)).or_default();
 
         domain_metrics.requests_count += 1;
         if success {
@@ -239,8 +383,91 @@ impl CrawlerMetrics {
     }
 }
 
+/// Report label for an error: variant name, with the status code or timeout stage
+pub(crate) fn error_class(error: &CrawlError) -> String {
+    match error {
+        CrawlError::HttpError(code) => format!("HTTP {}", code),
+        CrawlError::StageTimeout(stage, _) => format!("Timeout ({})", stage),
+        CrawlError::UnknownError(_) => "Other".to_string(),
+        other => {
+            let name = format!("{:?}", other);
+            name.split('(').next().unwrap_or(&name).to_string()
+        }
+    }
+}
+
 impl Default for CrawlerMetrics {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(path: &str) -> Url {
+        Url::parse(&format!("https://example.com{}", path)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_domain_report_counts_pages_errors_and_paths() {
+        let metrics = CrawlerMetrics::new();
+        let latency = Duration::from_millis(100);
+        for path in [
+            "/news/a", "/news/b", "/news/c", "/blog/a", "/blog/b", "/about",
+        ] {
+            metrics.record_page(&url(path), latency, 1000).await;
+        }
+        metrics
+            .record_error(&url("/news/d"), latency, &CrawlError::HttpError(504).into())
+            .await;
+        metrics
+            .record_error(
+                &url("/news/e"),
+                latency,
+                &anyhow::anyhow!("HTTP error: 404 Not Found"),
+            )
+            .await;
+        metrics.record_robots_block(&url("/private")).await;
+
+        let reports = metrics.domain_reports(2).await;
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.domain, "example.com");
+        assert_eq!(report.pages_fetched, 6);
+        assert_eq!(report.failures, 2);
+        assert_eq!(report.bytes, 6000);
+        assert_eq!(report.robots_blocks, 1);
+        assert_eq!(
+            report.errors_by_class,
+            BTreeMap::from([
+                ("HTTP 404".to_string(), 1),
+                ("NetworkTimeout".to_string(), 1),
+            ])
+        );
+        assert_eq!(
+            report.top_paths,
+            vec![("/news/".to_string(), 5), ("/blog/".to_string(), 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_domain_reports_are_sorted_by_domain() {
+        let metrics = CrawlerMetrics::new();
+        for host in ["b.example", "a.example"] {
+            let page = Url::parse(&format!("https://{}/", host)).unwrap();
+            metrics
+                .record_page(&page, Duration::from_millis(10), 10)
+                .await;
+        }
+
+        let domains: Vec<String> = metrics
+            .domain_reports(5)
+            .await
+            .into_iter()
+            .map(|report| report.domain)
+            .collect();
+        assert_eq!(domains, ["a.example", "b.example"]);
+    }
+}
@@ -0,0 +1,276 @@
+/// Upgrading stored session directories to the current output schema
+///
+/// Results and session summaries carry a `schema_version`; records written before
+/// it was introduced count as version 1. `migrate_dir` rewrites every JSON and JSONL
+/// file whose records are older than `SCHEMA_VERSION`, one upgrade step per version,
+/// so consumers can rely on the current field set. Files are replaced atomically
+/// and other JSON files (domain reports, the filename index) are left alone.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use super::durable::{FsyncPolicy, write_atomic};
+use super::reader::collect_result_files;
+
+/// Schema version written with results and session summaries
+pub const SCHEMA_VERSION: u32 = 11;
+
+/// Version of records written before `schema_version` existed
+pub(crate) fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Outcome of migrating a session directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Files that were rewritten
+    pub files_migrated: Vec<PathBuf>,
+    pub records_migrated: usize,
+    pub records_current: usize,
+    /// Files that could not be read, parsed or upgraded, with the reason
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+/// Upgrade every result and session summary file under `dir`
+pub fn migrate_dir(dir: &Path, policy: FsyncPolicy) -> Result<MigrationReport> {
+    let mut files = Vec::new();
+    collect_result_files(dir, &mut files)?;
+    files.sort();
+
+    let mut report = MigrationReport::default();
+    for path in files {
+        if let Err(e) = migrate_file(&path, policy, &mut report) {
+            report.errors.push((path, e.to_string()));
+        }
+    }
+    Ok(report)
+}
+
+fn migrate_file(path: &Path, policy: FsyncPolicy, report: &mut MigrationReport) -> Result<()> {
+    let content = std::fs::read_to_string(path)?;
+    let jsonl = path.extension().and_then(|s| s.to_str()) == Some("jsonl");
+
+    let mut migrated = 0;
+    let mut current = 0;
+    let mut count = |outcome: Outcome| match outcome {
+        Outcome::Upgraded => migrated += 1,
+        Outcome::Current => current += 1,
+        Outcome::Other => {}
+    };
+
+    let output = if jsonl {
+        let mut lines = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let mut record: Value = serde_json::from_str(line)?;
+            count(upgrade(&mut record)?);
+            lines.push(serde_json::to_string(&record)?);
+        }
+        lines.join("\n") + "\n"
+    } else {
+        let mut document: Value = serde_json::from_str(&content)?;
+        match &mut document {
+            Value::Array(records) => {
+                for record in records {
+                    count(upgrade(record)?);
+                }
+            }
+            record => count(upgrade(record)?),
+        }
+        serde_json::to_string_pretty(&document)?
+    };
+
+    report.records_migrated += migrated;
+    report.records_current += current;
+    if migrated > 0 {
+        write_atomic(path, output.as_bytes(), policy)?;
+        report.files_migrated.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Upgrade one stored result or session summary in place
+///
+/// Returns whether the record changed. Other JSON values are left untouched;
+/// records from a newer schema are an error.
+pub fn migrate_record(record: &mut Value) -> Result<bool> {
+    Ok(matches!(upgrade(record)?, Outcome::Upgraded))
+}
+
+enum Outcome {
+    Upgraded,
+    Current,
+    /// Not a result or session summary
+    Other,
+}
+
+fn upgrade(record: &mut Value) -> Result<Outcome> {
+    let Some(object) = record.as_object_mut() else {
+        return Ok(Outcome::Other);
+    };
+    let kind = if object.contains_key("url") && object.contains_key("metadata") {
+        RecordKind::Result
+    } else if object.contains_key("session_id") && object.contains_key("configuration") {
+        RecordKind::SessionSummary
+    } else {
+        return Ok(Outcome::Other);
+    };
+
+    let version = match object.get("schema_version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("Invalid schema_version: {}", version))?
+            as u32,
+        None => legacy_schema_version(),
+    };
+    if version > SCHEMA_VERSION {
+        return Err(anyhow::anyhow!(
+            "Schema version {} is newer than the supported version {}",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(Outcome::Current);
+    }
+
+    for from in version..SCHEMA_VERSION {
+        match (from, kind) {
+            (1, RecordKind::Result) => upgrade_result_v1(object),
+            (2, RecordKind::Result) => upgrade_result_v2(object),
+            (3, RecordKind::Result) => {
+                object.entry("raw_html_hash").or_insert(Value::Null);
+            }
+            (4, RecordKind::Result) => {
+                object
+                    .entry("pii_scrubbed")
+                    .or_insert(Value::Object(Map::new()));
+            }
+            (5, RecordKind::Result) => {
+                object.entry("keywords").or_insert(Value::Array(Vec::new()));
+            }
+            (6, RecordKind::Result) => upgrade_result_v6(object),
+            (7, RecordKind::Result) => upgrade_result_v7(object),
+            (8, RecordKind::Result) => upgrade_result_v8(object),
+            (9, RecordKind::Result) => {
+                object.entry("screenshot").or_insert(Value::Null);
+            }
+            (10, RecordKind::Result) => {
+                object.entry("sanitized_html").or_insert(Value::Null);
+            }
+            (1..=10, RecordKind::SessionSummary) => {}
+            _ => unreachable!("no upgrade step from schema version {}", from),
+        }
+    }
+    object.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    Ok(Outcome::Upgraded)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RecordKind {
+    Result,
+    SessionSummary,
+}
+
+/// Version 1 results omit fields added after their crate version; spell them out
+fn upgrade_result_v1(result: &mut Map<String, Value>) {
+    for (field, default) in [
+        ("relevance_score", Value::Null),
+        ("alternates", Value::Array(Vec::new())),
+        ("structured", Value::Null),
+        ("declared_language", Value::Null),
+        ("detected_language", Value::Null),
+    ] {
+        result.entry(field).or_insert(default);
+    }
+    if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.entry("user_agent").or_insert(Value::Null);
+        metadata
+            .entry("redirect_chain")
+            .or_insert(Value::Array(Vec::new()));
+    }
+}
+
+/// Version 3 adds caller metadata to results
+fn upgrade_result_v2(result: &mut Map<String, Value>) {
+    if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata
+            .entry("custom")
+            .or_insert(Value::Object(Map::new()));
+    }
+}
+
+fn upgrade_result_v6(result: &mut Map<String, Value>) {
+    if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.entry("content_encoding").or_insert(Value::Null);
+    }
+}
+
+/// Version 8 records the pagination chain a result belongs to
+fn upgrade_result_v7(result: &mut Map<String, Value>) {
+    if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.entry("pagination").or_insert(Value::Null);
+    }
+}
+
+/// Version 9 records the sniffed type of binary bodies
+fn upgrade_result_v8(result: &mut Map<String, Value>) {
+    if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.entry("sniffed_type").or_insert(Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StoredCrawlResult;
+
+    #[test]
+    fn test_legacy_directory_is_upgraded_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = r#"{"url":"https://a.example/","title":null,"content":"text","word_count":1,"language":null,"links_found":[],"metadata":{"status_code":200,"content_type":null,"content_length":null,"response_time_ms":5,"depth":0,"parent_url":null,"crawl_session_id":"s1"},"timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#;
+        std::fs::write(
+            dir.path().join("results.jsonl"),
+            format!("{}\n{}\n", legacy, legacy),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("session_summary_s1.json"),
+            r#"{"session_id":"s1","configuration":"{}"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("report.json"), r#"{"domain":"a.example"}"#).unwrap();
+
+        let report = migrate_dir(dir.path(), FsyncPolicy::Never).unwrap();
+        assert_eq!(report.records_migrated, 3);
+        assert_eq!(report.files_migrated.len(), 2);
+        assert!(report.errors.is_empty());
+
+        let line = std::fs::read_to_string(dir.path().join("results.jsonl")).unwrap();
+        let line = line.lines().next().unwrap();
+        let value: Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(
+            value["metadata"]["redirect_chain"],
+            Value::Array(Vec::new())
+        );
+        assert_eq!(value["metadata"]["custom"], Value::Object(Map::new()));
+        assert_eq!(value["raw_html_hash"], Value::Null);
+        assert_eq!(value["keywords"], Value::Array(Vec::new()));
+        assert_eq!(value["metadata"]["content_encoding"], Value::Null);
+        assert_eq!(value["metadata"]["pagination"], Value::Null);
+        assert_eq!(value["metadata"]["sniffed_type"], Value::Null);
+        assert_eq!(value["screenshot"], Value::Null);
+        assert_eq!(value["sanitized_html"], Value::Null);
+        let result: StoredCrawlResult = serde_json::from_str(line).unwrap();
+        assert_eq!(result.schema_version, SCHEMA_VERSION);
+
+        let again = migrate_dir(dir.path(), FsyncPolicy::Never).unwrap();
+        assert_eq!(again.records_migrated, 0);
+        assert_eq!(again.records_current, 3);
+
+        let mut future =
+            serde_json::json!({"session_id": "s2", "configuration": "", "schema_version": 99});
+        assert!(migrate_record(&mut future).is_err());
+    }
+}
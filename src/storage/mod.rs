@@ -1,11 +1,66 @@
 // Data persistence and analytics
 
+pub mod backend;
+pub mod content_hash;
+pub mod convert;
+pub mod coverage;
 pub mod data;
+pub mod durable;
+pub mod export;
+pub mod feed;
+pub mod manifest;
 pub mod metrics;
+pub mod migrate;
+pub mod naming;
+#[cfg(feature = "object-storage")]
+pub mod object_store;
+pub mod parquet;
+pub mod raw_html;
+pub mod reader;
+pub mod record_stream;
+pub mod retention;
+pub mod screenshots;
+#[cfg(feature = "search")]
+pub mod search;
+pub mod visited_store;
+pub mod writer;
 
 // Re-export storage components
+pub use backend::{ObjectStorageConfig, StorageBackend};
+pub use content_hash::{ContentHashStore, ContentHashes, hash_body, hash_text};
+pub use convert::{ConvertSummary, convert};
+pub use coverage::{FrontierDiff, SectionCoverage, SessionFrontier, section_of};
 pub use data::{
     CrawlAnalytics, CrawlMetadata, CrawlSessionSummary, DataStorage, OutputFormat,
     StoredCrawlResult,
 };
-pub use metrics::{CrawlerMetrics, MetricsSnapshot};
+pub use durable::{FsyncPolicy, RecoveryReport, recover_jsonl, recover_jsonl_tail};
+pub use export::{ExportProgress, ExportSummary};
+pub use feed::{
+    FEED_FILE, FEED_HASHES_FILE, FeedChange, FeedConfig, FeedEntry, FeedFormat, SessionFeed,
+    session_feed_path,
+};
+pub use manifest::{
+    IntegrityManifest, MANIFEST_PREFIX, ManifestConfig, ManifestEntry, ManifestVerification,
+    manifest_path,
+};
+pub use metrics::{CrawlerMetrics, DomainReport, MetricsSnapshot};
+pub use migrate::{MigrationReport, SCHEMA_VERSION, migrate_dir, migrate_record};
+pub use naming::{
+    CollisionPolicy, FileNamingTemplate, FilenameIndexEntry, path_safe_component, url_to_filename,
+};
+#[cfg(feature = "object-storage")]
+pub use object_store::ObjectStoreBackend;
+pub use parquet::{ParquetRecordWriter, read_parquet};
+pub use raw_html::{RawHtmlConfig, RawHtmlStore};
+pub use reader::{ResultFilter, ResultPage, SessionReader};
+pub use record_stream::{RecordStreamWriter, csv_header, encode_record};
+pub use retention::{GcReport, RetentionPolicy, collect_garbage};
+pub use screenshots::{
+    CaptureRoute, HeadlessChrome, ImageFormat, Screenshot, ScreenshotCapture, ScreenshotConfig,
+    ScreenshotRef, ScreenshotStore, Viewport,
+};
+#[cfg(feature = "search")]
+pub use search::{SearchHit, SearchIndex};
+pub use visited_store::{PersistentVisitedStore, VisitRecord};
+pub use writer::{OnStored, StorageWriter, StorageWriterConfig, WriterReport};
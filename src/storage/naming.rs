@@ -0,0 +1,461 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::content_hash::hash_body;
+
+/// Output file naming template
+///
+/// Templates are relative paths made of literal text and placeholders:
+/// - `{domain}` - host of the crawled URL (port joined with `_`)
+/// - `{date}` - UTC date of the result timestamp as `YYYY-MM-DD`
+/// - `{timestamp}` - result timestamp in seconds since the Unix epoch
+/// - `{hash}` - first 16 hex characters of the URL's SHA-256
+/// - `{url}` - path-safe, readable file stem derived from the URL (see `url_to_filename`)
+/// - `{session}` - crawl session id
+/// - `{seq}` / `{seq:06}` - per-storage sequence number, optionally zero padded
+/// - `{ext}` - extension of the configured output format
+///
+/// e.g. `{domain}/{date}/{hash}.json` or `{session}/{seq:06}.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileNamingTemplate {
+    /// Template string, `/` separates directories
+    pub template: String,
+    /// What to do when the generated path already exists
+    pub on_collision: CollisionPolicy,
+}
+
+/// Behaviour when a generated file name already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollisionPolicy {
+    /// Replace the existing file
+    Overwrite,
+    /// Append to the existing file (line-based formats only, JSON falls back to `Suffix`)
+    Append,
+    /// Write to `name_1.ext`, `name_2.ext`, ... instead
+    Suffix,
+}
+
+impl Default for FileNamingTemplate {
+    fn default() -> Self {
+        // Matches the built-in `crawl_{timestamp}_{url}` naming
+        Self {
+            template: "crawl_{timestamp}_{url}.{ext}".to_string(),
+            on_collision: CollisionPolicy::Suffix,
+        }
+    }
+}
+
+/// Maximum length of a single generated path component
+const MAX_COMPONENT_LEN: usize = 120;
+
+/// Maximum length of the readable part of a URL-derived file name
+const MAX_READABLE_PREFIX_LEN: usize = 80;
+
+/// Name of the index file mapping stored file names back to URLs
+pub const FILENAME_INDEX: &str = "filename_index.jsonl";
+
+/// One line of the filename index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilenameIndexEntry {
+    /// Path relative to the output directory, always `/`-separated
+    pub file: String,
+    pub url: String,
+    pub timestamp: SystemTime,
+}
+
+/// Values available to a naming template for one result
+pub struct NamingContext<'a> {
+    pub url: &'a str,
+    pub timestamp: &'a SystemTime,
+    pub session_id: &'a str,
+    pub sequence: u64,
+    pub extension: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Domain,
+    Date,
+    Timestamp,
+    Hash,
+    Url,
+    Session,
+    Sequence { width: usize },
+    Extension,
+}
+
+/// Parsed, validated form of a `FileNamingTemplate`
+#[derive(Debug, Clone)]
+pub struct FileNamer {
+    segments: Vec<Segment>,
+    on_collision: CollisionPolicy,
+}
+
+impl FileNamingTemplate {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the collision policy
+    pub fn with_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.on_collision = policy;
+        self
+    }
+
+    /// Parse the template, rejecting unknown placeholders and paths escaping the output dir
+    pub fn compile(&self) -> Result<FileNamer> {
+        if self.template.trim().is_empty() {
+            return Err(anyhow::anyhow!("Naming template cannot be empty"));
+        }
+        if self.template.starts_with('/') || self.template.split('/').any(|part| part == "..") {
+            return Err(anyhow::anyhow!(
+                "Naming template must be a relative path inside the output directory: {}",
+                self.template
+            ));
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = self.template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(anyhow::anyhow!(
+                                    "Unclosed '{{' in naming template: {}",
+                                    self.template
+                                ));
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_placeholder(&placeholder)?);
+                }
+                '}' => {
+                    return Err(anyhow::anyhow!(
+                        "Unmatched '}}' in naming template: {}",
+                        self.template
+                    ));
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(FileNamer {
+            segments,
+            on_collision: self.on_collision,
+        })
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Segment> {
+    let (name, spec) = match placeholder.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (placeholder, None),
+    };
+
+    let segment = match name {
+        "domain" => Segment::Domain,
+        "date" => Segment::Date,
+        "timestamp" => Segment::Timestamp,
+        "hash" => Segment::Hash,
+        "url" => Segment::Url,
+        "session" => Segment::Session,
+        "ext" => Segment::Extension,
+        "seq" => {
+            let width = match spec {
+                Some(spec) => spec.parse::<usize>().map_err(|_| {
+                    anyhow::anyhow!("Invalid sequence width in naming template: {}", spec)
+                })?,
+                None => 0,
+            };
+            return Ok(Segment::Sequence { width });
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown naming template placeholder: {{{}}}",
+                other
+            ));
+        }
+    };
+
+    if spec.is_some() {
+        return Err(anyhow::anyhow!(
+            "Placeholder {{{}}} does not take a format spec",
+            name
+        ));
+    }
+    Ok(segment)
+}
+
+impl FileNamer {
+    pub fn on_collision(&self) -> CollisionPolicy {
+        self.on_collision
+    }
+
+    /// Render the relative file path for one result
+    pub fn render(&self, ctx: &NamingContext<'_>) -> PathBuf {
+        let mut rendered = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Domain => rendered.push_str(&path_safe_component(&domain_of(ctx.url))),
+                Segment::Date => rendered.push_str(&format_date(ctx.timestamp)),
+                Segment::Timestamp => rendered.push_str(&epoch_secs(ctx.timestamp).to_string()),
+                Segment::Hash => rendered.push_str(&url_hash(ctx.url)),
+                Segment::Url => rendered.push_str(&url_to_filename(ctx.url)),
+                Segment::Session => rendered.push_str(&path_safe_component(ctx.session_id)),
+                Segment::Sequence { width } => {
+                    rendered.push_str(&format!("{:0width$}", ctx.sequence, width = *width))
+                }
+                Segment::Extension => rendered.push_str(ctx.extension),
+            }
+        }
+
+        rendered
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(path_safe_component)
+            .collect()
+    }
+}
+
+/// Find the first free `name_N.ext` sibling of `path`
+pub fn next_free_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    let mut counter = 1;
+    loop {
+        let file_name = match &extension {
+            Some(ext) => format!("{}_{}.{}", stem, counter, ext),
+            None => format!("{}_{}", stem, counter),
+        };
+        let candidate = path.with_file_name(file_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// First 16 hex characters of the URL's SHA-256, the same on every toolchain
+fn url_hash(url: &str) -> String {
+    let mut hash = hash_body(&[url]);
+    hash.truncate(16);
+    hash
+}
+
+fn domain_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str().map(|host| match u.port() {
+                Some(port) => format!("{}_{}", host, port),
+                None => host.to_string(),
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Make a single path component safe on both Windows and Unix
+///
+/// Replaces separators, reserved and control characters, strips trailing dots and
+/// spaces, caps the length and avoids reserved device names such as `CON` or `LPT1`.
+pub fn path_safe_component(value: &str) -> String {
+    let mut sanitized: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if sanitized.chars().count() > MAX_COMPONENT_LEN {
+        sanitized = sanitized.chars().take(MAX_COMPONENT_LEN).collect();
+    }
+    let sanitized = sanitized.trim_end_matches(['.', ' ']);
+
+    if sanitized.is_empty() {
+        return "_".to_string();
+    }
+
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if is_reserved_name(stem) {
+        format!("_{}", sanitized)
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Map a URL to a readable, collision-resistant file stem (without extension)
+///
+/// The stem is the host and path reduced to `[A-Za-z0-9._-]`, capped in length,
+/// followed by the 64-bit URL hash so distinct URLs never share a name.
+pub fn url_to_filename(url: &str) -> String {
+    let readable_source = match url::Url::parse(url) {
+        Ok(parsed) => format!(
+            "{}{}{}",
+            parsed.host_str().unwrap_or_default(),
+            parsed.path(),
+            parsed
+                .query()
+                .map(|q| format!("_{}", q))
+                .unwrap_or_default()
+        ),
+        Err(_) => url.to_string(),
+    };
+
+    let mut readable = String::new();
+    for c in readable_source.chars() {
+        let mapped = if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+            c
+        } else {
+            '_'
+        };
+        if !(mapped == '_' && readable.ends_with('_')) {
+            readable.push(mapped);
+        }
+    }
+    let mut readable: String = readable.chars().take(MAX_READABLE_PREFIX_LEN).collect();
+    readable = readable.trim_matches(['_', '.']).to_string();
+    if readable.is_empty() {
+        readable = "url".to_string();
+    }
+
+    path_safe_component(&format!("{}_{}", readable, url_hash(url)))
+}
+
+fn is_reserved_name(stem: &str) -> bool {
+    let upper = stem.trim_end().to_ascii_uppercase();
+    matches!(upper.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((upper.starts_with("COM") || upper.starts_with("LPT"))
+            && upper.len() == 4
+            && upper.as_bytes()[3].is_ascii_digit())
+}
+
+fn epoch_secs(timestamp: &SystemTime) -> u64 {
+    timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Format a timestamp as a UTC `YYYY-MM-DD` date
+fn format_date(timestamp: &SystemTime) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// UTC year, month and day of a timestamp
+pub(crate) fn civil_date(timestamp: &SystemTime) -> (i64, i64, i64) {
+    let days = (epoch_secs(timestamp) / 86_400) as i64;
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn context<'a>(timestamp: &'a SystemTime, sequence: u64) -> NamingContext<'a> {
+        NamingContext {
+            url: "https://example.com:8080/articles/1",
+            timestamp,
+            session_id: "session-1",
+            sequence,
+            extension: "jsonl",
+        }
+    }
+
+    #[test]
+    fn test_render_template_placeholders() {
+        // 2024-03-01T00:00:00Z
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_251_200);
+        let namer = FileNamingTemplate::new("{domain}/{date}/{session}/{seq:06}.{ext}")
+            .compile()
+            .unwrap();
+
+        let path = namer.render(&context(&timestamp, 42));
+        assert_eq!(
+            path,
+            PathBuf::from("example.com_8080/2024-03-01/session-1/000042.jsonl")
+        );
+    }
+
+    #[test]
+    fn test_url_to_filename_is_path_safe() {
+        let long_url = format!("https://example.com/{}?q=a:b|c", "segment/".repeat(40));
+        let name = url_to_filename(&long_url);
+        assert!(name.starts_with("example.com_segment_"));
+        assert!(name.len() <= MAX_READABLE_PREFIX_LEN + 17);
+        assert!(!name.contains(['/', '\\', ':', '|', '?']));
+        assert_ne!(name, url_to_filename("https://example.com/other"));
+
+        // Names are kept on disk and in the index, so the hash must not drift
+        assert_eq!(url_hash("https://example.com/"), "0f115db062b7c0dd");
+        assert_eq!(
+            url_to_filename("https://example.com/"),
+            "example.com_0f115db062b7c0dd"
+        );
+
+        assert_eq!(path_safe_component("CON.json"), "_CON.json");
+        assert_eq!(path_safe_component("lpt1"), "_lpt1");
+        assert_eq!(path_safe_component("name. "), "name");
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        assert!(FileNamingTemplate::new("{unknown}.json").compile().is_err());
+        assert!(FileNamingTemplate::new("../{hash}.json").compile().is_err());
+        assert!(FileNamingTemplate::new("{seq:abc}.json").compile().is_err());
+        assert!(FileNamingTemplate::new("{hash:4}.json").compile().is_err());
+        assert!(
+            FileNamingTemplate::new("{domain}/}.json")
+                .compile()
+                .is_err()
+        );
+        let unclosed = FileNamingTemplate::new("{domain}/{hash")
+            .compile()
+            .unwrap_err();
+        assert!(unclosed.to_string().contains("Unclosed '{'"));
+    }
+}
@@ -0,0 +1,136 @@
+/// Parquet encoding of stored results
+///
+/// A Parquet file has the columns of the CSV layout (see `record_stream`), each an
+/// optional UTF-8 string: scalar fields as their text, lists, maps and the
+/// timestamp JSON-encoded, and missing values as nulls. Results are buffered into
+/// row groups of `ROW_GROUP_RECORDS`, so a session is written without being held
+/// in memory, and read back one row at a time.
+use anyhow::Result;
+use parquet::data_type::{ByteArray, ByteArrayType};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::record::Field;
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::data::StoredCrawlResult;
+use super::record_stream::{CSV_LAYOUT, csv_row, record_from_cells};
+
+/// Results per row group
+const ROW_GROUP_RECORDS: usize = 1024;
+
+/// Writes results as Parquet rows, a row group at a time
+pub struct ParquetRecordWriter<W: Write + Send> {
+    writer: SerializedFileWriter<W>,
+    /// Cells of the buffered results, by column
+    columns: Vec<Vec<Option<String>>>,
+    records: usize,
+}
+
+impl<W: Write + Send> ParquetRecordWriter<W> {
+    pub fn new(writer: W) -> Result<Self> {
+        let fields: String = CSV_LAYOUT
+            .iter()
+            .map(|(name, _, _)| format!("OPTIONAL BYTE_ARRAY {} (UTF8);", name))
+            .collect();
+        let schema = parse_message_type(&format!("message crawl_result {{ {} }}", fields))?;
+        let properties = WriterProperties::builder().build();
+        Ok(Self {
+            writer: SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?,
+            columns: vec![Vec::new(); CSV_LAYOUT.len()],
+            records: 0,
+        })
+    }
+
+    pub fn write(&mut self, result: &StoredCrawlResult) -> Result<()> {
+        for (column, cell) in self.columns.iter_mut().zip(csv_row(result)?) {
+            column.push((!cell.is_empty()).then_some(cell));
+        }
+        self.records += 1;
+        if self.columns[0].len() >= ROW_GROUP_RECORDS {
+            self.write_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// Records written so far
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Write the buffered rows and the footer and hand back the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.write_row_group()?;
+        Ok(self.writer.into_inner()?)
+    }
+
+    fn write_row_group(&mut self) -> Result<()> {
+        if self.columns[0].is_empty() {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        for cells in &mut self.columns {
+            let Some(mut column) = row_group.next_column()? else {
+                break;
+            };
+            let levels: Vec<i16> = cells.iter().map(|cell| i16::from(cell.is_some())).collect();
+            let values: Vec<ByteArray> = cells
+                .drain(..)
+                .flatten()
+                .map(|cell| ByteArray::from(cell.into_bytes()))
+                .collect();
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(&values, Some(&levels), None)?;
+            column.close()?;
+        }
+        row_group.close()?;
+        Ok(())
+    }
+}
+
+/// Results of a Parquet file written by `ParquetRecordWriter`, one row at a time;
+/// `None` for rows that are not stored results
+pub fn read_parquet(
+    path: &Path,
+) -> Result<impl Iterator<Item = Result<Option<StoredCrawlResult>>>> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let names: Vec<String> = reader
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+    let positions = CSV_LAYOUT
+        .iter()
+        .map(|(name, _, _)| {
+            names
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "{} is not a converted Parquet file (no '{}' column)",
+                        path.display(),
+                        name
+                    )
+                })
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    Ok(reader.into_iter().map(move |row| {
+        let row = row?;
+        let fields: Vec<&Field> = row.get_column_iter().map(|(_, field)| field).collect();
+        let cells = positions
+            .iter()
+            .map(|position| match fields.get(*position) {
+                Some(Field::Str(text)) => Some(text.as_str()),
+                _ => None,
+            });
+        Ok(record_from_cells(cells))
+    }))
+}
@@ -0,0 +1,142 @@
+/// Content-addressed store for raw response bodies
+///
+/// Bodies are zstd-compressed and written once per SHA-256, under
+/// `<dir>/<first two hex digits>/<hash>.html.zst`, separately from the extracted
+/// text. Results reference their body by hash (`StoredCrawlResult::raw_html_hash`),
+/// so pages can be re-extracted after an extraction fix without re-crawling.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::content_hash::hash_body;
+use super::durable::{FsyncPolicy, touch, write_atomic};
+
+/// Directory under the output directory used when `RawHtmlConfig::dir` is unset
+pub const DEFAULT_RAW_HTML_DIR: &str = "raw_html";
+
+/// Retention of raw HTML bodies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawHtmlConfig {
+    /// Blob directory; defaults to `raw_html` in the output directory
+    pub dir: Option<PathBuf>,
+    /// zstd level, 1 (fastest) to 22 (smallest)
+    pub compression_level: i32,
+}
+
+impl Default for RawHtmlConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            compression_level: 3,
+        }
+    }
+}
+
+/// Compressed raw bodies keyed by their SHA-256
+#[derive(Clone)]
+pub struct RawHtmlStore {
+    dir: PathBuf,
+    compression_level: i32,
+    fsync_policy: FsyncPolicy,
+}
+
+impl RawHtmlStore {
+    pub fn new(dir: &Path, compression_level: i32, fsync_policy: FsyncPolicy) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            compression_level,
+            fsync_policy,
+        }
+    }
+
+    /// Path of the blob for `hash`
+    pub fn path_for(&self, hash: &str) -> PathBuf {
+        self.dir
+            .join(&hash[..2.min(hash.len())])
+            .join(format!("{}.html.zst", hash))
+    }
+
+    /// Store a body given as received chunks, returning its hash
+    ///
+    /// A body that is already stored is not written again; its modification time
+    /// is renewed instead.
+    pub fn put<B: AsRef<[u8]>>(&self, chunks: &[B]) -> Result<String> {
+        let hash = hash_body(chunks);
+        let path = self.path_for(&hash);
+        if path.exists() {
+            touch(&path)?;
+            return Ok(hash);
+        }
+
+        let mut encoder = zstd::Encoder::new(Vec::new(), self.compression_level)?;
+        for chunk in chunks {
+            encoder.write_all(chunk.as_ref())?;
+        }
+        let compressed = encoder.finish()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, &compressed, self.fsync_policy)?;
+        Ok(hash)
+    }
+
+    /// Decompressed body stored under `hash`, checked against the hash
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        if hash.len() != 64 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("Invalid raw HTML hash '{}'", hash));
+        }
+        let compressed = std::fs::read(self.path_for(hash))?;
+        let body = zstd::decode_all(compressed.as_slice())?;
+        if hash_body(&[&body]) != hash {
+            return Err(anyhow::anyhow!("Raw HTML blob {} is corrupt", hash));
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bodies_are_stored_once_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RawHtmlStore::new(dir.path(), 3, FsyncPolicy::Never);
+
+        let hash = store.put(&["<html><body>", "hello</body></html>"]).unwrap();
+        assert_eq!(
+            hash,
+            store.put(&["<html><body>hello</body></html>"]).unwrap()
+        );
+        assert_eq!(
+            store.get(&hash).unwrap(),
+            b"<html><body>hello</body></html>".to_vec()
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // Reusing a blob renews its age, so retention keeps it
+        let week_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7 * 86_400);
+        std::fs::File::options()
+            .write(true)
+            .open(store.path_for(&hash))
+            .unwrap()
+            .set_modified(week_ago)
+            .unwrap();
+        store.put(&["<html><body>hello</body></html>"]).unwrap();
+        let modified = std::fs::metadata(store.path_for(&hash))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert!(modified > week_ago + std::time::Duration::from_secs(86_400));
+
+        std::fs::write(
+            store.path_for(&hash),
+            zstd::encode_all(&b"edited"[..], 3).unwrap(),
+        )
+        .unwrap();
+        assert!(store.get(&hash).is_err());
+        assert!(store.get("../escape").is_err());
+    }
+}
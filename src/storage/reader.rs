@@ -0,0 +1,279 @@
+/// Paged, lazy reading of a stored session
+///
+/// Results are read one file (and, for JSONL, one line) at a time, so a UI can page
+/// through a large session without loading it into memory. Files are visited in
+/// name order, including subdirectories created by naming templates and rotated
+/// files. CSV output has no session id column and is not read.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+
+use super::data::{DataStorage, StoredCrawlResult};
+use super::naming::FILENAME_INDEX;
+use crate::core::host_key;
+use crate::logging::EVENTS_FILE;
+
+/// Which stored results to return; `None` matches everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultFilter {
+    /// Host of the result URL, subdomains included
+    pub domain: Option<String>,
+    /// Detected language, compared case-insensitively
+    pub language: Option<String>,
+    /// `Some(true)` keeps only 2xx responses, `Some(false)` only the rest
+    pub success: Option<bool>,
+}
+
+impl ResultFilter {
+    pub fn matches(&self, result: &StoredCrawlResult) -> bool {
+        if let Some(domain) = &self.domain {
+            let domain = host_key(domain);
+            let host = url::Url::parse(&result.url)
+                .ok()
+                .and_then(|url| url.host_str().map(host_key));
+            let Some(host) = host else {
+                return false;
+            };
+            if host != domain && !host.ends_with(&format!(".{}", domain)) {
+                return false;
+            }
+        }
+        if let Some(language) = &self.language
+            && !result
+                .language
+                .as_deref()
+                .is_some_and(|lang| lang.eq_ignore_ascii_case(language))
+        {
+            return false;
+        }
+        if let Some(success) = self.success {
+            let succeeded = result
+                .metadata
+                .status_code
+                .is_some_and(|code| (200..300).contains(&code));
+            if succeeded != success {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// One page of stored results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultPage {
+    pub results: Vec<StoredCrawlResult>,
+    pub offset: usize,
+    /// Offset of the next page, `None` on the last page
+    pub next_offset: Option<usize>,
+}
+
+enum FileRecords {
+    Lines(Lines<BufReader<File>>),
+    Parsed(std::vec::IntoIter<StoredCrawlResult>),
+}
+
+/// Iterator over the stored results of one session
+pub struct SessionReader {
+    session_id: String,
+    filter: ResultFilter,
+    files: VecDeque<PathBuf>,
+    current: Option<FileRecords>,
+}
+
+impl SessionReader {
+    fn open_next_file(&mut self) -> Result<bool> {
+        let Some(path) = self.files.pop_front() else {
+            return Ok(false);
+        };
+        let records = if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+            FileRecords::Lines(BufReader::new(File::open(&path)?).lines())
+        } else {
+            // Single result or array; unrelated JSON files parse as neither
+            let content = std::fs::read_to_string(&path)?;
+            let results = if let Ok(result) = serde_json::from_str::<StoredCrawlResult>(&content) {
+                vec![result]
+            } else {
+                serde_json::from_str::<Vec<StoredCrawlResult>>(&content).unwrap_or_default()
+            };
+            FileRecords::Parsed(results.into_iter())
+        };
+        self.current = Some(records);
+        Ok(true)
+    }
+
+    fn next_record(&mut self) -> Option<Result<StoredCrawlResult>> {
+        loop {
+            match &mut self.current {
+                Some(FileRecords::Lines(lines)) => match lines.next() {
+                    Some(Ok(line)) => {
+                        // Cheap check before parsing lines from other sessions
+                        if !line.contains(self.session_id.as_str()) {
+                            continue;
+                        }
+                        if let Ok(result) = serde_json::from_str::<StoredCrawlResult>(&line) {
+                            return Some(Ok(result));
+                        }
+                    }
+                    Some(Err(e)) => {
+                        self.current = None;
+                        return Some(Err(e.into()));
+                    }
+                    None => self.current = None,
+                },
+                Some(FileRecords::Parsed(results)) => match results.next() {
+                    Some(result) => return Some(Ok(result)),
+                    None => self.current = None,
+                },
+                None => match self.open_next_file() {
+                    Ok(true) => {}
+                    Ok(false) => return None,
+                    Err(e) => return Some(Err(e)),
+                },
+            }
+        }
+    }
+}
+
+impl Iterator for SessionReader {
+    type Item = Result<StoredCrawlResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_record()? {
+                Ok(result)
+                    if result.metadata.crawl_session_id == self.session_id
+                        && self.filter.matches(&result) =>
+                {
+                    return Some(Ok(result));
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl DataStorage {
+    /// Lazily read the stored results of `session_id` that match `filter`
+    pub fn read_session(&self, session_id: &str, filter: ResultFilter) -> Result<SessionReader> {
+        let mut files = Vec::new();
+        collect_result_files(self.output_dir(), &mut files)?;
+        files.sort();
+        Ok(SessionReader {
+            session_id: session_id.to_string(),
+            filter,
+            files: files.into(),
+            current: None,
+        })
+    }
+
+    /// Read one page of a session's results
+    ///
+    /// Pages are found by skipping `offset` matching results, so later pages cost
+    /// more to read, but memory use stays at one page.
+    pub async fn read_session_page(
+        &self,
+        session_id: &str,
+        filter: ResultFilter,
+        offset: usize,
+        limit: usize,
+    ) -> Result<ResultPage> {
+        let reader = self.read_session(session_id, filter)?;
+        tokio::task::spawn_blocking(move || {
+            let mut results = reader
+                .skip(offset)
+                .take(limit + 1)
+                .collect::<Result<Vec<_>>>()?;
+            let next_offset = (results.len() > limit).then(|| {
+                results.truncate(limit);
+                offset + limit
+            });
+            Ok(ResultPage {
+                results,
+                offset,
+                next_offset,
+            })
+        })
+        .await?
+    }
+}
+
+pub(super) fn collect_result_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_result_files(&path, files)?;
+            continue;
+        }
+        if matches!(
+            path.file_name().and_then(|s| s.to_str()),
+            Some(FILENAME_INDEX | EVENTS_FILE)
+        ) {
+            continue;
+        }
+        if matches!(
+            path.extension().and_then(|s| s.to_str()),
+            Some("json" | "jsonl")
+        ) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::OutputFormat;
+
+    fn result(url: &str, session: &str, status: u16) -> StoredCrawlResult {
+        StoredCrawlResult::for_test(url)
+            .with_language("Eng")
+            .with_session(session)
+            .with_status(status)
+    }
+
+    #[tokio::test]
+    async fn test_read_session_pages_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = DataStorage::new(dir.path(), OutputFormat::Jsonl).unwrap();
+        storage
+            .store_batch(&[
+                result("https://a.example/1", "s1", 200),
+                result("https://b.example/", "s2", 200),
+                result("https://www.a.example/2", "s1", 404),
+                result("https://a.example/3", "s1", 200),
+            ])
+            .await
+            .unwrap();
+
+        let page = storage
+            .read_session_page("s1", ResultFilter::default(), 0, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.next_offset, Some(2));
+        let page = storage
+            .read_session_page("s1", ResultFilter::default(), 2, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.next_offset, None);
+
+        let filter = ResultFilter {
+            domain: Some("a.example".to_string()),
+            success: Some(true),
+            ..ResultFilter::default()
+        };
+        let urls: Vec<String> = storage
+            .read_session("s1", filter)
+            .unwrap()
+            .map(|result| result.unwrap().url)
+            .collect();
+        assert_eq!(urls, vec!["https://a.example/1", "https://a.example/3"]);
+    }
+}
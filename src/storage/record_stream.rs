@@ -0,0 +1,261 @@
+/// Streaming JSONL and CSV encoding of stored results
+///
+/// Results are encoded one at a time into any `Write`, so writing a session holds
+/// no more than one record and the writer's buffer in memory. CSV cells are quoted
+/// and escaped by the `csv` crate: commas, quotes and line breaks in URLs, titles
+/// or error messages stay inside their cell. CSV has one column per scalar field
+/// (`session_id` for the crawl session) and JSON-encoded cells for lists, maps and
+/// the timestamp; a missing value is an empty cell. Every CSV file starts with the
+/// header row, also each new file a naming template rotates to.
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value};
+use std::io::Write;
+
+use super::data::{OutputFormat, StoredCrawlResult};
+
+#[derive(Clone, Copy)]
+pub(super) enum Cell {
+    /// Written as is
+    Text,
+    /// A JSON number or boolean
+    Number,
+    /// A JSON-encoded list, map or timestamp
+    Json,
+}
+
+/// CSV columns: header, JSON path within a stored result, cell encoding
+pub(super) const CSV_LAYOUT: &[(&str, &[&str], Cell)] = &[
+    ("schema_version", &["schema_version"], Cell::Number),
+    ("url", &["url"], Cell::Text),
+    ("title", &["title"], Cell::Text),
+    ("content", &["content"], Cell::Text),
+    ("word_count", &["word_count"], Cell::Number),
+    ("language", &["language"], Cell::Text),
+    ("links_found", &["links_found"], Cell::Json),
+    ("relevance_score", &["relevance_score"], Cell::Number),
+    ("alternates", &["alternates"], Cell::Json),
+    ("structured", &["structured"], Cell::Json),
+    ("declared_language", &["declared_language"], Cell::Text),
+    ("detected_language", &["detected_language"], Cell::Text),
+    ("raw_html_hash", &["raw_html_hash"], Cell::Text),
+    ("screenshot", &["screenshot"], Cell::Json),
+    ("sanitized_html", &["sanitized_html"], Cell::Text),
+    ("pii_scrubbed", &["pii_scrubbed"], Cell::Json),
+    ("keywords", &["keywords"], Cell::Json),
+    ("status_code", &["metadata", "status_code"], Cell::Number),
+    ("content_type", &["metadata", "content_type"], Cell::Text),
+    (
+        "content_length",
+        &["metadata", "content_length"],
+        Cell::Number,
+    ),
+    (
+        "content_encoding",
+        &["metadata", "content_encoding"],
+        Cell::Text,
+    ),
+    (
+        "response_time_ms",
+        &["metadata", "response_time_ms"],
+        Cell::Number,
+    ),
+    ("depth", &["metadata", "depth"], Cell::Number),
+    ("parent_url", &["metadata", "parent_url"], Cell::Text),
+    ("session_id", &["metadata", "crawl_session_id"], Cell::Text),
+    ("user_agent", &["metadata", "user_agent"], Cell::Text),
+    (
+        "redirect_chain",
+        &["metadata", "redirect_chain"],
+        Cell::Json,
+    ),
+    ("custom", &["metadata", "custom"], Cell::Json),
+    ("pagination", &["metadata", "pagination"], Cell::Json),
+    ("sniffed_type", &["metadata", "sniffed_type"], Cell::Text),
+    ("timestamp", &["timestamp"], Cell::Json),
+];
+
+/// Column names of the CSV header
+pub fn csv_header() -> impl Iterator<Item = &'static str> {
+    CSV_LAYOUT.iter().map(|(name, _, _)| *name)
+}
+
+/// Cells of a result in `CSV_LAYOUT` order
+pub(super) fn csv_row(result: &StoredCrawlResult) -> Result<Vec<String>> {
+    let value = serde_json::to_value(result)?;
+    Ok(CSV_LAYOUT
+        .iter()
+        .map(|(_, path, cell)| {
+            let value = path.iter().try_fold(&value, |value, key| value.get(key));
+            match (value, cell) {
+                (None | Some(Value::Null), _) => String::new(),
+                (Some(Value::String(text)), Cell::Text) => text.clone(),
+                (Some(value), _) => value.to_string(),
+            }
+        })
+        .collect())
+}
+
+/// A result from its cells in `CSV_LAYOUT` order; a missing or empty cell is a
+/// missing value. `None` if the cells do not make a stored result.
+pub(super) fn record_from_cells<'a>(
+    cells: impl IntoIterator<Item = Option<&'a str>>,
+) -> Option<StoredCrawlResult> {
+    let mut record = Map::new();
+    for ((_, path, cell), text) in CSV_LAYOUT.iter().zip(cells) {
+        let value = match (text, cell) {
+            (None | Some(""), _) => Value::Null,
+            (Some(text), Cell::Text) => Value::String(text.to_string()),
+            (Some(text), Cell::Number | Cell::Json) => serde_json::from_str(text).ok()?,
+        };
+        let (key, parents) = path.split_last()?;
+        let mut object = &mut record;
+        for parent in parents {
+            object = object
+                .entry(*parent)
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()?;
+        }
+        object.insert(key.to_string(), value);
+    }
+    serde_json::from_value(Value::Object(record)).ok()
+}
+
+enum Encoder<W: Write> {
+    Jsonl(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+/// Writes results as JSONL lines or CSV rows as they come
+pub struct RecordStreamWriter<W: Write> {
+    encoder: Encoder<W>,
+    records: usize,
+}
+
+impl<W: Write> RecordStreamWriter<W> {
+    /// Writer of `format` records, starting with the CSV header when `header` is
+    /// set; only JSONL and CSV are record formats
+    pub fn new(format: OutputFormat, writer: W, header: bool) -> Result<Self> {
+        let encoder = match format {
+            OutputFormat::Jsonl => Encoder::Jsonl(writer),
+            OutputFormat::Csv => {
+                let mut csv_writer = csv::Writer::from_writer(writer);
+                if header {
+                    csv_writer.write_record(csv_header())?;
+                }
+                Encoder::Csv(Box::new(csv_writer))
+            }
+            other => return Err(anyhow!("{:?} is not a record format", other)),
+        };
+        Ok(Self {
+            encoder,
+            records: 0,
+        })
+    }
+
+    pub fn write(&mut self, result: &StoredCrawlResult) -> Result<()> {
+        match &mut self.encoder {
+            Encoder::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, result)?;
+                writer.write_all(b"\n")?;
+            }
+            Encoder::Csv(writer) => writer.write_record(csv_row(result)?)?,
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Records written so far
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Flush what is buffered and hand back the underlying writer
+    pub fn finish(self) -> Result<W> {
+        let mut writer = match self.encoder {
+            Encoder::Jsonl(writer) => writer,
+            Encoder::Csv(writer) => (*writer).into_inner().map_err(|e| e.into_error())?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+/// One result encoded as a complete, newline-terminated JSONL line or CSV row,
+/// preceded by the CSV header when `header` is set
+pub fn encode_record(
+    format: OutputFormat,
+    result: &StoredCrawlResult,
+    header: bool,
+) -> Result<String> {
+    let mut writer = RecordStreamWriter::new(format, Vec::new(), header)?;
+    writer.write(result)?;
+    Ok(String::from_utf8(writer.finish()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{CollisionPolicy, DataStorage, FileNamingTemplate};
+
+    fn stored(url: &str, title: &str, session: &str) -> StoredCrawlResult {
+        StoredCrawlResult {
+            links_found: vec!["https://a.example/x,y".to_string()],
+            ..StoredCrawlResult::for_test(url)
+                .with_title(title)
+                .with_content("first line\nsecond, \"quoted\" line")
+                .with_session(session)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_rows_are_escaped_with_a_header_per_file() {
+        let tricky = stored(
+            "https://a.example/search?q=a,b&x=\"y\"",
+            "Comma, \"quote\"",
+            "s1",
+        );
+        let mut writer = RecordStreamWriter::new(OutputFormat::Csv, Vec::new(), true).unwrap();
+        writer.write(&tricky).unwrap();
+        writer.write(&tricky).unwrap();
+        assert_eq!(writer.records(), 2);
+        let bytes = writer.finish().unwrap();
+        let mut reader = csv::Reader::from_reader(bytes.as_slice());
+        assert!(reader.headers().unwrap().iter().eq(csv_header()));
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][1], tricky.url);
+        assert_eq!(&rows[0][2], "Comma, \"quote\"");
+        assert_eq!(&rows[0][3], tricky.content.as_deref().unwrap());
+
+        let line = encode_record(OutputFormat::Jsonl, &tricky, true).unwrap();
+        assert_eq!(line.lines().count(), 1);
+        assert!(RecordStreamWriter::new(OutputFormat::Json, Vec::new(), true).is_err());
+
+        // Appending to one file per session writes the header once per file
+        let dir = tempfile::tempdir().unwrap();
+        let naming = FileNamingTemplate::new("{session}/results.{ext}")
+            .with_collision_policy(CollisionPolicy::Append);
+        let storage = DataStorage::new(dir.path(), OutputFormat::Csv)
+            .unwrap()
+            .with_naming(&naming)
+            .unwrap();
+        for (url, session) in [
+            ("https://a.example/1", "s1"),
+            ("https://a.example/2", "s1"),
+            ("https://a.example/3", "s2"),
+        ] {
+            storage
+                .store_result(&stored(url, "Title, with comma", session))
+                .await
+                .unwrap();
+        }
+        for (session, rows) in [("s1", 2), ("s2", 1)] {
+            let path = dir.path().join(session).join("results.csv");
+            let mut reader = csv::Reader::from_path(&path).unwrap();
+            assert!(reader.headers().unwrap().iter().eq(csv_header()));
+            let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+            assert_eq!(records.len(), rows, "{}", path.display());
+            assert!(records.iter().all(|row| &row[2] == "Title, with comma"));
+        }
+    }
+}
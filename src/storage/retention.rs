@@ -0,0 +1,296 @@
+/// Data retention for crawl output directories
+///
+/// A `RetentionPolicy` bounds an output directory by file age and total size.
+/// Garbage collection removes expired files first, then the oldest remaining files
+/// until the directory fits the size budget, and finally drops filename-index
+/// entries that point at removed files.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+
+use super::naming::{FILENAME_INDEX, FilenameIndexEntry};
+
+/// Age and size limits for a storage directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Files last modified longer ago than this are removed
+    pub max_age_secs: Option<u64>,
+    /// Oldest files are removed until the directory is at most this size
+    pub max_total_bytes: Option<u64>,
+    /// File names that are never removed
+    pub protected_files: Vec<String>,
+    /// Files, or directories with everything below them, that are never removed;
+    /// sessions add the stores they share with later sessions when those sit in
+    /// the output directory
+    #[serde(default)]
+    pub protected_paths: Vec<PathBuf>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_secs: Some(30 * 24 * 60 * 60),         // 30 days
+            max_total_bytes: Some(5 * 1024 * 1024 * 1024), // 5 GiB
+            protected_files: Vec::new(),
+            protected_paths: Vec::new(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age_secs.map(Duration::from_secs)
+    }
+}
+
+/// Outcome of a garbage collection run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    pub files_removed: usize,
+    pub bytes_freed: u64,
+    pub files_kept: usize,
+    pub bytes_kept: u64,
+    /// Files that could not be removed, with the reason
+    pub errors: Vec<String>,
+}
+
+struct StoredFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Apply `policy` to every file below `root`
+pub async fn collect_garbage(root: &Path, policy: &RetentionPolicy) -> Result<GcReport> {
+    let mut report = GcReport::default();
+    if !root.exists() {
+        return Ok(report);
+    }
+    // Canonical paths, so protected paths match however they were spelled
+    let root = fs::canonicalize(root).await?;
+
+    let mut protected_paths = Vec::new();
+    for path in &policy.protected_paths {
+        if let Ok(path) = fs::canonicalize(path).await {
+            protected_paths.push(path);
+        }
+    }
+    let mut files = list_files(&root).await?;
+    files.retain(|file| {
+        let name = file
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        name != FILENAME_INDEX
+            && !policy.protected_files.iter().any(|p| p == name)
+            && !protected_paths
+                .iter()
+                .any(|protected| file.path.starts_with(protected))
+    });
+    // Oldest first
+    files.sort_by_key(|file| file.modified);
+
+    let now = SystemTime::now();
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    let mut removed: HashSet<PathBuf> = HashSet::new();
+
+    for file in &files {
+        let age = now.duration_since(file.modified).unwrap_or_default();
+        let expired = policy.max_age().is_some_and(|max_age| age > max_age);
+        let over_budget = policy.max_total_bytes.is_some_and(|max| total > max);
+        if !expired && !over_budget {
+            continue;
+        }
+
+        match fs::remove_file(&file.path).await {
+            Ok(()) => {
+                total = total.saturating_sub(file.size);
+                report.files_removed += 1;
+                report.bytes_freed += file.size;
+                removed.insert(file.path.clone());
+            }
+            Err(e) => report
+                .errors
+                .push(format!("{}: {}", file.path.display(), e)),
+        }
+    }
+
+    report.files_kept = files.len() - report.files_removed;
+    report.bytes_kept = total;
+
+    if !removed.is_empty() {
+        prune_filename_index(&root, &removed).await?;
+        remove_empty_dirs(&root).await;
+    }
+
+    Ok(report)
+}
+
+async fn list_files(root: &Path) -> Result<Vec<StoredFile>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                files.push(StoredFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Drop index entries whose files were removed
+async fn prune_filename_index(root: &Path, removed: &HashSet<PathBuf>) -> Result<()> {
+    let index_path = root.join(FILENAME_INDEX);
+    if !index_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&index_path).await?;
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(
+            |line| match serde_json::from_str::<FilenameIndexEntry>(line) {
+                Ok(entry) => {
+                    !removed.contains(&root.join(entry.file.split('/').collect::<PathBuf>()))
+                }
+                Err(_) => true,
+            },
+        )
+        .collect();
+
+    let mut rewritten = kept.join("\n");
+    if !rewritten.is_empty() {
+        rewritten.push('\n');
+    }
+    fs::write(&index_path, rewritten).await?;
+    Ok(())
+}
+
+/// Remove subdirectories left empty by garbage collection (never `root` itself)
+async fn remove_empty_dirs(root: &Path) {
+    let mut dirs = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(mut entries) = fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_type().await.is_ok_and(|kind| kind.is_dir()) {
+                dirs.push(entry.path());
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    // Deepest directories first; remove_dir fails harmlessly on non-empty ones
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in dirs {
+        let _ = fs::remove_dir(&dir).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Write `path` below `root` with `size` bytes, last modified `age` ago
+    fn write_file(root: &Path, path: &str, size: usize, age: Duration) -> PathBuf {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, vec![b'x'; size]).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+        path
+    }
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[tokio::test]
+    async fn test_expired_files_are_removed_except_protected_ones() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let old = write_file(root, "2024/old.json", 10, 10 * DAY);
+        let recent = write_file(root, "recent.json", 10, DAY);
+        let summary = write_file(root, "summary.json", 10, 10 * DAY);
+        let visited = write_file(root, "visited.jsonl", 10, 10 * DAY);
+        let blob = write_file(root, "raw_html/ab/abcdef.html.zst", 10, 10 * DAY);
+        let entry = |file: &str| {
+            serde_json::to_string(&FilenameIndexEntry {
+                file: file.to_string(),
+                url: format!("https://example.com/{}", file),
+                timestamp: SystemTime::now(),
+            })
+            .unwrap()
+        };
+        let recent_entry = entry("recent.json");
+        std::fs::write(
+            root.join(FILENAME_INDEX),
+            format!("{}\n{}\n", entry("2024/old.json"), recent_entry),
+        )
+        .unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_secs: Some(5 * DAY.as_secs()),
+            max_total_bytes: None,
+            protected_files: vec!["summary.json".to_string()],
+            protected_paths: vec![visited.clone(), root.join("raw_html")],
+        };
+        let report = collect_garbage(root, &policy).await.unwrap();
+
+        assert_eq!((report.files_removed, report.bytes_freed), (1, 10));
+        // Protected files are not counted against the policy at all
+        assert_eq!(report.files_kept, 1);
+        assert!(!old.exists());
+        // The directory it left empty is gone too
+        assert!(!root.join("2024").exists());
+        for kept in [&recent, &summary, &visited, &blob] {
+            assert!(kept.exists(), "{}", kept.display());
+        }
+        let index = std::fs::read_to_string(root.join(FILENAME_INDEX)).unwrap();
+        assert_eq!(index, format!("{}\n", recent_entry));
+    }
+
+    #[tokio::test]
+    async fn test_oldest_files_go_first_until_the_size_budget_fits() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let oldest = write_file(root, "a.json", 100, 3 * DAY);
+        let older = write_file(root, "b.json", 100, 2 * DAY);
+        let newest = write_file(root, "c.json", 100, DAY);
+        let store = write_file(root, "content_hashes.jsonl", 100, 4 * DAY);
+
+        let policy = RetentionPolicy {
+            max_age_secs: None,
+            max_total_bytes: Some(150),
+            protected_files: Vec::new(),
+            protected_paths: vec![store.clone()],
+        };
+        let report = collect_garbage(root, &policy).await.unwrap();
+
+        assert_eq!(report.files_removed, 2);
+        assert_eq!(report.bytes_kept, 100);
+        assert!(!oldest.exists() && !older.exists());
+        assert!(newest.exists() && store.exists());
+        assert!(report.errors.is_empty());
+    }
+}
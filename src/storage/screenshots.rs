@@ -0,0 +1,412 @@
+/// Page screenshots captured while crawling
+///
+/// The crawler fetches pages over plain HTTP, so screenshots come from a
+/// headless-browser backend implementing `ScreenshotCapture`; `HeadlessChrome`
+/// runs a local Chrome or Chromium binary. Images are written once per SHA-256
+/// under `<dir>/<first two hex digits>/<hash>.<png|jpg>`, like raw HTML bodies, and
+/// results reference theirs through `StoredCrawlResult::screenshot`. The browser
+/// fetches each page again, through the proxy and with the User-Agent the crawler
+/// used (`CaptureRoute`).
+use anyhow::{Result, anyhow};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use url::Url;
+
+use super::content_hash::hash_body;
+use super::durable::{FsyncPolicy, touch, write_atomic};
+
+/// Directory under the output directory used when `ScreenshotConfig::dir` is unset
+pub const DEFAULT_SCREENSHOT_DIR: &str = "screenshots";
+
+/// Image encoding of a screenshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+/// Browser window size in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 800,
+        }
+    }
+}
+
+/// Screenshot capture settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotConfig {
+    pub format: ImageFormat,
+    pub viewport: Viewport,
+    /// Capture the whole scrollable page instead of the viewport only
+    pub full_page: bool,
+    /// JPEG quality, 1 to 100; ignored for PNG
+    pub jpeg_quality: u8,
+    /// Image directory; defaults to `screenshots` in the output directory
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            format: ImageFormat::default(),
+            viewport: Viewport::default(),
+            full_page: false,
+            jpeg_quality: 80,
+            dir: None,
+        }
+    }
+}
+
+/// Encoded image returned by a browser backend
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    pub format: ImageFormat,
+    /// Image size in pixels
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// Stored screenshot of a result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreenshotRef {
+    /// SHA-256 of the image bytes
+    pub hash: String,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// How the crawler reached a page; the browser fetches it the same way
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureRoute {
+    /// Proxy server without credentials, e.g. "http://proxy.example:3128"; `None`
+    /// connects directly
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy, in NO_PROXY syntax
+    pub proxy_bypass: Vec<String>,
+    /// User-Agent the page was fetched with
+    pub user_agent: Option<String>,
+}
+
+/// Headless-browser backend that renders a page and captures it as an image
+///
+/// The browser fetches the page again; backends send it through `route`.
+pub trait ScreenshotCapture: Send + Sync + std::fmt::Debug {
+    fn capture<'a>(
+        &'a self,
+        url: &'a Url,
+        config: &'a ScreenshotConfig,
+        route: &'a CaptureRoute,
+    ) -> BoxFuture<'a, Result<Screenshot>>;
+}
+
+/// Captures with a Chrome or Chromium binary in headless mode
+///
+/// Every capture starts the browser with its own profile directory and
+/// `--screenshot`, which writes a PNG of the viewport. JPEG output and full-page
+/// captures need a DevTools backend and are refused.
+#[derive(Debug)]
+pub struct HeadlessChrome {
+    binary: PathBuf,
+    timeout: Duration,
+    captures: AtomicU64,
+}
+
+impl HeadlessChrome {
+    pub fn new(binary: impl Into<PathBuf>) -> Self {
+        Self {
+            binary: binary.into(),
+            timeout: Duration::from_secs(30),
+            captures: AtomicU64::new(0),
+        }
+    }
+
+    /// Kill the browser when a capture takes longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn run(
+        &self,
+        url: &Url,
+        config: &ScreenshotConfig,
+        route: &CaptureRoute,
+        dir: &Path,
+    ) -> Result<Screenshot> {
+        let image = dir.join("screenshot.png");
+        let mut command = tokio::process::Command::new(&self.binary);
+        command
+            .args(chrome_args(url, config, route, dir, &image))
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .map_err(|_| anyhow!("Browser did not finish within {:?}", self.timeout))??;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Browser exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let bytes = tokio::fs::read(&image).await?;
+        let (width, height) =
+            png_size(&bytes).ok_or_else(|| anyhow!("Browser did not write a PNG image"))?;
+        Ok(Screenshot {
+            format: ImageFormat::Png,
+            width,
+            height,
+            bytes,
+        })
+    }
+}
+
+impl ScreenshotCapture for HeadlessChrome {
+    fn capture<'a>(
+        &'a self,
+        url: &'a Url,
+        config: &'a ScreenshotConfig,
+        route: &'a CaptureRoute,
+    ) -> BoxFuture<'a, Result<Screenshot>> {
+        Box::pin(async move {
+            if config.format != ImageFormat::Png || config.full_page {
+                return Err(anyhow!("Headless Chrome captures the viewport as PNG only"));
+            }
+            let dir = std::env::temp_dir().join(format!(
+                "crawler-screenshot-{}-{}",
+                std::process::id(),
+                self.captures.fetch_add(1, Ordering::Relaxed)
+            ));
+            tokio::fs::create_dir_all(&dir).await?;
+            let screenshot = self.run(url, config, route, &dir).await;
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            screenshot
+        })
+    }
+}
+
+/// Command line of a headless Chrome capture writing `image`
+fn chrome_args(
+    url: &Url,
+    config: &ScreenshotConfig,
+    route: &CaptureRoute,
+    dir: &Path,
+    image: &Path,
+) -> Vec<String> {
+    let mut args = vec![
+        "--headless".to_string(),
+        "--disable-gpu".to_string(),
+        "--hide-scrollbars".to_string(),
+        "--no-first-run".to_string(),
+        format!("--user-data-dir={}", dir.join("profile").display()),
+        format!(
+            "--window-size={},{}",
+            config.viewport.width, config.viewport.height
+        ),
+    ];
+    if let Some(proxy) = &route.proxy {
+        args.push(format!("--proxy-server={}", proxy));
+        if !route.proxy_bypass.is_empty() {
+            args.push(format!(
+                "--proxy-bypass-list={}",
+                route.proxy_bypass.join(";")
+            ));
+        }
+    }
+    if let Some(user_agent) = &route.user_agent {
+        args.push(format!("--user-agent={}", user_agent));
+    }
+    args.push(format!("--screenshot={}", image.display()));
+    args.push(url.to_string());
+    args
+}
+
+/// Width and height from a PNG's header chunk
+fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 24 || !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Screenshot images keyed by their SHA-256
+#[derive(Clone)]
+pub struct ScreenshotStore {
+    dir: PathBuf,
+    fsync_policy: FsyncPolicy,
+}
+
+impl ScreenshotStore {
+    pub fn new(dir: &Path, fsync_policy: FsyncPolicy) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            fsync_policy,
+        }
+    }
+
+    /// Path of the image `screenshot` refers to
+    pub fn path_for(&self, screenshot: &ScreenshotRef) -> PathBuf {
+        let hash = &screenshot.hash;
+        self.dir.join(&hash[..2.min(hash.len())]).join(format!(
+            "{}.{}",
+            hash,
+            screenshot.format.extension()
+        ))
+    }
+
+    /// Store an image, returning its reference
+    ///
+    /// An image that is already stored is not written again; its modification time
+    /// is renewed instead.
+    pub fn put(&self, screenshot: &Screenshot) -> Result<ScreenshotRef> {
+        let reference = ScreenshotRef {
+            hash: hash_body(&[&screenshot.bytes]),
+            format: screenshot.format,
+            width: screenshot.width,
+            height: screenshot.height,
+        };
+        let path = self.path_for(&reference);
+        if path.exists() {
+            touch(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_atomic(&path, &screenshot.bytes, self.fsync_policy)?;
+        }
+        Ok(reference)
+    }
+
+    /// Image bytes `screenshot` refers to, checked against its hash
+    pub fn get(&self, screenshot: &ScreenshotRef) -> Result<Vec<u8>> {
+        let hash = &screenshot.hash;
+        if hash.len() != 64 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("Invalid screenshot hash '{}'", hash));
+        }
+        let bytes = std::fs::read(self.path_for(screenshot))?;
+        if hash_body(&[&bytes]) != *hash {
+            return Err(anyhow::anyhow!("Screenshot {} is corrupt", hash));
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshots_are_stored_once_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScreenshotStore::new(dir.path(), FsyncPolicy::Never);
+        let screenshot = Screenshot {
+            format: ImageFormat::Jpeg,
+            width: 1280,
+            height: 800,
+            bytes: b"\xff\xd8\xff\xe0 image".to_vec(),
+        };
+
+        let reference = store.put(&screenshot).unwrap();
+        assert_eq!(store.put(&screenshot).unwrap(), reference);
+        assert!(store.path_for(&reference).ends_with(format!(
+            "{}/{}.jpg",
+            &reference.hash[..2],
+            reference.hash
+        )));
+        assert_eq!(store.get(&reference).unwrap(), screenshot.bytes);
+        assert_eq!(
+            serde_json::to_value(&reference).unwrap()["format"],
+            serde_json::json!("jpeg")
+        );
+
+        std::fs::write(store.path_for(&reference), b"edited").unwrap();
+        assert!(store.get(&reference).is_err());
+        let escape = ScreenshotRef {
+            hash: "../escape".to_string(),
+            ..reference
+        };
+        assert!(store.get(&escape).is_err());
+    }
+
+    #[test]
+    fn test_png_size_is_read_from_the_header() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&1280u32.to_be_bytes());
+        png.extend_from_slice(&800u32.to_be_bytes());
+        assert_eq!(png_size(&png), Some((1280, 800)));
+        assert_eq!(png_size(b"\xff\xd8\xff\xe0 image"), None);
+        assert_eq!(png_size(&png[..20]), None);
+    }
+
+    #[tokio::test]
+    async fn test_headless_chrome_refuses_what_its_command_line_cannot_capture() {
+        let chrome = HeadlessChrome::new("/nonexistent/chrome");
+        let url = Url::parse("https://a.example/").unwrap();
+        let jpeg = ScreenshotConfig {
+            format: ImageFormat::Jpeg,
+            ..ScreenshotConfig::default()
+        };
+        let route = CaptureRoute::default();
+        let error = chrome.capture(&url, &jpeg, &route).await.unwrap_err();
+        assert!(error.to_string().contains("PNG only"));
+        assert!(
+            chrome
+                .capture(&url, &ScreenshotConfig::default(), &route)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_chrome_is_sent_through_the_crawlers_route() {
+        let url = Url::parse("https://a.example/page").unwrap();
+        let dir = Path::new("/tmp/capture");
+        let image = dir.join("screenshot.png");
+        let config = ScreenshotConfig::default();
+
+        let direct = chrome_args(&url, &config, &CaptureRoute::default(), dir, &image);
+        assert!(!direct.iter().any(|arg| arg.starts_with("--proxy")));
+        assert_eq!(direct.last().map(String::as_str), Some(url.as_str()));
+
+        let route = CaptureRoute {
+            proxy: Some("http://proxy.example:3128".to_string()),
+            proxy_bypass: vec!["localhost".to_string(), ".corp.example".to_string()],
+            user_agent: Some("ExampleBot/1.0".to_string()),
+        };
+        let routed = chrome_args(&url, &config, &route, dir, &image);
+        for expected in [
+            "--proxy-server=http://proxy.example:3128",
+            "--proxy-bypass-list=localhost;.corp.example",
+            "--user-agent=ExampleBot/1.0",
+        ] {
+            assert!(routed.iter().any(|arg| arg == expected), "{}", expected);
+        }
+        assert_eq!(routed.last().map(String::as_str), Some(url.as_str()));
+    }
+}
@@ -0,0 +1,261 @@
+/// Full-text search index over crawl results (requires the `search` feature)
+///
+/// Results are indexed while the crawl runs, so a searchable corpus is available
+/// without a second pass over the stored JSON. One document is kept per URL;
+/// re-crawled pages replace their previous version on the next commit.
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{
+    Field, IndexRecordOption, STORED, STRING, Schema, TEXT, TantivyDocument, Value,
+};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, Term, doc};
+
+use super::data::StoredCrawlResult;
+
+/// Memory budget for the index writer
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// A search result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub url: String,
+    pub title: Option<String>,
+    pub language: Option<String>,
+    pub session_id: String,
+    pub word_count: u64,
+    pub score: f32,
+}
+
+struct SearchFields {
+    url: Field,
+    title: Field,
+    body: Field,
+    language: Field,
+    session_id: Field,
+    word_count: Field,
+}
+
+/// Tantivy index of extracted text, titles and crawl metadata
+pub struct SearchIndex {
+    index: Index,
+    /// `None` for an index opened read-only
+    writer: Option<Mutex<IndexWriter>>,
+    reader: IndexReader,
+    fields: SearchFields,
+}
+
+impl SearchIndex {
+    /// Open the index in `path` for writing, creating it if needed
+    ///
+    /// Only one writer can hold an index at a time; use `open_read_only` to
+    /// search an index a crawl may be writing to.
+    pub fn open_or_create(path: &Path) -> Result<Self> {
+        std::fs::create_dir_all(path)?;
+        let (schema, fields) = schema();
+        let index = Index::open_or_create(MmapDirectory::open(path)?, schema)?;
+        let writer = index.writer(WRITER_HEAP_BYTES)?;
+        Self::with_writer(index, Some(writer), fields)
+    }
+
+    /// Open an existing index in `path` for searching only, without taking its writer lock
+    ///
+    /// Commits made by a writer elsewhere become visible shortly after they land.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let index = Index::open(MmapDirectory::open(path)?)?;
+        let (_, fields) = schema();
+        Self::with_writer(index, None, fields)
+    }
+
+    fn with_writer(
+        index: Index,
+        writer: Option<IndexWriter>,
+        fields: SearchFields,
+    ) -> Result<Self> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        Ok(Self {
+            index,
+            writer: writer.map(Mutex::new),
+            reader,
+            fields,
+        })
+    }
+
+    fn writer(&self) -> Result<std::sync::MutexGuard<'_, IndexWriter>> {
+        let writer = self
+            .writer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Search index was opened read-only"))?;
+        Ok(writer.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Queue a result for indexing; it becomes searchable after `commit`
+    pub fn add(&self, result: &StoredCrawlResult) -> Result<()> {
+        let fields = &self.fields;
+        let mut document = doc!(
+            fields.url => result.url.as_str(),
+            fields.title => result.title.as_deref().unwrap_or(""),
+            fields.body => result.content.as_deref().unwrap_or(""),
+            fields.session_id => result.metadata.crawl_session_id.as_str(),
+            fields.word_count => result.word_count as u64,
+        );
+        if let Some(language) = &result.language {
+            document.add_text(fields.language, language.to_lowercase());
+        }
+
+        let writer = self.writer()?;
+        writer.delete_term(Term::from_field_text(fields.url, &result.url));
+        writer.add_document(document)?;
+        Ok(())
+    }
+
+    /// Persist queued documents and make them visible to searches
+    ///
+    /// Writes and syncs segment files; call it off the async runtime.
+    pub fn commit(&self) -> Result<()> {
+        self.writer()?.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Search titles and text, optionally restricted to one language
+    ///
+    /// `query` uses tantivy's query syntax (`rust AND crawler`, `"exact phrase"`,
+    /// `title:release`). `lang` matches the stored language case-insensitively.
+    pub fn search(&self, query: &str, lang: Option<&str>, limit: usize) -> Result<Vec<SearchHit>> {
+        let fields = &self.fields;
+        let parser = QueryParser::for_index(&self.index, vec![fields.title, fields.body]);
+        let text_query = parser.parse_query(query)?;
+
+        let query: Box<dyn Query> = match lang {
+            Some(lang) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, text_query),
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(fields.language, &lang.to_lowercase()),
+                        IndexRecordOption::Basic,
+                    )),
+                ),
+            ])),
+            None => text_query,
+        };
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let text = |field: Field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            hits.push(SearchHit {
+                url: text(fields.url).unwrap_or_default(),
+                title: text(fields.title),
+                language: text(fields.language),
+                session_id: text(fields.session_id).unwrap_or_default(),
+                word_count: document
+                    .get_first(fields.word_count)
+                    .and_then(|value| value.as_u64())
+                    .unwrap_or(0),
+                score,
+            });
+        }
+
+        Ok(hits)
+    }
+}
+
+/// Schema of the index, with its fields
+fn schema() -> (Schema, SearchFields) {
+    let mut builder = Schema::builder();
+    let fields = SearchFields {
+        url: builder.add_text_field("url", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        body: builder.add_text_field("body", TEXT),
+        language: builder.add_text_field("language", STRING | STORED),
+        session_id: builder.add_text_field("session_id", STRING | STORED),
+        word_count: builder.add_u64_field("word_count", STORED),
+    };
+    (builder.build(), fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CrawlMetadata;
+
+    fn result(url: &str, title: &str, content: &str, language: &str) -> StoredCrawlResult {
+        StoredCrawlResult::for_test(url)
+            .with_title(title)
+            .with_content(content)
+            .with_language(language)
+    }
+
+    #[test]
+    fn test_search_with_language_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = SearchIndex::open_or_create(dir.path()).unwrap();
+        index
+            .add(&result(
+                "https://a.example/",
+                "Rust release",
+                "the crawler is fast",
+                "Eng",
+            ))
+            .unwrap();
+        index
+            .add(&result(
+                "https://b.example/",
+                "Rust Version",
+                "der crawler ist schnell",
+                "Deu",
+            ))
+            .unwrap();
+        index.commit().unwrap();
+
+        assert_eq!(index.search("crawler", None, 10).unwrap().len(), 2);
+        let hits = index.search("crawler", Some("deu"), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].url, "https://b.example/");
+        assert_eq!(hits[0].title.as_deref(), Some("Rust Version"));
+    }
+
+    #[test]
+    fn test_read_only_index_searches_beside_the_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = SearchIndex::open_or_create(dir.path()).unwrap();
+        index
+            .add(&result(
+                "https://a.example/",
+                "Rust release",
+                "the crawler is fast",
+                "Eng",
+            ))
+            .unwrap();
+        index.commit().unwrap();
+
+        // A second writer would fail on the lock; a reader does not need it
+        assert!(SearchIndex::open_or_create(dir.path()).is_err());
+        let reader = SearchIndex::open_read_only(dir.path()).unwrap();
+        assert_eq!(reader.search("crawler", None, 10).unwrap().len(), 1);
+        assert!(reader.commit().is_err());
+        assert!(
+            reader
+                .add(&result("https://b.example/", "Other", "text", "Eng"))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,261 @@
+/// Visited URLs shared across sessions
+///
+/// The in-memory visited set starts empty every run. This store keeps the time each
+/// URL was last fetched in an append-only JSONL file, so scheduled re-crawls can skip
+/// URLs fetched recently. Permanent failures (410, 451, NXDOMAIN, certificate name
+/// mismatch) are recorded the same way and exclude the URL from later crawls until
+/// a forced crawl fetches it successfully. The latest record per URL wins when the
+/// file is loaded, and `compact` rewrites the file with those records, dropping
+/// expired visits but keeping permanent failures.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use super::durable::{FsyncPolicy, append_record, write_atomic};
+use crate::core::PermanentFailure;
+
+/// One visit of a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisitRecord {
+    pub url: String,
+    pub visited_at: SystemTime,
+    /// Set when the visit failed permanently
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permanent_failure: Option<PermanentFailure>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Visit {
+    visited_at: SystemTime,
+    permanent_failure: Option<PermanentFailure>,
+}
+
+/// Persistent URL -> last visit store
+pub struct PersistentVisitedStore {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, Visit>>,
+    fsync_policy: FsyncPolicy,
+}
+
+impl PersistentVisitedStore {
+    /// Load the store at `path`, creating it on first write
+    ///
+    /// Lines that do not parse (e.g. a record torn by a crash) are skipped.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut entries = HashMap::new();
+        if path.exists() {
+            for line in std::fs::read_to_string(path)?.lines() {
+                if let Ok(record) = serde_json::from_str::<VisitRecord>(line) {
+                    entries.insert(
+                        record.url,
+                        Visit {
+                            visited_at: record.visited_at,
+                            permanent_failure: record.permanent_failure,
+                        },
+                    );
+                }
+            }
+        } else if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: Mutex::new(entries),
+            fsync_policy: FsyncPolicy::default(),
+        })
+    }
+
+    pub fn with_fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync_policy = policy;
+        self
+    }
+
+    pub fn last_visited(&self, url: &str) -> Option<SystemTime> {
+        self.lock().get(url).map(|visit| visit.visited_at)
+    }
+
+    /// Permanent failure of the latest visit of `url`
+    pub fn permanent_failure(&self, url: &str) -> Option<PermanentFailure> {
+        self.lock()
+            .get(url)
+            .and_then(|visit| visit.permanent_failure)
+    }
+
+    /// Every URL whose latest visit failed permanently, sorted by URL
+    pub fn permanent_failures(&self) -> Vec<(String, PermanentFailure)> {
+        let mut failures: Vec<(String, PermanentFailure)> = self
+            .lock()
+            .iter()
+            .filter_map(|(url, visit)| Some((url.clone(), visit.permanent_failure?)))
+            .collect();
+        failures.sort();
+        failures
+    }
+
+    /// Whether `url` was visited less than `max_age` ago
+    pub fn visited_within(&self, url: &str, max_age: Duration) -> bool {
+        self.last_visited(url).is_some_and(|visited_at| {
+            visited_at
+                .elapsed()
+                .map(|age| age < max_age)
+                // A visit in the future (clock change) counts as recent
+                .unwrap_or(true)
+        })
+    }
+
+    /// Record a visit of `url` now, clearing an earlier permanent failure
+    pub fn record(&self, url: &str) -> Result<()> {
+        self.record_at(url, SystemTime::now(), None)
+    }
+
+    /// Record that `url` failed permanently now
+    pub fn record_permanent_failure(&self, url: &str, failure: PermanentFailure) -> Result<()> {
+        self.record_at(url, SystemTime::now(), Some(failure))
+    }
+
+    fn record_at(
+        &self,
+        url: &str,
+        visited_at: SystemTime,
+        permanent_failure: Option<PermanentFailure>,
+    ) -> Result<()> {
+        let record = VisitRecord {
+            url: url.to_string(),
+            visited_at,
+            permanent_failure,
+        };
+        let line = serde_json::to_string(&record)?;
+
+        let mut entries = self.lock();
+        append_record(&self.path, &line, self.fsync_policy)?;
+        entries.insert(
+            record.url,
+            Visit {
+                visited_at,
+                permanent_failure,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Rewrite the file with one record per URL, dropping visits older than `max_age`
+    /// unless they failed permanently
+    ///
+    /// Returns the number of URLs dropped.
+    pub fn compact(&self, max_age: Option<Duration>) -> Result<usize> {
+        let mut entries = self.lock();
+        let before = entries.len();
+        if let Some(max_age) = max_age {
+            entries.retain(|_, visit| {
+                visit.permanent_failure.is_some()
+                    || visit
+                        .visited_at
+                        .elapsed()
+                        .map(|age| age < max_age)
+                        .unwrap_or(true)
+            });
+        }
+
+        let mut content = String::new();
+        for (url, visit) in entries.iter() {
+            let record = VisitRecord {
+                url: url.clone(),
+                visited_at: visit.visited_at,
+                permanent_failure: visit.permanent_failure,
+            };
+            content.push_str(&serde_json::to_string(&record)?);
+            content.push('\n');
+        }
+        write_atomic(&self.path, content.as_bytes(), self.fsync_policy)?;
+        Ok(before - entries.len())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Visit>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_visits_survive_reopen_and_old_ones_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("visited.jsonl");
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let store = PersistentVisitedStore::open(&path).unwrap();
+        store.record("https://a.example/").unwrap();
+        store
+            .record_at("https://b.example/", SystemTime::now() - 10 * day, None)
+            .unwrap();
+
+        let reopened = PersistentVisitedStore::open(&path).unwrap();
+        assert!(reopened.visited_within("https://a.example/", 7 * day));
+        assert!(!reopened.visited_within("https://b.example/", 7 * day));
+        assert!(!reopened.visited_within("https://c.example/", 7 * day));
+
+        assert_eq!(reopened.compact(Some(7 * day)).unwrap(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(PersistentVisitedStore::open(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_permanent_failures_are_kept_until_a_successful_visit() {
+        use crate::core::CrawlError;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("visited.jsonl");
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let gone = anyhow::anyhow!("HTTP error: 410 Gone");
+        let nxdomain = anyhow::anyhow!(
+            "error sending request: dns error: failed to lookup address information: \
+             Name or service not known"
+        );
+        let transient = anyhow::anyhow!("dns error: Temporary failure in name resolution");
+        let failure =
+            |error: &anyhow::Error| CrawlError::from_anyhow_error(error).permanent_failure();
+        assert_eq!(failure(&gone), Some(PermanentFailure::Gone));
+        assert_eq!(failure(&nxdomain), Some(PermanentFailure::NxDomain));
+        assert_eq!(failure(&transient), None);
+        assert!(!CrawlError::from_anyhow_error(&nxdomain).is_retryable());
+        assert!(CrawlError::from_anyhow_error(&transient).is_retryable());
+
+        let store = PersistentVisitedStore::open(&path).unwrap();
+        store
+            .record_at(
+                "https://gone.example/",
+                SystemTime::now() - 30 * day,
+                Some(PermanentFailure::Gone),
+            )
+            .unwrap();
+        store
+            .record_permanent_failure("https://b.example/", PermanentFailure::NxDomain)
+            .unwrap();
+        store.record("https://b.example/").unwrap();
+
+        let reopened = PersistentVisitedStore::open(&path).unwrap();
+        assert_eq!(reopened.compact(Some(7 * day)).unwrap(), 0);
+        assert_eq!(
+            reopened.permanent_failures(),
+            vec![("https://gone.example/".to_string(), PermanentFailure::Gone)]
+        );
+        assert_eq!(reopened.permanent_failure("https://b.example/"), None);
+    }
+}
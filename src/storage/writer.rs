@@ -0,0 +1,213 @@
+/// Single writer task for crawl results
+///
+/// Crawl workers finishing at the same time would otherwise race on the record
+/// buffer and on appends to shared output files. A `StorageWriter` owns the only
+/// task that writes to its `DataStorage`: workers send results over a bounded
+/// channel, which makes them wait while the writer is behind, and the writer
+/// stores them one at a time and flushes buffered records every `batch_size`
+/// results or `flush_interval_ms`, whichever comes first.
+use anyhow::{Result, anyhow};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use super::data::{DataStorage, StoredCrawlResult};
+
+/// Channel size and flush cadence of a `StorageWriter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageWriterConfig {
+    /// Results that can wait in the channel before senders are held back
+    pub channel_capacity: usize,
+    /// Flush after this many stored results
+    pub batch_size: usize,
+    /// Flush results stored since the last flush at least this often
+    pub flush_interval_ms: u64,
+}
+
+impl Default for StorageWriterConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: 256,
+            batch_size: 64,
+            flush_interval_ms: 1000,
+        }
+    }
+}
+
+/// What the writer task did before it stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriterReport {
+    pub stored: usize,
+    /// Results that could not be stored; the writer logs them and carries on
+    pub failed: usize,
+    pub flushes: usize,
+    pub failed_flushes: usize,
+}
+
+/// Called by the writer task with every result it stored; the writer waits for
+/// the returned future before it stores the next result
+pub type OnStored = Box<dyn Fn(&StoredCrawlResult) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Handle of a running writer task
+pub struct StorageWriter {
+    sender: mpsc::Sender<StoredCrawlResult>,
+    task: JoinHandle<WriterReport>,
+}
+
+impl StorageWriter {
+    /// Start the writer task for `storage`
+    pub fn spawn(storage: Arc<DataStorage>, config: StorageWriterConfig) -> Self {
+        Self::spawn_with(storage, config, None)
+    }
+
+    /// Start the writer task for `storage`, calling `on_stored` after each result
+    /// it stores
+    pub fn spawn_with(
+        storage: Arc<DataStorage>,
+        config: StorageWriterConfig,
+        on_stored: Option<OnStored>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(config.channel_capacity.max(1));
+        let task = tokio::spawn(run_writer(storage, config, receiver, on_stored));
+        Self { sender, task }
+    }
+
+    /// Sender for a crawl worker; the writer stops once every sender is dropped
+    /// and `close` is called
+    pub fn sender(&self) -> mpsc::Sender<StoredCrawlResult> {
+        self.sender.clone()
+    }
+
+    /// Queue a result, waiting while the channel is full
+    pub async fn send(&self, result: StoredCrawlResult) -> Result<()> {
+        self.sender
+            .send(result)
+            .await
+            .map_err(|_| anyhow!("Storage writer has stopped"))
+    }
+
+    /// Store what is still queued, flush, and wait for the writer to stop
+    pub async fn close(self) -> Result<WriterReport> {
+        drop(self.sender);
+        Ok(self.task.await?)
+    }
+}
+
+async fn run_writer(
+    storage: Arc<DataStorage>,
+    config: StorageWriterConfig,
+    mut receiver: mpsc::Receiver<StoredCrawlResult>,
+    on_stored: Option<OnStored>,
+) -> WriterReport {
+    let mut report = WriterReport::default();
+    let period = Duration::from_millis(config.flush_interval_ms.max(1));
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut unflushed = 0;
+
+    loop {
+        tokio::select! {
+            received = receiver.recv() => {
+                let Some(result) = received else {
+                    break;
+                };
+                match storage.store_result(&result).await {
+                    Ok(()) => {
+                        report.stored += 1;
+                        unflushed += 1;
+                        if let Some(on_stored) = &on_stored {
+                            on_stored(&result).await;
+                        }
+                    }
+                    Err(e) => {
+                        report.failed += 1;
+                        tracing::warn!(url = %result.url, error = %e, "Failed to store crawl result");
+                    }
+                }
+                if unflushed >= config.batch_size.max(1) {
+                    flush(&storage, &mut report).await;
+                    unflushed = 0;
+                }
+            }
+            _ = ticker.tick(), if unflushed > 0 => {
+                flush(&storage, &mut report).await;
+                unflushed = 0;
+            }
+        }
+    }
+
+    if unflushed > 0 {
+        flush(&storage, &mut report).await;
+    }
+    report
+}
+
+async fn flush(storage: &DataStorage, report: &mut WriterReport) {
+    match storage.flush().await {
+        Ok(()) => report.flushes += 1,
+        Err(e) => {
+            // Unflushed records stay buffered for the next flush
+            report.failed_flushes += 1;
+            tracing::warn!(error = %e, "Failed to flush stored results");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{CollisionPolicy, FileNamingTemplate, OutputFormat};
+
+    fn stored(url: &str) -> StoredCrawlResult {
+        StoredCrawlResult::for_test(url)
+            .with_content("body")
+            .with_session("writer")
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_senders_are_written_in_whole_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let naming =
+            FileNamingTemplate::new("results.{ext}").with_collision_policy(CollisionPolicy::Append);
+        let storage = Arc::new(
+            DataStorage::new(dir.path(), OutputFormat::Jsonl)
+                .unwrap()
+                .with_naming(&naming)
+                .unwrap(),
+        );
+        let writer = StorageWriter::spawn(
+            storage.clone(),
+            StorageWriterConfig {
+                channel_capacity: 2,
+                batch_size: 5,
+                flush_interval_ms: 60_000,
+            },
+        );
+
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                let sender = writer.sender();
+                tokio::spawn(async move {
+                    for page in 0..10 {
+                        let url = format!("https://w{}.example/{}", worker, page);
+                        sender.send(stored(&url)).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.await.unwrap();
+        }
+        let report = writer.close().await.unwrap();
+        assert_eq!(report.stored, 80);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.flushes, 16);
+
+        let results = storage.load_results(None).await.unwrap();
+        assert_eq!(results.len(), 80);
+    }
+}
@@ -0,0 +1,195 @@
+/// Resumable download integration tests
+/// Runs `WebCrawler::download_asset` against a local server that can cut a
+/// transfer short and then answer the resumed request in different ways
+use rust_web_crawler::crawler::{AssetEntry, AssetManifest};
+use rust_web_crawler::{WebCrawler, WebCrawlerConfig};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+/// Size of the served file
+const FILE_BYTES: usize = 2000;
+
+/// Bytes sent before a cut-off transfer is dropped
+const CUT_AT: usize = 1200;
+
+/// How the server answers requests for the file
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    /// The whole file, then the connection is closed after `CUT_AT` bytes
+    CutShort,
+    /// The whole file with 200, whatever the request asked for
+    Full,
+    /// The requested range with 206
+    Ranges,
+    /// 416 to any range request
+    Unsatisfiable,
+}
+
+fn file_body() -> Vec<u8> {
+    (0..FILE_BYTES).map(|i| b'a' + (i % 26) as u8).collect()
+}
+
+/// Serve `/report.pdf` as `mode` says, recording the `Range` header of each request
+async fn serve(mode: Arc<Mutex<Mode>>) -> (Url, Arc<Mutex<Vec<Option<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let requested = Arc::clone(&ranges);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mode = *mode.lock().unwrap();
+            let requested = Arc::clone(&requested);
+            tokio::spawn(async move {
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                if path != "/report.pdf" {
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        )
+                        .await;
+                    return;
+                }
+                let range = request
+                    .lines()
+                    .find_map(|line| {
+                        line.strip_prefix("range: ")
+                            .or(line.strip_prefix("Range: "))
+                    })
+                    .map(str::to_string);
+                requested.lock().unwrap().push(range.clone());
+
+                let body = file_body();
+                let headers = "ETag: \"v1\"\r\nAccept-Ranges: bytes\r\nContent-Type: application/pdf\r\nConnection: close";
+                let start = range
+                    .as_deref()
+                    .and_then(|range| range.strip_prefix("bytes="))
+                    .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+                let (head, sent) = match (mode, start) {
+                    (Mode::Ranges, Some(start)) => (
+                        format!(
+                            "HTTP/1.1 206 Partial Content\r\n{}\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n\r\n",
+                            headers,
+                            start,
+                            FILE_BYTES - 1,
+                            FILE_BYTES,
+                            FILE_BYTES - start
+                        ),
+                        &body[start..],
+                    ),
+                    (Mode::Unsatisfiable, Some(_)) => (
+                        format!(
+                            "HTTP/1.1 416 Range Not Satisfiable\r\n{}\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\n\r\n",
+                            headers, FILE_BYTES
+                        ),
+                        &body[..0],
+                    ),
+                    (Mode::CutShort, _) => (
+                        format!(
+                            "HTTP/1.1 200 OK\r\n{}\r\nContent-Length: {}\r\n\r\n",
+                            headers, FILE_BYTES
+                        ),
+                        &body[..CUT_AT],
+                    ),
+                    _ => (
+                        format!(
+                            "HTTP/1.1 200 OK\r\n{}\r\nContent-Length: {}\r\n\r\n",
+                            headers, FILE_BYTES
+                        ),
+                        &body[..],
+                    ),
+                };
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(sent).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    (base.join("report.pdf").unwrap(), ranges)
+}
+
+/// A crawler, a manifest in a fresh directory, and a download of `url` that was
+/// cut off after `CUT_AT` bytes
+async fn interrupted_download(
+    url: &Url,
+    mode: &Arc<Mutex<Mode>>,
+) -> (WebCrawler, AssetManifest, TempDir) {
+    let crawler = WebCrawler::new(WebCrawlerConfig::default(), 2, 1).unwrap();
+    let dir = TempDir::new().expect("Failed to create temp directory");
+    let mut manifest = AssetManifest::open(dir.path()).unwrap();
+
+    *mode.lock().unwrap() = Mode::CutShort;
+    assert!(crawler.download_asset(url, &mut manifest).await.is_err());
+    let entry = manifest.get(url).unwrap();
+    assert!(!entry.complete);
+    assert_eq!(entry.bytes_received, CUT_AT as u64);
+    assert!(manifest.path_of(&entry.partial_file()).exists());
+    (crawler, manifest, dir)
+}
+
+fn downloaded(manifest: &AssetManifest, entry: &AssetEntry) -> Vec<u8> {
+    std::fs::read(manifest.path_of(&entry.file)).unwrap()
+}
+
+#[tokio::test]
+async fn test_partial_content_is_appended_to_the_partial_file() {
+    let mode = Arc::new(Mutex::new(Mode::CutShort));
+    let (url, ranges) = serve(Arc::clone(&mode)).await;
+    let (crawler, mut manifest, dir) = interrupted_download(&url, &mode).await;
+
+    *mode.lock().unwrap() = Mode::Ranges;
+    let entry = crawler.download_asset(&url, &mut manifest).await.unwrap();
+    assert!(entry.complete);
+    assert_eq!(entry.resumed, 1);
+    assert_eq!(downloaded(&manifest, &entry), file_body());
+    assert_eq!(
+        ranges.lock().unwrap().last().cloned().flatten().as_deref(),
+        Some(format!("bytes={}-", CUT_AT).as_str())
+    );
+
+    // The manifest on disk records the finished download
+    let reopened = AssetManifest::open(dir.path()).unwrap();
+    assert_eq!(reopened.get(&url), Some(&entry));
+}
+
+#[tokio::test]
+async fn test_a_full_answer_to_a_range_request_restarts_the_file() {
+    let mode = Arc::new(Mutex::new(Mode::CutShort));
+    let (url, ranges) = serve(Arc::clone(&mode)).await;
+    let (crawler, mut manifest, _dir) = interrupted_download(&url, &mode).await;
+
+    *mode.lock().unwrap() = Mode::Full;
+    let entry = crawler.download_asset(&url, &mut manifest).await.unwrap();
+    assert!(entry.complete);
+    assert_eq!(entry.resumed, 0);
+    assert_eq!(entry.bytes_received, FILE_BYTES as u64);
+    assert_eq!(downloaded(&manifest, &entry), file_body());
+    assert!(ranges.lock().unwrap().last().unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_an_unsatisfiable_range_discards_the_partial_file() {
+    let mode = Arc::new(Mutex::new(Mode::CutShort));
+    let (url, _) = serve(Arc::clone(&mode)).await;
+    let (crawler, mut manifest, dir) = interrupted_download(&url, &mode).await;
+
+    *mode.lock().unwrap() = Mode::Unsatisfiable;
+    assert!(crawler.download_asset(&url, &mut manifest).await.is_err());
+    let entry = AssetManifest::open(dir.path())
+        .unwrap()
+        .get(&url)
+        .cloned()
+        .unwrap();
+    assert_eq!(entry.bytes_received, 0);
+    assert!(!manifest.path_of(&entry.partial_file()).exists());
+
+    // The next attempt starts from the first byte
+    *mode.lock().unwrap() = Mode::Full;
+    let entry = crawler.download_asset(&url, &mut manifest).await.unwrap();
+    assert_eq!(downloaded(&manifest, &entry), file_body());
+}
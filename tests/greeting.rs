@@ -0,0 +1,182 @@
+/// Domain greeting integration tests
+/// Crawls deep URLs of a local server whose homepage answers in different ways and
+/// checks what the greeting made of it
+use rust_web_crawler::config::{GreetingConfig, IdentityConfig, IdentityProfile, UaRotation};
+use rust_web_crawler::crawler::GreetingOutcome;
+use rust_web_crawler::{WebCrawler, WebCrawlerConfig};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+const ARTICLE: &str = "<html><head><title>Field notes</title></head><body><p>\
+    The river valley was quiet in the early morning, and the fishermen had already \
+    pushed their boats into the slow brown water. Along the bank the reeds bent \
+    under the weight of the dew, and a heron stood motionless near the old stone \
+    bridge, watching the current for any sign of movement. Farther upstream the \
+    mill wheel turned steadily, as it had done for more than a hundred years.\
+    </p></body></html>";
+
+/// Size of the homepage in `Homepage::Oversized`
+const OVERSIZED_BYTES: usize = 50_000;
+
+/// How the server answers for its homepage
+#[derive(Debug, Clone, Copy)]
+enum Homepage {
+    /// 403 Forbidden
+    Forbidden,
+    /// A page far larger than the crawler accepts
+    Oversized,
+    /// A short page setting a session cookie
+    SetsCookie,
+}
+
+/// A request the server received
+#[derive(Debug, Clone)]
+struct Received {
+    path: String,
+    cookie: Option<String>,
+}
+
+/// Serve an article under `/articles/` and `homepage` at `/`, recording every request
+async fn serve(homepage: Homepage) -> (Url, Arc<Mutex<Vec<Received>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let requests = Arc::clone(&received);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let requests = Arc::clone(&requests);
+            tokio::spawn(async move {
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let cookie = request.lines().find_map(|line| {
+                    let (name, value) = line.split_once(": ")?;
+                    name.eq_ignore_ascii_case("cookie")
+                        .then(|| value.trim().to_string())
+                });
+                requests.lock().unwrap().push(Received {
+                    path: path.clone(),
+                    cookie,
+                });
+
+                let (status, extra_headers, body) = match (path.as_str(), homepage) {
+                    ("/", Homepage::Forbidden) => ("403 Forbidden", "", String::new()),
+                    ("/", Homepage::Oversized) => ("200 OK", "", "x".repeat(OVERSIZED_BYTES)),
+                    ("/", Homepage::SetsCookie) => (
+                        "200 OK",
+                        "Set-Cookie: session=greeted; Path=/\r\n",
+                        "<html><body>Welcome</body></html>".to_string(),
+                    ),
+                    (path, _) if path.starts_with("/articles/") => {
+                        ("200 OK", "", ARTICLE.to_string())
+                    }
+                    _ => ("404 Not Found", "", String::new()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    extra_headers,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    (base, received)
+}
+
+/// A crawler that greets domains and keeps one browser identity per domain
+fn greeting_crawler(max_page_bytes: Option<usize>) -> WebCrawler {
+    let config = WebCrawlerConfig {
+        greeting: Some(GreetingConfig::default()),
+        identity: Some(IdentityConfig {
+            default_profile: IdentityProfile::RotatingBrowser {
+                rotation: UaRotation::PerDomain,
+            },
+            ..IdentityConfig::default()
+        }),
+        max_page_bytes,
+        ..WebCrawlerConfig::default()
+    };
+    WebCrawler::new(config, 4, 1).unwrap()
+}
+
+fn requests_for(received: &Mutex<Vec<Received>>, path: &str) -> Vec<Received> {
+    received
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.path == path)
+        .cloned()
+        .collect()
+}
+
+#[tokio::test]
+async fn test_a_blocked_greeting_fails_the_domain_once() {
+    let (base, received) = serve(Homepage::Forbidden).await;
+    let crawler = Arc::new(greeting_crawler(None));
+
+    let crawls: Vec<_> = ["articles/one", "articles/two", "articles/three"]
+        .into_iter()
+        .map(|path| {
+            let crawler = Arc::clone(&crawler);
+            let url = base.join(path).unwrap();
+            tokio::spawn(async move { crawler.crawl_page(url).await })
+        })
+        .collect();
+    for crawl in crawls {
+        let error = crawl.await.unwrap().unwrap_err();
+        assert!(error.to_string().contains("blocked the greeting"));
+    }
+
+    // One greeting for the host, and none of its deep URLs was sent
+    assert_eq!(requests_for(&received, "/").len(), 1);
+    assert!(
+        received
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|request| !request.path.starts_with("/articles/"))
+    );
+    let outcomes = crawler.greeting_outcomes().await;
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes.values().all(GreetingOutcome::is_blocked));
+}
+
+#[tokio::test]
+async fn test_an_oversized_homepage_fails_the_greeting() {
+    let (base, received) = serve(Homepage::Oversized).await;
+    let crawler = greeting_crawler(Some(OVERSIZED_BYTES / 10));
+
+    let _ = crawler.crawl_page(base.join("articles/one").unwrap()).await;
+
+    let outcomes = crawler.greeting_outcomes().await;
+    let outcome = outcomes.values().next().unwrap();
+    assert!(
+        matches!(outcome, GreetingOutcome::Failed { error } if error.contains("exceeds")),
+        "{:?}",
+        outcome
+    );
+    // A failed greeting lets the host's URLs go ahead
+    assert_eq!(requests_for(&received, "/articles/one").len(), 1);
+}
+
+#[tokio::test]
+async fn test_greeting_cookies_are_sent_by_the_same_identity() {
+    let (base, received) = serve(Homepage::SetsCookie).await;
+    let crawler = greeting_crawler(None);
+
+    let _ = crawler.crawl_page(base.join("articles/one").unwrap()).await;
+
+    let greeting = requests_for(&received, "/");
+    assert_eq!(greeting.len(), 1);
+    assert_eq!(greeting[0].cookie, None);
+    let article = requests_for(&received, "/articles/one");
+    assert_eq!(article.len(), 1);
+    assert_eq!(article[0].cookie.as_deref(), Some("session=greeted"));
+}
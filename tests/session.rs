@@ -0,0 +1,890 @@
+/// Crawl session integration tests
+/// Runs whole sessions against a local HTTP server and checks what they record
+use rust_web_crawler::config::{
+    ChangeDetectionConfig, ContactConfig, IdentityConfig, IdentityProfile, PaginationConfig,
+    PersistentVisitedConfig, SsrfProtectionConfig, UaRotation,
+};
+use rust_web_crawler::core::{PermanentFailure, SeedScope, SkipReason};
+use rust_web_crawler::logging::events::CrawlEventType;
+use rust_web_crawler::logging::{
+    SkipStage, load_skipped, load_timeline, session_events_path, session_skipped_path,
+};
+use rust_web_crawler::processing::{
+    ContentPipeline, LinkCategorization, SamplingConfig, SamplingReport, session_sampling_path,
+};
+use rust_web_crawler::session::{
+    CheckpointConfig, SeedHistory, SeedHistoryConfig, load_drift_records, session_config_drift_path,
+};
+use rust_web_crawler::storage::{
+    DataStorage, OutputFormat, PersistentVisitedStore, ResultFilter, RetentionPolicy,
+    StoredCrawlResult, url_to_filename,
+};
+use rust_web_crawler::{
+    ConfigUpdate, CrawlSession, CrawlSessionConfig, SeedGroup, WebCrawlerConfig,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+const ARTICLE: &str = "<html><head><title>Field notes</title></head><body><p>\
+    The river valley was quiet in the early morning, and the fishermen had already \
+    pushed their boats into the slow brown water. Along the bank the reeds bent \
+    under the weight of the dew, and a heron stood motionless near the old stone \
+    bridge, watching the current for any sign of movement. Farther upstream the \
+    mill wheel turned steadily, as it had done for more than a hundred years, and \
+    the miller's children carried sacks of flour to the cart waiting in the yard. \
+    By noon the sun had burned away the mist and the market square filled with \
+    traders selling bread, cheese, apples and honey to the people of the town.\
+    </p></body></html>";
+
+/// First page of a series whose next page is the article
+const SERIES: &str = "<html><head><title>Field notes</title>\
+    <link rel=\"next\" href=\"/article\"></head><body><p>\
+    The river valley was quiet in the early morning, and the fishermen had already \
+    pushed their boats into the slow brown water. Along the bank the reeds bent \
+    under the weight of the dew, and a heron stood motionless near the old stone \
+    bridge, watching the current for any sign of movement. Farther upstream the \
+    mill wheel turned steadily, as it had done for more than a hundred years.\
+    </p></body></html>";
+
+/// Serve `/robots.txt`, an article, the first page of a series leading to it, a page
+/// robots.txt disallows, a page that takes 30 seconds and a missing page
+async fn serve() -> Url {
+    serve_with_robots("User-agent: *\nDisallow: /private")
+        .await
+        .0
+}
+
+/// Serve the test site with `robots`, recording every requested path
+async fn serve_with_robots(robots: &'static str) -> (Url, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let paths = Arc::clone(&requested);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let paths = Arc::clone(&paths);
+            tokio::spawn(async move {
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                paths.lock().unwrap().push(path.to_string());
+                if path == "/slow" {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }
+                let (status, content_type, body) = match path {
+                    "/robots.txt" => ("200 OK", "text/plain", robots),
+                    "/humans.txt" => ("200 OK", "text/plain", "/* TEAM */\nDeveloper: Ada"),
+                    "/article" | "/private/article" => ("200 OK", "text/html", ARTICLE),
+                    "/series" => ("200 OK", "text/html", SERIES),
+                    _ => (
+                        "404 Not Found",
+                        "text/html",
+                        "<html><body>Not found</body></html>",
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (base, requested)
+}
+
+#[tokio::test]
+async fn test_skipped_seeds_are_not_recorded_as_seed_failures() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let history_path = temp_dir.path().join("seed_history.json");
+
+    let article = base.join("article").unwrap();
+    let disallowed = base.join("private/article").unwrap();
+    let missing = base.join("missing").unwrap();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        seed_history: Some(SeedHistoryConfig {
+            max_consecutive_failures: 1,
+            ..SeedHistoryConfig::new(&history_path)
+        }),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![article.clone(), disallowed.clone(), missing.clone()])
+        .await
+        .unwrap();
+
+    assert_eq!(result.successful_crawls, 1);
+    assert_eq!(result.skipped_crawls, 1);
+    assert_eq!(result.failed_crawls, 1);
+    let skipped = result
+        .results
+        .iter()
+        .find(|result| result.url == disallowed)
+        .unwrap();
+    assert!(skipped.skip.is_some() && skipped.error.is_none());
+
+    // The session's own queue accounts for the seed that failed
+    let snapshot = session.queue_snapshot(5).await;
+    assert_eq!(snapshot.domains.len(), 1);
+    assert_eq!(snapshot.domains[0].dead, 1);
+    assert_eq!(snapshot.domains[0].pending, 0);
+    assert!(snapshot.next_tasks.is_empty());
+
+    let history = SeedHistory::load(&history_path).unwrap();
+    assert_eq!(history.get(&article).unwrap().successes, 1);
+    assert_eq!(history.get(&missing).unwrap().consecutive_failures, 1);
+    // robots.txt blocking the seed says nothing about the site's health
+    assert!(history.get(&disallowed).is_none());
+
+    let blacklist = result.seed_blacklist.unwrap();
+    assert!(
+        blacklist
+            .blacklisted
+            .iter()
+            .any(|seed| seed.seed == missing.as_str())
+    );
+    assert_eq!(blacklist.blacklisted.len(), 1);
+}
+
+#[tokio::test]
+async fn test_stored_pages_are_skipped_by_the_next_session() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let visited_path = storage_dir.join("visited.jsonl");
+    let article = base.join("article").unwrap();
+
+    let config = || CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            persistent_visited: Some(PersistentVisitedConfig {
+                store_path: visited_path.clone(),
+                ..PersistentVisitedConfig::default()
+            }),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        // Everything in the output directory is expired, except the shared store
+        retention: Some(RetentionPolicy {
+            max_age_secs: Some(0),
+            max_total_bytes: None,
+            ..RetentionPolicy::default()
+        }),
+        ..CrawlSessionConfig::default()
+    };
+
+    let first = CrawlSession::new(config()).await.unwrap();
+    let result = first.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+    let store = PersistentVisitedStore::open(&visited_path).unwrap();
+    assert!(store.last_visited(article.as_str()).is_some());
+
+    let second = CrawlSession::new(config()).await.unwrap();
+    let result = second.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 0);
+    assert_eq!(result.results[0].skip, Some(SkipReason::VisitedPreviously));
+    assert!(visited_path.exists());
+}
+
+#[tokio::test]
+async fn test_built_in_file_names_are_mapped_from_the_url() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let article = base.join("article").unwrap();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    let index = DataStorage::new(&storage_dir, OutputFormat::Json)
+        .unwrap()
+        .load_filename_index()
+        .await
+        .unwrap();
+    let (file, url) = index
+        .iter()
+        .find(|(_, url)| url.as_str() == article.as_str())
+        .expect("stored page is indexed");
+    assert_eq!(url, article.as_str());
+    assert!(file.contains(&url_to_filename(article.as_str())));
+    assert!(storage_dir.join(file).exists());
+}
+
+/// Results `session` stored under `storage_dir`
+fn stored_results(storage_dir: &std::path::Path, session: &CrawlSession) -> Vec<StoredCrawlResult> {
+    DataStorage::new(storage_dir, OutputFormat::Json)
+        .unwrap()
+        .read_session(session.session_id(), ResultFilter::default())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_results_record_the_user_agent_of_the_chosen_identity() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let article = base.join("article").unwrap();
+
+    // The local host has a profile of its own, with a single User-Agent
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            identity: Some(IdentityConfig {
+                default_profile: IdentityProfile::Fixed,
+                domain_profiles: [(
+                    "127.0.0.1".to_string(),
+                    IdentityProfile::CustomList {
+                        user_agents: vec!["FieldNotesReader/2.0".to_string()],
+                        rotation: UaRotation::PerSession,
+                    },
+                )]
+                .into(),
+            }),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+    assert_eq!(
+        result.results[0].user_agent.as_deref(),
+        Some("FieldNotesReader/2.0")
+    );
+
+    let stored = stored_results(&storage_dir, &session);
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].url, article.as_str());
+    assert_eq!(
+        stored[0].metadata.user_agent.as_deref(),
+        Some("FieldNotesReader/2.0")
+    );
+}
+
+#[tokio::test]
+async fn test_domain_shards_keep_robots_rules_per_site() {
+    let (base, requested) = serve_with_robots("User-agent: *\nDisallow: /private").await;
+    let port = base.port().unwrap();
+    let sites = ["127.0.0.1", "localhost"];
+    let seeds: Vec<Url> = sites
+        .iter()
+        .flat_map(|host| {
+            ["article", "private/article"]
+                .map(|path| Url::parse(&format!("http://{}:{}/{}", host, port, path)).unwrap())
+        })
+        .collect();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            domain_shards: Some(4),
+            ..WebCrawlerConfig::default()
+        },
+        max_concurrent_requests: 1,
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_crawl(seeds).await.unwrap();
+
+    assert_eq!(result.successful_crawls, 2);
+    assert_eq!(result.skipped_crawls, 2);
+    for result in &result.results {
+        let private = result.url.path().starts_with("/private");
+        assert_eq!(result.skip.is_some(), private, "{}", result.url);
+    }
+    // Each site's robots.txt is read once and cached in its shard
+    let robots_fetches = requested
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|path| *path == "/robots.txt")
+        .count();
+    assert_eq!(robots_fetches, sites.len());
+}
+
+#[tokio::test]
+async fn test_seed_metadata_is_stored_with_the_seed_and_its_links() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let series = base.join("series").unwrap();
+    let article = base.join("article").unwrap();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            pagination: Some(PaginationConfig::default()),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 1,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        // Seed metadata wins over the domain's on conflicting keys
+        domain_metadata: Some(
+            [(
+                "127.0.0.1".to_string(),
+                [
+                    ("source".to_string(), "domain".to_string()),
+                    ("owner".to_string(), "archive team".to_string()),
+                ]
+                .into(),
+            )]
+            .into(),
+        ),
+        ..CrawlSessionConfig::default()
+    };
+    let group = SeedGroup::new("notes", vec![series.clone()]).with_metadata(
+        &series,
+        [
+            ("source".to_string(), "seed list".to_string()),
+            ("row".to_string(), "42".to_string()),
+        ]
+        .into(),
+    );
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_grouped_crawl(vec![group]).await.unwrap();
+    assert_eq!(result.successful_crawls, 2);
+
+    let mut stored = stored_results(&storage_dir, &session);
+    stored.sort_by(|a, b| a.url.cmp(&b.url));
+    assert_eq!(
+        stored
+            .iter()
+            .map(|result| result.url.as_str())
+            .collect::<Vec<_>>(),
+        vec![article.as_str(), series.as_str()]
+    );
+    for result in &stored {
+        let custom = &result.metadata.custom;
+        assert_eq!(custom.get("source").map(String::as_str), Some("seed list"));
+        assert_eq!(custom.get("row").map(String::as_str), Some("42"));
+        assert_eq!(
+            custom.get("owner").map(String::as_str),
+            Some("archive team")
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_events_are_recorded_in_a_set_timeline_without_storage() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let timeline_path = session_events_path(temp_dir.path(), "desktop-1");
+    let article = base.join("article").unwrap();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        storage_path: None,
+        event_timeline: Some(timeline_path.clone()),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    let events = load_timeline(&timeline_path).unwrap();
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event.event_type, CrawlEventType::Started)
+                && event.url == article.as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_permanent_failures_are_skipped_without_a_revisit_window() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let visited_path = temp_dir.path().join("visited.jsonl");
+    let article = base.join("article").unwrap();
+    PersistentVisitedStore::open(&visited_path)
+        .unwrap()
+        .record_permanent_failure(article.as_str(), PermanentFailure::Gone)
+        .unwrap();
+
+    let config = |force| CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            persistent_visited: Some(PersistentVisitedConfig {
+                store_path: visited_path.clone(),
+                skip_within_days: 0,
+                force,
+            }),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        ..CrawlSessionConfig::default()
+    };
+
+    let session = CrawlSession::new(config(false)).await.unwrap();
+    let result = session.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!((result.failed_crawls, result.skipped_crawls), (0, 1));
+    assert_eq!(
+        result.results[0].skip,
+        Some(SkipReason::FailedPreviously(
+            PermanentFailure::Gone.to_string()
+        ))
+    );
+
+    // Forcing the store crawls it again
+    let session = CrawlSession::new(config(true)).await.unwrap();
+    let result = session.execute_crawl(vec![article]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+}
+
+#[tokio::test]
+async fn test_site_contact_files_respect_robots_txt() {
+    let (base, requested) = serve_with_robots("User-agent: *\nDisallow: /.well-known/").await;
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            contact: Some(ContactConfig {
+                from: None,
+                fetch_security_txt: true,
+                fetch_humans_txt: true,
+            }),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![base.join("article").unwrap()])
+        .await
+        .unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    let requested = requested.lock().unwrap();
+    assert!(requested.iter().any(|path| path == "/humans.txt"));
+    assert!(
+        !requested
+            .iter()
+            .any(|path| path.starts_with("/.well-known/"))
+    );
+}
+
+#[tokio::test]
+async fn test_resumed_sessions_remember_visited_pages_and_append_drift() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let checkpoint_path = temp_dir.path().join("checkpoint.json");
+    let article = base.join("article").unwrap();
+
+    let config = |max_total_urls| CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            max_total_urls,
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        checkpoint: Some(CheckpointConfig::new(&checkpoint_path)),
+        ..CrawlSessionConfig::default()
+    };
+
+    let first = CrawlSession::new(config(100)).await.unwrap();
+    let result = first.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    // A changed limit is refused until acknowledged
+    assert!(
+        CrawlSession::resume(config(200), &checkpoint_path, false)
+            .await
+            .is_err()
+    );
+    let resumed = CrawlSession::resume(config(200), &checkpoint_path, true)
+        .await
+        .unwrap();
+    let result = resumed.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 0);
+    assert_eq!(result.results[0].skip, Some(SkipReason::AlreadyVisited));
+
+    let resumed = CrawlSession::resume(config(300), &checkpoint_path, true)
+        .await
+        .unwrap();
+    let drift_path = session_config_drift_path(&storage_dir, &result.session_id);
+    let records = load_drift_records(&drift_path).unwrap();
+    assert_eq!(records.len(), 2);
+    drop(resumed);
+}
+
+#[tokio::test]
+async fn test_unchanged_pages_are_skips_and_their_hashes_outlive_retention() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let hashes_path = storage_dir.join("content_hashes.jsonl");
+    let article = base.join("article").unwrap();
+
+    let config = || CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            change_detection: Some(ChangeDetectionConfig {
+                store_path: hashes_path.clone(),
+                skip_unchanged: true,
+            }),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        retention: Some(RetentionPolicy {
+            max_age_secs: Some(0),
+            max_total_bytes: None,
+            ..RetentionPolicy::default()
+        }),
+        ..CrawlSessionConfig::default()
+    };
+
+    let first = CrawlSession::new(config()).await.unwrap();
+    let result = first.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    let second = CrawlSession::new(config()).await.unwrap();
+    let result = second.execute_crawl(vec![article.clone()]).await.unwrap();
+    assert_eq!(result.successful_crawls, 0);
+    assert_eq!(result.skipped_crawls, 1);
+    assert_eq!(result.failed_crawls, 0);
+    assert_eq!(result.results[0].skip, Some(SkipReason::Unchanged));
+    assert_eq!(
+        std::fs::read_to_string(&hashes_path)
+            .unwrap()
+            .lines()
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_refused_addresses_are_skips_in_the_skip_report() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            ssrf_protection: Some(SsrfProtectionConfig::default()),
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        skip_report: true,
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![base.join("article").unwrap()])
+        .await
+        .unwrap();
+    assert_eq!(result.skipped_crawls, 1);
+    assert_eq!(result.failed_crawls, 0);
+
+    let skipped = load_skipped(&session_skipped_path(&storage_dir, &result.session_id)).unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert!(matches!(skipped[0].reason, SkipReason::AddressBlocked(_)));
+    assert_eq!(skipped[0].stage, SkipStage::Fetch);
+}
+
+#[tokio::test]
+async fn test_links_of_a_scoped_seed_stay_within_its_depth() {
+    let (base, requested) = serve_with_robots("User-agent: *\nDisallow: /private").await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let series = base.join("series").unwrap();
+
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            pagination: Some(PaginationConfig::default()),
+            ..WebCrawlerConfig::default()
+        },
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        skip_report: true,
+        ..CrawlSessionConfig::default()
+    };
+    let mut group = SeedGroup::new("scoped", vec![series.clone()]);
+    group.scopes.insert(
+        series.clone(),
+        SeedScope::for_seed(&series).with_max_depth(0),
+    );
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session.execute_grouped_crawl(vec![group]).await.unwrap();
+    assert_eq!(result.successful_crawls, 1);
+    assert!(!requested.lock().unwrap().contains(&"/article".to_string()));
+
+    let skipped = load_skipped(&session_skipped_path(&storage_dir, &result.session_id)).unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].url, base.join("article").unwrap().as_str());
+    assert!(matches!(skipped[0].reason, SkipReason::OutOfScope(_)));
+    assert_eq!(skipped[0].source_url.as_deref(), Some(series.as_str()));
+}
+
+#[tokio::test]
+async fn test_discovered_links_keep_their_category() {
+    let base = serve().await;
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            pagination: Some(PaginationConfig::default()),
+            ..WebCrawlerConfig::default()
+        },
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        link_categorization: Some(LinkCategorization::default()),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![base.join("series").unwrap()])
+        .await
+        .unwrap();
+    assert_eq!(result.successful_crawls, 2);
+
+    let next = result
+        .results
+        .iter()
+        .find(|page| page.url.path() == "/article")
+        .unwrap();
+    assert_eq!(next.metadata["link_category"], "Pagination");
+    assert!(!result.results[0].metadata.contains_key("link_category"));
+}
+
+#[tokio::test]
+async fn test_links_left_out_of_the_sample_are_skips() {
+    let base = serve().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let storage_dir = temp_dir.path().join("crawl_data");
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            pagination: Some(PaginationConfig::default()),
+            ..WebCrawlerConfig::default()
+        },
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: true,
+        storage_path: Some(storage_dir.to_string_lossy().into_owned()),
+        skip_report: true,
+        sampling: Some(SamplingConfig {
+            percent: 0.0,
+            seed: Some(7),
+            domain_percent: Default::default(),
+        }),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![base.join("series").unwrap()])
+        .await
+        .unwrap();
+    assert_eq!(result.successful_crawls, 1);
+
+    let skipped = load_skipped(&session_skipped_path(&storage_dir, &result.session_id)).unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].url, base.join("article").unwrap().as_str());
+    assert!(matches!(skipped[0].reason, SkipReason::SampledOut { .. }));
+
+    let path = session_sampling_path(&storage_dir, &result.session_id);
+    let report: SamplingReport = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    assert_eq!(report.seed, 7);
+    assert_eq!(
+        report
+            .domains
+            .values()
+            .map(|sample| sample.skipped)
+            .sum::<usize>(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_configured_target_words_filter_without_a_custom_pipeline() {
+    let base = serve().await;
+    let crawl = |words: &[&str]| {
+        let config = CrawlSessionConfig {
+            crawler_config: WebCrawlerConfig {
+                min_word_length: 20,
+                ..WebCrawlerConfig::default()
+            }
+            .with_target_words(words.iter().map(|word| word.to_string()).collect()),
+            max_depth: 0,
+            max_retries: 0,
+            session_timeout: Some(Duration::from_secs(60)),
+            enable_storage: false,
+            ..CrawlSessionConfig::default()
+        };
+        let article = base.join("article").unwrap();
+        async move {
+            let session = CrawlSession::new(config).await.unwrap();
+            session.execute_crawl(vec![article]).await.unwrap()
+        }
+    };
+
+    assert_eq!(crawl(&["zeppelin"]).await.successful_crawls, 0);
+    assert_eq!(crawl(&["heron"]).await.successful_crawls, 1);
+}
+
+#[tokio::test]
+async fn test_live_target_words_replace_the_configured_ones() {
+    let base = serve().await;
+    let crawler_config = WebCrawlerConfig {
+        min_word_length: 20,
+        ..WebCrawlerConfig::default()
+    }
+    .with_target_words(vec!["zeppelin".to_string()]);
+    let pipeline = Arc::new(ContentPipeline::from_config(&crawler_config).unwrap());
+    let crawl = |update: Option<ConfigUpdate>| {
+        let config = CrawlSessionConfig {
+            crawler_config: crawler_config.clone(),
+            content_pipeline: Some(Arc::clone(&pipeline)),
+            max_depth: 0,
+            max_retries: 0,
+            session_timeout: Some(Duration::from_secs(60)),
+            enable_storage: false,
+            ..CrawlSessionConfig::default()
+        };
+        let article = base.join("article").unwrap();
+        async move {
+            let session = CrawlSession::new(config).await.unwrap();
+            if let Some(update) = update {
+                session.update_config(update).await.unwrap();
+            }
+            session.execute_crawl(vec![article]).await.unwrap()
+        }
+    };
+
+    // The article does not mention the configured word
+    let filtered = crawl(None).await;
+    assert_eq!(filtered.successful_crawls, 0);
+
+    // An empty live list lifts the filter instead of adding to it
+    let widened = crawl(Some(ConfigUpdate {
+        target_words: Some(Vec::new()),
+        max_concurrent_requests: Some(2),
+        ..ConfigUpdate::default()
+    }))
+    .await;
+    assert_eq!(widened.successful_crawls, 1);
+
+    // A live list is applied in place of the configured one
+    let replaced = crawl(Some(ConfigUpdate {
+        target_words: Some(vec!["heron".to_string()]),
+        ..ConfigUpdate::default()
+    }))
+    .await;
+    assert_eq!(replaced.successful_crawls, 1);
+}
+
+#[tokio::test]
+async fn test_the_time_box_cuts_off_tasks_still_running() {
+    let base = serve().await;
+    let slow = base.join("slow").unwrap();
+    let config = CrawlSessionConfig {
+        crawler_config: WebCrawlerConfig {
+            min_word_length: 20,
+            ..WebCrawlerConfig::default()
+        },
+        max_depth: 0,
+        max_retries: 0,
+        session_timeout: Some(Duration::from_secs(60)),
+        enable_storage: false,
+        max_session_duration: Some(Duration::from_secs(2)),
+        ..CrawlSessionConfig::default()
+    };
+    let session = CrawlSession::new(config).await.unwrap();
+    let result = session
+        .execute_crawl(vec![base.join("article").unwrap(), slow.clone()])
+        .await
+        .unwrap();
+
+    assert!(result.total_duration < Duration::from_secs(5));
+    assert!(result.time_truncated);
+    assert!(result.results.iter().all(|result| result.url != slow));
+    // The cut-off task is back in the queue, not counted as a failure
+    assert_eq!(result.failed_crawls, 0);
+    assert!(result.remaining_queue.unwrap().pending >= 1);
+    assert!(
+        session
+            .queue_snapshot(5)
+            .await
+            .next_tasks
+            .iter()
+            .any(|task| task.url == slow.as_str())
+    );
+}